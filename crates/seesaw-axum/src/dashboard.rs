@@ -0,0 +1,315 @@
+//! Embedded queue dashboard over a [`JobAdmin`].
+//!
+//! [`Dashboard::router`] serves a single static page plus the JSON
+//! endpoints it polls, relative to wherever the router is nested:
+//!
+//! | Route                           | Response                                |
+//! |---------------------------------|-----------------------------------------|
+//! | `GET /`                         | the dashboard page                      |
+//! | `GET /api/stats`                | current counts and the depth history    |
+//! | `GET /api/job-types`            | counts and retries per job type         |
+//! | `GET /api/dead-letters`         | dead letters (`?limit=&offset=`)        |
+//! | `POST /api/jobs/{id}/retry`     | `204`, or `404` unless dead-lettered    |
+//! | `POST /api/jobs/{id}/cancel`    | `204`, or `404` unless pending          |
+//!
+//! The depth history is sampled in memory by a background task while the
+//! router is alive; it starts empty on every restart.
+//!
+//! The routes change jobs, so nest them behind your own authentication:
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .nest("/admin/jobs", Dashboard::new(Arc::new(store)).router())
+//!     .layer(require_admin);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Html;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use seesaw_core::{DeadLetterJob, JobAdmin, JobTypeStats, QueueStats};
+
+use crate::ApiError;
+
+/// How often the depth history is sampled by default.
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many samples the depth history keeps by default (one hour at the
+/// default interval).
+pub const DEFAULT_HISTORY_LEN: usize = 360;
+
+/// Most dead letters one request returns.
+const MAX_DEAD_LETTERS: i64 = 500;
+
+/// The dashboard page.
+const PAGE: &str = include_str!("dashboard.html");
+
+/// Queue dashboard for a job store.
+pub struct Dashboard {
+    admin: Arc<dyn JobAdmin>,
+    sample_interval: Duration,
+    history_len: usize,
+}
+
+impl Dashboard {
+    /// Create a dashboard over `admin`.
+    pub fn new(admin: Arc<dyn JobAdmin>) -> Self {
+        Self {
+            admin,
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+            history_len: DEFAULT_HISTORY_LEN,
+        }
+    }
+
+    /// Set how often queue depth is sampled for the history chart.
+    pub fn with_sample_interval(mut self, interval: Duration) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+
+    /// Set how many depth samples are kept.
+    pub fn with_history_len(mut self, len: usize) -> Self {
+        self.history_len = len;
+        self
+    }
+
+    /// Build the router and start sampling.
+    ///
+    /// Must be called inside a Tokio runtime. Sampling stops once the
+    /// router and every clone of it are dropped.
+    pub fn router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let state = Arc::new(DashboardState {
+            admin: self.admin,
+            history: Mutex::new(VecDeque::with_capacity(self.history_len)),
+            history_len: self.history_len,
+        });
+        tokio::spawn(sample(Arc::downgrade(&state), self.sample_interval));
+
+        Router::new()
+            .route("/", get(|| async { Html(PAGE) }))
+            .route("/api/stats", get(stats))
+            .route("/api/job-types", get(job_types))
+            .route("/api/dead-letters", get(dead_letters))
+            .route("/api/jobs/{id}/retry", post(retry))
+            .route("/api/jobs/{id}/cancel", post(cancel))
+            .with_state(state)
+    }
+}
+
+impl std::fmt::Debug for Dashboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dashboard")
+            .field("sample_interval", &self.sample_interval)
+            .field("history_len", &self.history_len)
+            .finish_non_exhaustive()
+    }
+}
+
+struct DashboardState {
+    admin: Arc<dyn JobAdmin>,
+    history: Mutex<VecDeque<DepthSample>>,
+    history_len: usize,
+}
+
+/// Queue counts at one point in time.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DepthSample {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub stats: QueueStats,
+}
+
+/// Body of `GET /api/stats`.
+#[derive(Debug, Serialize)]
+struct StatsResponse {
+    current: QueueStats,
+    history: Vec<DepthSample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+/// Record a depth sample every `interval` until the dashboard is dropped.
+async fn sample(state: Weak<DashboardState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        match state.admin.stats().await {
+            Ok(stats) => {
+                let mut history = state.history.lock().unwrap();
+                if history.len() >= state.history_len {
+                    history.pop_front();
+                }
+                history.push_back(DepthSample {
+                    at: Utc::now(),
+                    stats,
+                });
+            }
+            Err(e) => warn!(error = %e, "failed to sample queue depth"),
+        }
+    }
+}
+
+async fn stats(State(state): State<Arc<DashboardState>>) -> Result<Json<StatsResponse>, ApiError> {
+    let current = state.admin.stats().await?;
+    let history = state.history.lock().unwrap().iter().copied().collect();
+    Ok(Json(StatsResponse { current, history }))
+}
+
+async fn job_types(
+    State(state): State<Arc<DashboardState>>,
+) -> Result<Json<Vec<JobTypeStats>>, ApiError> {
+    Ok(Json(state.admin.job_type_stats().await?))
+}
+
+async fn dead_letters(
+    State(state): State<Arc<DashboardState>>,
+    Query(page): Query<Page>,
+) -> Result<Json<Vec<DeadLetterJob>>, ApiError> {
+    let limit = page.limit.unwrap_or(50).clamp(1, MAX_DEAD_LETTERS);
+    let offset = page.offset.unwrap_or(0).max(0);
+    Ok(Json(state.admin.dead_letters(limit, offset).await?))
+}
+
+async fn retry(
+    State(state): State<Arc<DashboardState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    Ok(found(state.admin.retry_dead_letter(id).await?))
+}
+
+async fn cancel(
+    State(state): State<Arc<DashboardState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    Ok(found(state.admin.cancel(id).await?))
+}
+
+fn found(changed: bool) -> StatusCode {
+    if changed {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use seesaw_core::async_trait;
+    use tower::ServiceExt;
+
+    /// One dead-lettered job, retryable once.
+    struct OneDeadLetter {
+        id: Uuid,
+        retried: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl JobAdmin for OneDeadLetter {
+        async fn stats(&self) -> Result<QueueStats> {
+            Ok(QueueStats {
+                dead_letter: 1,
+                ..Default::default()
+            })
+        }
+
+        async fn job_type_stats(&self) -> Result<Vec<JobTypeStats>> {
+            Ok(vec![])
+        }
+
+        async fn dead_letters(&self, _limit: i64, _offset: i64) -> Result<Vec<DeadLetterJob>> {
+            Ok(vec![DeadLetterJob {
+                id: self.id,
+                job_type: "email:send".into(),
+                payload: serde_json::json!({ "to": "a@example.com" }),
+                version: 1,
+                attempt: 3,
+                error_message: Some("smtp timeout".into()),
+                error_kind: Some("retryable".into()),
+                failed_at: Utc::now(),
+            }])
+        }
+
+        async fn retry_dead_letter(&self, job_id: Uuid) -> Result<bool> {
+            let mut retried = self.retried.lock().unwrap();
+            let changed = job_id == self.id && !*retried;
+            *retried |= changed;
+            Ok(changed)
+        }
+
+        async fn cancel(&self, _job_id: Uuid) -> Result<bool> {
+            Ok(false)
+        }
+    }
+
+    async fn call(router: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_lists_and_retries_dead_letters() {
+        let id = Uuid::new_v4();
+        let router: Router = Dashboard::new(Arc::new(OneDeadLetter {
+            id,
+            retried: Mutex::new(false),
+        }))
+        .with_sample_interval(Duration::from_millis(5))
+        .router();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let (status, body) = call(
+            &router,
+            Request::get("/api/stats").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["current"]["dead_letter"], 1);
+        assert!(!body["history"].as_array().unwrap().is_empty());
+
+        let (_, body) = call(
+            &router,
+            Request::get("/api/dead-letters")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(body[0]["id"], id.to_string());
+        assert_eq!(body[0]["error_message"], "smtp timeout");
+
+        let retry = || {
+            Request::post(format!("/api/jobs/{id}/retry"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(call(&router, retry()).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(call(&router, retry()).await.0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,499 @@
+//! Ingesting webhooks from providers such as Stripe and GitHub.
+//!
+//! Providers deliver at least once: a webhook whose `2xx` got lost is sent
+//! again, sometimes many times. [`WebhookIngest`] turns each delivery into
+//! at most one event:
+//!
+//! 1. The signature is checked by a [`WebhookVerifier`]; failures are `401`
+//! 2. The provider payload is parsed as JSON; failures are `400`
+//! 3. The provider's event ID is claimed in an [`IdempotencyStore`]; an ID
+//!    seen before is acknowledged with `200` without emitting again
+//! 4. The mapper turns the payload into a typed event, or `None` for event
+//!    types the app ignores
+//! 5. The event is emitted and its inline work awaited. If that fails the
+//!    claim is released and the error returned, so the provider retries
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_axum::{StripeSignature, WebhookIngest};
+//!
+//! #[derive(Deserialize)]
+//! struct StripeEvent {
+//!     id: String,
+//!     #[serde(rename = "type")]
+//!     kind: String,
+//!     data: serde_json::Value,
+//! }
+//!
+//! let stripe = WebhookIngest::new(
+//!     StripeSignature::new(std::env::var("STRIPE_WEBHOOK_SECRET")?),
+//!     |event: &StripeEvent| format!("stripe:{}", event.id),
+//!     |event: StripeEvent| match event.kind.as_str() {
+//!         "invoice.paid" => Some(BillingEvent::InvoicePaid {
+//!             invoice_id: event.data["object"]["id"].as_str()?.to_string(),
+//!         }),
+//!         _ => None,
+//!     },
+//! );
+//!
+//! let app = Router::new()
+//!     .route("/webhooks/stripe", post(stripe.handler()))
+//!     .with_state(handle);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+
+use seesaw_core::{async_trait, Event};
+
+use crate::{ApiError, Seesaw};
+
+// =============================================================================
+// Signatures
+// =============================================================================
+
+/// Checks that a webhook came from the provider.
+///
+/// Implemented for closures over the headers and raw body.
+pub trait WebhookVerifier: Send + Sync + 'static {
+    /// Whether `headers` carry a valid signature of `body`.
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool;
+}
+
+impl<F> WebhookVerifier for F
+where
+    F: Fn(&HeaderMap, &[u8]) -> bool + Send + Sync + 'static,
+{
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        self(headers, body)
+    }
+}
+
+/// GitHub's scheme: `X-Hub-Signature-256: sha256=<hex HMAC-SHA256 of body>`.
+#[derive(Clone)]
+pub struct GithubSignature {
+    secret: Arc<[u8]>,
+}
+
+impl GithubSignature {
+    /// Verify with the webhook's `secret`.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().into(),
+        }
+    }
+}
+
+impl WebhookVerifier for GithubSignature {
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let Some(signature) = header(headers, "x-hub-signature-256")
+            .and_then(|value| value.strip_prefix("sha256="))
+            .and_then(|digest| hex::decode(digest).ok())
+        else {
+            return false;
+        };
+        let mut mac = hmac(&self.secret);
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+}
+
+impl std::fmt::Debug for GithubSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GithubSignature").finish_non_exhaustive()
+    }
+}
+
+/// Stripe's scheme: `Stripe-Signature: t=<unix seconds>,v1=<hex HMAC-SHA256
+/// of "<t>.<body>">`, rejected once older than the tolerance.
+///
+/// seesaw-effects signs its webhook deliveries the same way under
+/// `Seesaw-Signature`; verify those with
+/// `StripeSignature::new(secret).with_header("seesaw-signature")`.
+#[derive(Clone)]
+pub struct StripeSignature {
+    secret: Arc<[u8]>,
+    header: &'static str,
+    tolerance: Duration,
+}
+
+impl StripeSignature {
+    /// Verify with the endpoint's signing `secret`, accepting signatures up
+    /// to 5 minutes old.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().into(),
+            header: "stripe-signature",
+            tolerance: Duration::from_secs(300),
+        }
+    }
+
+    /// Read the signature from `header` instead.
+    pub fn with_header(mut self, header: &'static str) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Accept signatures up to `tolerance` away from now.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+impl WebhookVerifier for StripeSignature {
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let Some(value) = header(headers, self.header) else {
+            return false;
+        };
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in value.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                Some(("v1", v1)) => signatures.extend(hex::decode(v1).ok()),
+                _ => {}
+            }
+        }
+        let Some(timestamp) = timestamp else {
+            return false;
+        };
+        let now = chrono::Utc::now().timestamp();
+        if now.abs_diff(timestamp) > self.tolerance.as_secs() {
+            return false;
+        }
+        signatures.iter().any(|signature| {
+            let mut mac = hmac(&self.secret);
+            mac.update(format!("{timestamp}.").as_bytes());
+            mac.update(body);
+            mac.verify_slice(signature).is_ok()
+        })
+    }
+}
+
+impl std::fmt::Debug for StripeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StripeSignature")
+            .field("header", &self.header)
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn hmac(secret: &[u8]) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length")
+}
+
+// =============================================================================
+// Idempotency
+// =============================================================================
+
+/// Remembers which provider event IDs were already ingested.
+///
+/// Instances sharing a store ingest each event once between them; a
+/// [`MemoryIdempotencyStore`] only deduplicates within one process.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Claim `key`, returning `false` if it is already claimed.
+    async fn claim(&self, key: &str) -> Result<bool>;
+
+    /// Release a claim whose work failed, so a redelivery is ingested.
+    async fn release(&self, key: &str) -> Result<()>;
+}
+
+/// In-process [`IdempotencyStore`] that forgets keys after a retention
+/// period.
+#[derive(Debug)]
+pub struct MemoryIdempotencyStore {
+    claimed: Mutex<HashMap<String, Instant>>,
+    retention: Duration,
+}
+
+impl MemoryIdempotencyStore {
+    /// Remember keys for `retention`, which should outlast the provider's
+    /// redelivery window (Stripe retries for 3 days).
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            claimed: Mutex::new(HashMap::new()),
+            retention,
+        }
+    }
+}
+
+impl Default for MemoryIdempotencyStore {
+    /// Remember keys for 3 days.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(3 * 24 * 60 * 60))
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn claim(&self, key: &str) -> Result<bool> {
+        let mut claimed = self.claimed.lock().unwrap();
+        let now = Instant::now();
+        claimed.retain(|_, at| now.duration_since(*at) < self.retention);
+        if claimed.contains_key(key) {
+            return Ok(false);
+        }
+        claimed.insert(key.to_string(), now);
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        self.claimed.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Ingestion
+// =============================================================================
+
+type IdFn<P> = Arc<dyn Fn(&P) -> String + Send + Sync>;
+type MapFn<P, E> = Arc<dyn Fn(P) -> Option<E> + Send + Sync>;
+
+/// Turns verified provider webhooks with payload `P` into events `E`.
+pub struct WebhookIngest<P, E> {
+    verifier: Arc<dyn WebhookVerifier>,
+    event_id: IdFn<P>,
+    map: MapFn<P, E>,
+    idempotency: Arc<dyn IdempotencyStore>,
+    timeout: Duration,
+}
+
+impl<P, E> Clone for WebhookIngest<P, E> {
+    fn clone(&self) -> Self {
+        Self {
+            verifier: self.verifier.clone(),
+            event_id: self.event_id.clone(),
+            map: self.map.clone(),
+            idempotency: self.idempotency.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<P, E> WebhookIngest<P, E>
+where
+    P: DeserializeOwned + Send + 'static,
+    E: Event,
+{
+    /// Ingest webhooks passing `verifier`, deduplicated by `event_id` and
+    /// converted by `map`.
+    ///
+    /// Deduplicates in a [`MemoryIdempotencyStore`] and waits up to 30
+    /// seconds for each event's inline work, unless configured otherwise.
+    pub fn new(
+        verifier: impl WebhookVerifier,
+        event_id: impl Fn(&P) -> String + Send + Sync + 'static,
+        map: impl Fn(P) -> Option<E> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            verifier: Arc::new(verifier),
+            event_id: Arc::new(event_id),
+            map: Arc::new(map),
+            idempotency: Arc::new(MemoryIdempotencyStore::default()),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Deduplicate in `store`, e.g. one shared by every instance.
+    pub fn with_idempotency(mut self, store: Arc<dyn IdempotencyStore>) -> Self {
+        self.idempotency = store;
+        self
+    }
+
+    /// Wait up to `timeout` for each event's inline work.
+    ///
+    /// Keep it below the provider's own timeout, or it retries deliveries
+    /// that succeeded.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Ingest one delivery, returning the status to answer with.
+    pub async fn ingest(
+        &self,
+        seesaw: &Seesaw,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<StatusCode, ApiError> {
+        if !self.verifier.verify(headers, body) {
+            return Ok(StatusCode::UNAUTHORIZED);
+        }
+        let Ok(payload) = serde_json::from_slice::<P>(body) else {
+            return Ok(StatusCode::BAD_REQUEST);
+        };
+
+        let key = (self.event_id)(&payload);
+        if !self.idempotency.claim(&key).await? {
+            tracing::debug!(key = %key, "duplicate webhook delivery");
+            return Ok(StatusCode::OK);
+        }
+        let Some(event) = (self.map)(payload) else {
+            return Ok(StatusCode::OK);
+        };
+
+        if let Err(e) = seesaw.emit_and_await_timeout(event, self.timeout).await {
+            self.idempotency.release(&key).await?;
+            return Err(ApiError(e));
+        }
+        Ok(StatusCode::OK)
+    }
+
+    /// An axum handler running [`ingest`](Self::ingest) on every request.
+    pub fn handler(
+        self,
+    ) -> impl Fn(Seesaw, HeaderMap, Bytes) -> BoxFuture<'static, Response>
+           + Clone
+           + Send
+           + Sync
+           + 'static {
+        let ingest = Arc::new(self);
+        move |seesaw: Seesaw, headers: HeaderMap, body: Bytes| {
+            let ingest = ingest.clone();
+            Box::pin(async move {
+                match ingest.ingest(&seesaw, &headers, &body).await {
+                    Ok(status) => status.into_response(),
+                    Err(e) => e.into_response(),
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::post;
+    use axum::Router;
+    use seesaw_core::{EngineBuilder, EngineHandle, EventTap, TapContext};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+
+    #[derive(Deserialize)]
+    struct ProviderEvent {
+        id: String,
+        kind: String,
+    }
+
+    #[derive(Debug, Clone)]
+    struct InvoicePaid;
+
+    struct Count(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl EventTap<InvoicePaid> for Count {
+        async fn on_event(&self, _event: &InvoicePaid, _ctx: &TapContext) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn app(received: Arc<AtomicUsize>) -> (Router, Arc<EngineHandle>) {
+        let handle = Arc::new(
+            EngineBuilder::new(())
+                .with_event_tap::<InvoicePaid, _>(Count(received))
+                .build()
+                .start(),
+        );
+        let ingest = WebhookIngest::new(
+            GithubSignature::new(SECRET),
+            |event: &ProviderEvent| event.id.clone(),
+            |event: ProviderEvent| (event.kind == "invoice.paid").then_some(InvoicePaid),
+        );
+        let router = Router::new()
+            .route("/webhooks", post(ingest.handler()))
+            .with_state(handle.clone());
+        (router, handle)
+    }
+
+    async fn deliver(router: Router, body: &str, secret: &str) -> StatusCode {
+        let mut mac = hmac(secret.as_bytes());
+        mac.update(body.as_bytes());
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        let request = Request::post("/webhooks")
+            .header("x-hub-signature-256", signature)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_webhook_emits_once() {
+        let received = Arc::new(AtomicUsize::new(0));
+        let (router, handle) = app(received.clone());
+        let body = r#"{"id":"evt_1","kind":"invoice.paid"}"#;
+        // Give runtime time to subscribe
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(deliver(router.clone(), body, SECRET).await, StatusCode::OK);
+        assert_eq!(deliver(router.clone(), body, SECRET).await, StatusCode::OK);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_signatures_and_ignores_unmapped_events() {
+        let received = Arc::new(AtomicUsize::new(0));
+        let (router, handle) = app(received.clone());
+
+        let paid = r#"{"id":"evt_1","kind":"invoice.paid"}"#;
+        assert_eq!(
+            deliver(router.clone(), paid, "wrong").await,
+            StatusCode::UNAUTHORIZED
+        );
+        let other = r#"{"id":"evt_2","kind":"customer.created"}"#;
+        assert_eq!(deliver(router, other, SECRET).await, StatusCode::OK);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(received.load(Ordering::SeqCst), 0);
+        handle.abort();
+    }
+
+    #[test]
+    fn test_stripe_signature_checks_timestamp_and_body() {
+        let verifier = StripeSignature::new(SECRET);
+        let body = br#"{"id":"evt_1"}"#;
+        let sign = |timestamp: i64| {
+            let mut mac = hmac(SECRET.as_bytes());
+            mac.update(format!("{timestamp}.").as_bytes());
+            mac.update(body);
+            let mut headers = HeaderMap::new();
+            let value = format!("t={timestamp},v1={}", hex::encode(mac.finalize().into_bytes()));
+            headers.insert("stripe-signature", value.parse().unwrap());
+            headers
+        };
+        let now = chrono::Utc::now().timestamp();
+
+        assert!(verifier.verify(&sign(now), body));
+        assert!(!verifier.verify(&sign(now), br#"{"id":"evt_2"}"#));
+        assert!(!verifier.verify(&sign(now - 600), body));
+    }
+}
@@ -0,0 +1,443 @@
+//! Axum integration for request/response over a running engine.
+//!
+//! HTTP handlers are edge code: they turn a request into an input event,
+//! wait for the correlated response event and turn that into a response.
+//! This crate provides the pieces:
+//!
+//! - [`Seesaw`]: extractor for the [`EngineHandle`] in the router state
+//! - [`Seesaw::request`]: emit an event and await the correlated response,
+//!   via the typed request API
+//! - [`ApiError`]: maps request failures to HTTP status codes and safe
+//!   JSON error bodies
+//! - [`request_handler`]: a complete JSON handler from two closures
+//! - [`WebhookIngest`]: a handler turning signed provider webhooks into
+//!   deduplicated events (see [`inbound`])
+//!
+//! # Status Codes
+//!
+//! A correlated `CommandFailed` maps by its [`SafeErrorCategory`] (see
+//! [`status_for`]); its `safe_message` becomes the body. Timeouts are
+//! `504`, a full bus or open circuit breaker `503`. Anything else is a
+//! `500` with a generic message, logged with the full error.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_axum::{request_handler, ApiError, Seesaw};
+//!
+//! let handle = Arc::new(engine.start());
+//! let app = Router::new()
+//!     // Whole handler from closures
+//!     .route(
+//!         "/orders",
+//!         post(request_handler(
+//!             |body: NewOrder| OrderEvent::PlaceRequested { sku: body.sku },
+//!             |e: &OrderEvent| match e {
+//!                 OrderEvent::Placed { order_id } => Some(OrderCreated { id: *order_id }),
+//!                 _ => None,
+//!             },
+//!         )),
+//!     )
+//!     // Or a handler of your own
+//!     .route("/orders/{id}/cancel", post(cancel_order))
+//!     .with_state(handle);
+//!
+//! async fn cancel_order(seesaw: Seesaw, Path(id): Path<Uuid>) -> Result<StatusCode, ApiError> {
+//!     seesaw
+//!         .request(OrderEvent::CancelRequested { id }, |e: &OrderEvent| {
+//!             matches!(e, OrderEvent::Cancelled { .. }).then_some(StatusCode::NO_CONTENT)
+//!         })
+//!         .await
+//! }
+//! ```
+//!
+//! See `examples/axum-orders` for a runnable service.
+//!
+//! # Queue Dashboard
+//!
+//! [`Dashboard`] serves a page showing queue depth over time, failure rates
+//! by job type and the dead letter queue, with retry and cancel actions, for
+//! any store implementing [`JobAdmin`](seesaw_core::JobAdmin):
+//!
+//! ```ignore
+//! let app = Router::new().nest("/admin/jobs", Dashboard::new(Arc::new(store)).router());
+//! ```
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures::future::BoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+
+use seesaw_core::{
+    dispatch_request_timeout, Categorizable, CommandFailed, EngineHandle, Event, SafeErrorCategory,
+    SeesawError, DEFAULT_REQUEST_TIMEOUT,
+};
+
+mod dashboard;
+pub mod inbound;
+
+// Re-export the queue dashboard
+pub use dashboard::{Dashboard, DepthSample, DEFAULT_HISTORY_LEN, DEFAULT_SAMPLE_INTERVAL};
+
+// Re-export inbound webhook ingestion
+pub use inbound::{
+    GithubSignature, IdempotencyStore, MemoryIdempotencyStore, StripeSignature, WebhookIngest,
+    WebhookVerifier,
+};
+
+// =============================================================================
+// Extractor
+// =============================================================================
+
+/// Extracts the engine from router state holding an `Arc<EngineHandle>`.
+///
+/// Works with the handle as the whole state (`.with_state(Arc::new(handle))`)
+/// or as a field of an app state implementing `FromRef`.
+#[derive(Clone)]
+pub struct Seesaw(pub Arc<EngineHandle>);
+
+impl<S> FromRequestParts<S> for Seesaw
+where
+    S: Send + Sync,
+    Arc<EngineHandle>: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(Arc::from_ref(state)))
+    }
+}
+
+impl Deref for Seesaw {
+    type Target = EngineHandle;
+
+    fn deref(&self) -> &EngineHandle {
+        &self.0
+    }
+}
+
+impl Seesaw {
+    /// Emit `event` and return `respond` of the first correlated response
+    /// it accepts, waiting up to [`DEFAULT_REQUEST_TIMEOUT`].
+    pub async fn request<Req, Resp, T>(
+        &self,
+        event: Req,
+        respond: impl Fn(&Resp) -> Option<T>,
+    ) -> Result<T, ApiError>
+    where
+        Req: Event + Clone,
+        Resp: Event,
+    {
+        self.request_timeout(event, respond, DEFAULT_REQUEST_TIMEOUT)
+            .await
+    }
+
+    /// [`request`](Self::request) with a custom timeout.
+    pub async fn request_timeout<Req, Resp, T>(
+        &self,
+        event: Req,
+        respond: impl Fn(&Resp) -> Option<T>,
+        timeout: Duration,
+    ) -> Result<T, ApiError>
+    where
+        Req: Event + Clone,
+        Resp: Event,
+    {
+        dispatch_request_timeout(event, self.bus(), timeout, |m| {
+            m.try_match(|resp: &Resp| respond(resp).map(Ok)).result()
+        })
+        .await
+        .map_err(ApiError)
+    }
+}
+
+impl std::fmt::Debug for Seesaw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Seesaw").field(&self.0).finish()
+    }
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// HTTP status for a failure category.
+///
+/// | Category          | Status                    |
+/// |-------------------|---------------------------|
+/// | `Validation`      | 400 Bad Request           |
+/// | `Unauthorized`    | 403 Forbidden             |
+/// | `NotFound`        | 404 Not Found             |
+/// | `RateLimited`     | 429 Too Many Requests     |
+/// | `InternalError`   | 500 Internal Server Error |
+/// | `ExternalService` | 502 Bad Gateway           |
+/// | `AIFailure`       | 503 Service Unavailable   |
+pub fn status_for(category: SafeErrorCategory) -> StatusCode {
+    match category {
+        SafeErrorCategory::Validation => StatusCode::BAD_REQUEST,
+        SafeErrorCategory::Unauthorized => StatusCode::FORBIDDEN,
+        SafeErrorCategory::NotFound => StatusCode::NOT_FOUND,
+        SafeErrorCategory::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        SafeErrorCategory::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        SafeErrorCategory::ExternalService => StatusCode::BAD_GATEWAY,
+        SafeErrorCategory::AIFailure => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// A failed request, rendered as `{"error": "<safe message>"}`.
+///
+/// Any error converts into it with `?`, so handlers can mix engine requests
+/// with their own fallible calls.
+#[derive(Debug)]
+pub struct ApiError(pub anyhow::Error);
+
+impl ApiError {
+    /// The status code and user-safe message for this error.
+    pub fn classify(&self) -> (StatusCode, Cow<'_, str>) {
+        if let Some(failed) = self.0.downcast_ref::<CommandFailed>() {
+            return (
+                status_for(failed.category),
+                Cow::Borrowed(&failed.safe_message),
+            );
+        }
+        if let Some(e) = self.0.downcast_ref::<SeesawError>() {
+            let status = match e {
+                SeesawError::Timeout { .. }
+                | SeesawError::BatchTimeout { .. }
+                | SeesawError::DeadlineExceeded { .. }
+                | SeesawError::EffectTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+                SeesawError::BusFull { .. } | SeesawError::CircuitOpen { .. } => {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                _ => status_for(e.category()),
+            };
+            return (status, e.safe_message());
+        }
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Cow::Borrowed("An internal error occurred"),
+        )
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.classify();
+        if status.is_server_error() {
+            tracing::error!(status = %status, error = ?self.0, "request failed");
+        }
+        let body = Json(serde_json::json!({ "error": message }));
+        (status, body).into_response()
+    }
+}
+
+// =============================================================================
+// Handler
+// =============================================================================
+
+/// A JSON handler that emits `into_event(body)` and responds with the
+/// first correlated `Resp` that `respond` accepts.
+///
+/// # Example
+///
+/// ```ignore
+/// .route(
+///     "/orders",
+///     post(request_handler(
+///         |body: NewOrder| OrderEvent::PlaceRequested { sku: body.sku },
+///         |e: &OrderEvent| match e {
+///             OrderEvent::Placed { order_id } => Some(OrderCreated { id: *order_id }),
+///             _ => None,
+///         },
+///     )),
+/// )
+/// ```
+pub fn request_handler<B, Req, Resp, T, F, G>(
+    into_event: F,
+    respond: G,
+) -> impl Fn(Seesaw, Json<B>) -> BoxFuture<'static, Result<Json<T>, ApiError>>
+       + Clone
+       + Send
+       + Sync
+       + 'static
+where
+    B: DeserializeOwned + Send + 'static,
+    Req: Event + Clone,
+    Resp: Event,
+    T: Serialize + Send + 'static,
+    F: Fn(B) -> Req + Send + Sync + 'static,
+    G: Fn(&Resp) -> Option<T> + Send + Sync + 'static,
+{
+    let into_event = Arc::new(into_event);
+    let respond = Arc::new(respond);
+    move |seesaw: Seesaw, Json(body): Json<B>| {
+        let event = into_event(body);
+        let respond = respond.clone();
+        Box::pin(async move {
+            seesaw
+                .request(event, |resp: &Resp| respond(resp))
+                .await
+                .map(Json)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::post;
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use seesaw_core::{Command, CorrelationId, Effect, EffectContext, EngineBuilder, Machine};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Debug, Clone)]
+    enum OrderEvent {
+        PlaceRequested { sku: String },
+        Placed { order_id: u32 },
+    }
+
+    #[derive(Debug, Clone)]
+    struct PlaceOrder {
+        sku: String,
+    }
+    impl Command for PlaceOrder {}
+
+    struct OrderMachine;
+
+    impl Machine for OrderMachine {
+        type Event = OrderEvent;
+        type Command = PlaceOrder;
+
+        fn decide(&mut self, event: &OrderEvent) -> Option<PlaceOrder> {
+            match event {
+                OrderEvent::PlaceRequested { sku } => Some(PlaceOrder { sku: sku.clone() }),
+                _ => None,
+            }
+        }
+    }
+
+    struct PlaceEffect;
+
+    #[seesaw_core::async_trait]
+    impl Effect<PlaceOrder, ()> for PlaceEffect {
+        type Event = OrderEvent;
+
+        async fn execute(&self, cmd: PlaceOrder, _ctx: EffectContext<()>) -> Result<OrderEvent> {
+            if cmd.sku == "missing" {
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            }
+            Ok(OrderEvent::Placed { order_id: 7 })
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct NewOrder {
+        sku: String,
+    }
+
+    #[derive(Serialize)]
+    struct OrderCreated {
+        id: u32,
+    }
+
+    fn app() -> (Router, Arc<EngineHandle>) {
+        let handle = Arc::new(
+            EngineBuilder::new(())
+                .with_machine(OrderMachine)
+                .with_effect::<PlaceOrder, _>(PlaceEffect)
+                .build()
+                .start(),
+        );
+        let router = Router::new()
+            .route(
+                "/orders",
+                post(request_handler(
+                    |body: NewOrder| OrderEvent::PlaceRequested { sku: body.sku },
+                    |e: &OrderEvent| match e {
+                        OrderEvent::Placed { order_id } => Some(OrderCreated { id: *order_id }),
+                        _ => None,
+                    },
+                )),
+            )
+            .with_state(handle.clone());
+        (router, handle)
+    }
+
+    async fn post_order(router: Router, sku: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::post("/orders")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::json!({ "sku": sku }).to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_request_handler_responds_with_correlated_event() {
+        let (router, handle) = app();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let (status, body) = post_order(router.clone(), "abc").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!({ "id": 7 }));
+
+        let (status, body) = post_order(router, "missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, serde_json::json!({ "error": "Resource not found" }));
+
+        handle.abort();
+    }
+
+    #[test]
+    fn test_failures_map_to_status_codes() {
+        let failed = CommandFailed {
+            command_type: "PlaceOrder",
+            category: SafeErrorCategory::Validation,
+            safe_message: "sku is required".into(),
+            retryable: false,
+            cid: CorrelationId::NONE,
+        };
+        let error = ApiError(anyhow::Error::new(failed).context("sku is required"));
+        assert_eq!(
+            error.classify(),
+            (StatusCode::BAD_REQUEST, Cow::Borrowed("sku is required"))
+        );
+
+        let timeout = ApiError::from(SeesawError::Timeout {
+            duration: Duration::from_secs(1),
+        });
+        assert_eq!(timeout.classify().0, StatusCode::GATEWAY_TIMEOUT);
+
+        // Unrecognized errors never leak their message
+        let other = ApiError::from(anyhow::anyhow!("connection refused to 10.0.0.3"));
+        assert_eq!(
+            other.classify(),
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Cow::Borrowed("An internal error occurred")
+            )
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! NATS bridge connecting the event buses of separate services.
+//!
+//! The [`EventBus`] is in-process. To coordinate two services that each run
+//! a seesaw `Engine`, a [`NatsBridge`] forwards selected local event types
+//! onto NATS subjects and injects events received from NATS back into the
+//! local bus, where machines decide on them like any other event.
+//!
+//! # Overview
+//!
+//! 1. Each service registers the types it publishes ([`RemoteEventRegistry::forward`])
+//!    and the types it consumes ([`RemoteEventRegistry::inject`])
+//! 2. Events are serialized as JSON; the correlation ID travels in the
+//!    [`CORRELATION_ID_HEADER`] header, so non-seesaw consumers see plain JSON
+//! 3. Injected events keep their correlation ID, so a workflow spanning
+//!    services stays traceable end to end
+//!
+//! # Guarantees
+//!
+//! - **At-most-once delivery**: Core NATS does not persist messages. Events
+//!   published while no peer is subscribed, or while the bridge is lagging
+//!   behind the bus, are lost. Use the outbox for events that must survive
+//! - **No echo**: A type is either forwarded or injected by one bridge,
+//!   never both, so injected events are not published back out
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_bus_nats::{NatsBridge, RemoteEventRegistry};
+//!
+//! // Orders service: publishes OrderPlaced, consumes PaymentCaptured
+//! let handle = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::new())
+//!     .build()
+//!     .start();
+//!
+//! let registry = RemoteEventRegistry::new()
+//!     .forward::<OrderPlaced>("orders.placed.v1")
+//!     .inject::<PaymentCaptured>("payments.captured.v1");
+//!
+//! let client = async_nats::connect("nats://localhost:4222").await?;
+//! let bridge = NatsBridge::new(client, registry, handle.bus().clone());
+//! tokio::spawn(bridge.run());
+//! ```
+//!
+//! To choose at runtime which events leave the process, publish through a
+//! seesaw `Router` with a [`NatsPublisher`] instead of forwarding them here.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use async_nats::{HeaderMap, Message};
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use seesaw_core::{async_trait, CorrelationId, Event, EventBus, EventEnvelope, RoutePublisher};
+
+/// NATS header carrying the event's correlation ID.
+///
+/// Omitted for uncorrelated events.
+pub const CORRELATION_ID_HEADER: &str = "Seesaw-Correlation-Id";
+
+type EncodeFn = Box<dyn Fn(&EventEnvelope) -> Option<Result<Bytes>> + Send + Sync>;
+type InjectFn = Box<dyn Fn(&[u8], CorrelationId, &EventBus) -> Result<()> + Send + Sync>;
+
+// =============================================================================
+// Registry
+// =============================================================================
+
+/// The event types that cross a [`NatsBridge`], and their subjects.
+///
+/// Subjects should include a version (`"orders.placed.v1"`), as with outbox
+/// event types: a changed payload is a new subject, so old and new services
+/// can run side by side.
+///
+/// # Example
+///
+/// ```ignore
+/// let registry = RemoteEventRegistry::new()
+///     .forward::<OrderPlaced>("orders.placed.v1")
+///     .inject::<PaymentCaptured>("payments.captured.v1");
+/// ```
+#[derive(Default)]
+pub struct RemoteEventRegistry {
+    outbound: HashMap<TypeId, (String, EncodeFn)>,
+    inbound: Vec<(String, TypeId, InjectFn)>,
+}
+
+impl RemoteEventRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish local `E` events on `subject`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `E` is already forwarded or injected.
+    pub fn forward<E>(mut self, subject: impl Into<String>) -> Self
+    where
+        E: Event + Serialize,
+    {
+        let type_id = TypeId::of::<E>();
+        assert!(
+            !self.outbound.contains_key(&type_id),
+            "{} is already forwarded",
+            std::any::type_name::<E>()
+        );
+        assert!(
+            !self.injects(type_id),
+            "{} is injected and cannot also be forwarded",
+            std::any::type_name::<E>()
+        );
+        self.outbound.insert(
+            type_id,
+            (
+                subject.into(),
+                Box::new(|envelope| {
+                    let event = envelope.downcast_ref::<E>()?;
+                    Some(
+                        serde_json::to_vec(event)
+                            .map(Bytes::from)
+                            .map_err(Into::into),
+                    )
+                }),
+            ),
+        );
+        self
+    }
+
+    /// Emit `E` events received on `subject` onto the local bus.
+    ///
+    /// `subject` may contain NATS wildcards (`"payments.*.v1"`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `E` is forwarded.
+    pub fn inject<E>(mut self, subject: impl Into<String>) -> Self
+    where
+        E: Event + DeserializeOwned,
+    {
+        let type_id = TypeId::of::<E>();
+        assert!(
+            !self.outbound.contains_key(&type_id),
+            "{} is forwarded and cannot also be injected",
+            std::any::type_name::<E>()
+        );
+        self.inbound.push((
+            subject.into(),
+            type_id,
+            Box::new(|payload, cid, bus| {
+                let event: E = serde_json::from_slice(payload)
+                    .with_context(|| format!("deserializing {}", std::any::type_name::<E>()))?;
+                bus.emit_with_correlation(event, cid);
+                Ok(())
+            }),
+        ));
+        self
+    }
+
+    fn injects(&self, type_id: TypeId) -> bool {
+        self.inbound.iter().any(|(_, id, _)| *id == type_id)
+    }
+
+    /// Subject and serialized payload for a forwarded envelope.
+    ///
+    /// Returns `None` for event types that are not forwarded.
+    fn encode(&self, envelope: &EventEnvelope) -> Option<(&str, Result<Bytes>)> {
+        let (subject, encode) = self.outbound.get(&envelope.type_id)?;
+        Some((subject, encode(envelope)?))
+    }
+
+    /// Emit a message received on the `index`th inbound subscription.
+    fn inject_message(&self, index: usize, message: &Message, bus: &EventBus) -> Result<()> {
+        let (_, _, inject) = &self.inbound[index];
+        inject(
+            &message.payload,
+            correlation_id(message.headers.as_ref()),
+            bus,
+        )
+    }
+}
+
+impl std::fmt::Debug for RemoteEventRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteEventRegistry")
+            .field(
+                "forwarded",
+                &self
+                    .outbound
+                    .values()
+                    .map(|(subject, _)| subject)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "injected",
+                &self
+                    .inbound
+                    .iter()
+                    .map(|(subject, _, _)| subject)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Headers for an outgoing event.
+fn headers(cid: CorrelationId) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if cid.is_some() {
+        headers.insert(CORRELATION_ID_HEADER, cid.to_string());
+    }
+    headers
+}
+
+/// Correlation ID from incoming headers, `NONE` if absent or malformed.
+fn correlation_id(headers: Option<&HeaderMap>) -> CorrelationId {
+    let Some(value) = headers.and_then(|headers| headers.get(CORRELATION_ID_HEADER)) else {
+        return CorrelationId::NONE;
+    };
+    match Uuid::parse_str(value.as_str()) {
+        Ok(uuid) => CorrelationId::from(uuid),
+        Err(_) => {
+            warn!(value = %value.as_str(), "ignoring malformed correlation id header");
+            CorrelationId::NONE
+        }
+    }
+}
+
+// =============================================================================
+// Bridge
+// =============================================================================
+
+/// Forwards and injects the events of a [`RemoteEventRegistry`] over NATS.
+///
+/// # Example
+///
+/// ```ignore
+/// let client = async_nats::connect("nats://localhost:4222").await?;
+/// let bridge = NatsBridge::new(client, registry, handle.bus().clone());
+/// tokio::spawn(bridge.run());
+/// ```
+pub struct NatsBridge {
+    client: async_nats::Client,
+    registry: RemoteEventRegistry,
+    bus: EventBus,
+}
+
+impl NatsBridge {
+    /// Create a bridge between `bus` and the NATS server behind `client`.
+    pub fn new(client: async_nats::Client, registry: RemoteEventRegistry, bus: EventBus) -> Self {
+        Self {
+            client,
+            registry,
+            bus,
+        }
+    }
+
+    /// Run the bridge until the bus closes.
+    ///
+    /// Events emitted before `run` subscribes to the bus are not forwarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if subscribing to an inbound subject fails. Publish
+    /// failures and undecodable messages are logged and skipped.
+    pub async fn run(self) -> Result<()> {
+        let mut events = self.bus.subscribe();
+
+        let mut subscriptions = Vec::with_capacity(self.registry.inbound.len());
+        for (index, (subject, _, _)) in self.registry.inbound.iter().enumerate() {
+            let subscriber = self
+                .client
+                .subscribe(subject.clone())
+                .await
+                .with_context(|| format!("subscribing to {}", subject))?;
+            subscriptions.push(subscriber.map(move |message| (index, message)));
+        }
+        let mut inbound = stream::select_all(subscriptions);
+
+        loop {
+            tokio::select! {
+                received = events.recv() => match received {
+                    Ok(envelope) => self.forward(&envelope).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "nats bridge lagged behind the event bus");
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                Some((index, message)) = inbound.next() => {
+                    if let Err(e) = self.registry.inject_message(index, &message, &self.bus) {
+                        error!(subject = %message.subject, error = %e, "failed to inject remote event");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Publish one envelope if its type is forwarded.
+    async fn forward(&self, envelope: &EventEnvelope) {
+        let Some((subject, payload)) = self.registry.encode(envelope) else {
+            return;
+        };
+        let result = match payload {
+            Ok(payload) => self
+                .client
+                .publish_with_headers(subject.to_string(), headers(envelope.cid), payload)
+                .await
+                .map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!(subject, cid = %envelope.cid, error = %e, "failed to forward event");
+        }
+    }
+}
+
+impl std::fmt::Debug for NatsBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NatsBridge")
+            .field("registry", &self.registry)
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Router Publisher
+// =============================================================================
+
+/// Publishes the `Destination::Subject` routes of a seesaw `Router` on NATS.
+///
+/// Messages carry the same JSON payload and [`CORRELATION_ID_HEADER`] as
+/// those of a [`NatsBridge`], so a peer's bridge can inject them.
+///
+/// # Example
+///
+/// ```ignore
+/// let router = Router::new(handle.bus().clone())
+///     .with_event::<OrderPlaced>("order.placed")
+///     .with_publisher(Arc::new(NatsPublisher::new(client)));
+/// router.set_routes("order.placed", vec![Destination::subject("orders.placed.v1")])?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct NatsPublisher {
+    client: async_nats::Client,
+}
+
+impl NatsPublisher {
+    /// Publish through `client`.
+    pub fn new(client: async_nats::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl RoutePublisher for NatsPublisher {
+    async fn publish(&self, subject: &str, cid: CorrelationId, payload: Bytes) -> Result<()> {
+        self.client
+            .publish_with_headers(subject.to_string(), headers(cid), payload)
+            .await
+            .with_context(|| format!("publishing to {subject}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct OrderPlaced {
+        order_id: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct PaymentCaptured {
+        order_id: u32,
+    }
+
+    fn message(subject: &str, headers: HeaderMap, payload: &'static [u8]) -> Message {
+        Message {
+            subject: subject.into(),
+            reply: None,
+            payload: Bytes::from_static(payload),
+            headers: Some(headers),
+            status: None,
+            description: None,
+            length: payload.len(),
+        }
+    }
+
+    #[test]
+    fn test_forward_encodes_registered_types_only() {
+        let registry = RemoteEventRegistry::new().forward::<OrderPlaced>("orders.placed.v1");
+
+        let envelope = EventEnvelope::new_random(OrderPlaced { order_id: 7 });
+        let (subject, payload) = registry.encode(&envelope).unwrap();
+        assert_eq!(subject, "orders.placed.v1");
+        assert_eq!(&payload.unwrap()[..], br#"{"order_id":7}"#);
+
+        let other = EventEnvelope::new_random(PaymentCaptured { order_id: 7 });
+        assert!(registry.encode(&other).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_inject_emits_with_remote_correlation_id() {
+        let registry = RemoteEventRegistry::new().inject::<PaymentCaptured>("payments.*.v1");
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        let cid = CorrelationId::new();
+        let msg = message("payments.captured.v1", headers(cid), br#"{"order_id":7}"#);
+        registry.inject_message(0, &msg, &bus).unwrap();
+
+        let envelope = rx.recv().await.unwrap();
+        assert_eq!(envelope.cid, cid);
+        assert_eq!(
+            envelope.downcast_ref::<PaymentCaptured>(),
+            Some(&PaymentCaptured { order_id: 7 })
+        );
+
+        // Undecodable payloads are reported, not emitted
+        let msg = message("payments.captured.v1", HeaderMap::new(), b"{}");
+        assert!(registry.inject_message(0, &msg, &bus).is_err());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_uncorrelated_events_omit_header() {
+        assert!(headers(CorrelationId::NONE)
+            .get(CORRELATION_ID_HEADER)
+            .is_none());
+        assert_eq!(correlation_id(Some(&HeaderMap::new())), CorrelationId::NONE);
+        assert_eq!(correlation_id(None), CorrelationId::NONE);
+    }
+
+    #[test]
+    #[should_panic(expected = "is forwarded and cannot also be injected")]
+    fn test_type_crosses_in_one_direction() {
+        let _ = RemoteEventRegistry::new()
+            .forward::<OrderPlaced>("orders.placed.v1")
+            .inject::<OrderPlaced>("orders.placed.v1");
+    }
+}
@@ -0,0 +1,535 @@
+//! # seesaw
+//!
+//! Queue administration against a Postgres job store, for when the dashboard
+//! is not deployed or the incident is on a box with only a shell.
+//!
+//! ```bash
+//! export DATABASE_URL=postgres://localhost/app
+//! seesaw stats
+//! seesaw jobs list --status dead_letter
+//! seesaw jobs show <id>
+//! seesaw jobs retry <id>
+//! seesaw jobs enqueue --type email:send --payload @file.json
+//! seesaw jobs upcoming --minutes 60
+//! seesaw jobs import legacy-jobs.jsonl
+//! seesaw workers
+//! ```
+//!
+//! Every command takes `--json` for machine-readable output.
+
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use clap::{Args, Parser, Subcommand};
+use seesaw_core::JobAdmin;
+use seesaw_job_postgres::{ImportJob, JobFilter, NewJob, PgJobStore};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// ============================================================================
+// Arguments
+// ============================================================================
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "seesaw",
+    version,
+    about = "Administer a seesaw Postgres job queue"
+)]
+struct Cli {
+    /// Postgres connection string.
+    #[arg(long, env = "DATABASE_URL", global = true, hide_env_values = true)]
+    database_url: Option<String>,
+
+    /// Print JSON instead of tables.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Inspect and change jobs.
+    #[command(subcommand)]
+    Jobs(JobsCommand),
+    /// Show job counts by status, overall and per job type.
+    Stats,
+    /// Show the workers holding running jobs.
+    Workers,
+}
+
+#[derive(Debug, Subcommand)]
+enum JobsCommand {
+    /// List jobs, most recently updated first.
+    List {
+        /// Only jobs in this status (pending, running, succeeded, failed, dead_letter, parked).
+        #[arg(long)]
+        status: Option<String>,
+        /// Only jobs of this type.
+        #[arg(long = "type")]
+        job_type: Option<String>,
+        /// Most jobs to print.
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+    /// Show one job, including its payload.
+    Show { id: Uuid },
+    /// Requeue a dead-lettered job with a fresh attempt count.
+    Retry { id: Uuid },
+    /// Requeue a parked job, keeping its attempt count.
+    Unpark { id: Uuid },
+    /// Cancel a pending job.
+    Cancel { id: Uuid },
+    /// Set the priority of a pending job (higher runs first).
+    SetPriority {
+        id: Uuid,
+        #[arg(allow_negative_numbers = true)]
+        priority: i32,
+    },
+    /// Add to the priority of matching pending jobs; positive moves them
+    /// towards the front.
+    Bump {
+        /// Only jobs of this type.
+        #[arg(long = "type")]
+        job_type: Option<String>,
+        /// Only jobs whose payload contains this JSON, e.g. '{"customer_id": 42}'.
+        #[arg(long)]
+        payload: Option<String>,
+        #[arg(long, allow_negative_numbers = true)]
+        delta: i32,
+    },
+    /// Insert a pending job.
+    Enqueue(EnqueueArgs),
+    /// Show pending jobs due soon, per job type.
+    Upcoming {
+        /// How far ahead to look.
+        #[arg(long, default_value_t = 60)]
+        minutes: u64,
+    },
+    /// Bulk import jobs from a JSON lines file (`-` for stdin).
+    Import {
+        path: PathBuf,
+        /// Jobs per COPY; each batch is imported all or nothing.
+        #[arg(long, default_value_t = 10_000)]
+        batch_size: usize,
+    },
+}
+
+#[derive(Debug, Args)]
+struct EnqueueArgs {
+    /// Job type the worker registry dispatches on, e.g. `email:send`.
+    #[arg(long = "type")]
+    job_type: String,
+    /// JSON payload, or `@path` to read it from a file.
+    #[arg(long)]
+    payload: String,
+    /// Payload version.
+    #[arg(long, default_value_t = 1)]
+    version: i32,
+    /// Attempts before the job is dead-lettered.
+    #[arg(long, default_value_t = 3)]
+    max_retries: i32,
+    /// Priority (higher runs first).
+    #[arg(long, default_value_t = 0)]
+    priority: i32,
+    /// Seconds to wait before the job is ready.
+    #[arg(long)]
+    delay: Option<i64>,
+    /// Skip the insert if a job with this key is queued or recently
+    /// succeeded.
+    #[arg(long)]
+    idempotency_key: Option<String>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let Some(url) = cli.database_url.as_deref() else {
+        bail!("no database given: pass --database-url or set DATABASE_URL");
+    };
+    let pool = PgPool::connect(url)
+        .await
+        .context("failed to connect to Postgres")?;
+    let store = PgJobStore::new(pool);
+    let out = Output { json: cli.json };
+
+    match cli.command {
+        Command::Jobs(command) => jobs(&store, command, out).await,
+        Command::Stats => {
+            let total = store.stats().await?;
+            let per_type = JobAdmin::job_type_stats(&store).await?;
+            if out.json {
+                return out.value(&serde_json::json!({ "total": total, "job_types": per_type }));
+            }
+            let counts = |s: &seesaw_core::QueueStats| {
+                [s.pending, s.running, s.succeeded, s.failed, s.dead_letter].map(|n| n.to_string())
+            };
+            let mut rows: Vec<Vec<String>> = per_type
+                .iter()
+                .map(|t| {
+                    let mut row = vec![t.job_type.clone()];
+                    row.extend(counts(&t.stats));
+                    row.push(t.retrying.to_string());
+                    row
+                })
+                .collect();
+            let mut row = vec!["(all)".to_string()];
+            row.extend(counts(&total));
+            row.push(String::new());
+            rows.push(row);
+            out.table(
+                &[
+                    "JOB TYPE",
+                    "PENDING",
+                    "RUNNING",
+                    "SUCCEEDED",
+                    "FAILED",
+                    "DEAD LETTER",
+                    "RETRYING",
+                ],
+                rows,
+            );
+            Ok(())
+        }
+        Command::Workers => {
+            let workers = store.workers().await?;
+            if out.json {
+                return out.value(&workers);
+            }
+            out.table(
+                &["WORKER", "RUNNING", "EXPIRED", "LAST SEEN"],
+                workers
+                    .iter()
+                    .map(|w| {
+                        vec![
+                            w.worker_id.clone(),
+                            w.running.to_string(),
+                            w.expired.to_string(),
+                            w.last_seen.to_rfc3339(),
+                        ]
+                    })
+                    .collect(),
+            );
+            Ok(())
+        }
+    }
+}
+
+async fn jobs(store: &PgJobStore, command: JobsCommand, out: Output) -> Result<()> {
+    match command {
+        JobsCommand::List {
+            status,
+            job_type,
+            limit,
+        } => {
+            let mut filter = JobFilter::default().with_limit(limit);
+            if let Some(status) = status {
+                filter = filter.with_status(status);
+            }
+            if let Some(job_type) = job_type {
+                filter = filter.with_job_type(job_type);
+            }
+            let jobs = store.list_jobs(&filter).await?;
+            if out.json {
+                return out.value(&jobs);
+            }
+            out.table(
+                &["ID", "TYPE", "STATUS", "ATTEMPT", "UPDATED", "ERROR"],
+                jobs.iter()
+                    .map(|job| {
+                        vec![
+                            job.id.to_string(),
+                            job.job_type.clone(),
+                            job.status.clone(),
+                            format!("{}/{}", job.attempt, job.max_retries),
+                            job.updated_at.to_rfc3339(),
+                            job.error_message.clone().unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            );
+            Ok(())
+        }
+        JobsCommand::Show { id } => {
+            let Some(job) = store.job(id).await? else {
+                bail!("job {id} not found");
+            };
+            out.value(&job)
+        }
+        JobsCommand::Retry { id } => {
+            if !store.retry_dead_letter(id).await? {
+                bail!("job {id} is not dead-lettered");
+            }
+            out.done(id, "requeued")
+        }
+        JobsCommand::Unpark { id } => {
+            if !store.unpark(id).await? {
+                bail!("job {id} is not parked");
+            }
+            out.done(id, "unparked")
+        }
+        JobsCommand::Cancel { id } => {
+            if !store.cancel(id).await? {
+                bail!("job {id} is not pending");
+            }
+            out.done(id, "cancelled")
+        }
+        JobsCommand::SetPriority { id, priority } => {
+            if !store.set_priority(id, priority).await? {
+                bail!("job {id} is not pending");
+            }
+            out.done(id, "reprioritized")
+        }
+        JobsCommand::Bump {
+            job_type,
+            payload,
+            delta,
+        } => {
+            let mut filter = JobFilter::default();
+            if let Some(job_type) = job_type {
+                filter = filter.with_job_type(job_type);
+            }
+            if let Some(payload) = payload {
+                filter = filter.with_payload_containing(parse_payload(&payload)?);
+            }
+            let bumped = store.bump_priority(&filter, delta).await?;
+            if out.json {
+                return out.value(&serde_json::json!({ "bumped": bumped }));
+            }
+            println!("reprioritized {bumped} jobs");
+            Ok(())
+        }
+        JobsCommand::Enqueue(args) => {
+            let mut job = NewJob::new(args.job_type, parse_payload(&args.payload)?)
+                .with_version(args.version)
+                .with_max_retries(args.max_retries)
+                .with_priority(args.priority);
+            if let Some(delay) = args.delay {
+                job = job.with_run_at(Utc::now() + Duration::seconds(delay));
+            }
+            if let Some(key) = args.idempotency_key {
+                job = job.with_idempotency_key(key);
+            }
+            let id = store.insert_job(&job).await?;
+            out.done(id, "enqueued")
+        }
+        JobsCommand::Import { path, batch_size } => {
+            let reader: Box<dyn BufRead> = if path == Path::new("-") {
+                Box::new(std::io::stdin().lock())
+            } else {
+                let file = std::fs::File::open(&path)
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                Box::new(BufReader::new(file))
+            };
+
+            let mut imported = 0;
+            let mut batch = Vec::with_capacity(batch_size);
+            for (number, line) in reader.lines().enumerate() {
+                if let Some(job) = parse_import_line(&line?, number + 1)? {
+                    batch.push(job);
+                }
+                if batch.len() >= batch_size.max(1) {
+                    imported += store.import_jobs(batch.drain(..)).await?;
+                    eprintln!("imported {imported} jobs");
+                }
+            }
+            imported += store.import_jobs(batch).await?;
+
+            if out.json {
+                return out.value(&serde_json::json!({ "imported": imported }));
+            }
+            println!("imported {imported} jobs");
+            Ok(())
+        }
+        JobsCommand::Upcoming { minutes } => {
+            let upcoming = store
+                .upcoming(std::time::Duration::from_secs(minutes * 60))
+                .await?;
+            if out.json {
+                return out.value(&upcoming);
+            }
+            out.table(
+                &["JOB TYPE", "DUE", "RETRYING", "NEXT RUN", "LAST RUN"],
+                upcoming
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            s.job_type.clone(),
+                            s.due.to_string(),
+                            s.retrying.to_string(),
+                            s.next_run_at.to_rfc3339(),
+                            s.last_run_at.to_rfc3339(),
+                        ]
+                    })
+                    .collect(),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Parse `--payload`: inline JSON, or `@path` to a JSON file.
+fn parse_payload(arg: &str) -> Result<serde_json::Value> {
+    match arg.strip_prefix('@') {
+        Some(path) => {
+            let text = std::fs::read_to_string(Path::new(path))
+                .with_context(|| format!("failed to read payload file {path}"))?;
+            serde_json::from_str(&text).with_context(|| format!("{path} is not valid JSON"))
+        }
+        None => serde_json::from_str(arg).context("payload is not valid JSON"),
+    }
+}
+
+/// Parse one line of an import file; blank lines are skipped.
+fn parse_import_line(line: &str, number: usize) -> Result<Option<ImportJob>> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let job: ImportJob = serde_json::from_str(line)
+        .with_context(|| format!("line {number} is not an import record"))?;
+    job.validate()
+        .with_context(|| format!("line {number} ({}) is invalid", job.id))?;
+    Ok(Some(job))
+}
+
+// ============================================================================
+// Output
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn value(&self, value: &impl Serialize) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        Ok(())
+    }
+
+    fn done(&self, id: Uuid, action: &str) -> Result<()> {
+        if self.json {
+            return self.value(&serde_json::json!({ "id": id, "result": action }));
+        }
+        println!("{id} {action}");
+        Ok(())
+    }
+
+    fn table(&self, headers: &[&str], rows: Vec<Vec<String>>) {
+        print!("{}", render_table(headers, &rows));
+    }
+}
+
+/// Left-aligned columns separated by two spaces.
+fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let headers = headers.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in std::iter::once(&headers).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_payload_inline_and_file() {
+        assert_eq!(
+            parse_payload(r#"{"to":"a@example.com"}"#).unwrap(),
+            serde_json::json!({ "to": "a@example.com" })
+        );
+
+        let path = std::env::temp_dir().join(format!("seesaw-cli-{}.json", Uuid::new_v4()));
+        std::fs::write(&path, "[1, 2]").unwrap();
+        let parsed = parse_payload(&format!("@{}", path.display())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(parsed, serde_json::json!([1, 2]));
+
+        assert!(parse_payload("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_import_line_applies_defaults_and_validates() {
+        let id = Uuid::new_v4();
+        let job = parse_import_line(
+            &format!(
+                r#"{{"id":"{id}","job_type":"email:send","payload":{{}},"created_at":"2024-05-01T12:00:00Z"}}"#
+            ),
+            1,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!((job.attempt, job.max_retries), (1, 3));
+
+        assert!(parse_import_line("  ", 2).unwrap().is_none());
+
+        let error = parse_import_line(
+            &format!(
+                r#"{{"id":"{id}","job_type":"email:send","payload":{{}},"status":"dead_letter","created_at":"2024-05-01T12:00:00Z"}}"#
+            ),
+            3,
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("line 3"));
+        assert!(format!("{error:#}").contains("need an error_message"));
+    }
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let table = render_table(
+            &["ID", "STATUS"],
+            &[
+                vec!["1".into(), "dead_letter".into()],
+                vec!["22".into(), "".into()],
+            ],
+        );
+        assert_eq!(table, "ID  STATUS\n1   dead_letter\n22\n");
+    }
+
+    #[test]
+    fn test_cli_parses_enqueue() {
+        let cli = Cli::parse_from([
+            "seesaw",
+            "jobs",
+            "enqueue",
+            "--type",
+            "email:send",
+            "--payload",
+            "{}",
+            "--json",
+        ]);
+        assert!(cli.json);
+        match cli.command {
+            Command::Jobs(JobsCommand::Enqueue(args)) => {
+                assert_eq!(args.job_type, "email:send");
+                assert_eq!(args.max_retries, 3);
+            }
+            other => panic!("expected enqueue, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! LLM calls through the Anthropic Messages API.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::llm::{
+    check, LlmCommand, LlmCompletion, LlmError, LlmProvider, LlmUsage, SseBuffer,
+};
+
+/// API version sent in the `anthropic-version` header.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Runs models with the Anthropic Messages API.
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl AnthropicProvider {
+    /// Call the API with `api_key`.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            base_url: "https://api.anthropic.com".into(),
+        }
+    }
+
+    /// Read the API key from `ANTHROPIC_API_KEY`.
+    pub fn from_env() -> Result<Self> {
+        let api_key =
+            std::env::var("ANTHROPIC_API_KEY").context("ANTHROPIC_API_KEY is not set")?;
+        Ok(Self::new(api_key))
+    }
+
+    /// Send requests to `base_url` instead, e.g. a proxy.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send requests with `client`, e.g. one with a timeout.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl std::fmt::Debug for AnthropicProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnthropicProvider")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    async fn complete(
+        &self,
+        request: &LlmCommand,
+        on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<LlmCompletion> {
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body(request))
+            .send()
+            .await?;
+        let response = check(self.name(), response).await?;
+
+        if !request.stream {
+            let message: Message = response.json().await?;
+            return Ok(message.into_completion());
+        }
+
+        let mut completion = LlmCompletion::default();
+        let mut buffer = SseBuffer::default();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            for data in buffer.push(&chunk?) {
+                apply(&data, &mut completion, on_text)?;
+            }
+        }
+        Ok(completion)
+    }
+}
+
+/// The request body for `request`.
+fn body(request: &LlmCommand) -> serde_json::Value {
+    let mut body = json!({
+        "model": request.model,
+        "max_tokens": request.max_tokens,
+        "messages": request.messages,
+        "stream": request.stream,
+    });
+    if let Some(system) = &request.system {
+        body["system"] = json!(system);
+    }
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    body
+}
+
+/// Add the stream event `data` to `completion`, passing new text to
+/// `on_text`.
+fn apply(
+    data: &str,
+    completion: &mut LlmCompletion,
+    on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+) -> Result<()> {
+    let event: StreamEvent = serde_json::from_str(data)
+        .with_context(|| format!("malformed Anthropic stream event: {}", data))?;
+    match event {
+        StreamEvent::MessageStart { message } => {
+            completion.usage.input_tokens = message.usage.input_tokens;
+        }
+        StreamEvent::ContentBlockDelta { delta } => {
+            if let Some(text) = delta.text {
+                on_text(&text);
+                completion.text.push_str(&text);
+            }
+        }
+        StreamEvent::MessageDelta { delta, usage } => {
+            completion.stop_reason = delta.stop_reason.or(completion.stop_reason.take());
+            completion.usage.output_tokens = usage.output_tokens;
+        }
+        StreamEvent::Error { error } => {
+            // Errors after the response started arrive in the stream
+            let status = if error.kind == "overloaded_error" { 529 } else { 500 };
+            return Err(LlmError::new("anthropic", status, &error.message).into());
+        }
+        StreamEvent::Other => {}
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    usage: Usage,
+}
+
+impl Message {
+    fn into_completion(self) -> LlmCompletion {
+        LlmCompletion {
+            text: self.content.into_iter().filter_map(|block| block.text).collect(),
+            stop_reason: self.stop_reason,
+            usage: LlmUsage {
+                input_tokens: self.usage.input_tokens,
+                output_tokens: self.usage.output_tokens,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart { message: StartedMessage },
+    ContentBlockDelta { delta: ContentDelta },
+    MessageDelta { delta: MessageDelta, usage: Usage },
+    Error { error: ApiError },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StartedMessage {
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct ContentDelta {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use seesaw_core::{Categorizable, SafeErrorCategory};
+
+    use super::*;
+
+    #[test]
+    fn test_applies_stream_events() {
+        let chunks = Mutex::new(Vec::new());
+        let on_text = |text: &str| chunks.lock().unwrap().push(text.to_string());
+        let mut completion = LlmCompletion::default();
+
+        for data in [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":12,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","delta":{"type":"text_delta","text":"Hello"}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","delta":{"type":"text_delta","text":" world"}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":4}}"#,
+            r#"{"type":"message_stop"}"#,
+        ] {
+            apply(data, &mut completion, &on_text).unwrap();
+        }
+
+        assert_eq!(completion.text, "Hello world");
+        assert_eq!(completion.stop_reason.as_deref(), Some("end_turn"));
+        assert_eq!(completion.usage.input_tokens, 12);
+        assert_eq!(completion.usage.output_tokens, 4);
+        assert_eq!(*chunks.lock().unwrap(), ["Hello", " world"]);
+    }
+
+    #[test]
+    fn test_overloaded_stream_error_is_retryable() {
+        let data = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+
+        let error = apply(data, &mut LlmCompletion::default(), &|_: &str| {}).unwrap_err();
+
+        let error = error.downcast_ref::<LlmError>().unwrap();
+        assert_eq!(error.status, 529);
+        assert!(error.is_retryable());
+        assert_eq!(error.category(), SafeErrorCategory::AIFailure);
+    }
+
+    #[test]
+    fn test_body_includes_optional_fields_only_when_set() {
+        let request = LlmCommand::new("claude-sonnet-4-5", "Hi");
+        assert!(body(&request).get("system").is_none());
+
+        let body = body(&request.with_system("Be brief").with_temperature(0.5));
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+}
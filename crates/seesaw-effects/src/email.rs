@@ -0,0 +1,198 @@
+//! Sending email through a pluggable [`Mailer`].
+
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use seesaw_core::{Command, Effect, EffectContext, FromDeps, RetryPolicy};
+
+use crate::retrying;
+
+/// Send an email.
+///
+/// At least one of `text` and `html` must be set; with both, the message
+/// is sent as `multipart/alternative`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendEmail {
+    /// Sender address, e.g. `"Acme <noreply@acme.com>"`.
+    pub from: String,
+    /// Recipient addresses.
+    pub to: Vec<String>,
+    /// Address replies go to instead of `from`.
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// Subject line.
+    pub subject: String,
+    /// Plain text body.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// HTML body.
+    #[serde(default)]
+    pub html: Option<String>,
+}
+
+impl SendEmail {
+    /// An email from `from` to `to` without a body yet.
+    pub fn new(from: impl Into<String>, to: impl Into<String>, subject: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: vec![to.into()],
+            reply_to: None,
+            subject: subject.into(),
+            text: None,
+            html: None,
+        }
+    }
+
+    /// Add a recipient.
+    pub fn with_to(mut self, to: impl Into<String>) -> Self {
+        self.to.push(to.into());
+        self
+    }
+
+    /// Set the reply-to address.
+    pub fn with_reply_to(mut self, reply_to: impl Into<String>) -> Self {
+        self.reply_to = Some(reply_to.into());
+        self
+    }
+
+    /// Set the plain text body.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Set the HTML body.
+    pub fn with_html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+}
+
+impl Command for SendEmail {}
+
+/// A [`SendEmail`] was accepted by the mail service.
+#[derive(Debug, Clone)]
+pub struct EmailSent {
+    /// Message ID assigned by the service, if it reported one.
+    pub message_id: Option<String>,
+    /// Recipient addresses.
+    pub to: Vec<String>,
+}
+
+/// A service that delivers email.
+///
+/// Hold one in the deps as `Arc<dyn Mailer>`, so [`SendEmailEffect`] can
+/// extract it; a test double recording what was sent needs no network.
+#[async_trait]
+pub trait Mailer: Send + Sync + 'static {
+    /// Hand `email` to the service, returning the message ID it assigned,
+    /// if any.
+    async fn send(&self, email: &SendEmail) -> Result<Option<String>>;
+}
+
+/// Executes [`SendEmail`] with the deps' `Arc<dyn Mailer>`.
+#[derive(Debug, Clone)]
+pub struct SendEmailEffect {
+    policy: RetryPolicy,
+}
+
+impl SendEmailEffect {
+    /// Send each email once.
+    pub fn new() -> Self {
+        Self {
+            policy: RetryPolicy::new().with_max_attempts(1),
+        }
+    }
+
+    /// Retry failed sends under `policy`.
+    ///
+    /// A service that accepted an email but failed to answer delivers it
+    /// again on retry, so only retry errors known to precede acceptance.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl Default for SendEmailEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<D> Effect<SendEmail, D> for SendEmailEffect
+where
+    D: Send + Sync + 'static,
+    Arc<dyn Mailer>: FromDeps<D>,
+{
+    type Event = EmailSent;
+
+    async fn execute(&self, command: SendEmail, ctx: EffectContext<D>) -> Result<EmailSent> {
+        if command.text.is_none() && command.html.is_none() {
+            bail!("email {:?} has neither a text nor an HTML body", command.subject);
+        }
+        let mailer = ctx.get::<Arc<dyn Mailer>>();
+        let (message_id, _) = retrying(&self.policy, "send_email", || mailer.send(&command)).await?;
+
+        Ok(EmailSent {
+            message_id,
+            to: command.to,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use seesaw_core::EventBus;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingMailer {
+        sent: Mutex<Vec<SendEmail>>,
+    }
+
+    #[async_trait]
+    impl Mailer for RecordingMailer {
+        async fn send(&self, email: &SendEmail) -> Result<Option<String>> {
+            self.sent.lock().unwrap().push(email.clone());
+            Ok(Some("msg-1".into()))
+        }
+    }
+
+    #[derive(Clone)]
+    struct Deps {
+        mailer: Arc<dyn Mailer>,
+    }
+
+    impl FromDeps<Deps> for Arc<dyn Mailer> {
+        fn from_deps(deps: &Deps) -> Self {
+            deps.mailer.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sends_through_mailer_from_deps() {
+        let mailer = Arc::new(RecordingMailer::default());
+        let ctx = EffectContext::new(
+            Arc::new(Deps {
+                mailer: mailer.clone(),
+            }),
+            EventBus::new(),
+        );
+
+        let email = SendEmail::new("noreply@acme.com", "ada@example.com", "Welcome")
+            .with_text("Hello Ada");
+        let sent = SendEmailEffect::new().execute(email, ctx).await.unwrap();
+
+        assert_eq!(sent.message_id.as_deref(), Some("msg-1"));
+        assert_eq!(sent.to, vec!["ada@example.com".to_string()]);
+        assert_eq!(mailer.sent.lock().unwrap()[0].subject, "Welcome");
+    }
+}
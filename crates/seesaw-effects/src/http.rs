@@ -0,0 +1,248 @@
+//! Fetching URLs, retrying transient failures.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use seesaw_core::{Command, Effect, EffectContext, FromDeps, RetryPolicy};
+
+use crate::retrying;
+
+/// Fetch a URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpFetch {
+    /// Request method, e.g. `"GET"`.
+    pub method: String,
+    /// Absolute URL to fetch.
+    pub url: String,
+    /// Request headers, in order.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Request body, if any.
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl HttpFetch {
+    /// A `GET` request for `url`.
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: "GET".into(),
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// A `POST` request for `url` with `body` as JSON.
+    pub fn post_json(url: impl Into<String>, body: &serde_json::Value) -> Self {
+        Self {
+            method: "POST".into(),
+            url: url.into(),
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: Some(body.to_string()),
+        }
+    }
+
+    /// Add a request header.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl Command for HttpFetch {}
+
+/// The successful response to an [`HttpFetch`].
+#[derive(Debug, Clone)]
+pub struct HttpFetched {
+    /// The URL that was fetched.
+    pub url: String,
+    /// Response status, always `2xx`.
+    pub status: u16,
+    /// Response headers whose values are valid UTF-8.
+    pub headers: Vec<(String, String)>,
+    /// Response body.
+    pub body: Bytes,
+    /// Attempts the fetch took, including the successful one.
+    pub attempts: u32,
+}
+
+impl HttpFetched {
+    /// The body as UTF-8 text.
+    pub fn text(&self) -> Result<&str> {
+        std::str::from_utf8(&self.body).context("response body is not UTF-8")
+    }
+
+    /// The body parsed as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).context("response body is not the expected JSON")
+    }
+}
+
+/// A response with a status outside `2xx`.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{method} {url} failed with status {status}")]
+pub struct HttpStatusError {
+    /// Request method.
+    pub method: String,
+    /// Requested URL.
+    pub url: String,
+    /// Response status.
+    pub status: u16,
+}
+
+/// Whether `error` is worth another attempt: a timeout, a failed
+/// connection, or an [`HttpStatusError`] with status `408`, `429` or `5xx`.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    if let Some(e) = error.downcast_ref::<HttpStatusError>() {
+        return matches!(e.status, 408 | 429 | 500..=599);
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_timeout() || e.is_connect())
+}
+
+/// Policy fetches retry with unless configured otherwise: 3 attempts,
+/// 200ms initial backoff doubling up to 5s, for [`is_retryable`] errors.
+pub(crate) fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .with_max_attempts(3)
+        .with_initial_backoff(Duration::from_millis(200))
+        .with_max_backoff(Duration::from_secs(5))
+        .with_retry_if(is_retryable)
+}
+
+/// Executes [`HttpFetch`] with the deps' `reqwest::Client`.
+///
+/// Each attempt is bounded by what is left of the triggering event's
+/// deadline, if it has one. A response outside `2xx` after the last attempt
+/// fails the command with an [`HttpStatusError`].
+#[derive(Debug, Clone)]
+pub struct HttpFetchEffect {
+    policy: RetryPolicy,
+}
+
+impl HttpFetchEffect {
+    /// Fetch with the default retry policy.
+    pub fn new() -> Self {
+        Self {
+            policy: default_retry_policy(),
+        }
+    }
+
+    /// Retry under `policy` instead, e.g. with more attempts.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl Default for HttpFetchEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<D> Effect<HttpFetch, D> for HttpFetchEffect
+where
+    D: Send + Sync + 'static,
+    reqwest::Client: FromDeps<D>,
+{
+    type Event = HttpFetched;
+
+    async fn execute(&self, command: HttpFetch, ctx: EffectContext<D>) -> Result<HttpFetched> {
+        let client = ctx.get::<reqwest::Client>();
+        let method = Method::from_bytes(command.method.as_bytes())
+            .with_context(|| format!("invalid HTTP method {:?}", command.method))?;
+
+        let (response, attempts) = retrying(&self.policy, "http_fetch", || {
+            let mut request = client.request(method.clone(), &command.url);
+            for (name, value) in &command.headers {
+                request = request.header(name, value);
+            }
+            if let Some(body) = &command.body {
+                request = request.body(body.clone());
+            }
+            if let Some(remaining) = ctx.remaining() {
+                request = request.timeout(remaining);
+            }
+            send(request, &command.method, &command.url)
+        })
+        .await?;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        Ok(HttpFetched {
+            url: command.url,
+            status,
+            headers,
+            body: response.bytes().await?,
+            attempts,
+        })
+    }
+}
+
+/// Send `request`, failing with an [`HttpStatusError`] outside `2xx`.
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+    method: &str,
+    url: &str,
+) -> Result<reqwest::Response> {
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(HttpStatusError {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: response.status().as_u16(),
+        }
+        .into());
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16) -> anyhow::Error {
+        HttpStatusError {
+            method: "GET".into(),
+            url: "https://example.com".into(),
+            status,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_retries_server_errors_and_throttling_only() {
+        assert!(is_retryable(&status(503)));
+        assert!(is_retryable(&status(429)));
+        assert!(is_retryable(&status(408)));
+        assert!(!is_retryable(&status(404)));
+        assert!(!is_retryable(&status(400)));
+        assert!(!is_retryable(&anyhow::anyhow!("invalid URL")));
+    }
+
+    #[test]
+    fn test_post_json_sets_content_type() {
+        let fetch = HttpFetch::post_json("https://example.com", &serde_json::json!({"a": 1}));
+
+        assert_eq!(fetch.method, "POST");
+        assert_eq!(fetch.body.as_deref(), Some(r#"{"a":1}"#));
+        assert_eq!(
+            fetch.headers,
+            vec![("content-type".to_string(), "application/json".to_string())]
+        );
+    }
+}
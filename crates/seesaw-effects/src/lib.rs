@@ -0,0 +1,133 @@
+//! Reusable effects for the IO most apps need.
+//!
+//! Every effect here is generic over the app's deps and reaches what it needs
+//! through [`FromDeps`](seesaw_core::FromDeps), so registering one only takes
+//! a deps struct holding the right types:
+//!
+//! | Effect | Command → Event | Feature | Needs from deps |
+//! |--------|-----------------|---------|-----------------|
+//! | [`HttpFetchEffect`] | [`HttpFetch`] → [`HttpFetched`] | `http` (default) | `reqwest::Client` |
+//! | [`WebhookDeliveryEffect`] | [`DeliverWebhook`] → [`WebhookDelivered`] | `webhook` (default) | `reqwest::Client`, [`WebhookSigner`] |
+//! | [`WebhookEffect`] | [`WebhookCommand`] → [`WebhookSent`] | `webhook` (default) | `reqwest::Client`, [`WebhookSigner`] |
+//! | [`SendEmailEffect`] | [`SendEmail`] → [`EmailSent`] | always | `Arc<dyn Mailer>` |
+//! | [`LlmEffect`] | [`LlmCommand`] → [`LlmResponded`] | `llm` | `Arc<dyn LlmProvider>` |
+//!
+//! [`Mailer`] has an SMTP implementation, [`SmtpMailer`] (`smtp` feature),
+//! and an Amazon SES one, [`SesMailer`] (`ses` feature). [`LlmProvider`] has
+//! [`AnthropicProvider`] and [`OpenAiProvider`], which also calls local
+//! OpenAI-compatible servers; see [`llm`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_core::{EngineBuilder, FromDeps};
+//! use seesaw_effects::{HttpFetch, HttpFetchEffect, Mailer, SendEmail, SendEmailEffect, SmtpMailer};
+//!
+//! #[derive(Clone, FromDeps)]
+//! struct Deps {
+//!     db: PgPool,
+//!     http: reqwest::Client,
+//!     mailer: Arc<dyn Mailer>,
+//! }
+//!
+//! let deps = Deps {
+//!     db,
+//!     http: reqwest::Client::new(),
+//!     mailer: Arc::new(SmtpMailer::relay("smtp.example.com", user, password)?),
+//! };
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect::<HttpFetch, _>(HttpFetchEffect::new())
+//!     .with_effect::<SendEmail, _>(SendEmailEffect::new())
+//!     .build();
+//! ```
+//!
+//! # Retries
+//!
+//! HTTP fetches and webhook deliveries retry timeouts, refused connections,
+//! `408`, `429` and `5xx` responses within the effect, under a
+//! [`RetryPolicy`](seesaw_core::RetryPolicy) set with `with_retry`. Email is
+//! sent once by default: a relay that accepted the message but dropped the
+//! connection before answering would otherwise deliver it twice.
+//!
+//! [`WebhookCommand`] is a background command instead: the job store
+//! persists every attempt and retries with its own backoff, and deliveries
+//! that never succeed are dead-lettered, viewable per endpoint with
+//! [`dead_letters_by_endpoint`]. See [`outbound`] for the wiring.
+
+#[cfg(feature = "llm")]
+mod anthropic;
+mod email;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "llm")]
+pub mod llm;
+#[cfg(feature = "llm")]
+mod openai;
+#[cfg(feature = "webhook")]
+pub mod outbound;
+#[cfg(feature = "ses")]
+mod ses;
+#[cfg(feature = "smtp")]
+mod smtp;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+#[cfg(feature = "llm")]
+pub use anthropic::AnthropicProvider;
+pub use email::{EmailSent, Mailer, SendEmail, SendEmailEffect};
+#[cfg(feature = "http")]
+pub use http::{is_retryable, HttpFetch, HttpFetchEffect, HttpFetched, HttpStatusError};
+#[cfg(feature = "llm")]
+pub use llm::{
+    LlmCommand, LlmCompletion, LlmEffect, LlmError, LlmMessage, LlmPrice, LlmProvider,
+    LlmResponded, LlmRole, LlmTokens, LlmUsage,
+};
+#[cfg(feature = "llm")]
+pub use openai::OpenAiProvider;
+#[cfg(feature = "webhook")]
+pub use outbound::{
+    dead_letters_by_endpoint, EndpointDeadLetters, WebhookCommand, WebhookEffect, WebhookSent,
+    DEFAULT_WEBHOOK_RETRIES, WEBHOOK_JOB_TYPE,
+};
+#[cfg(feature = "ses")]
+pub use ses::{AwsCredentials, SesMailer};
+#[cfg(feature = "smtp")]
+pub use smtp::SmtpMailer;
+#[cfg(feature = "webhook")]
+pub use webhook::{
+    DeliverWebhook, WebhookDelivered, WebhookDeliveryEffect, WebhookSigner, DELIVERY_HEADER,
+    EVENT_HEADER, SIGNATURE_HEADER,
+};
+
+use std::future::Future;
+
+use anyhow::Result;
+use seesaw_core::RetryPolicy;
+use tracing::warn;
+
+/// Run `operation` until it succeeds, fails permanently, or `policy` gives
+/// up, returning its value and the number of attempts it took.
+async fn retrying<T, F, Fut>(
+    policy: &RetryPolicy,
+    operation: &'static str,
+    mut attempt_once: F,
+) -> Result<(T, u32)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match attempt_once().await {
+            Ok(value) => return Ok((value, attempt)),
+            Err(e) if attempt < policy.max_attempts() && policy.should_retry(&e) => {
+                let delay = policy.backoff(attempt);
+                warn!(operation, attempt, delay_ms = delay.as_millis() as u64, error = %e, "retrying");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Calling large language models through a pluggable [`LlmProvider`].
+//!
+//! Machines decide an [`LlmCommand`]; [`LlmEffect`] sends it to the deps'
+//! `Arc<dyn LlmProvider>` and returns an [`LlmResponded`]. Two providers are
+//! built in:
+//!
+//! - [`AnthropicProvider`]: the Anthropic Messages API
+//! - [`OpenAiProvider`]: OpenAI Chat Completions, and local servers speaking
+//!   the same API (Ollama, vLLM, llama.cpp) through
+//!   [`OpenAiProvider::local`]
+//!
+//! ```ignore
+//! #[derive(Clone, FromDeps)]
+//! struct Deps {
+//!     llm: Arc<dyn LlmProvider>,
+//! }
+//!
+//! let sonnet = LlmPrice::per_million_tokens(300.0, 1500.0);
+//! let engine = EngineBuilder::new(Deps { llm: Arc::new(AnthropicProvider::from_env()?) })
+//!     .with_effect::<LlmCommand, _>(LlmEffect::new().with_price("claude-sonnet-4-5", sonnet))
+//!     .with_cost_ledger(CostLedger::new())
+//!     .build();
+//!
+//! // In a machine
+//! Some(LlmCommand::new("claude-sonnet-4-5", format!("Summarize:\n\n{}", text)).streaming())
+//! ```
+//!
+//! # Streaming
+//!
+//! A [`streaming`](LlmCommand::streaming) command emits each chunk of text
+//! as it arrives, as an [`LlmTokens`] signal: for UIs, not for machines,
+//! which only see the final [`LlmResponded`]. Chunks of an attempt that
+//! failed are followed by those of the next attempt, with a higher
+//! `attempt`; drop what was shown when it changes.
+//!
+//! # Retries
+//!
+//! Rate limits (`429`), overload (`529`), other `5xx` responses, timeouts and
+//! refused connections are retried within the effect: 4 attempts from 500ms.
+//! A provider's error response fails the command with an [`LlmError`],
+//! which implements [`Categorizable`]: register it with
+//! `EngineBuilder::with_error_category::<LlmError>()` so `CommandFailed`
+//! reports rate limits as `RateLimited` and rejected requests as
+//! non-retryable.
+//!
+//! # Costs
+//!
+//! Every response's token usage is reported to the dispatcher's
+//! `CostLedger` under `LlmCommand`, as `input_tokens` and `output_tokens`,
+//! and, for models given a price with [`LlmEffect::with_price`], as `cents`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use seesaw_core::{
+    Categorizable, Command, Effect, EffectContext, FromDeps, RetryPolicy, SafeErrorCategory,
+};
+
+use crate::retrying;
+
+/// Characters of a provider's error response kept in an [`LlmError`].
+const MAX_ERROR_MESSAGE: usize = 500;
+
+/// Ask a model to respond to a conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmCommand {
+    /// Identifies the request in its [`LlmResponded`] and [`LlmTokens`].
+    pub request_id: Uuid,
+    /// Model name, as the provider knows it.
+    pub model: String,
+    /// System prompt.
+    #[serde(default)]
+    pub system: Option<String>,
+    /// The conversation so far, oldest first, ending with a user message.
+    pub messages: Vec<LlmMessage>,
+    /// Most tokens the response may have.
+    pub max_tokens: u32,
+    /// Sampling temperature; the provider's default if unset.
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Whether to emit [`LlmTokens`] signals while the response arrives.
+    #[serde(default)]
+    pub stream: bool,
+}
+
+impl LlmCommand {
+    /// Send `prompt` as a user message to `model`, with up to 1024 tokens
+    /// of response.
+    pub fn new(model: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            request_id: Uuid::new_v4(),
+            model: model.into(),
+            system: None,
+            messages: vec![LlmMessage::user(prompt)],
+            max_tokens: 1024,
+            temperature: None,
+            stream: false,
+        }
+    }
+
+    /// Set the system prompt.
+    pub fn with_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    /// Add a message to the conversation.
+    pub fn with_message(mut self, message: LlmMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Set the most tokens the response may have.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the sampling temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Emit [`LlmTokens`] signals while the response arrives.
+    pub fn streaming(mut self) -> Self {
+        self.stream = true;
+        self
+    }
+}
+
+impl Command for LlmCommand {}
+
+/// Who wrote a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmRole {
+    User,
+    Assistant,
+}
+
+/// One message of a conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmMessage {
+    pub role: LlmRole,
+    pub content: String,
+}
+
+impl LlmMessage {
+    /// A message from the user.
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: LlmRole::User,
+            content: content.into(),
+        }
+    }
+
+    /// A message from the model, e.g. an earlier response.
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: LlmRole::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// Tokens a request consumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// What a provider returned for a request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmCompletion {
+    /// The response text.
+    pub text: String,
+    /// Why the model stopped, in the provider's words (`"end_turn"`,
+    /// `"stop"`, `"max_tokens"`, ...).
+    pub stop_reason: Option<String>,
+    pub usage: LlmUsage,
+}
+
+/// The response to an [`LlmCommand`].
+#[derive(Debug, Clone)]
+pub struct LlmResponded {
+    /// The command's `request_id`.
+    pub request_id: Uuid,
+    /// Name of the provider that answered.
+    pub provider: &'static str,
+    pub model: String,
+    pub text: String,
+    pub stop_reason: Option<String>,
+    pub usage: LlmUsage,
+    /// Attempts the request took, including the successful one.
+    pub attempts: u32,
+}
+
+/// A chunk of a streaming response, emitted as a signal as it arrives.
+#[derive(Debug, Clone)]
+pub struct LlmTokens {
+    /// The command's `request_id`.
+    pub request_id: Uuid,
+    /// The attempt the chunk belongs to, starting at 1.
+    pub attempt: u32,
+    pub text: String,
+}
+
+/// A service that runs language models.
+///
+/// Hold one in the deps as `Arc<dyn LlmProvider>`, so [`LlmEffect`] can
+/// extract it; a test double returning canned completions needs no network.
+#[async_trait]
+pub trait LlmProvider: Send + Sync + 'static {
+    /// Name reported in [`LlmResponded::provider`] and [`LlmError`]s.
+    fn name(&self) -> &'static str;
+
+    /// Run `request`. When it is [`streaming`](LlmCommand::stream), call
+    /// `on_text` with each chunk of text as it arrives.
+    ///
+    /// Fail with an [`LlmError`] for error responses, so they are
+    /// classified and retried.
+    async fn complete(
+        &self,
+        request: &LlmCommand,
+        on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<LlmCompletion>;
+}
+
+/// An error response from a provider.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{provider} request failed with status {status}: {message}")]
+pub struct LlmError {
+    /// Name of the provider.
+    pub provider: &'static str,
+    /// Response status; `529` when the provider reports overload.
+    pub status: u16,
+    /// The start of the provider's error response.
+    pub message: String,
+}
+
+impl LlmError {
+    /// An error for a response with `status` and `body`.
+    pub fn new(provider: &'static str, status: u16, body: &str) -> Self {
+        Self {
+            provider,
+            status,
+            message: body.chars().take(MAX_ERROR_MESSAGE).collect(),
+        }
+    }
+}
+
+impl Categorizable for LlmError {
+    fn category(&self) -> SafeErrorCategory {
+        match self.status {
+            429 => SafeErrorCategory::RateLimited,
+            401 | 403 => SafeErrorCategory::Unauthorized,
+            400 | 404 | 413 | 422 => SafeErrorCategory::Validation,
+            _ => SafeErrorCategory::AIFailure,
+        }
+    }
+
+    fn safe_message(&self) -> Cow<'static, str> {
+        match self.category() {
+            SafeErrorCategory::RateLimited => "The AI service is rate limiting requests".into(),
+            SafeErrorCategory::Unauthorized => "The AI service rejected its credentials".into(),
+            SafeErrorCategory::Validation => "The AI service rejected the request".into(),
+            _ => "The AI service is unavailable".into(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self.status, 408 | 429 | 500..=599)
+    }
+}
+
+/// Whether `error` is worth another attempt: a retryable [`LlmError`]
+/// (`408`, `429`, `5xx` including `529`), a timeout or a failed connection.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    if let Some(e) = error.downcast_ref::<LlmError>() {
+        return e.is_retryable();
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_timeout() || e.is_connect())
+}
+
+/// Price of a model's tokens, for reporting spend in cents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LlmPrice {
+    /// Cents per million input tokens.
+    pub input_cents: f64,
+    /// Cents per million output tokens.
+    pub output_cents: f64,
+}
+
+impl LlmPrice {
+    /// `input_cents` and `output_cents` per million tokens, as providers
+    /// list them (in dollars: $3 / $15 is `300.0, 1500.0`).
+    pub fn per_million_tokens(input_cents: f64, output_cents: f64) -> Self {
+        Self {
+            input_cents,
+            output_cents,
+        }
+    }
+
+    /// What `usage` cost, in cents.
+    pub fn cents(&self, usage: LlmUsage) -> f64 {
+        (f64::from(usage.input_tokens) * self.input_cents
+            + f64::from(usage.output_tokens) * self.output_cents)
+            / 1_000_000.0
+    }
+}
+
+/// Executes [`LlmCommand`] with the deps' `Arc<dyn LlmProvider>`.
+#[derive(Debug, Clone)]
+pub struct LlmEffect {
+    policy: RetryPolicy,
+    prices: HashMap<String, LlmPrice>,
+}
+
+impl LlmEffect {
+    /// Call the provider with the default retry policy.
+    pub fn new() -> Self {
+        Self {
+            policy: RetryPolicy::new()
+                .with_max_attempts(4)
+                .with_initial_backoff(Duration::from_millis(500))
+                .with_max_backoff(Duration::from_secs(20))
+                .with_retry_if(is_retryable),
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Retry under `policy` instead.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Report the spend of requests to `model` in cents as well as tokens.
+    pub fn with_price(mut self, model: impl Into<String>, price: LlmPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+}
+
+impl Default for LlmEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<D> Effect<LlmCommand, D> for LlmEffect
+where
+    D: Send + Sync + 'static,
+    Arc<dyn LlmProvider>: FromDeps<D>,
+{
+    type Event = LlmResponded;
+
+    async fn execute(&self, command: LlmCommand, ctx: EffectContext<D>) -> Result<LlmResponded> {
+        let provider = ctx.get::<Arc<dyn LlmProvider>>();
+        let mut attempt = 0;
+        let (completion, attempts) = retrying(&self.policy, "llm_call", || {
+            attempt += 1;
+            let (attempt, ctx, command, provider) = (attempt, &ctx, &command, &provider);
+            async move {
+                let on_text = |text: &str| {
+                    ctx.signal(LlmTokens {
+                        request_id: command.request_id,
+                        attempt,
+                        text: text.to_string(),
+                    })
+                };
+                provider.complete(command, &on_text).await
+            }
+        })
+        .await?;
+
+        let usage = completion.usage;
+        ctx.record_cost("input_tokens", f64::from(usage.input_tokens));
+        ctx.record_cost("output_tokens", f64::from(usage.output_tokens));
+        if let Some(price) = self.prices.get(&command.model) {
+            ctx.record_cost("cents", price.cents(usage));
+        }
+
+        Ok(LlmResponded {
+            request_id: command.request_id,
+            provider: provider.name(),
+            model: command.model,
+            text: completion.text,
+            stop_reason: completion.stop_reason,
+            usage,
+            attempts,
+        })
+    }
+}
+
+/// Fail with an [`LlmError`] unless `response` is `2xx`.
+pub(crate) async fn check(
+    provider: &'static str,
+    response: reqwest::Response,
+) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(LlmError::new(provider, status.as_u16(), &body).into())
+}
+
+/// Splits a server-sent event stream into the payloads of its `data:`
+/// lines, across chunk boundaries.
+#[derive(Debug, Default)]
+pub(crate) struct SseBuffer {
+    pending: Vec<u8>,
+}
+
+impl SseBuffer {
+    /// Add `chunk`, returning the payload of every `data:` line it
+    /// completed.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(payload) = line.trim_end_matches(['\r', '\n']).strip_prefix("data:") {
+                payloads.push(payload.trim_start().to_string());
+            }
+        }
+        payloads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use seesaw_core::{CostLedger, Dispatcher, EventBus};
+
+    use super::*;
+
+    /// Answers every request with "Hello world" in two chunks, failing the
+    /// first `overloaded` attempts with a `529`.
+    #[derive(Default)]
+    struct FakeProvider {
+        overloaded: usize,
+        calls: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        async fn complete(
+            &self,
+            request: &LlmCommand,
+            on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+        ) -> Result<LlmCompletion> {
+            let call = {
+                let mut calls = self.calls.lock().unwrap();
+                *calls += 1;
+                *calls
+            };
+            if call <= self.overloaded {
+                return Err(LlmError::new("fake", 529, "Overloaded").into());
+            }
+            if request.stream {
+                on_text("Hello");
+                on_text(" world");
+            }
+            Ok(LlmCompletion {
+                text: "Hello world".into(),
+                stop_reason: Some("end_turn".into()),
+                usage: LlmUsage {
+                    input_tokens: 1_000,
+                    output_tokens: 2_000,
+                },
+            })
+        }
+    }
+
+    #[derive(Clone)]
+    struct Deps {
+        llm: Arc<dyn LlmProvider>,
+    }
+
+    impl FromDeps<Deps> for Arc<dyn LlmProvider> {
+        fn from_deps(deps: &Deps) -> Self {
+            deps.llm.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streams_tokens_and_reports_usage() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let ledger = CostLedger::new();
+        let effect = LlmEffect::new()
+            .with_retry(
+                RetryPolicy::new()
+                    .with_initial_backoff(Duration::from_millis(1))
+                    .with_retry_if(is_retryable),
+            )
+            .with_price("fake-1", LlmPrice::per_million_tokens(300.0, 1500.0));
+        let provider = Arc::new(FakeProvider {
+            overloaded: 1,
+            ..Default::default()
+        });
+        let dispatcher = Dispatcher::new(Deps { llm: provider }, bus)
+            .with_effect::<LlmCommand, _>(effect)
+            .with_cost_ledger(ledger.clone());
+
+        let command = LlmCommand::new("fake-1", "Say hello").streaming();
+        dispatcher.dispatch(vec![Box::new(command)]).await.unwrap();
+
+        let mut chunks = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let Some(tokens) = envelope.downcast_ref::<LlmTokens>() {
+                chunks.push((tokens.attempt, tokens.text.clone()));
+            }
+        }
+        assert_eq!(chunks, [(2, "Hello".to_string()), (2, " world".to_string())]);
+
+        assert_eq!(ledger.total("LlmCommand", "input_tokens"), 1_000.0);
+        assert_eq!(ledger.total("LlmCommand", "output_tokens"), 2_000.0);
+        assert_eq!(ledger.total("LlmCommand", "cents"), 3.3);
+    }
+
+    #[test]
+    fn test_classifies_provider_errors() {
+        let error = |status| LlmError::new("fake", status, "");
+
+        assert_eq!(error(429).category(), SafeErrorCategory::RateLimited);
+        assert!(error(429).is_retryable());
+        assert_eq!(error(529).category(), SafeErrorCategory::AIFailure);
+        assert!(error(529).is_retryable());
+        assert_eq!(error(400).category(), SafeErrorCategory::Validation);
+        assert!(!error(400).is_retryable());
+        assert!(!error(401).is_retryable());
+        assert!(is_retryable(&error(503).into()));
+        assert!(!is_retryable(&anyhow::anyhow!("invalid model name")));
+    }
+
+    #[test]
+    fn test_sse_buffer_joins_lines_split_across_chunks() {
+        let mut buffer = SseBuffer::default();
+
+        assert!(buffer.push(b"event: ping\ndata: {\"a\"").is_empty());
+        assert_eq!(buffer.push(b":1}\r\n\ndata: [DONE]\n"), ["{\"a\":1}", "[DONE]"]);
+    }
+}
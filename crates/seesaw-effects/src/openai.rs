@@ -0,0 +1,259 @@
+//! LLM calls through the OpenAI Chat Completions API, and local servers
+//! speaking it.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::llm::{check, LlmCommand, LlmCompletion, LlmProvider, LlmUsage, SseBuffer};
+
+/// Runs models with the OpenAI Chat Completions API.
+#[derive(Clone)]
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    base_url: String,
+    name: &'static str,
+}
+
+impl OpenAiProvider {
+    /// Call the OpenAI API with `api_key`.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: Some(api_key.into()),
+            base_url: "https://api.openai.com/v1".into(),
+            name: "openai",
+        }
+    }
+
+    /// Read the API key from `OPENAI_API_KEY`.
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY is not set")?;
+        Ok(Self::new(api_key))
+    }
+
+    /// Call a local server with an OpenAI-compatible API, such as Ollama
+    /// at `http://localhost:11434/v1`, without an API key.
+    pub fn local(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: None,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            name: "local",
+        }
+    }
+
+    /// Send requests to `base_url` instead, e.g. a compatible gateway.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send requests with `client`, e.g. one with a timeout.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl std::fmt::Debug for OpenAiProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAiProvider")
+            .field("name", &self.name)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn complete(
+        &self,
+        request: &LlmCommand,
+        on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<LlmCompletion> {
+        let mut builder = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body(request));
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response = check(self.name, builder.send().await?).await?;
+
+        if !request.stream {
+            let chunk: Chunk = response.json().await?;
+            let mut completion = LlmCompletion::default();
+            apply(chunk, &mut completion, &|_: &str| {});
+            return Ok(completion);
+        }
+
+        let mut completion = LlmCompletion::default();
+        let mut buffer = SseBuffer::default();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            for data in buffer.push(&chunk?) {
+                if data == "[DONE]" {
+                    return Ok(completion);
+                }
+                let chunk = serde_json::from_str(&data)
+                    .with_context(|| format!("malformed {} stream chunk: {}", self.name, data))?;
+                apply(chunk, &mut completion, on_text);
+            }
+        }
+        Ok(completion)
+    }
+}
+
+/// The request body for `request`.
+fn body(request: &LlmCommand) -> serde_json::Value {
+    let mut messages = Vec::with_capacity(request.messages.len() + 1);
+    if let Some(system) = &request.system {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+    messages.extend(request.messages.iter().map(|message| json!(message)));
+
+    let mut body = json!({
+        "model": request.model,
+        "messages": messages,
+        "max_tokens": request.max_tokens,
+        "stream": request.stream,
+    });
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if request.stream {
+        // Usage is only reported in a final chunk when asked for
+        body["stream_options"] = json!({ "include_usage": true });
+    }
+    body
+}
+
+/// Add a response, or a chunk of a streamed one, to `completion`, passing
+/// new text to `on_text`.
+fn apply(
+    chunk: Chunk,
+    completion: &mut LlmCompletion,
+    on_text: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+) {
+    for choice in chunk.choices.into_iter().filter(|choice| choice.index == 0) {
+        let content = choice.message.or(choice.delta).and_then(|message| message.content);
+        if let Some(text) = content.filter(|text| !text.is_empty()) {
+            on_text(&text);
+            completion.text.push_str(&text);
+        }
+        if choice.finish_reason.is_some() {
+            completion.stop_reason = choice.finish_reason;
+        }
+    }
+    if let Some(usage) = chunk.usage {
+        completion.usage = LlmUsage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        };
+    }
+}
+
+/// A response, or a chunk of a streamed one.
+#[derive(Deserialize)]
+struct Chunk {
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    #[serde(default)]
+    index: u32,
+    /// Set in a complete response.
+    #[serde(default)]
+    message: Option<Content>,
+    /// Set in a chunk of a streamed response.
+    #[serde(default)]
+    delta: Option<Content>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn chunk(data: &str) -> Chunk {
+        serde_json::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn test_applies_streamed_chunks() {
+        let chunks = Mutex::new(Vec::new());
+        let on_text = |text: &str| chunks.lock().unwrap().push(text.to_string());
+        let mut completion = LlmCompletion::default();
+
+        for data in [
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":"Hello"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":" world"},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":9,"completion_tokens":2}}"#,
+        ] {
+            apply(chunk(data), &mut completion, &on_text);
+        }
+
+        assert_eq!(completion.text, "Hello world");
+        assert_eq!(completion.stop_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.usage.input_tokens, 9);
+        assert_eq!(completion.usage.output_tokens, 2);
+        assert_eq!(*chunks.lock().unwrap(), ["Hello", " world"]);
+    }
+
+    #[test]
+    fn test_applies_complete_response() {
+        let data = r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"Hi"},
+            "finish_reason":"length"}],"usage":{"prompt_tokens":3,"completion_tokens":1}}"#;
+        let mut completion = LlmCompletion::default();
+
+        apply(chunk(data), &mut completion, &|_: &str| {});
+
+        assert_eq!(completion.text, "Hi");
+        assert_eq!(completion.stop_reason.as_deref(), Some("length"));
+        assert_eq!(completion.usage.output_tokens, 1);
+    }
+
+    #[test]
+    fn test_body_puts_system_prompt_first() {
+        let request = LlmCommand::new("gpt-4o-mini", "Hi")
+            .with_system("Be brief")
+            .streaming();
+
+        let body = body(&request);
+
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Hi");
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+}
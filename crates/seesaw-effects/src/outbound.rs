@@ -0,0 +1,353 @@
+//! Webhook deliveries as background jobs.
+//!
+//! [`DeliverWebhook`](crate::DeliverWebhook) retries within one effect
+//! execution, so its attempts are lost if the process stops. A
+//! [`WebhookCommand`] is instead enqueued on the job queue: each attempt is
+//! one job attempt, the job store records its error and schedules the next
+//! one with its exponential backoff, and a delivery that never succeeds ends
+//! up dead-lettered, where it can be inspected and retried.
+//!
+//! ```rust,ignore
+//! use seesaw_effects::{WebhookCommand, WebhookEffect, WEBHOOK_JOB_TYPE};
+//!
+//! // Producers: route the command to the queue
+//! let engine = EngineBuilder::new(deps.clone())
+//!     .with_job_queue(store.clone())
+//!     .with_effect::<WebhookCommand, _>(WebhookEffect::new())
+//!     .build();
+//!
+//! // Workers: deserialize claimed deliveries
+//! registry.register::<WebhookCommand>(WEBHOOK_JOB_TYPE, vec![1]);
+//!
+//! // In a machine
+//! Some(WebhookCommand::new("acme-crm", &endpoint.url, "order.shipped", json!({ "id": id })))
+//! ```
+//!
+//! Every command names the endpoint it is for, so the dead letters of one
+//! failing receiver can be told apart with [`dead_letters_by_endpoint`]. On
+//! Postgres, `PgJobStore::list_jobs` filters them in the database:
+//!
+//! ```rust,ignore
+//! let failed = store
+//!     .list_jobs(
+//!         &JobFilter::default()
+//!             .with_status("dead_letter")
+//!             .with_job_type(WEBHOOK_JOB_TYPE)
+//!             .with_payload_containing(json!({ "endpoint": "acme-crm" })),
+//!     )
+//!     .await?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use seesaw_core::{
+    Command, DeadLetterJob, Effect, EffectContext, ExecutionMode, FromDeps, JobAdmin, JobSpec,
+};
+
+use crate::http::send;
+use crate::webhook::{signed_post, WebhookSigner};
+
+/// Job type of [`WebhookCommand`], for registering it with the worker's
+/// `CommandRegistry`.
+pub const WEBHOOK_JOB_TYPE: &str = "webhook:deliver";
+
+/// Retries a [`WebhookCommand`] gets unless configured otherwise. With the
+/// SQL stores' backoff doubling from 2s up to an hour, the last attempt
+/// runs about two hours after the first.
+pub const DEFAULT_WEBHOOK_RETRIES: i32 = 12;
+
+/// Deliver `payload` to `url` as a signed webhook, from a background job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookCommand {
+    /// Identifies the delivery to the receiver across attempts.
+    pub delivery_id: Uuid,
+    /// Names the receiving endpoint, e.g. a customer's integration.
+    pub endpoint: String,
+    /// Endpoint URL.
+    pub url: String,
+    /// Event type, e.g. `"order.shipped"`.
+    pub event_type: String,
+    /// JSON body.
+    pub payload: serde_json::Value,
+    /// Failed attempts retried before the delivery is dead-lettered.
+    pub max_retries: i32,
+}
+
+impl WebhookCommand {
+    /// A delivery of `payload` to `endpoint` at `url`, with a fresh delivery
+    /// ID and [`DEFAULT_WEBHOOK_RETRIES`].
+    pub fn new(
+        endpoint: impl Into<String>,
+        url: impl Into<String>,
+        event_type: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            delivery_id: Uuid::new_v4(),
+            endpoint: endpoint.into(),
+            url: url.into(),
+            event_type: event_type.into(),
+            payload,
+            max_retries: DEFAULT_WEBHOOK_RETRIES,
+        }
+    }
+
+    /// Retry failed attempts `max_retries` times.
+    pub fn with_max_retries(mut self, max_retries: i32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+impl Command for WebhookCommand {
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Background
+    }
+
+    fn job_spec(&self) -> Option<JobSpec> {
+        Some(
+            JobSpec::new(WEBHOOK_JOB_TYPE)
+                .with_max_retries(self.max_retries)
+                .with_idempotency_key(format!("webhook:{}", self.delivery_id)),
+        )
+    }
+
+    fn serialize_to_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
+/// A [`WebhookCommand`] was answered with a `2xx` status.
+#[derive(Debug, Clone)]
+pub struct WebhookSent {
+    /// The delivery ID.
+    pub delivery_id: Uuid,
+    /// The endpoint name.
+    pub endpoint: String,
+    /// Response status.
+    pub status: u16,
+}
+
+/// Executes [`WebhookCommand`] with the deps' `reqwest::Client` and
+/// [`WebhookSigner`], making one attempt per job attempt.
+///
+/// Any failure, including a receiver answering outside `2xx`, fails the job
+/// attempt, leaving the retry to the job store.
+#[derive(Debug, Clone)]
+pub struct WebhookEffect {
+    timeout: Duration,
+}
+
+impl WebhookEffect {
+    /// Deliver with a 10s timeout per attempt.
+    pub fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Give each attempt `timeout` to be answered.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for WebhookEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<D> Effect<WebhookCommand, D> for WebhookEffect
+where
+    D: Send + Sync + 'static,
+    reqwest::Client: FromDeps<D>,
+    WebhookSigner: FromDeps<D>,
+{
+    type Event = WebhookSent;
+
+    async fn execute(
+        &self,
+        command: WebhookCommand,
+        ctx: EffectContext<D>,
+    ) -> Result<WebhookSent> {
+        let client = ctx.get::<reqwest::Client>();
+        let signer = ctx.get::<WebhookSigner>();
+        let body = serde_json::to_vec(&command.payload)?;
+
+        let request = signed_post(
+            &client,
+            &signer,
+            &command.url,
+            &command.event_type,
+            &command.delivery_id.to_string(),
+            &body,
+        )
+        .timeout(self.timeout);
+        let response = send(request, "POST", &command.url).await?;
+
+        Ok(WebhookSent {
+            delivery_id: command.delivery_id,
+            endpoint: command.endpoint,
+            status: response.status().as_u16(),
+        })
+    }
+}
+
+/// The dead-lettered deliveries of one endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointDeadLetters {
+    /// The endpoint name.
+    pub endpoint: String,
+    /// Its dead-lettered delivery jobs, most recently failed first.
+    pub jobs: Vec<DeadLetterJob>,
+}
+
+impl EndpointDeadLetters {
+    /// When the most recent delivery failed for good.
+    pub fn last_failed_at(&self) -> Option<DateTime<Utc>> {
+        self.jobs.first().map(|job| job.failed_at)
+    }
+}
+
+/// Dead-lettered [`WebhookCommand`] deliveries grouped by endpoint, among
+/// the `scan` most recent dead letters of any job type.
+///
+/// Endpoints are ordered by name. Retry a delivery with
+/// [`JobAdmin::retry_dead_letter`] once its receiver is fixed.
+pub async fn dead_letters_by_endpoint<A>(admin: &A, scan: i64) -> Result<Vec<EndpointDeadLetters>>
+where
+    A: JobAdmin + ?Sized,
+{
+    const PAGE: i64 = 100;
+
+    let mut endpoints: BTreeMap<String, Vec<DeadLetterJob>> = BTreeMap::new();
+    let mut offset = 0;
+    while offset < scan {
+        let page = admin.dead_letters(PAGE.min(scan - offset), offset).await?;
+        let last_page = (page.len() as i64) < PAGE.min(scan - offset);
+        offset += page.len() as i64;
+
+        for job in page {
+            if job.job_type != WEBHOOK_JOB_TYPE {
+                continue;
+            }
+            let Some(endpoint) = job.payload["endpoint"].as_str() else {
+                continue;
+            };
+            endpoints.entry(endpoint.to_string()).or_default().push(job);
+        }
+        if last_page {
+            break;
+        }
+    }
+
+    Ok(endpoints
+        .into_iter()
+        .map(|(endpoint, jobs)| EndpointDeadLetters { endpoint, jobs })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use seesaw_core::{JobTypeStats, QueueStats};
+
+    use super::*;
+
+    struct DeadLetters(Vec<DeadLetterJob>);
+
+    #[async_trait]
+    impl JobAdmin for DeadLetters {
+        async fn stats(&self) -> Result<QueueStats> {
+            Ok(QueueStats::default())
+        }
+
+        async fn job_type_stats(&self) -> Result<Vec<JobTypeStats>> {
+            Ok(Vec::new())
+        }
+
+        async fn dead_letters(&self, limit: i64, offset: i64) -> Result<Vec<DeadLetterJob>> {
+            Ok(self
+                .0
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn retry_dead_letter(&self, _job_id: Uuid) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn cancel(&self, _job_id: Uuid) -> Result<bool> {
+            Ok(false)
+        }
+    }
+
+    fn dead_letter(job_type: &str, payload: serde_json::Value) -> DeadLetterJob {
+        DeadLetterJob {
+            id: Uuid::new_v4(),
+            job_type: job_type.to_string(),
+            payload,
+            version: 1,
+            attempt: 13,
+            error_message: Some("POST failed with status 503".into()),
+            error_kind: Some("retryable".into()),
+            failed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_webhook_command_is_an_idempotent_background_job() {
+        let command =
+            WebhookCommand::new("acme", "https://acme.test/hooks", "order.shipped", json())
+                .with_max_retries(3);
+        let spec = command.job_spec().unwrap();
+
+        assert_eq!(command.execution_mode(), ExecutionMode::Background);
+        assert_eq!(spec.job_type, WEBHOOK_JOB_TYPE);
+        assert_eq!(spec.max_retries, 3);
+        assert_eq!(
+            spec.idempotency_key,
+            Some(format!("webhook:{}", command.delivery_id))
+        );
+        assert_eq!(command.serialize_to_json().unwrap()["endpoint"], "acme");
+    }
+
+    #[tokio::test]
+    async fn test_groups_webhook_dead_letters_by_endpoint() {
+        let webhook = |endpoint| {
+            let command =
+                WebhookCommand::new(endpoint, "https://example.test", "order.shipped", json());
+            dead_letter(WEBHOOK_JOB_TYPE, command.serialize_to_json().unwrap())
+        };
+        let admin = DeadLetters(vec![
+            webhook("beta"),
+            dead_letter("email:send", json()),
+            webhook("acme"),
+            webhook("beta"),
+        ]);
+
+        let endpoints = dead_letters_by_endpoint(&admin, 1_000).await.unwrap();
+
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].endpoint, "acme");
+        assert_eq!(endpoints[0].jobs.len(), 1);
+        assert_eq!(endpoints[1].endpoint, "beta");
+        assert_eq!(endpoints[1].jobs.len(), 2);
+    }
+
+    fn json() -> serde_json::Value {
+        serde_json::json!({ "order_id": 7 })
+    }
+}
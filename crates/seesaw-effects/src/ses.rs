@@ -0,0 +1,254 @@
+//! Email through the Amazon SES v2 API.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::email::{Mailer, SendEmail};
+use crate::http::send;
+
+/// AWS access keys for signing requests.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Long-lived access keys.
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    /// Temporary credentials: the keys plus their session token.
+    pub fn with_session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    /// Read `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and, if set,
+    /// `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self> {
+        let credentials = Self::new(
+            std::env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+            std::env::var("AWS_SECRET_ACCESS_KEY").context("AWS_SECRET_ACCESS_KEY is not set")?,
+        );
+        Ok(match std::env::var("AWS_SESSION_TOKEN") {
+            Ok(token) => credentials.with_session_token(token),
+            Err(_) => credentials,
+        })
+    }
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sends email with the SES v2 `SendEmail` action.
+///
+/// Requests are signed with AWS Signature Version 4. Credentials are fixed
+/// when the mailer is created; temporary credentials must be replaced by
+/// creating a new mailer before they expire.
+#[derive(Debug, Clone)]
+pub struct SesMailer {
+    client: reqwest::Client,
+    region: String,
+    credentials: AwsCredentials,
+}
+
+impl SesMailer {
+    /// Send through SES in `region`.
+    pub fn new(region: impl Into<String>, credentials: AwsCredentials) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            region: region.into(),
+            credentials,
+        }
+    }
+
+    /// Send through SES, configured from `AWS_REGION` or
+    /// `AWS_DEFAULT_REGION` and [`AwsCredentials::from_env`].
+    pub fn from_env() -> Result<Self> {
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .context("AWS_REGION is not set")?;
+        Ok(Self::new(region, AwsCredentials::from_env()?))
+    }
+
+    /// Send requests with `client`, e.g. one with custom timeouts.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Mailer for SesMailer {
+    async fn send(&self, email: &SendEmail) -> Result<Option<String>> {
+        let url: Url = format!(
+            "https://email.{}.amazonaws.com/v2/email/outbound-emails",
+            self.region
+        )
+        .parse()?;
+        let body = serde_json::to_vec(&request_body(email))?;
+        let headers = sign(&url, &body, Utc::now(), &self.region, &self.credentials);
+
+        let mut request = self
+            .client
+            .post(url.clone())
+            .header("content-type", "application/json")
+            .body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = send(request, "POST", url.as_str()).await?;
+        let response: serde_json::Value = serde_json::from_slice(&response.bytes().await?)?;
+        Ok(response["MessageId"].as_str().map(String::from))
+    }
+}
+
+/// The `SendEmail` request for `email`.
+fn request_body(email: &SendEmail) -> serde_json::Value {
+    let mut body = serde_json::Map::new();
+    if let Some(text) = &email.text {
+        body.insert("Text".into(), json!({ "Data": text, "Charset": "UTF-8" }));
+    }
+    if let Some(html) = &email.html {
+        body.insert("Html".into(), json!({ "Data": html, "Charset": "UTF-8" }));
+    }
+    json!({
+        "FromEmailAddress": email.from,
+        "Destination": { "ToAddresses": email.to },
+        "ReplyToAddresses": email.reply_to.iter().collect::<Vec<_>>(),
+        "Content": {
+            "Simple": {
+                "Subject": { "Data": email.subject, "Charset": "UTF-8" },
+                "Body": body,
+            }
+        }
+    })
+}
+
+/// The headers that sign a `POST` with AWS Signature Version 4, apart from
+/// `host`, which the client sets from the URL.
+fn sign(
+    url: &Url,
+    body: &[u8],
+    now: DateTime<Utc>,
+    region: &str,
+    credentials: &AwsCredentials,
+) -> Vec<(&'static str, String)> {
+    let payload_hash = hex::encode(Sha256::digest(body));
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+
+    // Canonical headers must be sorted by name
+    let mut headers = vec![
+        ("host", url.host_str().unwrap_or_default().to_string()),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "POST\n{}\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        url.path(),
+    );
+
+    let scope = format!("{date}/{region}/ses/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut key = hmac(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [region, "ses", "aws4_request"] {
+        key = hmac(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    headers.remove(0);
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    headers
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_body_includes_only_given_parts() {
+        let email = SendEmail::new("noreply@acme.com", "ada@example.com", "Welcome")
+            .with_html("<p>Hello Ada</p>");
+
+        let body = request_body(&email);
+
+        assert_eq!(body["Destination"]["ToAddresses"][0], "ada@example.com");
+        assert_eq!(body["ReplyToAddresses"], json!([]));
+        assert_eq!(
+            body["Content"]["Simple"]["Body"],
+            json!({ "Html": { "Data": "<p>Hello Ada</p>", "Charset": "UTF-8" } })
+        );
+    }
+
+    #[test]
+    fn test_signs_with_ses_scope() {
+        let url = "https://email.eu-west-1.amazonaws.com/v2/email/outbound-emails"
+            .parse()
+            .unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let headers = sign(
+            &url,
+            b"{}",
+            now,
+            "eu-west-1",
+            &AwsCredentials::new("AKIDEXAMPLE", "secret"),
+        );
+
+        let authorization = &headers.last().unwrap().1;
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240501/eu-west-1/ses/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert_eq!(headers[1], ("x-amz-date", "20240501T120000Z".to_string()));
+    }
+}
@@ -0,0 +1,85 @@
+//! Email through an SMTP relay.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::email::{Mailer, SendEmail};
+
+/// Sends email through an SMTP relay, over TLS.
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    /// Send through a configured `transport`, e.g. one without TLS for a
+    /// local test server.
+    pub fn new(transport: AsyncSmtpTransport<Tokio1Executor>) -> Self {
+        Self { transport }
+    }
+
+    /// Send through `host` on the submission port with implicit TLS,
+    /// authenticating as `username`.
+    pub fn relay(
+        host: &str,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(host)?
+            .credentials(Credentials::new(username.into(), password.into()))
+            .build();
+        Ok(Self::new(transport))
+    }
+}
+
+impl std::fmt::Debug for SmtpMailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpMailer").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &SendEmail) -> Result<Option<String>> {
+        let message = message(email)?;
+        let message_id = message
+            .headers()
+            .get_raw("Message-ID")
+            .map(|id| id.to_string());
+        self.transport.send(message).await?;
+        Ok(message_id)
+    }
+}
+
+/// Build the MIME message for `email`, with a generated `Message-ID`.
+fn message(email: &SendEmail) -> Result<Message> {
+    let mut builder = Message::builder()
+        .from(parse_mailbox(&email.from)?)
+        .subject(&email.subject)
+        .message_id(None);
+    for to in &email.to {
+        builder = builder.to(parse_mailbox(to)?);
+    }
+    if let Some(reply_to) = &email.reply_to {
+        builder = builder.reply_to(parse_mailbox(reply_to)?);
+    }
+
+    let message = match (&email.text, &email.html) {
+        (Some(text), Some(html)) => {
+            builder.multipart(MultiPart::alternative_plain_html(text.clone(), html.clone()))
+        }
+        (Some(text), None) => builder.singlepart(SinglePart::plain(text.clone())),
+        (None, Some(html)) => builder.singlepart(SinglePart::html(html.clone())),
+        (None, None) => anyhow::bail!("email has neither a text nor an HTML body"),
+    };
+    Ok(message?)
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .with_context(|| format!("invalid email address {address:?}"))
+}
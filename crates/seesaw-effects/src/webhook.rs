@@ -0,0 +1,286 @@
+//! Delivering webhooks signed with HMAC-SHA256.
+//!
+//! Each delivery is a JSON `POST` carrying three headers:
+//!
+//! | Header | Value |
+//! |--------|-------|
+//! | `Seesaw-Event` | The event type, e.g. `order.shipped` |
+//! | `Seesaw-Delivery` | The delivery ID, the same on every attempt |
+//! | `Seesaw-Signature` | `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">` |
+//!
+//! Receivers recompute the signature with the shared secret, reject
+//! timestamps too far from their clock to stop replays, and deduplicate on
+//! the delivery ID, since a delivery whose response was lost is sent again.
+//! [`WebhookSigner::verify`] does the first two for Rust receivers.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use seesaw_core::{Command, Effect, EffectContext, FromDeps, RetryPolicy};
+
+use crate::http::{is_retryable, send};
+use crate::retrying;
+
+/// Header carrying the event type.
+pub const EVENT_HEADER: &str = "Seesaw-Event";
+
+/// Header carrying the delivery ID.
+pub const DELIVERY_HEADER: &str = "Seesaw-Delivery";
+
+/// Header carrying the timestamped signature.
+pub const SIGNATURE_HEADER: &str = "Seesaw-Signature";
+
+/// Deliver `payload` to `url` as a signed webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    /// Identifies the delivery to the receiver across attempts.
+    pub delivery_id: Uuid,
+    /// Endpoint URL.
+    pub url: String,
+    /// Event type, e.g. `"order.shipped"`.
+    pub event_type: String,
+    /// JSON body.
+    pub payload: serde_json::Value,
+}
+
+impl DeliverWebhook {
+    /// A delivery of `payload` to `url` with a fresh delivery ID.
+    pub fn new(
+        url: impl Into<String>,
+        event_type: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            delivery_id: Uuid::new_v4(),
+            url: url.into(),
+            event_type: event_type.into(),
+            payload,
+        }
+    }
+}
+
+impl Command for DeliverWebhook {}
+
+/// A [`DeliverWebhook`] was answered with a `2xx` status.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivered {
+    /// The delivery ID.
+    pub delivery_id: Uuid,
+    /// Endpoint URL.
+    pub url: String,
+    /// Response status.
+    pub status: u16,
+    /// Attempts the delivery took, including the successful one.
+    pub attempts: u32,
+}
+
+/// Signs and verifies webhook bodies with a shared secret.
+#[derive(Clone)]
+pub struct WebhookSigner {
+    secret: Arc<[u8]>,
+}
+
+impl WebhookSigner {
+    /// Sign with `secret`.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().into(),
+        }
+    }
+
+    /// The [`SIGNATURE_HEADER`] value for `body` sent at `timestamp` (unix
+    /// seconds).
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        format!(
+            "t={timestamp},v1={}",
+            hex::encode(self.mac(timestamp, body).finalize().into_bytes())
+        )
+    }
+
+    /// Whether `header` is a valid signature of `body` made at most
+    /// `tolerance` away from now.
+    pub fn verify(&self, header: &str, body: &[u8], tolerance: Duration) -> bool {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                Some(("v1", v1)) => signatures.extend(hex::decode(v1).ok()),
+                _ => {}
+            }
+        }
+        let Some(timestamp) = timestamp else {
+            return false;
+        };
+        if Utc::now().timestamp().abs_diff(timestamp) > tolerance.as_secs() {
+            return false;
+        }
+        // Several v1 entries let senders rotate secrets
+        signatures
+            .iter()
+            .any(|signature| self.mac(timestamp, body).verify_slice(signature).is_ok())
+    }
+
+    fn mac(&self, timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac
+    }
+}
+
+impl std::fmt::Debug for WebhookSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSigner").finish_non_exhaustive()
+    }
+}
+
+/// Policy deliveries retry with unless configured otherwise: 5 attempts,
+/// 1s initial backoff doubling up to 30s, for
+/// [`is_retryable`](crate::is_retryable) errors.
+pub(crate) fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .with_max_attempts(5)
+        .with_initial_backoff(Duration::from_secs(1))
+        .with_max_backoff(Duration::from_secs(30))
+        .with_retry_if(is_retryable)
+}
+
+/// Executes [`DeliverWebhook`] with the deps' `reqwest::Client` and
+/// [`WebhookSigner`].
+///
+/// Every attempt is signed afresh, so its timestamp stays current however
+/// long the retries take. A receiver still failing after the last attempt
+/// fails the command with an [`HttpStatusError`](crate::HttpStatusError).
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryEffect {
+    policy: RetryPolicy,
+}
+
+impl WebhookDeliveryEffect {
+    /// Deliver with the default retry schedule.
+    pub fn new() -> Self {
+        Self {
+            policy: default_retry_policy(),
+        }
+    }
+
+    /// Retry under `policy` instead.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl Default for WebhookDeliveryEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<D> Effect<DeliverWebhook, D> for WebhookDeliveryEffect
+where
+    D: Send + Sync + 'static,
+    reqwest::Client: FromDeps<D>,
+    WebhookSigner: FromDeps<D>,
+{
+    type Event = WebhookDelivered;
+
+    async fn execute(
+        &self,
+        command: DeliverWebhook,
+        ctx: EffectContext<D>,
+    ) -> Result<WebhookDelivered> {
+        let client = ctx.get::<reqwest::Client>();
+        let signer = ctx.get::<WebhookSigner>();
+        let body = serde_json::to_vec(&command.payload)?;
+        let delivery_id = command.delivery_id.to_string();
+
+        let (response, attempts) = retrying(&self.policy, "deliver_webhook", || {
+            let request = signed_post(
+                &client,
+                &signer,
+                &command.url,
+                &command.event_type,
+                &delivery_id,
+                &body,
+            );
+            send(request, "POST", &command.url)
+        })
+        .await?;
+
+        Ok(WebhookDelivered {
+            delivery_id: command.delivery_id,
+            url: command.url,
+            status: response.status().as_u16(),
+            attempts,
+        })
+    }
+}
+
+/// A `POST` of `body` to `url` carrying the delivery headers, signed now.
+pub(crate) fn signed_post(
+    client: &reqwest::Client,
+    signer: &WebhookSigner,
+    url: &str,
+    event_type: &str,
+    delivery_id: &str,
+    body: &[u8],
+) -> reqwest::RequestBuilder {
+    client
+        .post(url)
+        .header("content-type", "application/json")
+        .header(EVENT_HEADER, event_type)
+        .header(DELIVERY_HEADER, delivery_id)
+        .header(SIGNATURE_HEADER, signer.sign(Utc::now().timestamp(), body))
+        .body(body.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn test_signs_timestamp_and_body() {
+        let signer = WebhookSigner::new("whsec_test");
+
+        // echo -n '1700000000.{"id":1}' | openssl dgst -sha256 -hmac whsec_test
+        assert_eq!(
+            signer.sign(1_700_000_000, br#"{"id":1}"#),
+            "t=1700000000,v1=2f441ba4b3b2d50d28a9ab9d9fd8880376ecd1eb5d0435401553f5d8d0a5dcf8"
+        );
+    }
+
+    #[test]
+    fn test_verifies_own_signature() {
+        let signer = WebhookSigner::new("whsec_test");
+        let body = br#"{"id":1}"#;
+        let header = signer.sign(Utc::now().timestamp(), body);
+
+        assert!(signer.verify(&header, body, TOLERANCE));
+        assert!(!signer.verify(&header, br#"{"id":2}"#, TOLERANCE));
+        assert!(!WebhookSigner::new("other").verify(&header, body, TOLERANCE));
+    }
+
+    #[test]
+    fn test_rejects_stale_signature() {
+        let signer = WebhookSigner::new("whsec_test");
+        let body = br#"{"id":1}"#;
+        let header = signer.sign(Utc::now().timestamp() - 600, body);
+
+        assert!(!signer.verify(&header, body, TOLERANCE));
+    }
+}
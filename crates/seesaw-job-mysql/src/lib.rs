@@ -0,0 +1,630 @@
+//! MySQL/MariaDB implementation of Seesaw job queue.
+//!
+//! This crate provides a MySQL implementation of the `JobStore` trait from
+//! the Seesaw framework, behaving like `seesaw-job-postgres`: the same
+//! statuses, retry backoff and dead-lettering (both share
+//! `seesaw-job-sql-core`), and the same lease semantics.
+//!
+//! Requires MySQL 8.0+ or MariaDB 10.6+ for `FOR UPDATE SKIP LOCKED`.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE jobs (
+//!     id BINARY(16) PRIMARY KEY,
+//!     job_type VARCHAR(255) NOT NULL,
+//!     payload JSON NOT NULL,
+//!     version INT NOT NULL DEFAULT 1,
+//!
+//!     -- Execution
+//!     status ENUM('pending', 'running', 'succeeded', 'failed', 'dead_letter')
+//!         NOT NULL DEFAULT 'pending',
+//!     attempt INT NOT NULL DEFAULT 1,
+//!     max_retries INT NOT NULL DEFAULT 3,
+//!
+//!     -- Scheduling
+//!     priority INT NOT NULL DEFAULT 0,
+//!     run_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+//!
+//!     -- Worker tracking
+//!     worker_id VARCHAR(255),
+//!     lease_expires_at DATETIME(6),
+//!     lease_token BINARY(16),
+//!     lease_ms BIGINT,
+//!
+//!     -- Error tracking
+//!     error_message TEXT,
+//!     error_kind ENUM('retryable', 'non_retryable'),
+//!
+//!     -- Timestamps
+//!     created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+//!     updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+//!
+//!     INDEX idx_jobs_ready (status, priority, run_at),
+//!     INDEX idx_jobs_lease (status, lease_expires_at)
+//! );
+//! ```
+//!
+//! Every claim writes a fresh `lease_token`, and heartbeats and outcomes
+//! only apply while it still matches, failing with
+//! [`LeaseLost`](seesaw_core::LeaseLost) otherwise. Tables created before
+//! fencing need `ALTER TABLE jobs ADD COLUMN lease_token BINARY(16);`.
+//!
+//! A job with a `lease_ms` (from `JobSpec::with_lease_ms`) is leased for
+//! that long on claim and on every heartbeat; jobs without one get the
+//! store's lease timeout. Older tables need
+//! `ALTER TABLE jobs ADD COLUMN lease_ms BIGINT;`.
+//!
+//! Timestamps are UTC: sqlx sets each connection's `time_zone` to `+00:00`,
+//! so keep that if you configure the pool yourself.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use seesaw_job_mysql::MySqlJobStore;
+//! use sqlx::MySqlPool;
+//!
+//! let pool = MySqlPool::connect("mysql://localhost/mydb").await?;
+//! let store = MySqlJobStore::new(pool);
+//!
+//! // Use with seesaw dispatcher
+//! let dispatcher = Dispatcher::with_job_queue(deps, bus, Arc::new(store));
+//! ```
+//!
+//! MySQL has no `LISTEN`/`NOTIFY`, so `claim_stream` polls every
+//! `CLAIM_POLL_INTERVAL` while the queue is empty, or sooner when a
+//! scheduled job or retry comes due before then.
+//!
+//! # Integration Tests
+//!
+//! With the `testkit` feature, `testkit::MySqlTestDb` starts a MySQL
+//! container with the schema above, with the same helpers as
+//! `seesaw-job-postgres`'s `PgTestDb`.
+
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use seesaw_core::job::{
+    ClaimedJob, DeadLetterJob, FailureKind, JobAdmin, JobLease, JobStore, JobTypeStats, LeaseLost,
+    LookaheadClaim, UpcomingJob,
+};
+use seesaw_job_sql_core::{
+    error_kind_label, failure_outcome, lease_expiry, lookahead_until, ClaimQuery, FailureOutcome,
+    JobQueries, SqlDialect, DEFAULT_LEASE_MS,
+};
+use sqlx::{MySqlPool, Row};
+use uuid::Uuid;
+
+pub use seesaw_job_sql_core::QueueStats;
+
+#[cfg(feature = "testkit")]
+pub mod testkit;
+
+/// MySQL job store implementation.
+#[derive(Clone)]
+pub struct MySqlJobStore {
+    pool: MySqlPool,
+    default_lease_ms: i64,
+}
+
+impl MySqlJobStore {
+    /// Create a new MySQL job store.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - MySQL connection pool
+    ///
+    /// # Default Settings
+    ///
+    /// - Lease timeout: 60 seconds
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            default_lease_ms: DEFAULT_LEASE_MS,
+        }
+    }
+
+    /// Create a job store with custom lease timeout.
+    ///
+    /// The lease timeout determines how long a worker can hold a job
+    /// before it's considered abandoned, for jobs without their own
+    /// `lease_ms`.
+    pub fn with_lease_timeout(pool: MySqlPool, lease_ms: i64) -> Self {
+        Self {
+            pool,
+            default_lease_ms: lease_ms,
+        }
+    }
+
+    /// Get the underlying connection pool.
+    pub fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl JobStore for MySqlJobStore {
+    /// Claim ready jobs for execution.
+    ///
+    /// The ready rows are locked with `FOR UPDATE SKIP LOCKED` and marked
+    /// running in one transaction.
+    async fn claim_ready(&self, worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+        let ClaimQuery::LockThenUpdate { select } = &QUERIES.claim_ready else {
+            unreachable!("MySqlDialect claims with a locking select");
+        };
+        let lease_token = Uuid::new_v4();
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(select).bind(limit).fetch_all(&mut *tx).await?;
+
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let jobs: Vec<ClaimedJob> = rows
+            .iter()
+            .map(|row| ClaimedJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                lease_token,
+            })
+            .collect();
+
+        let query = QUERIES.mark_running(jobs.len());
+        let mut update = sqlx::query(&query)
+            .bind(worker_id)
+            .bind(lease_expiry(Utc::now(), self.default_lease_ms))
+            .bind(lease_token);
+        for job in &jobs {
+            update = update.bind(job.id);
+        }
+        update.execute(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok(jobs)
+    }
+
+    /// Claim ready jobs, and list those due within `lookahead`.
+    ///
+    /// The listing is skipped when `limit` jobs were claimed.
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: std::time::Duration,
+    ) -> Result<LookaheadClaim> {
+        let jobs = self.claim_ready(worker_id, limit).await?;
+        if jobs.len() as i64 >= limit {
+            return Ok(LookaheadClaim {
+                jobs,
+                upcoming: Vec::new(),
+            });
+        }
+
+        let rows = sqlx::query(&QUERIES.upcoming)
+            .bind(lookahead_until(Utc::now(), lookahead))
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        let upcoming = rows
+            .iter()
+            .map(|row| UpcomingJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                run_at: row.get("run_at"),
+            })
+            .collect();
+
+        Ok(LookaheadClaim { jobs, upcoming })
+    }
+
+    /// Mark a job as successfully completed.
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+        let result = sqlx::query(&QUERIES.mark_succeeded)
+            .bind(lease.job_id)
+            .bind(lease.token)
+            .execute(&self.pool)
+            .await?;
+
+        fenced(lease, result.rows_affected())
+    }
+
+    /// Mark a job as failed.
+    ///
+    /// # Retry Logic
+    ///
+    /// - Retryable failures: Exponential backoff (2^attempt seconds, max 1 hour)
+    /// - Non-retryable failures: Immediately moves to dead letter
+    /// - Max retries exceeded: Moves to dead letter
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+        self.record_failure(lease, error, kind).await?;
+        Ok(())
+    }
+
+    /// Mark a job as failed, returning whether it was dead-lettered.
+    async fn record_failure(
+        &self,
+        lease: JobLease,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<bool> {
+        let job_id = lease.job_id;
+        let mut tx = self.pool.begin().await?;
+
+        // Fetch current job state
+        let job = sqlx::query(&QUERIES.lock_for_failure)
+            .bind(job_id)
+            .bind(lease.token)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(LeaseLost { job_id })?;
+
+        let attempt: i32 = job.get("attempt");
+        let max_retries: i32 = job.get("max_retries");
+
+        let dead_lettered = match failure_outcome(kind, attempt, max_retries, Utc::now()) {
+            FailureOutcome::Retry { run_at } => {
+                sqlx::query(&QUERIES.retry)
+                    .bind(run_at)
+                    .bind(error)
+                    .bind(job_id)
+                    .execute(&mut *tx)
+                    .await?;
+                false
+            }
+            FailureOutcome::DeadLetter => {
+                sqlx::query(&QUERIES.dead_letter)
+                    .bind(error)
+                    .bind(error_kind_label(kind))
+                    .bind(job_id)
+                    .execute(&mut *tx)
+                    .await?;
+                true
+            }
+        };
+
+        tx.commit().await?;
+        Ok(dead_lettered)
+    }
+
+    /// Running jobs claimed by `worker_id`, with their current lease tokens.
+    async fn running_jobs(&self, worker_id: &str) -> Result<Vec<ClaimedJob>> {
+        let rows = sqlx::query(&QUERIES.running_jobs)
+            .bind(worker_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ClaimedJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                lease_token: row.get("lease_token"),
+            })
+            .collect())
+    }
+
+    /// Extend the lease for a running job.
+    ///
+    /// Workers should call this periodically for long-running jobs
+    /// to prevent them from being reclaimed.
+    async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+        let result = sqlx::query(&QUERIES.heartbeat)
+            .bind(lease_expiry(Utc::now(), self.default_lease_ms))
+            .bind(lease.job_id)
+            .bind(lease.token)
+            .execute(&self.pool)
+            .await?;
+
+        fenced(lease, result.rows_affected())
+    }
+}
+
+/// Fail with [`LeaseLost`] if a statement fenced by `lease` changed no row.
+fn fenced(lease: JobLease, rows_affected: u64) -> Result<()> {
+    if rows_affected == 0 {
+        return Err(LeaseLost {
+            job_id: lease.job_id,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl MySqlJobStore {
+    /// Reclaim abandoned jobs (lease expired).
+    ///
+    /// This should be run periodically by a maintenance worker.
+    pub async fn reclaim_expired(&self) -> Result<u64> {
+        let result = sqlx::query(&QUERIES.reclaim_expired)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Clean up old completed jobs.
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - Delete jobs completed before this timestamp
+    pub async fn cleanup_succeeded(&self, older_than: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(&QUERIES.cleanup_succeeded)
+            .bind(older_than)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get statistics about job queue health.
+    pub async fn stats(&self) -> Result<QueueStats> {
+        let row = sqlx::query(&QUERIES.stats).fetch_one(&self.pool).await?;
+
+        Ok(queue_stats(&row))
+    }
+}
+
+#[async_trait]
+impl JobAdmin for MySqlJobStore {
+    async fn stats(&self) -> Result<QueueStats> {
+        MySqlJobStore::stats(self).await
+    }
+
+    async fn job_type_stats(&self) -> Result<Vec<JobTypeStats>> {
+        let rows = sqlx::query(&QUERIES.job_type_stats)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| JobTypeStats {
+                job_type: row.get("job_type"),
+                stats: queue_stats(row),
+                retrying: row.get("retrying"),
+            })
+            .collect())
+    }
+
+    async fn dead_letters(&self, limit: i64, offset: i64) -> Result<Vec<DeadLetterJob>> {
+        let rows = sqlx::query(&QUERIES.dead_letters)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| DeadLetterJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                error_message: row.get("error_message"),
+                error_kind: row.get("error_kind"),
+                failed_at: row.get("failed_at"),
+            })
+            .collect())
+    }
+
+    async fn retry_dead_letter(&self, job_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(&QUERIES.retry_dead_letter)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn cancel(&self, job_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(&QUERIES.cancel)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Read the status counts of a `stats` or `job_type_stats` row.
+fn queue_stats(row: &sqlx::mysql::MySqlRow) -> QueueStats {
+    QueueStats {
+        pending: row.get("pending"),
+        running: row.get("running"),
+        succeeded: row.get("succeeded"),
+        failed: row.get("failed"),
+        dead_letter: row.get("dead_letter"),
+    }
+}
+
+/// SQL syntax of MySQL and MariaDB.
+///
+/// Claims use the default locking select, as MySQL has no
+/// `UPDATE ... RETURNING`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MySqlDialect;
+
+impl SqlDialect for MySqlDialect {
+    fn param(&self, _n: usize) -> String {
+        "?".to_string()
+    }
+
+    fn now(&self) -> &'static str {
+        "NOW(6)"
+    }
+
+    fn add_millis(&self, time: &str, millis: &str) -> String {
+        format!("{time} + INTERVAL ({millis} * 1000) MICROSECOND")
+    }
+}
+
+static QUERIES: LazyLock<JobQueries<MySqlDialect>> =
+    LazyLock::new(|| JobQueries::new(MySqlDialect));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_millis_scales_to_microseconds() {
+        assert_eq!(
+            MySqlDialect.add_millis("NOW(6)", "lease_ms"),
+            "NOW(6) + INTERVAL (lease_ms * 1000) MICROSECOND"
+        );
+    }
+
+    #[test]
+    fn test_queries_bind_positionally_in_documented_order() {
+        // `?` binds by position, so the COALESCE default must come before
+        // the WHERE clause: lease expiry, job ID, lease token.
+        assert_eq!(
+            QUERIES.heartbeat,
+            "UPDATE jobs SET lease_expires_at = \
+             COALESCE(NOW(6) + INTERVAL (lease_ms * 1000) MICROSECOND, ?), updated_at = NOW(6) \
+             WHERE id = ? AND lease_token = ? AND status = 'running'"
+        );
+        // Worker ID, lease expiry, lease token, then each job ID.
+        assert_eq!(
+            QUERIES.mark_running(2),
+            "UPDATE jobs SET status = 'running', worker_id = ?, lease_expires_at = \
+             COALESCE(NOW(6) + INTERVAL (lease_ms * 1000) MICROSECOND, ?), \
+             lease_token = ?, updated_at = NOW(6) WHERE id IN (?, ?)"
+        );
+        assert!(QUERIES
+            .upcoming
+            .ends_with("run_at > NOW(6) AND run_at <= ? ORDER BY run_at ASC LIMIT ?"));
+        assert!(QUERIES.dead_letter.contains("error_message = ?, error_kind = ?,"));
+        match &QUERIES.claim_ready {
+            ClaimQuery::LockThenUpdate { select } => {
+                assert!(select.contains("run_at <= NOW(6)"));
+                assert!(select.ends_with("LIMIT ? FOR UPDATE SKIP LOCKED"));
+            }
+            other => panic!("expected a locking select, got {other:?}"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod mysql_tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::{MySqlTestDb, TestJob};
+
+    #[tokio::test]
+    async fn test_claim_takes_highest_priority_first() -> Result<()> {
+        let db = MySqlTestDb::start().await?;
+        let store = db.store();
+        let low = db.enqueue_test_job("report:build", json!({ "n": 1 })).await?;
+        let high = db
+            .enqueue(TestJob::new("report:build", json!({ "n": 2 })).with_priority(10))
+            .await?;
+        db.enqueue(TestJob::new("report:build", json!({})).with_delay(Duration::from_secs(60)))
+            .await?;
+
+        let first = store.claim_ready("worker-1", 1).await?;
+        let second = store.claim_ready("worker-1", 5).await?;
+
+        assert_eq!(first.iter().map(|j| j.id).collect::<Vec<_>>(), [high]);
+        assert_eq!(second.iter().map(|j| j.id).collect::<Vec<_>>(), [low]);
+        assert_eq!(first[0].payload, json!({ "n": 2 }));
+        let running = store.running_jobs("worker-1").await?;
+        assert_eq!(running.len(), 2);
+        assert!(running.iter().all(|j| j.attempt == 1));
+        let job = db.job(high).await?.expect("claimed job");
+        assert_eq!((job.status.as_str(), job.worker_id.as_deref()), ("running", Some("worker-1")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retryable_failure_is_retried_after_backoff() -> Result<()> {
+        let db = MySqlTestDb::start().await?;
+        let store = db.store();
+        let id = db.enqueue_test_job("email:send", json!({})).await?;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+
+        let dead = store
+            .record_failure(claimed.lease(), "smtp timeout", FailureKind::Retryable)
+            .await?;
+
+        assert!(!dead);
+        let job = db.job(id).await?.expect("failed job");
+        assert_eq!((job.status.as_str(), job.attempt), ("pending", 2));
+        assert_eq!(job.error_kind.as_deref(), Some("retryable"));
+        assert!(store.claim_ready("worker-1", 1).await?.is_empty());
+
+        db.advance_leases(Duration::from_secs(3600)).await?;
+        let retried = store.claim_ready("worker-1", 1).await?.remove(0);
+        assert_eq!((retried.id, retried.attempt), (id, 2));
+        store.mark_succeeded(retried.lease()).await?;
+        assert_eq!(db.job(id).await?.expect("job").status, "succeeded");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_job_is_dead_lettered_and_retried_by_hand() -> Result<()> {
+        let db = MySqlTestDb::start().await?;
+        let store = db.store();
+        let id = db
+            .enqueue(TestJob::new("charge_card", json!({})).with_max_retries(1))
+            .await?;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+
+        store
+            .mark_failed(claimed.lease(), "card declined", FailureKind::Retryable)
+            .await?;
+
+        db.assert_dead_letter(id).await;
+        let dead = store.dead_letters(10, 0).await?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].error_message.as_deref(), Some("card declined"));
+        assert_eq!(dead[0].error_kind.as_deref(), Some("retryable"));
+        assert_eq!(MySqlJobStore::stats(&store).await?.dead_letter, 1);
+
+        assert!(store.retry_dead_letter(id).await?);
+        let retried = store.claim_ready("worker-1", 1).await?.remove(0);
+        assert_eq!((retried.id, retried.attempt), (id, 1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reclaimed_job_fences_out_the_old_lease() -> Result<()> {
+        let db = MySqlTestDb::start().await?;
+        let store = db.store();
+        let id = db.enqueue_test_job("video:encode", json!({})).await?;
+        let stale = store.claim_ready("worker-1", 1).await?.remove(0);
+
+        db.advance_leases(Duration::from_secs(120)).await?;
+        assert_eq!(store.reclaim_expired().await?, 1);
+        let current = store.claim_ready("worker-2", 1).await?.remove(0);
+        assert_eq!(current.id, id);
+        assert_ne!(current.lease_token, stale.lease_token);
+
+        for error in [
+            store.heartbeat(stale.lease()).await.unwrap_err(),
+            store.mark_succeeded(stale.lease()).await.unwrap_err(),
+            store
+                .mark_failed(stale.lease(), "late", FailureKind::Retryable)
+                .await
+                .unwrap_err(),
+        ] {
+            assert!(LeaseLost::is(&error), "{error:#}");
+        }
+        store.heartbeat(current.lease()).await?;
+        store.mark_succeeded(current.lease()).await?;
+        let job = db.job(id).await?.expect("job");
+        assert_eq!((job.status.as_str(), job.worker_id.as_deref()), ("succeeded", Some("worker-2")));
+        Ok(())
+    }
+}
@@ -0,0 +1,621 @@
+//! NATS JetStream implementation of Seesaw job queue.
+//!
+//! For teams that already run NATS, [`JetStreamJobStore`] keeps background
+//! jobs in a JetStream work-queue stream instead of a table in the OLTP
+//! database. It is both the [`JobQueue`] the dispatcher enqueues into and
+//! the [`JobStore`] workers claim from.
+//!
+//! # Mapping
+//!
+//! - **Enqueue**: one message per job on the jobs subject. The payload is
+//!   the command JSON; the job ID, type, version and retry limit travel in
+//!   headers. An idempotency key becomes the `Nats-Msg-Id`, so JetStream
+//!   drops duplicates within the stream's duplicate window; enqueueing a
+//!   duplicate returns the ID of the job already holding the key, or fails
+//!   if that job has finished and left the work queue
+//! - **Claim**: a fetch from a durable pull consumer. The lease is the
+//!   consumer's ack wait: a job whose worker dies is redelivered after it
+//! - **Heartbeat**: an in-progress ack, restarting the ack wait
+//! - **Success**: an ack, which removes the message from the work queue
+//! - **Retryable failure**: a nak with the same backoff as the SQL stores
+//!   (2^attempt seconds, max 1 hour)
+//! - **Dead letter**: the message is copied to the dead letter stream with
+//!   the error in headers, then terminated. This happens on a non-retryable
+//!   failure, when the retry limit is reached, and when a job is delivered
+//!   more often than its retry limit allows without ever being marked
+//!   failed (its workers kept dying)
+//!
+//! The attempt number is JetStream's delivery count, so redeliveries after a
+//! lost lease count as attempts. A scheduled job claimed before its time is
+//! nak'd until then, which takes one delivery that is not counted.
+//! Priorities are ignored: JetStream delivers in publish order.
+//!
+//! Acks are only possible on the connection that fetched the message, so a
+//! job must be finished by the same store instance that claimed it. Each
+//! delivery gets a fresh lease token: once a job is redelivered, the earlier
+//! claim's heartbeats and outcomes fail with [`LeaseLost`].
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use seesaw_job_nats::{JetStreamJobConfig, JetStreamJobStore};
+//!
+//! let client = async_nats::connect("nats://localhost:4222").await?;
+//! let store = Arc::new(
+//!     JetStreamJobStore::connect(async_nats::jetstream::new(client), JetStreamJobConfig::default())
+//!         .await?,
+//! );
+//!
+//! // Enqueue from the dispatcher, claim from a worker
+//! let dispatcher = Dispatcher::with_job_queue(deps, bus, store.clone());
+//! let worker = JobWorker::new(store, registry, dispatcher.clone());
+//! tokio::spawn(async move { worker.run().await });
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_nats::jetstream::consumer::pull;
+use async_nats::jetstream::consumer::PullConsumer;
+use async_nats::jetstream::message::AckKind;
+use async_nats::jetstream::stream::{self, RetentionPolicy};
+use async_nats::jetstream::{self, Context};
+use async_nats::HeaderMap;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use seesaw_core::job::{ClaimedJob, FailureKind, JobLease, JobStore, LeaseLost};
+use seesaw_core::{JobQueue, JobSpec};
+use seesaw_job_sql_core::{error_kind_label, failure_outcome, FailureOutcome, DEFAULT_LEASE_MS};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Header carrying the job ID.
+pub const JOB_ID_HEADER: &str = "Seesaw-Job-Id";
+
+/// Header carrying the job type.
+pub const JOB_TYPE_HEADER: &str = "Seesaw-Job-Type";
+
+/// Header carrying the payload version.
+pub const JOB_VERSION_HEADER: &str = "Seesaw-Job-Version";
+
+/// Header carrying the retry limit.
+pub const MAX_RETRIES_HEADER: &str = "Seesaw-Max-Retries";
+
+/// Header carrying the earliest run time of a scheduled job (RFC 3339).
+pub const RUN_AT_HEADER: &str = "Seesaw-Run-At";
+
+/// Header carrying the last error of a dead-lettered job.
+pub const ERROR_HEADER: &str = "Seesaw-Error";
+
+/// Header carrying the error kind of a dead-lettered job.
+pub const ERROR_KIND_HEADER: &str = "Seesaw-Error-Kind";
+
+/// Streams, subjects and consumer of a [`JetStreamJobStore`].
+#[derive(Debug, Clone)]
+pub struct JetStreamJobConfig {
+    stream: String,
+    subject: String,
+    dead_letter_stream: String,
+    dead_letter_subject: String,
+    consumer: String,
+    lease_timeout: Duration,
+}
+
+impl Default for JetStreamJobConfig {
+    /// Streams `SEESAW_JOBS` and `SEESAW_JOBS_DLQ` on subjects
+    /// `seesaw.jobs` and `seesaw.jobs.dlq`, consumer `seesaw-workers`, and
+    /// a 60 second lease.
+    fn default() -> Self {
+        Self {
+            stream: "SEESAW_JOBS".to_string(),
+            subject: "seesaw.jobs".to_string(),
+            dead_letter_stream: "SEESAW_JOBS_DLQ".to_string(),
+            dead_letter_subject: "seesaw.jobs.dlq".to_string(),
+            consumer: "seesaw-workers".to_string(),
+            lease_timeout: Duration::from_millis(DEFAULT_LEASE_MS as u64),
+        }
+    }
+}
+
+impl JetStreamJobConfig {
+    /// Set the work-queue stream and the subject jobs are published on.
+    pub fn with_stream(mut self, stream: impl Into<String>, subject: impl Into<String>) -> Self {
+        self.stream = stream.into();
+        self.subject = subject.into();
+        self
+    }
+
+    /// Set the dead letter stream and its subject.
+    ///
+    /// The subject must not be matched by the jobs stream's subject.
+    pub fn with_dead_letter_stream(
+        mut self,
+        stream: impl Into<String>,
+        subject: impl Into<String>,
+    ) -> Self {
+        self.dead_letter_stream = stream.into();
+        self.dead_letter_subject = subject.into();
+        self
+    }
+
+    /// Set the durable consumer workers share.
+    pub fn with_consumer(mut self, consumer: impl Into<String>) -> Self {
+        self.consumer = consumer.into();
+        self
+    }
+
+    /// Set how long a claim holds without a heartbeat (the consumer's ack
+    /// wait).
+    pub fn with_lease_timeout(mut self, lease_timeout: Duration) -> Self {
+        self.lease_timeout = lease_timeout;
+        self
+    }
+}
+
+/// A claimed job's message, kept for acking.
+struct Claim {
+    message: jetstream::Message,
+    token: Uuid,
+    attempt: i32,
+    max_retries: i32,
+}
+
+/// JetStream job queue and store.
+#[derive(Clone)]
+pub struct JetStreamJobStore {
+    context: Context,
+    consumer: PullConsumer,
+    config: JetStreamJobConfig,
+    /// Messages claimed through this store and not yet finished.
+    claims: Arc<Mutex<HashMap<Uuid, Claim>>>,
+}
+
+impl JetStreamJobStore {
+    /// Create (or reuse) the streams and the consumer, and return a store
+    /// on them.
+    pub async fn connect(context: Context, config: JetStreamJobConfig) -> Result<Self> {
+        let jobs = context
+            .get_or_create_stream(stream::Config {
+                name: config.stream.clone(),
+                subjects: vec![config.subject.clone()],
+                retention: RetentionPolicy::WorkQueue,
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("failed to create stream {}", config.stream))?;
+        context
+            .get_or_create_stream(stream::Config {
+                name: config.dead_letter_stream.clone(),
+                subjects: vec![config.dead_letter_subject.clone()],
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("failed to create stream {}", config.dead_letter_stream))?;
+        let consumer = jobs
+            .get_or_create_consumer(
+                &config.consumer,
+                pull::Config {
+                    durable_name: Some(config.consumer.clone()),
+                    ack_wait: config.lease_timeout,
+                    ..Default::default()
+                },
+            )
+            .await
+            .with_context(|| format!("failed to create consumer {}", config.consumer))?;
+
+        Ok(Self {
+            context,
+            consumer,
+            config,
+            claims: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Get the JetStream context.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    async fn publish(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        run_at: Option<DateTime<Utc>>,
+    ) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let mut headers = HeaderMap::new();
+        headers.insert(JOB_ID_HEADER, id.to_string().as_str());
+        headers.insert(JOB_TYPE_HEADER, spec.job_type);
+        headers.insert(JOB_VERSION_HEADER, spec.version.to_string().as_str());
+        headers.insert(MAX_RETRIES_HEADER, spec.max_retries.to_string().as_str());
+        if let Some(run_at) = run_at {
+            headers.insert(RUN_AT_HEADER, run_at.to_rfc3339().as_str());
+        }
+        if let Some(key) = &spec.idempotency_key {
+            headers.insert(async_nats::header::NATS_MESSAGE_ID, key.as_str());
+        }
+
+        let ack = self
+            .context
+            .publish_with_headers(
+                self.config.subject.clone(),
+                headers,
+                Bytes::from(serde_json::to_vec(&payload)?),
+            )
+            .await?
+            .await?;
+        if ack.duplicate {
+            return self.original_job_id(ack.sequence).await;
+        }
+        Ok(id)
+    }
+
+    /// The ID of the job in message `sequence` of the jobs stream, which a
+    /// publish was dropped as a duplicate of.
+    ///
+    /// Fails if the job has finished: the work queue removed its message.
+    async fn original_job_id(&self, sequence: u64) -> Result<Uuid> {
+        let original = self
+            .context
+            .get_stream(&self.config.stream)
+            .await?
+            .get_raw_message(sequence)
+            .await
+            .with_context(|| {
+                format!(
+                    "duplicate of message {sequence}, whose job has already finished \
+                     (its idempotency key is still in the duplicate window)"
+                )
+            })?;
+        Ok(header(Some(&original.headers), JOB_ID_HEADER)?.parse()?)
+    }
+
+    /// Copy a message to the dead letter stream, then stop its redelivery.
+    async fn dead_letter(
+        &self,
+        message: &jetstream::Message,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<()> {
+        let mut headers = message.headers.clone().unwrap_or_default();
+        // Header values cannot span lines
+        headers.insert(ERROR_HEADER, error.replace(['\r', '\n'], " ").as_str());
+        headers.insert(ERROR_KIND_HEADER, error_kind_label(kind));
+        self.context
+            .publish_with_headers(
+                self.config.dead_letter_subject.clone(),
+                headers,
+                message.payload.clone(),
+            )
+            .await?
+            .await?;
+        message
+            .ack_with(AckKind::Term)
+            .await
+            .map_err(anyhow::Error::from_boxed)
+    }
+
+    /// Remove the claim `lease` holds, leaving a newer claim in place.
+    fn take_claim(&self, lease: JobLease) -> Result<Claim> {
+        let mut claims = self.claims.lock().unwrap();
+        check_lease(claims.get(&lease.job_id).map(|claim| claim.token), lease)?;
+        Ok(claims.remove(&lease.job_id).expect("claim checked above"))
+    }
+}
+
+impl std::fmt::Debug for JetStreamJobStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JetStreamJobStore")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Fail unless `token`, the token of the job's current claim, is the one
+/// `lease` was issued with.
+fn check_lease(token: Option<Uuid>, lease: JobLease) -> Result<()> {
+    let job_id = lease.job_id;
+    match token {
+        Some(token) if token == lease.token => Ok(()),
+        Some(_) => Err(LeaseLost { job_id }.into()),
+        None => Err(anyhow!("job {job_id} is not claimed through this store")),
+    }
+}
+
+/// Read a header, failing if it is missing.
+fn header<'a>(headers: Option<&'a HeaderMap>, name: &str) -> Result<&'a str> {
+    headers
+        .and_then(|headers| headers.get(name))
+        .map(|value| value.as_str())
+        .ok_or_else(|| anyhow!("job message without {name} header"))
+}
+
+/// Read a claimed message into a job and its retry limit.
+fn parse_job(message: &async_nats::Message, delivered: i64) -> Result<(ClaimedJob, i32)> {
+    let headers = message.headers.as_ref();
+    let job = ClaimedJob {
+        id: header(headers, JOB_ID_HEADER)?.parse()?,
+        job_type: header(headers, JOB_TYPE_HEADER)?.to_string(),
+        payload: serde_json::from_slice(&message.payload)?,
+        version: header(headers, JOB_VERSION_HEADER)?.parse()?,
+        attempt: delivered as i32,
+        lease_token: Uuid::new_v4(),
+    };
+    Ok((job, header(headers, MAX_RETRIES_HEADER)?.parse()?))
+}
+
+/// What [`JetStreamJobStore::claim_ready`] does with a delivered message.
+#[derive(Debug)]
+enum Delivery {
+    /// Claim the job, which has the given retry limit.
+    Claim(ClaimedJob, i32),
+    /// The job is scheduled: nak it for this long.
+    Early(Duration),
+    /// Dead-letter the message with this error.
+    DeadLetter(String, FailureKind),
+}
+
+/// Decide what to do with `message`, delivered `delivered` times, at `now`.
+fn triage(message: &async_nats::Message, delivered: i64, now: DateTime<Utc>) -> Result<Delivery> {
+    let mut attempt = delivered;
+    if let Ok(run_at) = header(message.headers.as_ref(), RUN_AT_HEADER) {
+        let run_at = DateTime::parse_from_rfc3339(run_at)?.with_timezone(&Utc);
+        if run_at > now {
+            return Ok(Delivery::Early((run_at - now).to_std().unwrap_or_default()));
+        }
+        // The delivery that found it early does not count
+        if attempt > 1 {
+            attempt -= 1;
+        }
+    }
+
+    let (job, max_retries) = match parse_job(message, attempt) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            warn!(error = %e, "dead-lettering unreadable job message");
+            return Ok(Delivery::DeadLetter(format!("{e:#}"), FailureKind::NonRetryable));
+        }
+    };
+    if job.attempt > max_retries {
+        return Ok(Delivery::DeadLetter(
+            format!("job delivered {} times without finishing", job.attempt - 1),
+            FailureKind::Retryable,
+        ));
+    }
+    Ok(Delivery::Claim(job, max_retries))
+}
+
+#[async_trait]
+impl JobQueue for JetStreamJobStore {
+    async fn enqueue(&self, payload: serde_json::Value, spec: JobSpec) -> Result<Uuid> {
+        self.publish(payload, spec, None).await
+    }
+
+    async fn schedule(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        self.publish(payload, spec, Some(run_at)).await
+    }
+}
+
+#[async_trait]
+impl JobStore for JetStreamJobStore {
+    /// Fetch up to `limit` jobs from the consumer without waiting.
+    ///
+    /// Scheduled jobs that are not due yet are nak'd until their run time,
+    /// and jobs delivered more often than their retry limit are
+    /// dead-lettered; neither is returned. `worker_id` is not recorded:
+    /// JetStream tracks claims per consumer.
+    async fn claim_ready(&self, _worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+        let mut messages = self
+            .consumer
+            .fetch()
+            .max_messages(limit.max(1) as usize)
+            .messages()
+            .await
+            .map_err(|e| anyhow!(e))?;
+
+        let now = Utc::now();
+        let mut jobs = Vec::new();
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| anyhow!(e))?;
+            let delivered = message.info().map_err(anyhow::Error::from_boxed)?.delivered;
+
+            let (job, max_retries) = match triage(&message, delivered, now)? {
+                Delivery::Claim(job, max_retries) => (job, max_retries),
+                Delivery::Early(delay) => {
+                    message
+                        .ack_with(AckKind::Nak(Some(delay)))
+                        .await
+                        .map_err(anyhow::Error::from_boxed)?;
+                    continue;
+                }
+                Delivery::DeadLetter(error, kind) => {
+                    self.dead_letter(&message, &error, kind).await?;
+                    continue;
+                }
+            };
+
+            self.claims.lock().unwrap().insert(
+                job.id,
+                Claim {
+                    message,
+                    token: job.lease_token,
+                    attempt: job.attempt,
+                    max_retries,
+                },
+            );
+            jobs.push(job);
+        }
+        Ok(jobs)
+    }
+
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+        self.take_claim(lease)?
+            .message
+            .ack()
+            .await
+            .map_err(anyhow::Error::from_boxed)
+    }
+
+    /// Retry with a delayed nak, or dead-letter.
+    ///
+    /// Uses the same retry policy as the SQL stores.
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+        let claim = self.take_claim(lease)?;
+        let now = Utc::now();
+
+        match failure_outcome(kind, claim.attempt, claim.max_retries, now) {
+            FailureOutcome::Retry { run_at } => claim
+                .message
+                .ack_with(AckKind::Nak(Some((run_at - now).to_std()?)))
+                .await
+                .map_err(anyhow::Error::from_boxed),
+            FailureOutcome::DeadLetter => self.dead_letter(&claim.message, error, kind).await,
+        }
+    }
+
+    async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+        // Clone the ack handle out so the lock is not held across the await
+        let message = {
+            let claims = self.claims.lock().unwrap();
+            let claim = claims.get(&lease.job_id);
+            check_lease(claim.map(|claim| claim.token), lease)?;
+            claim.expect("claim checked above").message.clone()
+        };
+        message
+            .ack_with(AckKind::Progress)
+            .await
+            .map_err(anyhow::Error::from_boxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use serde_json::json;
+
+    use super::*;
+
+    const JOB_ID: &str = "9f6c1d1e-6a54-4a5f-b5d2-3c1e2f3a4b5c";
+
+    fn message(extra: &[(&str, &str)]) -> async_nats::Message {
+        let mut headers = HeaderMap::new();
+        headers.insert(JOB_ID_HEADER, JOB_ID);
+        headers.insert(JOB_TYPE_HEADER, "email:send");
+        headers.insert(JOB_VERSION_HEADER, "2");
+        headers.insert(MAX_RETRIES_HEADER, "3");
+        for (name, value) in extra {
+            headers.insert(*name, *value);
+        }
+        let payload = Bytes::from(json!({ "to": "a@example.com" }).to_string());
+        async_nats::Message {
+            subject: "seesaw.jobs".into(),
+            reply: None,
+            length: payload.len(),
+            payload,
+            headers: Some(headers),
+            status: None,
+            description: None,
+        }
+    }
+
+    fn claimed(delivery: Delivery) -> (ClaimedJob, i32) {
+        match delivery {
+            Delivery::Claim(job, max_retries) => (job, max_retries),
+            other => panic!("expected a claim, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_job_reads_headers_and_payload() {
+        let (job, max_retries) = parse_job(&message(&[]), 2).unwrap();
+
+        assert_eq!(job.id, JOB_ID.parse::<Uuid>().unwrap());
+        assert_eq!((job.job_type.as_str(), job.version, job.attempt), ("email:send", 2, 2));
+        assert_eq!(job.payload, json!({ "to": "a@example.com" }));
+        assert_eq!(max_retries, 3);
+    }
+
+    #[test]
+    fn test_parse_job_rejects_missing_headers() {
+        let mut message = message(&[]);
+        message.headers = None;
+
+        let error = parse_job(&message, 1).unwrap_err();
+
+        assert!(error.to_string().contains(JOB_ID_HEADER), "{error:#}");
+    }
+
+    #[test]
+    fn test_scheduled_job_is_nakked_until_due() {
+        let now = Utc::now();
+        let run_at = (now + TimeDelta::seconds(30)).to_rfc3339();
+
+        match triage(&message(&[(RUN_AT_HEADER, &run_at)]), 1, now).unwrap() {
+            Delivery::Early(delay) => assert_eq!(delay, Duration::from_secs(30)),
+            other => panic!("expected an early delivery, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_early_delivery_is_not_counted_as_an_attempt() {
+        let now = Utc::now();
+        let run_at = (now - TimeDelta::seconds(1)).to_rfc3339();
+        let scheduled = message(&[(RUN_AT_HEADER, &run_at)]);
+
+        // The first delivery found it early and was nak'd
+        assert_eq!(claimed(triage(&scheduled, 2, now).unwrap()).0.attempt, 1);
+        assert_eq!(claimed(triage(&scheduled, 3, now).unwrap()).0.attempt, 2);
+        // Due on its first delivery: nothing to discount
+        assert_eq!(claimed(triage(&scheduled, 1, now).unwrap()).0.attempt, 1);
+        assert_eq!(claimed(triage(&message(&[]), 2, now).unwrap()).0.attempt, 2);
+    }
+
+    #[test]
+    fn test_job_delivered_past_its_retry_limit_is_dead_lettered() {
+        let now = Utc::now();
+
+        assert_eq!(claimed(triage(&message(&[]), 3, now).unwrap()).0.attempt, 3);
+        match triage(&message(&[]), 4, now).unwrap() {
+            Delivery::DeadLetter(error, kind) => {
+                assert_eq!(error, "job delivered 3 times without finishing");
+                assert_eq!(kind, FailureKind::Retryable);
+            }
+            other => panic!("expected a dead letter, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unreadable_job_is_dead_lettered_as_non_retryable() {
+        let mut message = message(&[]);
+        message.payload = Bytes::from_static(b"not json");
+
+        match triage(&message, 1, Utc::now()).unwrap() {
+            Delivery::DeadLetter(_, kind) => assert_eq!(kind, FailureKind::NonRetryable),
+            other => panic!("expected a dead letter, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_lease() {
+        let lease = JobLease {
+            job_id: Uuid::new_v4(),
+            token: Uuid::new_v4(),
+        };
+
+        assert!(check_lease(Some(lease.token), lease).is_ok());
+        let superseded = check_lease(Some(Uuid::new_v4()), lease).unwrap_err();
+        assert!(LeaseLost::is(&superseded));
+        let unknown = check_lease(None, lease).unwrap_err();
+        assert!(!LeaseLost::is(&unknown));
+        assert!(unknown.to_string().contains("not claimed through this store"));
+    }
+}
@@ -0,0 +1,426 @@
+//! Job throughput benchmarks against Postgres.
+//!
+//! Measures enqueue throughput, claim latency under concurrent workers, and
+//! end-to-end job latency through `claim_stream`, so regressions in the
+//! claiming SQL show up before a release:
+//!
+//! ```text
+//! cargo bench -p seesaw-job-postgres --features testkit --bench throughput
+//! ```
+//!
+//! Runs against `DATABASE_URL` if it is set, in a throwaway schema that is
+//! dropped afterwards, and in a testcontainers Postgres otherwise. The
+//! schema is [`SCHEMA`] without the optional `job_events` trigger. Tuned with
+//! environment variables:
+//!
+//! - `SEESAW_BENCH_JOBS`: jobs per enqueue and claim run (default 5000)
+//! - `SEESAW_BENCH_WORKERS`: comma-separated worker counts for the claim
+//!   runs (default `1,4,16`); the end-to-end run uses the largest
+//! - `SEESAW_BENCH_SOAK_SECS`: how long the end-to-end run produces jobs
+//!   (default 10); raise it for a soak test
+//! - `SEESAW_BENCH_RATE`: jobs per second produced in that run (default 500)
+//! - `SEESAW_BENCH_SAVE`: write the results as JSON to this path
+//! - `SEESAW_BENCH_BASELINE`: compare with results saved earlier, failing
+//!   if a metric got worse by more than `SEESAW_BENCH_TOLERANCE` (a
+//!   fraction, default 0.2)
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use futures::StreamExt;
+use seesaw_core::job::JobStore;
+use seesaw_job_postgres::testkit::{PgTestDb, SCHEMA};
+use seesaw_job_postgres::{ImportJob, NewJob, PgJobStore};
+use serde_json::json;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Tasks inserting jobs in the enqueue run.
+const PRODUCERS: usize = 8;
+
+/// Jobs each worker claims per `claim_ready` call.
+const CLAIM_BATCH: i64 = 10;
+
+/// How long the end-to-end run waits for the last jobs to finish.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Benchmark results by metric name. Names ending in `_per_sec` are better
+/// higher, names ending in `_ms` better lower.
+type Metrics = BTreeMap<String, f64>;
+
+struct Config {
+    jobs: usize,
+    workers: Vec<usize>,
+    soak: Duration,
+    rate: u64,
+}
+
+impl Config {
+    fn from_env() -> Result<Self> {
+        let workers = env_or("SEESAW_BENCH_WORKERS", "1,4,16".to_string())?
+            .split(',')
+            .map(|n| n.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .context("SEESAW_BENCH_WORKERS must be comma-separated numbers")?;
+        if workers.is_empty() || workers.contains(&0) {
+            bail!("SEESAW_BENCH_WORKERS needs at least one worker per run");
+        }
+        Ok(Self {
+            jobs: env_or("SEESAW_BENCH_JOBS", 5000)?,
+            workers,
+            soak: Duration::from_secs(env_or("SEESAW_BENCH_SOAK_SECS", 10)?),
+            rate: env_or("SEESAW_BENCH_RATE", 500)?.max(1),
+        })
+    }
+
+    fn max_workers(&self) -> usize {
+        self.workers.iter().copied().max().unwrap_or(1)
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> Result<T>
+where
+    T::Err: Display,
+{
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid {name} {value:?}: {e}")),
+        Err(_) => Ok(default),
+    }
+}
+
+// =============================================================================
+// Database
+// =============================================================================
+
+/// Where the benchmark runs, kept alive until it is torn down.
+enum Database {
+    /// A schema of its own in the `DATABASE_URL` database.
+    External { admin: PgPool, schema: String },
+    /// A testcontainers Postgres, removed when dropped.
+    Container { _db: Box<PgTestDb> },
+}
+
+impl Database {
+    /// Set up the schema and return a pool on it sized for `workers`.
+    async fn open(workers: usize) -> Result<(Self, PgPool)> {
+        let (database, options) = match std::env::var("DATABASE_URL") {
+            Ok(url) => {
+                let options: PgConnectOptions = url.parse()?;
+                let admin = PgPoolOptions::new()
+                    .max_connections(1)
+                    .connect_with(options.clone())
+                    .await?;
+                let schema = format!("seesaw_bench_{}", Uuid::new_v4().simple());
+                sqlx::raw_sql(&format!("CREATE SCHEMA {schema}"))
+                    .execute(&admin)
+                    .await?;
+                let options = options.options([("search_path", schema.as_str())]);
+                (Database::External { admin, schema }, options)
+            }
+            Err(_) => {
+                let db = PgTestDb::start().await?;
+                let options = db.pool().connect_options().as_ref().clone();
+                (Database::Container { _db: Box::new(db) }, options)
+            }
+        };
+
+        let pool = PgPoolOptions::new()
+            .max_connections((workers + PRODUCERS + 2) as u32)
+            .connect_with(options)
+            .await?;
+        if let Database::External { .. } = database {
+            sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        }
+        sqlx::raw_sql("DROP TRIGGER jobs_record_event ON jobs")
+            .execute(&pool)
+            .await?;
+        Ok((database, pool))
+    }
+
+    async fn close(self) -> Result<()> {
+        if let Database::External { admin, schema } = self {
+            sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE"))
+                .execute(&admin)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+async fn reset(pool: &PgPool) -> Result<()> {
+    sqlx::raw_sql("TRUNCATE jobs").execute(pool).await?;
+    Ok(())
+}
+
+// =============================================================================
+// Runs
+// =============================================================================
+
+/// Insert `config.jobs` jobs one statement at a time from [`PRODUCERS`] tasks.
+async fn bench_enqueue(store: &PgJobStore, config: &Config, metrics: &mut Metrics) -> Result<()> {
+    let latencies = Arc::new(Mutex::new(Vec::with_capacity(config.jobs)));
+    let started = Instant::now();
+
+    let mut tasks = Vec::new();
+    for producer in 0..PRODUCERS {
+        let store = store.clone();
+        let latencies = latencies.clone();
+        let count = config.jobs / PRODUCERS + usize::from(producer < config.jobs % PRODUCERS);
+        tasks.push(tokio::spawn(async move {
+            for n in 0..count {
+                let job = NewJob::new("bench", json!({ "producer": producer, "n": n }));
+                let sent = Instant::now();
+                store.insert_job(&job).await?;
+                latencies.lock().unwrap().push(sent.elapsed());
+            }
+            anyhow::Ok(())
+        }));
+    }
+    for task in tasks {
+        task.await??;
+    }
+
+    let elapsed = started.elapsed();
+    let mut latencies = std::mem::take(&mut *latencies.lock().unwrap());
+    record(metrics, "enqueue", config.jobs, elapsed, &mut latencies);
+    Ok(())
+}
+
+/// Drain `config.jobs` seeded jobs with `workers` workers calling
+/// `claim_ready` and `mark_succeeded` in a loop.
+async fn bench_claim(
+    store: &PgJobStore,
+    config: &Config,
+    workers: usize,
+    metrics: &mut Metrics,
+) -> Result<()> {
+    let now = Utc::now();
+    store
+        .import_jobs(
+            (0..config.jobs).map(|_| ImportJob::pending(Uuid::new_v4(), "bench", json!({}), now)),
+        )
+        .await?;
+
+    let latencies = Arc::new(Mutex::new(Vec::new()));
+    let started = Instant::now();
+
+    let mut tasks = Vec::new();
+    for worker in 0..workers {
+        let store = store.clone();
+        let latencies = latencies.clone();
+        tasks.push(tokio::spawn(async move {
+            let worker_id = format!("bench-{worker}");
+            loop {
+                let sent = Instant::now();
+                let jobs = store.claim_ready(&worker_id, CLAIM_BATCH).await?;
+                latencies.lock().unwrap().push(sent.elapsed());
+                if jobs.is_empty() {
+                    return anyhow::Ok(());
+                }
+                for job in jobs {
+                    store.mark_succeeded(job.lease()).await?;
+                }
+            }
+        }));
+    }
+    for task in tasks {
+        task.await??;
+    }
+
+    let elapsed = started.elapsed();
+    let mut latencies = std::mem::take(&mut *latencies.lock().unwrap());
+    record(
+        metrics,
+        &format!("claim.workers_{workers}"),
+        config.jobs,
+        elapsed,
+        &mut latencies,
+    );
+    Ok(())
+}
+
+/// Produce jobs at `config.rate` for `config.soak` while workers follow
+/// `claim_stream`, timing each job from before its insert to after its
+/// success is recorded.
+async fn bench_end_to_end(
+    store: &PgJobStore,
+    config: &Config,
+    metrics: &mut Metrics,
+) -> Result<()> {
+    let epoch = Instant::now();
+    let latencies = Arc::new(Mutex::new(Vec::new()));
+    let finished = Arc::new(AtomicU64::new(0));
+
+    let mut workers = Vec::new();
+    for worker in 0..config.max_workers() {
+        let store = store.clone();
+        let latencies = latencies.clone();
+        let finished = finished.clone();
+        workers.push(tokio::spawn(async move {
+            let worker_id = format!("bench-{worker}");
+            store
+                .claim_stream(&worker_id, CLAIM_BATCH as usize)
+                .for_each_concurrent(CLAIM_BATCH as usize, |job| async {
+                    let Ok(job) = job else { return };
+                    let sent_us = job.payload["sent_us"].as_u64().unwrap_or_default();
+                    if store.mark_succeeded(job.lease()).await.is_ok() {
+                        let latency = epoch.elapsed() - Duration::from_micros(sent_us);
+                        latencies.lock().unwrap().push(latency);
+                        finished.fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+                .await;
+        }));
+    }
+
+    let mut produced = 0u64;
+    let mut ticks = tokio::time::interval(Duration::from_secs(1) / config.rate as u32);
+    let started = Instant::now();
+    while started.elapsed() < config.soak {
+        ticks.tick().await;
+        let sent_us = epoch.elapsed().as_micros() as u64;
+        store
+            .insert_job(&NewJob::new("bench", json!({ "sent_us": sent_us })))
+            .await?;
+        produced += 1;
+    }
+
+    let drained = tokio::time::timeout(DRAIN_TIMEOUT, async {
+        while finished.load(Ordering::Relaxed) < produced {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    let elapsed = started.elapsed();
+    for worker in &workers {
+        worker.abort();
+    }
+    if drained.is_err() {
+        bail!(
+            "only {} of {produced} jobs finished within {DRAIN_TIMEOUT:?}",
+            finished.load(Ordering::Relaxed)
+        );
+    }
+
+    let mut latencies = std::mem::take(&mut *latencies.lock().unwrap());
+    record(
+        metrics,
+        "end_to_end",
+        produced as usize,
+        elapsed,
+        &mut latencies,
+    );
+    Ok(())
+}
+
+/// Record throughput and latency percentiles of one run under `name`.
+fn record(
+    metrics: &mut Metrics,
+    name: &str,
+    jobs: usize,
+    elapsed: Duration,
+    latencies: &mut [Duration],
+) {
+    latencies.sort_unstable();
+    metrics.insert(
+        format!("{name}.jobs_per_sec"),
+        jobs as f64 / elapsed.as_secs_f64(),
+    );
+    metrics.insert(format!("{name}.p50_ms"), percentile(latencies, 0.50));
+    metrics.insert(format!("{name}.p99_ms"), percentile(latencies, 0.99));
+    println!(
+        "{name:<20} {jobs:>7} jobs in {:>7.2}s  {:>9.1} jobs/s  p50 {:>7.2}ms  p95 {:>7.2}ms  p99 {:>7.2}ms  max {:>7.2}ms",
+        elapsed.as_secs_f64(),
+        jobs as f64 / elapsed.as_secs_f64(),
+        percentile(latencies, 0.50),
+        percentile(latencies, 0.95),
+        percentile(latencies, 0.99),
+        percentile(latencies, 1.0),
+    );
+}
+
+/// The `p` quantile of `sorted`, in milliseconds.
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index].as_secs_f64() * 1000.0
+}
+
+// =============================================================================
+// Baselines
+// =============================================================================
+
+/// Describe every metric that got worse than `baseline` beyond `tolerance`.
+fn regressions(metrics: &Metrics, baseline: &Metrics, tolerance: f64) -> Vec<String> {
+    metrics
+        .iter()
+        .filter_map(|(name, &value)| {
+            let &base = baseline.get(name)?;
+            let worse = if name.ends_with("_per_sec") {
+                value < base * (1.0 - tolerance)
+            } else {
+                value > base * (1.0 + tolerance)
+            };
+            worse.then(|| format!("{name}: {value:.2} (baseline {base:.2})"))
+        })
+        .collect()
+}
+
+fn read_metrics(path: &str) -> Result<Metrics> {
+    let file = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+    let saved: serde_json::Value = serde_json::from_slice(&file)?;
+    Ok(serde_json::from_value(saved["metrics"].clone())?)
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let config = Config::from_env()?;
+    let (database, pool) = Database::open(config.max_workers()).await?;
+    let store = PgJobStore::new(pool.clone());
+    let mut metrics = Metrics::new();
+
+    let runs = async {
+        bench_enqueue(&store, &config, &mut metrics).await?;
+        for &workers in &config.workers {
+            reset(&pool).await?;
+            bench_claim(&store, &config, workers, &mut metrics).await?;
+        }
+        reset(&pool).await?;
+        bench_end_to_end(&store, &config, &mut metrics).await
+    }
+    .await;
+    pool.close().await;
+    database.close().await?;
+    runs?;
+
+    if let Ok(path) = std::env::var("SEESAW_BENCH_SAVE") {
+        let saved = json!({ "metrics": metrics });
+        std::fs::write(&path, serde_json::to_vec_pretty(&saved)?)?;
+        println!("results saved to {path}");
+    }
+    if let Ok(path) = std::env::var("SEESAW_BENCH_BASELINE") {
+        let tolerance = env_or("SEESAW_BENCH_TOLERANCE", 0.2)?;
+        let regressed = regressions(&metrics, &read_metrics(&path)?, tolerance);
+        if !regressed.is_empty() {
+            bail!(
+                "{} metric(s) regressed beyond {:.0}% of {path}:\n  {}",
+                regressed.len(),
+                tolerance * 100.0,
+                regressed.join("\n  ")
+            );
+        }
+        println!("no regressions against {path}");
+    }
+    Ok(())
+}
@@ -0,0 +1,519 @@
+//! Operator queries beyond [`JobAdmin`](seesaw_core::JobAdmin): listing
+//! jobs by status, previewing the schedule, inspecting workers, inserting
+//! jobs by hand, and reprioritizing pending jobs.
+//!
+//! These back `seesaw-cli`, for incidents where someone needs to see what
+//! the queue is doing right now:
+//!
+//! ```rust,ignore
+//! let stuck = store
+//!     .list_jobs(&JobFilter::default().with_status("running").with_limit(20))
+//!     .await?;
+//! for worker in store.workers().await? {
+//!     println!("{} holds {} jobs, {} expired", worker.worker_id, worker.running, worker.expired);
+//! }
+//!
+//! // Did the nightly export get scheduled?
+//! let upcoming = store.upcoming(Duration::from_secs(12 * 3600)).await?;
+//! assert!(upcoming.iter().any(|s| s.job_type == "export:nightly"));
+//!
+//! // Escalation: move one customer's pending syncs to the front
+//! let filter = JobFilter::default()
+//!     .with_job_type("crm:sync")
+//!     .with_payload_containing(json!({ "customer_id": 42 }));
+//! store.bump_priority(&filter, 100).await?;
+//! ```
+//!
+//! Priorities here run higher first, like `JobSpec` priorities, whatever
+//! the stored column says; see the [`queue`](crate::queue) module.
+
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgConnection, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// Statuses a job row can have.
+pub const JOB_STATUSES: [&str; 6] = [
+    "pending",
+    "running",
+    "succeeded",
+    "failed",
+    "dead_letter",
+    "parked",
+];
+
+/// Which jobs [`PgJobStore::list_jobs`] returns.
+#[derive(Debug, Clone)]
+pub struct JobFilter {
+    status: Option<String>,
+    job_type: Option<String>,
+    payload: Option<serde_json::Value>,
+    limit: i64,
+}
+
+impl Default for JobFilter {
+    /// Every status and job type, at most 50 jobs.
+    fn default() -> Self {
+        Self {
+            status: None,
+            job_type: None,
+            payload: None,
+            limit: 50,
+        }
+    }
+}
+
+impl JobFilter {
+    /// Only jobs in `status` (one of [`JOB_STATUSES`]).
+    pub fn with_status(mut self, status: impl Into<String>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    /// Only jobs of `job_type`.
+    pub fn with_job_type(mut self, job_type: impl Into<String>) -> Self {
+        self.job_type = Some(job_type.into());
+        self
+    }
+
+    /// Only jobs whose payload contains `payload`, as JSONB `@>`: e.g.
+    /// `{"customer_id": 42}` matches every payload with that field.
+    pub fn with_payload_containing(mut self, payload: serde_json::Value) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Return at most `limit` jobs.
+    pub fn with_limit(mut self, limit: i64) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// One row of the `jobs` table.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRow {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub version: i32,
+    pub status: String,
+    pub attempt: i32,
+    pub max_retries: i32,
+    /// Higher runs first.
+    pub priority: i32,
+    pub run_at: DateTime<Utc>,
+    pub worker_id: Option<String>,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub error_kind: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A worker currently holding jobs.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerInfo {
+    pub worker_id: String,
+    /// Jobs the worker has claimed and not finished.
+    pub running: i64,
+    /// Of those, jobs whose lease expired: the worker is likely gone.
+    pub expired: i64,
+    /// The last claim or heartbeat.
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Pending jobs of one type due within an [`upcoming`](PgJobStore::upcoming)
+/// window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledJobSummary {
+    pub job_type: String,
+    /// Jobs due in the window, including overdue ones not yet claimed.
+    pub due: i64,
+    /// Of those, retries waiting out their backoff.
+    pub retrying: i64,
+    /// Earliest run time; in the past if jobs are overdue.
+    pub next_run_at: DateTime<Utc>,
+    /// Latest run time within the window.
+    pub last_run_at: DateTime<Utc>,
+}
+
+/// A job to insert with [`PgJobStore::insert_job`].
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub(crate) job_type: String,
+    pub(crate) payload: serde_json::Value,
+    pub(crate) version: i32,
+    pub(crate) max_retries: i32,
+    pub(crate) priority: i32,
+    pub(crate) run_at: Option<DateTime<Utc>>,
+    pub(crate) lease_ms: Option<i64>,
+    pub(crate) idempotency_key: Option<String>,
+}
+
+impl NewJob {
+    /// Create a job of `job_type`: version 1, 3 retries, priority 0, ready now.
+    pub fn new(job_type: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            job_type: job_type.into(),
+            payload,
+            version: 1,
+            max_retries: 3,
+            priority: 0,
+            run_at: None,
+            lease_ms: None,
+            idempotency_key: None,
+        }
+    }
+
+    /// Set the payload version.
+    pub fn with_version(mut self, version: i32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set how many attempts the job gets before it is dead-lettered.
+    pub fn with_max_retries(mut self, max_retries: i32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the priority (higher runs first).
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority.saturating_neg();
+        self
+    }
+
+    /// Make the job ready at `run_at` instead of now.
+    pub fn with_run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = Some(run_at);
+        self
+    }
+
+    /// Lease the job for `lease_ms` when claimed, instead of the store's
+    /// default.
+    pub fn with_lease_ms(mut self, lease_ms: i64) -> Self {
+        self.lease_ms = Some(lease_ms);
+        self
+    }
+
+    /// Deduplicate the job by `key`; see [`replay`](crate::replay).
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+}
+
+impl JobFilter {
+    /// Append the filter's conditions to a query ending in a `WHERE` clause.
+    fn push_conditions<'a>(&'a self, query: &mut QueryBuilder<'a, Postgres>) -> Result<()> {
+        if let Some(status) = &self.status {
+            if !JOB_STATUSES.contains(&status.as_str()) {
+                bail!(
+                    "unknown job status {status:?}, expected one of {}",
+                    JOB_STATUSES.join(", ")
+                );
+            }
+            query.push(" AND status::TEXT = ").push_bind(status);
+        }
+        if let Some(job_type) = &self.job_type {
+            query.push(" AND job_type = ").push_bind(job_type);
+        }
+        if let Some(payload) = &self.payload {
+            query.push(" AND payload @> ").push_bind(payload);
+        }
+        Ok(())
+    }
+}
+
+impl PgJobStore {
+    /// List jobs matching `filter`, most recently updated first.
+    pub async fn list_jobs(&self, filter: &JobFilter) -> Result<Vec<JobRow>> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "SELECT id, job_type, payload, version, status::TEXT AS status, attempt, \
+             max_retries, priority, run_at, worker_id, lease_expires_at, error_message, \
+             error_kind::TEXT AS error_kind, created_at, updated_at FROM jobs WHERE TRUE",
+        );
+        filter.push_conditions(&mut query)?;
+        query
+            .push(" ORDER BY updated_at DESC LIMIT ")
+            .push_bind(filter.limit);
+
+        let rows = query.build().fetch_all(self.pool()).await?;
+        Ok(rows.iter().map(job_row).collect())
+    }
+
+    /// Get one job, or `None` if it does not exist.
+    pub async fn job(&self, job_id: Uuid) -> Result<Option<JobRow>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, job_type, payload, version, status::TEXT AS status, attempt,
+                   max_retries, priority, run_at, worker_id, lease_expires_at, error_message,
+                   error_kind::TEXT AS error_kind, created_at, updated_at
+            FROM jobs
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(row.as_ref().map(job_row))
+    }
+
+    /// Summarize the pending jobs due within `window` from now, per job
+    /// type, soonest first.
+    ///
+    /// Only rows in `jobs` are counted: scheduled commands, retries waiting
+    /// out their backoff and the next occurrence of each
+    /// [recurring](crate::recurring) schedule. Later occurrences are not
+    /// enqueued yet, so they are not previewed.
+    pub async fn upcoming(&self, window: Duration) -> Result<Vec<ScheduledJobSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT job_type,
+                   COUNT(*) AS due,
+                   COUNT(*) FILTER (WHERE attempt > 1) AS retrying,
+                   MIN(run_at) AS next_run_at,
+                   MAX(run_at) AS last_run_at
+            FROM jobs
+            WHERE status = 'pending' AND run_at <= NOW() + make_interval(secs => $1)
+            GROUP BY job_type
+            ORDER BY next_run_at, job_type
+            "#,
+        )
+        .bind(window.as_secs_f64())
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ScheduledJobSummary {
+                job_type: row.get("job_type"),
+                due: row.get("due"),
+                retrying: row.get("retrying"),
+                next_run_at: row.get("next_run_at"),
+                last_run_at: row.get("last_run_at"),
+            })
+            .collect())
+    }
+
+    /// List the workers holding running jobs.
+    pub async fn workers(&self) -> Result<Vec<WorkerInfo>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT worker_id,
+                   COUNT(*) AS running,
+                   COUNT(*) FILTER (WHERE lease_expires_at < NOW()) AS expired,
+                   MAX(updated_at) AS last_seen
+            FROM jobs
+            WHERE status = 'running' AND worker_id IS NOT NULL
+            GROUP BY worker_id
+            ORDER BY worker_id
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WorkerInfo {
+                worker_id: row.get("worker_id"),
+                running: row.get("running"),
+                expired: row.get("expired"),
+                last_seen: row.get("last_seen"),
+            })
+            .collect())
+    }
+
+    /// Set the priority of a pending job (higher runs first).
+    ///
+    /// Returns `false` if the job does not exist or is no longer pending.
+    pub async fn set_priority(&self, job_id: Uuid, priority: i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET priority = $2, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(job_id)
+        .bind(priority.saturating_neg())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add `delta` to the priority of every pending job matching `filter`;
+    /// a positive delta moves them towards the front of the queue.
+    ///
+    /// Priorities saturate rather than overflow. The filter's limit does not
+    /// apply, and a status other than `pending` is rejected. Returns the
+    /// number of jobs changed.
+    pub async fn bump_priority(&self, filter: &JobFilter, delta: i32) -> Result<u64> {
+        if filter.status.as_deref().is_some_and(|status| status != "pending") {
+            bail!("only pending jobs can be reprioritized");
+        }
+        // The column runs lower first, and stays within what negates
+        let mut query = QueryBuilder::<Postgres>::new(
+            "UPDATE jobs SET priority = GREATEST(LEAST(priority::BIGINT - ",
+        );
+        query
+            .push_bind(i64::from(delta))
+            .push(", ")
+            .push_bind(i64::from(i32::MAX))
+            .push("), ")
+            .push_bind(-i64::from(i32::MAX))
+            .push(")::INTEGER, updated_at = NOW() WHERE status = 'pending'");
+        filter.push_conditions(&mut query)?;
+
+        let result = query.build().execute(self.pool()).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Insert a pending job.
+    ///
+    /// A job with an idempotency key is only inserted if no job holds the
+    /// key; the ID of the job holding it is returned otherwise. See
+    /// [`replay`](crate::replay).
+    pub async fn insert_job(&self, job: &NewJob) -> Result<Uuid> {
+        let mut conn = self.pool().acquire().await?;
+        self.insert_job_on(&mut conn, job).await
+    }
+
+    /// Insert a pending job on `conn`, e.g. inside the caller's transaction.
+    pub(crate) async fn insert_job_on(
+        &self,
+        conn: &mut PgConnection,
+        job: &NewJob,
+    ) -> Result<Uuid> {
+        if let Some(key) = &job.idempotency_key {
+            return self.insert_idempotent(conn, job, key).await;
+        }
+
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO jobs (id, job_type, payload, version, max_retries, priority, run_at,
+                              lease_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, NOW()), $8)
+            "#,
+        )
+        .bind(id)
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.version)
+        .bind(job.max_retries)
+        .bind(job.priority)
+        .bind(job.run_at)
+        .bind(job.lease_ms)
+        .execute(conn)
+        .await?;
+
+        Ok(id)
+    }
+}
+
+fn job_row(row: &sqlx::postgres::PgRow) -> JobRow {
+    JobRow {
+        id: row.get("id"),
+        job_type: row.get("job_type"),
+        payload: row.get("payload"),
+        version: row.get("version"),
+        status: row.get("status"),
+        attempt: row.get("attempt"),
+        max_retries: row.get("max_retries"),
+        priority: row.get::<i32, _>("priority").saturating_neg(),
+        run_at: row.get("run_at"),
+        worker_id: row.get("worker_id"),
+        lease_expires_at: row.get("lease_expires_at"),
+        error_message: row.get("error_message"),
+        error_kind: row.get("error_kind"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::{FailureKind, JobStore};
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::{PgTestDb, TestJob};
+
+    #[tokio::test]
+    async fn test_priorities_run_higher_first() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let low = db.enqueue_test_job("crm:sync", json!({ "n": 1 })).await?;
+        let high = db.enqueue_test_job("crm:sync", json!({ "n": 2 })).await?;
+        let bumped = db.enqueue(TestJob::new("crm:sync", json!({ "n": 3 }))).await?;
+
+        assert!(store.set_priority(high, 10).await?);
+        let filter = JobFilter::default().with_payload_containing(json!({ "n": 3 }));
+        assert_eq!(store.bump_priority(&filter, 5).await?, 1);
+        assert_eq!(store.job(high).await?.unwrap().priority, 10);
+
+        let mut order = Vec::new();
+        for _ in 0..3 {
+            order.extend(store.claim_ready("worker-1", 1).await?.iter().map(|job| job.id));
+        }
+        assert_eq!(order, [high, bumped, low]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bump_priority_saturates() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let id = db
+            .enqueue(TestJob::new("crm:sync", json!({})).with_priority(i32::MAX - 1))
+            .await?;
+
+        store.bump_priority(&JobFilter::default(), i32::MAX).await?;
+        assert_eq!(store.job(id).await?.unwrap().priority, i32::MAX);
+
+        store.bump_priority(&JobFilter::default(), i32::MIN).await?;
+        store.bump_priority(&JobFilter::default(), i32::MIN).await?;
+        assert_eq!(store.job(id).await?.unwrap().priority, -i32::MAX);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upcoming_counts_retries_by_attempt() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let soon = TestJob::new("email:send", json!({ "n": 2 }));
+        let later = TestJob::new("email:send", json!({ "n": 3 }));
+        db.enqueue_test_job("email:send", json!({ "n": 1 })).await?;
+        db.enqueue(soon.with_delay(Duration::from_secs(1800))).await?;
+        db.enqueue(later.with_delay(Duration::from_secs(7200))).await?;
+        db.enqueue_test_job("crm:sync", json!({})).await?;
+
+        let claimed = store.claim_ready("worker-1", 10).await?;
+        let sync = claimed.iter().find(|job| job.job_type == "crm:sync").unwrap();
+        store.mark_failed(sync.lease(), "timed out", FailureKind::Retryable).await?;
+        // An error message alone does not make a pending job a retry
+        let email = claimed.iter().find(|job| job.job_type == "email:send").unwrap();
+        sqlx::query("UPDATE jobs SET status = 'pending', error_message = 'released' WHERE id = $1")
+            .bind(email.id)
+            .execute(db.pool())
+            .await?;
+
+        let upcoming = store.upcoming(Duration::from_secs(3600)).await?;
+
+        let counts: Vec<_> = upcoming
+            .iter()
+            .map(|summary| (summary.job_type.as_str(), summary.due, summary.retrying))
+            .collect();
+        assert_eq!(counts, [("email:send", 2, 0), ("crm:sync", 1, 1)]);
+        Ok(())
+    }
+}
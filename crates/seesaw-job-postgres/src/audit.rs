@@ -0,0 +1,84 @@
+//! PostgreSQL sink for the structured audit trail (`audit` feature).
+//!
+//! Each [`AuditRecord`] becomes one row. The queryable columns are copied
+//! out of the record; the full record, including decisions and effect
+//! outcomes, is kept as JSONB.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE seesaw_audit (
+//!     id BIGSERIAL PRIMARY KEY,
+//!     correlation_id UUID,
+//!     event_type TEXT,
+//!     hops INTEGER NOT NULL,
+//!     received_at TIMESTAMPTZ NOT NULL,
+//!     dropped BOOLEAN NOT NULL,
+//!     duration_us BIGINT NOT NULL,
+//!     record JSONB NOT NULL
+//! );
+//!
+//! CREATE INDEX idx_seesaw_audit_correlation ON seesaw_audit (correlation_id);
+//! CREATE INDEX idx_seesaw_audit_received ON seesaw_audit (received_at);
+//! ```
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_audit_sink(Arc::new(PgAuditSink::new(pool.clone())))
+//!     .build();
+//! ```
+
+use anyhow::Result;
+use async_trait::async_trait;
+use seesaw_core::{AuditRecord, AuditSink};
+use sqlx::PgPool;
+
+/// PostgreSQL audit sink writing to a `seesaw_audit` table.
+#[derive(Clone)]
+pub struct PgAuditSink {
+    pool: PgPool,
+}
+
+impl PgAuditSink {
+    /// Create a new PostgreSQL audit sink.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the underlying connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl AuditSink for PgAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO seesaw_audit
+                    (correlation_id, event_type, hops, received_at, dropped, duration_us, record)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(record.correlation_id)
+            .bind(record.event_type)
+            .bind(i32::try_from(record.hops).unwrap_or(i32::MAX))
+            .bind(record.received_at)
+            .bind(record.dropped)
+            .bind(i64::try_from(record.duration_us).unwrap_or(i64::MAX))
+            .bind(serde_json::to_value(record)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+//! Draining a queue before a deploy cutover.
+//!
+//! A queue here is a job type. [`PgJobStore::drain`] records the queue in a
+//! `drained_queues` table, which every claim of a store built
+//! [`with_draining`](PgJobStore::with_draining) checks, so no such worker on
+//! any deployment starts another job of that type. It then waits until the
+//! jobs already running have finished:
+//!
+//! ```rust,ignore
+//! let store = PgJobStore::new(pool).with_draining(true);
+//!
+//! // Blue/green: flush the old workers before the new ones take over
+//! store.drain("invoice:render").await?;
+//! deploy_green().await?;
+//! store.resume("invoice:render").await?;
+//! ```
+//!
+//! Running jobs that fail and are scheduled for a retry stay pending until
+//! the queue is [resumed](PgJobStore::resume), so the new deployment picks
+//! them up. A job whose worker died is only counted as finished once
+//! [`reclaim_expired`](PgJobStore::reclaim_expired) has put it back to
+//! pending.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE drained_queues (
+//!     queue TEXT PRIMARY KEY,
+//!     drained_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//! );
+//! ```
+//!
+//! Claims of a store built `with_draining` read the table, so it must exist
+//! even if no queue is ever drained. Workers built without it ignore drained
+//! queues.
+
+use anyhow::{bail, Result};
+use sqlx::Row;
+use tracing::debug;
+
+use crate::PgJobStore;
+
+impl PgJobStore {
+    /// Stop claiming jobs of `queue` and wait until none of them is running.
+    ///
+    /// Claims stop as soon as the call starts; the returned future then
+    /// polls every [`with_poll_interval`](PgJobStore::with_poll_interval)
+    /// until the last running job of the queue succeeds, fails or is
+    /// parked. Draining a queue that is already drained just waits again.
+    /// Dropping the future leaves the queue drained.
+    ///
+    /// Fails unless the store was built
+    /// [`with_draining`](PgJobStore::with_draining), since its own claims
+    /// would not stop.
+    pub async fn drain(&self, queue: &str) -> Result<()> {
+        if !self.draining {
+            bail!("cannot drain {queue}: store was not built with_draining");
+        }
+        let pool = &self.pool;
+        self.retrying("drain", move || async move {
+            Ok(sqlx::query(
+                "INSERT INTO drained_queues (queue) VALUES ($1) ON CONFLICT (queue) DO NOTHING",
+            )
+            .bind(queue)
+            .execute(pool)
+            .await?)
+        })
+        .await?;
+
+        loop {
+            let running = self.running_in(queue).await?;
+            if running == 0 {
+                return Ok(());
+            }
+            debug!(queue, running, "waiting for queue to drain");
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Let workers claim jobs of a drained `queue` again.
+    ///
+    /// Returns `false` if the queue was not drained.
+    pub async fn resume(&self, queue: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM drained_queues WHERE queue = $1")
+            .bind(queue)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Queues currently drained, oldest first.
+    pub async fn drained_queues(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT queue FROM drained_queues ORDER BY drained_at")
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get("queue")).collect())
+    }
+
+    /// Count the running jobs of `queue`, including unconfirmed reservations.
+    async fn running_in(&self, queue: &str) -> Result<i64> {
+        let pool = &self.pool;
+        let row = self
+            .retrying("drain", move || async move {
+                Ok(sqlx::query(
+                    "SELECT COUNT(*) AS running FROM jobs WHERE job_type = $1 AND status = 'running'",
+                )
+                .bind(queue)
+                .fetch_one(pool)
+                .await?)
+            })
+            .await?;
+
+        Ok(row.get("running"))
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use std::time::Duration;
+
+    use seesaw_core::JobStore;
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_running_jobs_and_stops_claims() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db
+            .store()
+            .with_draining(true)
+            .with_poll_interval(Duration::from_millis(10));
+        db.enqueue_test_job("invoice:render", json!({ "n": 1 })).await?;
+        let running = store.claim_ready("worker-1", 1).await?.remove(0);
+        let waiting = db.enqueue_test_job("invoice:render", json!({ "n": 2 })).await?;
+        let other = db.enqueue_test_job("email:send", json!({})).await?;
+
+        let drain = tokio::spawn({
+            let store = store.clone();
+            async move { store.drain("invoice:render").await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!drain.is_finished());
+        assert_eq!(store.drained_queues().await?, ["invoice:render"]);
+
+        let claimed = store.claim_ready("worker-2", 10).await?;
+        assert_eq!(claimed.iter().map(|job| job.id).collect::<Vec<_>>(), [other]);
+
+        store.mark_succeeded(running.lease()).await?;
+        tokio::time::timeout(Duration::from_secs(5), drain).await???;
+
+        assert!(store.resume("invoice:render").await?);
+        assert!(!store.resume("invoice:render").await?);
+        let claimed = store.claim_ready("worker-2", 10).await?;
+        assert_eq!(claimed.iter().map(|job| job.id).collect::<Vec<_>>(), [waiting]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_drain_requires_draining_store() -> Result<()> {
+        let db = PgTestDb::start().await?;
+
+        let error = db.store().drain("invoice:render").await.unwrap_err();
+
+        assert!(error.to_string().contains("with_draining"));
+        assert!(db.store().drained_queues().await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+//! Append-only log of job lifecycle transitions.
+//!
+//! With the schema below installed, every change to a job row records a
+//! [`JobEvent`]: enqueued, claimed, heartbeat, failed (retry scheduled),
+//! reclaimed, succeeded, dead-lettered, parked or cancelled. The trigger sees
+//! writes from every store instance, the CLI and manual SQL alike, in the
+//! same transaction as the change. Consumers read the log with
+//! [`PgJobStore::stream_job_events`] for audit, billing by compute time, or
+//! SLA reporting.
+//!
+//! The log is optional: without the table and trigger the store behaves as
+//! before.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE job_events (
+//!     seq BIGSERIAL PRIMARY KEY,
+//!     txid BIGINT NOT NULL DEFAULT pg_current_xact_id()::TEXT::BIGINT,
+//!     job_id UUID NOT NULL,
+//!     job_type TEXT NOT NULL,
+//!     kind TEXT NOT NULL,
+//!     attempt INTEGER NOT NULL,
+//!     worker_id TEXT,
+//!     error_message TEXT,
+//!     recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//! );
+//!
+//! CREATE INDEX idx_job_events_cursor ON job_events (txid, seq);
+//!
+//! CREATE FUNCTION seesaw_record_job_event() RETURNS trigger AS $$
+//! DECLARE
+//!     event_kind TEXT;
+//! BEGIN
+//!     IF TG_OP = 'INSERT' THEN
+//!         IF NEW.status <> 'pending' THEN RETURN NULL; END IF;
+//!         event_kind := 'enqueued';
+//!     ELSIF NEW.status = OLD.status THEN
+//!         IF NEW.status <> 'running'
+//!             OR NEW.lease_expires_at IS NOT DISTINCT FROM OLD.lease_expires_at THEN
+//!             RETURN NULL;
+//!         END IF;
+//!         event_kind := 'heartbeat';
+//!     ELSE
+//!         event_kind := CASE
+//!             WHEN NEW.status = 'running' THEN 'claimed'
+//!             WHEN NEW.status = 'succeeded' THEN 'succeeded'
+//!             WHEN NEW.status = 'dead_letter' THEN 'dead_lettered'
+//!             WHEN NEW.status = 'parked' THEN 'parked'
+//!             WHEN NEW.status = 'failed' THEN 'cancelled'
+//!             WHEN OLD.status = 'running' AND NEW.attempt > OLD.attempt THEN 'failed'
+//!             WHEN OLD.status = 'running' THEN 'reclaimed'
+//!             ELSE 'enqueued'
+//!         END;
+//!     END IF;
+//!
+//!     INSERT INTO job_events (job_id, job_type, kind, attempt, worker_id, error_message)
+//!     VALUES (NEW.id, NEW.job_type, event_kind, NEW.attempt,
+//!             CASE WHEN event_kind <> 'enqueued' THEN COALESCE(NEW.worker_id, OLD.worker_id) END,
+//!             NEW.error_message);
+//!     RETURN NULL;
+//! END;
+//! $$ LANGUAGE plpgsql;
+//!
+//! CREATE TRIGGER jobs_record_event AFTER INSERT OR UPDATE ON jobs
+//!     FOR EACH ROW EXECUTE FUNCTION seesaw_record_job_event();
+//! ```
+//!
+//! # Ordering
+//!
+//! Sequence numbers are assigned when a row is written but become visible
+//! when its transaction commits, so reading by `seq` alone can skip an event
+//! committed late. Events are therefore read in `(txid, seq)` order and only
+//! once every transaction that could still write an earlier position has
+//! finished. The stream is gap-free, and trails the oldest open transaction
+//! that writes jobs.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// Events one poll of [`PgJobStore::stream_job_events`] reads.
+const STREAM_BATCH: i64 = 500;
+
+/// A position in the job event log.
+///
+/// Persist the cursor of the last event handled and pass it to
+/// [`PgJobStore::stream_job_events`] to resume after it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct JobEventCursor {
+    pub txid: i64,
+    pub seq: i64,
+}
+
+impl JobEventCursor {
+    /// Before the first event.
+    pub const START: Self = Self { txid: 0, seq: 0 };
+}
+
+/// What happened to a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobEventKind {
+    /// Inserted as pending, or requeued from the dead letter queue.
+    Enqueued,
+    /// Claimed by a worker.
+    Claimed,
+    /// The worker extended its lease.
+    Heartbeat,
+    /// An attempt failed and a retry was scheduled.
+    Failed,
+    /// The lease expired and the job went back to pending.
+    Reclaimed,
+    Succeeded,
+    DeadLettered,
+    /// Set aside because no worker could run it.
+    Parked,
+    /// Cancelled while pending.
+    Cancelled,
+}
+
+impl JobEventKind {
+    fn parse(kind: &str) -> Result<Self> {
+        Ok(match kind {
+            "enqueued" => JobEventKind::Enqueued,
+            "claimed" => JobEventKind::Claimed,
+            "heartbeat" => JobEventKind::Heartbeat,
+            "failed" => JobEventKind::Failed,
+            "reclaimed" => JobEventKind::Reclaimed,
+            "succeeded" => JobEventKind::Succeeded,
+            "dead_lettered" => JobEventKind::DeadLettered,
+            "parked" => JobEventKind::Parked,
+            "cancelled" => JobEventKind::Cancelled,
+            other => return Err(anyhow!("unknown job event kind {other:?}")),
+        })
+    }
+}
+
+/// One transition in the job event log.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobEvent {
+    pub cursor: JobEventCursor,
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub kind: JobEventKind,
+    /// The job's attempt after the transition.
+    pub attempt: i32,
+    /// The worker that claimed, heartbeat, finished or lost the job; `None`
+    /// for enqueues.
+    pub worker_id: Option<String>,
+    pub error_message: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl PgJobStore {
+    /// Read up to `limit` events after `after`, oldest first.
+    ///
+    /// Returns fewer, possibly none, while a transaction that writes jobs
+    /// is still open; see [Ordering](self#ordering).
+    pub async fn job_events(&self, after: JobEventCursor, limit: i64) -> Result<Vec<JobEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT seq, txid, job_id, job_type, kind, attempt, worker_id, error_message,
+                   recorded_at
+            FROM job_events
+            WHERE (txid, seq) > ($1, $2)
+              AND txid < pg_snapshot_xmin(pg_current_snapshot())::TEXT::BIGINT
+            ORDER BY txid, seq
+            LIMIT $3
+            "#,
+        )
+        .bind(after.txid)
+        .bind(after.seq)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(JobEvent {
+                    cursor: JobEventCursor {
+                        txid: row.get("txid"),
+                        seq: row.get("seq"),
+                    },
+                    job_id: row.get("job_id"),
+                    job_type: row.get("job_type"),
+                    kind: JobEventKind::parse(row.get("kind"))?,
+                    attempt: row.get("attempt"),
+                    worker_id: row.get("worker_id"),
+                    error_message: row.get("error_message"),
+                    recorded_at: row.get("recorded_at"),
+                })
+            })
+            .collect()
+    }
+
+    /// Follow the event log from `since`, forever.
+    ///
+    /// Polls every [`with_poll_interval`](PgJobStore::with_poll_interval)
+    /// while caught up. A failed read is yielded as an error and retried
+    /// from the same position on the next poll.
+    pub fn stream_job_events(&self, since: JobEventCursor) -> BoxStream<'static, Result<JobEvent>> {
+        struct State {
+            store: PgJobStore,
+            cursor: JobEventCursor,
+            ready: std::vec::IntoIter<JobEvent>,
+        }
+
+        let state = State {
+            store: self.clone(),
+            cursor: since,
+            ready: Vec::new().into_iter(),
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.ready.next() {
+                    state.cursor = event.cursor;
+                    return Some((Ok(event), state));
+                }
+                match state.store.job_events(state.cursor, STREAM_BATCH).await {
+                    Ok(events) if !events.is_empty() => state.ready = events.into_iter(),
+                    Ok(_) => tokio::time::sleep(state.store.poll_interval).await,
+                    Err(e) => {
+                        tokio::time::sleep(state.store.poll_interval).await;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Delete events recorded more than `retention` ago.
+    pub async fn prune_job_events(&self, retention: Duration) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM job_events WHERE recorded_at < NOW() - make_interval(secs => $1)",
+        )
+        .bind(retention.as_secs_f64())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::{FailureKind, JobAdmin, JobStore};
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+    use crate::NewJob;
+
+    #[tokio::test]
+    async fn test_every_transition_is_logged() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let job = db.enqueue_test_job("email:send", json!({})).await?;
+        let cancelled = db.enqueue_test_job("email:send", json!({})).await?;
+        assert!(store.cancel(cancelled).await?);
+
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+        store.heartbeat(claimed.lease()).await?;
+        store
+            .mark_failed(claimed.lease(), "smtp timeout", FailureKind::Retryable)
+            .await?;
+        db.advance_leases(Duration::from_secs(60)).await?;
+        store.claim_ready("worker-2", 1).await?;
+        db.advance_leases(Duration::from_secs(120)).await?;
+        assert_eq!(store.reclaim_expired().await?, 1);
+        let claimed = store.claim_ready("worker-3", 1).await?.remove(0);
+        store.mark_succeeded(claimed.lease()).await?;
+
+        let events = store.job_events(JobEventCursor::START, 100).await?;
+
+        let of = |id| -> Vec<_> {
+            events
+                .iter()
+                .filter(|event| event.job_id == id)
+                .map(|event| (event.kind, event.attempt, event.worker_id.as_deref()))
+                .collect()
+        };
+        use JobEventKind::*;
+        assert_eq!(
+            of(job),
+            [
+                (Enqueued, 1, None),
+                (Claimed, 1, Some("worker-1")),
+                (Heartbeat, 1, Some("worker-1")),
+                (Failed, 2, Some("worker-1")),
+                (Claimed, 2, Some("worker-2")),
+                // advance_leases moves the lease like a heartbeat would
+                (Heartbeat, 2, Some("worker-2")),
+                (Reclaimed, 2, Some("worker-2")),
+                (Claimed, 2, Some("worker-3")),
+                (Succeeded, 2, Some("worker-3")),
+            ]
+        );
+        assert_eq!(of(cancelled), [(Enqueued, 1, None), (Cancelled, 1, None)]);
+        let failed = events.iter().find(|event| event.kind == Failed).unwrap();
+        assert_eq!(failed.error_message.as_deref(), Some("smtp timeout"));
+        assert!(events.windows(2).all(|pair| pair[0].cursor < pair[1].cursor));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_transaction_holds_back_later_events() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store().with_poll_interval(Duration::from_millis(10));
+        let mut tx = db.pool().begin().await?;
+        let early = store
+            .insert_job_on(&mut tx, &NewJob::new("email:send", json!({})))
+            .await?;
+        let late = db.enqueue_test_job("email:send", json!({})).await?;
+
+        assert!(store.job_events(JobEventCursor::START, 100).await?.is_empty());
+
+        tx.commit().await?;
+        let mut events = store.stream_job_events(JobEventCursor::START);
+        let first = events.next().await.unwrap()?;
+        let second = events.next().await.unwrap()?;
+        assert_eq!((first.job_id, second.job_id), (early, late));
+
+        let resumed = store.job_events(first.cursor, 100).await?;
+        assert_eq!(resumed.iter().map(|event| event.job_id).collect::<Vec<_>>(), [late]);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+//! Bulk import of jobs from another queue system.
+//!
+//! [`PgJobStore::import_jobs`] streams jobs into the `jobs` table with
+//! `COPY`, keeping the IDs, timestamps, attempt counts and statuses the
+//! caller provides. Every job is validated before it is sent; the first
+//! invalid one aborts the copy, and nothing is written.
+//!
+//! ```rust,ignore
+//! let imported = store
+//!     .import_jobs(legacy_jobs.into_iter().map(|j| ImportJob {
+//!         attempt: j.retry_count + 1,
+//!         ..ImportJob::pending(j.id, j.class, j.args, j.enqueued_at)
+//!     }))
+//!     .await?;
+//! ```
+//!
+//! Import records also deserialize from JSON, one per line, for
+//! `seesaw jobs import`:
+//!
+//! ```json
+//! {"id": "…", "job_type": "email:send", "payload": {}, "created_at": "2024-05-01T12:00:00Z"}
+//! ```
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use seesaw_core::job::FailureKind;
+use seesaw_job_sql_core::error_kind_label;
+use serde::Deserialize;
+use sqlx::postgres::PgPoolCopyExt;
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// Bytes buffered before a chunk is sent to the server.
+const COPY_CHUNK_BYTES: usize = 1 << 20;
+
+const COPY_STATEMENT: &str = "COPY jobs (id, job_type, payload, version, status, attempt, \
+     max_retries, priority, run_at, error_message, error_kind, created_at, updated_at) \
+     FROM STDIN WITH (FORMAT csv)";
+
+/// Status of an imported job.
+///
+/// There is no `Running`: an imported job cannot hold a lease, so jobs that
+/// were in flight in the old system should be imported as pending.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    #[default]
+    Pending,
+    Succeeded,
+    Failed,
+    DeadLetter,
+}
+
+impl ImportStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ImportStatus::Pending => "pending",
+            ImportStatus::Succeeded => "succeeded",
+            ImportStatus::Failed => "failed",
+            ImportStatus::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+/// A job to import, with the fields the old system already decided.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    #[serde(default = "default_version")]
+    pub version: i32,
+    #[serde(default)]
+    pub status: ImportStatus,
+    /// The attempt the job is on; 1 if it never failed.
+    #[serde(default = "default_attempt")]
+    pub attempt: i32,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: i32,
+    /// Higher runs first, as in `NewJob::with_priority`; stored negated.
+    #[serde(default)]
+    pub priority: i32,
+    /// When the job is ready; defaults to `created_at`.
+    #[serde(default)]
+    pub run_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub error_kind: Option<FailureKind>,
+    pub created_at: DateTime<Utc>,
+    /// When the job last changed (for dead letters, when it failed);
+    /// defaults to `created_at`.
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+fn default_version() -> i32 {
+    1
+}
+
+fn default_attempt() -> i32 {
+    1
+}
+
+fn default_max_retries() -> i32 {
+    3
+}
+
+impl ImportJob {
+    /// A pending job on its first attempt, with the store's defaults.
+    pub fn pending(
+        id: Uuid,
+        job_type: impl Into<String>,
+        payload: serde_json::Value,
+        created_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            job_type: job_type.into(),
+            payload,
+            version: default_version(),
+            status: ImportStatus::Pending,
+            attempt: default_attempt(),
+            max_retries: default_max_retries(),
+            priority: 0,
+            run_at: None,
+            error_message: None,
+            error_kind: None,
+            created_at,
+            updated_at: None,
+        }
+    }
+
+    /// Check the job is one the store could have produced itself.
+    pub fn validate(&self) -> Result<()> {
+        if self.job_type.is_empty() {
+            bail!("job_type is empty");
+        }
+        if self.version < 1 {
+            bail!("version {} is below 1", self.version);
+        }
+        if self.attempt < 1 || self.max_retries < 1 {
+            bail!("attempt and max_retries must be at least 1");
+        }
+        if self.status == ImportStatus::Pending && self.attempt > self.max_retries {
+            bail!(
+                "pending on attempt {} of {}: it has no attempts left",
+                self.attempt,
+                self.max_retries
+            );
+        }
+        if self.updated_at.is_some_and(|at| at < self.created_at) {
+            bail!("updated_at is before created_at");
+        }
+        if self.error_kind.is_some() && self.error_message.is_none() {
+            bail!("error_kind without an error_message");
+        }
+        match self.status {
+            ImportStatus::Succeeded if self.error_message.is_some() => {
+                bail!("succeeded jobs cannot have an error")
+            }
+            ImportStatus::DeadLetter if self.error_message.is_none() => {
+                bail!("dead-lettered jobs need an error_message")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Append the job as one CSV line in [`COPY_STATEMENT`] column order.
+    fn write_csv(&self, out: &mut String) {
+        let fields = [
+            Some(self.id.to_string()),
+            Some(self.job_type.clone()),
+            Some(self.payload.to_string()),
+            Some(self.version.to_string()),
+            Some(self.status.as_str().to_string()),
+            Some(self.attempt.to_string()),
+            Some(self.max_retries.to_string()),
+            Some(self.priority.saturating_neg().to_string()),
+            Some(self.run_at.unwrap_or(self.created_at).to_rfc3339()),
+            self.error_message.clone(),
+            self.error_kind
+                .map(|kind| error_kind_label(kind).to_string()),
+            Some(self.created_at.to_rfc3339()),
+            Some(self.updated_at.unwrap_or(self.created_at).to_rfc3339()),
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            // Unquoted empty is NULL; quoted is always a value, even "".
+            if let Some(value) = field {
+                out.push('"');
+                out.push_str(&value.replace('"', "\"\""));
+                out.push('"');
+            }
+        }
+        out.push('\n');
+    }
+}
+
+impl PgJobStore {
+    /// Import `jobs` with `COPY`, returning how many were written.
+    ///
+    /// The import is all or nothing: an invalid job, or an ID already in the
+    /// table, rejects the whole batch, so a failed import can be fixed and
+    /// re-run. For very large migrations, split the input into batches to
+    /// bound how much a retry repeats.
+    pub async fn import_jobs(&self, jobs: impl IntoIterator<Item = ImportJob>) -> Result<u64> {
+        let mut copy = self.pool().copy_in_raw(COPY_STATEMENT).await?;
+        let mut buffer = String::with_capacity(COPY_CHUNK_BYTES);
+
+        for (index, job) in jobs.into_iter().enumerate() {
+            if let Err(e) = job.validate() {
+                let reason = format!("job {index} ({}): {e}", job.id);
+                copy.abort(reason.clone()).await.ok();
+                bail!("import rejected {reason}");
+            }
+            job.write_csv(&mut buffer);
+            if buffer.len() >= COPY_CHUNK_BYTES {
+                copy.send(buffer.as_bytes()).await?;
+                buffer.clear();
+            }
+        }
+        if !buffer.is_empty() {
+            copy.send(buffer.as_bytes()).await?;
+        }
+
+        copy.finish().await.context("COPY into jobs failed")
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::job::JobStore;
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+    use crate::NewJob;
+
+    #[tokio::test]
+    async fn test_imported_priorities_order_with_enqueued_jobs() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let created_at = Utc::now() - chrono::Duration::minutes(1);
+        let urgent = Uuid::new_v4();
+        let routine = Uuid::new_v4();
+        store
+            .import_jobs([
+                ImportJob {
+                    priority: 10,
+                    ..ImportJob::pending(urgent, "report:build", json!({}), created_at)
+                },
+                ImportJob::pending(routine, "report:build", json!({}), created_at),
+            ])
+            .await?;
+        let enqueued = store
+            .insert_job(&NewJob::new("report:build", json!({})).with_priority(5))
+            .await?;
+
+        let mut order = Vec::new();
+        for _ in 0..3 {
+            order.push(store.claim_ready("worker-1", 1).await?.remove(0).id);
+        }
+        assert_eq!(order, [urgent, enqueued, routine]);
+        let imported = store.job(urgent).await?.expect("imported job");
+        assert_eq!(imported.priority, 10);
+        Ok(())
+    }
+}
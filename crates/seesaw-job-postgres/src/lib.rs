@@ -14,7 +14,9 @@
 //! # Database Schema
 //!
 //! ```sql
-//! CREATE TYPE job_status AS ENUM ('pending', 'running', 'succeeded', 'failed', 'dead_letter');
+//! CREATE TYPE job_status AS ENUM (
+//!     'pending', 'running', 'succeeded', 'failed', 'dead_letter', 'parked'
+//! );
 //! CREATE TYPE error_kind AS ENUM ('retryable', 'non_retryable');
 //!
 //! CREATE TABLE jobs (
@@ -35,6 +37,9 @@
 //!     -- Worker tracking
 //!     worker_id TEXT,
 //!     lease_expires_at TIMESTAMPTZ,
+//!     lease_token UUID,
+//!     lease_ms BIGINT,
+//!     reserved BOOLEAN NOT NULL DEFAULT FALSE,
 //!
 //!     -- Error tracking
 //!     error_message TEXT,
@@ -51,6 +56,64 @@
 //!     WHERE status = 'running' AND lease_expires_at IS NOT NULL;
 //! ```
 //!
+//! Every claim writes a fresh `lease_token`, and heartbeats and outcomes
+//! only apply while it still matches, failing with
+//! [`LeaseLost`](seesaw_core::LeaseLost) otherwise. Tables created before
+//! fencing need the column:
+//!
+//! ```sql
+//! ALTER TABLE jobs ADD COLUMN lease_token UUID;
+//! ```
+//!
+//! # Per-Job Leases
+//!
+//! A job with a `lease_ms` (from `JobSpec::with_lease_ms`) is leased for
+//! that long when claimed and on every heartbeat, so a 30 minute report and
+//! a 200ms email can share a queue. Jobs without one get the store's
+//! [`with_lease_timeout`](PgJobStore::with_lease_timeout). Tables created
+//! before per-job leases need the column:
+//!
+//! ```sql
+//! ALTER TABLE jobs ADD COLUMN lease_ms BIGINT;
+//! ```
+//!
+//! # Binary Payloads
+//!
+//! With [`PgJobStore::with_codecs`], jobs can be stored in a binary format
+//! picked by a `PayloadCodecs` registry. Encoded rows keep the bytes and the
+//! codec name in two extra columns (with `payload` set to `'null'`), and are
+//! decoded back to JSON when claimed:
+//!
+//! ```sql
+//! ALTER TABLE jobs ADD COLUMN payload_bytes BYTEA, ADD COLUMN codec TEXT;
+//! ```
+//!
+//! # Waking Workers
+//!
+//! [`PgJobStore::claim_stream`](JobStore::claim_stream) LISTENs on the
+//! [`JOB_CHANNEL`] channel and claims as soon as a job is announced there,
+//! falling back to polling every [`with_poll_interval`](PgJobStore::with_poll_interval)
+//! for jobs that become ready without one (scheduled, retried or reclaimed).
+//! Each empty claim looks that far ahead, so a scheduled job or retry due
+//! sooner wakes the stream at its `run_at` instead of on the next poll.
+//! Announce new and requeued jobs with a trigger:
+//!
+//! ```sql
+//! CREATE FUNCTION seesaw_notify_job() RETURNS trigger AS $$
+//! BEGIN
+//!     PERFORM pg_notify('seesaw_jobs', '');
+//!     RETURN NULL;
+//! END;
+//! $$ LANGUAGE plpgsql;
+//!
+//! CREATE TRIGGER jobs_notify AFTER INSERT OR UPDATE OF status ON jobs
+//!     FOR EACH ROW WHEN (NEW.status = 'pending')
+//!     EXECUTE FUNCTION seesaw_notify_job();
+//! ```
+//!
+//! Postgres folds identical notifications sent in one transaction, so a bulk
+//! insert wakes each worker once.
+//!
 //! # Usage
 //!
 //! ```rust,ignore
@@ -63,19 +126,201 @@
 //! // Use with seesaw dispatcher
 //! let dispatcher = Dispatcher::with_job_queue(deps, bus, Arc::new(store));
 //! ```
+//!
+//! The store is both the dispatcher's [`JobQueue`](seesaw_core::JobQueue),
+//! inserting background and scheduled commands with their `JobSpec`, and
+//! the workers' [`JobStore`]; see the [`queue`] module. Effects can also
+//! enqueue follow-up commands inside their own transaction with
+//! [`PgJobStore::enqueue_in_tx`], so the job commits with their write.
+//! Recurring commands are stored in a `recurring_jobs` table, one
+//! occurrence enqueued at a time by
+//! [`PgJobStore::materialize_recurring`]; see the [`recurring`] module.
+//!
+//! # Durable Events
+//!
+//! [`PgOutbox`] stores events in an `event_outbox` table on the same pool,
+//! for at-least-once delivery to the runtime via `seesaw_outbox::OutboxPublisher`.
+//! See the [`outbox`] module for the schema.
+//!
+//! ```rust,ignore
+//! let outbox = store.outbox();
+//! outbox.emit(&OrderPlaced { order_id }, CorrelationId::NONE).await?;
+//!
+//! let publisher = OutboxPublisher::new(outbox, registry, handle.bus().clone())
+//!     .with_acknowledgment(handle.inflight().clone());
+//! tokio::spawn(publisher.run());
+//! ```
+//!
+//! # Job Lifecycle Events
+//!
+//! With the `job_events` table and trigger from the [`events`] module
+//! installed, every transition of every job is appended to a log that
+//! [`PgJobStore::stream_job_events`] follows from a resumable cursor:
+//!
+//! ```rust,ignore
+//! let mut events = store.stream_job_events(last_cursor);
+//! while let Some(event) = events.next().await {
+//!     let event = event?;
+//!     billing.record(&event).await?;
+//!     save_cursor(event.cursor).await?;
+//! }
+//! ```
+//!
+//! # Two-Phase Claims
+//!
+//! [`reserve`](JobStore::reserve) claims jobs for a short window as
+//! `reserved` running rows; the worker validates each one and then
+//! [`confirm`](JobStore::confirm)s it, extending the lease, or
+//! [`release`](JobStore::release)s it back to pending without touching its
+//! attempt. Tables created before reservations need the column:
+//!
+//! ```sql
+//! ALTER TABLE jobs ADD COLUMN reserved BOOLEAN NOT NULL DEFAULT FALSE;
+//! ```
+//!
+//! # Parked Jobs
+//!
+//! A job whose payload does not decode or deserialize, or whose job type
+//! has no handler, is [parked](JobStore::park) rather than dead-lettered:
+//! it keeps its attempt count and waits for an operator to
+//! [`unpark`](JobAdmin::unpark) it once the handler is deployed. Databases
+//! created before parking need the status:
+//!
+//! ```sql
+//! ALTER TYPE job_status ADD VALUE 'parked';
+//! ```
+//!
+//! # Draining Queues
+//!
+//! [`PgJobStore::drain`] stops every worker from claiming jobs of one type
+//! and resolves once the running ones have finished, for deploys that must
+//! flush a queue before cutover; [`PgJobStore::resume`] lifts it. It is off
+//! by default: stores built [`with_draining`](PgJobStore::with_draining)
+//! check the `drained_queues` table from the [`drain`] module on every
+//! claim, so enable it on every worker once the table exists:
+//!
+//! ```sql
+//! CREATE TABLE drained_queues (
+//!     queue TEXT PRIMARY KEY,
+//!     drained_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//! );
+//! ```
+//!
+//! # Replay Protection
+//!
+//! Jobs inserted with an idempotency key are deduplicated against pending
+//! and running jobs, and, with the `completed_keys` table from the
+//! [`replay`] module, against jobs that succeeded within
+//! [`with_replay_window`](PgJobStore::with_replay_window).
+//!
+//! # Transient Errors
+//!
+//! Statements failing with a connection reset, a failover or a
+//! serialization failure are retried with a capped backoff, so a brief
+//! outage slows workers down instead of failing their claim loops. See the
+//! [`transient`] module; with the `metrics` feature every retry is counted
+//! in `seesaw_pg_transient_retries_total`.
+//!
+//! # Durable Waits
+//!
+//! [`PgJobStore::await_event_durable`] long-polls for the outcome of work
+//! tied to a correlation ID, with the wait recorded in a `job_waiters`
+//! table rather than in memory. Jobs attached with
+//! [`PgJobStore::watch_job`] complete it when they finish, and waiters on
+//! any process are woken through [`WAITER_CHANNEL`], so a client whose API
+//! pod restarted polls again and gets the result. See the [`waiters`]
+//! module for the schema and trigger.
+//!
+//! # Audit Trail
+//!
+//! With the `audit` feature, [`PgAuditSink`] writes the runtime's structured
+//! audit records to a `seesaw_audit` table. See the `audit` module for the
+//! schema.
+//!
+//! # Operating the Queue
+//!
+//! Beyond [`JobAdmin`], the [`admin`] module lists jobs by status, previews
+//! what is due in the next N minutes, shows which workers hold leases, and
+//! inserts jobs by hand. The `seesaw` CLI in `seesaw-cli` is built on it.
+//!
+//! To migrate from another queue system, [`PgJobStore::import_jobs`] bulk
+//! loads jobs with `COPY`, keeping their IDs, attempts and statuses. See the
+//! [`import`] module.
+//!
+//! # Integration Tests
+//!
+//! With the `testkit` feature, `testkit::PgTestDb` starts a migrated
+//! Postgres in a throwaway container, with helpers to enqueue jobs, expire
+//! leases and backoffs, and snapshot where every job ended up.
+
+use std::collections::VecDeque;
+use std::sync::LazyLock;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
-use seesaw_core::job::{ClaimedJob, FailureKind, JobStore};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt};
+use seesaw_core::job::{
+    ClaimedJob, DeadLetterJob, FailureKind, JobAdmin, JobLease, JobStore, JobTypeStats, LeaseLost,
+    LookaheadClaim, ParkedJob, UpcomingJob, CLAIM_POLL_INTERVAL,
+};
+use seesaw_core::{PayloadCodecs, RetryPolicy};
+use seesaw_job_sql_core::{
+    error_kind_label, failure_outcome, job_lease_expiry, lease_expiry, lookahead_until, ClaimQuery,
+    FailureOutcome, JobQueries, SqlDialect, CLAIM_COLUMNS, DEFAULT_LEASE_MS,
+};
+use sqlx::postgres::{PgListener, PgRow};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+pub mod admin;
+pub mod drain;
+pub mod events;
+pub mod import;
+mod metrics;
+pub mod outbox;
+pub mod queue;
+pub mod recurring;
+pub mod replay;
+pub mod transient;
+pub mod waiters;
+
+#[cfg(feature = "audit")]
+pub mod audit;
+
+#[cfg(feature = "testkit")]
+pub mod testkit;
+
+pub use admin::{JobFilter, JobRow, NewJob, ScheduledJobSummary, WorkerInfo, JOB_STATUSES};
+pub use events::{JobEvent, JobEventCursor, JobEventKind};
+pub use import::{ImportJob, ImportStatus};
+pub use outbox::{PgOutbox, PgOutboxWriter};
+pub use replay::DEFAULT_REPLAY_WINDOW;
+pub use transient::is_transient_error;
+pub use waiters::{WaiterOutcome, WAITER_CHANNEL};
+pub use seesaw_job_sql_core::QueueStats;
+
+#[cfg(feature = "audit")]
+pub use audit::PgAuditSink;
+
+/// Channel `claim_stream` listens on for newly ready jobs.
+pub const JOB_CHANNEL: &str = "seesaw_jobs";
+
 /// PostgreSQL job store implementation.
 #[derive(Clone)]
 pub struct PgJobStore {
     pool: PgPool,
     default_lease_ms: i64,
+    /// Decoders for binary payloads; `None` reads only the JSONB column.
+    codecs: Option<PayloadCodecs>,
+    /// How often `claim_stream` polls when no notification arrives.
+    poll_interval: std::time::Duration,
+    /// How long a succeeded job's idempotency key blocks a replay.
+    replay_window: std::time::Duration,
+    /// How statements failing with transient errors are retried.
+    retry: RetryPolicy,
+    /// Whether claims skip queues in `drained_queues`.
+    draining: bool,
 }
 
 impl PgJobStore {
@@ -88,24 +333,112 @@ impl PgJobStore {
     /// # Default Settings
     ///
     /// - Lease timeout: 60 seconds
+    /// - Claim stream poll interval: 500 milliseconds
+    /// - Replay window: 7 days
+    /// - Transient errors: 5 attempts, 50ms backoff doubling up to 2s
     pub fn new(pool: PgPool) -> Self {
         Self {
             pool,
-            default_lease_ms: 60_000,
+            default_lease_ms: DEFAULT_LEASE_MS,
+            codecs: None,
+            poll_interval: CLAIM_POLL_INTERVAL,
+            replay_window: DEFAULT_REPLAY_WINDOW,
+            retry: transient::default_retry_policy(),
+            draining: false,
         }
     }
 
     /// Create a job store with custom lease timeout.
     ///
     /// The lease timeout determines how long a worker can hold a job
-    /// before it's considered abandoned.
+    /// before it's considered abandoned, for jobs without their own
+    /// `lease_ms`.
     pub fn with_lease_timeout(pool: PgPool, lease_ms: i64) -> Self {
         Self {
             pool,
             default_lease_ms: lease_ms,
+            codecs: None,
+            poll_interval: CLAIM_POLL_INTERVAL,
+            replay_window: DEFAULT_REPLAY_WINDOW,
+            retry: transient::default_retry_policy(),
+            draining: false,
         }
     }
 
+    /// Decode binary payloads from the `payload_bytes` and `codec` columns.
+    ///
+    /// Rows without a codec are read from the JSONB `payload` column as
+    /// before. A row whose codec is unknown or whose bytes do not decode is
+    /// parked instead of returned.
+    pub fn with_codecs(mut self, codecs: PayloadCodecs) -> Self {
+        self.codecs = Some(codecs);
+        self
+    }
+
+    /// Set how often `claim_stream` polls for ready jobs between
+    /// notifications.
+    ///
+    /// With the NOTIFY trigger installed this only bounds how late reclaimed
+    /// jobs, and scheduled or retried jobs due beyond the look-ahead of the
+    /// last claim, are picked up, so it can be generous.
+    pub fn with_poll_interval(mut self, interval: std::time::Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Set how long a succeeded job's idempotency key keeps a job with the
+    /// same key from being enqueued again.
+    ///
+    /// Requires the `completed_keys` schema from the [`replay`] module.
+    pub fn with_replay_window(mut self, window: std::time::Duration) -> Self {
+        self.replay_window = window;
+        self
+    }
+
+    /// Set how statements failing with transient errors (connection resets,
+    /// failovers, serialization failures) are retried.
+    ///
+    /// The default policy retries only [`is_transient_error`]s; pass
+    /// `RetryPolicy::new().with_max_attempts(1)` to surface every error at
+    /// once. See the [`transient`] module.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Skip jobs of queues [drained](Self::drain) in the `drained_queues`
+    /// table when claiming.
+    ///
+    /// Off by default, so databases without the table keep working. Enable
+    /// it on every worker before draining; see the [`drain`] module.
+    pub fn with_draining(mut self, enabled: bool) -> Self {
+        self.draining = enabled;
+        self
+    }
+
+    /// The statement claiming jobs, as reservations if `reserved`, for this
+    /// store's codecs and draining.
+    fn claim_statement(&self, reserved: bool) -> &'static str {
+        let statements = if self.codecs.is_some() {
+            &*CODEC_CLAIMS
+        } else {
+            &*CLAIMS
+        };
+        match (reserved, self.draining) {
+            (false, false) => &statements.claim,
+            (false, true) => &statements.draining_claim,
+            (true, false) => &statements.reserve,
+            (true, true) => &statements.draining_reserve,
+        }
+    }
+
+    /// Open a connection listening on [`JOB_CHANNEL`].
+    async fn listen(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(JOB_CHANNEL).await?;
+        Ok(listener)
+    }
+
     /// Get the underlying connection pool.
     pub fn pool(&self) -> &PgPool {
         &self.pool
@@ -116,180 +449,419 @@ impl PgJobStore {
 impl JobStore for PgJobStore {
     /// Claim ready jobs for execution.
     ///
-    /// Uses `FOR UPDATE SKIP LOCKED` for optimistic concurrency.
+    /// Uses `FOR UPDATE SKIP LOCKED` for optimistic concurrency. Each job is
+    /// leased for its own `lease_ms` if it has one.
     async fn claim_ready(&self, worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
-        let lease_expires_at = Utc::now() + Duration::milliseconds(self.default_lease_ms);
+        let query = self.claim_statement(false);
+        let lease_expires_at = lease_expiry(Utc::now(), self.default_lease_ms);
 
-        let rows = sqlx::query(
-            r#"
-            WITH claimable AS (
-                SELECT id
-                FROM jobs
-                WHERE status = 'pending'
-                  AND run_at <= NOW()
-                ORDER BY priority ASC, run_at ASC
-                LIMIT $1
-                FOR UPDATE SKIP LOCKED
-            )
-            UPDATE jobs
-            SET status = 'running',
-                worker_id = $2,
-                lease_expires_at = $3,
-                updated_at = NOW()
-            WHERE id IN (SELECT id FROM claimable)
-            RETURNING id, job_type, payload, version, attempt
-            "#,
-        )
-        .bind(limit)
-        .bind(worker_id)
-        .bind(lease_expires_at)
-        .fetch_all(&self.pool)
-        .await?;
+        self.claim_with("claim_ready", query, worker_id, limit, lease_expires_at).await
+    }
 
-        Ok(rows
-            .into_iter()
-            .map(|row| ClaimedJob {
+    /// Claim ready jobs, and list those due within `lookahead`.
+    ///
+    /// The listing is skipped when `limit` jobs were claimed.
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: std::time::Duration,
+    ) -> Result<LookaheadClaim> {
+        let jobs = self.claim_ready(worker_id, limit).await?;
+        if jobs.len() as i64 >= limit {
+            return Ok(LookaheadClaim {
+                jobs,
+                upcoming: Vec::new(),
+            });
+        }
+
+        let until = lookahead_until(Utc::now(), lookahead);
+        let pool = &self.pool;
+        let rows = self
+            .retrying("upcoming", move || async move {
+                Ok(sqlx::query(&QUERIES.upcoming)
+                    .bind(until)
+                    .bind(limit)
+                    .fetch_all(pool)
+                    .await?)
+            })
+            .await?;
+        let upcoming = rows
+            .iter()
+            .map(|row| UpcomingJob {
                 id: row.get("id"),
                 job_type: row.get("job_type"),
-                payload: row.get("payload"),
-                version: row.get("version"),
-                attempt: row.get("attempt"),
+                run_at: row.get("run_at"),
             })
-            .collect())
+            .collect();
+
+        Ok(LookaheadClaim { jobs, upcoming })
     }
 
     /// Mark a job as successfully completed.
-    async fn mark_succeeded(&self, job_id: Uuid) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE jobs
-            SET status = 'succeeded',
-                updated_at = NOW()
-            WHERE id = $1
-            "#,
-        )
-        .bind(job_id)
-        .execute(&self.pool)
-        .await?;
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+        let pool = &self.pool;
+        let result = self
+            .retrying("mark_succeeded", move || async move {
+                Ok(sqlx::query(&QUERIES.mark_succeeded)
+                    .bind(lease.job_id)
+                    .bind(lease.token)
+                    .execute(pool)
+                    .await?)
+            })
+            .await?;
 
-        Ok(())
+        fenced(lease, result.rows_affected())
     }
 
-    /// Mark a job as failed and handle retries.
+    /// Mark a job as failed.
     ///
     /// # Retry Logic
     ///
-    /// - Retryable failures: Schedules retry with exponential backoff (2^attempt seconds, max 1 hour)
+    /// - Retryable failures: Exponential backoff (2^attempt seconds, max 1 hour)
     /// - Non-retryable failures: Immediately moves to dead letter
     /// - Max retries exceeded: Moves to dead letter
-    async fn mark_failed(&self, job_id: Uuid, error: &str, kind: FailureKind) -> Result<()> {
-        let mut tx = self.pool.begin().await?;
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+        self.record_failure(lease, error, kind).await?;
+        Ok(())
+    }
 
-        // Fetch current job state
-        let job = sqlx::query("SELECT attempt, max_retries FROM jobs WHERE id = $1 FOR UPDATE")
-            .bind(job_id)
-            .fetch_one(&mut *tx)
+    /// Mark a job as failed, returning whether it was dead-lettered.
+    async fn record_failure(
+        &self,
+        lease: JobLease,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<bool> {
+        self.retrying("mark_failed", || self.fail_attempt(lease, error, kind)).await
+    }
+
+    /// Running jobs claimed by `worker_id`, with their current lease tokens.
+    ///
+    /// Unconfirmed reservations are left out.
+    async fn running_jobs(&self, worker_id: &str) -> Result<Vec<ClaimedJob>> {
+        let query = if self.codecs.is_some() {
+            &*CODEC_RUNNING_JOBS
+        } else {
+            &*RUNNING_JOBS
+        };
+        let pool = &self.pool;
+        let rows = self
+            .retrying("running_jobs", move || async move {
+                Ok(sqlx::query(query).bind(worker_id).fetch_all(pool).await?)
+            })
             .await?;
 
-        let attempt: i32 = job.get("attempt");
-        let max_retries: i32 = job.get("max_retries");
+        self.claimed_jobs(rows, |row| row.get("lease_token")).await
+    }
 
-        match kind {
-            FailureKind::Retryable if attempt < max_retries => {
-                // Schedule retry with exponential backoff
-                let delay_secs = 2i64.pow(attempt as u32).min(3600); // Max 1 hour
-                let retry_at = Utc::now() + Duration::seconds(delay_secs);
+    /// Extend the lease for a running job.
+    ///
+    /// Workers should call this periodically for long-running jobs
+    /// to prevent them from being reclaimed. The lease is extended by the
+    /// job's own `lease_ms` if it has one.
+    async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+        let pool = &self.pool;
+        let result = self
+            .retrying("heartbeat", move || async move {
+                Ok(sqlx::query(&QUERIES.heartbeat)
+                    .bind(lease_expiry(Utc::now(), self.default_lease_ms))
+                    .bind(lease.job_id)
+                    .bind(lease.token)
+                    .execute(pool)
+                    .await?)
+            })
+            .await?;
+
+        fenced(lease, result.rows_affected())
+    }
 
-                sqlx::query(
+    /// Park a running job, keeping its attempt count.
+    async fn park(&self, lease: JobLease, reason: &str) -> Result<()> {
+        let pool = &self.pool;
+        let result = self
+            .retrying("park", move || async move {
+                Ok(sqlx::query(
                     r#"
                     UPDATE jobs
-                    SET status = 'pending',
-                        run_at = $1,
-                        attempt = attempt + 1,
-                        error_message = $2,
-                        error_kind = 'retryable',
+                    SET status = 'parked',
+                        error_message = $3,
+                        reserved = FALSE,
                         worker_id = NULL,
                         lease_expires_at = NULL,
+                        lease_token = NULL,
                         updated_at = NOW()
-                    WHERE id = $3
+                    WHERE id = $1 AND lease_token = $2 AND status = 'running'
                     "#,
                 )
-                .bind(retry_at)
-                .bind(error)
-                .bind(job_id)
-                .execute(&mut *tx)
-                .await?;
-            }
-            _ => {
-                // No retries left or non-retryable failure - dead letter
-                sqlx::query(
+                .bind(lease.job_id)
+                .bind(lease.token)
+                .bind(reason)
+                .execute(pool)
+                .await?)
+            })
+            .await?;
+
+        fenced(lease, result.rows_affected())
+    }
+
+    /// Reserve ready jobs until `window` from now.
+    ///
+    /// Reserved jobs are running rows flagged `reserved`; one that is
+    /// neither confirmed nor released in time goes back to pending with
+    /// [`reclaim_expired`](PgJobStore::reclaim_expired), like any expired
+    /// lease.
+    async fn reserve(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        window: std::time::Duration,
+    ) -> Result<Vec<ClaimedJob>> {
+        let query = self.claim_statement(true);
+        let lease_expires_at = Utc::now() + chrono::Duration::from_std(window)?;
+
+        self.claim_with("reserve", query, worker_id, limit, lease_expires_at).await
+    }
+
+    /// Confirm a reservation, extending it to the job's full lease.
+    async fn confirm(&self, lease: JobLease) -> Result<()> {
+        let pool = &self.pool;
+        let result = self
+            .retrying("confirm", move || async move {
+                Ok(sqlx::query(&CONFIRM)
+                    .bind(lease_expiry(Utc::now(), self.default_lease_ms))
+                    .bind(lease.job_id)
+                    .bind(lease.token)
+                    .execute(pool)
+                    .await?)
+            })
+            .await?;
+
+        fenced(lease, result.rows_affected())
+    }
+
+    /// Put a reserved job back to pending, ready at once, with its attempt
+    /// and error untouched.
+    async fn release(&self, lease: JobLease) -> Result<()> {
+        let pool = &self.pool;
+        let result = self
+            .retrying("release", move || async move {
+                Ok(sqlx::query(
                     r#"
                     UPDATE jobs
-                    SET status = 'dead_letter',
-                        error_message = $1,
-                        error_kind = $2,
+                    SET status = 'pending',
+                        reserved = FALSE,
+                        worker_id = NULL,
+                        lease_expires_at = NULL,
+                        lease_token = NULL,
                         updated_at = NOW()
-                    WHERE id = $3
+                    WHERE id = $1 AND lease_token = $2 AND status = 'running' AND reserved
                     "#,
                 )
-                .bind(error)
-                .bind(match kind {
-                    FailureKind::Retryable => "retryable",
-                    FailureKind::NonRetryable => "non_retryable",
-                })
-                .bind(job_id)
-                .execute(&mut *tx)
-                .await?;
-            }
-        }
+                .bind(lease.job_id)
+                .bind(lease.token)
+                .execute(pool)
+                .await?)
+            })
+            .await?;
 
-        tx.commit().await?;
-        Ok(())
+        fenced(lease, result.rows_affected())
     }
 
-    /// Extend the lease for a running job.
+    /// Stream claimed jobs, woken by NOTIFY on [`JOB_CHANNEL`].
     ///
-    /// Workers should call this periodically for long-running jobs
-    /// to prevent them from being reclaimed.
-    async fn heartbeat(&self, job_id: Uuid) -> Result<()> {
-        let lease_expires_at = Utc::now() + Duration::milliseconds(self.default_lease_ms);
+    /// Each stream holds one dedicated listener connection, opened from the
+    /// pool's options on first poll. Every wake-up claims a batch of up to
+    /// `concurrency` jobs in a single statement; notifications that arrive
+    /// while a batch is being handed out are covered by the next claim. An
+    /// empty claim waits for a notification, the `run_at` of the next
+    /// upcoming job, or the poll interval, whichever comes first. If the
+    /// listener connection fails, the error is yielded and the stream
+    /// reconnects on the next poll.
+    fn claim_stream<'a>(
+        &'a self,
+        worker_id: &'a str,
+        concurrency: usize,
+    ) -> BoxStream<'a, Result<ClaimedJob>> {
+        struct State {
+            listener: Option<PgListener>,
+            ready: VecDeque<ClaimedJob>,
+        }
 
-        sqlx::query(
-            r#"
-            UPDATE jobs
-            SET lease_expires_at = $1,
-                updated_at = NOW()
-            WHERE id = $2 AND status = 'running'
-            "#,
-        )
-        .bind(lease_expires_at)
-        .bind(job_id)
-        .execute(&self.pool)
-        .await?;
+        let limit = concurrency.max(1) as i64;
+        let state = State {
+            listener: None,
+            ready: VecDeque::new(),
+        };
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(job) = state.ready.pop_front() {
+                    return Some((Ok(job), state));
+                }
+                let listener = match &mut state.listener {
+                    Some(listener) => listener,
+                    None => match self.listen().await {
+                        Ok(listener) => state.listener.insert(listener),
+                        Err(e) => {
+                            tokio::time::sleep(self.poll_interval).await;
+                            return Some((Err(e), state));
+                        }
+                    },
+                };
 
-        Ok(())
+                // Claim before waiting: jobs enqueued before LISTEN took
+                // effect were announced to no one
+                let wait = match self
+                    .claim_ready_with_lookahead(worker_id, limit, self.poll_interval)
+                    .await
+                {
+                    Ok(claim) if !claim.jobs.is_empty() => {
+                        state.ready.extend(claim.jobs);
+                        continue;
+                    }
+                    Ok(claim) => claim.next_poll_in(self.poll_interval),
+                    Err(e) => {
+                        tokio::time::sleep(self.poll_interval).await;
+                        return Some((Err(e), state));
+                    }
+                };
+
+                match tokio::time::timeout(wait, listener.recv()).await {
+                    // Notified, a job came due, or time for a fallback poll
+                    Ok(Ok(_)) | Err(_) => {}
+                    Ok(Err(e)) => {
+                        state.listener = None;
+                        return Some((Err(e.into()), state));
+                    }
+                }
+            }
+        })
+        .boxed()
     }
 }
 
 /// Utility functions for job management.
 impl PgJobStore {
+    /// Run a claim or reserve `query`, leasing the jobs until
+    /// `lease_expires_at` (or their own `lease_ms`, if the query uses it).
+    ///
+    /// Jobs whose binary payload cannot be decoded are parked instead of
+    /// returned.
+    async fn claim_with(
+        &self,
+        operation: &'static str,
+        query: &str,
+        worker_id: &str,
+        limit: i64,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Vec<ClaimedJob>> {
+        let lease_token = Uuid::new_v4();
+        let pool = &self.pool;
+        let rows = self
+            .retrying(operation, move || async move {
+                Ok(sqlx::query(query)
+                    .bind(limit)
+                    .bind(worker_id)
+                    .bind(lease_expires_at)
+                    .bind(lease_token)
+                    .fetch_all(pool)
+                    .await?)
+            })
+            .await?;
+
+        self.claimed_jobs(rows, |_| lease_token).await
+    }
+
+    /// Turn claimed `rows` into jobs leased with the token `lease_token`
+    /// reads from each, decoding binary payloads.
+    ///
+    /// Jobs whose binary payload cannot be decoded are parked instead of
+    /// returned.
+    async fn claimed_jobs(
+        &self,
+        rows: Vec<PgRow>,
+        lease_token: impl Fn(&PgRow) -> Uuid,
+    ) -> Result<Vec<ClaimedJob>> {
+        let mut jobs = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut job = ClaimedJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                lease_token: lease_token(&row),
+            };
+            if let Some(codecs) = &self.codecs {
+                let codec: Option<String> = row.get("codec");
+                if let Some(codec) = codec {
+                    let bytes: Vec<u8> = row.get("payload_bytes");
+                    match codecs.decode(&codec, &bytes) {
+                        Ok(payload) => job.payload = payload,
+                        Err(e) => {
+                            self.park(job.lease(), &format!("{:#}", e)).await?;
+                            continue;
+                        }
+                    }
+                }
+            }
+            jobs.push(job);
+        }
+        Ok(jobs)
+    }
+
+    /// Retry or dead-letter the job `lease` holds, in one transaction.
+    /// Returns whether it was dead-lettered.
+    async fn fail_attempt(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<bool> {
+        let job_id = lease.job_id;
+        let mut tx = self.pool.begin().await?;
+
+        // Fetch current job state
+        let job = sqlx::query(&QUERIES.lock_for_failure)
+            .bind(job_id)
+            .bind(lease.token)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(LeaseLost { job_id })?;
+
+        let attempt: i32 = job.get("attempt");
+        let max_retries: i32 = job.get("max_retries");
+
+        let dead_lettered = match failure_outcome(kind, attempt, max_retries, Utc::now()) {
+            FailureOutcome::Retry { run_at } => {
+                sqlx::query(&QUERIES.retry)
+                    .bind(run_at)
+                    .bind(error)
+                    .bind(job_id)
+                    .execute(&mut *tx)
+                    .await?;
+                false
+            }
+            FailureOutcome::DeadLetter => {
+                sqlx::query(&QUERIES.dead_letter)
+                    .bind(error)
+                    .bind(error_kind_label(kind))
+                    .bind(job_id)
+                    .execute(&mut *tx)
+                    .await?;
+                true
+            }
+        };
+
+        tx.commit().await?;
+        Ok(dead_lettered)
+    }
+
     /// Reclaim abandoned jobs (lease expired).
     ///
     /// This should be run periodically by a maintenance worker.
     pub async fn reclaim_expired(&self) -> Result<u64> {
-        let result = sqlx::query(
-            r#"
-            UPDATE jobs
-            SET status = 'pending',
-                worker_id = NULL,
-                lease_expires_at = NULL,
-                updated_at = NOW()
-            WHERE status = 'running'
-              AND lease_expires_at < NOW()
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        let pool = &self.pool;
+        let result = self
+            .retrying("reclaim_expired", move || async move {
+                Ok(sqlx::query(&QUERIES.reclaim_expired).execute(pool).await?)
+            })
+            .await?;
 
         Ok(result.rows_affected())
     }
@@ -300,52 +872,334 @@ impl PgJobStore {
     ///
     /// * `older_than` - Delete jobs completed before this timestamp
     pub async fn cleanup_succeeded(&self, older_than: DateTime<Utc>) -> Result<u64> {
-        let result = sqlx::query(
-            r#"
-            DELETE FROM jobs
-            WHERE status = 'succeeded'
-              AND updated_at < $1
-            "#,
-        )
-        .bind(older_than)
-        .execute(&self.pool)
-        .await?;
+        let result = sqlx::query(&QUERIES.cleanup_succeeded)
+            .bind(older_than)
+            .execute(&self.pool)
+            .await?;
 
         Ok(result.rows_affected())
     }
 
     /// Get statistics about job queue health.
     pub async fn stats(&self) -> Result<QueueStats> {
-        let row = sqlx::query(
+        let row = sqlx::query(&QUERIES.stats).fetch_one(&self.pool).await?;
+
+        Ok(queue_stats(&row))
+    }
+}
+
+#[async_trait]
+impl JobAdmin for PgJobStore {
+    async fn stats(&self) -> Result<QueueStats> {
+        PgJobStore::stats(self).await
+    }
+
+    async fn job_type_stats(&self) -> Result<Vec<JobTypeStats>> {
+        let rows = sqlx::query(&QUERIES.job_type_stats)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| JobTypeStats {
+                job_type: row.get("job_type"),
+                stats: queue_stats(row),
+                retrying: row.get("retrying"),
+            })
+            .collect())
+    }
+
+    async fn dead_letters(&self, limit: i64, offset: i64) -> Result<Vec<DeadLetterJob>> {
+        let rows = sqlx::query(&QUERIES.dead_letters)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| DeadLetterJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                error_message: row.get("error_message"),
+                error_kind: row.get("error_kind"),
+                failed_at: row.get("failed_at"),
+            })
+            .collect())
+    }
+
+    async fn retry_dead_letter(&self, job_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(&QUERIES.retry_dead_letter)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_parked(&self, limit: i64, offset: i64) -> Result<Vec<ParkedJob>> {
+        let rows = sqlx::query(
             r#"
-            SELECT
-                COUNT(*) FILTER (WHERE status = 'pending') as pending,
-                COUNT(*) FILTER (WHERE status = 'running') as running,
-                COUNT(*) FILTER (WHERE status = 'succeeded') as succeeded,
-                COUNT(*) FILTER (WHERE status = 'failed') as failed,
-                COUNT(*) FILTER (WHERE status = 'dead_letter') as dead_letter
+            SELECT id, job_type, payload, version, attempt, error_message,
+                   updated_at AS parked_at
             FROM jobs
+            WHERE status = 'parked'
+            ORDER BY updated_at DESC
+            LIMIT $1 OFFSET $2
             "#,
         )
-        .fetch_one(&self.pool)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(QueueStats {
-            pending: row.get("pending"),
-            running: row.get("running"),
-            succeeded: row.get("succeeded"),
-            failed: row.get("failed"),
-            dead_letter: row.get("dead_letter"),
-        })
+        Ok(rows
+            .iter()
+            .map(|row| ParkedJob {
+                id: row.get("id"),
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                attempt: row.get("attempt"),
+                reason: row.get("error_message"),
+                parked_at: row.get("parked_at"),
+            })
+            .collect())
+    }
+
+    async fn unpark(&self, job_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'pending', run_at = NOW(), error_message = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'parked'
+            "#,
+        )
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn cancel(&self, job_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(&QUERIES.cancel)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
     }
 }
 
-/// Job queue statistics.
-#[derive(Debug, Clone, Copy)]
-pub struct QueueStats {
-    pub pending: i64,
-    pub running: i64,
-    pub succeeded: i64,
-    pub failed: i64,
-    pub dead_letter: i64,
+/// Fail with [`LeaseLost`] if a statement fenced by `lease` changed no row.
+fn fenced(lease: JobLease, rows_affected: u64) -> Result<()> {
+    if rows_affected == 0 {
+        return Err(LeaseLost {
+            job_id: lease.job_id,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Read the status counts of a `stats` or `job_type_stats` row.
+fn queue_stats(row: &sqlx::postgres::PgRow) -> QueueStats {
+    QueueStats {
+        pending: row.get("pending"),
+        running: row.get("running"),
+        succeeded: row.get("succeeded"),
+        failed: row.get("failed"),
+        dead_letter: row.get("dead_letter"),
+    }
+}
+
+/// SQL syntax of Postgres.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PgDialect;
+
+impl SqlDialect for PgDialect {
+    fn param(&self, n: usize) -> String {
+        format!("${n}")
+    }
+
+    fn now(&self) -> &'static str {
+        "NOW()"
+    }
+
+    fn add_millis(&self, time: &str, millis: &str) -> String {
+        format!("{time} + {millis} * INTERVAL '1 millisecond'")
+    }
+
+    fn cast(&self, expr: String, sql_type: &str) -> String {
+        format!("{expr}::{sql_type}")
+    }
+
+    /// Claims in one statement: `UPDATE ... RETURNING` over a
+    /// `FOR UPDATE SKIP LOCKED` select.
+    fn claim_ready(&self, columns: &str) -> ClaimQuery {
+        ClaimQuery::Returning(claim_statement(
+            columns,
+            &job_lease_expiry(self, "$3".to_string()),
+            false,
+            false,
+        ))
+    }
+}
+
+/// `UPDATE ... RETURNING` claiming up to `$1` ready jobs for worker `$2`
+/// with lease token `$4`, leased until `lease_expires_at`, marked as
+/// unconfirmed reservations if `reserved`, and skipping drained queues if
+/// `draining`.
+fn claim_statement(columns: &str, lease_expires_at: &str, reserved: bool, draining: bool) -> String {
+    let drained = if draining {
+        "AND job_type NOT IN (SELECT queue FROM drained_queues)"
+    } else {
+        ""
+    };
+    format!(
+        r#"
+        WITH claimable AS (
+            SELECT id
+            FROM jobs
+            WHERE status = 'pending'
+              AND run_at <= NOW()
+              {drained}
+            ORDER BY priority ASC, run_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE jobs
+        SET status = 'running',
+            worker_id = $2,
+            lease_expires_at = {lease_expires_at},
+            lease_token = $4,
+            reserved = {reserved},
+            updated_at = NOW()
+        WHERE id IN (SELECT id FROM claimable)
+        RETURNING {columns}
+        "#,
+    )
+}
+
+static QUERIES: LazyLock<JobQueries<PgDialect>> = LazyLock::new(|| JobQueries::new(PgDialect));
+
+/// Claim and reservation statements returning one set of columns.
+struct ClaimStatements {
+    claim: String,
+    reserve: String,
+    draining_claim: String,
+    draining_reserve: String,
+}
+
+impl ClaimStatements {
+    /// Claims leased for the job's own `lease_ms` or `$3`; reservations
+    /// leased until `$3`, whatever the job's own lease.
+    fn new(columns: &str) -> Self {
+        let lease_expires_at = job_lease_expiry(&PgDialect, "$3".to_string());
+        Self {
+            claim: claim_statement(columns, &lease_expires_at, false, false),
+            reserve: claim_statement(columns, "$3", true, false),
+            draining_claim: claim_statement(columns, &lease_expires_at, false, true),
+            draining_reserve: claim_statement(columns, "$3", true, true),
+        }
+    }
+}
+
+static CLAIMS: LazyLock<ClaimStatements> = LazyLock::new(|| ClaimStatements::new(CLAIM_COLUMNS));
+
+/// Claims for stores with codecs, also returning the binary payload columns.
+static CODEC_CLAIMS: LazyLock<ClaimStatements> =
+    LazyLock::new(|| ClaimStatements::new(&format!("{CLAIM_COLUMNS}, payload_bytes, codec")));
+
+/// Confirm a reservation. Binds: default lease expiry, job ID, lease token.
+static CONFIRM: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        "UPDATE jobs SET reserved = FALSE, lease_expires_at = {}, updated_at = NOW() \
+         WHERE id = $2 AND lease_token = $3 AND status = 'running' AND reserved",
+        job_lease_expiry(&PgDialect, "$1".to_string())
+    )
+});
+
+/// Running, confirmed jobs of worker `$1`, with their lease tokens.
+static RUNNING_JOBS: LazyLock<String> =
+    LazyLock::new(|| running_jobs_statement(&format!("{CLAIM_COLUMNS}, lease_token")));
+
+/// Running jobs for stores with codecs.
+static CODEC_RUNNING_JOBS: LazyLock<String> = LazyLock::new(|| {
+    running_jobs_statement(&format!("{CLAIM_COLUMNS}, lease_token, payload_bytes, codec"))
+});
+
+fn running_jobs_statement(columns: &str) -> String {
+    format!(
+        "SELECT {columns} FROM jobs \
+         WHERE worker_id = $1 AND status = 'running' AND NOT reserved \
+         ORDER BY updated_at"
+    )
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[tokio::test]
+    async fn test_parked_job_keeps_its_attempt_until_unparked() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let id = db.enqueue_test_job("invoice:render", json!({ "n": 1 })).await?;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+        store
+            .mark_failed(claimed.lease(), "timed out", FailureKind::Retryable)
+            .await?;
+        sqlx::query("UPDATE jobs SET run_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(db.pool())
+            .await?;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+
+        store.park(claimed.lease(), "no handler for invoice:render").await?;
+
+        let parked = store.list_parked(10, 0).await?;
+        assert_eq!(parked.len(), 1);
+        assert_eq!((parked[0].id, parked[0].attempt), (id, 2));
+        assert_eq!(parked[0].reason.as_deref(), Some("no handler for invoice:render"));
+        assert!(store.claim_ready("worker-1", 1).await?.is_empty());
+        let error = store.park(claimed.lease(), "again").await.unwrap_err();
+        assert!(LeaseLost::is(&error));
+
+        assert!(store.unpark(id).await?);
+        assert!(!store.unpark(id).await?);
+        assert!(store.list_parked(10, 0).await?.is_empty());
+        let claimed = store.claim_ready("worker-2", 1).await?;
+        assert_eq!((claimed[0].id, claimed[0].attempt), (id, 2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_payload_is_parked_on_claim() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store().with_codecs(PayloadCodecs::new());
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO jobs (id, job_type, payload, payload_bytes, codec) \
+             VALUES ($1, 'invoice:render', 'null', '\\x00', 'rot13')",
+        )
+        .bind(id)
+        .execute(db.pool())
+        .await?;
+
+        assert!(store.claim_ready("worker-1", 10).await?.is_empty());
+
+        let parked = store.list_parked(10, 0).await?;
+        assert_eq!(parked.len(), 1);
+        assert_eq!((parked[0].id, parked[0].attempt), (id, 1));
+        assert!(parked[0].reason.as_deref().unwrap().contains("rot13"));
+        Ok(())
+    }
 }
@@ -0,0 +1,20 @@
+//! Store metrics through the [`metrics`](https://docs.rs/metrics) facade
+//! (`metrics` feature).
+//!
+//! | Metric                              | Kind    | Labels      |
+//! |-------------------------------------|---------|-------------|
+//! | `seesaw_pg_transient_retries_total` | counter | `operation` |
+//!
+//! `operation` is the store method whose statement was retried, e.g.
+//! `claim_ready` or `heartbeat`.
+
+#[cfg(feature = "metrics")]
+use ::metrics::counter;
+
+/// A statement failed with a transient error and is about to be retried.
+pub(crate) fn retried(operation: &'static str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_pg_transient_retries_total", "operation" => operation).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = operation;
+}
@@ -0,0 +1,243 @@
+//! PostgreSQL event outbox for durable, at-least-once event delivery.
+//!
+//! Events written here survive a crash between emit and decide: an
+//! [`OutboxPublisher`](seesaw_outbox::OutboxPublisher) claims them, emits them
+//! to the runtime, and (with acknowledgment enabled) marks them published only
+//! after the runtime processed them.
+//!
+//! Claims are leases: an entry claimed by a publisher that crashes becomes
+//! claimable again when its lease expires.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE event_outbox (
+//!     id UUID PRIMARY KEY,
+//!     event_type TEXT NOT NULL,
+//!     payload JSONB NOT NULL,
+//!     correlation_id UUID,
+//!     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+//!     claimed_until TIMESTAMPTZ,
+//!     published_at TIMESTAMPTZ
+//! );
+//!
+//! CREATE INDEX idx_event_outbox_unpublished ON event_outbox (created_at)
+//!     WHERE published_at IS NULL;
+//! ```
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use seesaw_core::CorrelationId;
+use seesaw_outbox::{OutboxEntry, OutboxEvent, OutboxReader, OutboxWriter};
+use sqlx::{PgExecutor, PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// PostgreSQL outbox reader and durable emitter.
+#[derive(Clone)]
+pub struct PgOutbox {
+    pool: PgPool,
+    claim_lease_ms: i64,
+}
+
+impl PgOutbox {
+    /// Create a new PostgreSQL outbox.
+    ///
+    /// # Default Settings
+    ///
+    /// - Claim lease: 60 seconds
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            claim_lease_ms: 60_000,
+        }
+    }
+
+    /// Create an outbox with a custom claim lease.
+    ///
+    /// The lease should exceed the publisher's `ack_timeout`, or entries
+    /// still being processed may be claimed again by another publisher.
+    pub fn with_lease_timeout(pool: PgPool, lease_ms: i64) -> Self {
+        Self {
+            pool,
+            claim_lease_ms: lease_ms,
+        }
+    }
+
+    /// Get the underlying connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Durably emit an event outside of any business transaction.
+    ///
+    /// Use this instead of `EventBus::emit` for workflow triggers that must
+    /// not be lost. Effects that also write business data should use
+    /// [`PgOutboxWriter`] to write in the same transaction.
+    pub async fn emit<E: OutboxEvent>(
+        &self,
+        event: &E,
+        correlation_id: CorrelationId,
+    ) -> Result<Uuid> {
+        insert_event(&self.pool, event, correlation_id).await
+    }
+}
+
+#[async_trait]
+impl OutboxReader for PgOutbox {
+    /// Claim unpublished entries whose lease is free or expired.
+    ///
+    /// Uses `FOR UPDATE SKIP LOCKED` so concurrent publishers claim disjoint entries.
+    async fn claim_unpublished(&self, limit: usize) -> Result<Vec<OutboxEntry>> {
+        let claimed_until = Utc::now() + Duration::milliseconds(self.claim_lease_ms);
+
+        let rows = sqlx::query(
+            r#"
+            WITH claimable AS (
+                SELECT id
+                FROM event_outbox
+                WHERE published_at IS NULL
+                  AND (claimed_until IS NULL OR claimed_until < NOW())
+                ORDER BY created_at ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE event_outbox
+            SET claimed_until = $2
+            WHERE id IN (SELECT id FROM claimable)
+            RETURNING id, event_type, payload, correlation_id, created_at, published_at
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(claimed_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut entries: Vec<OutboxEntry> = rows
+            .into_iter()
+            .map(|row| {
+                let cid: Option<Uuid> = row.get("correlation_id");
+                OutboxEntry {
+                    id: row.get("id"),
+                    event_type: row.get("event_type"),
+                    payload: row.get("payload"),
+                    correlation_id: CorrelationId::from(cid),
+                    created_at: row.get("created_at"),
+                    published_at: row.get("published_at"),
+                }
+            })
+            .collect();
+
+        // RETURNING does not preserve the CTE's order
+        entries.sort_by_key(|entry| entry.created_at);
+        Ok(entries)
+    }
+
+    /// Mark entries as published (acknowledged).
+    async fn mark_published(&self, ids: &[Uuid]) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE event_outbox
+            SET published_at = NOW(),
+                claimed_until = NULL
+            WHERE id = ANY($1)
+            "#,
+        )
+        .bind(ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete published entries older than the given timestamp.
+    async fn cleanup_published(&self, older_than: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM event_outbox
+            WHERE published_at IS NOT NULL
+              AND published_at < $1
+            "#,
+        )
+        .bind(older_than)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Writes outbox entries inside an existing transaction.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut tx = pool.begin().await?;
+/// let notification = Notification::create(&cmd, &mut tx).await?;
+///
+/// PgOutboxWriter::new(&mut tx)
+///     .write_event(&NotificationCreated { id: notification.id }, cid)
+///     .await?;
+///
+/// tx.commit().await?;
+/// ```
+pub struct PgOutboxWriter<'a> {
+    tx: &'a mut Transaction<'static, Postgres>,
+}
+
+impl<'a> PgOutboxWriter<'a> {
+    /// Create a writer bound to a transaction.
+    pub fn new(tx: &'a mut Transaction<'static, Postgres>) -> Self {
+        Self { tx }
+    }
+}
+
+#[async_trait]
+impl OutboxWriter for PgOutboxWriter<'_> {
+    async fn write_event<E: OutboxEvent + Send + Sync>(
+        &mut self,
+        event: &E,
+        correlation_id: CorrelationId,
+    ) -> Result<Uuid> {
+        insert_event(&mut **self.tx, event, correlation_id).await
+    }
+}
+
+/// Utility functions for sharing the job store's connection pool.
+impl PgJobStore {
+    /// Create an outbox backed by this store's connection pool.
+    pub fn outbox(&self) -> PgOutbox {
+        PgOutbox::new(self.pool().clone())
+    }
+}
+
+async fn insert_event<'e, E: OutboxEvent>(
+    executor: impl PgExecutor<'e>,
+    event: &E,
+    correlation_id: CorrelationId,
+) -> Result<Uuid> {
+    let id = Uuid::new_v4();
+    let payload = serde_json::to_value(event)?;
+    let cid: Option<Uuid> = if correlation_id.is_none() {
+        None
+    } else {
+        Some(correlation_id.into_inner())
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_outbox (id, event_type, payload, correlation_id)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(id)
+    .bind(E::event_type())
+    .bind(payload)
+    .bind(cid)
+    .execute(executor)
+    .await?;
+
+    Ok(id)
+}
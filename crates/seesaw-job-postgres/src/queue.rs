@@ -0,0 +1,249 @@
+//! The enqueueing side of the store: [`JobQueue`] for the dispatcher.
+//!
+//! One `PgJobStore` can serve the engine routing background and scheduled
+//! commands and the workers claiming them:
+//!
+//! ```rust,ignore
+//! let store = Arc::new(PgJobStore::new(pool));
+//!
+//! let engine = EngineBuilder::new(deps.clone())
+//!     .with_job_queue(store.clone())
+//!     .build();
+//! let worker = JobWorker::new(store, registry, Dispatcher::new(deps, engine.bus().clone()));
+//! ```
+//!
+//! An effect that writes business data can enqueue its follow-up commands in
+//! the same transaction with [`PgJobStore::enqueue_in_tx`], so the row and
+//! the job commit or roll back together, without an outbox in between:
+//!
+//! ```rust,ignore
+//! let mut tx = ctx.deps().db.begin().await?;
+//! let order = Order::ship(&cmd, &mut tx).await?;
+//! ctx.deps()
+//!     .jobs
+//!     .enqueue_in_tx(&mut tx, &SendShippingEmail { order_id: order.id })
+//!     .await?;
+//! tx.commit().await?;
+//! ```
+//!
+//! Every [`JobSpec`] field is persisted. `JobSpec` priorities run higher
+//! first while the `priority` column runs lower first, so the priority is
+//! stored negated: a command with priority 10 is stored as -10.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use seesaw_core::{Command, ExecutionMode, JobQueue, JobSpec};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::admin::NewJob;
+use crate::PgJobStore;
+
+impl NewJob {
+    /// The row for a command the dispatcher routed to the queue.
+    pub(crate) fn from_spec(payload: serde_json::Value, spec: JobSpec) -> Self {
+        Self {
+            job_type: spec.job_type.to_string(),
+            payload,
+            version: spec.version,
+            max_retries: spec.max_retries,
+            priority: spec.priority.saturating_neg(),
+            run_at: None,
+            lease_ms: spec.lease_ms,
+            idempotency_key: spec.idempotency_key,
+        }
+    }
+}
+
+impl PgJobStore {
+    /// Enqueue a background or scheduled `command` on `tx`, typically the
+    /// caller's open transaction, instead of through the pool.
+    ///
+    /// The job only becomes visible to workers once `tx` commits, and is
+    /// discarded if it rolls back. Inline and recurring commands are
+    /// rejected.
+    pub async fn enqueue_in_tx<C: Command>(
+        &self,
+        tx: &mut PgConnection,
+        command: &C,
+    ) -> Result<Uuid> {
+        let type_name = std::any::type_name::<C>();
+        let run_at = match command.execution_mode() {
+            ExecutionMode::Background => None,
+            ExecutionMode::Scheduled { run_at } => Some(run_at),
+            mode => bail!("{type_name} uses {mode:?} execution mode and cannot be enqueued"),
+        };
+        let spec = command
+            .job_spec()
+            .ok_or_else(|| anyhow!("{type_name} did not provide job_spec()"))?;
+        let payload = command
+            .serialize_to_json()
+            .ok_or_else(|| anyhow!("{type_name} could not be serialized"))?;
+
+        let mut job = NewJob::from_spec(payload, spec);
+        job.run_at = run_at;
+        self.insert_job_on(tx, &job).await
+    }
+}
+
+#[async_trait]
+impl JobQueue for PgJobStore {
+    /// Insert a pending job, ready now.
+    ///
+    /// With an idempotency key, returns the ID of the job already holding
+    /// it instead; see [`replay`](crate::replay).
+    async fn enqueue(&self, payload: serde_json::Value, spec: JobSpec) -> Result<Uuid> {
+        self.insert_job(&NewJob::from_spec(payload, spec)).await
+    }
+
+    /// Insert a pending job, ready at `run_at`.
+    async fn schedule(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        self.insert_job(&NewJob::from_spec(payload, spec).with_run_at(run_at))
+            .await
+    }
+
+    /// Store a recurring schedule and enqueue its first occurrence; see
+    /// [`recurring`](crate::recurring).
+    ///
+    /// Fails without storing anything if `cron` does not parse or `tz` is
+    /// not a time zone Postgres knows.
+    async fn schedule_recurring(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        cron: &str,
+        tz: &str,
+    ) -> Result<Uuid> {
+        self.insert_recurring(payload, spec, cron, tz).await
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::JobStore;
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[derive(Debug, Clone)]
+    struct SendShippingEmail {
+        order_id: u32,
+        mode: ExecutionMode,
+    }
+
+    impl SendShippingEmail {
+        fn new(order_id: u32, mode: ExecutionMode) -> Self {
+            Self { order_id, mode }
+        }
+    }
+
+    impl Command for SendShippingEmail {
+        fn execution_mode(&self) -> ExecutionMode {
+            self.mode
+        }
+
+        fn job_spec(&self) -> Option<JobSpec> {
+            Some(JobSpec::new("email:shipping"))
+        }
+
+        fn serialize_to_json(&self) -> Option<serde_json::Value> {
+            Some(json!({ "order_id": self.order_id }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_persists_every_spec_field() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let spec = JobSpec::new("invoice:render")
+            .with_version(3)
+            .with_max_retries(7)
+            .with_priority(10)
+            .with_lease_ms(300_000)
+            .with_idempotency_key("invoice:42");
+
+        let id = store.enqueue(json!({ "invoice": 42 }), spec.clone()).await?;
+
+        assert_eq!(store.enqueue(json!({ "invoice": 42 }), spec).await?, id);
+        let job = store.job(id).await?.unwrap();
+        assert_eq!(job.job_type, "invoice:render");
+        assert_eq!(job.payload, json!({ "invoice": 42 }));
+        assert_eq!((job.version, job.max_retries, job.priority), (3, 7, 10));
+        assert!(job.run_at <= Utc::now());
+
+        store.claim_ready("worker-1", 1).await?;
+        let leased = store.job(id).await?.unwrap().lease_expires_at.unwrap();
+        assert!(leased > Utc::now() + chrono::Duration::seconds(240));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schedule_waits_for_run_at() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let run_at = Utc::now() + chrono::Duration::minutes(30);
+
+        let id = store
+            .schedule(json!({}), JobSpec::new("reminder:send"), run_at)
+            .await?;
+
+        let job = store.job(id).await?.unwrap();
+        assert_eq!(job.run_at.timestamp_micros(), run_at.timestamp_micros());
+        assert!(store.claim_ready("worker-1", 1).await?.is_empty());
+        db.advance_leases(std::time::Duration::from_secs(1800)).await?;
+        assert_eq!(store.claim_ready("worker-1", 1).await?[0].id, id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_in_tx_commits_and_rolls_back_with_tx() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let background = SendShippingEmail::new(1, ExecutionMode::Background);
+
+        let mut tx = db.pool().begin().await?;
+        store.enqueue_in_tx(&mut tx, &background).await?;
+        tx.rollback().await?;
+        assert!(db.snapshot().await?.is_empty());
+
+        let run_at = Utc::now() + chrono::Duration::minutes(5);
+        let scheduled = SendShippingEmail::new(2, ExecutionMode::Scheduled { run_at });
+        let mut tx = db.pool().begin().await?;
+        let id = store.enqueue_in_tx(&mut tx, &background).await?;
+        let later = store.enqueue_in_tx(&mut tx, &scheduled).await?;
+        assert!(store.claim_ready("worker-1", 10).await?.is_empty());
+        tx.commit().await?;
+
+        let claimed = store.claim_ready("worker-1", 10).await?;
+        assert_eq!(claimed.iter().map(|job| job.id).collect::<Vec<_>>(), [id]);
+        assert_eq!(claimed[0].payload, json!({ "order_id": 1 }));
+        let later = store.job(later).await?.unwrap();
+        assert_eq!(later.run_at.timestamp_micros(), run_at.timestamp_micros());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_in_tx_rejects_inline_and_recurring() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let recurring = ExecutionMode::Recurring {
+            cron: "0 9 * * *",
+            tz: "UTC",
+        };
+        let mut tx = db.pool().begin().await?;
+
+        for mode in [ExecutionMode::Inline, recurring] {
+            let command = SendShippingEmail::new(1, mode);
+            let error = store.enqueue_in_tx(&mut tx, &command).await.unwrap_err();
+            assert!(error.to_string().contains("cannot be enqueued"), "{error}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,606 @@
+//! Recurring jobs, for commands with
+//! [`ExecutionMode::Recurring`](seesaw_core::ExecutionMode::Recurring).
+//!
+//! [`JobQueue::schedule_recurring`](seesaw_core::JobQueue::schedule_recurring)
+//! validates the cron expression and time zone, stores the command in a
+//! `recurring_jobs` table and enqueues its first occurrence as a pending
+//! job. Only one occurrence of a schedule is in `jobs` at a time:
+//! [`PgJobStore::materialize_recurring`] enqueues the next one once the
+//! current one is due. Run it periodically, like
+//! [`reclaim_expired`](PgJobStore::reclaim_expired):
+//!
+//! ```rust,ignore
+//! let mut interval = tokio::time::interval(Duration::from_secs(30));
+//! loop {
+//!     interval.tick().await;
+//!     store.materialize_recurring().await?;
+//! }
+//! ```
+//!
+//! A schedule that falls behind, e.g. while no maintenance task ran, catches
+//! up with one job rather than one per missed occurrence. Registering a
+//! command again with the same idempotency key updates its schedule and
+//! replaces its pending occurrence.
+//!
+//! Cron expressions have the five standard fields, minute, hour, day of
+//! month, month and day of week, each a `*`, a value, a range or a list of
+//! them, optionally with a `/step`. Months and days of week may be named
+//! (`JAN`, `MON`), and `@hourly`, `@daily`, `@weekly`, `@monthly` and
+//! `@yearly` are accepted. When both the day of month and the day of week
+//! are restricted, either matching is enough, as in cron. Occurrences are
+//! computed in local time of the IANA time zone `tz` (e.g.
+//! `Europe/Berlin`), which Postgres converts to UTC.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE recurring_jobs (
+//!     id UUID PRIMARY KEY,
+//!     job_type TEXT NOT NULL,
+//!     payload JSONB NOT NULL,
+//!     version INTEGER NOT NULL,
+//!     max_retries INTEGER NOT NULL,
+//!     priority INTEGER NOT NULL,
+//!     lease_ms BIGINT,
+//!     cron TEXT NOT NULL,
+//!     tz TEXT NOT NULL,
+//!     idempotency_key TEXT UNIQUE,
+//!     next_run_at TIMESTAMPTZ NOT NULL,
+//!     next_job_id UUID NOT NULL,
+//!     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+//!     updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//! );
+//!
+//! CREATE INDEX idx_recurring_jobs_next ON recurring_jobs (next_run_at);
+//! ```
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use seesaw_core::JobSpec;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+use crate::admin::NewJob;
+use crate::PgJobStore;
+
+/// How many schedules one [`materialize_recurring`](PgJobStore::materialize_recurring)
+/// transaction advances.
+const MATERIALIZE_BATCH: i64 = 100;
+
+/// How far ahead an occurrence is searched for: cron expressions such as
+/// `0 0 29 2 MON` match only every few years.
+const SEARCH_YEARS: i64 = 30;
+
+impl PgJobStore {
+    /// Register `payload` to run at every occurrence of `cron` in time zone
+    /// `tz`, enqueueing its first occurrence. Returns the schedule's ID.
+    pub(crate) async fn insert_recurring(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        cron: &str,
+        tz: &str,
+    ) -> Result<Uuid> {
+        let schedule: Cron = cron.parse()?;
+        let mut job = NewJob::from_spec(payload, spec);
+        // Each occurrence is a new job; the key identifies the schedule
+        let key = job.idempotency_key.take();
+
+        let mut tx = self.pool().begin().await?;
+        let run_at = next_occurrence(&mut tx, &schedule, tz, Utc::now()).await?;
+        if let Some(key) = &key {
+            sqlx::query(
+                r#"
+                DELETE FROM jobs
+                WHERE status = 'pending'
+                  AND id = (SELECT next_job_id FROM recurring_jobs
+                            WHERE idempotency_key = $1 FOR UPDATE)
+                "#,
+            )
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+        }
+        job.run_at = Some(run_at);
+        let job_id = self.insert_job_on(&mut tx, &job).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO recurring_jobs (id, job_type, payload, version, max_retries, priority,
+                                        lease_ms, cron, tz, idempotency_key, next_run_at,
+                                        next_job_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (idempotency_key) DO UPDATE
+            SET job_type = EXCLUDED.job_type,
+                payload = EXCLUDED.payload,
+                version = EXCLUDED.version,
+                max_retries = EXCLUDED.max_retries,
+                priority = EXCLUDED.priority,
+                lease_ms = EXCLUDED.lease_ms,
+                cron = EXCLUDED.cron,
+                tz = EXCLUDED.tz,
+                next_run_at = EXCLUDED.next_run_at,
+                next_job_id = EXCLUDED.next_job_id,
+                updated_at = NOW()
+            RETURNING id
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.version)
+        .bind(job.max_retries)
+        .bind(job.priority)
+        .bind(job.lease_ms)
+        .bind(cron)
+        .bind(tz)
+        .bind(&key)
+        .bind(run_at)
+        .bind(job_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(row.get("id"))
+    }
+
+    /// Enqueue the next occurrence of every recurring schedule whose
+    /// current occurrence is due. Returns the number of jobs enqueued.
+    ///
+    /// Safe to run from several workers at once: each schedule is advanced
+    /// by one of them. See the [module docs](self).
+    pub async fn materialize_recurring(&self) -> Result<u64> {
+        let mut enqueued = 0;
+        loop {
+            let advanced = self.materialize_batch().await?;
+            enqueued += advanced;
+            if advanced < MATERIALIZE_BATCH as u64 {
+                return Ok(enqueued);
+            }
+        }
+    }
+
+    /// Advance up to [`MATERIALIZE_BATCH`] due schedules in one transaction.
+    async fn materialize_batch(&self) -> Result<u64> {
+        let mut tx = self.pool().begin().await?;
+        let due = sqlx::query(
+            r#"
+            SELECT id, job_type, payload, version, max_retries, priority, lease_ms, cron, tz
+            FROM recurring_jobs
+            WHERE next_run_at <= NOW()
+            ORDER BY next_run_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(MATERIALIZE_BATCH)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for row in &due {
+            let id: Uuid = row.get("id");
+            let cron: String = row.get("cron");
+            let tz: String = row.get("tz");
+            let schedule: Cron = cron
+                .parse()
+                .with_context(|| format!("recurring job {id} has an invalid schedule"))?;
+            let run_at = next_occurrence(&mut tx, &schedule, &tz, Utc::now()).await?;
+
+            let job = NewJob {
+                job_type: row.get("job_type"),
+                payload: row.get("payload"),
+                version: row.get("version"),
+                max_retries: row.get("max_retries"),
+                priority: row.get("priority"),
+                run_at: Some(run_at),
+                lease_ms: row.get("lease_ms"),
+                idempotency_key: None,
+            };
+            let job_id = self.insert_job_on(&mut tx, &job).await?;
+
+            sqlx::query(
+                "UPDATE recurring_jobs SET next_run_at = $1, next_job_id = $2, updated_at = NOW() \
+                 WHERE id = $3",
+            )
+            .bind(run_at)
+            .bind(job_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(due.len() as u64)
+    }
+}
+
+/// The first occurrence of `schedule` in time zone `tz` after `after`.
+///
+/// Postgres resolves the time zone, so an unknown one fails here. Local
+/// times skipped by a daylight saving change are moved forward by
+/// Postgres, and never land at or before `after`.
+async fn next_occurrence(
+    conn: &mut PgConnection,
+    schedule: &Cron,
+    tz: &str,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let local: NaiveDateTime = sqlx::query_scalar("SELECT $1::TIMESTAMPTZ AT TIME ZONE $2")
+        .bind(after)
+        .bind(tz)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            // invalid_parameter_value: time zone not recognized
+            Some(code) if code == "22023" => anyhow!("unknown time zone {tz:?}"),
+            _ => e.into(),
+        })?;
+
+    let mut local = local;
+    loop {
+        local = schedule
+            .next_after(local)
+            .ok_or_else(|| anyhow!("cron expression has no occurrence in {SEARCH_YEARS} years"))?;
+        let run_at: DateTime<Utc> = sqlx::query_scalar("SELECT $1::TIMESTAMP AT TIME ZONE $2")
+            .bind(local)
+            .bind(tz)
+            .fetch_one(&mut *conn)
+            .await?;
+        if run_at > after {
+            return Ok(run_at);
+        }
+    }
+}
+
+/// A parsed five-field cron expression. Each field is a bit set of the
+/// values it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    /// Sunday is 0.
+    days_of_week: u64,
+    /// Whether the day of month and day of week fields were both
+    /// restricted, so that either matching is enough.
+    either_day: bool,
+}
+
+/// Names accepted in the month field, from 1.
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// Names accepted in the day of week field, from 0.
+const DAYS_OF_WEEK: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expr => expr,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "invalid cron expression {expr:?}: expected 5 fields, got {}",
+                fields.len()
+            );
+        };
+        let parse = |field, min, max, names: &[&str], name| {
+            parse_field(field, min, max, names)
+                .with_context(|| format!("invalid cron {name} in {expr:?}"))
+        };
+
+        let days_of_week = parse(weekday, 0, 7, &DAYS_OF_WEEK, "day of week")?;
+        Ok(Self {
+            minutes: parse(minute, 0, 59, &[], "minute")?,
+            hours: parse(hour, 0, 23, &[], "hour")?,
+            days_of_month: parse(day, 1, 31, &[], "day of month")?,
+            months: parse(month, 1, 12, &MONTHS, "month")?,
+            // 7 is Sunday too
+            days_of_week: (days_of_week | days_of_week >> 7) & 0x7f,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl Cron {
+    /// The first local time after `after` matching the expression, to the
+    /// minute.
+    fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let last = start.date() + Duration::days(366 * SEARCH_YEARS);
+
+        let mut date = start.date();
+        while date <= last {
+            if self.matches_day(date) {
+                let from = if date == start.date() {
+                    start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                for hour in from.hour()..24 {
+                    if !has(self.hours, hour) {
+                        continue;
+                    }
+                    let first_minute = if hour == from.hour() { from.minute() } else { 0 };
+                    if let Some(minute) = (first_minute..60).find(|&m| has(self.minutes, m)) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day_of_month = has(self.days_of_month, date.day());
+        let day_of_week = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        if self.either_day {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parse a comma-separated list of `*`, values and ranges, each with an
+/// optional `/step`, into the bit set of values between `min` and `max`.
+/// `names` are accepted for values from `min`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let value = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(index) => index as u32 + min,
+            None => s.parse().map_err(|_| anyhow!("{s:?} is not a number"))?,
+        };
+        if !(min..=max).contains(&value) {
+            bail!("{value} is outside {min}-{max}");
+        }
+        Ok(value)
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| anyhow!("{step:?} is not a step"))?;
+                if step == 0 {
+                    bail!("step must be positive");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            bail!("range {range:?} is reversed");
+        }
+        for v in (start..=end).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(cron: &str, after: &str) -> NaiveDateTime {
+        cron.parse::<Cron>().unwrap().next_after(at(after)).unwrap()
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        assert_eq!(next("*/15 * * * *", "2026-10-15 10:07"), at("2026-10-15 10:15"));
+        assert_eq!(next("*/15 * * * *", "2026-10-15 10:15"), at("2026-10-15 10:30"));
+        assert_eq!(next("30 9 * * *", "2026-10-15 10:07"), at("2026-10-16 09:30"));
+        // 2026-10-15 is a Thursday
+        assert_eq!(next("0 9 * * MON", "2026-10-15 10:07"), at("2026-10-19 09:00"));
+        assert_eq!(next("0 9 * * 7", "2026-10-15 10:07"), at("2026-10-18 09:00"));
+        assert_eq!(next("0 0 1 jan,jul *", "2026-10-15 10:07"), at("2027-01-01 00:00"));
+        assert_eq!(next("0 12 29 2 *", "2026-10-15 10:07"), at("2028-02-29 12:00"));
+        assert_eq!(next("5-10/5 8-9 * * 1-5", "2026-10-16 09:10"), at("2026-10-19 08:05"));
+        assert_eq!(next("@monthly", "2026-10-15 10:07"), at("2026-11-01 00:00"));
+    }
+
+    #[test]
+    fn test_restricted_days_match_either() {
+        // The 1st of the month, or any Friday
+        let cron: Cron = "0 0 1 * FRI".parse().unwrap();
+
+        assert_eq!(cron.next_after(at("2026-10-15 10:07")), Some(at("2026-10-16 00:00")));
+        assert_eq!(cron.next_after(at("2026-10-30 10:07")), Some(at("2026-11-01 00:00")));
+    }
+
+    #[test]
+    fn test_invalid_expressions_are_rejected() {
+        for (expr, error) in [
+            ("* * * *", "expected 5 fields"),
+            ("60 * * * *", "minute"),
+            ("* 24 * * *", "hour"),
+            ("* * 0 * *", "day of month"),
+            ("* * * FOO *", "month"),
+            ("* * * * 8", "day of week"),
+            ("*/0 * * * *", "minute"),
+            ("10-5 * * * *", "minute"),
+        ] {
+            let e = expr.parse::<Cron>().unwrap_err();
+            assert!(format!("{e:#}").contains(error), "{expr}: {e:#}");
+        }
+    }
+
+    #[test]
+    fn test_impossible_date_has_no_occurrence() {
+        let cron: Cron = "0 0 30 2 *".parse().unwrap();
+
+        assert_eq!(cron.next_after(at("2026-01-01 00:00")), None);
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod pg_tests {
+    use std::sync::Arc;
+
+    use seesaw_core::{Command, Dispatcher, EventBus, ExecutionMode, JobQueue, JobStore};
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[derive(Debug, Clone, Serialize)]
+    struct SendDigest {
+        team: String,
+    }
+
+    impl Command for SendDigest {
+        fn execution_mode(&self) -> ExecutionMode {
+            ExecutionMode::Recurring {
+                cron: "*/5 * * * *",
+                tz: "Europe/Berlin",
+            }
+        }
+
+        fn job_spec(&self) -> Option<JobSpec> {
+            Some(JobSpec::new("digest:send").with_idempotency_key(format!("digest:{}", self.team)))
+        }
+
+        fn serialize_to_json(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self).ok()
+        }
+    }
+
+    /// Make every pending occurrence and schedule due, as if its time came.
+    async fn make_due(db: &PgTestDb) -> Result<()> {
+        sqlx::query("UPDATE jobs SET run_at = NOW() WHERE status = 'pending'")
+            .execute(db.pool())
+            .await?;
+        sqlx::query("UPDATE recurring_jobs SET next_run_at = NOW()")
+            .execute(db.pool())
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recurring_command_runs_each_occurrence() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let dispatcher = Dispatcher::with_job_queue((), EventBus::new(), Arc::new(store.clone()));
+        let command = SendDigest { team: "core".into() };
+
+        dispatcher.dispatch_one(Box::new(command.clone())).await?;
+        dispatcher.dispatch_one(Box::new(command)).await?;
+
+        let schedules: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recurring_jobs")
+            .fetch_one(db.pool())
+            .await?;
+        assert_eq!(schedules, 1);
+        let run_at: DateTime<Utc> =
+            sqlx::query_scalar("SELECT run_at FROM jobs WHERE status = 'pending'")
+                .fetch_one(db.pool())
+                .await?;
+        assert!(run_at > Utc::now() && run_at <= Utc::now() + Duration::minutes(5));
+        assert_eq!((run_at.minute() % 5, run_at.second()), (0, 0));
+        assert_eq!(store.materialize_recurring().await?, 0);
+        assert!(store.claim_ready("worker-1", 10).await?.is_empty());
+
+        for _ in 0..2 {
+            make_due(&db).await?;
+            let claimed = store.claim_ready("worker-1", 10).await?;
+            assert_eq!(claimed.len(), 1);
+            assert_eq!(claimed[0].job_type, "digest:send");
+            assert_eq!(claimed[0].payload, json!({ "team": "core" }));
+            store.mark_succeeded(claimed[0].lease()).await?;
+
+            assert_eq!(store.materialize_recurring().await?, 1);
+            assert_eq!(store.materialize_recurring().await?, 0);
+        }
+        let pending = db.snapshot().await?;
+        assert_eq!(pending.iter().filter(|job| job.status == "pending").count(), 1);
+        assert_eq!(pending.iter().filter(|job| job.status == "succeeded").count(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reregistering_replaces_pending_occurrence() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let spec = || JobSpec::new("report:weekly").with_idempotency_key("weekly");
+
+        let id = store
+            .schedule_recurring(json!({}), spec(), "0 * * * *", "UTC")
+            .await?;
+        let again = store
+            .schedule_recurring(json!({}), spec(), "0 9 * * MON", "Europe/Berlin")
+            .await?;
+
+        assert_eq!(again, id);
+        let local: Vec<(f64, f64, f64)> = sqlx::query_as(
+            r#"
+            SELECT EXTRACT(ISODOW FROM run_at AT TIME ZONE 'Europe/Berlin')::FLOAT8,
+                   EXTRACT(HOUR FROM run_at AT TIME ZONE 'Europe/Berlin')::FLOAT8,
+                   EXTRACT(MINUTE FROM run_at AT TIME ZONE 'Europe/Berlin')::FLOAT8
+            FROM jobs
+            "#,
+        )
+        .fetch_all(db.pool())
+        .await?;
+        assert_eq!(local, [(1.0, 9.0, 0.0)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_schedules_are_rejected() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let spec = JobSpec::new("report:weekly");
+
+        let error = store
+            .schedule_recurring(json!({}), spec.clone(), "0 9 * * FUNDAY", "UTC")
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("invalid cron day of week"));
+
+        let error = store
+            .schedule_recurring(json!({}), spec, "0 9 * * MON", "Europe/Atlantis")
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), r#"unknown time zone "Europe/Atlantis""#);
+
+        let schedules: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recurring_jobs")
+            .fetch_one(db.pool())
+            .await?;
+        assert_eq!(schedules, 0);
+        assert!(db.snapshot().await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Replay protection for jobs with an idempotency key.
+//!
+//! While a job is pending or running, a second job with the same
+//! idempotency key is not inserted. With the schema below, the key is also
+//! remembered once the job succeeds, in a `completed_keys` table that
+//! outlives [`cleanup_succeeded`](PgJobStore::cleanup_succeeded). A job
+//! enqueued again with that key within the store's
+//! [`replay_window`](PgJobStore::with_replay_window) is ignored, which
+//! protects against an upstream webhook replayed days later.
+//!
+//! [`PgJobStore::insert_job`] returns the ID of the job that already holds
+//! the key instead of inserting a new one:
+//!
+//! ```rust,ignore
+//! let job = NewJob::new("payment:capture", payload)
+//!     .with_idempotency_key(format!("stripe:{}", event.id));
+//! let first = store.insert_job(&job).await?;
+//! // ... the job runs and succeeds; Stripe redelivers the webhook ...
+//! assert_eq!(store.insert_job(&job).await?, first);
+//! ```
+//!
+//! Failed, cancelled and dead-lettered jobs do not hold their key, so the
+//! work can be enqueued again.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! ALTER TABLE jobs ADD COLUMN idempotency_key TEXT;
+//!
+//! CREATE UNIQUE INDEX idx_jobs_idempotency ON jobs (idempotency_key)
+//!     WHERE status IN ('pending', 'running');
+//!
+//! CREATE TABLE completed_keys (
+//!     idempotency_key TEXT PRIMARY KEY,
+//!     job_id UUID NOT NULL,
+//!     completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//! );
+//!
+//! CREATE INDEX idx_completed_keys_completed ON completed_keys (completed_at);
+//!
+//! CREATE FUNCTION seesaw_record_completed_key() RETURNS trigger AS $$
+//! BEGIN
+//!     INSERT INTO completed_keys (idempotency_key, job_id)
+//!     VALUES (NEW.idempotency_key, NEW.id)
+//!     ON CONFLICT (idempotency_key)
+//!         DO UPDATE SET job_id = EXCLUDED.job_id, completed_at = EXCLUDED.completed_at;
+//!     RETURN NULL;
+//! END;
+//! $$ LANGUAGE plpgsql;
+//!
+//! CREATE TRIGGER jobs_record_completed_key AFTER UPDATE OF status ON jobs
+//!     FOR EACH ROW WHEN (NEW.status = 'succeeded' AND NEW.idempotency_key IS NOT NULL)
+//!     EXECUTE FUNCTION seesaw_record_completed_key();
+//! ```
+//!
+//! Keys older than the window no longer block anything; delete them
+//! periodically with [`PgJobStore::purge_completed_keys`].
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+use crate::admin::NewJob;
+use crate::PgJobStore;
+
+/// How long a succeeded job's idempotency key blocks a replay unless the
+/// store is configured otherwise: 7 days.
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(7 * 24 * 3600);
+
+impl PgJobStore {
+    /// Insert `job` unless its idempotency key is held by a pending or
+    /// running job, or by one that succeeded within the replay window.
+    ///
+    /// Returns the ID of the inserted job, or of the job holding the key.
+    pub(crate) async fn insert_idempotent(
+        &self,
+        conn: &mut PgConnection,
+        job: &NewJob,
+        key: &str,
+    ) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let row = sqlx::query(
+            r#"
+            WITH existing AS (
+                SELECT job_id AS id FROM completed_keys
+                WHERE idempotency_key = $8
+                  AND completed_at > NOW() - make_interval(secs => $9)
+                UNION ALL
+                SELECT id FROM jobs
+                WHERE idempotency_key = $8 AND status IN ('pending', 'running')
+            ),
+            inserted AS (
+                INSERT INTO jobs (id, job_type, payload, version, max_retries, priority, run_at,
+                                  lease_ms, idempotency_key)
+                SELECT $1, $2, $3, $4, $5, $6, COALESCE($7, NOW()), $10, $8
+                WHERE NOT EXISTS (SELECT 1 FROM existing)
+                ON CONFLICT (idempotency_key) WHERE status IN ('pending', 'running') DO NOTHING
+                RETURNING id
+            )
+            SELECT id FROM inserted
+            UNION ALL
+            SELECT id FROM existing
+            LIMIT 1
+            "#,
+        )
+        .bind(id)
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.version)
+        .bind(job.max_retries)
+        .bind(job.priority)
+        .bind(job.run_at)
+        .bind(key)
+        .bind(self.replay_window.as_secs_f64())
+        .bind(job.lease_ms)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        if let Some(row) = row {
+            return Ok(row.get("id"));
+        }
+
+        // Lost a race with a concurrent insert of the same key, which the
+        // snapshot above could not see yet
+        let row = sqlx::query(
+            "SELECT id FROM jobs WHERE idempotency_key = $1 AND status IN ('pending', 'running')",
+        )
+        .bind(key)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| anyhow!("job with idempotency key {key:?} vanished while enqueueing"))?;
+
+        Ok(row.get("id"))
+    }
+
+    /// Delete completed idempotency keys older than the replay window.
+    ///
+    /// This should be run periodically by a maintenance worker.
+    pub async fn purge_completed_keys(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM completed_keys WHERE completed_at <= NOW() - make_interval(secs => $1)",
+        )
+        .bind(self.replay_window.as_secs_f64())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::{FailureKind, JobStore};
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[tokio::test]
+    async fn test_succeeded_key_blocks_replays_within_window() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let job = NewJob::new("payment:capture", json!({})).with_idempotency_key("stripe:evt_1");
+
+        let first = store.insert_job(&job).await?;
+        assert_eq!(store.insert_job(&job).await?, first);
+        let claimed = store.claim_ready("worker-1", 1).await?;
+        assert_eq!(store.insert_job(&job).await?, first);
+        store.mark_succeeded(claimed[0].lease()).await?;
+
+        let recorded: Uuid =
+            sqlx::query_scalar("SELECT job_id FROM completed_keys WHERE idempotency_key = $1")
+                .bind("stripe:evt_1")
+                .fetch_one(db.pool())
+                .await?;
+        assert_eq!(recorded, first);
+        assert_eq!(store.insert_job(&job).await?, first);
+        assert_eq!(db.snapshot().await?.len(), 1);
+
+        // Past the window the key is purged and the work can run again
+        sqlx::query("UPDATE completed_keys SET completed_at = NOW() - INTERVAL '8 days'")
+            .execute(db.pool())
+            .await?;
+        assert_eq!(store.purge_completed_keys().await?, 1);
+        assert_ne!(store.insert_job(&job).await?, first);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replay_window_is_configurable() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store().with_replay_window(Duration::from_secs(60));
+        let job = NewJob::new("payment:capture", json!({})).with_idempotency_key("stripe:evt_2");
+        let first = store.insert_job(&job).await?;
+        let claimed = store.claim_ready("worker-1", 1).await?;
+        store.mark_succeeded(claimed[0].lease()).await?;
+
+        sqlx::query("UPDATE completed_keys SET completed_at = NOW() - INTERVAL '2 minutes'")
+            .execute(db.pool())
+            .await?;
+
+        assert_ne!(store.insert_job(&job).await?, first);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dead_lettered_key_does_not_block() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let job = NewJob::new("payment:capture", json!({})).with_idempotency_key("stripe:evt_3");
+        let first = store.insert_job(&job).await?;
+        let claimed = store.claim_ready("worker-1", 1).await?;
+        store
+            .mark_failed(claimed[0].lease(), "card declined", FailureKind::NonRetryable)
+            .await?;
+        db.assert_dead_letter(first).await;
+
+        let second = store.insert_job(&job).await?;
+
+        assert_ne!(second, first);
+        let completed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM completed_keys")
+            .fetch_one(db.pool())
+            .await?;
+        assert_eq!(completed, 0);
+        Ok(())
+    }
+}
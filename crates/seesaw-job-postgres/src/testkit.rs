@@ -0,0 +1,492 @@
+//! Disposable Postgres databases for integration tests (`testkit` feature).
+//!
+//! [`PgTestDb::start`] runs a Postgres container via testcontainers, creates
+//! every table this crate uses, and hands back a store on it. The helpers
+//! cover what job-handling tests keep rewriting: enqueueing a job, letting
+//! time pass for leases and retry backoffs, and checking where a job ended
+//! up.
+//!
+//! ```rust,ignore
+//! #[tokio::test]
+//! async fn failing_charge_is_dead_lettered() -> anyhow::Result<()> {
+//!     let db = PgTestDb::start().await?;
+//!     let job_id = db.enqueue_test_job("charge_card", json!({ "order_id": 1 })).await?;
+//!
+//!     let store = Arc::new(db.store());
+//!     let worker = JobWorker::new(store.clone(), registry, dispatcher);
+//!     for _ in 0..3 {
+//!         for job in store.claim_ready("test", 10).await? {
+//!             worker.run_job(job).await?;
+//!         }
+//!         // Skip the retry backoff
+//!         db.advance_leases(Duration::from_secs(3600)).await?;
+//!     }
+//!
+//!     db.assert_dead_letter(job_id).await;
+//!     insta::assert_debug_snapshot!(db.snapshot().await?);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Each `PgTestDb` owns its container, which is removed when it is dropped,
+//! so tests running in parallel never share rows. Docker must be reachable.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Row};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::ContainerAsync;
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// Schema [`PgTestDb::start`] creates: the `jobs` table with the binary
+/// payload columns, the NOTIFY trigger, the `job_events` log,
+/// `completed_keys` and `recurring_jobs`, and the `event_outbox` and
+/// `seesaw_audit` tables.
+pub const SCHEMA: &str = r#"
+CREATE TYPE job_status AS ENUM (
+    'pending', 'running', 'succeeded', 'failed', 'dead_letter', 'parked'
+);
+CREATE TYPE error_kind AS ENUM ('retryable', 'non_retryable');
+
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY,
+    job_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    status job_status NOT NULL DEFAULT 'pending',
+    attempt INTEGER NOT NULL DEFAULT 1,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    priority INTEGER NOT NULL DEFAULT 0,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    worker_id TEXT,
+    lease_expires_at TIMESTAMPTZ,
+    lease_token UUID,
+    lease_ms BIGINT,
+    reserved BOOLEAN NOT NULL DEFAULT FALSE,
+    idempotency_key TEXT,
+    error_message TEXT,
+    error_kind error_kind,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    payload_bytes BYTEA,
+    codec TEXT
+);
+
+CREATE INDEX idx_jobs_ready ON jobs (priority, run_at) WHERE status = 'pending';
+CREATE INDEX idx_jobs_lease ON jobs (lease_expires_at)
+    WHERE status = 'running' AND lease_expires_at IS NOT NULL;
+CREATE UNIQUE INDEX idx_jobs_idempotency ON jobs (idempotency_key)
+    WHERE status IN ('pending', 'running');
+
+CREATE TABLE drained_queues (
+    queue TEXT PRIMARY KEY,
+    drained_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE FUNCTION seesaw_notify_job() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('seesaw_jobs', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER jobs_notify AFTER INSERT OR UPDATE OF status ON jobs
+    FOR EACH ROW WHEN (NEW.status = 'pending')
+    EXECUTE FUNCTION seesaw_notify_job();
+
+CREATE TABLE job_events (
+    seq BIGSERIAL PRIMARY KEY,
+    txid BIGINT NOT NULL DEFAULT pg_current_xact_id()::TEXT::BIGINT,
+    job_id UUID NOT NULL,
+    job_type TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    worker_id TEXT,
+    error_message TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_job_events_cursor ON job_events (txid, seq);
+
+CREATE FUNCTION seesaw_record_job_event() RETURNS trigger AS $$
+DECLARE
+    event_kind TEXT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.status <> 'pending' THEN RETURN NULL; END IF;
+        event_kind := 'enqueued';
+    ELSIF NEW.status = OLD.status THEN
+        IF NEW.status <> 'running'
+            OR NEW.lease_expires_at IS NOT DISTINCT FROM OLD.lease_expires_at THEN
+            RETURN NULL;
+        END IF;
+        event_kind := 'heartbeat';
+    ELSE
+        event_kind := CASE
+            WHEN NEW.status = 'running' THEN 'claimed'
+            WHEN NEW.status = 'succeeded' THEN 'succeeded'
+            WHEN NEW.status = 'dead_letter' THEN 'dead_lettered'
+            WHEN NEW.status = 'parked' THEN 'parked'
+            WHEN NEW.status = 'failed' THEN 'cancelled'
+            WHEN OLD.status = 'running' AND NEW.attempt > OLD.attempt THEN 'failed'
+            WHEN OLD.status = 'running' THEN 'reclaimed'
+            ELSE 'enqueued'
+        END;
+    END IF;
+
+    INSERT INTO job_events (job_id, job_type, kind, attempt, worker_id, error_message)
+    VALUES (NEW.id, NEW.job_type, event_kind, NEW.attempt,
+            CASE WHEN event_kind <> 'enqueued' THEN COALESCE(NEW.worker_id, OLD.worker_id) END,
+            NEW.error_message);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER jobs_record_event AFTER INSERT OR UPDATE ON jobs
+    FOR EACH ROW EXECUTE FUNCTION seesaw_record_job_event();
+
+CREATE TABLE completed_keys (
+    idempotency_key TEXT PRIMARY KEY,
+    job_id UUID NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_completed_keys_completed ON completed_keys (completed_at);
+
+CREATE FUNCTION seesaw_record_completed_key() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO completed_keys (idempotency_key, job_id)
+    VALUES (NEW.idempotency_key, NEW.id)
+    ON CONFLICT (idempotency_key)
+        DO UPDATE SET job_id = EXCLUDED.job_id, completed_at = EXCLUDED.completed_at;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER jobs_record_completed_key AFTER UPDATE OF status ON jobs
+    FOR EACH ROW WHEN (NEW.status = 'succeeded' AND NEW.idempotency_key IS NOT NULL)
+    EXECUTE FUNCTION seesaw_record_completed_key();
+
+CREATE TABLE recurring_jobs (
+    id UUID PRIMARY KEY,
+    job_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    version INTEGER NOT NULL,
+    max_retries INTEGER NOT NULL,
+    priority INTEGER NOT NULL,
+    lease_ms BIGINT,
+    cron TEXT NOT NULL,
+    tz TEXT NOT NULL,
+    idempotency_key TEXT UNIQUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    next_job_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_recurring_jobs_next ON recurring_jobs (next_run_at);
+
+CREATE TABLE job_waiters (
+    correlation_id UUID PRIMARY KEY,
+    job_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    result JSONB
+);
+
+CREATE INDEX idx_job_waiters_job ON job_waiters (job_id)
+    WHERE completed_at IS NULL;
+CREATE INDEX idx_job_waiters_completed ON job_waiters (completed_at);
+
+CREATE FUNCTION seesaw_complete_waiters() RETURNS trigger AS $$
+DECLARE
+    waiter RECORD;
+BEGIN
+    FOR waiter IN
+        UPDATE job_waiters
+        SET completed_at = NOW(),
+            result = jsonb_build_object(
+                'job_id', NEW.id,
+                'status', NEW.status,
+                'error', NEW.error_message
+            )
+        WHERE job_id = NEW.id AND completed_at IS NULL
+        RETURNING correlation_id
+    LOOP
+        PERFORM pg_notify('seesaw_waiters', waiter.correlation_id::text);
+    END LOOP;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER jobs_complete_waiters AFTER UPDATE OF status ON jobs
+    FOR EACH ROW WHEN (NEW.status IN ('succeeded', 'dead_letter'))
+    EXECUTE FUNCTION seesaw_complete_waiters();
+
+CREATE TABLE event_outbox (
+    id UUID PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    correlation_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    claimed_until TIMESTAMPTZ,
+    published_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_event_outbox_unpublished ON event_outbox (created_at)
+    WHERE published_at IS NULL;
+
+CREATE TABLE seesaw_audit (
+    id BIGSERIAL PRIMARY KEY,
+    correlation_id UUID,
+    event_type TEXT,
+    hops INTEGER NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL,
+    dropped BOOLEAN NOT NULL,
+    duration_us BIGINT NOT NULL,
+    record JSONB NOT NULL
+);
+
+CREATE INDEX idx_seesaw_audit_correlation ON seesaw_audit (correlation_id);
+CREATE INDEX idx_seesaw_audit_received ON seesaw_audit (received_at);
+"#;
+
+/// A migrated Postgres database in a container of its own.
+pub struct PgTestDb {
+    pool: PgPool,
+    /// Kept alive for the lifetime of the database; dropping it removes the
+    /// container.
+    _container: ContainerAsync<Postgres>,
+}
+
+impl PgTestDb {
+    /// Start a container and create [`SCHEMA`] in it.
+    pub async fn start() -> Result<Self> {
+        let container = Postgres::default()
+            .start()
+            .await
+            .context("failed to start postgres container (is docker running?)")?;
+        let url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            container.get_host().await?,
+            container.get_host_port_ipv4(5432).await?
+        );
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .connect(&url)
+            .await?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+
+        Ok(Self {
+            pool,
+            _container: container,
+        })
+    }
+
+    /// Get the connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Create a job store on the database, with default settings.
+    ///
+    /// Configure it further as in production, e.g. with
+    /// [`PgJobStore::with_codecs`].
+    pub fn store(&self) -> PgJobStore {
+        PgJobStore::new(self.pool.clone())
+    }
+
+    /// Insert a pending job that is ready to claim now.
+    pub async fn enqueue_test_job(
+        &self,
+        job_type: &str,
+        payload: serde_json::Value,
+    ) -> Result<Uuid> {
+        self.enqueue(TestJob::new(job_type, payload)).await
+    }
+
+    /// Insert a pending job with the fields of `job`.
+    pub async fn enqueue(&self, job: TestJob) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO jobs (id, job_type, payload, version, max_retries, priority, run_at,
+                              lease_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW() + make_interval(secs => $7), $8)
+            "#,
+        )
+        .bind(id)
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.version)
+        .bind(job.max_retries)
+        .bind(job.priority)
+        .bind(job.delay.as_secs_f64())
+        .bind(job.lease_ms)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Move every lease expiry and scheduled run time `by` into the past, as
+    /// if that much time had passed.
+    ///
+    /// Running jobs whose lease this expires become reclaimable with
+    /// [`PgJobStore::reclaim_expired`], and retries waiting out their backoff
+    /// become claimable. Returns the number of jobs moved.
+    pub async fn advance_leases(&self, by: Duration) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET lease_expires_at = lease_expires_at - make_interval(secs => $1),
+                run_at = run_at - make_interval(secs => $1)
+            WHERE status IN ('pending', 'running')
+            "#,
+        )
+        .bind(by.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get the current state of a job, or `None` if it does not exist.
+    pub async fn job(&self, job_id: Uuid) -> Result<Option<JobSnapshot>> {
+        let row = sqlx::query(&format!("{SNAPSHOT_SELECT} WHERE id = $1"))
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| JobSnapshot::from_row(&row)))
+    }
+
+    /// Panic unless the job was moved to the dead letter queue.
+    pub async fn assert_dead_letter(&self, job_id: Uuid) {
+        let job = self
+            .job(job_id)
+            .await
+            .unwrap_or_else(|e| panic!("failed to load job {job_id}: {e}"))
+            .unwrap_or_else(|| panic!("job {job_id} does not exist"));
+        assert_eq!(
+            job.status, "dead_letter",
+            "expected job {job_id} to be dead-lettered, got {job:#?}"
+        );
+    }
+
+    /// Get the state of every job, in insertion order.
+    ///
+    /// Snapshots leave out IDs and timestamps, so they can be compared
+    /// against a stored snapshot from an earlier run.
+    pub async fn snapshot(&self) -> Result<Vec<JobSnapshot>> {
+        let rows = sqlx::query(&format!("{SNAPSHOT_SELECT} ORDER BY created_at, id"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(JobSnapshot::from_row).collect())
+    }
+}
+
+impl std::fmt::Debug for PgTestDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PgTestDb")
+            .field("container", &self._container.id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A job for [`PgTestDb::enqueue`], with the schema's defaults.
+#[derive(Debug, Clone)]
+pub struct TestJob {
+    job_type: String,
+    payload: serde_json::Value,
+    version: i32,
+    max_retries: i32,
+    priority: i32,
+    delay: Duration,
+    lease_ms: Option<i64>,
+}
+
+impl TestJob {
+    /// Create a job of `job_type`: version 1, 3 retries, priority 0, ready now.
+    pub fn new(job_type: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            job_type: job_type.into(),
+            payload,
+            version: 1,
+            max_retries: 3,
+            priority: 0,
+            delay: Duration::ZERO,
+            lease_ms: None,
+        }
+    }
+
+    /// Set the payload version.
+    pub fn with_version(mut self, version: i32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set how many attempts the job gets before it is dead-lettered.
+    pub fn with_max_retries(mut self, max_retries: i32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the priority (higher runs first, as in `JobSpec`).
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority.saturating_neg();
+        self
+    }
+
+    /// Make the job ready `delay` from now.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Lease the job for `lease_ms` when claimed, instead of the store's
+    /// default.
+    pub fn with_lease_ms(mut self, lease_ms: i64) -> Self {
+        self.lease_ms = Some(lease_ms);
+        self
+    }
+}
+
+/// Columns of a [`JobSnapshot`].
+const SNAPSHOT_SELECT: &str = r#"
+    SELECT job_type, payload, status::TEXT AS status, attempt, worker_id,
+           error_kind::TEXT AS error_kind, error_message
+    FROM jobs
+"#;
+
+/// The state of one job row, without IDs and timestamps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobSnapshot {
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempt: i32,
+    pub worker_id: Option<String>,
+    pub error_kind: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl JobSnapshot {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Self {
+        Self {
+            job_type: row.get("job_type"),
+            payload: row.get("payload"),
+            status: row.get("status"),
+            attempt: row.get("attempt"),
+            worker_id: row.get("worker_id"),
+            error_kind: row.get("error_kind"),
+            error_message: row.get("error_message"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Retrying the store's statements through transient database errors.
+//!
+//! A Postgres failover or a connection reset lasts a few seconds at most,
+//! but without retries every worker's claim loop would see it as a hard
+//! error. The store re-runs each statement while it fails with an error
+//! [`is_transient_error`] accepts, backing off per its
+//! [`RetryPolicy`](seesaw_core::RetryPolicy) (see
+//! [`PgJobStore::with_retry`]).
+//!
+//! A statement whose commit reached the server before the connection broke
+//! is run again: a repeated outcome fails with
+//! [`LeaseLost`](seesaw_core::LeaseLost), and jobs claimed by the lost
+//! attempt are picked up again by
+//! [`reclaim_expired`](PgJobStore::reclaim_expired) once their lease runs out.
+
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use seesaw_core::RetryPolicy;
+use tracing::warn;
+
+use crate::PgJobStore;
+
+/// Whether `error` is a database error worth retrying: a lost or refused
+/// connection, a pool timeout, a serialization failure or deadlock, or a
+/// server shutting down or read-only during failover.
+pub fn is_transient_error(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<sqlx::Error>() else {
+        return false;
+    };
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(e) => e.code().is_some_and(|code| {
+            // connection_exception class
+            code.starts_with("08")
+                || matches!(
+                    &*code,
+                    // serialization_failure, deadlock_detected
+                    "40001" | "40P01"
+                    // admin_shutdown, crash_shutdown, cannot_connect_now
+                    | "57P01" | "57P02" | "57P03"
+                    // read_only_sql_transaction: a demoted primary
+                    | "25006"
+                )
+        }),
+        _ => false,
+    }
+}
+
+/// Policy the store retries with unless configured otherwise: 5 attempts,
+/// 50ms initial backoff doubling up to 2s, for [`is_transient_error`].
+pub(crate) fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .with_max_attempts(5)
+        .with_initial_backoff(Duration::from_millis(50))
+        .with_max_backoff(Duration::from_secs(2))
+        .with_retry_if(is_transient_error)
+}
+
+impl PgJobStore {
+    /// Run `statement` until it succeeds, fails permanently, or the retry
+    /// policy gives up.
+    pub(crate) async fn retrying<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        statement: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.retry
+            .retry(statement, |attempt, error, delay| {
+                warn!(
+                    operation,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %error,
+                    "transient database error, retrying"
+                );
+                crate::metrics::retried(operation);
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+
+    /// A database error with only a SQLSTATE.
+    #[derive(Debug)]
+    struct Sqlstate(&'static str);
+
+    impl std::fmt::Display for Sqlstate {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for Sqlstate {}
+
+    impl DatabaseError for Sqlstate {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str) -> anyhow::Error {
+        sqlx::Error::Database(Box::new(Sqlstate(code))).into()
+    }
+
+    #[test]
+    fn test_is_transient_error() {
+        let cases = [
+            ("connection_failure", database_error("08006"), true),
+            ("connection_exception class", database_error("08P01"), true),
+            ("serialization_failure", database_error("40001"), true),
+            ("deadlock_detected", database_error("40P01"), true),
+            ("admin_shutdown", database_error("57P01"), true),
+            ("crash_shutdown", database_error("57P02"), true),
+            ("cannot_connect_now", database_error("57P03"), true),
+            ("read_only_sql_transaction", database_error("25006"), true),
+            ("unique_violation", database_error("23505"), false),
+            ("undefined_table", database_error("42P01"), false),
+            ("query_canceled", database_error("57014"), false),
+            (
+                "connection reset",
+                sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into()).into(),
+                true,
+            ),
+            (
+                "broken pipe",
+                sqlx::Error::Io(std::io::ErrorKind::BrokenPipe.into()).into(),
+                true,
+            ),
+            ("pool timeout", sqlx::Error::PoolTimedOut.into(), true),
+            ("row not found", sqlx::Error::RowNotFound.into(), false),
+            ("other error", anyhow::anyhow!("connection reset"), false),
+        ];
+
+        for (name, error, transient) in cases {
+            assert_eq!(is_transient_error(&error), transient, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retrying_reruns_transient_failures_only() {
+        let pool = PgPoolOptions::new().connect_lazy("postgres://localhost/seesaw").unwrap();
+        let store = PgJobStore::new(pool).with_retry(
+            default_retry_policy()
+                .with_max_attempts(3)
+                .with_initial_backoff(Duration::ZERO),
+        );
+        let calls = AtomicU32::new(0);
+
+        let result = store
+            .retrying("test", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(database_error("40001")),
+                    _ => Ok(7),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        let result: Result<()> = store
+            .retrying("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(database_error("23505"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+
+        let result: Result<()> = store
+            .retrying("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(database_error("08006"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
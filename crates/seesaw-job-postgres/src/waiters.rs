@@ -0,0 +1,364 @@
+//! Durable waits on correlated work, surviving process restarts.
+//!
+//! An API that long-polls for the outcome of a background job loses every
+//! in-memory waiter when its pod restarts. Here the wait is a row in a
+//! `job_waiters` table instead: [`PgJobStore::await_event_durable`] records
+//! it under the request's correlation ID, and a client that reconnects to
+//! any pod calls it again and picks up the same wait, or the result that
+//! arrived in the meantime:
+//!
+//! ```rust,ignore
+//! // POST /reports: enqueue the job and tie it to the request
+//! let job_id = store.insert_job(&NewJob::new("report:render", payload)).await?;
+//! store.watch_job(cid, job_id).await?;
+//!
+//! // GET /reports/{cid}: long-poll, on whichever pod the client lands
+//! match store.await_event_durable(cid, Duration::from_secs(30)).await? {
+//!     Some(outcome) => respond(outcome.result),
+//!     None => respond_pending(),
+//! }
+//! ```
+//!
+//! When a watched job succeeds or is dead-lettered, the trigger below
+//! completes its waiter with the job's status and error, and announces the
+//! correlation ID on [`WAITER_CHANNEL`]. Effects that finish the work
+//! without a job complete the waiter themselves with
+//! [`PgJobStore::complete_waiter`], inside their own transaction if they
+//! have one.
+//!
+//! # Database Schema
+//!
+//! ```sql
+//! CREATE TABLE job_waiters (
+//!     correlation_id UUID PRIMARY KEY,
+//!     job_id UUID,
+//!     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+//!     completed_at TIMESTAMPTZ,
+//!     result JSONB
+//! );
+//!
+//! CREATE INDEX idx_job_waiters_job ON job_waiters (job_id)
+//!     WHERE completed_at IS NULL;
+//! CREATE INDEX idx_job_waiters_completed ON job_waiters (completed_at);
+//!
+//! CREATE FUNCTION seesaw_complete_waiters() RETURNS trigger AS $$
+//! DECLARE
+//!     waiter RECORD;
+//! BEGIN
+//!     FOR waiter IN
+//!         UPDATE job_waiters
+//!         SET completed_at = NOW(),
+//!             result = jsonb_build_object(
+//!                 'job_id', NEW.id,
+//!                 'status', NEW.status,
+//!                 'error', NEW.error_message
+//!             )
+//!         WHERE job_id = NEW.id AND completed_at IS NULL
+//!         RETURNING correlation_id
+//!     LOOP
+//!         PERFORM pg_notify('seesaw_waiters', waiter.correlation_id::text);
+//!     END LOOP;
+//!     RETURN NULL;
+//! END;
+//! $$ LANGUAGE plpgsql;
+//!
+//! CREATE TRIGGER jobs_complete_waiters AFTER UPDATE OF status ON jobs
+//!     FOR EACH ROW WHEN (NEW.status IN ('succeeded', 'dead_letter'))
+//!     EXECUTE FUNCTION seesaw_complete_waiters();
+//! ```
+//!
+//! Completed waiters are kept so a late client still gets its result;
+//! delete them periodically with [`PgJobStore::purge_waiters`].
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use seesaw_core::CorrelationId;
+use sqlx::postgres::PgListener;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+use crate::PgJobStore;
+
+/// Channel completed waiters are announced on, with their correlation ID as
+/// the payload.
+pub const WAITER_CHANNEL: &str = "seesaw_waiters";
+
+/// The recorded outcome of a durable wait.
+#[derive(Debug, Clone)]
+pub struct WaiterOutcome {
+    /// The correlation ID the wait was recorded under.
+    pub correlation_id: CorrelationId,
+    /// The job whose completion resolved the wait, if one was watched.
+    pub job_id: Option<Uuid>,
+    /// For watched jobs, `{"job_id", "status", "error"}`; otherwise what was
+    /// passed to [`complete_waiter`](PgJobStore::complete_waiter).
+    pub result: serde_json::Value,
+    /// When the wait was resolved.
+    pub completed_at: DateTime<Utc>,
+}
+
+impl PgJobStore {
+    /// Wait up to `timeout` for the work correlated by `correlation_id` to
+    /// complete.
+    ///
+    /// Records the wait if no call has yet, then returns its outcome as
+    /// soon as it is completed, whether that happened before the call,
+    /// during it, or while no process was waiting at all. Returns `None` on
+    /// timeout; the wait stays recorded, so calling again resumes it.
+    ///
+    /// Wakes on notifications from [`WAITER_CHANNEL`] and re-reads the
+    /// waiter every [`with_poll_interval`](PgJobStore::with_poll_interval)
+    /// in case one was missed while the listener reconnected.
+    pub async fn await_event_durable(
+        &self,
+        correlation_id: CorrelationId,
+        timeout: Duration,
+    ) -> Result<Option<WaiterOutcome>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let cid = correlation_id.into_inner();
+        self.record_waiter(cid, None).await?;
+
+        // Listen before the first read: a completion committed in between
+        // would otherwise be announced to no one
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(WAITER_CHANNEL).await?;
+
+        loop {
+            if let Some(outcome) = self.waiter_outcome(cid).await? {
+                return Ok(Some(outcome));
+            }
+
+            let wake = deadline.min(tokio::time::Instant::now() + self.poll_interval);
+            loop {
+                match tokio::time::timeout_at(wake, listener.recv()).await {
+                    Ok(Ok(notification)) if notification.payload() != cid.to_string() => {}
+                    // Ours, or time to re-read
+                    Ok(Ok(_)) | Err(_) => break,
+                    Ok(Err(e)) => return Err(e.into()),
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return self.waiter_outcome(cid).await;
+            }
+        }
+    }
+
+    /// Resolve the wait under `correlation_id` when job `job_id` succeeds or
+    /// is dead-lettered.
+    ///
+    /// Records the wait if no [`await_event_durable`](Self::await_event_durable)
+    /// call has yet, so it can be set up before the client starts polling.
+    /// A job that already finished completes the wait at once.
+    pub async fn watch_job(&self, correlation_id: CorrelationId, job_id: Uuid) -> Result<()> {
+        let cid = correlation_id.into_inner();
+        self.record_waiter(cid, Some(job_id)).await?;
+
+        // The trigger only sees jobs finishing after the waiter was recorded
+        let pool = &self.pool;
+        self.retrying("watch_job", move || async move {
+            Ok(sqlx::query(
+                "WITH done AS ( \
+                     UPDATE job_waiters w \
+                     SET completed_at = NOW(), \
+                         result = jsonb_build_object( \
+                             'job_id', j.id, 'status', j.status, 'error', j.error_message \
+                         ) \
+                     FROM jobs j \
+                     WHERE w.correlation_id = $1 AND j.id = w.job_id AND w.completed_at IS NULL \
+                       AND j.status IN ('succeeded', 'dead_letter') \
+                     RETURNING w.correlation_id \
+                 ) \
+                 SELECT pg_notify($2, correlation_id::text) FROM done",
+            )
+            .bind(cid)
+            .bind(WAITER_CHANNEL)
+            .execute(pool)
+            .await?)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Complete the wait under `correlation_id` with `result` and wake its
+    /// waiters.
+    ///
+    /// Runs on `conn`, so an effect can complete the wait in the transaction
+    /// that records the work. Returns `false` if the wait was already
+    /// completed; a wait no one has recorded yet is recorded completed, for
+    /// a client that starts polling late.
+    pub async fn complete_waiter(
+        &self,
+        conn: &mut PgConnection,
+        correlation_id: CorrelationId,
+        result: &serde_json::Value,
+    ) -> Result<bool> {
+        let cid = correlation_id.into_inner();
+        let row = sqlx::query(
+            "INSERT INTO job_waiters (correlation_id, completed_at, result) VALUES ($1, NOW(), $2) \
+             ON CONFLICT (correlation_id) DO UPDATE \
+             SET completed_at = EXCLUDED.completed_at, result = EXCLUDED.result \
+             WHERE job_waiters.completed_at IS NULL \
+             RETURNING correlation_id",
+        )
+        .bind(cid)
+        .bind(result)
+        .fetch_optional(&mut *conn)
+        .await?;
+        if row.is_none() {
+            return Ok(false);
+        }
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(WAITER_CHANNEL)
+            .bind(cid.to_string())
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Delete waiters completed before `older_than`.
+    pub async fn purge_waiters(&self, older_than: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM job_waiters WHERE completed_at < $1")
+            .bind(older_than)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Record a wait under `cid`, attaching `job_id` if given.
+    async fn record_waiter(&self, cid: Uuid, job_id: Option<Uuid>) -> Result<()> {
+        let pool = &self.pool;
+        self.retrying("record_waiter", move || async move {
+            Ok(sqlx::query(
+                "INSERT INTO job_waiters (correlation_id, job_id) VALUES ($1, $2) \
+                 ON CONFLICT (correlation_id) DO UPDATE \
+                 SET job_id = COALESCE(EXCLUDED.job_id, job_waiters.job_id)",
+            )
+            .bind(cid)
+            .bind(job_id)
+            .execute(pool)
+            .await?)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// The outcome of the wait under `cid`, if it has completed.
+    async fn waiter_outcome(&self, cid: Uuid) -> Result<Option<WaiterOutcome>> {
+        let pool = &self.pool;
+        let row = self
+            .retrying("waiter_outcome", move || async move {
+                Ok(sqlx::query(
+                    "SELECT job_id, completed_at, result FROM job_waiters \
+                     WHERE correlation_id = $1 AND completed_at IS NOT NULL",
+                )
+                .bind(cid)
+                .fetch_optional(pool)
+                .await?)
+            })
+            .await?;
+
+        Ok(row.map(|row| WaiterOutcome {
+            correlation_id: CorrelationId::from(cid),
+            job_id: row.get("job_id"),
+            result: row.get("result"),
+            completed_at: row.get("completed_at"),
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "testkit"))]
+mod tests {
+    use seesaw_core::{FailureKind, JobStore};
+    use serde_json::json;
+
+    use super::*;
+    use crate::testkit::PgTestDb;
+
+    #[tokio::test]
+    async fn test_watched_job_wakes_waiter_on_success() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        // Only the notification can wake the waiter within the test
+        let store = db.store().with_poll_interval(Duration::from_secs(60));
+        let cid = CorrelationId::new();
+        let job_id = db.enqueue_test_job("report:render", json!({})).await?;
+        store.watch_job(cid, job_id).await?;
+
+        let waiter = tokio::spawn({
+            let store = store.clone();
+            async move { store.await_event_durable(cid, Duration::from_secs(30)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+        store.mark_succeeded(claimed.lease()).await?;
+
+        let outcome = tokio::time::timeout(Duration::from_secs(5), waiter).await???;
+        let outcome = outcome.unwrap();
+        assert_eq!(outcome.correlation_id, cid);
+        assert_eq!(outcome.job_id, Some(job_id));
+        assert_eq!(
+            outcome.result,
+            json!({ "job_id": job_id, "status": "succeeded", "error": null })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watching_finished_job_completes_at_once() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let cid = CorrelationId::new();
+        let job_id = db.enqueue_test_job("report:render", json!({})).await?;
+        let claimed = store.claim_ready("worker-1", 1).await?.remove(0);
+        store
+            .mark_failed(claimed.lease(), "template missing", FailureKind::NonRetryable)
+            .await?;
+
+        store.watch_job(cid, job_id).await?;
+
+        let outcome = store
+            .await_event_durable(cid, Duration::from_millis(50))
+            .await?
+            .unwrap();
+        assert_eq!(outcome.result["status"], "dead_letter");
+        assert_eq!(outcome.result["error"], "template missing");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_resumes_after_timeout_and_completes_once() -> Result<()> {
+        let db = PgTestDb::start().await?;
+        let store = db.store();
+        let cid = CorrelationId::new();
+
+        assert!(store
+            .await_event_durable(cid, Duration::from_millis(50))
+            .await?
+            .is_none());
+
+        let mut conn = db.pool().acquire().await?;
+        assert!(store.complete_waiter(&mut conn, cid, &json!({ "url": "a" })).await?);
+        assert!(!store.complete_waiter(&mut conn, cid, &json!({ "url": "b" })).await?);
+        let outcome = store
+            .await_event_durable(cid, Duration::from_millis(50))
+            .await?
+            .unwrap();
+        assert_eq!((outcome.job_id, outcome.result), (None, json!({ "url": "a" })));
+
+        let pending = CorrelationId::new();
+        store.await_event_durable(pending, Duration::ZERO).await?;
+        assert_eq!(store.purge_waiters(Utc::now() + chrono::Duration::seconds(1)).await?, 1);
+        let left: Vec<Uuid> = sqlx::query_scalar("SELECT correlation_id FROM job_waiters")
+            .fetch_all(db.pool())
+            .await?;
+        assert_eq!(left, [pending.into_inner()]);
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+//! SQL generation for the job store statements.
+//!
+//! The statements against the `jobs` table are the same in every backend
+//! up to syntax: bind parameters, the current time, enum casts, and how a
+//! batch is claimed. A backend describes those in a [`SqlDialect`] and gets
+//! every statement from [`JobQueries`]; its store only binds and runs them.
+
+/// Columns a claim returns, in the order of [`ClaimedJob`](seesaw_core::job::ClaimedJob).
+pub const CLAIM_COLUMNS: &str = "id, job_type, payload, version, attempt";
+
+/// The syntax of one SQL backend.
+pub trait SqlDialect: Send + Sync + 'static {
+    /// Bind parameter `n` (1-based): `$1` in Postgres, `?` in MySQL.
+    fn param(&self, n: usize) -> String;
+
+    /// Expression for the current time.
+    fn now(&self) -> &'static str;
+
+    /// Expression for the time `millis` milliseconds after `time`, where
+    /// `millis` is an integer column or parameter.
+    fn add_millis(&self, time: &str, millis: &str) -> String;
+
+    /// Cast `expr` to `sql_type`, where the backend does not convert
+    /// implicitly (text parameters to enums, enums to text).
+    fn cast(&self, expr: String, sql_type: &str) -> String {
+        let _ = sql_type;
+        expr
+    }
+
+    /// How ready jobs are claimed, returning `columns`.
+    ///
+    /// The default locks up to `limit` ready rows with
+    /// `FOR UPDATE SKIP LOCKED` and marks them running in a second statement.
+    fn claim_ready(&self, columns: &str) -> ClaimQuery {
+        ClaimQuery::LockThenUpdate {
+            select: format!(
+                "SELECT {columns} FROM jobs \
+                 WHERE status = 'pending' AND run_at <= {now} \
+                 ORDER BY priority ASC, run_at ASC \
+                 LIMIT {limit} \
+                 FOR UPDATE SKIP LOCKED",
+                now = self.now(),
+                limit = self.param(1),
+            ),
+        }
+    }
+}
+
+/// A backend's claim statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimQuery {
+    /// One statement that claims and returns the jobs.
+    ///
+    /// Binds: limit, worker ID, lease expiry, lease token.
+    Returning(String),
+    /// A locking select of the ready jobs (binds: limit), to be followed by
+    /// [`JobQueries::mark_running`] for the selected IDs in the same
+    /// transaction.
+    LockThenUpdate { select: String },
+}
+
+/// Every job store statement, generated for one dialect.
+///
+/// Build it once per backend (typically in a `LazyLock`); the bind order of
+/// each statement is documented on its field.
+///
+/// The statements have no notion of reservations: every claim starts an
+/// attempt, as with `JobStore`'s default `reserve`. A backend that supports
+/// two-phase claims needs a `reserved` column and its own claim, confirm,
+/// release and running-jobs statements, as `seesaw-job-postgres` has.
+#[derive(Debug)]
+pub struct JobQueries<D> {
+    dialect: D,
+    /// Claim ready jobs returning [`CLAIM_COLUMNS`].
+    pub claim_ready: ClaimQuery,
+    /// Pending jobs not yet ready, returning `id`, `job_type` and `run_at`,
+    /// soonest first. Binds: ready before, limit.
+    pub upcoming: String,
+    /// Running jobs of a worker, returning [`CLAIM_COLUMNS`] and
+    /// `lease_token`, oldest first. Binds: worker ID.
+    ///
+    /// Unconfirmed reservations are not filtered out (see above), so this
+    /// is only correct for backends that keep the default `reserve`.
+    pub running_jobs: String,
+    /// Affects no row if the lease was lost. Binds: job ID, lease token.
+    pub mark_succeeded: String,
+    /// Lock a job for a failure decision, returning `attempt` and
+    /// `max_retries`; no row if the lease was lost. Binds: job ID, lease
+    /// token.
+    pub lock_for_failure: String,
+    /// Requeue a job for another attempt. Binds: run at, error message, job ID.
+    pub retry: String,
+    /// Binds: error message, error kind, job ID.
+    pub dead_letter: String,
+    /// Extends by the job's own `lease_ms`, or to the bound expiry for jobs
+    /// without one. Affects no row if the lease was lost. Binds: lease
+    /// expiry, job ID, lease token.
+    pub heartbeat: String,
+    /// Binds: none.
+    pub reclaim_expired: String,
+    /// Binds: succeeded before.
+    pub cleanup_succeeded: String,
+    /// Returns `pending`, `running`, `succeeded`, `failed` and
+    /// `dead_letter` counts as 64-bit integers. Binds: none.
+    pub stats: String,
+    /// The `stats` counts plus `retrying`, per `job_type`. Binds: none.
+    pub job_type_stats: String,
+    /// Dead-lettered jobs, with `error_kind` as text and `failed_at`.
+    /// Binds: limit, offset.
+    pub dead_letters: String,
+    /// Binds: job ID.
+    pub retry_dead_letter: String,
+    /// Binds: job ID.
+    pub cancel: String,
+}
+
+impl<D: SqlDialect> JobQueries<D> {
+    /// Generate the statements for `dialect`.
+    pub fn new(dialect: D) -> Self {
+        let p = |n| dialect.param(n);
+        let now = dialect.now();
+        let count = |status: &str| format!("COUNT(CASE WHEN status = '{status}' THEN 1 END)");
+        let counts = format!(
+            "{} AS pending, {} AS running, {} AS succeeded, {} AS failed, {} AS dead_letter",
+            count("pending"),
+            count("running"),
+            count("succeeded"),
+            count("failed"),
+            count("dead_letter")
+        );
+
+        Self {
+            claim_ready: dialect.claim_ready(CLAIM_COLUMNS),
+            upcoming: format!(
+                "SELECT id, job_type, run_at FROM jobs \
+                 WHERE status = 'pending' AND run_at > {now} AND run_at <= {} \
+                 ORDER BY run_at ASC LIMIT {}",
+                p(1),
+                p(2)
+            ),
+            running_jobs: format!(
+                "SELECT {CLAIM_COLUMNS}, lease_token FROM jobs \
+                 WHERE worker_id = {} AND status = 'running' ORDER BY updated_at",
+                p(1)
+            ),
+            mark_succeeded: format!(
+                "UPDATE jobs SET status = 'succeeded', lease_token = NULL, updated_at = {now} \
+                 WHERE id = {} AND lease_token = {} AND status = 'running'",
+                p(1),
+                p(2)
+            ),
+            lock_for_failure: format!(
+                "SELECT attempt, max_retries FROM jobs \
+                 WHERE id = {} AND lease_token = {} AND status = 'running' FOR UPDATE",
+                p(1),
+                p(2)
+            ),
+            retry: format!(
+                "UPDATE jobs SET status = 'pending', run_at = {}, attempt = attempt + 1, \
+                 error_message = {}, error_kind = 'retryable', worker_id = NULL, \
+                 lease_expires_at = NULL, lease_token = NULL, updated_at = {now} WHERE id = {}",
+                p(1),
+                p(2),
+                p(3)
+            ),
+            dead_letter: format!(
+                "UPDATE jobs SET status = 'dead_letter', error_message = {}, error_kind = {}, \
+                 lease_token = NULL, updated_at = {now} WHERE id = {}",
+                p(1),
+                dialect.cast(p(2), "error_kind"),
+                p(3)
+            ),
+            heartbeat: format!(
+                "UPDATE jobs SET lease_expires_at = {}, updated_at = {now} \
+                 WHERE id = {} AND lease_token = {} AND status = 'running'",
+                job_lease_expiry(&dialect, p(1)),
+                p(2),
+                p(3)
+            ),
+            reclaim_expired: format!(
+                "UPDATE jobs SET status = 'pending', worker_id = NULL, lease_expires_at = NULL, \
+                 lease_token = NULL, updated_at = {now} \
+                 WHERE status = 'running' AND lease_expires_at < {now}"
+            ),
+            cleanup_succeeded: format!(
+                "DELETE FROM jobs WHERE status = 'succeeded' AND updated_at < {}",
+                p(1)
+            ),
+            stats: format!("SELECT {counts} FROM jobs"),
+            job_type_stats: format!(
+                "SELECT job_type, {counts}, \
+                 COUNT(CASE WHEN status = 'pending' AND attempt > 1 THEN 1 END) \
+                 AS retrying FROM jobs GROUP BY job_type ORDER BY job_type"
+            ),
+            dead_letters: format!(
+                "SELECT id, job_type, payload, version, attempt, error_message, \
+                 {} AS error_kind, updated_at AS failed_at FROM jobs \
+                 WHERE status = 'dead_letter' ORDER BY updated_at DESC LIMIT {} OFFSET {}",
+                dialect.cast("error_kind".to_string(), "TEXT"),
+                p(1),
+                p(2)
+            ),
+            retry_dead_letter: format!(
+                "UPDATE jobs SET status = 'pending', attempt = 1, run_at = {now}, \
+                 error_message = NULL, error_kind = NULL, worker_id = NULL, \
+                 lease_expires_at = NULL, lease_token = NULL, updated_at = {now} \
+                 WHERE id = {} AND status = 'dead_letter'",
+                p(1)
+            ),
+            cancel: format!(
+                "UPDATE jobs SET status = 'failed', error_message = 'cancelled', \
+                 updated_at = {now} WHERE id = {} AND status = 'pending'",
+                p(1)
+            ),
+            dialect,
+        }
+    }
+
+    /// Get the dialect the statements were generated for.
+    pub fn dialect(&self) -> &D {
+        &self.dialect
+    }
+
+    /// Mark `ids` claimed jobs running, after a
+    /// [`ClaimQuery::LockThenUpdate`] select.
+    ///
+    /// The bound lease expiry applies to jobs without their own `lease_ms`.
+    /// Binds: worker ID, lease expiry, lease token, then each job ID.
+    pub fn mark_running(&self, ids: usize) -> String {
+        let ids = (4..4 + ids)
+            .map(|n| self.dialect.param(n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "UPDATE jobs SET status = 'running', worker_id = {}, lease_expires_at = {}, \
+             lease_token = {}, updated_at = {} WHERE id IN ({ids})",
+            self.dialect.param(1),
+            job_lease_expiry(&self.dialect, self.dialect.param(2)),
+            self.dialect.param(3),
+            self.dialect.now()
+        )
+    }
+}
+
+/// Lease expiry of a job claimed or heartbeated now: its own `lease_ms`
+/// from now if set, `default` otherwise.
+pub fn job_lease_expiry(dialect: &impl SqlDialect, default: String) -> String {
+    format!(
+        "COALESCE({}, {default})",
+        dialect.add_millis(dialect.now(), "lease_ms")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Numbered;
+
+    impl SqlDialect for Numbered {
+        fn param(&self, n: usize) -> String {
+            format!("${n}")
+        }
+
+        fn now(&self) -> &'static str {
+            "NOW()"
+        }
+
+        fn add_millis(&self, time: &str, millis: &str) -> String {
+            format!("{time} + {millis} * INTERVAL '1 millisecond'")
+        }
+
+        fn cast(&self, expr: String, sql_type: &str) -> String {
+            format!("{expr}::{sql_type}")
+        }
+    }
+
+    #[test]
+    fn test_queries_use_dialect_syntax() {
+        let queries = JobQueries::new(Numbered);
+        assert_eq!(
+            queries.heartbeat,
+            "UPDATE jobs SET lease_expires_at = \
+             COALESCE(NOW() + lease_ms * INTERVAL '1 millisecond', $1), updated_at = NOW() \
+             WHERE id = $2 AND lease_token = $3 AND status = 'running'"
+        );
+        assert!(queries.dead_letter.contains("error_kind = $2::error_kind"));
+        assert!(queries
+            .upcoming
+            .ends_with("run_at > NOW() AND run_at <= $1 ORDER BY run_at ASC LIMIT $2"));
+        match &queries.claim_ready {
+            ClaimQuery::LockThenUpdate { select } => {
+                assert!(select.starts_with("SELECT id, job_type, payload, version, attempt FROM"));
+                assert!(select.contains("LIMIT $1 FOR UPDATE SKIP LOCKED"));
+            }
+            other => panic!("expected the default claim, got {other:?}"),
+        }
+        let mark_running = queries.mark_running(2);
+        assert!(mark_running.contains(
+            "lease_expires_at = COALESCE(NOW() + lease_ms * INTERVAL '1 millisecond', $2)"
+        ));
+        assert!(mark_running.ends_with("WHERE id IN ($4, $5)"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Backend-agnostic core of the seesaw SQL job stores.
+//!
+//! Every SQL backend (`seesaw-job-postgres`, `seesaw-job-mysql`) stores jobs
+//! in the same `jobs` table shape and must agree on what a failure does to
+//! a job: how long the retry backoff is, when a job is dead-lettered, and
+//! how long a claim holds. Keeping those decisions here means a job behaves
+//! the same whichever database it is queued in.
+//!
+//! The statements themselves come from [`JobQueries`], generated from a
+//! backend's [`SqlDialect`], so a backend only describes its syntax and
+//! binds parameters:
+//!
+//! ```ignore
+//! struct MyDialect;
+//!
+//! impl SqlDialect for MyDialect {
+//!     fn param(&self, _n: usize) -> String {
+//!         "?".to_string()
+//!     }
+//!
+//!     fn now(&self) -> &'static str {
+//!         "CURRENT_TIMESTAMP"
+//!     }
+//!
+//!     fn add_millis(&self, time: &str, millis: &str) -> String {
+//!         format!("{time} + {millis} * INTERVAL '1' MILLISECOND")
+//!     }
+//! }
+//!
+//! static QUERIES: LazyLock<JobQueries<MyDialect>> = LazyLock::new(|| JobQueries::new(MyDialect));
+//! ```
+//!
+//! Failures are decided by [`failure_outcome`]:
+//!
+//! ```ignore
+//! match failure_outcome(kind, attempt, max_retries, Utc::now()) {
+//!     FailureOutcome::Retry { run_at } => {
+//!         sqlx::query(&QUERIES.retry).bind(run_at).bind(error).bind(job_id)
+//!     }
+//!     FailureOutcome::DeadLetter => {
+//!         sqlx::query(&QUERIES.dead_letter).bind(error).bind(error_kind_label(kind)).bind(job_id)
+//!     }
+//! }
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use seesaw_core::job::FailureKind;
+
+mod dialect;
+
+// Re-export statement generation
+pub use dialect::{job_lease_expiry, ClaimQuery, JobQueries, SqlDialect, CLAIM_COLUMNS};
+
+pub use seesaw_core::job::QueueStats;
+
+/// Lease a claim holds unless the store or the job's `lease_ms` says
+/// otherwise.
+pub const DEFAULT_LEASE_MS: i64 = 60_000;
+
+/// Longest backoff between two attempts, in seconds.
+pub const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+/// Backoff after a failed `attempt` (1-based): 2, 4, 8, ... seconds, capped
+/// at [`MAX_RETRY_DELAY_SECS`].
+pub fn retry_delay(attempt: i32) -> Duration {
+    let secs = 2i64
+        .checked_pow(attempt.max(0) as u32)
+        .unwrap_or(MAX_RETRY_DELAY_SECS)
+        .min(MAX_RETRY_DELAY_SECS);
+    Duration::seconds(secs)
+}
+
+/// When a lease taken at `now` for `lease_ms` expires.
+pub fn lease_expiry(now: DateTime<Utc>, lease_ms: i64) -> DateTime<Utc> {
+    now + Duration::milliseconds(lease_ms)
+}
+
+/// End of a claim look-ahead of `lookahead` from `now`, for binding to
+/// [`JobQueries::upcoming`].
+pub fn lookahead_until(now: DateTime<Utc>, lookahead: std::time::Duration) -> DateTime<Utc> {
+    Duration::from_std(lookahead)
+        .ok()
+        .and_then(|lookahead| now.checked_add_signed(lookahead))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// What a failed attempt does to its job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    /// Back to pending, claimable again at `run_at`, with the attempt
+    /// counter incremented.
+    Retry { run_at: DateTime<Utc> },
+    /// Moved to the dead letter queue, never claimed again.
+    DeadLetter,
+}
+
+/// Decide what a failure of `kind` on `attempt` of `max_retries` leads to.
+///
+/// Retryable failures are retried until the attempt reaches `max_retries`;
+/// non-retryable failures are dead-lettered at once.
+pub fn failure_outcome(
+    kind: FailureKind,
+    attempt: i32,
+    max_retries: i32,
+    now: DateTime<Utc>,
+) -> FailureOutcome {
+    match kind {
+        FailureKind::Retryable if attempt < max_retries => FailureOutcome::Retry {
+            run_at: now + retry_delay(attempt),
+        },
+        _ => FailureOutcome::DeadLetter,
+    }
+}
+
+/// Value of the `error_kind` column for a failure of `kind`.
+pub fn error_kind_label(kind: FailureKind) -> &'static str {
+    match kind {
+        FailureKind::Retryable => "retryable",
+        FailureKind::NonRetryable => "non_retryable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1), Duration::seconds(2));
+        assert_eq!(retry_delay(3), Duration::seconds(8));
+        assert_eq!(retry_delay(12), Duration::seconds(MAX_RETRY_DELAY_SECS));
+        assert_eq!(retry_delay(100), Duration::seconds(MAX_RETRY_DELAY_SECS));
+    }
+
+    #[test]
+    fn test_lookahead_until_saturates() {
+        let now = Utc::now();
+        assert_eq!(
+            lookahead_until(now, std::time::Duration::from_secs(5)),
+            now + Duration::seconds(5)
+        );
+        assert_eq!(
+            lookahead_until(now, std::time::Duration::MAX),
+            DateTime::<Utc>::MAX_UTC
+        );
+    }
+
+    #[test]
+    fn test_failure_outcome() {
+        let now = Utc::now();
+        assert_eq!(
+            failure_outcome(FailureKind::Retryable, 1, 3, now),
+            FailureOutcome::Retry {
+                run_at: now + Duration::seconds(2)
+            }
+        );
+        assert_eq!(
+            failure_outcome(FailureKind::Retryable, 3, 3, now),
+            FailureOutcome::DeadLetter
+        );
+        assert_eq!(
+            failure_outcome(FailureKind::NonRetryable, 1, 3, now),
+            FailureOutcome::DeadLetter
+        );
+    }
+}
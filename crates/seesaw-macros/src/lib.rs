@@ -0,0 +1,546 @@
+//! Derive macros for Seesaw.
+//!
+//! Use these through `seesaw-core` with the `derive` feature rather than
+//! depending on this crate directly; the generated code refers to
+//! `::seesaw_core`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, LitStr};
+
+/// Implement `Command`, configured through `#[command(...)]` attributes.
+///
+/// | Attribute                 | Generates                                           |
+/// |---------------------------|-----------------------------------------------------|
+/// | `mode = "background"`     | `execution_mode` (`inline` by default)              |
+/// | `mode = "scheduled"`      | `execution_mode`, needs `run_at`                    |
+/// | `run_at = "field"`        | `ExecutionMode::Scheduled { run_at: self.field }`   |
+/// | `mode = "recurring"`      | `execution_mode`, needs `cron`                      |
+/// | `cron = "0 9 * * MON"`    | `ExecutionMode::Recurring { cron, tz }`             |
+/// | `tz = "Europe/Berlin"`    | the `Recurring` time zone (`"UTC"` by default)      |
+/// | `job_type = "email:send"` | `job_spec` and `serialize_to_json` (via serde)      |
+/// | `max_retries = 5`         | `JobSpec::with_max_retries`                         |
+/// | `job_priority = 10`       | `JobSpec::with_priority`                            |
+/// | `version = 2`             | `JobSpec::with_version`                             |
+/// | `idempotency_key = "..."` | `JobSpec::with_idempotency_key`, formatting fields  |
+/// | `priority = 1`            | `priority` (inline dispatch lane)                   |
+/// | `independent`             | `independent` (concurrent inline dispatch)          |
+///
+/// Background, scheduled and recurring commands must set `job_type` and
+/// derive `Serialize`.
+///
+/// # Example
+///
+/// ```ignore
+/// use seesaw_core::SeesawCommand;
+///
+/// #[derive(Debug, Clone, Serialize, Deserialize, SeesawCommand)]
+/// #[command(
+///     mode = "background",
+///     job_type = "email:send",
+///     max_retries = 5,
+///     idempotency_key = "email:{user_id}:{template}"
+/// )]
+/// struct SendEmailCommand {
+///     user_id: Uuid,
+///     template: String,
+/// }
+/// ```
+#[proc_macro_derive(SeesawCommand, attributes(command))]
+pub fn derive_seesaw_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implement `CommandVariant` and `From` for each variant of an enum command.
+///
+/// Every variant must wrap exactly one command type, and no two variants may
+/// wrap the same type. Each wrapped type can then get its own effect through
+/// `with_effect_for`.
+///
+/// # Example
+///
+/// ```ignore
+/// use seesaw_core::CommandVariants;
+///
+/// #[derive(Debug, Clone, CommandVariants)]
+/// enum DeckCommand {
+///     Setup(SetupLoaf),
+///     Summarize(Summarize),
+/// }
+///
+/// let engine = EngineBuilder::new(deps)
+///     .with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+///     .with_effect_for::<DeckCommand, Summarize, _>(SummarizeEffect)
+///     .build();
+/// ```
+#[proc_macro_derive(CommandVariants)]
+pub fn derive_command_variants(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_variants(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implement `EventSelector` and `From` for an enum over several event types.
+///
+/// Every variant must wrap exactly one event type, and no two variants may
+/// wrap the same type. A machine whose `Event` is the enum, registered with
+/// `with_selector_machine`, receives every event of a wrapped type.
+///
+/// # Example
+///
+/// ```ignore
+/// use seesaw_core::EventSelector;
+///
+/// #[derive(Debug, Clone, EventSelector)]
+/// enum FulfillmentInput {
+///     Order(OrderEvent),
+///     Payment(PaymentEvent),
+/// }
+/// ```
+#[proc_macro_derive(EventSelector)]
+pub fn derive_event_selector(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_selector(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implement `FromDeps<Self>` for the type of every field of a deps struct,
+/// cloning the field out.
+///
+/// Two fields of the same type would conflict; mark all but one with
+/// `#[from_deps(skip)]`.
+///
+/// # Example
+///
+/// ```ignore
+/// use seesaw_core::FromDeps;
+///
+/// #[derive(Clone, FromDeps)]
+/// struct Deps {
+///     db: PgPool,
+///     http: reqwest::Client,
+///     #[from_deps(skip)]
+///     replica: PgPool,
+/// }
+/// ```
+#[proc_macro_derive(FromDeps, attributes(from_deps))]
+pub fn derive_from_deps(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_deps(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+// =============================================================================
+// Attribute Parsing
+// =============================================================================
+
+#[derive(Default)]
+struct CommandAttrs {
+    mode: Option<LitStr>,
+    run_at: Option<LitStr>,
+    cron: Option<LitStr>,
+    tz: Option<LitStr>,
+    job_type: Option<LitStr>,
+    max_retries: Option<LitInt>,
+    job_priority: Option<LitInt>,
+    version: Option<LitInt>,
+    idempotency_key: Option<LitStr>,
+    priority: Option<LitInt>,
+    independent: Option<syn::Path>,
+}
+
+impl CommandAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("command")) {
+            attr.parse_nested_meta(|meta| {
+                let slot_str = |slot: &mut Option<LitStr>| -> syn::Result<()> {
+                    set_once(slot, meta.value()?.parse()?, &meta.path)
+                };
+                let slot_int = |slot: &mut Option<LitInt>| -> syn::Result<()> {
+                    set_once(slot, meta.value()?.parse()?, &meta.path)
+                };
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(Ident::to_string)
+                    .unwrap_or_default();
+                match key.as_str() {
+                    "mode" => slot_str(&mut attrs.mode),
+                    "run_at" => slot_str(&mut attrs.run_at),
+                    "cron" => slot_str(&mut attrs.cron),
+                    "tz" => slot_str(&mut attrs.tz),
+                    "job_type" => slot_str(&mut attrs.job_type),
+                    "idempotency_key" => slot_str(&mut attrs.idempotency_key),
+                    "max_retries" => slot_int(&mut attrs.max_retries),
+                    "job_priority" => slot_int(&mut attrs.job_priority),
+                    "version" => slot_int(&mut attrs.version),
+                    "priority" => slot_int(&mut attrs.priority),
+                    "independent" => {
+                        set_once(&mut attrs.independent, meta.path.clone(), &meta.path)
+                    }
+                    _ => Err(meta.error(
+                        "unknown command attribute; expected one of `mode`, `run_at`, \
+                         `cron`, `tz`, `job_type`, `max_retries`, `job_priority`, `version`, \
+                         `idempotency_key`, `priority`, `independent`",
+                    )),
+                }
+            })?;
+        }
+        Ok(attrs)
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, path: &syn::Path) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(path, "duplicate command attribute"));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+// =============================================================================
+// Expansion
+// =============================================================================
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = CommandAttrs::parse(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mode = attrs
+        .mode
+        .as_ref()
+        .map_or_else(|| "inline".to_string(), LitStr::value);
+    if mode != "recurring" {
+        reject(&attrs.cron, "`cron` requires `mode = \"recurring\"`")?;
+        reject(&attrs.tz, "`tz` requires `mode = \"recurring\"`")?;
+    }
+    let execution_mode = match mode.as_str() {
+        "inline" => {
+            reject(&attrs.run_at, "`run_at` requires `mode = \"scheduled\"`")?;
+            None
+        }
+        "background" => {
+            reject(&attrs.run_at, "`run_at` requires `mode = \"scheduled\"`")?;
+            Some(quote! { ::seesaw_core::ExecutionMode::Background })
+        }
+        "recurring" => {
+            reject(&attrs.run_at, "`run_at` requires `mode = \"scheduled\"`")?;
+            let cron = attrs.cron.as_ref().ok_or_else(|| {
+                syn::Error::new_spanned(
+                    attrs.mode.as_ref().unwrap(),
+                    "recurring commands need `cron = \"...\"`",
+                )
+            })?;
+            let tz = attrs.tz.as_ref().map_or_else(|| "UTC".to_string(), LitStr::value);
+            Some(quote! { ::seesaw_core::ExecutionMode::Recurring { cron: #cron, tz: #tz } })
+        }
+        "scheduled" => {
+            let field = attrs.run_at.as_ref().ok_or_else(|| {
+                syn::Error::new_spanned(
+                    attrs.mode.as_ref().unwrap(),
+                    "scheduled commands need `run_at = \"field\"`",
+                )
+            })?;
+            let field: Ident = field.parse()?;
+            Some(quote! { ::seesaw_core::ExecutionMode::Scheduled { run_at: self.#field } })
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attrs.mode.as_ref().unwrap(),
+                "`mode` must be \"inline\", \"background\", \"scheduled\" or \"recurring\"",
+            ))
+        }
+    };
+
+    if mode != "inline" && attrs.job_type.is_none() {
+        return Err(syn::Error::new_spanned(
+            attrs.mode.as_ref().unwrap(),
+            "background, scheduled and recurring commands need `job_type = \"...\"`",
+        ));
+    }
+    if attrs.job_type.is_none() {
+        for (slot, key) in [
+            (&attrs.max_retries, "max_retries"),
+            (&attrs.job_priority, "job_priority"),
+            (&attrs.version, "version"),
+        ] {
+            if let Some(lit) = slot {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    format!("`{}` requires `job_type`", key),
+                ));
+            }
+        }
+        reject(
+            &attrs.idempotency_key,
+            "`idempotency_key` requires `job_type`",
+        )?;
+    }
+
+    let execution_mode = execution_mode.map(|mode| {
+        quote! {
+            fn execution_mode(&self) -> ::seesaw_core::ExecutionMode {
+                #mode
+            }
+        }
+    });
+
+    let job_methods = match &attrs.job_type {
+        Some(job_type) => {
+            let mut spec = quote! { ::seesaw_core::JobSpec::new(#job_type) };
+            if let Some(n) = &attrs.max_retries {
+                spec = quote! { #spec.with_max_retries(#n) };
+            }
+            if let Some(n) = &attrs.job_priority {
+                spec = quote! { #spec.with_priority(#n) };
+            }
+            if let Some(n) = &attrs.version {
+                spec = quote! { #spec.with_version(#n) };
+            }
+            if let Some(key) = &attrs.idempotency_key {
+                let key = idempotency_key(input, key)?;
+                spec = quote! { #spec.with_idempotency_key(#key) };
+            }
+            Some(quote! {
+                fn job_spec(&self) -> ::std::option::Option<::seesaw_core::JobSpec> {
+                    ::std::option::Option::Some(#spec)
+                }
+
+                fn serialize_to_json(
+                    &self,
+                ) -> ::std::option::Option<::seesaw_core::__private::serde_json::Value> {
+                    ::seesaw_core::__private::serde_json::to_value(self).ok()
+                }
+            })
+        }
+        None => None,
+    };
+
+    let priority = attrs.priority.as_ref().map(|n| {
+        quote! {
+            fn priority(&self) -> u8 {
+                #n
+            }
+        }
+    });
+
+    let independent = attrs.independent.as_ref().map(|_| {
+        quote! {
+            fn independent(&self) -> bool {
+                true
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::seesaw_core::Command for #name #ty_generics #where_clause {
+            #execution_mode
+            #job_methods
+            #priority
+            #independent
+        }
+    })
+}
+
+fn reject(slot: &Option<LitStr>, message: &str) -> syn::Result<()> {
+    match slot {
+        Some(lit) => Err(syn::Error::new_spanned(lit, message)),
+        None => Ok(()),
+    }
+}
+
+/// `format!(key)` with every named field in scope, so that `"email:{user_id}"`
+/// reads `self.user_id`.
+fn idempotency_key(input: &DeriveInput, key: &LitStr) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().filter_map(|f| f.ident.as_ref()),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "`idempotency_key` is only supported on structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                key,
+                "`idempotency_key` is only supported on structs with named fields",
+            ))
+        }
+    };
+    Ok(quote! {
+        {
+            #[allow(unused_variables)]
+            let Self { #(#fields,)* .. } = self;
+            ::std::format!(#key)
+        }
+    })
+}
+
+// =============================================================================
+// Variant Expansion
+// =============================================================================
+
+fn expand_variants(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "CommandVariants can only be derived for enums",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut impls = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let ty = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "each variant must wrap exactly one command type, e.g. `Setup(SetupLoaf)`",
+                ))
+            }
+        };
+        impls.push(quote! {
+            impl #impl_generics ::seesaw_core::CommandVariant<#ty> for #name #ty_generics #where_clause {
+                #[allow(unreachable_patterns)]
+                fn into_variant(self) -> ::std::result::Result<#ty, Self> {
+                    match self {
+                        Self::#ident(command) => ::std::result::Result::Ok(command),
+                        other => ::std::result::Result::Err(other),
+                    }
+                }
+            }
+
+            impl #impl_generics ::std::convert::From<#ty> for #name #ty_generics #where_clause {
+                fn from(command: #ty) -> Self {
+                    Self::#ident(command)
+                }
+            }
+        });
+    }
+    Ok(quote! { #(#impls)* })
+}
+
+// =============================================================================
+// Selector Expansion
+// =============================================================================
+
+fn expand_selector(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "EventSelector can only be derived for enums",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut idents = Vec::with_capacity(data.variants.len());
+    let mut types = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                idents.push(&variant.ident);
+                types.push(&fields.unnamed[0].ty);
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "each variant must wrap exactly one event type, e.g. `Order(OrderEvent)`",
+                ))
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::seesaw_core::EventSelector for #name #ty_generics #where_clause {
+            fn event_types() -> ::std::vec::Vec<::std::any::TypeId> {
+                ::std::vec![#(::std::any::TypeId::of::<#types>()),*]
+            }
+
+            fn select(event: &dyn ::std::any::Any) -> ::std::option::Option<Self> {
+                #(
+                    if let ::std::option::Option::Some(event) = event.downcast_ref::<#types>() {
+                        return ::std::option::Option::Some(Self::#idents(::std::clone::Clone::clone(event)));
+                    }
+                )*
+                ::std::option::Option::None
+            }
+        }
+
+        #(
+            impl #impl_generics ::std::convert::From<#types> for #name #ty_generics #where_clause {
+                fn from(event: #types) -> Self {
+                    Self::#idents(event)
+                }
+            }
+        )*
+    })
+}
+
+// =============================================================================
+// Deps Expansion
+// =============================================================================
+
+fn expand_from_deps(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromDeps can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromDeps can only be derived for structs with named fields",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut impls = Vec::with_capacity(fields.named.len());
+    for field in &fields.named {
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("from_deps")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown from_deps attribute; expected `skip`"))
+                }
+            })?;
+        }
+        if skip {
+            continue;
+        }
+
+        let ident = &field.ident;
+        let ty = &field.ty;
+        impls.push(quote! {
+            impl #impl_generics ::seesaw_core::FromDeps<#name #ty_generics> for #ty #where_clause {
+                fn from_deps(deps: &#name #ty_generics) -> Self {
+                    ::std::clone::Clone::clone(&deps.#ident)
+                }
+            }
+        });
+    }
+    Ok(quote! { #(#impls)* })
+}
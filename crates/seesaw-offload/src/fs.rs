@@ -0,0 +1,120 @@
+//! Payloads as files in a directory.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use uuid::Uuid;
+
+use seesaw_core::PayloadOffload;
+
+/// Stores each payload as one file in a directory.
+///
+/// Files are written to a temporary name and renamed into place, so a
+/// reader never sees a partial payload. The directory is created on first
+/// write.
+#[derive(Debug, Clone)]
+pub struct FsPayloadOffload {
+    dir: PathBuf,
+}
+
+impl FsPayloadOffload {
+    /// Store payloads in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory payloads are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file for `key`, refusing keys that could leave the directory.
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let safe = !key.is_empty()
+            && !key.starts_with('.')
+            && key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+        if !safe {
+            bail!("invalid payload key {key:?}");
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+#[async_trait]
+impl PayloadOffload for FsPayloadOffload {
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("creating {}", self.dir.display()))?;
+
+        let temp = self
+            .dir
+            .join(format!(".{key}.{}.tmp", Uuid::new_v4().simple()));
+        tokio::fs::write(&temp, &bytes)
+            .await
+            .with_context(|| format!("writing {}", temp.display()))?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            tokio::fs::remove_file(&temp).await.ok();
+            return Err(e).with_context(|| format!("renaming into {}", path.display()));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes> {
+        let path = self.path(key)?;
+        let bytes = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("reading {}", path.display()))?;
+        Ok(bytes.into())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("deleting {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("seesaw-offload-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_and_delete() {
+        let offload = FsPayloadOffload::new(temp_dir());
+        let key = Uuid::new_v4().to_string();
+
+        offload.put(&key, Bytes::from("payload")).await.unwrap();
+        assert_eq!(offload.get(&key).await.unwrap(), Bytes::from("payload"));
+
+        offload.delete(&key).await.unwrap();
+        assert!(offload.get(&key).await.is_err());
+        // Deleting again is not an error
+        offload.delete(&key).await.unwrap();
+
+        tokio::fs::remove_dir_all(offload.dir()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_keys_outside_the_directory() {
+        let offload = FsPayloadOffload::new(temp_dir());
+
+        for key in ["../escape", "nested/key", ".hidden", ""] {
+            assert!(offload.put(key, Bytes::new()).await.is_err(), "{key:?}");
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Payloads as objects in Google Cloud Storage.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use percent_encoding::utf8_percent_encode;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use seesaw_core::PayloadOffload;
+
+use crate::{response_error, OBJECT_NAME};
+
+/// Default JSON API endpoint.
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+
+/// Access token endpoint of the GCE/GKE metadata server.
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// How long before expiry a cached token is refreshed.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Supplies OAuth2 access tokens for Cloud Storage requests.
+#[async_trait]
+pub trait GcsTokenSource: Send + Sync + 'static {
+    /// A currently valid access token.
+    async fn token(&self) -> Result<String>;
+}
+
+/// A fixed access token, e.g. from `gcloud auth print-access-token`.
+#[derive(Clone)]
+pub struct StaticToken(pub String);
+
+#[async_trait]
+impl GcsTokenSource for StaticToken {
+    async fn token(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Tokens of the instance's service account, from the metadata server on
+/// Compute Engine, GKE and Cloud Run. Cached until shortly before they
+/// expire.
+#[derive(Default)]
+pub struct MetadataServerToken {
+    client: reqwest::Client,
+    cached: Mutex<Option<(String, Instant)>>,
+}
+
+impl MetadataServerToken {
+    /// Create a token source with an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[async_trait]
+impl GcsTokenSource for MetadataServerToken {
+    async fn token(&self) -> Result<String> {
+        if let Some((token, expires)) = &*self.cached.lock().unwrap() {
+            if Instant::now() + TOKEN_REFRESH_MARGIN < *expires {
+                return Ok(token.clone());
+            }
+        }
+
+        let body = self
+            .client
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .context("requesting a token from the metadata server")?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let response: TokenResponse = serde_json::from_slice(&body)?;
+        let expires = Instant::now() + Duration::from_secs(response.expires_in);
+        *self.cached.lock().unwrap() = Some((response.access_token.clone(), expires));
+        Ok(response.access_token)
+    }
+}
+
+/// Stores payloads as objects in a Cloud Storage bucket, through the JSON
+/// API.
+#[derive(Clone)]
+pub struct GcsPayloadOffload {
+    client: reqwest::Client,
+    bucket: String,
+    prefix: String,
+    endpoint: String,
+    tokens: Arc<dyn GcsTokenSource>,
+}
+
+impl GcsPayloadOffload {
+    /// Store payloads in `bucket`, authorized by `tokens`.
+    pub fn new(bucket: impl Into<String>, tokens: impl GcsTokenSource) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            bucket: bucket.into(),
+            prefix: String::new(),
+            endpoint: GCS_ENDPOINT.to_string(),
+            tokens: Arc::new(tokens),
+        }
+    }
+
+    /// Prepend `prefix` to every object name, e.g. `"jobs/"`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Send requests to `endpoint` instead of Cloud Storage, e.g. an
+    /// emulator.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send requests with `client`, e.g. one with custom timeouts.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// `key`'s object name, encoded as one URL path segment or query value.
+    fn encoded_name(&self, key: &str) -> String {
+        utf8_percent_encode(&format!("{}{key}", self.prefix), OBJECT_NAME).to_string()
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o/{}",
+            self.endpoint,
+            self.bucket,
+            self.encoded_name(key)
+        )
+    }
+
+    fn upload_url(&self, key: &str) -> String {
+        format!(
+            "{}/upload/storage/v1/b/{}/o?uploadType=media&name={}",
+            self.endpoint,
+            self.bucket,
+            self.encoded_name(key)
+        )
+    }
+}
+
+impl std::fmt::Debug for GcsPayloadOffload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcsPayloadOffload")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl PayloadOffload for GcsPayloadOffload {
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()> {
+        let response = self
+            .client
+            .post(self.upload_url(key))
+            .bearer_auth(self.tokens.token().await?)
+            .header("Content-Type", "application/octet-stream")
+            .body(bytes)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(response_error("upload", key, response).await);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes> {
+        let response = self
+            .client
+            .get(format!("{}?alt=media", self.object_url(key)))
+            .bearer_auth(self.tokens.token().await?)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(response_error("download", key, response).await);
+        }
+        Ok(response.bytes().await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(self.object_url(key))
+            .bearer_auth(self.tokens.token().await?)
+            .send()
+            .await?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            return Err(response_error("delete", key, response).await);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_names_are_encoded_whole() {
+        let offload =
+            GcsPayloadOffload::new("payloads", StaticToken("t".into())).with_prefix("jobs/");
+
+        assert_eq!(
+            offload.object_url("4f1c-key"),
+            "https://storage.googleapis.com/storage/v1/b/payloads/o/jobs%2F4f1c-key"
+        );
+        assert_eq!(
+            offload.upload_url("4f1c-key"),
+            "https://storage.googleapis.com/upload/storage/v1/b/payloads/o?uploadType=media&name=jobs%2F4f1c-key"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Storage backends for offloading large seesaw job payloads.
+//!
+//! Each backend implements [`PayloadOffload`], which
+//! [`OffloadingJobQueue`](seesaw_core::OffloadingJobQueue) writes payloads
+//! above a size threshold to and
+//! [`OffloadingJobStore`](seesaw_core::OffloadingJobStore) reads them back
+//! from when jobs are claimed. The queue keeps only a reference, so the
+//! jobs table stays small however large the documents being processed are.
+//!
+//! | Backend | Feature | Storage |
+//! |---------|---------|---------|
+//! | [`FsPayloadOffload`] | `fs` (default) | A directory, local or on a shared volume |
+//! | [`S3PayloadOffload`] | `s3` | Amazon S3, or an S3-compatible store such as MinIO or R2 |
+//! | [`GcsPayloadOffload`] | `gcs` | Google Cloud Storage |
+//!
+//! Producers and workers must use the same backend and location. The
+//! filesystem backend only works across machines on a shared volume.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_core::{OffloadingJobQueue, OffloadingJobStore, PayloadOffload};
+//! use seesaw_offload::S3PayloadOffload;
+//!
+//! let offload: Arc<dyn PayloadOffload> =
+//!     Arc::new(S3PayloadOffload::from_env("acme-job-payloads")?.with_prefix("jobs/"));
+//!
+//! // Producer
+//! let queue = OffloadingJobQueue::new(queue, offload.clone());
+//!
+//! // Worker
+//! let store = OffloadingJobStore::new(PgJobStore::new(pool), offload);
+//! ```
+
+#[cfg(feature = "fs")]
+mod fs;
+#[cfg(feature = "gcs")]
+mod gcs;
+#[cfg(feature = "s3")]
+mod s3;
+
+#[cfg(feature = "fs")]
+pub use fs::FsPayloadOffload;
+#[cfg(feature = "gcs")]
+pub use gcs::{GcsPayloadOffload, GcsTokenSource, MetadataServerToken, StaticToken};
+#[cfg(feature = "s3")]
+pub use s3::{S3Credentials, S3PayloadOffload};
+pub use seesaw_core::PayloadOffload;
+
+/// Status and body of an unsuccessful storage response, for errors.
+#[cfg(any(feature = "s3", feature = "gcs"))]
+async fn response_error(operation: &str, key: &str, response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.chars().take(512).collect();
+    anyhow::anyhow!("{operation} of payload {key} failed with {status}: {body}")
+}
+
+/// Characters left unescaped in an object name as one URL component: the
+/// unreserved ones.
+#[cfg(any(feature = "s3", feature = "gcs"))]
+const OBJECT_NAME: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Characters left unescaped in an object name as a URL path: the
+/// unreserved ones and `/`.
+#[cfg(feature = "s3")]
+const OBJECT_PATH: &percent_encoding::AsciiSet = &OBJECT_NAME.remove(b'/');
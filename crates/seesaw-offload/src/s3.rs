@@ -0,0 +1,341 @@
+//! Payloads as objects in S3 or an S3-compatible store.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::utf8_percent_encode;
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+use seesaw_core::PayloadOffload;
+
+use crate::{response_error, OBJECT_PATH};
+
+/// AWS access keys for signing requests.
+#[derive(Clone)]
+pub struct S3Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl S3Credentials {
+    /// Long-lived access keys.
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    /// Temporary credentials: the keys plus their session token.
+    pub fn with_session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    /// Read `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and, if set,
+    /// `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self> {
+        let credentials = Self::new(
+            std::env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+            std::env::var("AWS_SECRET_ACCESS_KEY").context("AWS_SECRET_ACCESS_KEY is not set")?,
+        );
+        Ok(match std::env::var("AWS_SESSION_TOKEN") {
+            Ok(token) => credentials.with_session_token(token),
+            Err(_) => credentials,
+        })
+    }
+}
+
+impl std::fmt::Debug for S3Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stores payloads as objects in an S3 bucket.
+///
+/// Requests are signed with AWS Signature Version 4. Credentials are fixed
+/// when the backend is created; temporary credentials must be replaced by
+/// creating a new backend before they expire.
+#[derive(Debug, Clone)]
+pub struct S3PayloadOffload {
+    client: reqwest::Client,
+    bucket: String,
+    region: String,
+    endpoint: Option<Url>,
+    prefix: String,
+    credentials: S3Credentials,
+}
+
+impl S3PayloadOffload {
+    /// Store payloads in `bucket` in `region`.
+    pub fn new(
+        bucket: impl Into<String>,
+        region: impl Into<String>,
+        credentials: S3Credentials,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            bucket: bucket.into(),
+            region: region.into(),
+            endpoint: None,
+            prefix: String::new(),
+            credentials,
+        }
+    }
+
+    /// Store payloads in `bucket`, configured from the standard AWS
+    /// environment variables.
+    ///
+    /// The region comes from `AWS_REGION` or `AWS_DEFAULT_REGION`, the
+    /// credentials from [`S3Credentials::from_env`], and a custom endpoint
+    /// from `AWS_ENDPOINT_URL_S3` or `AWS_ENDPOINT_URL` if set.
+    pub fn from_env(bucket: impl Into<String>) -> Result<Self> {
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .context("AWS_REGION is not set")?;
+        let offload = Self::new(bucket, region, S3Credentials::from_env()?);
+        match std::env::var("AWS_ENDPOINT_URL_S3").or_else(|_| std::env::var("AWS_ENDPOINT_URL")) {
+            Ok(endpoint) => Ok(offload.with_endpoint(endpoint.parse()?)),
+            Err(_) => Ok(offload),
+        }
+    }
+
+    /// Send requests to `endpoint` with path-style URLs, for S3-compatible
+    /// stores such as MinIO.
+    pub fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Prepend `prefix` to every object name, e.g. `"jobs/"`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Send requests with `client`, e.g. one with custom timeouts.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn object_url(&self, key: &str) -> Result<Url> {
+        let name = utf8_percent_encode(&format!("{}{key}", self.prefix), OBJECT_PATH).to_string();
+        let url = match &self.endpoint {
+            Some(endpoint) => format!(
+                "{}/{}/{name}",
+                endpoint.as_str().trim_end_matches('/'),
+                self.bucket
+            ),
+            None => format!(
+                "https://{}.s3.{}.amazonaws.com/{name}",
+                self.bucket, self.region
+            ),
+        };
+        Ok(url.parse()?)
+    }
+
+    async fn send(&self, method: Method, key: &str, body: Bytes) -> Result<reqwest::Response> {
+        let url = self.object_url(key)?;
+        let headers = sign(
+            &method,
+            &url,
+            &body,
+            Utc::now(),
+            &self.region,
+            &self.credentials,
+        );
+        let mut request = self.client.request(method, url).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        Ok(request.send().await?)
+    }
+}
+
+#[async_trait]
+impl PayloadOffload for S3PayloadOffload {
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()> {
+        let response = self.send(Method::PUT, key, bytes).await?;
+        if !response.status().is_success() {
+            return Err(response_error("upload", key, response).await);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes> {
+        let response = self.send(Method::GET, key, Bytes::new()).await?;
+        if !response.status().is_success() {
+            return Err(response_error("download", key, response).await);
+        }
+        Ok(response.bytes().await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.send(Method::DELETE, key, Bytes::new()).await?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            return Err(response_error("delete", key, response).await);
+        }
+        Ok(())
+    }
+}
+
+/// The headers that sign a request with AWS Signature Version 4, apart
+/// from `host`, which the client sets from the URL.
+fn sign(
+    method: &Method,
+    url: &Url,
+    body: &[u8],
+    now: DateTime<Utc>,
+    region: &str,
+    credentials: &S3Credentials,
+) -> Vec<(&'static str, String)> {
+    let payload_hash = hex::encode(Sha256::digest(body));
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    // Canonical headers must be sorted by name
+    let mut headers = vec![
+        ("host", host),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        url.path(),
+        url.query().unwrap_or_default(),
+    );
+
+    let scope = format!("{date}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut key = hmac(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [region, "s3", "aws4_request"] {
+        key = hmac(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    headers.remove(0);
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    headers
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials() -> S3Credentials {
+        S3Credentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY")
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2026-10-14T19:34:25Z".parse().unwrap()
+    }
+
+    fn header<'a>(headers: &'a [(&str, String)], name: &str) -> &'a str {
+        &headers.iter().find(|(n, _)| *n == name).unwrap().1
+    }
+
+    #[test]
+    fn test_object_urls() {
+        let aws =
+            S3PayloadOffload::new("examplebucket", "us-east-1", credentials()).with_prefix("jobs/");
+        assert_eq!(
+            aws.object_url("4f1c-key").unwrap().as_str(),
+            "https://examplebucket.s3.us-east-1.amazonaws.com/jobs/4f1c-key"
+        );
+
+        let minio = S3PayloadOffload::new("payloads", "us-east-1", credentials())
+            .with_endpoint("http://localhost:9000/".parse().unwrap());
+        assert_eq!(
+            minio.object_url("4f1c-key").unwrap().as_str(),
+            "http://localhost:9000/payloads/4f1c-key"
+        );
+    }
+
+    // Expected signatures computed with botocore's SigV4Auth
+    #[test]
+    fn test_signs_put_like_botocore() {
+        let url: Url = "https://examplebucket.s3.us-east-1.amazonaws.com/jobs/4f1c-key"
+            .parse()
+            .unwrap();
+        let headers = sign(
+            &Method::PUT,
+            &url,
+            b"hello",
+            now(),
+            "us-east-1",
+            &credentials(),
+        );
+
+        assert_eq!(header(&headers, "x-amz-date"), "20261014T193425Z");
+        assert_eq!(
+            header(&headers, "authorization"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20261014/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=b3a567a9d0856efca31a39512fa52025e9abb3ff71736bf66bfa2ca8211cba86"
+        );
+    }
+
+    #[test]
+    fn test_signs_session_token_and_port() {
+        let url: Url = "http://localhost:9000/payloads/4f1c-key".parse().unwrap();
+        let headers = sign(
+            &Method::GET,
+            &url,
+            b"",
+            now(),
+            "us-east-1",
+            &credentials().with_session_token("session-token"),
+        );
+
+        assert_eq!(header(&headers, "x-amz-security-token"), "session-token");
+        assert_eq!(
+            header(&headers, "authorization"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20261014/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token, \
+             Signature=dc3a0a9eaff7df86631075de9e54a9ccae6429b98723c917428da697cc900eb3"
+        );
+    }
+}
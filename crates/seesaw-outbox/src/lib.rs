@@ -5,13 +5,22 @@
 //!
 //! # Overview
 //!
-//! 1. Effect writes business data AND outbox entry in single transaction
-//! 2. Background publisher polls outbox, emits to EventBus, marks as published
+//! 1. Effect writes business data AND outbox entry in single transaction,
+//!    returning an [`Outboxed`] receipt ([`OutboxWriter::write_result`])
+//!    instead of emitting its result event directly
+//! 2. [`OutboxPublisher`] polls outbox, emits to EventBus, marks as published
 //! 3. Cleanup job removes old published entries
 //!
+//! Emitting an effect's event after committing is not crash-safe: a crash
+//! between commit and emit loses the event for a write that happened.
+//! Through the outbox, every committed write gets its event.
+//!
 //! # Guarantees
 //!
-//! - **At-least-once delivery**: Events may be re-delivered after publisher crash
+//! - **At-least-once delivery**: Events may be re-delivered after publisher crash.
+//!   With [`OutboxPublisher::with_acknowledgment`] entries are only marked
+//!   published after the runtime processed them, so a crash between emit and
+//!   decide redelivers the event instead of losing it
 //! - **Same-transaction durability**: Event survives if business write survives
 //! - **Multi-instance safe**: Uses `FOR UPDATE SKIP LOCKED` for concurrent publishers
 //!
@@ -26,7 +35,7 @@
 //! # Example
 //!
 //! ```ignore
-//! use seesaw_core::{CorrelationId, outbox::{OutboxEvent, OutboxWriter}};
+//! use seesaw_outbox::{DurableEventRegistry, OutboxEvent, OutboxPublisher, OutboxWriter, Outboxed};
 //!
 //! // 1. Mark event for outbox persistence
 //! #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,8 +48,8 @@
 //!     fn event_type() -> &'static str { "notification.created.v1" }
 //! }
 //!
-//! // 2. In effect, write to outbox in same transaction
-//! async fn execute(&self, cmd: CreateNotificationCmd, ctx: EffectContext<Kernel>) -> Result<()> {
+//! // 2. In effect, write the result event to outbox in same transaction
+//! async fn execute(&self, cmd: CreateNotificationCmd, ctx: EffectContext<Kernel>) -> Result<Outboxed> {
 //!     let mut tx = ctx.deps().db.begin().await?;
 //!
 //!     // Business write
@@ -48,14 +57,18 @@
 //!
 //!     // Outbox write (same transaction)
 //!     let mut writer = PgOutboxWriter::new(&mut tx);
-//!     writer.write_event(
+//!     let outboxed = writer.write_result(
 //!         &NotificationCreated { id: notification.id, user_id: cmd.user_id },
 //!         ctx.outbox_correlation_id(),
 //!     ).await?;
 //!
 //!     tx.commit().await?;
-//!     Ok(())
+//!     Ok(outboxed)
 //! }
+//!
+//! // 3. Relay committed events to the bus
+//! let registry = DurableEventRegistry::new().register::<NotificationCreated>();
+//! tokio::spawn(OutboxPublisher::new(outbox, registry, handle.bus().clone()).run());
 //! ```
 
 use anyhow::Result;
@@ -64,12 +77,19 @@ use chrono::{DateTime, Utc};
 use serde::{de::DeserializeOwned, Serialize};
 use uuid::Uuid;
 
-use seesaw_core::EventBus;
 use seesaw_core::Event;
+use seesaw_core::EventBus;
+
+mod publisher;
+mod registry;
 
 // Re-export CorrelationId from core for backwards compatibility
 pub use seesaw_core::CorrelationId;
 
+// Re-export publisher types
+pub use publisher::OutboxPublisher;
+pub use registry::DurableEventRegistry;
+
 // =============================================================================
 // OutboxEvent Trait
 // =============================================================================
@@ -196,6 +216,56 @@ pub trait OutboxWriter: Send + Sync {
         event: &E,
         correlation_id: CorrelationId,
     ) -> Result<Uuid>;
+
+    /// Write an effect's result event to the outbox.
+    ///
+    /// Return the [`Outboxed`] receipt from the effect instead of the event:
+    /// the event reaches the bus through the [`OutboxPublisher`] once the
+    /// transaction commits, and never if it rolls back.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// async fn execute(&self, cmd: PlaceOrder, ctx: EffectContext<Deps>) -> Result<Outboxed> {
+    ///     let mut tx = ctx.deps().db.begin().await?;
+    ///     let order = Order::create(&cmd, &mut tx).await?;
+    ///
+    ///     let outboxed = PgOutboxWriter::new(&mut tx)
+    ///         .write_result(&OrderPlaced { order_id: order.id }, ctx.outbox_correlation_id())
+    ///         .await?;
+    ///
+    ///     tx.commit().await?;
+    ///     Ok(outboxed)
+    /// }
+    /// ```
+    async fn write_result<E: OutboxEvent + Send + Sync>(
+        &mut self,
+        event: &E,
+        correlation_id: CorrelationId,
+    ) -> Result<Outboxed> {
+        let entry_id = self.write_event(event, correlation_id).await?;
+        Ok(Outboxed {
+            entry_id,
+            event_type: E::event_type(),
+            correlation_id,
+        })
+    }
+}
+
+/// Receipt returned by an effect whose result event went to the outbox.
+///
+/// Emitted like any effect result, but carries no domain meaning: machines
+/// react to the outboxed event itself when the publisher delivers it. An
+/// effect returning its event directly *and* writing it to the outbox would
+/// deliver it twice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outboxed {
+    /// ID of the outbox entry holding the event.
+    pub entry_id: Uuid,
+    /// `event_type()` of the outboxed event.
+    pub event_type: &'static str,
+    /// Correlation ID the event will be emitted with.
+    pub correlation_id: CorrelationId,
 }
 
 // =============================================================================
@@ -281,37 +351,8 @@ pub trait OutboxReader: Send + Sync {
 /// Maps event type strings to deserialize+emit functions. Used by the
 /// publisher to convert outbox entries back into typed events.
 ///
-/// # Example Implementation
-///
-/// ```ignore
-/// pub struct DurableEventRegistry {
-///     handlers: HashMap<&'static str, Box<dyn Fn(&OutboxEntry, &EventBus) -> Result<()> + Send + Sync>>,
-/// }
-///
-/// impl DurableEventRegistry {
-///     pub fn new() -> Self {
-///         Self { handlers: HashMap::new() }
-///     }
-///
-///     pub fn register<E: OutboxEvent + 'static>(mut self) -> Self {
-///         self.handlers.insert(E::event_type(), Box::new(|entry, bus| {
-///             let event: E = serde_json::from_value(entry.payload.clone())?;
-///             let cid = entry.correlation_id.into_inner();
-///             bus.emit_with_correlation(event, cid);
-///             Ok(())
-///         }));
-///         self
-///     }
-/// }
-///
-/// impl OutboxEventRegistry for DurableEventRegistry {
-///     fn emit_entry(&self, entry: &OutboxEntry, bus: &EventBus) -> Result<()> {
-///         let handler = self.handlers.get(entry.event_type.as_str())
-///             .ok_or_else(|| anyhow!("Unknown event type: {}", entry.event_type))?;
-///         handler(entry, bus)
-///     }
-/// }
-/// ```
+/// [`DurableEventRegistry`] is the standard implementation, built by
+/// registering [`OutboxEvent`] types.
 pub trait OutboxEventRegistry: Send + Sync {
     /// Deserialize an outbox entry and emit it to the event bus.
     ///
@@ -336,6 +377,9 @@ pub struct OutboxPublisherConfig {
     pub retention: std::time::Duration,
     /// How often to run cleanup.
     pub cleanup_interval: std::time::Duration,
+    /// How long to wait for the runtime to process an entry before leaving
+    /// it for redelivery (at-least-once mode only).
+    pub ack_timeout: std::time::Duration,
 }
 
 impl Default for OutboxPublisherConfig {
@@ -345,6 +389,7 @@ impl Default for OutboxPublisherConfig {
             batch_size: 100,
             retention: std::time::Duration::from_secs(7 * 24 * 60 * 60), // 7 days
             cleanup_interval: std::time::Duration::from_secs(60 * 60),   // 1 hour
+            ack_timeout: std::time::Duration::from_secs(30),
         }
     }
 }
@@ -424,5 +469,6 @@ mod tests {
             config.cleanup_interval,
             std::time::Duration::from_secs(60 * 60)
         );
+        assert_eq!(config.ack_timeout, std::time::Duration::from_secs(30));
     }
 }
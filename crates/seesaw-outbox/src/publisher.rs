@@ -0,0 +1,503 @@
+//! Outbox publisher - moves outbox entries onto the EventBus.
+//!
+//! # Delivery Modes
+//!
+//! - **At-most-once** (default): entries are marked published as soon as
+//!   they are emitted. A crash after the emit but before the runtime decides
+//!   on the event loses it.
+//! - **At-least-once** ([`OutboxPublisher::with_acknowledgment`]): each entry
+//!   is tracked in the engine's [`InflightTracker`] and only marked published
+//!   once the runtime has decided on it and its inline effects have finished.
+//!   Entries not acknowledged within `ack_timeout` stay unpublished and are
+//!   redelivered once their claim expires, so machines must tolerate
+//!   duplicates.
+//!
+//! An entry whose effects fail is still acknowledged: the failure was
+//! observed (as `CommandFailed`), redelivering would only repeat it.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::Utc;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use seesaw_core::{CorrelationId, EventBus, InflightTracker};
+
+use crate::{OutboxEntry, OutboxEventRegistry, OutboxPublisherConfig, OutboxReader};
+
+/// Polls an [`OutboxReader`] and emits entries through an [`OutboxEventRegistry`].
+///
+/// # Example
+///
+/// ```ignore
+/// let engine = EngineBuilder::new(deps)
+///     .with_machine(OrderMachine::new())
+///     .with_effect::<ShipCommand, _>(ShipEffect)
+///     .build();
+/// let handle = engine.start();
+///
+/// let publisher = OutboxPublisher::new(job_store.outbox(), registry, handle.bus().clone())
+///     .with_acknowledgment(handle.inflight().clone());
+/// tokio::spawn(publisher.run());
+/// ```
+pub struct OutboxPublisher<R, G> {
+    reader: R,
+    registry: G,
+    bus: EventBus,
+    config: OutboxPublisherConfig,
+    inflight: Option<Arc<InflightTracker>>,
+}
+
+impl<R: OutboxReader, G: OutboxEventRegistry> OutboxPublisher<R, G> {
+    /// Create an at-most-once publisher with default configuration.
+    pub fn new(reader: R, registry: G, bus: EventBus) -> Self {
+        Self {
+            reader,
+            registry,
+            bus,
+            config: OutboxPublisherConfig::default(),
+            inflight: None,
+        }
+    }
+
+    /// Set the publisher configuration.
+    pub fn with_config(mut self, config: OutboxPublisherConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Acknowledge entries only after the runtime has processed them.
+    ///
+    /// `inflight` must be the tracker of the engine consuming the bus
+    /// (`EngineHandle::inflight`).
+    pub fn with_acknowledgment(mut self, inflight: Arc<InflightTracker>) -> Self {
+        self.inflight = Some(inflight);
+        self
+    }
+
+    /// Claim one batch, emit it, and mark the delivered entries published.
+    ///
+    /// Returns the number of entries marked published.
+    pub async fn publish_once(&self) -> Result<usize> {
+        let entries = self
+            .reader
+            .claim_unpublished(self.config.batch_size)
+            .await?;
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        let delivered = match &self.inflight {
+            None => entries
+                .iter()
+                .filter(|entry| self.emit(entry))
+                .map(|entry| entry.id)
+                .collect(),
+            Some(inflight) => self.emit_and_await(inflight, entries).await,
+        };
+
+        if !delivered.is_empty() {
+            self.reader.mark_published(&delivered).await?;
+        }
+        Ok(delivered.len())
+    }
+
+    /// Run the publisher until the task is aborted.
+    ///
+    /// Polls every `poll_interval` (immediately again while full batches
+    /// are being published) and deletes published entries older than
+    /// `retention` every `cleanup_interval`.
+    pub async fn run(self) {
+        let mut last_cleanup = Instant::now();
+        loop {
+            let published = match self.publish_once().await {
+                Ok(n) => n,
+                Err(e) => {
+                    error!(error = %e, "outbox publish failed");
+                    0
+                }
+            };
+
+            if last_cleanup.elapsed() >= self.config.cleanup_interval {
+                last_cleanup = Instant::now();
+                self.cleanup().await;
+            }
+
+            if published < self.config.batch_size {
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+        }
+    }
+
+    /// Emit one entry, logging (and skipping) entries that cannot be decoded.
+    fn emit(&self, entry: &OutboxEntry) -> bool {
+        match self.registry.emit_entry(entry, &self.bus) {
+            Ok(()) => true,
+            Err(e) => {
+                error!(id = %entry.id, event_type = %entry.event_type, error = %e, "failed to emit outbox entry");
+                false
+            }
+        }
+    }
+
+    /// Emit a batch with inflight tracking and wait for the runtime to finish it.
+    async fn emit_and_await(
+        &self,
+        inflight: &Arc<InflightTracker>,
+        entries: Vec<OutboxEntry>,
+    ) -> Vec<Uuid> {
+        let mut pending = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            // Uncorrelated entries get a fresh cid so they can be tracked
+            if entry.correlation_id.is_none() {
+                entry.correlation_id = CorrelationId::new();
+            }
+            let cid = entry.correlation_id;
+
+            // Register and count BEFORE emitting, as emit_and_await does
+            let guard = inflight.register_waiter(cid);
+            inflight.inc(cid, 1);
+            if !self.emit(&entry) {
+                inflight.dec(cid, 1);
+                continue;
+            }
+            pending.push((entry.id, cid, guard));
+        }
+
+        let ack_timeout = self.config.ack_timeout;
+        let waits = pending.into_iter().map(|(id, cid, _guard)| async move {
+            match tokio::time::timeout(ack_timeout, inflight.wait_zero(cid)).await {
+                Ok(Ok(())) => Some(id),
+                Ok(Err(e)) => {
+                    warn!(%id, %cid, error = %e, "outbox event processed with errors");
+                    Some(id)
+                }
+                Err(_) => {
+                    inflight.abandon(cid);
+                    warn!(%id, %cid, "outbox event not acknowledged in time, will redeliver");
+                    None
+                }
+            }
+        });
+
+        futures::future::join_all(waits)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    async fn cleanup(&self) {
+        let Some(older_than) = chrono::Duration::from_std(self.config.retention)
+            .ok()
+            .and_then(|retention| Utc::now().checked_sub_signed(retention))
+        else {
+            return;
+        };
+        if let Err(e) = self.reader.cleanup_published(older_than).await {
+            error!(error = %e, "outbox cleanup failed");
+        }
+    }
+}
+
+impl<R, G> std::fmt::Debug for OutboxPublisher<R, G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutboxPublisher")
+            .field("config", &self.config)
+            .field("acknowledged", &self.inflight.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DurableEventRegistry, OutboxEvent, OutboxWriter, Outboxed};
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use seesaw_core::{Command, Effect, EffectContext, EngineBuilder, Machine};
+    use serde::{Deserialize, Serialize};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct OrderPlaced {
+        order_id: u32,
+    }
+
+    impl OutboxEvent for OrderPlaced {
+        fn event_type() -> &'static str {
+            "order.placed.v1"
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct OrderShipped;
+
+    #[derive(Debug, Clone)]
+    struct ShipOrder(u32);
+    impl Command for ShipOrder {}
+
+    struct OrderMachine;
+
+    impl Machine for OrderMachine {
+        type Event = OrderPlaced;
+        type Command = ShipOrder;
+
+        fn decide(&mut self, event: &OrderPlaced) -> Option<ShipOrder> {
+            Some(ShipOrder(event.order_id))
+        }
+    }
+
+    /// Records the IDs of the orders it ships.
+    struct ShipEffect {
+        shipped: Arc<Mutex<Vec<u32>>>,
+    }
+
+    #[async_trait]
+    impl Effect<ShipOrder, ()> for ShipEffect {
+        type Event = OrderShipped;
+
+        async fn execute(&self, cmd: ShipOrder, _ctx: EffectContext<()>) -> Result<OrderShipped> {
+            self.shipped.lock().unwrap().push(cmd.0);
+            Ok(OrderShipped)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct CheckoutRequested;
+
+    #[derive(Debug, Clone)]
+    struct PlaceOrder;
+    impl Command for PlaceOrder {}
+
+    struct CheckoutMachine;
+
+    impl Machine for CheckoutMachine {
+        type Event = CheckoutRequested;
+        type Command = PlaceOrder;
+
+        fn decide(&mut self, _event: &CheckoutRequested) -> Option<PlaceOrder> {
+            Some(PlaceOrder)
+        }
+    }
+
+    /// Writes its result to the outbox instead of emitting it.
+    struct PlaceEffect {
+        outbox: Arc<MemoryOutbox>,
+    }
+
+    #[async_trait]
+    impl Effect<PlaceOrder, ()> for PlaceEffect {
+        type Event = Outboxed;
+
+        async fn execute(&self, _cmd: PlaceOrder, ctx: EffectContext<()>) -> Result<Outboxed> {
+            let mut writer = self.outbox.clone();
+            writer
+                .write_result(&OrderPlaced { order_id: 7 }, ctx.outbox_correlation_id())
+                .await
+        }
+    }
+
+    /// In-memory outbox without claim leases.
+    #[derive(Default)]
+    struct MemoryOutbox {
+        entries: Mutex<Vec<OutboxEntry>>,
+    }
+
+    impl MemoryOutbox {
+        fn with_entry(event_type: &str, payload: serde_json::Value) -> Self {
+            let outbox = Self::default();
+            outbox.entries.lock().unwrap().push(OutboxEntry {
+                id: Uuid::new_v4(),
+                event_type: event_type.to_string(),
+                payload,
+                correlation_id: CorrelationId::NONE,
+                created_at: Utc::now(),
+                published_at: None,
+            });
+            outbox
+        }
+
+        fn unpublished(&self) -> usize {
+            let entries = self.entries.lock().unwrap();
+            entries.iter().filter(|e| e.published_at.is_none()).count()
+        }
+    }
+
+    #[async_trait]
+    impl OutboxReader for Arc<MemoryOutbox> {
+        async fn claim_unpublished(&self, limit: usize) -> Result<Vec<OutboxEntry>> {
+            let entries = self.entries.lock().unwrap();
+            Ok(entries
+                .iter()
+                .filter(|e| e.published_at.is_none())
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn mark_published(&self, ids: &[Uuid]) -> Result<()> {
+            for entry in self.entries.lock().unwrap().iter_mut() {
+                if ids.contains(&entry.id) {
+                    entry.published_at = Some(Utc::now());
+                }
+            }
+            Ok(())
+        }
+
+        async fn cleanup_published(&self, _older_than: DateTime<Utc>) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
+    #[async_trait]
+    impl OutboxWriter for Arc<MemoryOutbox> {
+        async fn write_event<E: OutboxEvent + Send + Sync>(
+            &mut self,
+            event: &E,
+            correlation_id: CorrelationId,
+        ) -> Result<Uuid> {
+            let id = Uuid::new_v4();
+            self.entries.lock().unwrap().push(OutboxEntry {
+                id,
+                event_type: E::event_type().to_string(),
+                payload: serde_json::to_value(event)?,
+                correlation_id,
+                created_at: Utc::now(),
+                published_at: None,
+            });
+            Ok(id)
+        }
+    }
+
+    fn order_outbox() -> Arc<MemoryOutbox> {
+        Arc::new(MemoryOutbox::with_entry(
+            "order.placed.v1",
+            serde_json::json!({ "order_id": 7 }),
+        ))
+    }
+
+    fn fast_ack() -> OutboxPublisherConfig {
+        OutboxPublisherConfig {
+            ack_timeout: Duration::from_millis(50),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_at_least_once_acks_after_runtime_processed() {
+        let shipped = Arc::new(Mutex::new(Vec::new()));
+        let handle = EngineBuilder::new(())
+            .with_machine(OrderMachine)
+            .with_effect::<ShipOrder, _>(ShipEffect {
+                shipped: shipped.clone(),
+            })
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let outbox = order_outbox();
+        let publisher = OutboxPublisher::new(
+            outbox.clone(),
+            DurableEventRegistry::new().register::<OrderPlaced>(),
+            handle.bus().clone(),
+        )
+        .with_acknowledgment(handle.inflight().clone());
+
+        assert_eq!(publisher.publish_once().await.unwrap(), 1);
+        assert_eq!(*shipped.lock().unwrap(), [7]);
+        assert_eq!(outbox.unpublished(), 0);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_at_least_once_leaves_unprocessed_entries_for_redelivery() {
+        // No runtime is consuming the bus
+        let inflight = Arc::new(InflightTracker::new());
+        let outbox = order_outbox();
+        let publisher = OutboxPublisher::new(
+            outbox.clone(),
+            DurableEventRegistry::new().register::<OrderPlaced>(),
+            EventBus::new(),
+        )
+        .with_config(fast_ack())
+        .with_acknowledgment(inflight.clone());
+
+        assert_eq!(publisher.publish_once().await.unwrap(), 0);
+        assert_eq!(outbox.unpublished(), 1);
+        assert_eq!(inflight.active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_at_most_once_marks_published_on_emit() {
+        let outbox = order_outbox();
+        let publisher = OutboxPublisher::new(
+            outbox.clone(),
+            DurableEventRegistry::new().register::<OrderPlaced>(),
+            EventBus::new(),
+        );
+
+        assert_eq!(publisher.publish_once().await.unwrap(), 1);
+        assert_eq!(outbox.unpublished(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_event_type_stays_unpublished() {
+        let outbox = Arc::new(MemoryOutbox::with_entry(
+            "order.cancelled.v1",
+            serde_json::json!({}),
+        ));
+        let publisher = OutboxPublisher::new(
+            outbox.clone(),
+            DurableEventRegistry::new().register::<OrderPlaced>(),
+            EventBus::new(),
+        )
+        .with_config(fast_ack())
+        .with_acknowledgment(Arc::new(InflightTracker::new()));
+
+        assert_eq!(publisher.publish_once().await.unwrap(), 0);
+        assert_eq!(outbox.unpublished(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_outboxed_result_is_delivered_once_by_publisher() {
+        let shipped = Arc::new(Mutex::new(Vec::new()));
+        let outbox = Arc::new(MemoryOutbox::default());
+        let handle = EngineBuilder::new(())
+            .with_machine(CheckoutMachine)
+            .with_machine(OrderMachine)
+            .with_effect::<PlaceOrder, _>(PlaceEffect {
+                outbox: outbox.clone(),
+            })
+            .with_effect::<ShipOrder, _>(ShipEffect {
+                shipped: shipped.clone(),
+            })
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        handle.bus().emit(CheckoutRequested);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Written to the outbox, not yet on the bus
+        assert_eq!(outbox.unpublished(), 1);
+        assert!(shipped.lock().unwrap().is_empty());
+
+        let publisher = OutboxPublisher::new(
+            outbox.clone(),
+            DurableEventRegistry::new().register::<OrderPlaced>(),
+            handle.bus().clone(),
+        )
+        .with_acknowledgment(handle.inflight().clone());
+
+        assert_eq!(publisher.publish_once().await.unwrap(), 1);
+        assert_eq!(publisher.publish_once().await.unwrap(), 0);
+        assert_eq!(*shipped.lock().unwrap(), [7]);
+
+        handle.abort();
+    }
+}
@@ -0,0 +1,72 @@
+//! Typed registry mapping outbox event types back to events.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use seesaw_core::EventBus;
+
+use crate::{OutboxEntry, OutboxEvent, OutboxEventRegistry};
+
+type EmitFn = Box<dyn Fn(&OutboxEntry, &EventBus) -> Result<()> + Send + Sync>;
+
+/// [`OutboxEventRegistry`] built from registered [`OutboxEvent`] types.
+///
+/// Register every event type (and every version of it) the outbox may
+/// contain. Entries are deserialized and emitted with their stored
+/// correlation ID.
+///
+/// # Example
+///
+/// ```ignore
+/// let registry = DurableEventRegistry::new()
+///     .register::<NotificationCreated>()
+///     .register::<NotificationCreatedV2>();
+/// ```
+#[derive(Default)]
+pub struct DurableEventRegistry {
+    handlers: HashMap<&'static str, EmitFn>,
+}
+
+impl DurableEventRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an event type under its `event_type()` identifier.
+    pub fn register<E: OutboxEvent>(mut self) -> Self {
+        self.handlers.insert(
+            E::event_type(),
+            Box::new(|entry, bus| {
+                let event: E = serde_json::from_value(entry.payload.clone())?;
+                bus.emit_with_correlation(event, entry.correlation_id);
+                Ok(())
+            }),
+        );
+        self
+    }
+
+    /// Check if an event type identifier is registered.
+    pub fn contains(&self, event_type: &str) -> bool {
+        self.handlers.contains_key(event_type)
+    }
+}
+
+impl OutboxEventRegistry for DurableEventRegistry {
+    fn emit_entry(&self, entry: &OutboxEntry, bus: &EventBus) -> Result<()> {
+        let handler = self
+            .handlers
+            .get(entry.event_type.as_str())
+            .ok_or_else(|| anyhow!("unknown outbox event type: {}", entry.event_type))?;
+        handler(entry, bus)
+    }
+}
+
+impl std::fmt::Debug for DurableEventRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DurableEventRegistry")
+            .field("event_types", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
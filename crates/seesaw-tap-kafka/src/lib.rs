@@ -0,0 +1,61 @@
+//! Kafka event tap for analytics pipelines.
+//!
+//! [`KafkaTap`] is an [`EventTap`](seesaw_core::EventTap) that serializes
+//! chosen event types as JSON and publishes them to Kafka topics, so
+//! product analytics can consume the event stream without touching the
+//! services producing it.
+//!
+//! # Overview
+//!
+//! 1. Choose a topic per event type, optionally with a partition key
+//! 2. Register a clone of the tap for each of those types
+//! 3. Tapped events are batched, sent through a [`KafkaProducer`] (rdkafka's
+//!    `FutureProducer` out of the box) and retried under a `RetryPolicy`
+//!
+//! # Guarantees
+//!
+//! - **Best-effort**: Like every tap, delivery never affects the main flow.
+//!   Records are lost if the process exits while batched, or when retries
+//!   run out; both are visible in [`KafkaTap::stats`]
+//! - **Ordered per key**: Records with the same key go to one partition.
+//!   Retries can reorder records within a batch
+//! - **Correlated**: The correlation ID travels in the
+//!   [`CORRELATION_ID_HEADER`] header
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rdkafka::config::ClientConfig;
+//! use rdkafka::producer::FutureProducer;
+//! use seesaw_tap_kafka::KafkaTap;
+//!
+//! let producer: FutureProducer = ClientConfig::new()
+//!     .set("bootstrap.servers", "localhost:9092")
+//!     .create()?;
+//!
+//! let kafka = KafkaTap::new(producer)
+//!     .with_keyed_topic::<OrderPlaced, _>("analytics.orders", |e| e.order_id.to_string())
+//!     .with_topic::<UserSignedUp>("analytics.users");
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_event_tap::<OrderPlaced, _>(kafka.clone())
+//!     .with_event_tap::<UserSignedUp, _>(kafka.clone())
+//!     .build();
+//! ```
+//!
+//! # Feature Flags
+//!
+//! - `metrics`: `seesaw_kafka_*` counters through the `metrics` facade
+
+mod metrics;
+mod producer;
+mod tap;
+
+// Re-export producer types
+pub use producer::{KafkaProducer, KafkaRecord, CORRELATION_ID_HEADER};
+
+// Re-export tap types
+pub use tap::{KafkaTap, KafkaTapStats};
+
+// Re-export the client, so applications configure the same rdkafka version
+pub use rdkafka;
@@ -0,0 +1,38 @@
+//! Delivery metrics through the [`metrics`](https://docs.rs/metrics) facade
+//! (`metrics` feature).
+//!
+//! | Metric                                 | Kind    | Labels  |
+//! |----------------------------------------|---------|---------|
+//! | `seesaw_kafka_records_published_total` | counter | `topic` |
+//! | `seesaw_kafka_send_retries_total`      | counter | `topic` |
+//! | `seesaw_kafka_records_failed_total`    | counter | `topic` |
+//!
+//! The same counts are always available from
+//! [`KafkaTap::stats`](crate::KafkaTap::stats).
+
+#[cfg(feature = "metrics")]
+use ::metrics::counter;
+
+/// The broker acknowledged a record.
+pub(crate) fn published(topic: &str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_kafka_records_published_total", "topic" => topic.to_string()).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = topic;
+}
+
+/// A failed send is about to be retried.
+pub(crate) fn retried(topic: &str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_kafka_send_retries_total", "topic" => topic.to_string()).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = topic;
+}
+
+/// A record was dropped after its last attempt failed.
+pub(crate) fn failed(topic: &str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_kafka_records_failed_total", "topic" => topic.to_string()).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = topic;
+}
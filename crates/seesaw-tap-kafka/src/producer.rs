@@ -0,0 +1,64 @@
+//! Kafka producers - where a [`KafkaTap`](crate::KafkaTap) sends its records.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+
+use seesaw_core::CorrelationId;
+
+/// Kafka header carrying the event's correlation ID.
+///
+/// Omitted for uncorrelated events.
+pub const CORRELATION_ID_HEADER: &str = "seesaw-correlation-id";
+
+/// One serialized event, addressed to a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaRecord {
+    /// Destination topic.
+    pub topic: String,
+    /// Partition key; `None` lets the producer spread records over partitions.
+    pub key: Option<String>,
+    /// The event, serialized as JSON.
+    pub payload: Bytes,
+    /// Correlation ID of the event (NONE if uncorrelated).
+    pub correlation_id: CorrelationId,
+}
+
+/// Delivers records to Kafka.
+///
+/// Implemented for rdkafka's [`FutureProducer`]; implement it directly to
+/// route records elsewhere (or to record them in tests).
+#[async_trait]
+pub trait KafkaProducer: Send + Sync + 'static {
+    /// Deliver one record, returning once the broker acknowledged it.
+    async fn send(&self, record: &KafkaRecord) -> Result<()>;
+}
+
+#[async_trait]
+impl KafkaProducer for FutureProducer {
+    async fn send(&self, record: &KafkaRecord) -> Result<()> {
+        let mut future_record =
+            FutureRecord::<str, [u8]>::to(&record.topic).payload(&record.payload);
+        if let Some(key) = &record.key {
+            future_record = future_record.key(key.as_str());
+        }
+
+        let cid = record.correlation_id.to_string();
+        if record.correlation_id.is_some() {
+            future_record = future_record.headers(OwnedHeaders::new().insert(Header {
+                key: CORRELATION_ID_HEADER,
+                value: Some(&cid),
+            }));
+        }
+
+        // Wait for queue space instead of failing; librdkafka's
+        // `message.timeout.ms` bounds the delivery itself
+        FutureProducer::send(self, future_record, Timeout::Never)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| e.into())
+    }
+}
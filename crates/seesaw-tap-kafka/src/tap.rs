@@ -0,0 +1,446 @@
+//! The Kafka tap - batches tapped events and publishes them.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+use seesaw_core::{Event, EventTap, RetryPolicy, TapContext};
+
+use crate::{KafkaProducer, KafkaRecord};
+
+/// Derives a partition key from an event.
+type KeyFn = Arc<dyn Fn(&dyn Any) -> Option<String> + Send + Sync>;
+
+/// Where events of one type are published.
+#[derive(Clone)]
+struct Route {
+    topic: String,
+    key: Option<KeyFn>,
+}
+
+// =============================================================================
+// Stats
+// =============================================================================
+
+/// Delivery counters of a [`KafkaTap`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KafkaTapStats {
+    /// Records acknowledged by the broker.
+    pub published: u64,
+    /// Failed sends that were retried.
+    pub retried: u64,
+    /// Records dropped after the retry policy gave up.
+    pub failed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    published: AtomicU64,
+    retried: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// State shared by every clone of a tap.
+#[derive(Default)]
+struct Shared {
+    counters: Counters,
+    /// Feeds the flush task, spawned by the first tapped event.
+    sender: OnceLock<mpsc::Sender<KafkaRecord>>,
+}
+
+// =============================================================================
+// Kafka Tap
+// =============================================================================
+
+/// [`EventTap`] publishing chosen event types to Kafka topics.
+///
+/// Events are serialized as JSON and collected into batches of up to
+/// `batch_size` records, or whatever arrived within `linger` of the first
+/// one. Each record of a batch is sent concurrently and retried on its own
+/// under the [`RetryPolicy`]; records still failing after the last attempt
+/// are counted and dropped.
+///
+/// Clones share one batch and one set of [`stats`](Self::stats): configure
+/// the tap, then register a clone for every event type it has a topic for.
+///
+/// # Backpressure
+///
+/// While Kafka is slow or down, up to `2 * batch_size` records wait for the
+/// flush task; beyond that the tap stops taking events and the engine's
+/// [`TapPolicy`](seesaw_core::TapPolicy) decides what to drop.
+///
+/// # Example
+///
+/// ```ignore
+/// let producer: FutureProducer = ClientConfig::new()
+///     .set("bootstrap.servers", "localhost:9092")
+///     .create()?;
+///
+/// let kafka = KafkaTap::new(producer)
+///     .with_keyed_topic::<OrderPlaced, _>("analytics.orders", |e| e.order_id.to_string())
+///     .with_topic::<UserSignedUp>("analytics.users")
+///     .with_batching(500, Duration::from_millis(50));
+///
+/// let engine = EngineBuilder::new(deps)
+///     .with_event_tap::<OrderPlaced, _>(kafka.clone())
+///     .with_event_tap::<UserSignedUp, _>(kafka.clone())
+///     .build();
+/// ```
+pub struct KafkaTap<P> {
+    producer: Arc<P>,
+    routes: HashMap<TypeId, Route>,
+    batch_size: usize,
+    linger: Duration,
+    retry: RetryPolicy,
+    shared: Arc<Shared>,
+}
+
+impl<P: KafkaProducer> KafkaTap<P> {
+    /// Create a tap without topics, sending batches of up to 100 records
+    /// lingering at most 100ms, with the default [`RetryPolicy`].
+    pub fn new(producer: P) -> Self {
+        Self {
+            producer: Arc::new(producer),
+            routes: HashMap::new(),
+            batch_size: 100,
+            linger: Duration::from_millis(100),
+            retry: RetryPolicy::new(),
+            shared: Arc::default(),
+        }
+    }
+
+    /// Publish `E` events to `topic`, without a partition key.
+    pub fn with_topic<E: Event>(mut self, topic: impl Into<String>) -> Self {
+        self.routes.insert(
+            TypeId::of::<E>(),
+            Route {
+                topic: topic.into(),
+                key: None,
+            },
+        );
+        self
+    }
+
+    /// Publish `E` events to `topic`, partitioned by `key`.
+    ///
+    /// Events with the same key land on the same partition, so consumers
+    /// see them in order.
+    pub fn with_keyed_topic<E, F>(mut self, topic: impl Into<String>, key: F) -> Self
+    where
+        E: Event,
+        F: Fn(&E) -> String + Send + Sync + 'static,
+    {
+        let key: KeyFn = Arc::new(move |event| event.downcast_ref::<E>().map(&key));
+        self.routes.insert(
+            TypeId::of::<E>(),
+            Route {
+                topic: topic.into(),
+                key: Some(key),
+            },
+        );
+        self
+    }
+
+    /// Send batches of up to `batch_size` records, waiting at most `linger`
+    /// for a batch to fill.
+    ///
+    /// A `batch_size` of 0 is treated as 1.
+    pub fn with_batching(mut self, batch_size: usize, linger: Duration) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.linger = linger;
+        self
+    }
+
+    /// Set how failed sends are retried.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Delivery counters, shared by all clones of this tap.
+    pub fn stats(&self) -> KafkaTapStats {
+        let counters = &self.shared.counters;
+        KafkaTapStats {
+            published: counters.published.load(Ordering::Relaxed),
+            retried: counters.retried.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The flush task's queue, spawning the task on first use.
+    fn sender(&self) -> &mpsc::Sender<KafkaRecord> {
+        self.shared.sender.get_or_init(|| {
+            let (tx, rx) = mpsc::channel(self.batch_size.saturating_mul(2));
+            tokio::spawn(flush_loop(
+                self.producer.clone(),
+                rx,
+                self.batch_size,
+                self.linger,
+                self.retry.clone(),
+                self.shared.clone(),
+            ));
+            tx
+        })
+    }
+}
+
+impl<P> Clone for KafkaTap<P> {
+    fn clone(&self) -> Self {
+        Self {
+            producer: self.producer.clone(),
+            routes: self.routes.clone(),
+            batch_size: self.batch_size,
+            linger: self.linger,
+            retry: self.retry.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P> EventTap<E> for KafkaTap<P>
+where
+    E: Event + Serialize,
+    P: KafkaProducer,
+{
+    async fn on_event(&self, event: &E, ctx: &TapContext) -> Result<()> {
+        let route = self
+            .routes
+            .get(&TypeId::of::<E>())
+            .ok_or_else(|| anyhow!("KafkaTap has no topic for {}", std::any::type_name::<E>()))?;
+
+        let record = KafkaRecord {
+            topic: route.topic.clone(),
+            key: route.key.as_ref().and_then(|key| key(event)),
+            payload: serde_json::to_vec(event)?.into(),
+            correlation_id: ctx.correlation_id,
+        };
+        self.sender()
+            .send(record)
+            .await
+            .map_err(|_| anyhow!("KafkaTap flush task stopped"))
+    }
+}
+
+impl<P> std::fmt::Debug for KafkaTap<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaTap")
+            .field(
+                "topics",
+                &self
+                    .routes
+                    .values()
+                    .map(|route| route.topic.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("batch_size", &self.batch_size)
+            .field("linger", &self.linger)
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Flushing
+// =============================================================================
+
+/// Collect records into batches and publish them until every tap is dropped.
+async fn flush_loop<P: KafkaProducer>(
+    producer: Arc<P>,
+    mut rx: mpsc::Receiver<KafkaRecord>,
+    batch_size: usize,
+    linger: Duration,
+    retry: RetryPolicy,
+    shared: Arc<Shared>,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(first) = rx.recv().await {
+        batch.push(first);
+
+        let deadline = tokio::time::sleep(linger);
+        tokio::pin!(deadline);
+        while batch.len() < batch_size {
+            tokio::select! {
+                record = rx.recv() => match record {
+                    Some(record) => batch.push(record),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        let sends = batch
+            .drain(..)
+            .map(|record| publish(producer.as_ref(), &retry, &shared.counters, record));
+        futures::future::join_all(sends).await;
+    }
+}
+
+/// Send one record, retrying while the policy allows.
+async fn publish<P: KafkaProducer>(
+    producer: &P,
+    retry: &RetryPolicy,
+    counters: &Counters,
+    record: KafkaRecord,
+) {
+    let mut attempt = 1;
+    loop {
+        match producer.send(&record).await {
+            Ok(()) => {
+                counters.published.fetch_add(1, Ordering::Relaxed);
+                crate::metrics::published(&record.topic);
+                return;
+            }
+            Err(e) if attempt < retry.max_attempts() && retry.should_retry(&e) => {
+                warn!(topic = %record.topic, attempt, error = %e, "kafka send failed, retrying");
+                counters.retried.fetch_add(1, Ordering::Relaxed);
+                crate::metrics::retried(&record.topic);
+                tokio::time::sleep(retry.backoff(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                error!(topic = %record.topic, cid = %record.correlation_id, error = %e, "kafka send failed, dropping record");
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+                crate::metrics::failed(&record.topic);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seesaw_core::CorrelationId;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, Serialize)]
+    struct OrderPlaced {
+        order_id: u32,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    struct UserSignedUp {
+        user_id: u32,
+    }
+
+    /// Records sends, failing the first `failures` of them.
+    #[derive(Default)]
+    struct MemoryProducer {
+        sent: Mutex<Vec<KafkaRecord>>,
+        failures: AtomicU64,
+    }
+
+    #[async_trait]
+    impl KafkaProducer for Arc<MemoryProducer> {
+        async fn send(&self, record: &KafkaRecord) -> Result<()> {
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                anyhow::bail!("broker unavailable");
+            }
+            self.sent.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+    }
+
+    fn fast_retry(attempts: u32) -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_attempts(attempts)
+            .with_initial_backoff(Duration::from_millis(1))
+    }
+
+    async fn tap<E: Event + Serialize>(
+        kafka: &KafkaTap<Arc<MemoryProducer>>,
+        event: E,
+    ) -> Result<()> {
+        kafka
+            .on_event(&event, &TapContext::new(CorrelationId::NONE))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_publishes_to_topic_per_event_type() {
+        let producer = Arc::new(MemoryProducer::default());
+        let kafka = KafkaTap::new(producer.clone())
+            .with_keyed_topic::<OrderPlaced, _>("analytics.orders", |e| e.order_id.to_string())
+            .with_topic::<UserSignedUp>("analytics.users")
+            .with_batching(2, Duration::from_secs(10));
+
+        tap(&kafka, OrderPlaced { order_id: 7 }).await.unwrap();
+        tap(&kafka.clone(), UserSignedUp { user_id: 3 })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let sent = producer.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].topic, "analytics.orders");
+        assert_eq!(sent[0].key.as_deref(), Some("7"));
+        assert_eq!(&sent[0].payload[..], br#"{"order_id":7}"#);
+        assert_eq!(sent[1].topic, "analytics.users");
+        assert_eq!(sent[1].key, None);
+        assert_eq!(kafka.stats().published, 2);
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_flushes_after_linger() {
+        let producer = Arc::new(MemoryProducer::default());
+        let kafka = KafkaTap::new(producer.clone())
+            .with_topic::<OrderPlaced>("analytics.orders")
+            .with_batching(100, Duration::from_millis(20));
+
+        tap(&kafka, OrderPlaced { order_id: 1 }).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(producer.sent.lock().unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(producer.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_counts_failures() {
+        let producer = Arc::new(MemoryProducer {
+            failures: AtomicU64::new(4),
+            ..Default::default()
+        });
+        let kafka = KafkaTap::new(producer.clone())
+            .with_topic::<OrderPlaced>("analytics.orders")
+            .with_batching(1, Duration::ZERO)
+            .with_retry(fast_retry(3));
+
+        // First record fails three times and is dropped; the second
+        // succeeds on its second attempt
+        tap(&kafka, OrderPlaced { order_id: 1 }).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tap(&kafka, OrderPlaced { order_id: 2 }).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            kafka.stats(),
+            KafkaTapStats {
+                published: 1,
+                retried: 3,
+                failed: 1,
+            }
+        );
+        assert_eq!(
+            &producer.sent.lock().unwrap()[0].payload[..],
+            br#"{"order_id":2}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_without_topic_is_an_error() {
+        let kafka = KafkaTap::new(Arc::new(MemoryProducer::default()));
+        let err = tap(&kafka, OrderPlaced { order_id: 1 }).await.unwrap_err();
+        assert!(err.to_string().contains("has no topic for"));
+    }
+}
@@ -0,0 +1,568 @@
+//! Chaos injection for effects and job stores (`chaos` feature).
+//!
+//! [`ChaosEffect`] and [`ChaosJobStore`] wrap a real effect or store and
+//! misbehave on purpose, the way production infrastructure does:
+//!
+//! - **Latency**: every call is delayed by a random duration in a range
+//! - **Transient errors**: calls fail before reaching the inner effect/store
+//! - **Duplicated deliveries**: an effect runs twice for one command; a
+//!   claimed job is handed out twice
+//! - **Lease expiries**: a claimed job's lease lapses, so its heartbeats and
+//!   outcomes fail with [`LeaseLost`] and the job is handed out again on the
+//!   next claim
+//!
+//! All randomness comes from one seeded generator per [`ChaosConfig`], so a
+//! failing CI run can be reproduced with the same seed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_testing::{ChaosConfig, ChaosEffect, ChaosJobStore};
+//!
+//! let chaos = ChaosConfig::new()
+//!     .with_seed(42)
+//!     .with_latency(Duration::from_millis(5), Duration::from_millis(50))
+//!     .with_error_rate(0.2)
+//!     .with_duplicate_rate(0.1);
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect_retry::<ChargeCard, _>(ChaosEffect::new(ChargeEffect, chaos.clone()), policy)
+//!     .build();
+//! let store = ChaosJobStore::new(PgJobStore::new(pool), chaos.with_lease_expiry_rate(0.05));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use seesaw_core::{
+    ClaimedJob, Command, Effect, EffectContext, FailureKind, JobLease, JobStore, LeaseLost,
+    LookaheadClaim,
+};
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// What to inject, and how often.
+///
+/// Rates are probabilities in `[0, 1]` checked independently on every call.
+/// Clones share the random generator and the [`ChaosStats`] counters.
+#[derive(Clone)]
+pub struct ChaosConfig {
+    latency: Option<(Duration, Duration)>,
+    error_rate: f64,
+    duplicate_rate: f64,
+    lease_expiry_rate: f64,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    rng: Mutex<fastrand::Rng>,
+    delays: AtomicU64,
+    errors: AtomicU64,
+    duplicates: AtomicU64,
+    lease_expiries: AtomicU64,
+}
+
+/// How much chaos was injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// Calls that were delayed.
+    pub delays: u64,
+    /// Calls that failed with an injected error.
+    pub errors: u64,
+    /// Effect executions or job claims that were duplicated.
+    pub duplicates: u64,
+    /// Claimed jobs whose lease was expired.
+    pub lease_expiries: u64,
+}
+
+impl ChaosConfig {
+    /// Create a config that injects nothing, with a random seed.
+    pub fn new() -> Self {
+        Self {
+            latency: None,
+            error_rate: 0.0,
+            duplicate_rate: 0.0,
+            lease_expiry_rate: 0.0,
+            shared: Arc::new(Shared {
+                rng: Mutex::new(fastrand::Rng::new()),
+                delays: AtomicU64::new(0),
+                errors: AtomicU64::new(0),
+                duplicates: AtomicU64::new(0),
+                lease_expiries: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Seed the random generator, for reproducible runs.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.shared.rng.lock().unwrap().seed(seed);
+        self
+    }
+
+    /// Delay every call by a random duration in `min..=max`.
+    pub fn with_latency(mut self, min: Duration, max: Duration) -> Self {
+        self.latency = Some((min, max.max(min)));
+        self
+    }
+
+    /// Fail calls with an injected transient error at this rate.
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Duplicate effect executions and job claims at this rate.
+    pub fn with_duplicate_rate(mut self, rate: f64) -> Self {
+        self.duplicate_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Expire the lease of claimed jobs at this rate (job stores only).
+    pub fn with_lease_expiry_rate(mut self, rate: f64) -> Self {
+        self.lease_expiry_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Counters of what was injected so far.
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            delays: self.shared.delays.load(Ordering::Relaxed),
+            errors: self.shared.errors.load(Ordering::Relaxed),
+            duplicates: self.shared.duplicates.load(Ordering::Relaxed),
+            lease_expiries: self.shared.lease_expiries.load(Ordering::Relaxed),
+        }
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0 && self.shared.rng.lock().unwrap().f64() < rate
+    }
+
+    /// Sleep for the configured latency, then fail at the error rate.
+    async fn disturb(&self, operation: &str) -> Result<()> {
+        if let Some((min, max)) = self.latency {
+            let nanos = self
+                .shared
+                .rng
+                .lock()
+                .unwrap()
+                .u64(min.as_nanos() as u64..=max.as_nanos() as u64);
+            self.shared.delays.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_nanos(nanos)).await;
+        }
+        if self.roll(self.error_rate) {
+            self.shared.errors.fetch_add(1, Ordering::Relaxed);
+            return Err(anyhow!(
+                "chaos: injected transient failure in {}",
+                operation
+            ));
+        }
+        Ok(())
+    }
+
+    fn duplicate(&self) -> bool {
+        let duplicate = self.roll(self.duplicate_rate);
+        if duplicate {
+            self.shared.duplicates.fetch_add(1, Ordering::Relaxed);
+        }
+        duplicate
+    }
+
+    fn expire_lease(&self) -> bool {
+        let expire = self.roll(self.lease_expiry_rate);
+        if expire {
+            self.shared.lease_expiries.fetch_add(1, Ordering::Relaxed);
+        }
+        expire
+    }
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ChaosConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosConfig")
+            .field("latency", &self.latency)
+            .field("error_rate", &self.error_rate)
+            .field("duplicate_rate", &self.duplicate_rate)
+            .field("lease_expiry_rate", &self.lease_expiry_rate)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Chaos Effect
+// =============================================================================
+
+/// An effect wrapper that injects latency, transient errors and duplicated
+/// executions.
+///
+/// A duplicated command runs the inner effect twice with clones of the
+/// command and context; the second result is returned, as if the first
+/// delivery's acknowledgement was lost. Wrap it in a `RetryingEffect` to
+/// check that the retry policy absorbs the injected errors.
+pub struct ChaosEffect<E> {
+    inner: E,
+    config: ChaosConfig,
+}
+
+impl<E> ChaosEffect<E> {
+    /// Wrap `inner` with the given chaos.
+    pub fn new(inner: E, config: ChaosConfig) -> Self {
+        Self { inner, config }
+    }
+
+    /// Counters of what was injected so far.
+    pub fn stats(&self) -> ChaosStats {
+        self.config.stats()
+    }
+}
+
+#[async_trait]
+impl<C, D, E> Effect<C, D> for ChaosEffect<E>
+where
+    C: Command + Clone,
+    D: Send + Sync + 'static,
+    E: Effect<C, D>,
+{
+    type Event = E::Event;
+
+    async fn execute(&self, command: C, ctx: EffectContext<D>) -> Result<Self::Event> {
+        self.config.disturb(std::any::type_name::<C>()).await?;
+        if self.config.duplicate() {
+            let _ = self.inner.execute(command.clone(), ctx.clone()).await;
+        }
+        self.inner.execute(command, ctx).await
+    }
+}
+
+impl<E> std::fmt::Debug for ChaosEffect<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosEffect")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Chaos Job Store
+// =============================================================================
+
+/// A job store wrapper that injects latency, transient errors, duplicated
+/// claims and lease expiries.
+///
+/// A job whose lease expires is still returned to the worker that claimed
+/// it, but its heartbeats and outcomes fail with [`LeaseLost`] and it is
+/// handed out again on the next claim with a new lease token and
+/// its attempt number bumped, as if another worker had reclaimed it after
+/// the lease timed out.
+pub struct ChaosJobStore<S> {
+    inner: S,
+    config: ChaosConfig,
+    /// Jobs whose lease expired, waiting to be handed out again.
+    reclaimable: Mutex<Vec<ClaimedJob>>,
+    /// Lease tokens of claims that no longer hold their job.
+    lapsed: Mutex<HashSet<Uuid>>,
+    /// Tokens of reissued claims, mapped to the inner store's token.
+    reissued: Mutex<HashMap<Uuid, Uuid>>,
+}
+
+impl<S> ChaosJobStore<S> {
+    /// Wrap `inner` with the given chaos.
+    pub fn new(inner: S, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            config,
+            reclaimable: Mutex::new(Vec::new()),
+            lapsed: Mutex::new(HashSet::new()),
+            reissued: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Counters of what was injected so far.
+    pub fn stats(&self) -> ChaosStats {
+        self.config.stats()
+    }
+
+    /// Hand out freshly claimed `jobs` along with those whose lease lapsed
+    /// on an earlier claim, expiring and duplicating at random.
+    fn hand_out(&self, jobs: Vec<ClaimedJob>) -> Vec<ClaimedJob> {
+        let mut claimed: Vec<ClaimedJob> = std::mem::take(&mut *self.reclaimable.lock().unwrap());
+        for job in jobs {
+            if self.config.expire_lease() {
+                let token = Uuid::new_v4();
+                self.lapsed.lock().unwrap().insert(job.lease_token);
+                self.reissued.lock().unwrap().insert(token, job.lease_token);
+                self.reclaimable.lock().unwrap().push(ClaimedJob {
+                    attempt: job.attempt + 1,
+                    lease_token: token,
+                    ..job.clone()
+                });
+            }
+            if self.config.duplicate() {
+                claimed.push(job.clone());
+            }
+            claimed.push(job);
+        }
+        claimed
+    }
+
+    /// Translate `lease` to the inner store's, failing if it lapsed.
+    fn inner_lease(&self, lease: JobLease) -> Result<JobLease> {
+        if self.lapsed.lock().unwrap().contains(&lease.token) {
+            return Err(LeaseLost {
+                job_id: lease.job_id,
+            }
+            .into());
+        }
+        let token = self.reissued.lock().unwrap().get(&lease.token).copied();
+        Ok(JobLease {
+            token: token.unwrap_or(lease.token),
+            ..lease
+        })
+    }
+}
+
+#[async_trait]
+impl<S: JobStore> JobStore for ChaosJobStore<S> {
+    async fn claim_ready(&self, worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+        self.config.disturb("claim_ready").await?;
+        let jobs = self.inner.claim_ready(worker_id, limit).await?;
+        Ok(self.hand_out(jobs))
+    }
+
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: Duration,
+    ) -> Result<LookaheadClaim> {
+        self.config.disturb("claim_ready_with_lookahead").await?;
+        let claim = self
+            .inner
+            .claim_ready_with_lookahead(worker_id, limit, lookahead)
+            .await?;
+        Ok(LookaheadClaim {
+            jobs: self.hand_out(claim.jobs),
+            upcoming: claim.upcoming,
+        })
+    }
+
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+        self.config.disturb("mark_succeeded").await?;
+        self.inner.mark_succeeded(self.inner_lease(lease)?).await
+    }
+
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+        self.config.disturb("mark_failed").await?;
+        self.inner
+            .mark_failed(self.inner_lease(lease)?, error, kind)
+            .await
+    }
+
+    async fn record_failure(
+        &self,
+        lease: JobLease,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<bool> {
+        self.config.disturb("record_failure").await?;
+        self.inner
+            .record_failure(self.inner_lease(lease)?, error, kind)
+            .await
+    }
+
+    async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+        self.config.disturb("heartbeat").await?;
+        self.inner.heartbeat(self.inner_lease(lease)?).await
+    }
+
+    async fn park(&self, lease: JobLease, reason: &str) -> Result<()> {
+        self.config.disturb("park").await?;
+        self.inner.park(self.inner_lease(lease)?, reason).await
+    }
+
+    async fn reserve(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        window: Duration,
+    ) -> Result<Vec<ClaimedJob>> {
+        self.config.disturb("reserve").await?;
+        self.inner.reserve(worker_id, limit, window).await
+    }
+
+    async fn confirm(&self, lease: JobLease) -> Result<()> {
+        self.config.disturb("confirm").await?;
+        self.inner.confirm(self.inner_lease(lease)?).await
+    }
+
+    async fn release(&self, lease: JobLease) -> Result<()> {
+        self.config.disturb("release").await?;
+        self.inner.release(self.inner_lease(lease)?).await
+    }
+
+    async fn running_jobs(&self, worker_id: &str) -> Result<Vec<ClaimedJob>> {
+        self.config.disturb("running_jobs").await?;
+        self.inner.running_jobs(worker_id).await
+    }
+}
+
+impl<S> std::fmt::Debug for ChaosJobStore<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosJobStore")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockJobStore;
+    use seesaw_core::{EventBus, RetryPolicy, RetryingEffect};
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Debug, Clone)]
+    struct Charge;
+    impl Command for Charge {}
+
+    #[derive(Debug, Clone)]
+    struct Charged;
+
+    #[derive(Default)]
+    struct CountingEffect {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Effect<Charge, ()> for CountingEffect {
+        type Event = Charged;
+
+        async fn execute(&self, _cmd: Charge, _ctx: EffectContext<()>) -> Result<Charged> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Charged)
+        }
+    }
+
+    fn ctx() -> EffectContext<()> {
+        EffectContext::new(Arc::new(()), EventBus::new())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_policy_survives_chaos_effect() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let chaos = ChaosConfig::new()
+            .with_seed(7)
+            .with_latency(Duration::from_millis(1), Duration::from_millis(20))
+            .with_error_rate(0.5)
+            .with_duplicate_rate(0.5);
+        let effect = RetryingEffect::new(
+            ChaosEffect::new(
+                CountingEffect {
+                    calls: calls.clone(),
+                },
+                chaos.clone(),
+            ),
+            RetryPolicy::new().with_max_attempts(20),
+        );
+
+        for _ in 0..20 {
+            effect.execute(Charge, ctx()).await.unwrap();
+        }
+
+        let stats = chaos.stats();
+        assert!(stats.errors > 0);
+        assert!(stats.duplicates > 0);
+        assert_eq!(stats.delays, stats.errors + 20);
+        assert_eq!(calls.load(Ordering::SeqCst) as u64, 20 + stats.duplicates);
+    }
+
+    #[tokio::test]
+    async fn test_chaos_job_store_duplicates_and_expires_leases() {
+        let mock = MockJobStore::new();
+        for _ in 0..4 {
+            mock.seed_job("charge", serde_json::json!({}), 1);
+        }
+        let store = ChaosJobStore::new(
+            mock,
+            ChaosConfig::new()
+                .with_duplicate_rate(1.0)
+                .with_lease_expiry_rate(1.0),
+        );
+
+        let first = store.claim_ready("worker-1", 10).await.unwrap();
+        assert_eq!(first.len(), 8);
+        let err = store.heartbeat(first[0].lease()).await.unwrap_err();
+        assert!(LeaseLost::is(&err));
+        assert!(store.mark_succeeded(first[0].lease()).await.is_err());
+
+        let reclaimed = store.claim_ready("worker-2", 10).await.unwrap();
+        assert_eq!(reclaimed.len(), 4);
+        assert!(reclaimed.iter().all(|job| job.attempt == 2));
+        assert_eq!(store.stats().lease_expiries, 4);
+
+        store.heartbeat(reclaimed[0].lease()).await.unwrap();
+        store.mark_succeeded(reclaimed[0].lease()).await.unwrap();
+        assert!(store.inner().job_succeeded(reclaimed[0].id));
+    }
+
+    #[tokio::test]
+    async fn test_lookahead_claims_are_disturbed_like_claims() {
+        let mock = MockJobStore::new();
+        mock.seed_job("charge", serde_json::json!({}), 1);
+        let store = ChaosJobStore::new(
+            mock,
+            ChaosConfig::new()
+                .with_duplicate_rate(1.0)
+                .with_lease_expiry_rate(1.0),
+        );
+        let lookahead = Duration::from_secs(60);
+
+        let first = store
+            .claim_ready_with_lookahead("worker-1", 10, lookahead)
+            .await
+            .unwrap();
+        assert_eq!(first.jobs.len(), 2);
+        assert!(LeaseLost::is(&store.heartbeat(first.jobs[0].lease()).await.unwrap_err()));
+
+        let reclaimed = store
+            .claim_ready_with_lookahead("worker-2", 10, lookahead)
+            .await
+            .unwrap();
+        assert_eq!(reclaimed.jobs.len(), 1);
+        assert_eq!(reclaimed.jobs[0].attempt, first.jobs[0].attempt + 1);
+
+        let failing =
+            ChaosJobStore::new(MockJobStore::new(), ChaosConfig::new().with_error_rate(1.0));
+        assert!(failing
+            .claim_ready_with_lookahead("worker-1", 1, lookahead)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_injected_errors_fail_calls() {
+        let store =
+            ChaosJobStore::new(MockJobStore::new(), ChaosConfig::new().with_error_rate(1.0));
+        let err = store.claim_ready("worker-1", 1).await.unwrap_err();
+        assert!(err.to_string().contains("injected transient failure"));
+    }
+}
@@ -0,0 +1,400 @@
+//! Running a single effect in isolation, over test dependencies.
+//!
+//! [`TestEffectHarness`] builds the [`EffectContext`] an effect would get
+//! from the dispatcher, runs the effect once, and captures what came out of
+//! it: the returned event (or error), any signals it emitted on the bus, and
+//! how long it took. Under `#[tokio::test(start_paused = true)]` the elapsed
+//! time is virtual, so retries and timeouts can be asserted without waiting.
+//!
+//! [`FaultInjector`] lets fake dependencies fail or stall on demand.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_testing::{FaultInjector, TestEffectHarness};
+//!
+//! #[tokio::test(start_paused = true)]
+//! async fn test_charge_retries_once() {
+//!     let faults = FaultInjector::new();
+//!     faults.fail("gateway.charge", 1, "connection reset");
+//!     faults.delay("gateway.charge", Duration::from_millis(200));
+//!
+//!     let harness = TestEffectHarness::new(FakeDeps { gateway: FakeGateway::new(faults.clone()) });
+//!     let run = harness.execute(&ChargeEffect, ChargeCard { amount: 10 }).await;
+//!
+//!     assert!(matches!(run.event(), PaymentEvent::Charged { .. }));
+//!     assert_eq!(faults.calls("gateway.charge"), 2);
+//!     assert_eq!(run.elapsed(), Duration::from_millis(400));
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::sync::broadcast::error::TryRecvError;
+
+use seesaw_core::{Command, Effect, EffectContext, EventBus, EventEnvelope, Metadata};
+
+// =============================================================================
+// Harness
+// =============================================================================
+
+/// Executes effects against test dependencies.
+pub struct TestEffectHarness<D> {
+    deps: Arc<D>,
+    metadata: Metadata,
+}
+
+impl<D: Send + Sync + 'static> TestEffectHarness<D> {
+    /// Create a harness over the given dependencies.
+    pub fn new(deps: D) -> Self {
+        Self::from_arc(Arc::new(deps))
+    }
+
+    /// Create a harness over Arc-wrapped dependencies, to keep a handle on
+    /// them for assertions.
+    pub fn from_arc(deps: Arc<D>) -> Self {
+        Self {
+            deps,
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Run effects with request-scoped `metadata`, as if their command was
+    /// decided from an event carrying it.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Get the dependencies.
+    pub fn deps(&self) -> &D {
+        &self.deps
+    }
+
+    /// Execute `effect` once with `command`.
+    pub async fn execute<C, E>(&self, effect: &E, command: C) -> EffectRun<E::Event>
+    where
+        C: Command,
+        E: Effect<C, D>,
+    {
+        self.run(|ctx| effect.execute(command, ctx)).await
+    }
+
+    /// Execute `effect` with a batch of commands through `execute_batch`.
+    pub async fn execute_batch<C, E>(
+        &self,
+        effect: &E,
+        commands: Vec<C>,
+    ) -> EffectRun<Vec<E::Event>>
+    where
+        C: Command,
+        E: Effect<C, D>,
+    {
+        self.run(|ctx| effect.execute_batch(commands, ctx)).await
+    }
+
+    async fn run<T, F, Fut>(&self, execute: F) -> EffectRun<T>
+    where
+        F: FnOnce(EffectContext<D>) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let ctx = EffectContext::new(self.deps.clone(), bus).with_metadata(self.metadata.clone());
+
+        let started = tokio::time::Instant::now();
+        let result = execute(ctx).await;
+        let elapsed = started.elapsed();
+
+        let mut signals = Vec::new();
+        loop {
+            match receiver.try_recv() {
+                Ok(envelope) => signals.push(envelope),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+
+        EffectRun {
+            result,
+            signals,
+            elapsed,
+        }
+    }
+}
+
+impl<D> std::fmt::Debug for TestEffectHarness<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestEffectHarness").finish_non_exhaustive()
+    }
+}
+
+/// The outcome of one effect execution.
+#[derive(Debug)]
+pub struct EffectRun<T> {
+    result: Result<T>,
+    signals: Vec<EventEnvelope>,
+    elapsed: Duration,
+}
+
+impl<T: std::fmt::Debug> EffectRun<T> {
+    /// The effect's result.
+    pub fn result(&self) -> &Result<T> {
+        &self.result
+    }
+
+    /// Consume the run and return the effect's result.
+    pub fn into_result(self) -> Result<T> {
+        self.result
+    }
+
+    /// Check if the effect succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The returned event (or events, for a batch).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the effect failed.
+    pub fn event(&self) -> &T {
+        match &self.result {
+            Ok(event) => event,
+            Err(e) => panic!("expected the effect to succeed, it failed: {:#}", e),
+        }
+    }
+
+    /// The error the effect failed with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the effect succeeded.
+    pub fn error(&self) -> &anyhow::Error {
+        match &self.result {
+            Ok(event) => panic!("expected the effect to fail, it returned {:?}", event),
+            Err(e) => e,
+        }
+    }
+
+    /// Signals of type `S` the effect emitted through `ctx.signal`, in order.
+    pub fn signals<S: Clone + 'static>(&self) -> Vec<S> {
+        self.signals
+            .iter()
+            .filter_map(|envelope| envelope.payload.downcast_ref::<S>().cloned())
+            .collect()
+    }
+
+    /// Number of signals of any type the effect emitted.
+    pub fn signal_count(&self) -> usize {
+        self.signals.len()
+    }
+
+    /// How long the effect took (virtual time when the clock is paused).
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+// =============================================================================
+// Fault Injection
+// =============================================================================
+
+/// Scripted failures and delays for fake dependencies.
+///
+/// Fakes call [`hit`](Self::hit) at each named point, e.g.
+/// `"gateway.charge"`; tests decide what happens there. Unconfigured points
+/// succeed immediately. Clones share the same script.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    points: Arc<Mutex<HashMap<String, FaultPoint>>>,
+}
+
+#[derive(Debug, Default)]
+struct FaultPoint {
+    calls: usize,
+    /// Remaining calls that fail (`usize::MAX` = every call).
+    failures: usize,
+    message: String,
+    delay: Duration,
+}
+
+impl FaultInjector {
+    /// Create an injector with no faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the next `times` calls at `point` with `message`.
+    pub fn fail(&self, point: &str, times: usize, message: &str) {
+        let mut points = self.points.lock().unwrap();
+        let fault = points.entry(point.to_string()).or_default();
+        fault.failures = times;
+        fault.message = message.to_string();
+    }
+
+    /// Fail every call at `point` with `message`.
+    pub fn fail_always(&self, point: &str, message: &str) {
+        self.fail(point, usize::MAX, message);
+    }
+
+    /// Delay every call at `point` by `delay` before it succeeds or fails.
+    pub fn delay(&self, point: &str, delay: Duration) {
+        self.points
+            .lock()
+            .unwrap()
+            .entry(point.to_string())
+            .or_default()
+            .delay = delay;
+    }
+
+    /// Remove every fault, keeping call counts.
+    pub fn heal(&self) {
+        for fault in self.points.lock().unwrap().values_mut() {
+            fault.failures = 0;
+            fault.delay = Duration::ZERO;
+        }
+    }
+
+    /// Number of times `point` was hit.
+    pub fn calls(&self, point: &str) -> usize {
+        self.points
+            .lock()
+            .unwrap()
+            .get(point)
+            .map_or(0, |fault| fault.calls)
+    }
+
+    /// Pass through `point`: wait out its delay, then fail if scripted to.
+    ///
+    /// Call this from fake dependencies.
+    pub async fn hit(&self, point: &str) -> Result<()> {
+        let (delay, failure) = {
+            let mut points = self.points.lock().unwrap();
+            let fault = points.entry(point.to_string()).or_default();
+            fault.calls += 1;
+            let failure = (fault.failures > 0).then(|| fault.message.clone());
+            if fault.failures > 0 && fault.failures != usize::MAX {
+                fault.failures -= 1;
+            }
+            (fault.delay, failure)
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        match failure {
+            Some(message) => Err(anyhow!("{}", message)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeGateway {
+        faults: FaultInjector,
+    }
+
+    impl FakeGateway {
+        async fn charge(&self, amount: u32) -> Result<String> {
+            self.faults.hit("gateway.charge").await?;
+            Ok(format!("ch_{}", amount))
+        }
+    }
+
+    struct Deps {
+        gateway: FakeGateway,
+    }
+
+    #[derive(Debug, Clone)]
+    struct ChargeCard {
+        amount: u32,
+    }
+    impl Command for ChargeCard {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum PaymentEvent {
+        Charged { charge_id: String },
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Attempt(u32);
+
+    /// Tries the gateway twice, signalling each attempt.
+    struct ChargeEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<ChargeCard, Deps> for ChargeEffect {
+        type Event = PaymentEvent;
+
+        async fn execute(&self, cmd: ChargeCard, ctx: EffectContext<Deps>) -> Result<PaymentEvent> {
+            let mut last_error = None;
+            for attempt in 1..=2 {
+                ctx.signal(Attempt(attempt));
+                match ctx.deps().gateway.charge(cmd.amount).await {
+                    Ok(charge_id) => return Ok(PaymentEvent::Charged { charge_id }),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            Err(last_error.unwrap())
+        }
+    }
+
+    fn harness(faults: &FaultInjector) -> TestEffectHarness<Deps> {
+        TestEffectHarness::new(Deps {
+            gateway: FakeGateway {
+                faults: faults.clone(),
+            },
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_harness_captures_event_signals_and_virtual_time() {
+        let faults = FaultInjector::new();
+        faults.fail("gateway.charge", 1, "connection reset");
+        faults.delay("gateway.charge", Duration::from_millis(200));
+
+        let run = harness(&faults)
+            .execute(&ChargeEffect, ChargeCard { amount: 10 })
+            .await;
+
+        assert_eq!(
+            run.event(),
+            &PaymentEvent::Charged {
+                charge_id: "ch_10".to_string()
+            }
+        );
+        assert_eq!(run.signals::<Attempt>(), vec![Attempt(1), Attempt(2)]);
+        assert_eq!(run.elapsed(), Duration::from_millis(400));
+        assert_eq!(faults.calls("gateway.charge"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_harness_reports_injected_failure() {
+        let faults = FaultInjector::new();
+        faults.fail_always("gateway.charge", "card declined");
+
+        let run = harness(&faults)
+            .execute(&ChargeEffect, ChargeCard { amount: 10 })
+            .await;
+
+        assert_eq!(run.error().to_string(), "card declined");
+        assert_eq!(run.signal_count(), 2);
+
+        faults.heal();
+        let run = harness(&faults)
+            .execute_batch(
+                &ChargeEffect,
+                vec![ChargeCard { amount: 1 }, ChargeCard { amount: 2 }],
+            )
+            .await;
+        assert_eq!(run.event().len(), 2);
+        assert_eq!(faults.calls("gateway.charge"), 4);
+    }
+}
@@ -45,6 +45,54 @@
 //!     .assert_state(|m| m.steps_completed == 2);
 //! ```
 //!
+//! ## Using `MachineTester`
+//!
+//! ```ignore
+//! use seesaw_testing::MachineTester;
+//!
+//! MachineTester::new(CartMachine::default())
+//!     .given([CartEvent::Added { sku: "apple" }, CartEvent::Added { sku: "pear" }])
+//!     .when(CartEvent::CheckedOut)
+//!     .then_commands([CartCommand::Charge { items: 2 }]);
+//! ```
+//!
+//! ## Using `MachineProperty` for Invariants (`proptest` feature)
+//!
+//! ```ignore
+//! use seesaw_testing::MachineProperty;
+//!
+//! MachineProperty::new(BakeMachine::default, deck_event_strategy())
+//!     .assert_holds(|_machine, trace| {
+//!         let decks: Vec<_> = trace.commands().map(|BakeCommand::SetupLoaf { deck_id }| deck_id).collect();
+//!         let unique: HashSet<_> = decks.iter().collect();
+//!         if unique.len() == decks.len() { Ok(()) } else { Err("deck set up twice".into()) }
+//!     });
+//! ```
+//!
+//! ## Using `TestEffectHarness` for Effect Tests
+//!
+//! ```ignore
+//! use seesaw_testing::{FaultInjector, TestEffectHarness};
+//!
+//! let faults = FaultInjector::new();
+//! faults.fail("gateway.charge", 1, "connection reset");
+//!
+//! let run = TestEffectHarness::new(FakeDeps::new(faults.clone()))
+//!     .execute(&ChargeEffect, ChargeCard { amount: 10 })
+//!     .await;
+//! assert!(matches!(run.event(), PaymentEvent::Charged { .. }));
+//! ```
+//!
+//! ## Using `ChaosEffect` and `ChaosJobStore` (`chaos` feature)
+//!
+//! ```ignore
+//! use seesaw_testing::{ChaosConfig, ChaosEffect, ChaosJobStore};
+//!
+//! let chaos = ChaosConfig::new().with_seed(42).with_error_rate(0.2).with_duplicate_rate(0.1);
+//! let effect = RetryingEffect::new(ChaosEffect::new(ChargeEffect, chaos.clone()), policy);
+//! let store = ChaosJobStore::new(store, chaos.with_lease_expiry_rate(0.05));
+//! ```
+//!
 //! ## Using `EventLatch` for Fan-Out Tests
 //!
 //! ```ignore
@@ -59,6 +107,40 @@
 //! latch.await_zero().await;  // Wait for all 3 events
 //! assert!(all_notifications_sent());
 //! ```
+//!
+//! ## Using `SimulationBuilder` for Time-Based Workflows
+//!
+//! ```ignore
+//! use seesaw_testing::SimulationBuilder;
+//!
+//! SimulationBuilder::new(deps)
+//!     .with_machine(ReminderMachine::default())
+//!     .with_effects(|d| d.with_effect::<SendReminder, _>(SendReminderEffect))
+//!     .with_job::<SendReminder>("reminder:send", vec![1])
+//!     .run(|sim| async move {
+//!         sim.emit(UserEvent::SignedUp { user_id }).await;
+//!         sim.advance(Duration::from_secs(5 * 60)).await;  // virtual, instant
+//!         sim.machine(|m: &ReminderMachine| assert!(m.reminded.contains(&user_id)));
+//!     });
+//! ```
+//!
+//! ## Using `ScenarioRecorder` for Regression Tests
+//!
+//! ```ignore
+//! use seesaw_testing::{Scenario, ScenarioRecorder, ScenarioReplayer};
+//!
+//! // Record decisions during a real run...
+//! let recorder = ScenarioRecorder::new().with_event::<OrderEvent>().with_command::<OrderCommand>();
+//! let engine = EngineBuilder::new(deps).with_machine_middleware(recorder.clone()) /* ... */;
+//! std::fs::write("checkout.json", recorder.scenario().to_json()?)?;
+//!
+//! // ...and check a refactored machine still decides the same
+//! ScenarioReplayer::new(&Scenario::from_json(include_str!("checkout.json"))?)
+//!     .with_event::<OrderEvent>()
+//!     .with_command::<OrderCommand>()
+//!     .with_machine(OrderMachine::default())
+//!     .assert_replays();
+//! ```
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
@@ -70,6 +152,27 @@ use uuid::Uuid;
 
 use seesaw_core::{JobQueue, JobSpec, Machine};
 
+#[cfg(feature = "chaos")]
+mod chaos;
+mod effect_harness;
+mod machine_tester;
+#[cfg(feature = "proptest")]
+mod property;
+mod scenario;
+mod simulation;
+
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosConfig, ChaosEffect, ChaosJobStore, ChaosStats};
+pub use effect_harness::{EffectRun, FaultInjector, TestEffectHarness};
+pub use machine_tester::MachineTester;
+#[cfg(feature = "proptest")]
+pub use property::{MachineProperty, PropertyFailure, Trace};
+pub use scenario::{
+    RecordedDecision, RecordedOutcome, Scenario, ScenarioMismatch, ScenarioRecorder,
+    ScenarioReplayer, ScenarioReport,
+};
+pub use simulation::{SimJob, Simulation, SimulationBuilder};
+
 /// Asserts a sequence of event → command transitions for a machine.
 ///
 /// This macro provides a concise way to test state machine transitions.
@@ -670,6 +773,8 @@ pub struct EnqueuedJob {
     pub spec: JobSpec,
     /// When the job is scheduled to run (None for immediate background jobs).
     pub scheduled_at: Option<DateTime<Utc>>,
+    /// `(cron, tz)` of a recurring job (None for one-off jobs).
+    pub recurring: Option<(String, String)>,
     /// When the job was enqueued.
     pub enqueued_at: DateTime<Utc>,
 }
@@ -837,6 +942,25 @@ impl SpyJobQueue {
         );
     }
 
+    /// Assert a job was registered to recur on `cron`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no recurring job of this type has that cron expression.
+    pub fn assert_recurring(&self, job_type: &str, cron: &str) {
+        let jobs = self.jobs_of_type(job_type);
+        let found = jobs
+            .iter()
+            .any(|j| j.recurring.as_ref().is_some_and(|(c, _)| c == cron));
+        assert!(
+            found,
+            "Expected job '{}' to recur on '{}', found: {:?}",
+            job_type,
+            cron,
+            jobs.iter().map(|j| &j.recurring).collect::<Vec<_>>()
+        );
+    }
+
     /// Assert a job was scheduled to run at or after a specific time.
     ///
     /// # Panics
@@ -846,7 +970,7 @@ impl SpyJobQueue {
         let jobs = self.jobs_of_type(job_type);
         let found = jobs
             .iter()
-            .any(|j| j.scheduled_at.map_or(false, |t| t >= min_time));
+            .any(|j| j.scheduled_at.is_some_and(|t| t >= min_time));
         assert!(
             found,
             "Expected job '{}' to be scheduled at or after {}, found: {:?}",
@@ -867,6 +991,7 @@ impl JobQueue for SpyJobQueue {
             payload,
             spec,
             scheduled_at: None,
+            recurring: None,
             enqueued_at: Utc::now(),
         };
 
@@ -887,6 +1012,28 @@ impl JobQueue for SpyJobQueue {
             payload,
             spec,
             scheduled_at: Some(run_at),
+            recurring: None,
+            enqueued_at: Utc::now(),
+        };
+        self.enqueued.lock().unwrap().push(job);
+        Ok(id)
+    }
+
+    async fn schedule_recurring(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        cron: &str,
+        tz: &str,
+    ) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let job = EnqueuedJob {
+            id,
+            job_type: spec.job_type.to_string(),
+            payload,
+            spec,
+            scheduled_at: None,
+            recurring: Some((cron.to_string(), tz.to_string())),
             enqueued_at: Utc::now(),
         };
         self.enqueued.lock().unwrap().push(job);
@@ -917,6 +1064,8 @@ pub struct RecordedJob {
     pub error: Option<String>,
     /// When the job should run (for scheduled jobs).
     pub run_at: Option<DateTime<Utc>>,
+    /// Lease token of the current claim, if claimed.
+    pub lease_token: Option<Uuid>,
 }
 
 /// Job status in the mock store.
@@ -963,9 +1112,12 @@ pub enum JobStatus {
 #[derive(Debug, Clone, Default)]
 pub struct MockJobStore {
     jobs: Arc<Mutex<Vec<RecordedJob>>>,
-    heartbeats: Arc<Mutex<Vec<(Uuid, DateTime<Utc>)>>>,
+    heartbeats: HeartbeatLog,
 }
 
+/// Shared log of `(job_id, timestamp)` heartbeat records.
+type HeartbeatLog = Arc<Mutex<Vec<(Uuid, DateTime<Utc>)>>>;
+
 impl MockJobStore {
     /// Create a new empty mock store.
     pub fn new() -> Self {
@@ -991,6 +1143,7 @@ impl MockJobStore {
             status: JobStatus::Pending,
             error: None,
             run_at: None,
+            lease_token: None,
         };
         self.jobs.lock().unwrap().push(job);
         id
@@ -1014,6 +1167,7 @@ impl MockJobStore {
             status: JobStatus::Pending,
             error: None,
             run_at: Some(run_at),
+            lease_token: None,
         };
         self.jobs.lock().unwrap().push(job);
         id
@@ -1118,8 +1272,10 @@ impl seesaw_core::JobStore for MockJobStore {
                     }
                 }
 
+                let lease_token = Uuid::new_v4();
                 job.status = JobStatus::Claimed;
                 job.attempt += 1;
+                job.lease_token = Some(lease_token);
 
                 claimed.push(seesaw_core::ClaimedJob {
                     id: job.id,
@@ -1127,6 +1283,7 @@ impl seesaw_core::JobStore for MockJobStore {
                     payload: job.payload.clone(),
                     version: job.version,
                     attempt: job.attempt,
+                    lease_token,
                 });
             }
         }
@@ -1134,10 +1291,47 @@ impl seesaw_core::JobStore for MockJobStore {
         Ok(claimed)
     }
 
-    async fn mark_succeeded(&self, job_id: Uuid) -> Result<()> {
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: std::time::Duration,
+    ) -> Result<seesaw_core::LookaheadClaim> {
+        let jobs = self.claim_ready(worker_id, limit).await?;
+        let now = Utc::now();
+        let until = chrono::Duration::from_std(lookahead)
+            .ok()
+            .and_then(|lookahead| now.checked_add_signed(lookahead))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        let mut upcoming: Vec<_> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.status == JobStatus::Pending)
+            .filter_map(|job| {
+                let run_at = job.run_at.filter(|run_at| *run_at > now && *run_at <= until)?;
+                Some(seesaw_core::UpcomingJob {
+                    id: job.id,
+                    job_type: job.job_type.clone(),
+                    run_at,
+                })
+            })
+            .collect();
+        upcoming.sort_by_key(|job| job.run_at);
+        upcoming.truncate(limit.max(0) as usize);
+
+        Ok(seesaw_core::LookaheadClaim { jobs, upcoming })
+    }
+
+    async fn mark_succeeded(&self, lease: seesaw_core::JobLease) -> Result<()> {
+        let job_id = lease.job_id;
         let mut jobs = self.jobs.lock().unwrap();
         if let Some(job) = jobs.iter_mut().find(|j| j.id == job_id) {
+            check_lease(job, lease)?;
             job.status = JobStatus::Succeeded;
+            job.lease_token = None;
             Ok(())
         } else {
             Err(anyhow::anyhow!("job not found: {}", job_id))
@@ -1146,13 +1340,16 @@ impl seesaw_core::JobStore for MockJobStore {
 
     async fn mark_failed(
         &self,
-        job_id: Uuid,
+        lease: seesaw_core::JobLease,
         error: &str,
         kind: seesaw_core::FailureKind,
     ) -> Result<()> {
+        let job_id = lease.job_id;
         let mut jobs = self.jobs.lock().unwrap();
         if let Some(job) = jobs.iter_mut().find(|j| j.id == job_id) {
+            check_lease(job, lease)?;
             job.error = Some(error.to_string());
+            job.lease_token = None;
             match kind {
                 seesaw_core::FailureKind::Retryable => {
                     job.status = JobStatus::Failed;
@@ -1169,8 +1366,9 @@ impl seesaw_core::JobStore for MockJobStore {
         }
     }
 
-    async fn heartbeat(&self, job_id: Uuid) -> Result<()> {
-        // Verify job exists and is claimed
+    async fn heartbeat(&self, lease: seesaw_core::JobLease) -> Result<()> {
+        let job_id = lease.job_id;
+        // Verify job exists and is claimed under this lease
         {
             let jobs = self.jobs.lock().unwrap();
             let job = jobs
@@ -1180,6 +1378,7 @@ impl seesaw_core::JobStore for MockJobStore {
             if job.status != JobStatus::Claimed {
                 return Err(anyhow::anyhow!("job not claimed: {}", job_id));
             }
+            check_lease(job, lease)?;
         }
 
         self.heartbeats.lock().unwrap().push((job_id, Utc::now()));
@@ -1187,6 +1386,14 @@ impl seesaw_core::JobStore for MockJobStore {
     }
 }
 
+/// Fail with [`seesaw_core::LeaseLost`] unless `lease` holds the job's current claim.
+fn check_lease(job: &RecordedJob, lease: seesaw_core::JobLease) -> Result<()> {
+    if job.lease_token != Some(lease.token) {
+        return Err(seesaw_core::LeaseLost { job_id: job.id }.into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod mock_store_tests {
     use super::*;
@@ -1253,6 +1460,29 @@ mod mock_store_tests {
         assert_eq!(claimed.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_mock_store_lookahead_lists_jobs_due_soon() {
+        let store = MockJobStore::new();
+        let ready = store.seed_job("test:job", serde_json::json!({}), 1);
+        let soon = Utc::now() + chrono::Duration::seconds(30);
+        let soon_id = store.seed_scheduled_job("scheduled:job", serde_json::json!({}), 1, soon);
+        let later = Utc::now() + chrono::Duration::hours(1);
+        store.seed_scheduled_job("scheduled:job", serde_json::json!({}), 1, later);
+
+        let claim = store
+            .claim_ready_with_lookahead("worker-1", 10, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_eq!(claim.jobs.len(), 1);
+        assert_eq!(claim.jobs[0].id, ready);
+        assert_eq!(claim.upcoming.len(), 1);
+        assert_eq!(claim.upcoming[0].id, soon_id);
+        assert_eq!(claim.next_run_at(), Some(soon));
+        // Listed, not claimed
+        assert_eq!(store.get_job(soon_id).unwrap().status, JobStatus::Pending);
+    }
+
     #[tokio::test]
     async fn test_mock_store_scheduled_job_ready() {
         let store = MockJobStore::new();
@@ -1272,10 +1502,10 @@ mod mock_store_tests {
         let job_id = store.seed_job("test:job", serde_json::json!({}), 1);
 
         // Claim first
-        store.claim_ready("worker-1", 10).await.unwrap();
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
 
         // Mark succeeded
-        store.mark_succeeded(job_id).await.unwrap();
+        store.mark_succeeded(claimed[0].lease()).await.unwrap();
 
         assert!(store.job_succeeded(job_id));
     }
@@ -1285,9 +1515,9 @@ mod mock_store_tests {
         let store = MockJobStore::new();
         let job_id = store.seed_job("test:job", serde_json::json!({}), 1);
 
-        store.claim_ready("worker-1", 10).await.unwrap();
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
         store
-            .mark_failed(job_id, "transient error", FailureKind::Retryable)
+            .mark_failed(claimed[0].lease(), "transient error", FailureKind::Retryable)
             .await
             .unwrap();
 
@@ -1302,9 +1532,9 @@ mod mock_store_tests {
         let store = MockJobStore::new();
         let job_id = store.seed_job("test:job", serde_json::json!({}), 1);
 
-        store.claim_ready("worker-1", 10).await.unwrap();
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
         store
-            .mark_failed(job_id, "permanent error", FailureKind::NonRetryable)
+            .mark_failed(claimed[0].lease(), "permanent error", FailureKind::NonRetryable)
             .await
             .unwrap();
 
@@ -1316,12 +1546,13 @@ mod mock_store_tests {
         let store = MockJobStore::new();
         let job_id = store.seed_job("long:job", serde_json::json!({}), 1);
 
-        store.claim_ready("worker-1", 10).await.unwrap();
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
+        let lease = claimed[0].lease();
 
         // Send heartbeats
-        store.heartbeat(job_id).await.unwrap();
-        store.heartbeat(job_id).await.unwrap();
-        store.heartbeat(job_id).await.unwrap();
+        store.heartbeat(lease).await.unwrap();
+        store.heartbeat(lease).await.unwrap();
+        store.heartbeat(lease).await.unwrap();
 
         assert_eq!(store.heartbeat_count(job_id), 3);
     }
@@ -1332,7 +1563,11 @@ mod mock_store_tests {
         let job_id = store.seed_job("test:job", serde_json::json!({}), 1);
 
         // Job is pending, not claimed
-        let result = store.heartbeat(job_id).await;
+        let lease = seesaw_core::JobLease {
+            job_id,
+            token: Uuid::new_v4(),
+        };
+        let result = store.heartbeat(lease).await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not claimed"));
@@ -1370,13 +1605,35 @@ mod mock_store_tests {
 
         // Fail with retryable
         store
-            .mark_failed(job_id, "error", FailureKind::Retryable)
+            .mark_failed(claimed[0].lease(), "error", FailureKind::Retryable)
             .await
             .unwrap();
 
         // Second attempt
         let claimed = store.claim_ready("worker-1", 10).await.unwrap();
         assert_eq!(claimed[0].attempt, 2);
+        assert_eq!(store.job_attempt(job_id), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_mock_store_rejects_stale_lease() {
+        let store = MockJobStore::new();
+        store.seed_job("retry:job", serde_json::json!({}), 1);
+
+        let first = store.claim_ready("worker-1", 10).await.unwrap();
+        store
+            .mark_failed(first[0].lease(), "error", FailureKind::Retryable)
+            .await
+            .unwrap();
+        let second = store.claim_ready("worker-2", 10).await.unwrap();
+
+        // The first claim's lease no longer holds the job
+        let err = store.mark_succeeded(first[0].lease()).await.unwrap_err();
+        assert!(seesaw_core::LeaseLost::is(&err));
+        assert!(store.heartbeat(first[0].lease()).await.is_err());
+
+        store.mark_succeeded(second[0].lease()).await.unwrap();
+        assert!(store.job_succeeded(second[0].id));
     }
 }
 
@@ -0,0 +1,276 @@
+//! Given / when / then assertions for pure machine tests.
+
+use std::fmt::Debug;
+
+use seesaw_core::Machine;
+
+/// Given / when / then test builder for machines.
+///
+/// `given` events set up state and their commands are ignored; `when`
+/// events produce the commands that the next `then_*` call asserts on.
+/// Mismatches panic with a line diff of expected and actual commands.
+///
+/// # Example
+///
+/// ```ignore
+/// use seesaw_testing::MachineTester;
+///
+/// MachineTester::new(CartMachine::default())
+///     .given([CartEvent::Added { sku: "apple" }, CartEvent::Added { sku: "pear" }])
+///     .when(CartEvent::CheckedOut)
+///     .then_commands([CartCommand::Charge { items: 2 }])
+///     .when(CartEvent::CheckedOut)
+///     .then_no_commands()
+///     .then_state(|m| m.checked_out);
+/// ```
+///
+/// Failure output:
+///
+/// ```text
+/// commands differ after when(CheckedOut) (- expected, + actual):
+///   [
+///       Charge {
+/// -         items: 2,
+/// +         items: 1,
+///       },
+///   ]
+/// ```
+pub struct MachineTester<M>
+where
+    M: Machine,
+{
+    machine: M,
+    /// `{:?}` of the `when` events since the last `then_*`.
+    when: Vec<String>,
+    /// Commands decided for those events.
+    commands: Vec<M::Command>,
+}
+
+impl<M> MachineTester<M>
+where
+    M: Machine,
+    M::Event: Debug,
+    M::Command: Debug + PartialEq,
+{
+    /// Create a new tester for the given machine.
+    pub fn new(machine: M) -> Self {
+        Self {
+            machine,
+            when: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    /// Feed setup events to the machine, ignoring the commands they produce.
+    pub fn given(mut self, events: impl IntoIterator<Item = M::Event>) -> Self {
+        for event in events {
+            self.machine.decide(&event);
+        }
+        self
+    }
+
+    /// Feed an event whose commands the next `then_*` call asserts on.
+    ///
+    /// Can be called several times before asserting.
+    pub fn when(mut self, event: M::Event) -> Self {
+        self.when.push(format!("{:?}", event));
+        self.commands.extend(self.machine.decide(&event));
+        self
+    }
+
+    /// Assert the `when` events produced exactly these commands, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a line diff if the commands differ.
+    pub fn then_commands(mut self, expected: impl IntoIterator<Item = M::Command>) -> Self {
+        let expected: Vec<M::Command> = expected.into_iter().collect();
+        if expected != self.commands {
+            panic!(
+                "commands differ after {} (- expected, + actual):\n{}",
+                self.describe_when(),
+                diff(
+                    &format!("{:#?}", expected),
+                    &format!("{:#?}", self.commands)
+                )
+            );
+        }
+        self.when.clear();
+        self.commands.clear();
+        self
+    }
+
+    /// Assert the `when` events produced no commands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any command was produced.
+    pub fn then_no_commands(self) -> Self {
+        self.then_commands([])
+    }
+
+    /// Assert the machine state matches a predicate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the predicate returns false.
+    pub fn then_state<F>(self, predicate: F) -> Self
+    where
+        F: FnOnce(&M) -> bool,
+    {
+        assert!(
+            predicate(&self.machine),
+            "state predicate failed after {}",
+            self.describe_when()
+        );
+        self
+    }
+
+    /// Get a reference to the machine for custom assertions.
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    /// Consume the tester and return the machine.
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+
+    fn describe_when(&self) -> String {
+        if self.when.is_empty() {
+            "given events".to_string()
+        } else {
+            format!("when({})", self.when.join(", "))
+        }
+    }
+}
+
+impl<M> Debug for MachineTester<M>
+where
+    M: Machine,
+    M::Command: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MachineTester")
+            .field("when", &self.when)
+            .field("commands", &self.commands)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Line diff of `expected` and `actual`: unchanged lines are indented,
+/// removed lines prefixed with `-` and added lines with `+`.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] = length of the longest common subsequence of expected[i..] and actual[j..]
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            out.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seesaw_core::Command;
+
+    #[derive(Debug, Clone)]
+    enum CartEvent {
+        Added(&'static str),
+        CheckedOut,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum CartCommand {
+        Charge { items: usize },
+    }
+    impl Command for CartCommand {}
+
+    #[derive(Default)]
+    struct CartMachine {
+        skus: Vec<&'static str>,
+        checked_out: bool,
+    }
+
+    impl Machine for CartMachine {
+        type Event = CartEvent;
+        type Command = CartCommand;
+
+        fn decide(&mut self, event: &CartEvent) -> Option<CartCommand> {
+            match event {
+                CartEvent::Added(sku) => {
+                    self.skus.push(sku);
+                    None
+                }
+                CartEvent::CheckedOut if !self.checked_out => {
+                    self.checked_out = true;
+                    Some(CartCommand::Charge {
+                        items: self.skus.len(),
+                    })
+                }
+                CartEvent::CheckedOut => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_machine_tester_given_when_then() {
+        let machine = MachineTester::new(CartMachine::default())
+            .given([CartEvent::Added("apple"), CartEvent::Added("pear")])
+            .when(CartEvent::CheckedOut)
+            .then_commands([CartCommand::Charge { items: 2 }])
+            .when(CartEvent::CheckedOut)
+            .then_no_commands()
+            .then_state(|m| m.checked_out)
+            .into_machine();
+
+        assert_eq!(machine.skus, ["apple", "pear"]);
+    }
+
+    #[test]
+    fn test_machine_tester_collects_several_whens() {
+        MachineTester::new(CartMachine::default())
+            .when(CartEvent::Added("apple"))
+            .when(CartEvent::CheckedOut)
+            .then_commands([CartCommand::Charge { items: 1 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "-         items: 2,\n+         items: 1,")]
+    fn test_machine_tester_mismatch_shows_diff() {
+        MachineTester::new(CartMachine::default())
+            .given([CartEvent::Added("apple")])
+            .when(CartEvent::CheckedOut)
+            .then_commands([CartCommand::Charge { items: 2 }]);
+    }
+
+    #[test]
+    fn test_diff_marks_added_and_removed_lines() {
+        assert_eq!(diff("a\nb\nc", "a\nc\nd"), "  a\n- b\n  c\n+ d\n");
+    }
+}
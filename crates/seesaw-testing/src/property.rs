@@ -0,0 +1,321 @@
+//! Property-based machine testing (`proptest` feature).
+//!
+//! [`MachineProperty`] feeds randomly generated event sequences to a fresh
+//! machine and checks an invariant after every decision. When the invariant
+//! fails, proptest shrinks the sequence to the shortest one that still fails.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use proptest::prelude::*;
+//! use seesaw_testing::MachineProperty;
+//!
+//! let deck_events = prop_oneof![
+//!     (0..4u32).prop_map(|deck_id| BakeEvent::DeckRequested { deck_id }),
+//!     (0..4u32).prop_map(|deck_id| BakeEvent::DeckReleased { deck_id }),
+//! ];
+//!
+//! MachineProperty::new(BakeMachine::default, deck_events)
+//!     .with_max_events(50)
+//!     .assert_holds(|_machine, trace| {
+//!         let mut loaded = HashSet::new();
+//!         for command in trace.commands() {
+//!             if let BakeCommand::SetupLoaf { deck_id } = command {
+//!                 if !loaded.insert(*deck_id) {
+//!                     return Err(format!("two SetupLoaf commands for deck {}", deck_id));
+//!                 }
+//!             }
+//!         }
+//!         Ok(())
+//!     });
+//! ```
+
+use std::fmt::Debug;
+
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::strategy::{BoxedStrategy, Strategy};
+use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+
+use seesaw_core::Machine;
+
+/// Default number of generated sequences.
+const DEFAULT_CASES: u32 = 256;
+
+/// Default maximum sequence length.
+const DEFAULT_MAX_EVENTS: usize = 32;
+
+/// Events fed to a machine so far and the command each one produced.
+#[derive(Debug, Clone)]
+pub struct Trace<E, C> {
+    steps: Vec<(E, Option<C>)>,
+}
+
+impl<E, C> Trace<E, C> {
+    /// The (event, command) pairs, in order.
+    pub fn steps(&self) -> &[(E, Option<C>)] {
+        &self.steps
+    }
+
+    /// The events, in order.
+    pub fn events(&self) -> impl Iterator<Item = &E> {
+        self.steps.iter().map(|(event, _)| event)
+    }
+
+    /// The commands produced, in order.
+    pub fn commands(&self) -> impl Iterator<Item = &C> {
+        self.steps
+            .iter()
+            .filter_map(|(_, command)| command.as_ref())
+    }
+
+    /// The most recent step.
+    pub fn last(&self) -> Option<&(E, Option<C>)> {
+        self.steps.last()
+    }
+
+    /// Number of events fed.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Check if no events were fed.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// A minimal event sequence that breaks the invariant.
+#[derive(Debug, Clone)]
+pub struct PropertyFailure<E> {
+    /// The invariant's error message, or proptest's reason for aborting.
+    pub message: String,
+    /// The shrunk failing sequence. Empty if the run aborted.
+    pub events: Vec<E>,
+}
+
+impl<E: Debug> std::fmt::Display for PropertyFailure<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invariant violated: {}\nminimal failing sequence ({} events): {:#?}",
+            self.message,
+            self.events.len(),
+            self.events
+        )
+    }
+}
+
+/// Checks an invariant over randomly generated event sequences.
+pub struct MachineProperty<M: Machine> {
+    make: Box<dyn Fn() -> M>,
+    events: BoxedStrategy<M::Event>,
+    cases: u32,
+    max_events: usize,
+}
+
+impl<M> MachineProperty<M>
+where
+    M: Machine,
+    M::Event: Clone + Debug,
+    M::Command: Clone + Debug,
+{
+    /// Create a property over machines built by `make`, with events drawn
+    /// from `events`.
+    pub fn new<S>(make: impl Fn() -> M + 'static, events: S) -> Self
+    where
+        S: Strategy<Value = M::Event> + 'static,
+    {
+        Self {
+            make: Box::new(make),
+            events: events.boxed(),
+            cases: DEFAULT_CASES,
+            max_events: DEFAULT_MAX_EVENTS,
+        }
+    }
+
+    /// Create a property with events drawn from `M::Event`'s `Arbitrary` impl.
+    pub fn arbitrary(make: impl Fn() -> M + 'static) -> Self
+    where
+        M::Event: Arbitrary,
+    {
+        Self::new(make, any::<M::Event>())
+    }
+
+    /// Set the number of sequences to generate (default 256).
+    pub fn with_cases(mut self, cases: u32) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Set the maximum sequence length (default 32).
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    /// Run the property, returning the minimal failing sequence if the
+    /// invariant is ever violated.
+    ///
+    /// The invariant runs after every decision with the machine and the
+    /// trace so far.
+    pub fn check<F>(&self, invariant: F) -> Result<(), PropertyFailure<M::Event>>
+    where
+        F: Fn(&M, &Trace<M::Event, M::Command>) -> Result<(), String>,
+    {
+        let mut runner = TestRunner::new(Config::with_cases(self.cases));
+        let sequences = proptest::collection::vec(self.events.clone(), 0..=self.max_events);
+
+        let result = runner.run(&sequences, |events| {
+            let mut machine = (self.make)();
+            let mut trace = Trace { steps: Vec::new() };
+            for event in events {
+                let command = machine.decide(&event);
+                trace.steps.push((event, command));
+                invariant(&machine, &trace).map_err(|message| {
+                    TestCaseError::fail(format!("{} (after event {})", message, trace.len()))
+                })?;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(TestError::Fail(reason, events)) => Err(PropertyFailure {
+                message: reason.message().to_string(),
+                events,
+            }),
+            Err(TestError::Abort(reason)) => Err(PropertyFailure {
+                message: format!("aborted: {}", reason.message()),
+                events: Vec::new(),
+            }),
+        }
+    }
+
+    /// Run the property and panic with the minimal failing sequence if the
+    /// invariant is ever violated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invariant returns an error for any generated sequence.
+    pub fn assert_holds<F>(&self, invariant: F)
+    where
+        F: Fn(&M, &Trace<M::Event, M::Command>) -> Result<(), String>,
+    {
+        if let Err(failure) = self.check(invariant) {
+            panic!("{}", failure);
+        }
+    }
+}
+
+impl<M: Machine> std::fmt::Debug for MachineProperty<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MachineProperty")
+            .field("cases", &self.cases)
+            .field("max_events", &self.max_events)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use seesaw_core::Command;
+    use std::collections::HashSet;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum BakeEvent {
+        DeckRequested { deck_id: u32 },
+        DeckReleased { deck_id: u32 },
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum BakeCommand {
+        SetupLoaf { deck_id: u32 },
+    }
+    impl Command for BakeCommand {}
+
+    /// Loads a deck on request; `forget_release` makes it lose track of
+    /// loaded decks on release, so a second request loads again.
+    #[derive(Default)]
+    struct BakeMachine {
+        loaded: HashSet<u32>,
+        forget_release: bool,
+    }
+
+    impl Machine for BakeMachine {
+        type Event = BakeEvent;
+        type Command = BakeCommand;
+
+        fn decide(&mut self, event: &BakeEvent) -> Option<BakeCommand> {
+            match event {
+                BakeEvent::DeckRequested { deck_id } if self.loaded.insert(*deck_id) => {
+                    Some(BakeCommand::SetupLoaf { deck_id: *deck_id })
+                }
+                BakeEvent::DeckRequested { .. } => None,
+                BakeEvent::DeckReleased { deck_id } => {
+                    if self.forget_release {
+                        self.loaded.remove(deck_id);
+                    }
+                    None
+                }
+            }
+        }
+    }
+
+    fn deck_events() -> impl Strategy<Value = BakeEvent> {
+        prop_oneof![
+            (0..3u32).prop_map(|deck_id| BakeEvent::DeckRequested { deck_id }),
+            (0..3u32).prop_map(|deck_id| BakeEvent::DeckReleased { deck_id }),
+        ]
+    }
+
+    fn one_setup_per_deck(
+        _machine: &BakeMachine,
+        trace: &Trace<BakeEvent, BakeCommand>,
+    ) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for BakeCommand::SetupLoaf { deck_id } in trace.commands() {
+            if !seen.insert(*deck_id) {
+                return Err(format!("two SetupLoaf commands for deck {}", deck_id));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_property_holds() {
+        MachineProperty::new(BakeMachine::default, deck_events())
+            .with_cases(64)
+            .assert_holds(one_setup_per_deck);
+    }
+
+    #[test]
+    fn test_property_failure_is_shrunk() {
+        let failure = MachineProperty::new(
+            || BakeMachine {
+                forget_release: true,
+                ..Default::default()
+            },
+            deck_events(),
+        )
+        .check(one_setup_per_deck)
+        .unwrap_err();
+
+        assert!(failure.message.contains("two SetupLoaf commands"));
+        // request, release, request on the same deck is the shortest failure
+        assert_eq!(failure.events.len(), 3);
+        let deck_id = match failure.events[0] {
+            BakeEvent::DeckRequested { deck_id } => deck_id,
+            ref other => panic!("unexpected first event {:?}", other),
+        };
+        assert_eq!(
+            failure.events,
+            vec![
+                BakeEvent::DeckRequested { deck_id },
+                BakeEvent::DeckReleased { deck_id },
+                BakeEvent::DeckRequested { deck_id },
+            ]
+        );
+    }
+}
@@ -0,0 +1,621 @@
+//! Scenario recording and replay for regression-testing machines.
+//!
+//! A [`ScenarioRecorder`] is machine middleware that captures every decision
+//! made during a real run - which machine saw which event and what it
+//! decided - as an ordered, JSON-serializable [`Scenario`]. A
+//! [`ScenarioReplayer`] later feeds the recorded events back into fresh
+//! machines and checks they still decide the same commands.
+//!
+//! # Recording
+//!
+//! ```ignore
+//! let recorder = ScenarioRecorder::new()
+//!     .with_event::<OrderEvent>()
+//!     .with_command::<OrderCommand>();
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine_middleware(recorder.clone())
+//!     .with_machine(OrderMachine::default())
+//!     .build();
+//!
+//! // ... drive the engine ...
+//!
+//! std::fs::write("tests/scenarios/checkout.json", recorder.scenario().to_json()?)?;
+//! ```
+//!
+//! # Replaying
+//!
+//! ```ignore
+//! let scenario = Scenario::from_json(include_str!("scenarios/checkout.json"))?;
+//!
+//! ScenarioReplayer::new(&scenario)
+//!     .with_event::<OrderEvent>()
+//!     .with_command::<OrderCommand>()
+//!     .with_machine(OrderMachine::default())
+//!     .assert_replays();
+//! ```
+//!
+//! Machines are matched by type name. Use
+//! [`with_machine_as`](ScenarioReplayer::with_machine_as) after renaming a
+//! machine. Events and commands must be registered with `with_event` /
+//! `with_command` on both sides; a command of an unregistered type is
+//! recorded without payload and only its type is compared.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use seesaw_core::{
+    AnyCommand, Decision, DecisionContext, DecisionOutcome, Machine, MachineMiddleware,
+};
+
+/// Serializes a type-erased value of a registered type.
+type EncodeFn = fn(&dyn Any) -> serde_json::Value;
+
+/// Deserializes a recorded event back into its type.
+type DecodeFn = fn(&serde_json::Value) -> Result<Box<dyn Any>>;
+
+/// `decide` of a replay machine, taking a type-erased event.
+type DecideFn = Box<dyn FnMut(&dyn Any) -> Option<Box<dyn AnyCommand>>>;
+
+fn encode<T: Serialize + 'static>(value: &dyn Any) -> serde_json::Value {
+    value
+        .downcast_ref::<T>()
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+fn decode<T: DeserializeOwned + 'static>(value: &serde_json::Value) -> Result<Box<dyn Any>> {
+    Ok(Box::new(serde_json::from_value::<T>(value.clone())?))
+}
+
+// =============================================================================
+// Scenario
+// =============================================================================
+
+/// An ordered trace of machine decisions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Decisions in the order the runtime made them.
+    pub decisions: Vec<RecordedDecision>,
+}
+
+impl Scenario {
+    /// Serialize the scenario as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a scenario from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Number of recorded decisions.
+    pub fn len(&self) -> usize {
+        self.decisions.len()
+    }
+
+    /// Check if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty()
+    }
+}
+
+/// One machine deciding on one event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedDecision {
+    /// Type name of the machine.
+    pub machine: String,
+    /// Type name of the event.
+    pub event_type: String,
+    /// The serialized event (`null` if its type was not registered).
+    pub event: serde_json::Value,
+    /// What the machine decided.
+    pub outcome: RecordedOutcome,
+}
+
+/// What a machine decided, in serializable form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    /// The machine emitted a command.
+    Command {
+        /// Type name of the command.
+        command_type: String,
+        /// The serialized command (`null` if its type was not registered).
+        command: serde_json::Value,
+    },
+    /// The machine emitted nothing.
+    NoCommand,
+    /// `decide` panicked.
+    Panicked {
+        /// The panic message.
+        message: String,
+    },
+}
+
+impl RecordedOutcome {
+    /// Whether `actual` matches this recorded outcome. A command recorded
+    /// without payload only has its type compared.
+    fn matches(&self, actual: &RecordedOutcome) -> bool {
+        match (self, actual) {
+            (
+                RecordedOutcome::Command {
+                    command_type,
+                    command: serde_json::Value::Null,
+                },
+                RecordedOutcome::Command {
+                    command_type: actual_type,
+                    ..
+                },
+            ) => command_type == actual_type,
+            _ => self == actual,
+        }
+    }
+}
+
+// =============================================================================
+// Recorder
+// =============================================================================
+
+/// Machine middleware that records every decision into a [`Scenario`].
+///
+/// Register event and command types before handing a clone to the engine;
+/// clones share the recorded decisions but not later registrations.
+#[derive(Clone, Default)]
+pub struct ScenarioRecorder {
+    events: HashMap<TypeId, EncodeFn>,
+    commands: HashMap<TypeId, EncodeFn>,
+    decisions: Arc<Mutex<Vec<RecordedDecision>>>,
+}
+
+impl ScenarioRecorder {
+    /// Create a new recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the payload of events of type `E`.
+    pub fn with_event<E: Serialize + 'static>(mut self) -> Self {
+        self.events.insert(TypeId::of::<E>(), encode::<E>);
+        self
+    }
+
+    /// Record the payload of commands of type `C`.
+    pub fn with_command<C: Serialize + 'static>(mut self) -> Self {
+        self.commands.insert(TypeId::of::<C>(), encode::<C>);
+        self
+    }
+
+    /// The decisions recorded so far.
+    pub fn scenario(&self) -> Scenario {
+        Scenario {
+            decisions: self.decisions.lock().unwrap().clone(),
+        }
+    }
+
+    /// Discard everything recorded so far.
+    pub fn clear(&self) {
+        self.decisions.lock().unwrap().clear();
+    }
+}
+
+impl MachineMiddleware for ScenarioRecorder {
+    fn after_decide(&self, ctx: &DecisionContext<'_>, decision: &Decision<'_>) {
+        let event = ctx.event();
+        let event = self
+            .events
+            .get(&event.type_id())
+            .map_or(serde_json::Value::Null, |encode| encode(event));
+        let outcome = match decision.outcome() {
+            DecisionOutcome::Command(command) => command_outcome(&self.commands, ctx, *command),
+            DecisionOutcome::NoCommand => RecordedOutcome::NoCommand,
+            DecisionOutcome::Panicked(message) => RecordedOutcome::Panicked {
+                message: message.to_string(),
+            },
+        };
+
+        self.decisions.lock().unwrap().push(RecordedDecision {
+            machine: ctx.machine().to_string(),
+            event_type: ctx.event_type().to_string(),
+            event,
+            outcome,
+        });
+    }
+}
+
+impl std::fmt::Debug for ScenarioRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScenarioRecorder")
+            .field("events", &self.events.len())
+            .field("commands", &self.commands.len())
+            .field("decisions", &self.decisions.lock().unwrap().len())
+            .finish()
+    }
+}
+
+fn command_outcome(
+    commands: &HashMap<TypeId, EncodeFn>,
+    ctx: &DecisionContext<'_>,
+    command: &dyn AnyCommand,
+) -> RecordedOutcome {
+    RecordedOutcome::Command {
+        command_type: ctx.command_type().to_string(),
+        command: commands
+            .get(&command.command_type_id())
+            .map_or(serde_json::Value::Null, |encode| encode(command.as_any())),
+    }
+}
+
+// =============================================================================
+// Replayer
+// =============================================================================
+
+/// A replayed decision that differs from the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioMismatch {
+    /// Index of the decision in the scenario.
+    pub index: usize,
+    /// Type name of the machine, as recorded.
+    pub machine: String,
+    /// The recorded event.
+    pub event: serde_json::Value,
+    /// What the machine decided when recorded.
+    pub expected: RecordedOutcome,
+    /// What the machine decided on replay.
+    pub actual: RecordedOutcome,
+}
+
+/// Result of replaying a scenario.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScenarioReport {
+    /// Number of decisions replayed.
+    pub decisions: usize,
+    /// Decisions that came out differently.
+    pub mismatches: Vec<ScenarioMismatch>,
+}
+
+impl ScenarioReport {
+    /// Whether every decision matched the recording.
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// A machine registered for replay.
+struct ReplayMachine {
+    command_type: &'static str,
+    decide: DecideFn,
+}
+
+/// Replays a [`Scenario`] against fresh machines.
+///
+/// Each machine sees exactly the events it saw when recorded, in the same
+/// order, so a deterministic machine reproduces its decisions.
+pub struct ScenarioReplayer<'a> {
+    scenario: &'a Scenario,
+    events: HashMap<String, DecodeFn>,
+    commands: HashMap<TypeId, EncodeFn>,
+    machines: HashMap<String, ReplayMachine>,
+}
+
+impl<'a> ScenarioReplayer<'a> {
+    /// Create a replayer for `scenario`.
+    pub fn new(scenario: &'a Scenario) -> Self {
+        Self {
+            scenario,
+            events: HashMap::new(),
+            commands: HashMap::new(),
+            machines: HashMap::new(),
+        }
+    }
+
+    /// Decode recorded events of type `E`.
+    pub fn with_event<E: DeserializeOwned + 'static>(mut self) -> Self {
+        self.events
+            .insert(std::any::type_name::<E>().to_string(), decode::<E>);
+        self
+    }
+
+    /// Serialize replayed commands of type `C` for comparison.
+    pub fn with_command<C: Serialize + 'static>(mut self) -> Self {
+        self.commands.insert(TypeId::of::<C>(), encode::<C>);
+        self
+    }
+
+    /// Replay the decisions recorded for machines of type `M`.
+    pub fn with_machine<M: Machine>(self, machine: M) -> Self {
+        self.with_machine_as(std::any::type_name::<M>(), machine)
+    }
+
+    /// Replay the decisions recorded for the machine named `recorded` -
+    /// for example the type name a machine had before it was renamed.
+    pub fn with_machine_as<M: Machine>(mut self, recorded: &str, mut machine: M) -> Self {
+        let decide: DecideFn = Box::new(move |event| {
+            event
+                .downcast_ref::<M::Event>()
+                .and_then(|event| machine.decide(event))
+                .map(|command| Box::new(command) as Box<dyn AnyCommand>)
+        });
+        self.machines.insert(
+            recorded.to_string(),
+            ReplayMachine {
+                command_type: std::any::type_name::<M::Command>(),
+                decide,
+            },
+        );
+        self
+    }
+
+    /// Replay every decision and report the ones that differ.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a recorded machine has no replacement registered,
+    /// or a recorded event cannot be decoded.
+    pub fn replay(mut self) -> Result<ScenarioReport> {
+        let mut report = ScenarioReport::default();
+
+        for (index, recorded) in self.scenario.decisions.iter().enumerate() {
+            let machine = self.machines.get_mut(&recorded.machine).ok_or_else(|| {
+                anyhow!(
+                    "no machine registered for {} (decision {})",
+                    recorded.machine,
+                    index
+                )
+            })?;
+            if recorded.event.is_null() {
+                bail!(
+                    "event of type {} was recorded without payload (decision {}); \
+                     register it with ScenarioRecorder::with_event",
+                    recorded.event_type,
+                    index
+                );
+            }
+            let decode = self.events.get(&recorded.event_type).ok_or_else(|| {
+                anyhow!(
+                    "no decoder registered for event type {}",
+                    recorded.event_type
+                )
+            })?;
+            let event = decode(&recorded.event)?;
+
+            let actual = match catch_unwind(AssertUnwindSafe(|| (machine.decide)(event.as_ref()))) {
+                Ok(Some(command)) => RecordedOutcome::Command {
+                    command_type: machine.command_type.to_string(),
+                    command: self
+                        .commands
+                        .get(&command.command_type_id())
+                        .map_or(serde_json::Value::Null, |encode| encode(command.as_any())),
+                },
+                Ok(None) => RecordedOutcome::NoCommand,
+                Err(panic) => RecordedOutcome::Panicked {
+                    message: panic_message(panic.as_ref()),
+                },
+            };
+
+            if !recorded.outcome.matches(&actual) {
+                report.mismatches.push(ScenarioMismatch {
+                    index,
+                    machine: recorded.machine.clone(),
+                    event: recorded.event.clone(),
+                    expected: recorded.outcome.clone(),
+                    actual,
+                });
+            }
+            report.decisions += 1;
+        }
+
+        Ok(report)
+    }
+
+    /// Replay every decision and panic if any differs.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the first mismatches, or if the scenario cannot be replayed.
+    pub fn assert_replays(self) {
+        let report = self
+            .replay()
+            .unwrap_or_else(|e| panic!("scenario could not be replayed: {e}"));
+        if let Some(first) = report.mismatches.first() {
+            panic!(
+                "{} of {} decisions differ from the recording. First at decision {} ({}):\n  \
+                 event:    {}\n  expected: {:?}\n  actual:   {:?}",
+                report.mismatches.len(),
+                report.decisions,
+                first.index,
+                first.machine,
+                first.event,
+                first.expected,
+                first.actual
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for ScenarioReplayer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScenarioReplayer")
+            .field("decisions", &self.scenario.len())
+            .field("machines", &self.machines.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seesaw_core::{Command, Dispatcher, Effect, EffectContext, EventBus, Runtime};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    enum CartEvent {
+        Added { sku: String },
+        CheckedOut,
+        Paid,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct ChargeCart {
+        items: usize,
+    }
+    impl Command for ChargeCart {}
+
+    #[derive(Default)]
+    struct CartMachine {
+        items: usize,
+    }
+
+    impl Machine for CartMachine {
+        type Event = CartEvent;
+        type Command = ChargeCart;
+
+        fn decide(&mut self, event: &CartEvent) -> Option<ChargeCart> {
+            match event {
+                CartEvent::Added { .. } => {
+                    self.items += 1;
+                    None
+                }
+                CartEvent::CheckedOut => Some(ChargeCart { items: self.items }),
+                CartEvent::Paid => None,
+            }
+        }
+    }
+
+    /// A "refactor" that forgets to count duplicates.
+    #[derive(Default)]
+    struct UniqueCartMachine {
+        skus: Vec<String>,
+    }
+
+    impl Machine for UniqueCartMachine {
+        type Event = CartEvent;
+        type Command = ChargeCart;
+
+        fn decide(&mut self, event: &CartEvent) -> Option<ChargeCart> {
+            match event {
+                CartEvent::Added { sku } => {
+                    if !self.skus.contains(sku) {
+                        self.skus.push(sku.clone());
+                    }
+                    None
+                }
+                CartEvent::CheckedOut => Some(ChargeCart {
+                    items: self.skus.len(),
+                }),
+                CartEvent::Paid => None,
+            }
+        }
+    }
+
+    struct ChargeEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<ChargeCart, ()> for ChargeEffect {
+        type Event = CartEvent;
+
+        async fn execute(&self, _cmd: ChargeCart, _ctx: EffectContext<()>) -> Result<CartEvent> {
+            Ok(CartEvent::Paid)
+        }
+    }
+
+    async fn record_checkout() -> Scenario {
+        let recorder = ScenarioRecorder::new()
+            .with_event::<CartEvent>()
+            .with_command::<ChargeCart>();
+
+        let bus = EventBus::new();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<ChargeCart, _>(ChargeEffect);
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine_middleware(recorder.clone())
+            .with_machine(CartMachine::default());
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        for sku in ["apple", "apple", "pear"] {
+            bus.emit(CartEvent::Added {
+                sku: sku.to_string(),
+            });
+        }
+        bus.emit(CartEvent::CheckedOut);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        recorder.scenario()
+    }
+
+    fn replayer(scenario: &Scenario) -> ScenarioReplayer<'_> {
+        ScenarioReplayer::new(scenario)
+            .with_event::<CartEvent>()
+            .with_command::<ChargeCart>()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_scenario_survives_json_and_replays() {
+        let scenario = record_checkout().await;
+
+        // 3 adds, checkout, and the Paid event from the effect
+        assert_eq!(scenario.len(), 5);
+        assert_eq!(
+            scenario.decisions[3].outcome,
+            RecordedOutcome::Command {
+                command_type: std::any::type_name::<ChargeCart>().to_string(),
+                command: serde_json::json!({ "items": 3 }),
+            }
+        );
+
+        let scenario = Scenario::from_json(&scenario.to_json().unwrap()).unwrap();
+        replayer(&scenario)
+            .with_machine(CartMachine::default())
+            .assert_replays();
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_changed_decisions() {
+        let scenario = record_checkout().await;
+
+        let report = replayer(&scenario)
+            .with_machine_as(
+                std::any::type_name::<CartMachine>(),
+                UniqueCartMachine::default(),
+            )
+            .replay()
+            .unwrap();
+
+        assert_eq!(report.decisions, 5);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].index, 3);
+        assert_eq!(
+            report.mismatches[0].actual,
+            RecordedOutcome::Command {
+                command_type: std::any::type_name::<ChargeCart>().to_string(),
+                command: serde_json::json!({ "items": 2 }),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_machine_fails() {
+        let scenario = record_checkout().await;
+
+        let err = replayer(&scenario).replay().unwrap_err();
+
+        assert!(err.to_string().contains("no machine registered"));
+    }
+}
@@ -0,0 +1,727 @@
+//! Deterministic simulation of a running engine on virtual time.
+//!
+//! A [`Simulation`] runs the real [`Runtime`] and [`Dispatcher`] on a single
+//! thread with Tokio's clock paused. Time only moves when the test calls
+//! [`advance`](Simulation::advance), and background and scheduled commands
+//! are executed by an in-memory job queue when their time comes, so tests can
+//! assert on hours of workflow without sleeping or racing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw_testing::SimulationBuilder;
+//!
+//! #[test]
+//! fn test_reminder_fires_after_five_minutes() {
+//!     SimulationBuilder::new(deps)
+//!         .with_machine(ReminderMachine::default())
+//!         .with_effects(|d| d.with_effect::<SendReminder, _>(SendReminderEffect))
+//!         .with_job::<SendReminder>("reminder:send", vec![1])
+//!         .run(|sim| async move {
+//!             sim.emit(UserEvent::SignedUp { user_id }).await;
+//!             assert_eq!(sim.pending_jobs(), 1);
+//!
+//!             sim.advance(Duration::from_secs(5 * 60)).await;
+//!
+//!             assert_eq!(sim.events::<UserEvent>().len(), 2);
+//!             sim.machine(|m: &ReminderMachine| assert!(m.reminded.contains(&user_id)));
+//!         });
+//! }
+//! ```
+//!
+//! # Virtual Time
+//!
+//! [`run`](SimulationBuilder::run) creates its own paused current-thread
+//! Tokio runtime. [`start`](SimulationBuilder::start) can be used instead
+//! from `#[tokio::test(start_paused = true)]`.
+//!
+//! Only Tokio's clock is virtual. A command scheduled for
+//! `Utc::now() + 5 minutes` fires after five virtual minutes, because the
+//! queue converts `run_at` into a delay when the command is scheduled, but
+//! `Utc::now()` itself keeps reporting wall-clock time. Use
+//! [`Simulation::now`] for the virtual wall clock.
+//!
+//! # Quiescence
+//!
+//! [`emit`](Simulation::emit) and [`advance`](Simulation::advance) return once
+//! the engine is idle: no events waiting on the bus, no job executing, and
+//! no progress for several scheduler rounds. Effects waiting on a timer count
+//! as idle; they resume when time is advanced past it.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use seesaw_core::{
+    ClaimedJob, Command, CommandRegistry, Dispatcher, Event, EventBus, EventEnvelope, JobQueue,
+    JobSpec, Machine, Runtime,
+};
+
+use crate::JobStatus;
+
+/// Scheduler rounds without progress before the engine is considered idle.
+const QUIET_ROUNDS: usize = 32;
+
+/// Scheduler rounds after which [`Simulation::settle`] gives up.
+const MAX_SETTLE_ROUNDS: usize = 100_000;
+
+/// Registers effects on a dispatcher. Applied to both the runtime's dispatcher
+/// and the job executor's.
+type EffectsStep<D> = Box<dyn Fn(Dispatcher<D>) -> Dispatcher<D> + Send>;
+
+/// Deferred runtime configuration, applied by `start`.
+type RuntimeStep<D> = Box<dyn FnOnce(Runtime<D>) -> Runtime<D> + Send>;
+
+// =============================================================================
+// Builder
+// =============================================================================
+
+/// Builder for a [`Simulation`].
+///
+/// Effects are registered through a closure rather than one by one, because
+/// the simulation builds two dispatchers from it: one inside the runtime for
+/// inline commands, and one in the job queue for background and scheduled
+/// commands.
+pub struct SimulationBuilder<D> {
+    deps: Arc<D>,
+    bus: EventBus,
+    machines: Vec<RuntimeStep<D>>,
+    effects: Vec<EffectsStep<D>>,
+    registry: CommandRegistry,
+    probes: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    start_time: DateTime<Utc>,
+}
+
+impl<D: Send + Sync + 'static> SimulationBuilder<D> {
+    /// Create a new simulation builder with the given dependencies.
+    ///
+    /// The virtual wall clock starts at `2024-01-01T00:00:00Z`.
+    pub fn new(deps: D) -> Self {
+        Self {
+            deps: Arc::new(deps),
+            bus: EventBus::new(),
+            machines: Vec::new(),
+            effects: Vec::new(),
+            registry: CommandRegistry::new(),
+            probes: HashMap::new(),
+            start_time: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    /// Start the virtual wall clock at `start_time` instead.
+    pub fn starting_at(mut self, start_time: DateTime<Utc>) -> Self {
+        self.start_time = start_time;
+        self
+    }
+
+    /// Use an existing event bus instead of creating a new one.
+    pub fn with_bus(mut self, bus: EventBus) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// Add a machine to the simulation.
+    ///
+    /// Its state can be inspected while the simulation runs with
+    /// [`Simulation::machine`]. Registering two machines of the same type
+    /// makes only the last one inspectable.
+    pub fn with_machine<M: Machine>(mut self, machine: M) -> Self {
+        let shared = Arc::new(Mutex::new(machine));
+        self.probes.insert(TypeId::of::<M>(), shared.clone());
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_machine(Probed(shared))
+        }));
+        self
+    }
+
+    /// Register effects, e.g. `|d| d.with_effect::<Ship, _>(ShipEffect)`.
+    ///
+    /// The closure may be called more than once.
+    pub fn with_effects<F>(mut self, register: F) -> Self
+    where
+        F: Fn(Dispatcher<D>) -> Dispatcher<D> + Send + 'static,
+    {
+        self.effects.push(Box::new(register));
+        self
+    }
+
+    /// Register a background or scheduled command so the job queue can
+    /// execute it.
+    ///
+    /// See [`CommandRegistry::register`].
+    pub fn with_job<C>(mut self, job_type: &'static str, supported_versions: Vec<i32>) -> Self
+    where
+        C: Command + DeserializeOwned + 'static,
+    {
+        self.registry.register::<C>(job_type, supported_versions);
+        self
+    }
+
+    /// Start the simulation on the current Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless called from a current-thread runtime with the clock
+    /// paused, e.g. `#[tokio::test(start_paused = true)]`.
+    pub async fn start(self) -> Simulation<D> {
+        // Fails fast with "time is not frozen" instead of really sleeping later
+        tokio::time::advance(Duration::ZERO).await;
+
+        let clock = SimClock {
+            origin: tokio::time::Instant::now(),
+            start_time: self.start_time,
+        };
+
+        let register = |dispatcher: Dispatcher<D>| {
+            self.effects
+                .iter()
+                .fold(dispatcher, |dispatcher, register| register(dispatcher))
+        };
+
+        let jobs = Arc::new(SimJobs {
+            registry: self.registry,
+            dispatcher: register(Dispatcher::from_arc(self.deps.clone(), self.bus.clone())),
+            clock,
+            log: Mutex::new(Vec::new()),
+            busy: AtomicUsize::new(0),
+        });
+        let dispatcher = register(Dispatcher::from_arc_with_job_queue(
+            self.deps,
+            self.bus.clone(),
+            Arc::new(SimJobQueue(jobs.clone())),
+        ));
+
+        let mut runtime = Runtime::new(dispatcher, self.bus.clone());
+        for add_machine in self.machines {
+            runtime = add_machine(runtime);
+        }
+
+        let receiver = self.bus.subscribe();
+        let subscribers = self.bus.subscriber_count();
+        let handle = tokio::spawn(runtime.run());
+
+        // Wait for the runtime to subscribe so the first emit isn't missed
+        while self.bus.subscriber_count() <= subscribers {
+            tokio::task::yield_now().await;
+        }
+
+        Simulation {
+            bus: self.bus,
+            jobs,
+            probes: self.probes,
+            receiver: Mutex::new(receiver),
+            events: Mutex::new(Vec::new()),
+            handle,
+        }
+    }
+
+    /// Run `test` against the simulation on a fresh, paused current-thread
+    /// Tokio runtime, returning its result.
+    pub fn run<F, Fut, T>(self, test: F) -> T
+    where
+        F: FnOnce(Simulation<D>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .expect("failed to build simulation runtime")
+            .block_on(async move { test(self.start().await).await })
+    }
+}
+
+impl<D> std::fmt::Debug for SimulationBuilder<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimulationBuilder")
+            .field("machines", &self.machines.len())
+            .field("effects", &self.effects.len())
+            .field("registry", &self.registry)
+            .field("start_time", &self.start_time)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Machine wrapper whose state stays reachable from the test.
+struct Probed<M>(Arc<Mutex<M>>);
+
+impl<M: Machine> Machine for Probed<M> {
+    type Event = M::Event;
+    type Command = M::Command;
+
+    fn decide(&mut self, event: &M::Event) -> Option<M::Command> {
+        // A panicking decide poisons the lock; the runtime carries on, so do we
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .decide(event)
+    }
+}
+
+// =============================================================================
+// Simulation
+// =============================================================================
+
+/// A running engine on virtual time. Created by [`SimulationBuilder`].
+///
+/// Dropping the simulation stops the runtime.
+pub struct Simulation<D> {
+    bus: EventBus,
+    jobs: Arc<SimJobs<D>>,
+    probes: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    receiver: Mutex<broadcast::Receiver<EventEnvelope>>,
+    events: Mutex<Vec<EventEnvelope>>,
+    handle: JoinHandle<()>,
+}
+
+impl<D: Send + Sync + 'static> Simulation<D> {
+    /// Get the event bus.
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
+
+    /// Emit an event and wait until the engine is idle again.
+    pub async fn emit<E: Event>(&self, event: E) {
+        self.bus.emit(event);
+        self.settle().await;
+    }
+
+    /// Move virtual time forward by `duration`, running every timer and job
+    /// that comes due along the way, then wait until the engine is idle.
+    pub async fn advance(&self, duration: Duration) {
+        // With the clock paused, Tokio jumps from timer to timer, letting
+        // woken tasks run before moving on
+        tokio::time::sleep(duration).await;
+        self.settle().await;
+    }
+
+    /// Wait until the engine is idle without moving virtual time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the engine never goes idle, e.g. two machines and effects
+    /// feeding each other without end.
+    pub async fn settle(&self) {
+        let mut quiet = 0;
+        for _ in 0..MAX_SETTLE_ROUNDS {
+            tokio::task::yield_now().await;
+
+            let progressed = self.drain_events();
+            if progressed || self.jobs.busy.load(Ordering::Acquire) > 0 || self.bus.buffered() > 0 {
+                quiet = 0;
+            } else {
+                quiet += 1;
+                if quiet >= QUIET_ROUNDS {
+                    return;
+                }
+            }
+        }
+        panic!(
+            "simulation did not settle after {} rounds",
+            MAX_SETTLE_ROUNDS
+        );
+    }
+
+    /// Virtual time elapsed since the simulation started.
+    pub fn elapsed(&self) -> Duration {
+        self.jobs.clock.elapsed()
+    }
+
+    /// The virtual wall clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.jobs.clock.now()
+    }
+
+    /// Inspect the state of a machine registered with
+    /// [`with_machine`](SimulationBuilder::with_machine).
+    ///
+    /// # Panics
+    ///
+    /// Panics if no machine of type `M` was registered.
+    pub fn machine<M: Machine, R>(&self, inspect: impl FnOnce(&M) -> R) -> R {
+        let machine = self
+            .probes
+            .get(&TypeId::of::<M>())
+            .and_then(|probe| probe.clone().downcast::<Mutex<M>>().ok())
+            .unwrap_or_else(|| {
+                panic!(
+                    "machine {} is not registered with the simulation",
+                    std::any::type_name::<M>()
+                )
+            });
+        let guard = machine.lock().unwrap_or_else(PoisonError::into_inner);
+        inspect(&guard)
+    }
+
+    /// All events of type `E` seen on the bus so far, in emission order.
+    pub fn events<E: Event + Clone>(&self) -> Vec<E> {
+        self.drain_events();
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|envelope| envelope.payload.downcast_ref::<E>().cloned())
+            .collect()
+    }
+
+    /// All background and scheduled jobs, in the order they were queued.
+    pub fn jobs(&self) -> Vec<SimJob> {
+        self.jobs.log.lock().unwrap().clone()
+    }
+
+    /// All jobs of a specific type.
+    pub fn jobs_of_type(&self, job_type: &str) -> Vec<SimJob> {
+        self.jobs
+            .log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.job_type == job_type)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of jobs that have not run yet.
+    pub fn pending_jobs(&self) -> usize {
+        self.jobs
+            .log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.status == JobStatus::Pending)
+            .count()
+    }
+
+    /// Move newly received envelopes into the event log.
+    /// Returns whether there were any.
+    fn drain_events(&self) -> bool {
+        let mut receiver = self.receiver.lock().unwrap();
+        let mut events = self.events.lock().unwrap();
+        let before = events.len();
+        loop {
+            match receiver.try_recv() {
+                Ok(envelope) => events.push(envelope),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        events.len() > before
+    }
+}
+
+impl<D> Drop for Simulation<D> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl<D> std::fmt::Debug for Simulation<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Simulation")
+            .field("elapsed", &self.jobs.clock.elapsed())
+            .field("machines", &self.probes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Virtual Clock
+// =============================================================================
+
+/// Wall clock driven by Tokio's (paused) clock.
+#[derive(Debug, Clone, Copy)]
+struct SimClock {
+    origin: tokio::time::Instant,
+    start_time: DateTime<Utc>,
+}
+
+impl SimClock {
+    fn elapsed(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.after(Duration::ZERO)
+    }
+
+    /// Virtual wall-clock time `delay` from now.
+    fn after(&self, delay: Duration) -> DateTime<Utc> {
+        self.start_time + chrono::Duration::from_std(self.elapsed() + delay).unwrap_or_default()
+    }
+}
+
+// =============================================================================
+// Job Queue
+// =============================================================================
+
+/// A background or scheduled job handled by the simulation's job queue.
+#[derive(Debug, Clone)]
+pub struct SimJob {
+    /// The job ID (synthetic, generated by the queue).
+    pub id: Uuid,
+    /// The job type from the spec.
+    pub job_type: String,
+    /// The serialized command payload.
+    pub payload: serde_json::Value,
+    /// The full job specification.
+    pub spec: JobSpec,
+    /// Virtual time the job was queued.
+    pub enqueued_at: DateTime<Utc>,
+    /// Virtual time the job is due (None for immediate background jobs).
+    pub run_at: Option<DateTime<Utc>>,
+    /// `Pending`, `Succeeded` or `Failed`.
+    pub status: JobStatus,
+    /// Virtual time the job finished running.
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Error message if the job failed.
+    pub error: Option<String>,
+}
+
+/// State shared by the job queue and the simulation.
+struct SimJobs<D> {
+    registry: CommandRegistry,
+    /// Executes job commands inline; not connected to a job queue.
+    dispatcher: Dispatcher<D>,
+    clock: SimClock,
+    log: Mutex<Vec<SimJob>>,
+    /// Jobs due now that have not finished yet.
+    busy: AtomicUsize,
+}
+
+impl<D: Send + Sync + 'static> SimJobs<D> {
+    /// Record a job and run it after `delay` (immediately if `None`).
+    fn submit(
+        self: &Arc<Self>,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        delay: Option<Duration>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        let job = SimJob {
+            id,
+            job_type: spec.job_type.to_string(),
+            payload,
+            spec,
+            enqueued_at: self.clock.now(),
+            run_at: delay.map(|delay| self.clock.after(delay)),
+            status: JobStatus::Pending,
+            finished_at: None,
+            error: None,
+        };
+        let claimed = ClaimedJob {
+            id,
+            job_type: job.job_type.clone(),
+            payload: job.payload.clone(),
+            version: job.spec.version,
+            attempt: 1,
+            lease_token: Uuid::new_v4(),
+        };
+        self.log.lock().unwrap().push(job);
+
+        if delay.is_none() {
+            self.busy.fetch_add(1, Ordering::AcqRel);
+        }
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+                jobs.busy.fetch_add(1, Ordering::AcqRel);
+            }
+            let result = jobs.execute(&claimed).await;
+            jobs.finish(id, result);
+            jobs.busy.fetch_sub(1, Ordering::AcqRel);
+        });
+
+        id
+    }
+
+    async fn execute(&self, job: &ClaimedJob) -> Result<()> {
+        let command = self.registry.deserialize(job)?;
+        self.dispatcher.dispatch(vec![command]).await
+    }
+
+    fn finish(&self, id: Uuid, result: Result<()>) {
+        let mut log = self.log.lock().unwrap();
+        if let Some(job) = log.iter_mut().find(|job| job.id == id) {
+            job.finished_at = Some(self.clock.now());
+            match result {
+                Ok(()) => job.status = JobStatus::Succeeded,
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Job queue that runs jobs on virtual time.
+struct SimJobQueue<D>(Arc<SimJobs<D>>);
+
+#[async_trait::async_trait]
+impl<D: Send + Sync + 'static> JobQueue for SimJobQueue<D> {
+    async fn enqueue(&self, payload: serde_json::Value, spec: JobSpec) -> Result<Uuid> {
+        Ok(self.0.submit(payload, spec, None))
+    }
+
+    async fn schedule(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        // `run_at` was most likely computed from `Utc::now()`, so measure the
+        // delay against the real clock and apply it to the virtual one
+        let delay = (run_at - Utc::now()).to_std().unwrap_or_default();
+        // Round up to Tokio's timer resolution, which also absorbs the few
+        // microseconds since the machine computed `run_at`
+        let delay = Duration::from_millis(delay.as_nanos().div_ceil(1_000_000) as u64);
+        Ok(self.0.submit(payload, spec, Some(delay)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seesaw_core::{Effect, EffectContext, ExecutionMode};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum UserEvent {
+        SignedUp(u32),
+        Imported(u32),
+        ReminderSent(u32),
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SendReminder {
+        user_id: u32,
+        run_at: Option<DateTime<Utc>>,
+    }
+
+    impl Command for SendReminder {
+        fn execution_mode(&self) -> ExecutionMode {
+            match self.run_at {
+                Some(run_at) => ExecutionMode::Scheduled { run_at },
+                None => ExecutionMode::Background,
+            }
+        }
+
+        fn job_spec(&self) -> Option<JobSpec> {
+            Some(JobSpec::new("reminder:send"))
+        }
+
+        fn serialize_to_json(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self).ok()
+        }
+    }
+
+    #[derive(Default)]
+    struct ReminderMachine {
+        reminded: Vec<u32>,
+    }
+
+    impl Machine for ReminderMachine {
+        type Event = UserEvent;
+        type Command = SendReminder;
+
+        fn decide(&mut self, event: &UserEvent) -> Option<SendReminder> {
+            match event {
+                UserEvent::SignedUp(user_id) => Some(SendReminder {
+                    user_id: *user_id,
+                    run_at: Some(Utc::now() + chrono::Duration::minutes(5)),
+                }),
+                UserEvent::Imported(user_id) => Some(SendReminder {
+                    user_id: *user_id,
+                    run_at: None,
+                }),
+                UserEvent::ReminderSent(user_id) => {
+                    self.reminded.push(*user_id);
+                    None
+                }
+            }
+        }
+    }
+
+    struct ReminderEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<SendReminder, ()> for ReminderEffect {
+        type Event = UserEvent;
+
+        async fn execute(&self, cmd: SendReminder, _ctx: EffectContext<()>) -> Result<UserEvent> {
+            if cmd.user_id == 0 {
+                anyhow::bail!("no such user");
+            }
+            Ok(UserEvent::ReminderSent(cmd.user_id))
+        }
+    }
+
+    fn reminders() -> SimulationBuilder<()> {
+        SimulationBuilder::new(())
+            .with_machine(ReminderMachine::default())
+            .with_effects(|d| d.with_effect::<SendReminder, _>(ReminderEffect))
+            .with_job::<SendReminder>("reminder:send", vec![1])
+    }
+
+    #[test]
+    fn test_scheduled_command_fires_after_advancing() {
+        reminders().run(|sim| async move {
+            sim.emit(UserEvent::SignedUp(7)).await;
+            assert_eq!(sim.pending_jobs(), 1);
+
+            sim.advance(Duration::from_secs(4 * 60)).await;
+            assert_eq!(sim.pending_jobs(), 1);
+            sim.machine(|m: &ReminderMachine| assert!(m.reminded.is_empty()));
+
+            sim.advance(Duration::from_secs(60)).await;
+            assert_eq!(sim.pending_jobs(), 0);
+            assert_eq!(sim.elapsed(), Duration::from_secs(5 * 60));
+            assert_eq!(
+                sim.events::<UserEvent>(),
+                vec![UserEvent::SignedUp(7), UserEvent::ReminderSent(7)]
+            );
+            sim.machine(|m: &ReminderMachine| assert_eq!(m.reminded, vec![7]));
+
+            let job = &sim.jobs_of_type("reminder:send")[0];
+            assert_eq!(job.status, JobStatus::Succeeded);
+            assert_eq!(
+                job.run_at,
+                Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 5, 0).unwrap())
+            );
+        });
+    }
+
+    #[test]
+    fn test_background_command_runs_on_emit() {
+        reminders().run(|sim| async move {
+            sim.emit(UserEvent::Imported(3)).await;
+
+            assert_eq!(sim.elapsed(), Duration::ZERO);
+            assert_eq!(sim.jobs()[0].status, JobStatus::Succeeded);
+            sim.machine(|m: &ReminderMachine| assert_eq!(m.reminded, vec![3]));
+        });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_job_is_recorded() {
+        let sim = reminders().start().await;
+
+        sim.emit(UserEvent::Imported(0)).await;
+
+        let job = &sim.jobs()[0];
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.as_deref().unwrap().contains("no such user"));
+        sim.machine(|m: &ReminderMachine| assert!(m.reminded.is_empty()));
+    }
+}
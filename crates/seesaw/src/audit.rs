@@ -3,6 +3,9 @@
 //! This module provides tools to track which machines observe and emit commands
 //! in response to events. It's only active in debug builds and has zero production cost.
 //!
+//! For a forensics trail in production, enable the `audit` feature and give
+//! the runtime an `AuditSink` instead.
+//!
 //! # Purpose
 //!
 //! Auditing catches:
@@ -0,0 +1,503 @@
+//! Structured audit trail for release builds (`audit` feature).
+//!
+//! The debug [`AuditLog`](crate::audit::AuditLog) keeps recent events in
+//! memory and compiles out of release builds. With the `audit` feature
+//! enabled, a runtime given an [`AuditSink`] writes one [`AuditRecord`] per
+//! envelope instead: the event, what each machine decided, each effect's
+//! outcome, and how long it all took.
+//!
+//! Records are handed to a dedicated writer task through a bounded buffer
+//! and written in batches. A sink that cannot keep up loses records (with a
+//! warning) rather than stalling dispatch.
+//!
+//! Built-in sinks:
+//! - [`JsonLinesAuditSink`]: one JSON object per line, to stdout or a file
+//! - [`MemoryAuditSink`]: in-memory, for tests
+//!
+//! `seesaw-job-postgres` provides a Postgres sink behind its own `audit`
+//! feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_effect::<ChargeCommand, _>(ChargeEffect)
+//!     .with_audit_sink(Arc::new(JsonLinesAuditSink::file("audit.jsonl")?))
+//!     .build();
+//! ```
+
+use std::any::TypeId;
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::EventEnvelope;
+
+/// Number of records buffered between the runtime and the sink.
+const AUDIT_BUFFER: usize = 4096;
+
+/// Maximum number of records passed to one [`AuditSink::write`] call.
+const AUDIT_WRITE_BATCH: usize = 256;
+
+// =============================================================================
+// Records
+// =============================================================================
+
+/// Everything the runtime did with one envelope.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Correlation ID of the event, if it had one.
+    pub correlation_id: Option<Uuid>,
+    /// Type name of the event, or `None` if no machine handles it.
+    pub event_type: Option<&'static str>,
+    /// Causation depth of the event.
+    pub hops: u32,
+    /// When the runtime received the event.
+    pub received_at: DateTime<Utc>,
+    /// Whether the event was dropped as a loop instead of decided.
+    pub dropped: bool,
+    /// One entry per machine that handles the event, in registration order.
+    pub decisions: Vec<AuditDecision>,
+    /// One entry per command batch executed or queued for the event.
+    pub effects: Vec<AuditEffect>,
+    /// Time from receipt to the end of inline dispatch, in microseconds.
+    pub duration_us: u64,
+}
+
+/// What one machine decided for an event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditDecision {
+    /// Name of the machine.
+    pub machine: &'static str,
+    /// Type name of the command decided, or `None` if the machine stayed silent.
+    pub command_type: Option<&'static str>,
+    /// Panic message, if `decide` panicked.
+    pub panic: Option<String>,
+    /// How long `decide` took, in microseconds.
+    pub duration_us: u64,
+}
+
+/// How an audited command was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditExecution {
+    /// Executed by an inline effect.
+    Inline,
+    /// Handed to the job queue for a background worker.
+    Background,
+    /// Handed to the job queue to run later.
+    Scheduled,
+    /// Handed to the job queue to run periodically.
+    Recurring,
+}
+
+/// Outcome of one inline batch or job queue hand-off.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEffect {
+    /// Type name of the command.
+    pub command_type: &'static str,
+    /// How the command was executed.
+    pub execution: AuditExecution,
+    /// Number of commands in the batch.
+    pub commands: usize,
+    /// How long execution (or enqueueing) took, in microseconds.
+    pub duration_us: u64,
+    /// Error message, if execution (or enqueueing) failed.
+    pub error: Option<String>,
+}
+
+/// Collects an [`AuditRecord`] while the runtime processes an envelope.
+pub(crate) struct AuditRecordBuilder {
+    record: AuditRecord,
+    started: Instant,
+    /// Command type names of the inline batches decided for this event.
+    inline_types: Vec<(TypeId, &'static str)>,
+}
+
+impl AuditRecordBuilder {
+    pub(crate) fn new(envelope: &EventEnvelope) -> Self {
+        Self {
+            record: AuditRecord {
+                correlation_id: envelope.cid.is_some().then(|| envelope.cid.into_inner()),
+                event_type: None,
+                hops: envelope.hops,
+                received_at: Utc::now(),
+                dropped: false,
+                decisions: Vec::new(),
+                effects: Vec::new(),
+                duration_us: 0,
+            },
+            started: Instant::now(),
+            inline_types: Vec::new(),
+        }
+    }
+
+    /// Mark the event as dropped without being decided.
+    pub(crate) fn dropped(&mut self) {
+        self.record.dropped = true;
+    }
+
+    /// Record a machine's decision on the event.
+    pub(crate) fn decided(
+        &mut self,
+        machine: &'static str,
+        event_type: &'static str,
+        command_type: Option<&'static str>,
+        panic: Option<String>,
+        elapsed: Duration,
+    ) {
+        self.record.event_type.get_or_insert(event_type);
+        self.record.decisions.push(AuditDecision {
+            machine,
+            command_type,
+            panic,
+            duration_us: micros(elapsed),
+        });
+    }
+
+    /// Remember the name of an inline command type for its batch.
+    pub(crate) fn batched(&mut self, type_id: TypeId, command_type: &'static str) {
+        if !self.inline_types.iter().any(|(id, _)| *id == type_id) {
+            self.inline_types.push((type_id, command_type));
+        }
+    }
+
+    /// Record the result of executing an inline batch.
+    pub(crate) fn dispatched(
+        &mut self,
+        type_id: TypeId,
+        commands: usize,
+        elapsed: Duration,
+        error: Option<String>,
+    ) {
+        let command_type = self
+            .inline_types
+            .iter()
+            .find(|(id, _)| *id == type_id)
+            .map_or("unknown", |(_, name)| *name);
+        self.effect(
+            command_type,
+            AuditExecution::Inline,
+            commands,
+            elapsed,
+            error,
+        );
+    }
+
+    /// Record the result of one execution or hand-off.
+    pub(crate) fn effect(
+        &mut self,
+        command_type: &'static str,
+        execution: AuditExecution,
+        commands: usize,
+        elapsed: Duration,
+        error: Option<String>,
+    ) {
+        self.record.effects.push(AuditEffect {
+            command_type,
+            execution,
+            commands,
+            duration_us: micros(elapsed),
+            error,
+        });
+    }
+
+    pub(crate) fn finish(mut self) -> AuditRecord {
+        self.record.duration_us = micros(self.started.elapsed());
+        self.record
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+// =============================================================================
+// Sink
+// =============================================================================
+
+/// Pluggable destination for audit records.
+///
+/// Called from a dedicated task, never from the runtime loop, with up to
+/// 256 records at a time. A failed write is logged and the records are
+/// discarded.
+#[async_trait]
+pub trait AuditSink: Send + Sync + 'static {
+    /// Persist a batch of records, in the order they were produced.
+    async fn write(&self, records: &[AuditRecord]) -> Result<()>;
+}
+
+/// [`AuditSink`] writing one JSON object per line.
+pub struct JsonLinesAuditSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesAuditSink {
+    /// Write to any writer, flushing after every batch.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Write to standard output.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Append to a file, creating it if it does not exist.
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonLinesAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        for record in records {
+            serde_json::to_writer(&mut *writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for JsonLinesAuditSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonLinesAuditSink").finish_non_exhaustive()
+    }
+}
+
+/// In-memory [`AuditSink`], for tests.
+#[derive(Debug, Default)]
+pub struct MemoryAuditSink {
+    records: Mutex<Vec<AuditRecord>>,
+}
+
+impl MemoryAuditSink {
+    /// Create an empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All records written so far.
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[async_trait]
+impl AuditSink for MemoryAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(records);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Writer
+// =============================================================================
+
+/// Receiver and sink of a writer task that has not started yet.
+type PendingWriter = (mpsc::Receiver<AuditRecord>, Arc<dyn AuditSink>);
+
+/// Bounded hand-off from the runtime loop to the sink's writer task.
+pub(crate) struct AuditWriter {
+    sender: mpsc::Sender<AuditRecord>,
+    /// Receiver and sink, until the writer task is started.
+    pending: Mutex<Option<PendingWriter>>,
+    dropped: AtomicU64,
+}
+
+impl AuditWriter {
+    pub(crate) fn new(sink: Arc<dyn AuditSink>) -> Self {
+        let (sender, receiver) = mpsc::channel(AUDIT_BUFFER);
+        Self {
+            sender,
+            pending: Mutex::new(Some((receiver, sink))),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Spawn the writer task. Records written before this are buffered.
+    ///
+    /// The task exits once the writer is dropped and the buffer is drained.
+    pub(crate) fn start(&self) {
+        let Some((mut receiver, sink)) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(AUDIT_WRITE_BATCH);
+            while receiver.recv_many(&mut batch, AUDIT_WRITE_BATCH).await > 0 {
+                if let Err(e) = sink.write(&batch).await {
+                    warn!(records = batch.len(), error = %e, "audit sink write failed");
+                }
+                batch.clear();
+            }
+        });
+    }
+
+    /// Queue a record for the sink, without waiting.
+    pub(crate) fn write(&self, record: AuditRecord) {
+        if self.sender.try_send(record).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            // Log the first drop and then every thousandth, not every record
+            if dropped == 1 || dropped.is_multiple_of(1000) {
+                warn!(dropped, "audit sink lagging, dropping records");
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for AuditWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditWriter")
+            .field("dropped", &self.dropped.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::core::{Command, CorrelationId};
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::machine::Machine;
+    use crate::runtime::Runtime;
+
+    #[derive(Debug, Clone)]
+    struct Ordered(u32);
+
+    #[derive(Debug, Clone)]
+    struct Charge(u32);
+    impl Command for Charge {}
+
+    #[derive(Debug, Clone)]
+    struct Charged;
+
+    struct ChargeMachine;
+
+    impl Machine for ChargeMachine {
+        type Event = Ordered;
+        type Command = Charge;
+
+        fn decide(&mut self, event: &Ordered) -> Option<Charge> {
+            Some(Charge(event.0))
+        }
+    }
+
+    struct ChargeEffect;
+
+    #[async_trait]
+    impl Effect<Charge, ()> for ChargeEffect {
+        type Event = Charged;
+
+        async fn execute(&self, cmd: Charge, _ctx: EffectContext<()>) -> Result<Charged> {
+            if cmd.0 == 0 {
+                anyhow::bail!("nothing to charge");
+            }
+            Ok(Charged)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runtime_writes_a_record_per_envelope() {
+        let sink = Arc::new(MemoryAuditSink::new());
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new((), bus.clone()).with_effect::<Charge, _>(ChargeEffect);
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(ChargeMachine)
+            .with_audit_sink(sink.clone());
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let cid = CorrelationId::new();
+        bus.emit_with_correlation(Ordered(5), cid);
+        bus.emit(Ordered(0));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        let records = sink.records();
+        let charged = &records[0];
+        assert_eq!(charged.correlation_id, Some(cid.into_inner()));
+        assert!(charged.event_type.unwrap().ends_with("Ordered"));
+        assert_eq!(charged.decisions.len(), 1);
+        assert!(charged.decisions[0].machine.ends_with("ChargeMachine"));
+        assert!(charged.decisions[0]
+            .command_type
+            .unwrap()
+            .ends_with("Charge"));
+        assert_eq!(charged.effects.len(), 1);
+        assert_eq!(charged.effects[0].execution, AuditExecution::Inline);
+        assert!(charged.effects[0].command_type.ends_with("Charge"));
+        assert!(charged.effects[0].error.is_none());
+
+        // The Charged event is audited too, with no machine handling it
+        let failed = records
+            .iter()
+            .find(|r| r.effects.iter().any(|e| e.error.is_some()))
+            .expect("failed charge should be audited");
+        assert_eq!(
+            failed.effects[0].error.as_deref(),
+            Some("nothing to charge")
+        );
+        assert!(records
+            .iter()
+            .any(|r| r.event_type.is_none() && r.hops == 1));
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_sink_writes_one_line_per_record() {
+        #[derive(Clone, Default)]
+        struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for SharedBuf {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf = SharedBuf::default();
+        let sink = JsonLinesAuditSink::new(buf.clone());
+        let envelope = EventEnvelope::new(CorrelationId::NONE, Ordered(1));
+        let mut builder = AuditRecordBuilder::new(&envelope);
+        builder.dropped();
+        let record = builder.finish();
+
+        sink.write(&[record.clone(), record]).await.unwrap();
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(json["dropped"], true);
+        assert_eq!(json["correlation_id"], serde_json::Value::Null);
+    }
+}
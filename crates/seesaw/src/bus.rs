@@ -4,29 +4,269 @@
 //!
 //! - **At-most-once delivery**: Slow receivers may miss events
 //! - **In-memory only**: Events are not persisted
-//! - **No replay**: Lagged receivers get `RecvError::Lagged`
+//! - **No replay**: Lagged receivers get `RecvError::Lagged`, unless the
+//!   bus retains recent events (see [Lag recovery](#lag-recovery))
 //!
 //! For durability, use:
 //! - Entity status fields for workflow state
 //! - Jobs for durable command execution
 //! - Reapers for crash recovery
 //!
+//! # Backpressure
+//!
+//! The bus buffers up to [`EventBus::capacity`] events that have not yet been
+//! seen by every subscriber. What happens when a burst fills the buffer is
+//! selected with [`BackpressurePolicy`]; every event lost to backpressure is
+//! counted in [`EventBus::stats`], so overload is never silent.
+//!
+//! ```ignore
+//! let bus = EventBus::with_capacity(1024)
+//!     .with_backpressure(BackpressurePolicy::Block);
+//!
+//! // Waits for the slowest subscriber to make room
+//! bus.emit_envelope_async(EventEnvelope::new_random(OrderPlaced { id })).await?;
+//!
+//! println!("dropped so far: {}", bus.stats().dropped);
+//! ```
+//!
+//! # Lag recovery
+//!
+//! A subscriber more than `capacity` events behind loses the overwritten
+//! events. [`EventBus::with_retention`] trades memory for resilience against
+//! brief stalls: the bus keeps a ring of recent events, and a
+//! [`RecoveringReceiver`] that lagged replays the events it missed from the
+//! ring before continuing. Only events older than the ring are lost. The
+//! runtime always subscribes this way, so retention alone turns a brief
+//! stall from lost events into a delay.
+//!
+//! ```ignore
+//! // Survive falling up to 4096 events behind a full buffer
+//! let bus = EventBus::with_capacity(1024).with_retention(4096);
+//! ```
+//!
+//! # Event TTL
+//!
+//! Some events are only worth acting on while fresh: a presence update or a
+//! price tick that waited out a backlog is superseded by the time it is
+//! read. [`EventBus::with_ttl`] gives an event type a freshness budget,
+//! measured from when the bus sent it; the runtime drops events older than
+//! that instead of passing them to machines, counts them in
+//! [`BusStats::expired`] and reports them to the dead-letter sink as
+//! [`DeadLetterReason::Expired`].
+//!
+//! ```ignore
+//! let bus = EventBus::with_capacity(1024)
+//!     .with_ttl::<PresenceChanged>(Duration::from_secs(2))
+//!     .with_ttl::<PriceTick>(Duration::from_millis(500));
+//! ```
+//!
 //! # Correlation
 //!
 //! Events can be emitted with a correlation ID for tracking related work.
 //! Use `emit_with_correlation` when you need to await completion of all
 //! work triggered by an event.
 
-use std::any::Any;
-use std::sync::Arc;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
 
 use crate::core::{CorrelationId, Event, EventEnvelope};
+use crate::dead_letter::{DeadLetterReason, DeadLetterSink};
+use crate::error::SeesawError;
+use crate::metrics;
 
 /// Default channel capacity for the event bus.
 const DEFAULT_CAPACITY: usize = 10000;
 
+/// How often a blocked emitter re-checks for free buffer space while
+/// receivers from [`EventBus::subscribe`], which cannot wake it, are
+/// subscribed.
+const UNSIGNALLED_RECHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+// =============================================================================
+// Backpressure
+// =============================================================================
+
+/// What the bus does with a new event when its buffer is full.
+///
+/// The buffer is full when the slowest subscriber is `capacity` events
+/// behind. A subscriber that is never polled therefore stalls the bus for
+/// every policy except `DropOldest`; drop receivers you no longer read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Overwrite the oldest buffered event (default).
+    ///
+    /// The new event is delivered; lagging subscribers skip the overwritten
+    /// event and observe `RecvError::Lagged`. Counted as `dropped`.
+    #[default]
+    DropOldest,
+    /// Discard the new event. Counted as `dropped`.
+    DropNewest,
+    /// Reject the new event with [`SeesawError::BusFull`]. Counted as `rejected`.
+    ///
+    /// The infallible `emit*` methods log a warning and return 0;
+    /// use [`EventBus::try_emit_envelope`] to get the error.
+    Error,
+    /// Make emitters wait until the slowest subscriber makes room.
+    ///
+    /// [`EventBus::emit_envelope_async`] (and `EngineHandle::emit_and_await`)
+    /// waits. Synchronous `emit*` calls, including the events the runtime
+    /// emits on behalf of effects, cannot: their events queue behind the
+    /// buffer and are sent, in emit order, as subscribers make room. The
+    /// runtime is itself a subscriber, so waiting on its own backlog would
+    /// deadlock. Queued events are counted as `deferred` and return 0
+    /// receivers; the queue is unbounded, so keep external producers on the
+    /// async path.
+    ///
+    /// Outside a tokio runtime nothing can send queued events, so a
+    /// synchronous emit onto a full buffer is rejected as under
+    /// [`BackpressurePolicy::Error`].
+    Block,
+}
+
+/// Counters describing what the bus did with emitted events.
+///
+/// Counts are shared by all clones of a bus and are approximate while
+/// emitters race each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BusStats {
+    /// Events accepted into the buffer.
+    pub emitted: u64,
+    /// Events lost to `DropOldest` or `DropNewest`.
+    pub dropped: u64,
+    /// Events refused under `Error` or `Block`.
+    pub rejected: u64,
+    /// Events synchronous emitters queued behind a full buffer under
+    /// `Block`, to be sent as subscribers make room.
+    pub deferred: u64,
+    /// Events replayed from retention to receivers that lagged.
+    pub recovered: u64,
+    /// Events the runtime dropped for outliving their type's TTL.
+    pub expired: u64,
+}
+
+#[derive(Debug, Default)]
+struct BusCounters {
+    emitted: AtomicU64,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+    deferred: AtomicU64,
+    recovered: AtomicU64,
+    expired: AtomicU64,
+}
+
+/// How long events of one type stay worth handling.
+#[derive(Debug, Clone, Copy)]
+struct EventTtl {
+    ttl: Duration,
+    event_type: &'static str,
+}
+
+// =============================================================================
+// Retention
+// =============================================================================
+
+/// The most recent events sent, numbered in send order.
+///
+/// Held locked while sending, so numbering matches the order receivers see.
+#[derive(Debug)]
+struct Retention {
+    events: VecDeque<EventEnvelope>,
+    /// Most events kept: the buffer capacity plus the configured retention.
+    limit: usize,
+    /// Number of the next event sent.
+    next: u64,
+}
+
+impl Retention {
+    fn push(&mut self, envelope: EventEnvelope) {
+        if self.events.len() == self.limit {
+            self.events.pop_front();
+        }
+        self.events.push_back(envelope);
+        self.next += 1;
+    }
+
+    /// Retained events numbered `from..from + count`, and how many of them
+    /// are no longer retained.
+    fn range(&self, from: u64, count: u64) -> (Vec<EventEnvelope>, u64) {
+        let oldest = self.next - self.events.len() as u64;
+        let lost = oldest.saturating_sub(from).min(count);
+        if lost == count {
+            return (Vec::new(), lost);
+        }
+        let start = (from + lost - oldest) as usize;
+        let recovered = self
+            .events
+            .range(start..start + (count - lost) as usize)
+            .cloned()
+            .collect();
+        (recovered, lost)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// =============================================================================
+// Blocking
+// =============================================================================
+
+/// Events synchronous emitters could not fit under `Block`, in emit order.
+#[derive(Debug, Default)]
+struct Overflow {
+    events: VecDeque<EventEnvelope>,
+    /// Whether a task is sending `events` as room frees up.
+    flushing: bool,
+}
+
+/// Wakes emitters blocked on a full buffer when receivers take events.
+#[derive(Debug, Default)]
+struct Room {
+    notify: Notify,
+    /// Receivers that signal `notify`, from
+    /// [`EventBus::subscribe_recovering`] on a `Block` bus.
+    signalling: AtomicUsize,
+}
+
+/// A receiver's registration with its bus's [`Room`], signalling once more
+/// when dropped.
+#[derive(Debug)]
+struct RoomSignal(Arc<Room>);
+
+impl RoomSignal {
+    fn new(room: Arc<Room>) -> Self {
+        room.signalling.fetch_add(1, Ordering::Relaxed);
+        Self(room)
+    }
+
+    /// Wake blocked emitters to look for room again.
+    fn signal(&self) {
+        self.0.notify.notify_waiters();
+    }
+}
+
+impl Drop for RoomSignal {
+    fn drop(&mut self) {
+        self.0.signalling.fetch_sub(1, Ordering::Relaxed);
+        self.signal();
+    }
+}
+
+// =============================================================================
+// Event Bus
+// =============================================================================
+
 /// Type-erased event bus for broadcasting events.
 ///
 /// The `EventBus` is a broadcast channel that allows multiple subscribers
@@ -53,6 +293,14 @@ const DEFAULT_CAPACITY: usize = 10000;
 #[derive(Clone)]
 pub struct EventBus {
     sender: broadcast::Sender<EventEnvelope>,
+    capacity: usize,
+    policy: BackpressurePolicy,
+    counters: Arc<BusCounters>,
+    dead_letters: Option<DeadLetterSink>,
+    retention: Option<Arc<Mutex<Retention>>>,
+    ttls: Arc<HashMap<TypeId, EventTtl>>,
+    overflow: Arc<Mutex<Overflow>>,
+    room: Arc<Room>,
 }
 
 impl EventBus {
@@ -64,10 +312,76 @@ impl EventBus {
     /// Create a new event bus with the specified capacity.
     ///
     /// The capacity determines how many events can be buffered before
-    /// slow receivers start lagging.
+    /// slow receivers start lagging. It is rounded up to the next power of
+    /// two; [`capacity`](Self::capacity) reports the effective value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
     pub fn with_capacity(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            capacity: capacity.next_power_of_two(),
+            policy: BackpressurePolicy::default(),
+            counters: Arc::new(BusCounters::default()),
+            dead_letters: None,
+            retention: None,
+            ttls: Arc::default(),
+            overflow: Arc::default(),
+            room: Arc::default(),
+        }
+    }
+
+    /// Set the policy applied when the buffer is full.
+    pub fn with_backpressure(mut self, policy: BackpressurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Retain recent events so receivers from
+    /// [`subscribe_recovering`](Self::subscribe_recovering) can replay up to
+    /// `events` events they missed by lagging behind a full buffer.
+    ///
+    /// The bus keeps `capacity + events` envelopes; payloads are shared, not
+    /// copied. Applies to this bus and clones made from it afterwards.
+    pub fn with_retention(mut self, events: usize) -> Self {
+        self.retention = (events > 0).then(|| {
+            Arc::new(Mutex::new(Retention {
+                events: VecDeque::new(),
+                limit: self.capacity + events,
+                next: 0,
+            }))
+        });
+        self
+    }
+
+    /// Report events this bus cannot deliver to `sink`.
+    ///
+    /// Applies to this bus and clones made from it afterwards. Events
+    /// emitted without subscribers, and events refused by backpressure, are
+    /// sent to the sink; a runtime on this bus also reports events nothing
+    /// handles. See [`DeadLetterReason`].
+    pub fn with_dead_letters(mut self, sink: DeadLetterSink) -> Self {
+        self.dead_letters = Some(sink);
+        self
+    }
+
+    /// Drop events of type `E` that wait longer than `ttl` between being
+    /// sent and reaching the runtime.
+    ///
+    /// Expired events are counted in [`BusStats::expired`] and sent to the
+    /// dead-letter sink; other subscribers still receive them. Applies to
+    /// this bus and clones made from it afterwards.
+    pub fn with_ttl<E: Event>(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.ttls).insert(
+            TypeId::of::<E>(),
+            EventTtl {
+                ttl,
+                event_type: std::any::type_name::<E>(),
+            },
+        );
+        self
     }
 
     /// Emit an event to all subscribers (fire-and-forget).
@@ -77,8 +391,7 @@ impl EventBus {
     ///
     /// Returns the number of receivers that received the event.
     pub fn emit<E: Event>(&self, event: E) -> usize {
-        let envelope = EventEnvelope::new_random(event);
-        self.sender.send(envelope).unwrap_or(0)
+        self.emit_envelope(EventEnvelope::new_random(event))
     }
 
     /// Emit an event with a specific correlation ID.
@@ -90,8 +403,15 @@ impl EventBus {
     ///
     /// Returns the number of receivers that received the event.
     pub fn emit_with_correlation<E: Event>(&self, event: E, cid: CorrelationId) -> usize {
-        let envelope = EventEnvelope::new(cid, event);
-        self.sender.send(envelope).unwrap_or(0)
+        self.emit_envelope(EventEnvelope::new(cid, event))
+    }
+
+    /// Emit an already shared event without copying it.
+    ///
+    /// Use this to publish a large event you also keep a handle on, such as
+    /// a fetched document. Generates a new random correlation ID.
+    pub fn emit_arc<E: Event>(&self, event: Arc<E>) -> usize {
+        self.emit_envelope(EventEnvelope::from_arc(CorrelationId::new(), event))
     }
 
     /// Emit an event envelope directly.
@@ -99,7 +419,16 @@ impl EventBus {
     /// This is useful when forwarding envelopes or when you've already
     /// constructed the envelope.
     pub fn emit_envelope(&self, envelope: EventEnvelope) -> usize {
-        self.sender.send(envelope).unwrap_or(0)
+        if self.policy == BackpressurePolicy::Block {
+            return self.emit_or_defer(envelope);
+        }
+        match self.try_emit_envelope(envelope) {
+            Ok(receivers) => receivers,
+            Err(e) => {
+                warn!(error = %e, policy = ?self.policy, "event rejected by bus backpressure");
+                0
+            }
+        }
     }
 
     /// Emit a type-erased event to all subscribers.
@@ -110,8 +439,56 @@ impl EventBus {
             cid: CorrelationId::new(),
             type_id: (*event).type_id(),
             payload: event,
+            hops: 0,
+            metadata: Default::default(),
+            emitted_at: Instant::now(),
         };
-        self.sender.send(envelope).unwrap_or(0)
+        self.emit_envelope(envelope)
+    }
+
+    /// Emit an event envelope without waiting, reporting rejection.
+    ///
+    /// Returns [`SeesawError::BusFull`] if the buffer is full and the policy
+    /// is `Error` or `Block`. Under `Block`, events queued by synchronous
+    /// emitters count as filling the buffer. Under `DropNewest` a discarded
+    /// event returns `Ok(0)`.
+    pub fn try_emit_envelope(&self, envelope: EventEnvelope) -> Result<usize, SeesawError> {
+        if self.policy == BackpressurePolicy::Block {
+            let overflow = lock(&self.overflow);
+            if !self.has_room(&overflow) {
+                drop(overflow);
+                return self.refuse(envelope);
+            }
+            return Ok(self.send(envelope));
+        }
+        if self.policy != BackpressurePolicy::DropOldest && self.is_full() {
+            return self.refuse(envelope);
+        }
+        Ok(self.send(envelope))
+    }
+
+    /// Emit an event envelope, waiting for buffer space under `Block`.
+    ///
+    /// Under every other policy this behaves like
+    /// [`try_emit_envelope`](Self::try_emit_envelope). Never call this from
+    /// an effect: the runtime cannot make room while it waits on itself.
+    pub async fn emit_envelope_async(&self, envelope: EventEnvelope) -> Result<usize, SeesawError> {
+        if self.policy != BackpressurePolicy::Block {
+            return self.try_emit_envelope(envelope);
+        }
+        loop {
+            let notified = self.room.notify.notified();
+            tokio::pin!(notified);
+            // Registered before looking, so room made meanwhile still wakes us
+            notified.as_mut().enable();
+            {
+                let overflow = lock(&self.overflow);
+                if self.has_room(&overflow) {
+                    return Ok(self.send(envelope));
+                }
+            }
+            self.wait_for_room(notified).await;
+        }
     }
 
     /// Subscribe to events on this bus.
@@ -122,10 +499,207 @@ impl EventBus {
         self.sender.subscribe()
     }
 
+    /// Subscribe with recovery from lag.
+    ///
+    /// Behaves like [`subscribe`](Self::subscribe), except that after
+    /// lagging the receiver first replays the missed events the bus still
+    /// retains (see [`with_retention`](Self::with_retention)). Only events
+    /// no longer retained are reported as `Lagged`.
+    pub fn subscribe_recovering(&self) -> RecoveringReceiver {
+        // Locked so no event is sent between subscribing and numbering
+        let retention = self.retention.as_ref().map(|retention| lock(retention));
+        RecoveringReceiver {
+            receiver: self.sender.subscribe(),
+            position: retention.as_ref().map_or(0, |retention| retention.next),
+            retention: self.retention.clone(),
+            counters: self.counters.clone(),
+            replay: VecDeque::new(),
+            room: (self.policy == BackpressurePolicy::Block)
+                .then(|| RoomSignal::new(self.room.clone())),
+        }
+    }
+
     /// Returns the number of active subscribers.
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
     }
+
+    /// Returns the effective buffer capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the configured backpressure policy.
+    pub fn backpressure(&self) -> BackpressurePolicy {
+        self.policy
+    }
+
+    /// Returns the number of events not yet seen by every subscriber.
+    pub fn buffered(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns a snapshot of the bus counters.
+    pub fn stats(&self) -> BusStats {
+        BusStats {
+            emitted: self.counters.emitted.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            rejected: self.counters.rejected.load(Ordering::Relaxed),
+            deferred: self.counters.deferred.load(Ordering::Relaxed),
+            recovered: self.counters.recovered.load(Ordering::Relaxed),
+            expired: self.counters.expired.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether `envelope` outlived its type's TTL, counting and
+    /// dead-lettering it if so.
+    pub(crate) fn expire_stale(&self, envelope: &EventEnvelope) -> bool {
+        let Some(ttl) = self.ttls.get(&envelope.type_id) else {
+            return false;
+        };
+        let age = envelope.emitted_at.elapsed();
+        if age <= ttl.ttl {
+            return false;
+        }
+        debug!(
+            cid = %envelope.cid,
+            event_type = ttl.event_type,
+            age_ms = age.as_millis() as u64,
+            "event outlived its TTL, dropping"
+        );
+        self.counters.expired.fetch_add(1, Ordering::Relaxed);
+        metrics::event_expired(ttl.event_type);
+        self.dead_letter(envelope.clone(), DeadLetterReason::Expired);
+        true
+    }
+
+    /// Send `envelope` to the dead-letter sink, if one is installed.
+    pub(crate) fn dead_letter(&self, envelope: EventEnvelope, reason: DeadLetterReason) {
+        if let Some(sink) = &self.dead_letters {
+            sink.send(envelope, reason);
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.sender.len() >= self.capacity
+    }
+
+    /// Whether a `Block` emitter may send now: the buffer has room and no
+    /// queued event goes first. Without subscribers the event is
+    /// dead-lettered rather than waiting for them.
+    fn has_room(&self, overflow: &Overflow) -> bool {
+        self.subscriber_count() == 0 || (overflow.events.is_empty() && !self.is_full())
+    }
+
+    /// Send `envelope` under `Block`, or queue it for [`flush_overflow`]
+    /// if the buffer is full.
+    ///
+    /// [`flush_overflow`]: Self::flush_overflow
+    fn emit_or_defer(&self, envelope: EventEnvelope) -> usize {
+        let mut overflow = lock(&self.overflow);
+        if self.has_room(&overflow) {
+            return self.send(envelope);
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            drop(overflow);
+            if let Err(e) = self.refuse(envelope) {
+                warn!(error = %e, "event rejected by bus backpressure outside a tokio runtime");
+            }
+            return 0;
+        };
+        overflow.events.push_back(envelope);
+        self.counters.deferred.fetch_add(1, Ordering::Relaxed);
+        if !overflow.flushing {
+            overflow.flushing = true;
+            runtime.spawn(self.clone().flush_overflow());
+        }
+        debug!(
+            queued = overflow.events.len(),
+            "event bus full, queued event behind the buffer"
+        );
+        0
+    }
+
+    /// Send queued events as receivers make room, until none are left.
+    async fn flush_overflow(self) {
+        loop {
+            let notified = self.room.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut overflow = lock(&self.overflow);
+                while self.subscriber_count() == 0 || !self.is_full() {
+                    let Some(envelope) = overflow.events.pop_front() else {
+                        overflow.flushing = false;
+                        drop(overflow);
+                        // Async emitters waited for the queue to empty
+                        self.room.notify.notify_waiters();
+                        return;
+                    };
+                    self.send(envelope);
+                }
+            }
+            self.wait_for_room(notified).await;
+        }
+    }
+
+    /// Wait for a receiver to take an event. Receivers from
+    /// [`subscribe`](Self::subscribe) cannot signal, so while any is
+    /// subscribed this also returns after [`UNSIGNALLED_RECHECK_INTERVAL`].
+    async fn wait_for_room(&self, notified: Pin<&mut Notified<'_>>) {
+        if self.subscriber_count() > self.room.signalling.load(Ordering::Relaxed) {
+            let _ = tokio::time::timeout(UNSIGNALLED_RECHECK_INTERVAL, notified).await;
+        } else {
+            notified.await;
+        }
+    }
+
+    /// Send into the channel, counting an overwritten event as dropped.
+    fn send(&self, mut envelope: EventEnvelope) -> usize {
+        envelope.emitted_at = Instant::now();
+        // Held until the event is retained, so retention keeps send order
+        let mut retention = self.retention.as_ref().map(|retention| lock(retention));
+        let retained = retention.as_ref().map(|_| envelope.clone());
+        let evicts = self.is_full();
+        #[cfg(feature = "tracing-spans")]
+        let (cid, hops) = (envelope.cid, envelope.hops);
+        match self.sender.send(envelope) {
+            Ok(receivers) => {
+                #[cfg(feature = "tracing-spans")]
+                tracing::trace!(%cid, hops, receivers, "event emitted");
+                if let (Some(retention), Some(envelope)) = (&mut retention, retained) {
+                    retention.push(envelope);
+                }
+                self.counters.emitted.fetch_add(1, Ordering::Relaxed);
+                if evicts {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                receivers
+            }
+            // No subscribers: nothing was buffered, so nothing was lost
+            Err(broadcast::error::SendError(envelope)) => {
+                self.dead_letter(envelope, DeadLetterReason::NoSubscribers);
+                0
+            }
+        }
+    }
+
+    /// Apply a refusing policy to an event that does not fit.
+    fn refuse(&self, envelope: EventEnvelope) -> Result<usize, SeesawError> {
+        self.dead_letter(envelope, DeadLetterReason::Rejected);
+        if self.policy == BackpressurePolicy::DropNewest {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                capacity = self.capacity,
+                "event bus full, dropped newest event"
+            );
+            return Ok(0);
+        }
+        self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+        Err(SeesawError::BusFull {
+            capacity: self.capacity,
+        })
+    }
 }
 
 impl Default for EventBus {
@@ -138,10 +712,124 @@ impl std::fmt::Debug for EventBus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EventBus")
             .field("subscriber_count", &self.subscriber_count())
+            .field("capacity", &self.capacity)
+            .field("policy", &self.policy)
+            .field(
+                "retained",
+                &self.retention.as_ref().map(|r| lock(r).events.len()),
+            )
             .finish()
     }
 }
 
+// =============================================================================
+// Recovering Receiver
+// =============================================================================
+
+/// A bus receiver that replays retained events it missed by lagging.
+///
+/// Created by [`EventBus::subscribe_recovering`]. Events are received in
+/// emission order, replayed ones included. Without retention it behaves like
+/// a plain `broadcast::Receiver`.
+pub struct RecoveringReceiver {
+    receiver: broadcast::Receiver<EventEnvelope>,
+    retention: Option<Arc<Mutex<Retention>>>,
+    counters: Arc<BusCounters>,
+    /// Number of the next event the channel will deliver.
+    position: u64,
+    /// Recovered events, delivered before the channel is read again.
+    replay: VecDeque<EventEnvelope>,
+    /// Set on a `Block` bus. Declared after `receiver`, so the signal on
+    /// drop comes once the receiver's buffer slots are freed.
+    room: Option<RoomSignal>,
+}
+
+impl RecoveringReceiver {
+    /// Receive the next event, waiting for one to be emitted.
+    ///
+    /// Returns `Lagged(n)` only for the `n` missed events that could not be
+    /// recovered; the next call returns the events after them.
+    pub async fn recv(&mut self) -> Result<EventEnvelope, RecvError> {
+        if let Some(envelope) = self.replay.pop_front() {
+            return Ok(envelope);
+        }
+        let received = self.receiver.recv().await;
+        self.signal_room();
+        match received {
+            Ok(envelope) => {
+                self.position += 1;
+                Ok(envelope)
+            }
+            Err(RecvError::Lagged(missed)) => match self.recover(missed) {
+                0 => Ok(self.replay.pop_front().expect("recovered missed events")),
+                lost => Err(RecvError::Lagged(lost)),
+            },
+            Err(RecvError::Closed) => Err(RecvError::Closed),
+        }
+    }
+
+    /// Receive the next event if one is waiting.
+    ///
+    /// Reports lag like [`recv`](Self::recv).
+    pub fn try_recv(&mut self) -> Result<EventEnvelope, TryRecvError> {
+        if let Some(envelope) = self.replay.pop_front() {
+            return Ok(envelope);
+        }
+        let received = self.receiver.try_recv();
+        if !matches!(received, Err(TryRecvError::Empty)) {
+            self.signal_room();
+        }
+        match received {
+            Ok(envelope) => {
+                self.position += 1;
+                Ok(envelope)
+            }
+            Err(TryRecvError::Lagged(missed)) => match self.recover(missed) {
+                0 => Ok(self.replay.pop_front().expect("recovered missed events")),
+                lost => Err(TryRecvError::Lagged(lost)),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Wake emitters blocked on the room taking an event makes.
+    fn signal_room(&self) {
+        if let Some(room) = &self.room {
+            room.signal();
+        }
+    }
+
+    /// Queue the retained events among the `missed` ones for replay,
+    /// returning how many were lost.
+    fn recover(&mut self, missed: u64) -> u64 {
+        let from = self.position;
+        self.position += missed;
+        let Some(retention) = &self.retention else {
+            return missed;
+        };
+        let (recovered, lost) = lock(retention).range(from, missed);
+        debug!(
+            missed,
+            recovered = recovered.len(),
+            "receiver lagged, replaying retained events"
+        );
+        self.counters
+            .recovered
+            .fetch_add(recovered.len() as u64, Ordering::Relaxed);
+        self.replay.extend(recovered);
+        lost
+    }
+}
+
+impl std::fmt::Debug for RecoveringReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecoveringReceiver")
+            .field("position", &self.position)
+            .field("replaying", &self.replay.len())
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,4 +1021,294 @@ mod tests {
             assert_eq!(envelope.downcast_ref::<TestEvent>().unwrap().value, i);
         }
     }
+
+    #[tokio::test]
+    async fn test_drop_oldest_counts_overwritten_events() {
+        let bus = EventBus::with_capacity(4);
+        let mut receiver = bus.subscribe();
+
+        for i in 0..6 {
+            assert_eq!(bus.emit(TestEvent { value: i }), 1);
+        }
+
+        assert_eq!(
+            bus.stats(),
+            BusStats {
+                emitted: 6,
+                dropped: 2,
+                rejected: 0,
+                deferred: 0,
+                recovered: 0,
+                expired: 0,
+            }
+        );
+        assert!(matches!(
+            receiver.recv().await,
+            Err(broadcast::error::RecvError::Lagged(2))
+        ));
+        let envelope = receiver.recv().await.unwrap();
+        assert_eq!(envelope.downcast_ref::<TestEvent>().unwrap().value, 2);
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_keeps_buffered_events() {
+        let bus = EventBus::with_capacity(4).with_backpressure(BackpressurePolicy::DropNewest);
+        let mut receiver = bus.subscribe();
+
+        for i in 0..6 {
+            bus.emit(TestEvent { value: i });
+        }
+
+        assert_eq!(bus.stats().emitted, 4);
+        assert_eq!(bus.stats().dropped, 2);
+        assert_eq!(bus.buffered(), 4);
+        for i in 0..4 {
+            let envelope = receiver.recv().await.unwrap();
+            assert_eq!(envelope.downcast_ref::<TestEvent>().unwrap().value, i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_policy_rejects_when_full() {
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Error);
+        let _receiver = bus.subscribe();
+
+        bus.emit(TestEvent { value: 1 });
+        bus.emit(TestEvent { value: 2 });
+        assert_eq!(bus.emit(TestEvent { value: 3 }), 0);
+        let err = bus
+            .try_emit_envelope(EventEnvelope::new_random(TestEvent { value: 4 }))
+            .unwrap_err();
+
+        assert!(matches!(err, SeesawError::BusFull { capacity: 2 }));
+        assert_eq!(bus.stats().rejected, 2);
+        assert_eq!(bus.stats().dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_room() {
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Block);
+        let mut receiver = bus.subscribe();
+        bus.emit(TestEvent { value: 1 });
+        bus.emit(TestEvent { value: 2 });
+
+        let emitter = bus.clone();
+        let blocked = tokio::spawn(async move {
+            emitter
+                .emit_envelope_async(EventEnvelope::new_random(TestEvent { value: 3 }))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+
+        receiver.recv().await.unwrap();
+        assert_eq!(blocked.await.unwrap().unwrap(), 1);
+
+        for expected in [2, 3] {
+            let envelope = receiver.recv().await.unwrap();
+            assert_eq!(
+                envelope.downcast_ref::<TestEvent>().unwrap().value,
+                expected
+            );
+        }
+        assert_eq!(bus.stats().dropped + bus.stats().rejected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_wakes_emitters_on_receive() {
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Block);
+        // Only signalling receivers, so nothing re-checks on a timer
+        let mut receiver = bus.subscribe_recovering();
+        bus.emit(TestEvent { value: 1 });
+        bus.emit(TestEvent { value: 2 });
+
+        let emitter = bus.clone();
+        let blocked = tokio::spawn(async move {
+            emitter
+                .emit_envelope_async(EventEnvelope::new_random(TestEvent { value: 3 }))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+
+        receiver.recv().await.unwrap();
+        let sent = tokio::time::timeout(Duration::from_secs(1), blocked)
+            .await
+            .expect("receiving should wake the emitter");
+        assert_eq!(sent.unwrap().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_queues_sync_emits_in_order() {
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Block);
+        let mut receiver = bus.subscribe_recovering();
+
+        for i in 0..5 {
+            bus.emit(TestEvent { value: i });
+        }
+        assert_eq!(bus.stats().deferred, 3);
+        assert!(matches!(
+            bus.try_emit_envelope(EventEnvelope::new_random(TestEvent { value: 9 })),
+            Err(SeesawError::BusFull { capacity: 2 })
+        ));
+
+        // Waits behind the queued events
+        let emitter = bus.clone();
+        let waiting = tokio::spawn(async move {
+            emitter
+                .emit_envelope_async(EventEnvelope::new_random(TestEvent { value: 5 }))
+                .await
+        });
+
+        for expected in 0..6 {
+            let envelope = tokio::time::timeout(Duration::from_secs(1), receiver.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                envelope.downcast_ref::<TestEvent>().unwrap().value,
+                expected
+            );
+        }
+        waiting.await.unwrap().unwrap();
+        assert_eq!(bus.stats().emitted, 6);
+        assert_eq!(bus.stats().rejected, 1);
+        assert_eq!(bus.stats().dropped, 0);
+    }
+
+    #[test]
+    fn test_block_policy_rejects_sync_emits_outside_a_runtime() {
+        let bus = EventBus::with_capacity(1).with_backpressure(BackpressurePolicy::Block);
+        let _receiver = bus.subscribe();
+
+        bus.emit(TestEvent { value: 1 });
+        assert_eq!(bus.emit(TestEvent { value: 2 }), 0);
+
+        assert_eq!(bus.stats().rejected, 1);
+        assert_eq!(bus.stats().deferred, 0);
+    }
+
+    #[test]
+    fn test_capacity_rounds_to_power_of_two() {
+        let bus = EventBus::with_capacity(10);
+        assert_eq!(bus.capacity(), 16);
+        assert_eq!(bus.backpressure(), BackpressurePolicy::DropOldest);
+    }
+
+    #[test]
+    fn test_dead_letters_undeliverable_events() {
+        let (sink, mut dead) = crate::dead_letter::DeadLetterSink::channel();
+        let bus = EventBus::with_capacity(1)
+            .with_backpressure(BackpressurePolicy::DropNewest)
+            .with_dead_letters(sink);
+
+        bus.emit(TestEvent { value: 1 });
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(letter.reason, DeadLetterReason::NoSubscribers);
+        assert_eq!(
+            letter.envelope.downcast_ref::<TestEvent>().unwrap().value,
+            1
+        );
+
+        let _receiver = bus.subscribe();
+        bus.emit(TestEvent { value: 2 });
+        bus.emit(TestEvent { value: 3 });
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(letter.reason, DeadLetterReason::Rejected);
+        assert_eq!(
+            letter.envelope.downcast_ref::<TestEvent>().unwrap().value,
+            3
+        );
+        assert!(dead.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_ttl_expires_stale_events_of_its_type() {
+        let (sink, mut dead) = crate::dead_letter::DeadLetterSink::channel();
+        let bus = EventBus::new()
+            .with_ttl::<TestEvent>(Duration::from_millis(5))
+            .with_dead_letters(sink);
+        let mut receiver = bus.subscribe();
+
+        bus.emit(TestEvent { value: 1 });
+        bus.emit(OtherEvent {
+            message: "no ttl".into(),
+        });
+        let fresh = receiver.try_recv().unwrap();
+        assert!(!bus.expire_stale(&fresh));
+
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(bus.expire_stale(&fresh));
+        assert!(!bus.expire_stale(&receiver.try_recv().unwrap()));
+
+        assert_eq!(bus.stats().expired, 1);
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(letter.reason, DeadLetterReason::Expired);
+        assert_eq!(
+            letter.envelope.downcast_ref::<TestEvent>().unwrap().value,
+            1
+        );
+        assert!(dead.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recovering_receiver_replays_missed_events() {
+        let bus = EventBus::with_capacity(4).with_retention(4);
+        let mut receiver = bus.subscribe_recovering();
+
+        for i in 0..8 {
+            bus.emit(TestEvent { value: i });
+        }
+
+        for expected in 0..8 {
+            let envelope = receiver.recv().await.unwrap();
+            assert_eq!(
+                envelope.downcast_ref::<TestEvent>().unwrap().value,
+                expected
+            );
+        }
+        assert_eq!(bus.stats().recovered, 4);
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn test_recovering_receiver_reports_events_beyond_retention() {
+        let bus = EventBus::with_capacity(2).with_retention(2);
+        let mut receiver = bus.subscribe_recovering();
+
+        for i in 0..7 {
+            bus.emit(TestEvent { value: i });
+        }
+
+        // 0..3 fell out of the ring; 3 and 4 are replayed before 5 and 6
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Lagged(3))));
+        for expected in 3..7 {
+            let envelope = receiver.try_recv().unwrap();
+            assert_eq!(
+                envelope.downcast_ref::<TestEvent>().unwrap().value,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_without_retention_recovering_receiver_reports_lag() {
+        let bus = EventBus::with_capacity(2);
+        let mut receiver = bus.subscribe_recovering();
+
+        for i in 0..3 {
+            bus.emit(TestEvent { value: i });
+        }
+
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Lagged(1))));
+        assert_eq!(
+            receiver
+                .try_recv()
+                .unwrap()
+                .downcast_ref::<TestEvent>()
+                .unwrap()
+                .value,
+            1
+        );
+    }
 }
@@ -0,0 +1,260 @@
+//! Sub-engines - one engine per bounded context, fed by a parent.
+//!
+//! A large app does not need one event enum and one flat registration list.
+//! Each bounded context builds its own engine, with its own machines,
+//! effects and deps, and the parent forwards it the event types it cares
+//! about:
+//!
+//! ```ignore
+//! let billing = EngineBuilder::new(billing_deps)
+//!     .with_machine(InvoiceMachine::default())
+//!     .with_effect::<InvoiceCommand, _>(InvoiceEffect)
+//!     .build()
+//!     .start();
+//!
+//! let handle = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_effect::<OrderCommand, _>(OrderEffect)
+//!     .with_child(
+//!         ChildEngine::new("billing", billing)
+//!             .forward::<OrderPlaced>()
+//!             .forward::<OrderCancelled>(),
+//!     )
+//!     .build()
+//!     .start();
+//!
+//! let health = handle.health(); // health.children[0].health is billing's
+//! ```
+//!
+//! Forwarded events keep their correlation ID, causation depth and metadata.
+//! They are forwarded as the parent's bus delivers them, whether emitted from
+//! outside or by the parent's effects, so `emit_and_await` on the parent does
+//! not wait for the children's inline work. Aborting the parent aborts its
+//! children.
+
+use std::any::TypeId;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::bus::{EventBus, RecoveringReceiver};
+use crate::core::Event;
+use crate::engine::EngineHandle;
+use crate::health::Health;
+
+/// A running engine and the event types its parent forwards to it.
+///
+/// Added to a parent with
+/// [`EngineBuilder::with_child`](crate::EngineBuilder::with_child).
+pub struct ChildEngine {
+    name: String,
+    handle: EngineHandle,
+    forwards: Vec<TypeId>,
+}
+
+impl ChildEngine {
+    /// Name `handle` for the parent's health, forwarding nothing yet.
+    pub fn new(name: impl Into<String>, handle: EngineHandle) -> Self {
+        Self {
+            name: name.into(),
+            handle,
+            forwards: Vec::new(),
+        }
+    }
+
+    /// Forward events of type `E` from the parent's bus.
+    pub fn forward<E: Event>(mut self) -> Self {
+        self.forwards.push(TypeId::of::<E>());
+        self
+    }
+
+    /// The child's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The child's handle.
+    pub fn handle(&self) -> &EngineHandle {
+        &self.handle
+    }
+}
+
+impl std::fmt::Debug for ChildEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildEngine")
+            .field("name", &self.name)
+            .field("forwards", &self.forwards.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The health of one child engine, as part of its parent's [`Health`].
+#[derive(Debug, Clone)]
+pub struct ChildHealth {
+    /// The name the child was added under.
+    pub name: String,
+    /// The child's own health, its children included.
+    pub health: Health,
+}
+
+/// The children of a running engine and the tasks forwarding to them.
+pub(crate) struct ChildEngines {
+    children: Vec<ChildEngine>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ChildEngines {
+    /// Start forwarding from `bus` to each child.
+    ///
+    /// Subscribes before returning, so every event emitted afterwards is
+    /// forwarded.
+    pub(crate) fn spawn(children: Vec<ChildEngine>, bus: &EventBus) -> Self {
+        let tasks = children
+            .iter()
+            .filter(|child| !child.forwards.is_empty())
+            .map(|child| {
+                tokio::spawn(forward(
+                    child.name.clone(),
+                    bus.subscribe_recovering(),
+                    child.forwards.clone(),
+                    child.handle.bus().clone(),
+                ))
+            })
+            .collect();
+        Self { children, tasks }
+    }
+
+    /// The child added under `name`.
+    pub(crate) fn get(&self, name: &str) -> Option<&EngineHandle> {
+        self.children
+            .iter()
+            .find(|child| child.name == name)
+            .map(|child| &child.handle)
+    }
+
+    /// Whether every child is processing events.
+    pub(crate) fn are_ready(&self) -> bool {
+        self.children.iter().all(|child| child.handle.is_ready())
+    }
+
+    /// The health of every child, in the order they were added.
+    pub(crate) fn health(&self) -> Vec<ChildHealth> {
+        self.children
+            .iter()
+            .map(|child| ChildHealth {
+                name: child.name.clone(),
+                health: child.handle.health(),
+            })
+            .collect()
+    }
+
+    /// Stop forwarding and abort every child.
+    pub(crate) fn abort(&self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        for child in &self.children {
+            child.handle.abort();
+        }
+    }
+}
+
+async fn forward(
+    child: String,
+    mut events: RecoveringReceiver,
+    forwards: Vec<TypeId>,
+    to: EventBus,
+) {
+    loop {
+        match events.recv().await {
+            Ok(envelope) if forwards.contains(&envelope.type_id) => {
+                if let Err(e) = to.emit_envelope_async(envelope).await {
+                    warn!(child = %child, error = %e, "child engine rejected forwarded event");
+                }
+            }
+            Ok(_) => {}
+            Err(RecvError::Lagged(missed)) => {
+                warn!(child = %child, missed, "child engine forwarder lagged, events lost");
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::{Command, Effect, EffectContext, EngineBuilder, Machine};
+
+    #[derive(Debug, Clone)]
+    struct OrderPlaced;
+
+    #[derive(Debug, Clone)]
+    struct Ignored;
+
+    #[derive(Debug, Clone)]
+    struct Invoice;
+
+    impl Command for Invoice {}
+
+    struct InvoiceMachine;
+
+    impl Machine for InvoiceMachine {
+        type Event = OrderPlaced;
+        type Command = Invoice;
+
+        fn decide(&mut self, _event: &OrderPlaced) -> Option<Invoice> {
+            Some(Invoice)
+        }
+    }
+
+    struct InvoiceEffect(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl Effect<Invoice, ()> for InvoiceEffect {
+        type Event = Ignored;
+
+        async fn execute(&self, _command: Invoice, _ctx: EffectContext<()>) -> Result<Ignored> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Ignored)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parent_forwards_selected_events_to_child() {
+        let invoices = Arc::new(AtomicUsize::new(0));
+        let billing = EngineBuilder::new(())
+            .with_machine(InvoiceMachine)
+            .with_effect::<Invoice, _>(InvoiceEffect(invoices.clone()))
+            .build()
+            .start();
+        let parent = EngineBuilder::new(())
+            .with_child(ChildEngine::new("billing", billing).forward::<OrderPlaced>())
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        parent.emit(Ignored);
+        parent.emit(OrderPlaced);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(invoices.load(Ordering::SeqCst), 1);
+        let health = parent.health();
+        assert!(health.ready);
+        assert_eq!(health.children.len(), 1);
+        assert_eq!(health.children[0].name, "billing");
+        assert_eq!(health.children[0].health.machines, 1);
+        assert!(parent.child("billing").is_some());
+
+        parent.abort();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!parent.child("billing").unwrap().is_ready());
+    }
+}
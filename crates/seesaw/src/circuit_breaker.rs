@@ -0,0 +1,460 @@
+//! Circuit breakers for inline effects.
+//!
+//! When a downstream dependency is failing, continuing to call it wastes
+//! resources and prolongs the incident. A circuit breaker registered for a
+//! command type watches the recent outcomes of its effect and, once the
+//! failure rate crosses a threshold, fails fast instead of executing.
+//!
+//! # States
+//!
+//! ```text
+//!            failure rate >= threshold
+//!   Closed ─────────────────────────────► Open
+//!     ▲                                    │
+//!     │ probe succeeds                     │ open_duration elapsed
+//!     │                                    ▼
+//!     └──────────────────────────────── HalfOpen
+//!                 probe fails: back to Open
+//! ```
+//!
+//! - **Closed**: commands execute normally; outcomes are recorded in a
+//!   sliding window.
+//! - **Open**: commands fail immediately with [`SeesawError::CircuitOpen`],
+//!   which surfaces as `CommandFailed` like any other effect failure.
+//! - **HalfOpen**: a single probe command is let through. Its outcome decides
+//!   whether the breaker closes or re-opens; concurrent commands fail fast.
+//!
+//! State transitions are announced on the bus as [`CircuitBreakerOpened`]
+//! and [`CircuitBreakerClosed`] events so machines and taps can react.
+//!
+//! Only transient failures count against the breaker. Validation, not-found
+//! and unauthorized errors mean the downstream answered - they say nothing
+//! about its health.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect::<ChargeCommand, _>(ChargeEffect)
+//!     .with_circuit_breaker::<ChargeCommand>(
+//!         CircuitBreakerPolicy::new()
+//!             .with_failure_threshold(0.5)
+//!             .with_open_duration(Duration::from_secs(30)),
+//!     )
+//!     .build();
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::bus::EventBus;
+use crate::error::{CommandFailed, SeesawError};
+
+// =============================================================================
+// Policy
+// =============================================================================
+
+/// Thresholds controlling when a circuit breaker trips and recovers.
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerPolicy {
+    failure_threshold: f64,
+    min_calls: usize,
+    window: usize,
+    open_duration: Duration,
+}
+
+impl CircuitBreakerPolicy {
+    /// Create a policy with the defaults: trip at a 50% failure rate over the
+    /// last 20 calls (once at least 5 have been seen), stay open for 30s.
+    pub fn new() -> Self {
+        Self {
+            failure_threshold: 0.5,
+            min_calls: 5,
+            window: 20,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the failure rate, in `(0.0, 1.0]`, at which the breaker trips.
+    pub fn with_failure_threshold(mut self, threshold: f64) -> Self {
+        self.failure_threshold = threshold.clamp(f64::MIN_POSITIVE, 1.0);
+        self
+    }
+
+    /// Set the minimum number of recorded calls before the breaker may trip.
+    pub fn with_min_calls(mut self, min_calls: usize) -> Self {
+        self.min_calls = min_calls.max(1);
+        self
+    }
+
+    /// Set how many recent outcomes the failure rate is computed over.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Set how long the breaker stays open before letting a probe through.
+    pub fn with_open_duration(mut self, duration: Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Events
+// =============================================================================
+
+/// Emitted when a circuit breaker trips (or a half-open probe fails).
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerOpened {
+    /// Human-readable type name of the command whose effect is failing.
+    pub command_type: &'static str,
+    /// Failure rate over the window at the moment the breaker tripped.
+    pub failure_rate: f64,
+    /// How long the breaker will stay open before probing.
+    pub open_for: Duration,
+}
+
+/// Emitted when a half-open probe succeeds and the breaker closes again.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerClosed {
+    /// Human-readable type name of the command whose effect recovered.
+    pub command_type: &'static str,
+}
+
+// =============================================================================
+// Breaker
+// =============================================================================
+
+/// Observable state of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Commands execute normally.
+    Closed,
+    /// Commands fail fast without executing.
+    Open,
+    /// A probe command is deciding whether to close or re-open.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed,
+    Open { until: Instant },
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: BreakerState,
+    /// Recent outcomes while closed, `true` meaning failure.
+    outcomes: VecDeque<bool>,
+}
+
+/// State transition to announce once the lock is released.
+enum Transition {
+    Opened { failure_rate: f64 },
+    Closed,
+}
+
+/// Per-command-type circuit breaker shared by all dispatches of that type.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    type_name: &'static str,
+    policy: CircuitBreakerPolicy,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(type_name: &'static str, policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            type_name,
+            policy,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                outcomes: VecDeque::with_capacity(policy.window),
+            }),
+        }
+    }
+
+    /// Current state, as seen by the next caller.
+    pub(crate) fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match inner.state {
+            BreakerState::Closed => CircuitState::Closed,
+            BreakerState::Open { .. } => CircuitState::Open,
+            BreakerState::HalfOpen => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Ask permission to execute, failing fast if the breaker is open.
+    ///
+    /// The returned permit must be resolved with [`BreakerPermit::record`];
+    /// dropping it unresolved (e.g. on panic) counts as a failure.
+    pub(crate) fn try_acquire(
+        self: &Arc<Self>,
+        bus: &EventBus,
+    ) -> Result<BreakerPermit, SeesawError> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let probe = match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(SeesawError::CircuitOpen {
+                        type_name: self.type_name,
+                        retry_after: until - now,
+                    });
+                }
+                inner.state = BreakerState::HalfOpen;
+                true
+            }
+            BreakerState::HalfOpen => {
+                return Err(SeesawError::CircuitOpen {
+                    type_name: self.type_name,
+                    retry_after: Duration::ZERO,
+                });
+            }
+        };
+
+        Ok(BreakerPermit {
+            breaker: Some(self.clone()),
+            bus: bus.clone(),
+            probe,
+        })
+    }
+
+    fn complete(&self, probe: bool, failed: bool) -> Option<Transition> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        if probe {
+            if failed {
+                inner.state = BreakerState::Open {
+                    until: Instant::now() + self.policy.open_duration,
+                };
+                return Some(Transition::Opened { failure_rate: 1.0 });
+            }
+            inner.state = BreakerState::Closed;
+            inner.outcomes.clear();
+            return Some(Transition::Closed);
+        }
+
+        // Another call tripped the breaker while this one was running.
+        if !matches!(inner.state, BreakerState::Closed) {
+            return None;
+        }
+
+        if inner.outcomes.len() == self.policy.window {
+            inner.outcomes.pop_front();
+        }
+        inner.outcomes.push_back(failed);
+
+        let calls = inner.outcomes.len();
+        if calls < self.policy.min_calls {
+            return None;
+        }
+        let failures = inner.outcomes.iter().filter(|failed| **failed).count();
+        let failure_rate = failures as f64 / calls as f64;
+        if failure_rate < self.policy.failure_threshold {
+            return None;
+        }
+
+        inner.state = BreakerState::Open {
+            until: Instant::now() + self.policy.open_duration,
+        };
+        inner.outcomes.clear();
+        Some(Transition::Opened { failure_rate })
+    }
+
+    fn announce(&self, transition: Transition, bus: &EventBus) {
+        match transition {
+            Transition::Opened { failure_rate } => {
+                warn!(
+                    command = self.type_name,
+                    failure_rate,
+                    open_for = ?self.policy.open_duration,
+                    "circuit breaker opened"
+                );
+                bus.emit(CircuitBreakerOpened {
+                    command_type: self.type_name,
+                    failure_rate,
+                    open_for: self.policy.open_duration,
+                });
+            }
+            Transition::Closed => {
+                warn!(command = self.type_name, "circuit breaker closed");
+                bus.emit(CircuitBreakerClosed {
+                    command_type: self.type_name,
+                });
+            }
+        }
+    }
+}
+
+/// Permission to run one effect execution under a circuit breaker.
+pub(crate) struct BreakerPermit {
+    breaker: Option<Arc<CircuitBreaker>>,
+    bus: EventBus,
+    probe: bool,
+}
+
+impl BreakerPermit {
+    /// Record the outcome of the execution this permit was issued for.
+    pub(crate) fn record<T>(mut self, result: &anyhow::Result<T>) {
+        let failed = match result {
+            Ok(_) => false,
+            Err(e) => CommandFailed::categorize_and_sanitize(e).0.is_transient(),
+        };
+        self.finish(failed);
+    }
+
+    fn finish(&mut self, failed: bool) {
+        if let Some(breaker) = self.breaker.take() {
+            if let Some(transition) = breaker.complete(self.probe, failed) {
+                breaker.announce(transition, &self.bus);
+            }
+        }
+    }
+}
+
+impl Drop for BreakerPermit {
+    fn drop(&mut self) {
+        // Unresolved permit: the execution panicked or was cancelled.
+        self.finish(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(policy: CircuitBreakerPolicy) -> Arc<CircuitBreaker> {
+        Arc::new(CircuitBreaker::new("TestCommand", policy))
+    }
+
+    fn fail(breaker: &Arc<CircuitBreaker>, bus: &EventBus) {
+        let permit = breaker.try_acquire(bus).expect("breaker should be closed");
+        permit.record::<()>(&Err(anyhow::anyhow!("downstream unavailable")));
+    }
+
+    fn succeed(breaker: &Arc<CircuitBreaker>, bus: &EventBus) {
+        let permit = breaker.try_acquire(bus).expect("breaker should be closed");
+        permit.record(&Ok(()));
+    }
+
+    #[test]
+    fn test_trips_once_failure_rate_reaches_threshold() {
+        let bus = EventBus::new();
+        let breaker = breaker(
+            CircuitBreakerPolicy::new()
+                .with_min_calls(4)
+                .with_failure_threshold(0.5),
+        );
+
+        succeed(&breaker, &bus);
+        fail(&breaker, &bus);
+        succeed(&breaker, &bus);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        fail(&breaker, &bus);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let err = breaker.try_acquire(&bus).err().unwrap();
+        assert!(matches!(err, SeesawError::CircuitOpen { .. }));
+    }
+
+    #[test]
+    fn test_window_only_considers_recent_calls() {
+        let bus = EventBus::new();
+        let breaker = breaker(
+            CircuitBreakerPolicy::new()
+                .with_window(4)
+                .with_min_calls(4)
+                .with_failure_threshold(0.75),
+        );
+
+        for _ in 0..10 {
+            succeed(&breaker, &bus);
+        }
+        fail(&breaker, &bus);
+        fail(&breaker, &bus);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        // 3 of the last 4 calls failed, even though most calls overall succeeded
+        fail(&breaker, &bus);
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_permanent_errors_do_not_count() {
+        let bus = EventBus::new();
+        let breaker = breaker(CircuitBreakerPolicy::new().with_min_calls(2));
+
+        for _ in 0..5 {
+            let permit = breaker.try_acquire(&bus).unwrap();
+            let not_found: anyhow::Error =
+                std::io::Error::new(std::io::ErrorKind::NotFound, "missing").into();
+            permit.record::<()>(&Err(not_found));
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe_success_closes() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let breaker = breaker(
+            CircuitBreakerPolicy::new()
+                .with_min_calls(1)
+                .with_open_duration(Duration::ZERO),
+        );
+
+        fail(&breaker, &bus);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Open duration elapsed: first caller becomes the probe, others fail fast
+        let probe = breaker.try_acquire(&bus).unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire(&bus).is_err());
+
+        probe.record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let opened = rx.try_recv().unwrap();
+        assert!(opened.downcast_ref::<CircuitBreakerOpened>().is_some());
+        let closed = rx.try_recv().unwrap();
+        assert!(closed.downcast_ref::<CircuitBreakerClosed>().is_some());
+    }
+
+    #[test]
+    fn test_half_open_probe_failure_reopens() {
+        let bus = EventBus::new();
+        let breaker = breaker(
+            CircuitBreakerPolicy::new()
+                .with_min_calls(1)
+                .with_open_duration(Duration::ZERO),
+        );
+
+        fail(&breaker, &bus);
+        fail(&breaker, &bus); // probe fails
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_dropped_permit_counts_as_failure() {
+        let bus = EventBus::new();
+        let breaker = breaker(CircuitBreakerPolicy::new().with_min_calls(1));
+
+        drop(breaker.try_acquire(&bus).unwrap());
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+}
@@ -0,0 +1,320 @@
+//! Payload codecs - the wire format of persisted job payloads.
+//!
+//! Commands serialize to a `serde_json::Value`, which job stores usually
+//! persist as JSONB. For large or schema'd payloads a binary format is
+//! smaller and faster. A [`PayloadCodecs`] registry picks a [`PayloadCodec`]
+//! globally or per job type:
+//!
+//! - **Dispatcher**: with [`Dispatcher::with_payload_codecs`], background and
+//!   scheduled commands are encoded before they reach the job queue, through
+//!   [`JobQueue::enqueue_encoded`] and [`JobQueue::schedule_encoded`].
+//! - **Job store**: an [`EncodedPayload`] records the codec it was written
+//!   with, so a store persisting the bytes (e.g. a `BYTEA` column next to a
+//!   codec name) decodes them with [`PayloadCodecs::decode`] when claiming,
+//!   even after the configured codec changes.
+//!
+//! [`JsonCodec`] is always available; [`MessagePackCodec`] (`msgpack`
+//! feature) and [`CborCodec`] (`cbor` feature) are optional. Protobuf needs
+//! the generated message types, so it is a [`PayloadCodec`] implemented by
+//! the application for the job types that use it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let codecs = PayloadCodecs::new()
+//!     .with_default(MessagePackCodec)
+//!     .with_codec("report:render", CborCodec);
+//!
+//! let dispatcher = Dispatcher::with_job_queue(deps, bus, queue)
+//!     .with_payload_codecs(codecs.clone());
+//! let store = PgJobStore::new(pool).with_codecs(codecs);
+//! ```
+//!
+//! [`Dispatcher::with_payload_codecs`]: crate::Dispatcher::with_payload_codecs
+//! [`JobQueue::enqueue_encoded`]: crate::JobQueue::enqueue_encoded
+//! [`JobQueue::schedule_encoded`]: crate::JobQueue::schedule_encoded
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+
+/// Encodes job payloads to bytes and back.
+pub trait PayloadCodec: Send + Sync + 'static {
+    /// Name stored alongside encoded payloads, e.g. `"json"`.
+    ///
+    /// Must be unique within a [`PayloadCodecs`] registry and stable across
+    /// deployments, since jobs are decoded by it.
+    fn name(&self) -> &'static str;
+
+    /// Encode a serialized command.
+    fn encode(&self, payload: &serde_json::Value) -> Result<Bytes>;
+
+    /// Decode bytes written by [`encode`](Self::encode).
+    fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value>;
+}
+
+/// A job payload encoded by a named codec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedPayload {
+    /// [`PayloadCodec::name`] of the codec that wrote `bytes`.
+    pub codec: &'static str,
+    /// The encoded payload.
+    pub bytes: Bytes,
+}
+
+// =============================================================================
+// Codecs
+// =============================================================================
+
+/// JSON, the default codec.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl PayloadCodec for JsonCodec {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn encode(&self, payload: &serde_json::Value) -> Result<Bytes> {
+        Ok(serde_json::to_vec(payload)?.into())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// MessagePack, via `rmp-serde` (`msgpack` feature).
+///
+/// Maps are written with field names, so payloads stay self-describing.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "msgpack")]
+impl PayloadCodec for MessagePackCodec {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn encode(&self, payload: &serde_json::Value) -> Result<Bytes> {
+        Ok(rmp_serde::to_vec_named(payload)?.into())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// CBOR, via `ciborium` (`cbor` feature).
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl PayloadCodec for CborCodec {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn encode(&self, payload: &serde_json::Value) -> Result<Bytes> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(payload, &mut bytes)?;
+        Ok(bytes.into())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+        Ok(ciborium::from_reader(bytes)?)
+    }
+}
+
+// =============================================================================
+// Registry
+// =============================================================================
+
+/// Chooses the codec for each job type and decodes by codec name.
+///
+/// Cheap to clone; share one registry between the dispatcher and the store.
+#[derive(Clone)]
+pub struct PayloadCodecs {
+    default: Arc<dyn PayloadCodec>,
+    /// Codec overrides, keyed by job type.
+    by_job_type: HashMap<String, Arc<dyn PayloadCodec>>,
+    /// Every known codec, keyed by name, for decoding.
+    by_name: HashMap<&'static str, Arc<dyn PayloadCodec>>,
+}
+
+impl PayloadCodecs {
+    /// Create a registry that encodes everything as JSON.
+    pub fn new() -> Self {
+        let json: Arc<dyn PayloadCodec> = Arc::new(JsonCodec);
+        Self {
+            by_name: HashMap::from([(json.name(), json.clone())]),
+            default: json,
+            by_job_type: HashMap::new(),
+        }
+    }
+
+    /// Encode job types without an override with `codec`.
+    pub fn with_default<C: PayloadCodec>(mut self, codec: C) -> Self {
+        self.default = self.known(Arc::new(codec));
+        self
+    }
+
+    /// Encode `job_type` payloads with `codec`.
+    pub fn with_codec<C: PayloadCodec>(mut self, job_type: impl Into<String>, codec: C) -> Self {
+        let codec = self.known(Arc::new(codec));
+        self.by_job_type.insert(job_type.into(), codec);
+        self
+    }
+
+    /// Make `codec` available for decoding without using it to encode.
+    ///
+    /// Keep a retired codec registered until its jobs have drained.
+    pub fn with_decoder<C: PayloadCodec>(mut self, codec: C) -> Self {
+        self.known(Arc::new(codec));
+        self
+    }
+
+    /// Register `codec` by name, returning it.
+    fn known(&mut self, codec: Arc<dyn PayloadCodec>) -> Arc<dyn PayloadCodec> {
+        self.by_name.insert(codec.name(), codec.clone());
+        codec
+    }
+
+    /// The codec used to encode `job_type` payloads.
+    pub fn codec_for(&self, job_type: &str) -> &dyn PayloadCodec {
+        self.by_job_type
+            .get(job_type)
+            .unwrap_or(&self.default)
+            .as_ref()
+    }
+
+    /// Encode a `job_type` payload with its codec.
+    pub fn encode(&self, job_type: &str, payload: &serde_json::Value) -> Result<EncodedPayload> {
+        let codec = self.codec_for(job_type);
+        let bytes = codec
+            .encode(payload)
+            .with_context(|| format!("encoding {} payload as {}", job_type, codec.name()))?;
+        Ok(EncodedPayload {
+            codec: codec.name(),
+            bytes,
+        })
+    }
+
+    /// Decode bytes written by the codec named `codec`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no codec by that name is registered, or if the
+    /// bytes do not decode.
+    pub fn decode(&self, codec: &str, bytes: &[u8]) -> Result<serde_json::Value> {
+        let decoder = self
+            .by_name
+            .get(codec)
+            .with_context(|| format!("unknown payload codec: {}", codec))?;
+        decoder
+            .decode(bytes)
+            .with_context(|| format!("decoding {} payload", codec))
+    }
+}
+
+impl Default for PayloadCodecs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for PayloadCodecs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayloadCodecs")
+            .field("default", &self.default.name())
+            .field(
+                "by_job_type",
+                &self
+                    .by_job_type
+                    .iter()
+                    .map(|(job_type, codec)| (job_type.as_str(), codec.name()))
+                    .collect::<HashMap<_, _>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reverses JSON bytes, standing in for a binary format.
+    struct ReversedJson;
+
+    impl PayloadCodec for ReversedJson {
+        fn name(&self) -> &'static str {
+            "reversed"
+        }
+
+        fn encode(&self, payload: &serde_json::Value) -> Result<Bytes> {
+            let mut bytes = serde_json::to_vec(payload)?;
+            bytes.reverse();
+            Ok(bytes.into())
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+            let mut bytes = bytes.to_vec();
+            bytes.reverse();
+            Ok(serde_json::from_slice(&bytes)?)
+        }
+    }
+
+    #[test]
+    fn test_codec_chosen_per_job_type() {
+        let codecs = PayloadCodecs::new().with_codec("report:render", ReversedJson);
+        let payload = serde_json::json!({ "id": 7 });
+
+        let report = codecs.encode("report:render", &payload).unwrap();
+        assert_eq!(report.codec, "reversed");
+        assert_eq!(codecs.decode(report.codec, &report.bytes).unwrap(), payload);
+
+        let email = codecs.encode("email:send", &payload).unwrap();
+        assert_eq!(email.codec, "json");
+        assert_eq!(&email.bytes[..], br#"{"id":7}"#);
+    }
+
+    #[test]
+    fn test_decode_follows_the_writing_codec() {
+        let old = PayloadCodecs::new().with_default(ReversedJson);
+        let written = old
+            .encode("email:send", &serde_json::json!([1, 2]))
+            .unwrap();
+
+        // JSON is always decodable; a retired codec needs `with_decoder`
+        let new = PayloadCodecs::new();
+        let err = new.decode(written.codec, &written.bytes).unwrap_err();
+        assert!(err.to_string().contains("unknown payload codec: reversed"));
+
+        let new = new.with_decoder(ReversedJson);
+        assert_eq!(
+            new.decode(written.codec, &written.bytes).unwrap(),
+            serde_json::json!([1, 2])
+        );
+        assert_eq!(new.codec_for("email:send").name(), "json");
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_round_trip() {
+        let payload = serde_json::json!({ "to": "a@b.c", "retries": 3, "tags": ["x"] });
+        let bytes = MessagePackCodec.encode(&payload).unwrap();
+        assert_eq!(MessagePackCodec.decode(&bytes).unwrap(), payload);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_round_trip() {
+        let payload = serde_json::json!({ "to": "a@b.c", "retries": 3, "tags": ["x"] });
+        let bytes = CborCodec.encode(&payload).unwrap();
+        assert_eq!(CborCodec.decode(&bytes).unwrap(), payload);
+    }
+}
@@ -19,7 +19,7 @@ mod tests {
     use anyhow::Result;
 
     use crate::bus::EventBus;
-    use crate::core::{Command, Event};
+    use crate::core::Command;
     use crate::dispatch::Dispatcher;
     use crate::effect_impl::{Effect, EffectContext};
     use crate::engine::InflightTracker;
@@ -313,6 +313,7 @@ mod tests {
 
         let executed = Arc::new(Mutex::new(Vec::new()));
         let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
         let dispatcher =
             Dispatcher::new(TestDeps, bus).with_effect::<BatchCommand, _>(FailingEffect {
                 executed: executed.clone(),
@@ -350,7 +351,23 @@ mod tests {
         assert!(err_msg.contains("command 2 failed"));
 
         // Commands 3 and 4 were never executed (fail-fast from default execute_batch)
-        let executed = executed.lock().unwrap();
-        assert_eq!(*executed, vec![0, 1, 2]);
+        assert_eq!(*executed.lock().unwrap(), vec![0, 1, 2]);
+        // Nor were the events of commands 0 and 1 emitted
+        assert!(receiver.try_recv().is_err());
+
+        // A batch that succeeds emits every event, in order
+        let batch: Vec<Box<dyn crate::core::AnyCommand>> = (5..7)
+            .map(|id| {
+                Box::new(BatchCommand {
+                    id,
+                    should_fail: false,
+                }) as Box<dyn crate::core::AnyCommand>
+            })
+            .collect();
+        dispatcher.dispatch(batch).await.unwrap();
+        let ids: Vec<usize> = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|envelope| envelope.downcast_ref::<BatchEvent>().unwrap().id)
+            .collect();
+        assert_eq!(ids, vec![5, 6]);
     }
 }
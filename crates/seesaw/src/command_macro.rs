@@ -37,3 +37,102 @@ macro_rules! auto_serialize {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Command, ExecutionMode};
+    use chrono::{DateTime, Utc};
+    use seesaw_macros::SeesawCommand;
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, Serialize, SeesawCommand)]
+    #[command(
+        mode = "background",
+        job_type = "email:send",
+        max_retries = 5,
+        job_priority = 10,
+        version = 2,
+        idempotency_key = "email:{user_id}:{template}"
+    )]
+    struct SendEmail {
+        user_id: Uuid,
+        template: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, SeesawCommand)]
+    #[command(mode = "scheduled", run_at = "remind_at", job_type = "reminder:send")]
+    struct SendReminder {
+        remind_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug, Clone, Serialize, SeesawCommand)]
+    #[command(mode = "recurring", cron = "0 9 * * MON", job_type = "digest:weekly")]
+    struct SendDigest;
+
+    #[derive(Debug, Clone, SeesawCommand)]
+    #[command(priority = 2, independent)]
+    struct LookupUser;
+
+    #[test]
+    fn test_derive_background_command() {
+        let user_id = Uuid::new_v4();
+        let cmd = SendEmail {
+            user_id,
+            template: "welcome".to_string(),
+        };
+
+        assert_eq!(cmd.execution_mode(), ExecutionMode::Background);
+        let spec = cmd.job_spec().unwrap();
+        assert_eq!(spec.job_type, "email:send");
+        assert_eq!(spec.max_retries, 5);
+        assert_eq!(spec.priority, 10);
+        assert_eq!(spec.version, 2);
+        assert_eq!(
+            spec.idempotency_key,
+            Some(format!("email:{}:welcome", user_id))
+        );
+        assert_eq!(
+            cmd.serialize_to_json().unwrap(),
+            serde_json::json!({ "user_id": user_id, "template": "welcome" })
+        );
+        assert_eq!(cmd.priority(), 0);
+        assert!(!cmd.independent());
+    }
+
+    #[test]
+    fn test_derive_scheduled_command() {
+        let remind_at = Utc::now();
+        let cmd = SendReminder { remind_at };
+
+        assert_eq!(
+            cmd.execution_mode(),
+            ExecutionMode::Scheduled { run_at: remind_at }
+        );
+        let spec = cmd.job_spec().unwrap();
+        assert_eq!(spec.job_type, "reminder:send");
+        assert_eq!(spec.max_retries, 3);
+        assert!(spec.idempotency_key.is_none());
+    }
+
+    #[test]
+    fn test_derive_recurring_command() {
+        assert_eq!(
+            SendDigest.execution_mode(),
+            ExecutionMode::Recurring {
+                cron: "0 9 * * MON",
+                tz: "UTC",
+            }
+        );
+        assert_eq!(SendDigest.job_spec().unwrap().job_type, "digest:weekly");
+    }
+
+    #[test]
+    fn test_derive_inline_command() {
+        assert_eq!(LookupUser.execution_mode(), ExecutionMode::Inline);
+        assert!(LookupUser.job_spec().is_none());
+        assert!(LookupUser.serialize_to_json().is_none());
+        assert_eq!(LookupUser.priority(), 2);
+        assert!(LookupUser.independent());
+    }
+}
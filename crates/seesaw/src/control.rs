@@ -0,0 +1,90 @@
+//! Control channel for changing a running runtime's machines and effects.
+//!
+//! `EngineHandle::add_machine` and friends do not touch the runtime's state
+//! directly: they send a [`WiringChange`] to the runtime loop, which applies
+//! it between ticks and replies once it has. A tick therefore never sees a
+//! half-applied change, and every event the runtime takes from the bus after
+//! the reply is decided with the new wiring.
+
+use std::any::{Any, TypeId};
+
+use anyhow::Result;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::dispatch::Dispatcher;
+use crate::error::SeesawError;
+use crate::machine::MachineRunner;
+
+/// Adds an effect to the dispatcher of a runtime whose dependencies are `D`.
+pub(crate) type AddEffect<D> = Box<dyn FnOnce(&Dispatcher<D>) -> Result<()> + Send>;
+
+/// A change to a running runtime's wiring.
+pub(crate) enum WiringChange {
+    /// Insert a machine, after machines with the same or higher priority.
+    AddMachine(Box<MachineRunner>),
+    /// Remove every machine of a type.
+    RemoveMachine(TypeId),
+    /// Add an effect. `add` is an [`AddEffect`] for `deps_type`, which only
+    /// a runtime with those dependencies can apply.
+    AddEffect {
+        add: Box<dyn Any + Send>,
+        deps_type: &'static str,
+    },
+    /// Remove an effect added with `AddEffect`.
+    RemoveEffect(TypeId),
+}
+
+/// A change waiting for the runtime loop, with where to send the outcome.
+pub(crate) struct ControlRequest {
+    pub(crate) change: WiringChange,
+    /// Number of machines or effects added or removed, or why the change
+    /// was refused.
+    pub(crate) done: oneshot::Sender<Result<usize>>,
+}
+
+/// Sending half of a runtime's control channel, held by engine handles.
+#[derive(Clone)]
+pub(crate) struct RuntimeControl {
+    tx: mpsc::UnboundedSender<ControlRequest>,
+}
+
+impl RuntimeControl {
+    /// Create a control channel.
+    pub(crate) fn channel() -> (Self, ControlReceiver) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx }, ControlReceiver { rx: Some(rx) })
+    }
+
+    /// Send `change` to the runtime loop and wait until it is applied.
+    ///
+    /// Fails with [`SeesawError::EngineStopped`] if the runtime is not
+    /// running, or stops before getting to the change.
+    pub(crate) async fn apply(&self, change: WiringChange) -> Result<usize> {
+        let (done, applied) = oneshot::channel();
+        self.tx
+            .send(ControlRequest { change, done })
+            .map_err(|_| SeesawError::EngineStopped)?;
+        applied.await.map_err(|_| SeesawError::EngineStopped)?
+    }
+}
+
+/// Receiving half of a runtime's control channel.
+///
+/// The default receiver has no senders, for runtimes run without an engine.
+#[derive(Default)]
+pub(crate) struct ControlReceiver {
+    rx: Option<mpsc::UnboundedReceiver<ControlRequest>>,
+}
+
+impl ControlReceiver {
+    /// Wait for the next change. Never completes once every sender is gone.
+    pub(crate) async fn recv(&mut self) -> ControlRequest {
+        if let Some(rx) = &mut self.rx {
+            if let Some(request) = rx.recv().await {
+                return request;
+            }
+            self.rx = None;
+        }
+        std::future::pending().await
+    }
+}
@@ -20,10 +20,13 @@
 use std::any::{Any, TypeId};
 use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use crate::metadata::Metadata;
+
 /// Job specification for background and scheduled commands.
 ///
 /// Commands that use `ExecutionMode::Background` or `ExecutionMode::Scheduled`
@@ -71,6 +74,10 @@ pub struct JobSpec {
     /// Payload schema version for backward compatibility.
     /// Versioning/migration logic belongs in the job worker, not here.
     pub version: i32,
+
+    /// How long a claim on this job holds before it is considered abandoned,
+    /// in milliseconds. `None` uses the store's default lease.
+    pub lease_ms: Option<i64>,
 }
 
 impl JobSpec {
@@ -86,6 +93,7 @@ impl JobSpec {
             max_retries: 3,
             priority: 0,
             version: 1,
+            lease_ms: None,
         }
     }
 
@@ -112,6 +120,13 @@ impl JobSpec {
         self.version = v;
         self
     }
+
+    /// Set the lease a worker gets when claiming this job, overriding the
+    /// store's default. Heartbeats extend it by the same amount.
+    pub fn with_lease_ms(mut self, ms: i64) -> Self {
+        self.lease_ms = Some(ms);
+        self
+    }
 }
 
 /// Correlation ID for tracking related events and commands.
@@ -215,8 +230,15 @@ impl fmt::Display for CorrelationId {
 /// - The correlation ID for tracking related work
 /// - The type ID for filtering by machines
 /// - The event payload
+/// - The causation depth (hop count)
+/// - Request-scoped [`Metadata`] (tenant, user, locale, ...)
 ///
 /// Domain event enums remain clean - correlation is transport-level metadata.
+///
+/// The payload is shared, not copied: cloning an envelope (once per bus
+/// subscriber) clones an `Arc`. Machines receive `&Event` and taps receive
+/// the shared `Arc`, so fanning a large event out to many machines and taps
+/// keeps one copy in memory.
 #[derive(Clone)]
 pub struct EventEnvelope {
     /// Correlation ID for tracking related work
@@ -225,6 +247,14 @@ pub struct EventEnvelope {
     pub type_id: TypeId,
     /// The actual event payload
     pub payload: Arc<dyn Any + Send + Sync>,
+    /// Causation depth: 0 for events emitted from outside the runtime, one
+    /// more than the triggering event for events produced by inline effects.
+    pub hops: u32,
+    /// Request-scoped context, inherited by events produced by inline effects.
+    pub metadata: Metadata,
+    /// When the envelope was last sent on a bus, or created if it has not
+    /// been sent yet. Event TTLs are measured from here.
+    pub emitted_at: Instant,
 }
 
 impl EventEnvelope {
@@ -234,27 +264,53 @@ impl EventEnvelope {
             cid,
             type_id: TypeId::of::<E>(),
             payload: Arc::new(event),
+            hops: 0,
+            metadata: Metadata::default(),
+            emitted_at: Instant::now(),
         }
     }
 
-    /// Create a new event envelope from a raw UUID (for internal use).
-    pub(crate) fn new_with_uuid<E: Any + Send + Sync + 'static>(cid: Uuid, event: E) -> Self {
+    /// Create an envelope with a new random correlation ID.
+    pub fn new_random<E: Any + Send + Sync + 'static>(event: E) -> Self {
+        Self::new(CorrelationId::new(), event)
+    }
+
+    /// Create an envelope around an already shared event, without copying it.
+    pub fn from_arc<E: Any + Send + Sync + 'static>(cid: CorrelationId, event: Arc<E>) -> Self {
         Self {
-            cid: CorrelationId::from(cid),
+            cid,
             type_id: TypeId::of::<E>(),
-            payload: Arc::new(event),
+            payload: event,
+            hops: 0,
+            metadata: Metadata::default(),
+            emitted_at: Instant::now(),
         }
     }
 
-    /// Create an envelope with a new random correlation ID.
-    pub fn new_random<E: Any + Send + Sync + 'static>(event: E) -> Self {
-        Self::new(CorrelationId::new(), event)
+    /// Set the causation depth.
+    pub fn with_hops(mut self, hops: u32) -> Self {
+        self.hops = hops;
+        self
+    }
+
+    /// Set the request-scoped metadata.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
     }
 
     /// Downcast the payload to a concrete event type.
     pub fn downcast_ref<E: Any>(&self) -> Option<&E> {
         self.payload.downcast_ref()
     }
+
+    /// Downcast the payload to a shared handle on a concrete event type.
+    ///
+    /// Unlike cloning the result of [`downcast_ref`](Self::downcast_ref),
+    /// this keeps the event alive without copying it.
+    pub fn downcast_arc<E: Any + Send + Sync>(&self) -> Option<Arc<E>> {
+        self.payload.clone().downcast().ok()
+    }
 }
 
 impl std::fmt::Debug for EventEnvelope {
@@ -262,6 +318,8 @@ impl std::fmt::Debug for EventEnvelope {
         f.debug_struct("EventEnvelope")
             .field("cid", &self.cid)
             .field("type_id", &self.type_id)
+            .field("hops", &self.hops)
+            .field("metadata", &self.metadata)
             .finish_non_exhaustive()
     }
 }
@@ -457,6 +515,50 @@ pub trait Command: Any + Send + Sync + 'static {
     fn serialize_to_json(&self) -> Option<serde_json::Value> {
         None
     }
+
+    /// Dispatch priority for inline commands: higher runs sooner.
+    ///
+    /// Only takes effect when the runtime has more than one priority lane
+    /// (see `Runtime::with_priority_lanes`); values past the last lane are
+    /// clamped to it. Defaults to `0`, the lowest lane.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// impl Command for LookupUserCommand {
+    ///     // Answers an HTTP request - jump ahead of bulk reindexing
+    ///     fn priority(&self) -> u8 {
+    ///         1
+    ///     }
+    /// }
+    /// ```
+    fn priority(&self) -> u8 {
+        0
+    }
+
+    /// Whether this command's effect may run concurrently with others.
+    ///
+    /// When every command in an inline batch is independent, the dispatcher
+    /// executes them concurrently rather than through
+    /// [`Effect::execute_batch`](crate::Effect::execute_batch), and reports
+    /// which ones failed with [`BatchOutcome::Concurrent`](crate::BatchOutcome::Concurrent).
+    /// Batches whose commands are all independent also run alongside other
+    /// such batches decided in the same tick; a batch with any dependent
+    /// command waits for everything dispatched before it. Defaults to `false`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// impl Command for NotifyCommand {
+    ///     // Notifications share no state - fan them out
+    ///     fn independent(&self) -> bool {
+    ///         true
+    ///     }
+    /// }
+    /// ```
+    fn independent(&self) -> bool {
+        false
+    }
 }
 
 /// Execution mode for commands.
@@ -512,6 +614,43 @@ pub enum ExecutionMode {
         /// The time at which to execute the command.
         run_at: DateTime<Utc>,
     },
+
+    /// Register the command to run periodically.
+    ///
+    /// The command is handed to the job queue's recurring-job support (see
+    /// [`JobQueue::schedule_recurring`](crate::JobQueue::schedule_recurring)),
+    /// which runs it at every occurrence of `cron` in time zone `tz`. Job
+    /// queues without recurring jobs reject it.
+    ///
+    /// **Note**: Commands using this mode must provide a [`JobSpec`] via
+    /// [`Command::job_spec`] and must be serializable. Give the spec an
+    /// idempotency key so dispatching the command again updates the
+    /// schedule instead of adding a second one.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// impl Command for WeeklyDigest {
+    ///     fn execution_mode(&self) -> ExecutionMode {
+    ///         ExecutionMode::Recurring {
+    ///             cron: "0 9 * * MON",
+    ///             tz: "Europe/Berlin",
+    ///         }
+    ///     }
+    ///
+    ///     fn job_spec(&self) -> Option<JobSpec> {
+    ///         Some(JobSpec::new("digest:weekly").with_idempotency_key("digest:weekly"))
+    ///     }
+    /// }
+    /// ```
+    Recurring {
+        /// Five-field cron expression (minute, hour, day of month, month,
+        /// day of week).
+        cron: &'static str,
+        /// IANA time zone the expression is evaluated in, e.g. `"UTC"` or
+        /// `"America/New_York"`.
+        tz: &'static str,
+    },
 }
 
 /// Type-erased command trait for internal use.
@@ -529,6 +668,12 @@ pub trait AnyCommand: Send + Sync {
     /// Serialize the command to JSON for job queue persistence.
     fn get_serialize_to_json(&self) -> Option<serde_json::Value>;
 
+    /// Returns the dispatch priority for inline commands.
+    fn get_priority(&self) -> u8;
+
+    /// Returns whether the command may run concurrently with others.
+    fn is_independent(&self) -> bool;
+
     /// Returns the TypeId of this command.
     fn command_type_id(&self) -> std::any::TypeId;
 
@@ -558,6 +703,14 @@ impl<C: Command> AnyCommand for C {
         Command::serialize_to_json(self)
     }
 
+    fn get_priority(&self) -> u8 {
+        Command::priority(self)
+    }
+
+    fn is_independent(&self) -> bool {
+        Command::independent(self)
+    }
+
     fn command_type_id(&self) -> std::any::TypeId {
         std::any::TypeId::of::<C>()
     }
@@ -767,7 +920,7 @@ mod tests {
     fn test_event_is_any() {
         let event = TestEvent { value: 42 };
         let any: &dyn Any = &event;
-        assert!(any.downcast_ref::<TestEvent>().is_some());
+        assert_eq!(any.downcast_ref::<TestEvent>().map(|e| e.value), Some(42));
     }
 
     #[test]
@@ -827,6 +980,7 @@ mod tests {
         assert_eq!(spec.max_retries, 3);
         assert_eq!(spec.priority, 0);
         assert_eq!(spec.version, 1);
+        assert_eq!(spec.lease_ms, None);
     }
 
     #[test]
@@ -879,6 +1033,13 @@ mod tests {
         assert_eq!(spec.version, 3);
     }
 
+    #[test]
+    fn test_job_spec_with_lease_ms() {
+        let spec = JobSpec::new("report:render").with_lease_ms(30 * 60 * 1000);
+
+        assert_eq!(spec.lease_ms, Some(1_800_000));
+    }
+
     #[test]
     fn test_job_spec_builder_chaining() {
         let spec = JobSpec::new("complex:job")
@@ -1094,6 +1255,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_execution_mode_recurring_ne_different_tz() {
+        assert_ne!(
+            ExecutionMode::Recurring {
+                cron: "0 9 * * *",
+                tz: "UTC",
+            },
+            ExecutionMode::Recurring {
+                cron: "0 9 * * *",
+                tz: "Europe/Berlin",
+            }
+        );
+    }
+
     #[test]
     fn test_execution_mode_debug() {
         let debug = format!("{:?}", ExecutionMode::Background);
@@ -1119,6 +1294,19 @@ mod tests {
         reason: String,
     }
 
+    #[test]
+    fn test_envelope_from_arc_shares_the_event() {
+        let event = Arc::new(UserCreated {
+            user_id: Uuid::new_v4(),
+        });
+        let envelope = EventEnvelope::from_arc(CorrelationId::new(), event.clone());
+        assert_eq!(envelope.type_id, TypeId::of::<UserCreated>());
+
+        let shared = envelope.clone().downcast_arc::<UserCreated>().unwrap();
+        assert!(Arc::ptr_eq(&shared, &event));
+        assert!(envelope.downcast_arc::<UserDeleted>().is_none());
+    }
+
     #[test]
     fn test_envelope_match_event() {
         let user_id = Uuid::new_v4();
@@ -1208,7 +1396,7 @@ mod tests {
 
         let result: Option<Result<Uuid, &str>> = EnvelopeMatch::new(&envelope)
             .try_match(|e: &UserCreated| Some(Ok(e.user_id)))
-            .or_try(|e: &UserDeleted| Some(Err("deleted")))
+            .or_try(|_: &UserDeleted| Some(Err("deleted")))
             .result();
 
         assert!(matches!(result, Some(Err("deleted"))));
@@ -0,0 +1,347 @@
+//! Cost accounting - what each command type and workflow spent.
+//!
+//! Effects that call metered APIs report what a call cost with
+//! [`EffectContext::record_cost`](crate::EffectContext::record_cost), in
+//! whatever units they are billed in: tokens, cents, API calls. A
+//! [`CostLedger`] given to the dispatcher adds each report to a running
+//! total per command type and per correlation ID, so the spend of one
+//! workflow can be read back once it finishes:
+//!
+//! ```ignore
+//! let ledger = CostLedger::new();
+//! let handle = EngineBuilder::new(deps)
+//!     .with_effect::<SummarizeCommand, _>(SummarizeEffect)
+//!     .with_cost_ledger(ledger.clone())
+//!     .build()
+//!     .start();
+//!
+//! // In the effect
+//! ctx.record_cost("input_tokens", response.usage.input_tokens as f64);
+//! ctx.record_cost("cents", price_cents(&response.usage));
+//!
+//! // After the workflow
+//! let spent = handle.costs().unwrap().correlation_total(cid, "cents");
+//! ```
+//!
+//! Totals are kept in memory: per command type for the life of the ledger,
+//! per correlation for the last [`DEFAULT_COST_CAPACITY`] correlations, the
+//! oldest forgotten first. To persist costs, give the ledger a [`CostSink`]:
+//! it receives every report as a [`CostRecord`].
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::core::CorrelationId;
+
+/// Correlations a ledger keeps totals for by default.
+pub const DEFAULT_COST_CAPACITY: usize = 1024;
+
+/// One cost reported by an effect.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostRecord {
+    /// The correlation the effect ran for; nil outside correlated dispatch.
+    pub correlation_id: Uuid,
+    /// Type name of the command the effect executed.
+    pub command_type: &'static str,
+    /// What was spent, such as `"tokens"` or `"cents"`.
+    pub unit: String,
+    pub amount: f64,
+    /// When the cost was reported.
+    pub at: DateTime<Utc>,
+}
+
+/// The sum of the costs reported in one unit by one command type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostTotal {
+    /// Type name of the command.
+    pub command_type: &'static str,
+    pub unit: String,
+    pub amount: f64,
+    /// Number of reports summed.
+    pub reports: u64,
+}
+
+/// Where reported costs are sent, to persist them.
+///
+/// The sink is called synchronously on the effect's task, so it should hand
+/// the record off rather than do IO; [`channel`](Self::channel) does that.
+#[derive(Clone)]
+pub struct CostSink {
+    send: Arc<dyn Fn(CostRecord) + Send + Sync>,
+}
+
+impl CostSink {
+    /// Call `f` with every reported cost.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(CostRecord) + Send + Sync + 'static,
+    {
+        Self { send: Arc::new(f) }
+    }
+
+    /// A sink that forwards costs to the returned receiver.
+    ///
+    /// Records are dropped once the receiver is dropped.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<CostRecord>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sink = Self::new(move |record| {
+            let _ = tx.send(record);
+        });
+        (sink, rx)
+    }
+}
+
+impl std::fmt::Debug for CostSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CostSink").finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Ledger
+// =============================================================================
+
+/// Totals of reported costs, shared between a dispatcher and its handles.
+///
+/// Cloning is cheap; clones record into and read from the same totals.
+#[derive(Clone)]
+pub struct CostLedger {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    capacity: usize,
+    sink: Option<CostSink>,
+    totals: Mutex<Totals>,
+}
+
+/// Running sums keyed by command type and unit.
+type Sums = HashMap<(&'static str, String), (f64, u64)>;
+
+#[derive(Default)]
+struct Totals {
+    by_type: Sums,
+    by_cid: HashMap<CorrelationId, Sums>,
+    /// Correlations in the order they were first seen.
+    order: VecDeque<CorrelationId>,
+}
+
+impl CostLedger {
+    /// Create a ledger keeping totals for the last
+    /// [`DEFAULT_COST_CAPACITY`] correlations.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_COST_CAPACITY)
+    }
+
+    /// Create a ledger keeping totals for the last `capacity` correlations.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                capacity: capacity.max(1),
+                sink: None,
+                totals: Mutex::new(Totals::default()),
+            }),
+        }
+    }
+
+    /// Send every reported cost to `sink` as well.
+    ///
+    /// Call on a new ledger: totals recorded so far, and clones handed out
+    /// before, are not carried over.
+    pub fn with_sink(self, sink: CostSink) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                capacity: self.inner.capacity,
+                sink: Some(sink),
+                totals: Mutex::new(Totals::default()),
+            }),
+        }
+    }
+
+    /// Totals per command type and unit, since the ledger was created.
+    pub fn totals(&self) -> Vec<CostTotal> {
+        collect(&self.inner.totals.lock().unwrap().by_type)
+    }
+
+    /// Total spent in `unit` by `command_type`, matched against the full
+    /// or the short type name.
+    pub fn total(&self, command_type: &str, unit: &str) -> f64 {
+        self.totals()
+            .iter()
+            .filter(|total| total.unit == unit && type_matches(total.command_type, command_type))
+            .map(|total| total.amount)
+            .sum()
+    }
+
+    /// Totals per command type and unit for `cid`, if it is still recorded.
+    pub fn correlation_totals(&self, cid: CorrelationId) -> Option<Vec<CostTotal>> {
+        let totals = self.inner.totals.lock().unwrap();
+        totals.by_cid.get(&cid).map(collect)
+    }
+
+    /// Total spent in `unit` by every command run for `cid`.
+    pub fn correlation_total(&self, cid: CorrelationId, unit: &str) -> f64 {
+        self.correlation_totals(cid)
+            .unwrap_or_default()
+            .iter()
+            .filter(|total| total.unit == unit)
+            .map(|total| total.amount)
+            .sum()
+    }
+
+    /// Correlations currently recorded, oldest first.
+    pub fn correlations(&self) -> Vec<CorrelationId> {
+        self.inner
+            .totals
+            .lock()
+            .unwrap()
+            .order
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Forget every total.
+    pub fn clear(&self) {
+        let mut totals = self.inner.totals.lock().unwrap();
+        *totals = Totals::default();
+    }
+
+    /// Add `amount` of `unit` spent by `command_type` for `cid`.
+    pub(crate) fn record(
+        &self,
+        cid: CorrelationId,
+        command_type: &'static str,
+        unit: &str,
+        amount: f64,
+    ) {
+        {
+            let mut totals = self.inner.totals.lock().unwrap();
+            add(&mut totals.by_type, command_type, unit, amount);
+            if cid != CorrelationId::NONE {
+                if !totals.by_cid.contains_key(&cid) {
+                    if totals.order.len() >= self.inner.capacity {
+                        if let Some(oldest) = totals.order.pop_front() {
+                            totals.by_cid.remove(&oldest);
+                        }
+                    }
+                    totals.order.push_back(cid);
+                }
+                add(totals.by_cid.entry(cid).or_default(), command_type, unit, amount);
+            }
+        }
+
+        if let Some(sink) = &self.inner.sink {
+            (sink.send)(CostRecord {
+                correlation_id: cid.into_inner(),
+                command_type,
+                unit: unit.to_string(),
+                amount,
+                at: Utc::now(),
+            });
+        }
+    }
+}
+
+impl Default for CostLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CostLedger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CostLedger")
+            .field("capacity", &self.inner.capacity)
+            .field("sink", &self.inner.sink.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+fn add(sums: &mut Sums, command_type: &'static str, unit: &str, amount: f64) {
+    let sum = sums
+        .entry((command_type, unit.to_string()))
+        .or_insert((0.0, 0));
+    sum.0 += amount;
+    sum.1 += 1;
+}
+
+/// `sums` as totals, sorted by command type and unit.
+fn collect(sums: &Sums) -> Vec<CostTotal> {
+    let mut totals: Vec<_> = sums
+        .iter()
+        .map(|(&(command_type, ref unit), &(amount, reports))| CostTotal {
+            command_type,
+            unit: unit.clone(),
+            amount,
+            reports,
+        })
+        .collect();
+    totals.sort_by(|a, b| (a.command_type, &a.unit).cmp(&(b.command_type, &b.unit)));
+    totals
+}
+
+/// Whether the type name `full` is `name`, or ends with `::name`.
+fn type_matches(full: &str, name: &str) -> bool {
+    full == name || full.strip_suffix(name).is_some_and(|prefix| prefix.ends_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals_per_command_type_and_correlation() {
+        let ledger = CostLedger::new();
+        let (first, second) = (CorrelationId::new(), CorrelationId::new());
+
+        ledger.record(first, "app::Summarize", "tokens", 100.0);
+        ledger.record(first, "app::Summarize", "tokens", 50.0);
+        ledger.record(first, "app::Translate", "tokens", 20.0);
+        ledger.record(second, "app::Summarize", "tokens", 5.0);
+        ledger.record(second, "app::Summarize", "cents", 0.25);
+
+        assert_eq!(ledger.total("Summarize", "tokens"), 155.0);
+        assert_eq!(ledger.total("app::Summarize", "cents"), 0.25);
+        assert_eq!(ledger.total("Summ", "tokens"), 0.0);
+        assert_eq!(ledger.correlation_total(first, "tokens"), 170.0);
+        assert_eq!(ledger.correlation_total(second, "tokens"), 5.0);
+
+        let totals = ledger.correlation_totals(first).unwrap();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].command_type, "app::Summarize");
+        assert_eq!(totals[0].reports, 2);
+    }
+
+    #[test]
+    fn test_oldest_correlations_are_forgotten() {
+        let ledger = CostLedger::with_capacity(2);
+        let cids: Vec<_> = (0..3).map(|_| CorrelationId::new()).collect();
+        for cid in &cids {
+            ledger.record(*cid, "Call", "calls", 1.0);
+        }
+
+        assert!(ledger.correlation_totals(cids[0]).is_none());
+        assert!(ledger.correlation_totals(cids[2]).is_some());
+        // Per-type totals are kept
+        assert_eq!(ledger.total("Call", "calls"), 3.0);
+    }
+
+    #[test]
+    fn test_sink_receives_every_record() {
+        let (sink, mut records) = CostSink::channel();
+        let ledger = CostLedger::new().with_sink(sink);
+        let cid = CorrelationId::new();
+
+        ledger.record(cid, "Call", "calls", 1.0);
+
+        let record = records.try_recv().unwrap();
+        assert_eq!(record.correlation_id, cid.into_inner());
+        assert_eq!(record.unit, "calls");
+    }
+}
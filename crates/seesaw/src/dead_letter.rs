@@ -0,0 +1,136 @@
+//! Dead letters - events that reached nobody.
+//!
+//! By default an event that no machine handles, or that is emitted while
+//! nothing is subscribed to the bus, disappears without a trace. A
+//! [`DeadLetterSink`] installed on the bus receives each such event with the
+//! reason it went undelivered, so miswired engines and missing handlers show
+//! up in production instead of silently doing nothing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let (sink, mut dead_letters) = DeadLetterSink::channel();
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_dead_letters(sink)
+//!     .build();
+//!
+//! tokio::spawn(async move {
+//!     while let Some(letter) = dead_letters.recv().await {
+//!         warn!(cid = %letter.envelope.cid, reason = ?letter.reason, "dead letter");
+//!     }
+//! });
+//! ```
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::core::EventEnvelope;
+
+/// Why an event was not delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// No machine or tap is registered for the event's type.
+    ///
+    /// Events consumed only by other bus subscribers, such as
+    /// `EngineHandle::request`, are reported too.
+    Unhandled,
+    /// The bus had no subscribers, e.g. the engine was not started yet or
+    /// has stopped.
+    NoSubscribers,
+    /// The bus was full and its backpressure policy refused the event
+    /// (`DropNewest`, `Error`, or `Block` from `try_emit_envelope` or a
+    /// synchronous emit outside a tokio runtime).
+    ///
+    /// Events overwritten under `DropOldest` are not recoverable and only
+    /// show up in `EventBus::stats`.
+    Rejected,
+    /// The event waited longer than its type's TTL (see
+    /// `EventBus::with_ttl`) before reaching the runtime, so no machine saw
+    /// it.
+    Expired,
+}
+
+/// An undelivered event and the reason it was not delivered.
+#[derive(Clone)]
+pub struct DeadLetter {
+    /// The event as it was emitted.
+    pub envelope: EventEnvelope,
+    /// Why it was not delivered.
+    pub reason: DeadLetterReason,
+}
+
+impl std::fmt::Debug for DeadLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("cid", &self.envelope.cid)
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where dead letters are sent.
+///
+/// The sink is called synchronously on the emitting task, so it should hand
+/// the letter off rather than do IO; [`channel`](Self::channel) does that.
+#[derive(Clone)]
+pub struct DeadLetterSink {
+    send: Arc<dyn Fn(DeadLetter) + Send + Sync>,
+}
+
+impl DeadLetterSink {
+    /// Call `f` with every dead letter.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(DeadLetter) + Send + Sync + 'static,
+    {
+        Self { send: Arc::new(f) }
+    }
+
+    /// A sink that forwards dead letters to the returned receiver.
+    ///
+    /// Letters are dropped once the receiver is dropped.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<DeadLetter>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sink = Self::new(move |letter| {
+            let _ = tx.send(letter);
+        });
+        (sink, rx)
+    }
+
+    /// Report `envelope` as undelivered.
+    pub(crate) fn send(&self, envelope: EventEnvelope, reason: DeadLetterReason) {
+        (self.send)(DeadLetter { envelope, reason });
+    }
+}
+
+impl std::fmt::Debug for DeadLetterSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeadLetterSink").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_forwards_letters() {
+        let (sink, mut rx) = DeadLetterSink::channel();
+
+        sink.send(EventEnvelope::new_random(7u32), DeadLetterReason::Unhandled);
+
+        let letter = rx.try_recv().unwrap();
+        assert_eq!(letter.reason, DeadLetterReason::Unhandled);
+        assert_eq!(letter.envelope.downcast_ref::<u32>(), Some(&7));
+    }
+
+    #[test]
+    fn test_channel_ignores_dropped_receiver() {
+        let (sink, rx) = DeadLetterSink::channel();
+        drop(rx);
+
+        sink.send(EventEnvelope::new_random(7u32), DeadLetterReason::Rejected);
+    }
+}
@@ -7,20 +7,38 @@
 
 use std::any::TypeId;
 use std::collections::HashMap;
+use std::future::Future;
 use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use futures::FutureExt;
+use tokio::sync::Semaphore;
 use uuid::Uuid;
 
 use crate::bus::EventBus;
-use crate::core::{AnyCommand, Command, CorrelationId, ExecutionMode, JobSpec};
-use crate::effect_impl::{AnyEffect, Effect, EffectContext, EffectWrapper};
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerPolicy, CircuitState};
+use crate::codec::{EncodedPayload, PayloadCodecs};
+use crate::cost::CostLedger;
+use crate::core::{AnyCommand, Command, CorrelationId, EventEnvelope, ExecutionMode, JobSpec};
+use crate::effect_impl::{AnyEffect, Effect, EffectContext, EffectFn, EffectWrapper, FnEffect};
 use crate::engine::{InflightBatch, InflightTracker};
-use crate::error::{BatchOutcome, CommandFailed, SeesawError};
-use tracing::error;
+use crate::error::{
+    BatchOutcome, Categorizable, CommandExpired, CommandFailed, ErrorCategories, SeesawError,
+};
+use crate::health::HealthMonitor;
+use crate::metadata::Metadata;
+use crate::metrics;
+use crate::middleware::{EffectCall, EffectMiddleware, EffectOutput, Next};
+use crate::rate_limit::{RateLimitPolicy, RateLimiter};
+use crate::redact::Redactor;
+use crate::routing::{CommandVariant, VariantRouter};
+use crate::spans;
+use tracing::{error, warn, Instrument};
 
 /// Job queue trait for background and scheduled command execution.
 ///
@@ -90,6 +108,72 @@ pub trait JobQueue: Send + Sync + 'static {
         spec: JobSpec,
         run_at: DateTime<Utc>,
     ) -> Result<Uuid>;
+
+    /// Enqueue several commands for immediate background execution.
+    ///
+    /// Called by runtimes with micro-batching (`Runtime::with_micro_batching`)
+    /// for the background commands decided in one tick. Returns one result
+    /// per job, in order. The default enqueues them one at a time; override
+    /// it to insert them in one round trip.
+    async fn enqueue_batch(&self, jobs: Vec<(serde_json::Value, JobSpec)>) -> Vec<Result<Uuid>> {
+        let mut ids = Vec::with_capacity(jobs.len());
+        for (payload, spec) in jobs {
+            ids.push(self.enqueue(payload, spec).await);
+        }
+        ids
+    }
+
+    /// Enqueue a command payload encoded by a [`PayloadCodec`](crate::PayloadCodec).
+    ///
+    /// Called instead of [`enqueue`](Self::enqueue) when the dispatcher has
+    /// [payload codecs](Dispatcher::with_payload_codecs). Persist the codec
+    /// name with the bytes so the store can decode them. The default rejects
+    /// encoded payloads.
+    async fn enqueue_encoded(&self, payload: EncodedPayload, spec: JobSpec) -> Result<Uuid> {
+        let _ = spec;
+        Err(anyhow!(
+            "job queue does not accept {}-encoded payloads",
+            payload.codec
+        ))
+    }
+
+    /// Schedule a command payload encoded by a [`PayloadCodec`](crate::PayloadCodec).
+    ///
+    /// The encoded counterpart of [`schedule`](Self::schedule). The default
+    /// rejects encoded payloads.
+    async fn schedule_encoded(
+        &self,
+        payload: EncodedPayload,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let _ = (spec, run_at);
+        Err(anyhow!(
+            "job queue does not accept {}-encoded payloads",
+            payload.codec
+        ))
+    }
+
+    /// Register a command to run at every occurrence of `cron` in time zone
+    /// `tz`, for [`ExecutionMode::Recurring`].
+    ///
+    /// Returns the ID of the recurring schedule. A schedule registered again
+    /// with the same idempotency key should be updated rather than
+    /// duplicated. The default rejects recurring commands, for queues
+    /// without recurring jobs.
+    async fn schedule_recurring(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        cron: &str,
+        tz: &str,
+    ) -> Result<Uuid> {
+        let _ = (payload, cron, tz);
+        Err(anyhow!(
+            "job queue does not support recurring jobs (job type {})",
+            spec.job_type
+        ))
+    }
 }
 
 /// A no-op job queue that rejects all background and scheduled commands.
@@ -115,6 +199,23 @@ impl JobQueue for NoOpJobQueue {
             "scheduled commands not supported: no job queue configured"
         ))
     }
+
+    async fn enqueue_encoded(&self, _payload: EncodedPayload, _spec: JobSpec) -> Result<Uuid> {
+        Err(anyhow!(
+            "background commands not supported: no job queue configured"
+        ))
+    }
+
+    async fn schedule_encoded(
+        &self,
+        _payload: EncodedPayload,
+        _spec: JobSpec,
+        _run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        Err(anyhow!(
+            "scheduled commands not supported: no job queue configured"
+        ))
+    }
 }
 
 /// Command dispatcher for routing commands to effects.
@@ -138,9 +239,49 @@ impl JobQueue for NoOpJobQueue {
 /// ```
 pub struct Dispatcher<D> {
     effects: HashMap<TypeId, Box<dyn AnyEffect<D>>>,
+    /// Effects added to a running engine, consulted after `effects`.
+    added_effects: DashMap<TypeId, Arc<dyn AnyEffect<D>>>,
+    /// Per-command-type execution budgets for inline effects.
+    timeouts: HashMap<TypeId, EffectTimeout>,
+    /// Per-command-type circuit breakers for inline effects.
+    breakers: HashMap<TypeId, Arc<CircuitBreaker>>,
+    /// Per-command-type caps on concurrent inline executions.
+    limits: HashMap<TypeId, ConcurrencyLimit>,
+    /// Per-command-type token buckets for inline executions.
+    rate_limits: HashMap<TypeId, Arc<RateLimiter>>,
+    /// Token bucket shared by every inline execution.
+    global_rate_limit: Option<Arc<RateLimiter>>,
+    /// Middleware wrapping every inline effect execution, outermost first.
+    middleware: Vec<Arc<dyn EffectMiddleware<D>>>,
+    /// Number of inline effect executions that panicked.
+    panics: Arc<AtomicU64>,
+    /// Execution counters reported by `EngineHandle::health`.
+    health: Arc<HealthMonitor>,
     deps: Arc<D>,
     bus: EventBus,
     job_queue: Arc<dyn JobQueue>,
+    /// Encodes background and scheduled payloads, when configured.
+    payload_codecs: Option<PayloadCodecs>,
+    /// Domain error types recognized when effect failures are reported.
+    error_categories: ErrorCategories,
+    /// Scrubs effect failures before they are reported.
+    redactor: Redactor,
+    /// Where effects report their costs, when configured.
+    costs: Option<CostLedger>,
+}
+
+/// Execution budget registered for a single command type.
+#[derive(Debug, Clone, Copy)]
+struct EffectTimeout {
+    duration: Duration,
+    type_name: &'static str,
+}
+
+/// Concurrency cap registered for a single command type.
+#[derive(Debug, Clone)]
+struct ConcurrencyLimit {
+    max: usize,
+    permits: Arc<Semaphore>,
 }
 
 impl<D: Send + Sync + 'static> Dispatcher<D> {
@@ -150,9 +291,22 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     pub fn new(deps: D, bus: EventBus) -> Self {
         Self {
             effects: HashMap::new(),
+            added_effects: DashMap::new(),
+            timeouts: HashMap::new(),
+            breakers: HashMap::new(),
+            limits: HashMap::new(),
+            rate_limits: HashMap::new(),
+            global_rate_limit: None,
+            middleware: Vec::new(),
+            panics: Arc::new(AtomicU64::new(0)),
+            health: Arc::new(HealthMonitor::default()),
             deps: Arc::new(deps),
             bus,
             job_queue: Arc::new(NoOpJobQueue),
+            payload_codecs: None,
+            error_categories: ErrorCategories::default(),
+            redactor: Redactor::default(),
+            costs: None,
         }
     }
 
@@ -162,9 +316,22 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     pub fn from_arc(deps: Arc<D>, bus: EventBus) -> Self {
         Self {
             effects: HashMap::new(),
+            added_effects: DashMap::new(),
+            timeouts: HashMap::new(),
+            breakers: HashMap::new(),
+            limits: HashMap::new(),
+            rate_limits: HashMap::new(),
+            global_rate_limit: None,
+            middleware: Vec::new(),
+            panics: Arc::new(AtomicU64::new(0)),
+            health: Arc::new(HealthMonitor::default()),
             deps,
             bus,
             job_queue: Arc::new(NoOpJobQueue),
+            payload_codecs: None,
+            error_categories: ErrorCategories::default(),
+            redactor: Redactor::default(),
+            costs: None,
         }
     }
 
@@ -172,9 +339,22 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     pub fn with_job_queue(deps: D, bus: EventBus, job_queue: Arc<dyn JobQueue>) -> Self {
         Self {
             effects: HashMap::new(),
+            added_effects: DashMap::new(),
+            timeouts: HashMap::new(),
+            breakers: HashMap::new(),
+            limits: HashMap::new(),
+            rate_limits: HashMap::new(),
+            global_rate_limit: None,
+            middleware: Vec::new(),
+            panics: Arc::new(AtomicU64::new(0)),
+            health: Arc::new(HealthMonitor::default()),
             deps: Arc::new(deps),
             bus,
             job_queue,
+            payload_codecs: None,
+            error_categories: ErrorCategories::default(),
+            redactor: Redactor::default(),
+            costs: None,
         }
     }
 
@@ -186,9 +366,22 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     ) -> Self {
         Self {
             effects: HashMap::new(),
+            added_effects: DashMap::new(),
+            timeouts: HashMap::new(),
+            breakers: HashMap::new(),
+            limits: HashMap::new(),
+            rate_limits: HashMap::new(),
+            global_rate_limit: None,
+            middleware: Vec::new(),
+            panics: Arc::new(AtomicU64::new(0)),
+            health: Arc::new(HealthMonitor::default()),
             deps,
             bus,
             job_queue,
+            payload_codecs: None,
+            error_categories: ErrorCategories::default(),
+            redactor: Redactor::default(),
+            costs: None,
         }
     }
 
@@ -250,6 +443,504 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
         self
     }
 
+    /// Register an async closure as the effect for command type `C`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an effect is already registered for `C`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect_fn::<PingCommand, _>(|cmd, _ctx| async move {
+    ///         Ok(PingEvent::Pong { seq: cmd.seq })
+    ///     });
+    /// ```
+    pub fn with_effect_fn<C, F>(self, f: F) -> Self
+    where
+        C: Command,
+        F: EffectFn<C, D>,
+    {
+        self.with_effect::<C, FnEffect<F>>(FnEffect::new(f))
+    }
+
+    /// Register an effect for one variant of enum command `C`.
+    ///
+    /// `V` is the command type wrapped by the variant (see
+    /// [`CommandVariant`]). Each variant can have its own effect; dispatching
+    /// a variant without one fails with `NoEffectRegistered`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `C` already has a whole-enum effect, or `V` already has an
+    /// effect.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+    ///     .with_effect_for::<DeckCommand, Summarize, _>(SummarizeEffect);
+    /// ```
+    pub fn with_effect_for<C, V, E>(self, effect: E) -> Self
+    where
+        C: CommandVariant<V>,
+        V: Command,
+        E: Effect<V, D>,
+    {
+        self.try_with_effect_for::<C, V, E>(effect)
+            .unwrap_or_else(|e| {
+                panic!("{}", e);
+            })
+    }
+
+    /// Register an effect for one variant of enum command `C`, returning an
+    /// error if it conflicts with an existing registration.
+    ///
+    /// This is the non-panicking version of `with_effect_for`.
+    pub fn try_with_effect_for<C, V, E>(mut self, effect: E) -> Result<Self>
+    where
+        C: CommandVariant<V>,
+        V: Command,
+        E: Effect<V, D>,
+    {
+        let entry = self
+            .effects
+            .entry(TypeId::of::<C>())
+            .or_insert_with(|| Box::new(VariantRouter::<C, D>::new()));
+        let router = entry
+            .as_any_mut()
+            .and_then(|any| any.downcast_mut::<VariantRouter<C, D>>())
+            .ok_or(SeesawError::EffectAlreadyRegistered {
+                type_name: std::any::type_name::<C>(),
+            })?;
+        router.add::<V, E>(effect)?;
+        Ok(self)
+    }
+
+    /// Add an effect for command type `C` while the dispatcher is shared.
+    ///
+    /// Fails if `C` already has an effect, registered or added.
+    pub(crate) fn add_effect<C, E>(&self, effect: E) -> Result<()>
+    where
+        C: Command,
+        E: Effect<C, D>,
+    {
+        let type_id = TypeId::of::<C>();
+        let already_registered = || SeesawError::EffectAlreadyRegistered {
+            type_name: std::any::type_name::<C>(),
+        };
+        if self.effects.contains_key(&type_id) {
+            return Err(already_registered().into());
+        }
+        match self.added_effects.entry(type_id) {
+            dashmap::mapref::entry::Entry::Occupied(_) => Err(already_registered().into()),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(Arc::new(EffectWrapper::new(effect)));
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove the effect added for `type_id` with `add_effect`.
+    ///
+    /// Effects registered before the dispatcher was built stay in place.
+    /// Returns whether an effect was removed.
+    pub(crate) fn remove_effect(&self, type_id: TypeId) -> bool {
+        self.added_effects.remove(&type_id).is_some()
+    }
+
+    /// Look up the effect for `type_id`, registered or added.
+    fn effect(&self, type_id: TypeId) -> Option<EffectRef<'_, D>> {
+        match self.effects.get(&type_id) {
+            Some(effect) => Some(EffectRef::Registered(effect.as_ref())),
+            None => self
+                .added_effects
+                .get(&type_id)
+                .map(|effect| EffectRef::Added(effect.clone())),
+        }
+    }
+
+    /// Set an execution budget for the effect handling command type `C`.
+    ///
+    /// If an inline execution of the effect takes longer than `timeout`, the
+    /// effect future is dropped (cancelling any pending IO at its next await
+    /// point) and the dispatch fails with [`SeesawError::EffectTimeout`]. With
+    /// correlation tracking this surfaces as a `CommandFailed` event and an
+    /// error from `emit_and_await`, instead of a hung call holding the
+    /// inflight count up forever.
+    ///
+    /// The budget covers one dispatch call: a single `execute`, or the whole
+    /// `execute_batch` when commands are batched. Background and scheduled
+    /// commands are not affected - the job queue owns their execution.
+    ///
+    /// May be called before or after the effect is registered.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect::<FetchCommand, _>(FetchEffect)
+    ///     .with_effect_timeout::<FetchCommand>(Duration::from_secs(10));
+    /// ```
+    pub fn with_effect_timeout<C: Command>(mut self, timeout: Duration) -> Self {
+        self.timeouts.insert(
+            TypeId::of::<C>(),
+            EffectTimeout {
+                duration: timeout,
+                type_name: std::any::type_name::<C>(),
+            },
+        );
+        self
+    }
+
+    /// Guard the effect handling command type `C` with a circuit breaker.
+    ///
+    /// The breaker tracks the outcomes of inline executions and, once the
+    /// failure rate over its window reaches the policy threshold, rejects new
+    /// executions with [`SeesawError::CircuitOpen`] without calling the
+    /// effect. After the open duration a single probe is let through to
+    /// decide whether to close again. Transitions are emitted on the bus as
+    /// `CircuitBreakerOpened` / `CircuitBreakerClosed` events.
+    ///
+    /// A batch counts as one call. Background and scheduled commands are not
+    /// affected.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect::<ChargeCommand, _>(ChargeEffect)
+    ///     .with_circuit_breaker::<ChargeCommand>(CircuitBreakerPolicy::new());
+    /// ```
+    pub fn with_circuit_breaker<C: Command>(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.breakers.insert(
+            TypeId::of::<C>(),
+            Arc::new(CircuitBreaker::new(std::any::type_name::<C>(), policy)),
+        );
+        self
+    }
+
+    /// Cap the number of concurrent inline executions of the effect handling
+    /// command type `C`.
+    ///
+    /// Once `max` executions are in flight, further dispatches of `C` wait
+    /// for a slot instead of calling the effect, so a burst of events cannot
+    /// fan out into thousands of simultaneous calls to the same downstream.
+    /// The wait is not counted against the effect timeout, and an open
+    /// circuit breaker still fails fast without waiting.
+    ///
+    /// A batch counts as one execution. Background and scheduled commands are
+    /// not affected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect::<FetchCommand, _>(FetchEffect)
+    ///     .with_concurrency_limit::<FetchCommand>(16);
+    /// ```
+    pub fn with_concurrency_limit<C: Command>(mut self, max: usize) -> Self {
+        assert!(max > 0, "concurrency limit must be at least 1");
+        self.limits.insert(
+            TypeId::of::<C>(),
+            ConcurrencyLimit {
+                max,
+                permits: Arc::new(Semaphore::new(max)),
+            },
+        );
+        self
+    }
+
+    /// Rate limit inline executions of the effect handling command type `C`.
+    ///
+    /// Each execution takes a token from a bucket refilled at the policy's
+    /// rate. When the bucket is empty the execution is delayed until a token
+    /// is available, or - with [`ThrottleMode::Reject`](crate::ThrottleMode::Reject) -
+    /// fails with [`SeesawError::Throttled`] and a `Throttled` event is emitted.
+    ///
+    /// Rate limiting is applied before the circuit breaker, so rejected
+    /// executions do not count as failures against it. A batch counts as one
+    /// execution. Background and scheduled commands are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_effect::<GeocodeCommand, _>(GeocodeEffect)
+    ///     .with_rate_limit::<GeocodeCommand>(RateLimitPolicy::per_second(10));
+    /// ```
+    pub fn with_rate_limit<C: Command>(mut self, policy: RateLimitPolicy) -> Self {
+        self.rate_limits
+            .insert(TypeId::of::<C>(), Arc::new(RateLimiter::new(policy)));
+        self
+    }
+
+    /// Report effect failures caused by an `E` with `E`'s category,
+    /// retryability and safe message in their `CommandFailed` event.
+    ///
+    /// The error is found anywhere in the failure's `source()` chain, so
+    /// effects can add `anyhow` context freely. See [`ErrorCategories`].
+    pub fn with_error_category<E: Categorizable + Send + Sync + 'static>(mut self) -> Self {
+        self.error_categories.register::<E>();
+        self
+    }
+
+    /// Scrub effect failures with `redactor` before they are reported.
+    ///
+    /// Applies to the failures recorded in audit records and traces, to
+    /// the `safe_message` of `CommandFailed` events, and to the errors a
+    /// [`JobWorker`](crate::JobWorker) running on this dispatcher persists.
+    /// Defaults to [`Redactor::new`]. See [`Redactor`].
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Get the redactor failures are scrubbed with.
+    pub fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
+
+    /// Add the costs effects report with
+    /// [`EffectContext::record_cost`] to `ledger`.
+    ///
+    /// Without a ledger, reported costs are discarded. See [`CostLedger`].
+    pub fn with_cost_ledger(mut self, ledger: CostLedger) -> Self {
+        self.costs = Some(ledger);
+        self
+    }
+
+    /// Get the ledger effects report costs to, if any.
+    pub fn cost_ledger(&self) -> Option<&CostLedger> {
+        self.costs.as_ref()
+    }
+
+    /// Rate limit all inline executions, across every command type.
+    ///
+    /// Applied before any per-type limit: an execution needs a token from
+    /// both. See [`with_rate_limit`](Self::with_rate_limit).
+    pub fn with_global_rate_limit(mut self, policy: RateLimitPolicy) -> Self {
+        self.global_rate_limit = Some(Arc::new(RateLimiter::new(policy)));
+        self
+    }
+
+    /// Encode background and scheduled payloads with `codecs`.
+    ///
+    /// Each payload is encoded by the codec for its job type and handed to
+    /// [`JobQueue::enqueue_encoded`] / [`JobQueue::schedule_encoded`] instead
+    /// of the JSON methods, so the job queue must support them.
+    pub fn with_payload_codecs(mut self, codecs: PayloadCodecs) -> Self {
+        self.payload_codecs = Some(codecs);
+        self
+    }
+
+    /// Add a middleware that runs around every inline effect execution.
+    ///
+    /// Middleware runs in registration order, the first registered being the
+    /// outermost. It wraps the circuit breaker and timeout, so it sees their
+    /// errors like any other effect failure. An error returned by a
+    /// middleware fails the execution exactly like an effect error.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let dispatcher = Dispatcher::new(deps, bus)
+    ///     .with_middleware(LogMiddleware)
+    ///     .with_middleware(MetricsMiddleware::new(registry))
+    ///     .with_effect::<FetchCommand, _>(FetchEffect);
+    /// ```
+    pub fn with_middleware<M: EffectMiddleware<D>>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Describe an execution for the middleware chain.
+    fn effect_call(
+        &self,
+        effect: &dyn AnyEffect<D>,
+        batch_size: usize,
+        cid: CorrelationId,
+    ) -> EffectCall<D> {
+        EffectCall::new(
+            effect.command_type_name(),
+            batch_size,
+            cid,
+            self.deps.clone(),
+        )
+    }
+
+    /// Count a caught effect panic and convert it into an error.
+    fn effect_panicked(
+        &self,
+        effect: &dyn AnyEffect<D>,
+        cid: CorrelationId,
+        panic_info: Box<dyn std::any::Any + Send>,
+    ) -> anyhow::Error {
+        self.panics.fetch_add(1, Ordering::Relaxed);
+        let type_name = effect.command_type_name();
+        self.health.record_effect(type_name, false);
+        let message = extract_panic_message(&panic_info);
+        error!(%cid, command = type_name, panic = %message, "effect panicked");
+        SeesawError::EffectPanicked { type_name, message }.into()
+    }
+
+    /// `ctx`, executing `effect`'s command type and reporting costs to the
+    /// ledger, if configured.
+    fn command_context(
+        &self,
+        ctx: EffectContext<D>,
+        effect: &dyn AnyEffect<D>,
+    ) -> EffectContext<D> {
+        ctx.for_command(effect.command_type_name(), self.costs.clone())
+    }
+
+    /// The `CommandFailed` event reporting `error` from an execution of
+    /// `effect`, categorized by the registered error types.
+    fn command_failed(
+        &self,
+        effect: &dyn AnyEffect<D>,
+        error: &anyhow::Error,
+        cid: CorrelationId,
+    ) -> CommandFailed {
+        let mut failed = CommandFailed::categorized(
+            error,
+            effect.command_type_name(),
+            cid,
+            &self.error_categories,
+        );
+        failed.safe_message = self.redactor.redact(&failed.safe_message);
+        failed
+    }
+
+    /// Run an effect execution in its `seesaw.effect` span, recording it for
+    /// health reporting.
+    async fn run_effect(
+        &self,
+        type_id: TypeId,
+        call: EffectCall<D>,
+        execution: impl Future<Output = Result<Vec<EventEnvelope>>> + Send,
+    ) -> Result<Vec<EventEnvelope>> {
+        let _batch = self.health.begin_batch();
+        let command_type = call.command_type();
+        let span = spans::effect(command_type, call.correlation_id(), call.batch_size());
+        let started = Instant::now();
+        let result = self
+            .run_chain(type_id, call, execution)
+            .instrument(span.clone())
+            .await;
+        spans::record_effect(&span, &result);
+        metrics::effect_finished(command_type, started.elapsed(), result.is_ok());
+        self.health.record_effect(command_type, result.is_ok());
+        result
+    }
+
+    /// Run an effect execution through the middleware chain.
+    ///
+    /// The innermost layer applies rate limits, the circuit breaker, the
+    /// concurrency limit and the execution budget, in that order.
+    async fn run_chain(
+        &self,
+        type_id: TypeId,
+        call: EffectCall<D>,
+        execution: impl Future<Output = Result<Vec<EventEnvelope>>> + Send,
+    ) -> Result<Vec<EventEnvelope>> {
+        let guarded = self.run_throttled(
+            type_id,
+            call.command_type(),
+            call.correlation_id(),
+            self.run_guarded(type_id, execution),
+        );
+        if self.middleware.is_empty() {
+            return guarded.await;
+        }
+
+        let innermost = Box::pin(async move { guarded.await.map(EffectOutput::new) });
+        Next::new(&self.middleware, &call, innermost)
+            .run()
+            .await
+            .map(EffectOutput::into_envelopes)
+    }
+
+    /// Run an effect future once the global and per-type rate limits allow it.
+    async fn run_throttled<T>(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        cid: CorrelationId,
+        execution: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if let Some(limiter) = &self.global_rate_limit {
+            limiter.acquire(type_name, cid, &self.bus).await?;
+        }
+        if let Some(limiter) = self.rate_limits.get(&type_id) {
+            limiter.acquire(type_name, cid, &self.bus).await?;
+        }
+        execution.await
+    }
+
+    /// Run an effect future behind its circuit breaker, concurrency limit and
+    /// execution budget.
+    async fn run_guarded<T>(
+        &self,
+        type_id: TypeId,
+        execution: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(breaker) = self.breakers.get(&type_id) else {
+            return self.run_limited(type_id, execution).await;
+        };
+
+        let permit = breaker.try_acquire(&self.bus)?;
+        let result = self.run_limited(type_id, execution).await;
+        permit.record(&result);
+        result
+    }
+
+    /// Run an effect future once a concurrency slot for its command type is free.
+    async fn run_limited<T>(
+        &self,
+        type_id: TypeId,
+        execution: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(limit) = self.limits.get(&type_id) else {
+            return self.run_with_timeout(type_id, execution).await;
+        };
+
+        let _slot = limit
+            .permits
+            .acquire()
+            .await
+            .expect("concurrency semaphore is never closed");
+        self.run_with_timeout(type_id, execution).await
+    }
+
+    /// Run an effect future under the budget configured for its command type.
+    ///
+    /// Without a configured budget the future runs to completion.
+    async fn run_with_timeout<T>(
+        &self,
+        type_id: TypeId,
+        execution: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(limit) = self.timeouts.get(&type_id).copied() else {
+            return execution.await;
+        };
+
+        match tokio::time::timeout(limit.duration, execution).await {
+            Ok(result) => result,
+            Err(_) => Err(SeesawError::EffectTimeout {
+                type_name: limit.type_name,
+                duration: limit.duration,
+            }
+            .into()),
+        }
+    }
+
     /// Dispatch a batch of commands of the same type.
     ///
     /// Routes the commands to the appropriate effect based on their type.
@@ -263,6 +954,9 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     /// - `Ok(())` if all commands succeeded
     /// - `Err` if any command failed (error contains context about which failed)
     ///
+    /// A panicking effect is caught and returned as
+    /// [`SeesawError::EffectPanicked`].
+    ///
     /// # Panics
     ///
     /// Panics if commands have different `TypeId`s (runtime guarantees this).
@@ -272,26 +966,55 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
         }
 
         let type_id = commands[0].command_type_id();
-        let effect = self.effects.get(&type_id).ok_or_else(|| {
-            SeesawError::NoEffectRegistered {
+        let effect = self
+            .effect(type_id)
+            .ok_or(SeesawError::NoEffectRegistered {
                 type_id,
                 type_name: "unknown", // TypeId doesn't preserve type name at runtime
-            }
-        })?;
+            })?;
+        let effect = &effect;
 
-        let ctx = EffectContext::new(self.deps.clone(), self.bus.clone());
+        let ctx = self.command_context(
+            EffectContext::new(self.deps.clone(), self.bus.clone()),
+            effect.as_ref(),
+        );
 
         if commands.len() == 1 {
             // Single command: direct path, no batch overhead
             let command = commands.into_iter().next().unwrap();
-            let envelope = effect.execute_any(command.into_any(), ctx).await?;
+            let envelopes = AssertUnwindSafe(
+                self.run_effect(
+                    type_id,
+                    self.effect_call(effect.as_ref(), 1, CorrelationId::NONE),
+                    effect
+                        .execute_any(command.into_any(), ctx)
+                        .map(|result| result.map(|envelope| vec![envelope])),
+                ),
+            )
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|panic_info| {
+                Err(self.effect_panicked(effect.as_ref(), CorrelationId::NONE, panic_info))
+            })?;
             // Runtime is the sole emitter
-            self.bus.emit_envelope(envelope);
+            for envelope in envelopes {
+                self.bus.emit_envelope(envelope);
+            }
             Ok(())
         } else {
             // Batch: delegate to execute_any_batch
+            let batch_size = commands.len();
             let commands_any: Vec<_> = commands.into_iter().map(|c| c.into_any()).collect();
-            let envelopes = effect.execute_any_batch(commands_any, ctx).await?;
+            let envelopes = AssertUnwindSafe(self.run_effect(
+                type_id,
+                self.effect_call(effect.as_ref(), batch_size, CorrelationId::NONE),
+                effect.execute_any_batch(commands_any, ctx),
+            ))
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|panic_info| {
+                Err(self.effect_panicked(effect.as_ref(), CorrelationId::NONE, panic_info))
+            })?;
             // Runtime is the sole emitter - emit all returned events
             for envelope in envelopes {
                 self.bus.emit_envelope(envelope);
@@ -303,7 +1026,8 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     /// Dispatch a single command.
     ///
     /// Convenience method that wraps the command in a vec.
-    /// Handles execution mode routing (inline, background, scheduled).
+    /// Handles execution mode routing (inline, background, scheduled,
+    /// recurring).
     ///
     /// # Errors
     ///
@@ -318,37 +1042,55 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
         match mode {
             ExecutionMode::Inline => self.dispatch(vec![command]).await,
             ExecutionMode::Background => {
+                let (payload, spec) = background_job(command.as_ref())?;
+                match &self.payload_codecs {
+                    Some(codecs) => {
+                        let payload = codecs.encode(spec.job_type, &payload)?;
+                        self.job_queue.enqueue_encoded(payload, spec).await
+                    }
+                    None => self.job_queue.enqueue(payload, spec).await,
+                }
+                .map(|_| ())
+            }
+            ExecutionMode::Scheduled { run_at } => {
                 let spec = command.get_job_spec().ok_or_else(|| {
                     anyhow!(
-                        "command with TypeId {:?} uses Background execution mode but did not provide job_spec()",
+                        "command with TypeId {:?} uses Scheduled execution mode but did not provide job_spec()",
                         command.command_type_id()
                     )
                 })?;
                 let payload = command.get_serialize_to_json().ok_or_else(|| {
                     anyhow!(
-                        "command with TypeId {:?} uses Background execution mode but could not be serialized. \
+                        "command with TypeId {:?} uses Scheduled execution mode but could not be serialized. \
                          Add #[derive(Serialize, Deserialize)] to your command struct.",
                         command.command_type_id()
                     )
                 })?;
-                self.job_queue.enqueue(payload, spec).await.map(|_| ())
+                match &self.payload_codecs {
+                    Some(codecs) => {
+                        let payload = codecs.encode(spec.job_type, &payload)?;
+                        self.job_queue.schedule_encoded(payload, spec, run_at).await
+                    }
+                    None => self.job_queue.schedule(payload, spec, run_at).await,
+                }
+                .map(|_| ())
             }
-            ExecutionMode::Scheduled { run_at } => {
+            ExecutionMode::Recurring { cron, tz } => {
                 let spec = command.get_job_spec().ok_or_else(|| {
                     anyhow!(
-                        "command with TypeId {:?} uses Scheduled execution mode but did not provide job_spec()",
+                        "command with TypeId {:?} uses Recurring execution mode but did not provide job_spec()",
                         command.command_type_id()
                     )
                 })?;
                 let payload = command.get_serialize_to_json().ok_or_else(|| {
                     anyhow!(
-                        "command with TypeId {:?} uses Scheduled execution mode but could not be serialized. \
+                        "command with TypeId {:?} uses Recurring execution mode but could not be serialized. \
                          Add #[derive(Serialize, Deserialize)] to your command struct.",
                         command.command_type_id()
                     )
                 })?;
                 self.job_queue
-                    .schedule(payload, spec, run_at)
+                    .schedule_recurring(payload, spec, cron, tz)
                     .await
                     .map(|_| ())
             }
@@ -370,28 +1112,66 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     ///
     /// # Panic Safety
     ///
-    /// If execution panics before completion, the `InflightBatch` logs a warning
-    /// on drop. The timeout backstop in `emit_and_await` handles cleanup.
+    /// A panicking effect is caught and treated like any other effect failure
+    /// with [`SeesawError::EffectPanicked`]: the batch completes, the panic
+    /// message is logged and recorded for `emit_and_await`, `CommandFailed` is
+    /// emitted (with a generic safe message), and
+    /// [`effect_panics`](Self::effect_panics) is incremented. The runtime keeps
+    /// processing events.
+    ///
+    /// # Effect Timeouts
+    ///
+    /// If a budget was set with [`with_effect_timeout`](Self::with_effect_timeout),
+    /// an effect that exceeds it is cancelled and treated like any other effect
+    /// failure: the batch completes, the error is recorded, and `CommandFailed`
+    /// is emitted.
+    ///
+    /// # Circuit Breakers
+    ///
+    /// If a breaker was registered with
+    /// [`with_circuit_breaker`](Self::with_circuit_breaker) and is open, the
+    /// effect is not called; the batch fails fast with
+    /// [`SeesawError::CircuitOpen`] through the same path.
     pub async fn dispatch_with_correlation(
         &self,
         commands: Vec<Box<dyn AnyCommand>>,
         cid: CorrelationId,
         inflight: Option<&Arc<InflightTracker>>,
     ) -> Result<()> {
+        self.dispatch_at_depth(commands, cid, 0, None, &Metadata::default(), inflight)
+            .await
+            .map(|_| ())
+    }
+
+    /// Dispatch with correlation for commands decided from an event `hops`
+    /// deep in its causation chain, of type `event_type` when known.
+    ///
+    /// Events returned by the effect (and `CommandFailed`) are emitted one hop
+    /// deeper, carrying the event's `metadata`. An effect failure is reported through `CommandFailed` rather
+    /// than returned as an error; its raw message is returned as `Some`.
+    pub(crate) async fn dispatch_at_depth(
+        &self,
+        commands: Vec<Box<dyn AnyCommand>>,
+        cid: CorrelationId,
+        hops: u32,
+        event_type: Option<&'static str>,
+        metadata: &Metadata,
+        inflight: Option<&Arc<InflightTracker>>,
+    ) -> Result<Option<String>> {
         if commands.is_empty() {
-            return Ok(());
+            return Ok(None);
         }
 
         let batch_size = commands.len();
         let type_id = commands[0].command_type_id();
 
         let effect = self
-            .effects
-            .get(&type_id)
-            .ok_or_else(|| SeesawError::NoEffectRegistered {
+            .effect(type_id)
+            .ok_or(SeesawError::NoEffectRegistered {
                 type_id,
                 type_name: "unknown",
             })?;
+        let effect = &effect;
 
         // Create context with correlation ID for event propagation
         let ctx = EffectContext::with_correlation(
@@ -399,11 +1179,44 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
             self.bus.clone(),
             cid,
             inflight.cloned(),
-        );
+        )
+        .with_metadata(metadata.clone());
+        let ctx = self.command_context(ctx, effect.as_ref());
+        let caused =
+            |envelope: EventEnvelope| envelope.with_hops(hops + 1).with_metadata(metadata.clone());
+
+        // Nobody can use the result once the deadline has passed
+        if let Some(deadline) = metadata.deadline() {
+            let now = tokio::time::Instant::now();
+            if deadline <= now {
+                let command_type = effect.command_type_name();
+                let overdue = now - deadline;
+                warn!(%cid, command_type, batch_size, ?overdue, "deadline passed, skipping commands");
+                let expired = SeesawError::DeadlineExceeded {
+                    type_name: command_type,
+                    overdue,
+                };
+                let message = expired.to_string();
+                if let Some(tracker) = inflight {
+                    tracker.record_error(cid, expired.into());
+                }
+                self.bus.emit_envelope(caused(EventEnvelope::new(
+                    cid,
+                    CommandExpired {
+                        command_type,
+                        count: batch_size,
+                        overdue,
+                        cid,
+                    },
+                )));
+                return Ok(Some(message));
+            }
+        }
 
         // Use receipt pattern for batch tracking (if inflight tracker provided)
-        let batch: Option<InflightBatch> =
-            inflight.map(|tracker| tracker.begin_batch(cid, batch_size));
+        let batch: Option<InflightBatch> = inflight.map(|tracker| {
+            tracker.begin_described_batch(cid, batch_size, effect.command_type_name(), event_type)
+        });
 
         if commands.len() == 1 {
             // Single command: direct path
@@ -411,19 +1224,22 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
 
             // Wrap effect execution with panic catching
             // AssertUnwindSafe is required because effect/ctx are not UnwindSafe
-            let result = AssertUnwindSafe(effect.execute_any(command.into_any(), ctx))
-                .catch_unwind()
-                .await;
+            let result = AssertUnwindSafe(
+                self.run_effect(
+                    type_id,
+                    self.effect_call(effect.as_ref(), 1, cid),
+                    effect
+                        .execute_any(command.into_any(), ctx)
+                        .map(|result| result.map(|envelope| vec![envelope])),
+                ),
+            )
+            .catch_unwind()
+            .await;
 
             // Convert panic to error
-            let result = match result {
-                Ok(inner) => inner,
-                Err(panic_info) => {
-                    let panic_msg = extract_panic_message(&panic_info);
-                    error!(%cid, panic = %panic_msg, "effect panicked");
-                    Err(anyhow::anyhow!("effect panicked: {}", panic_msg))
-                }
-            };
+            let result = result.unwrap_or_else(|panic_info| {
+                Err(self.effect_panicked(effect.as_ref(), cid, panic_info))
+            });
 
             // Complete batch with synthetic outcome
             if let Some(batch) = batch {
@@ -439,10 +1255,12 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
             }
 
             match result {
-                Ok(envelope) => {
+                Ok(envelopes) => {
                     // Runtime is the sole emitter
-                    self.bus.emit_envelope(envelope);
-                    Ok(())
+                    for envelope in envelopes {
+                        self.bus.emit_envelope(caused(envelope));
+                    }
+                    Ok(None)
                 }
                 Err(e) => {
                     // Log raw error for developers (before sanitization)
@@ -455,29 +1273,88 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
 
                     // Emit sanitized CommandFailed event with same correlation ID
                     // so dispatch_request can match it
-                    let failed = CommandFailed::from_error(&e, "unknown", cid);
-                    self.bus.emit_with_correlation(failed, cid);
-                    Ok(())
+                    let failed = self.command_failed(effect.as_ref(), &e, cid);
+                    metrics::command_failed(effect.command_type_name());
+                    self.bus
+                        .emit_envelope(caused(EventEnvelope::new(cid, failed)));
+                    Ok(Some(self.redactor.redact(&e.to_string())))
                 }
             }
+        } else if commands.iter().all(|c| c.is_independent()) {
+            // Independent batch: execute each command concurrently
+            let results = futures::future::join_all(commands.into_iter().map(|command| {
+                let result = AssertUnwindSafe(
+                    self.run_effect(
+                        type_id,
+                        self.effect_call(effect.as_ref(), 1, cid),
+                        effect
+                            .execute_any(command.into_any(), ctx.clone())
+                            .map(|result| result.map(|envelope| vec![envelope])),
+                    ),
+                )
+                .catch_unwind();
+                async move {
+                    // Convert panic to error
+                    result.await.unwrap_or_else(|panic_info| {
+                        Err(self.effect_panicked(effect.as_ref(), cid, panic_info))
+                    })
+                }
+            }))
+            .await;
+
+            // Emit in batch order, once every command has run
+            let mut failed = Vec::new();
+            for (index, result) in results.into_iter().enumerate() {
+                match result {
+                    Ok(envelopes) => {
+                        for envelope in envelopes {
+                            self.bus.emit_envelope(caused(envelope));
+                        }
+                    }
+                    Err(e) => {
+                        error!(%cid, index, error = ?e, "independent effect failed");
+
+                        if let Some(tracker) = inflight {
+                            tracker.record_error(cid, anyhow::anyhow!("{}", e));
+                        }
+
+                        let failed_event = self.command_failed(effect.as_ref(), &e, cid);
+                        metrics::command_failed(effect.command_type_name());
+                        self.bus
+                            .emit_envelope(caused(EventEnvelope::new(cid, failed_event)));
+                        failed.push((index, e));
+                    }
+                }
+            }
+
+            let outcome = if failed.is_empty() {
+                BatchOutcome::Complete
+            } else {
+                BatchOutcome::Concurrent { failed }
+            };
+            let failure =
+                (!outcome.is_complete()).then(|| self.redactor.redact(&outcome.to_string()));
+            if let Some(batch) = batch {
+                batch.complete(outcome);
+            }
+            Ok(failure)
         } else {
             // Batch: delegate to execute_any_batch
             let commands_any: Vec<_> = commands.into_iter().map(|c| c.into_any()).collect();
 
             // Wrap effect execution with panic catching
-            let result = AssertUnwindSafe(effect.execute_any_batch(commands_any, ctx))
-                .catch_unwind()
-                .await;
+            let result = AssertUnwindSafe(self.run_effect(
+                type_id,
+                self.effect_call(effect.as_ref(), batch_size, cid),
+                effect.execute_any_batch(commands_any, ctx),
+            ))
+            .catch_unwind()
+            .await;
 
             // Convert panic to error
-            let result = match result {
-                Ok(inner) => inner,
-                Err(panic_info) => {
-                    let panic_msg = extract_panic_message(&panic_info);
-                    error!(%cid, panic = %panic_msg, "batch effect panicked");
-                    Err(anyhow::anyhow!("batch effect panicked: {}", panic_msg))
-                }
-            };
+            let result = result.unwrap_or_else(|panic_info| {
+                Err(self.effect_panicked(effect.as_ref(), cid, panic_info))
+            });
 
             // Complete batch with outcome based on success/failure
             if let Some(batch) = batch {
@@ -499,9 +1376,9 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
                 Ok(envelopes) => {
                     // Runtime is the sole emitter - emit all returned events
                     for envelope in envelopes {
-                        self.bus.emit_envelope(envelope);
+                        self.bus.emit_envelope(caused(envelope));
                     }
-                    Ok(())
+                    Ok(None)
                 }
                 Err(e) => {
                     // Log raw error for developers (before sanitization)
@@ -514,22 +1391,92 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
 
                     // Emit sanitized CommandFailed event with same correlation ID
                     // so dispatch_request can match it
-                    let failed = CommandFailed::from_error(&e, "unknown", cid);
-                    self.bus.emit_with_correlation(failed, cid);
-                    Ok(())
+                    let failed = self.command_failed(effect.as_ref(), &e, cid);
+                    metrics::command_failed(effect.command_type_name());
+                    self.bus
+                        .emit_envelope(caused(EventEnvelope::new(cid, failed)));
+                    Ok(Some(self.redactor.redact(&e.to_string())))
                 }
             }
         }
     }
 
+    /// Enqueue background commands together through
+    /// [`JobQueue::enqueue_batch`].
+    ///
+    /// Returns one result per command, in order. With payload codecs, each
+    /// command is encoded and enqueued on its own.
+    pub(crate) async fn enqueue_background(
+        &self,
+        commands: Vec<Box<dyn AnyCommand>>,
+    ) -> Vec<Result<()>> {
+        let mut results = Vec::with_capacity(commands.len());
+        if self.payload_codecs.is_some() {
+            for command in commands {
+                results.push(Some(self.dispatch_one(command).await));
+            }
+        } else {
+            let mut jobs = Vec::new();
+            let mut slots = Vec::new();
+            for command in commands {
+                match background_job(command.as_ref()) {
+                    Ok(job) => {
+                        slots.push(results.len());
+                        jobs.push(job);
+                        results.push(None);
+                    }
+                    Err(e) => results.push(Some(Err(e))),
+                }
+            }
+            let enqueued = self.job_queue.enqueue_batch(jobs).await;
+            for (slot, result) in slots.into_iter().zip(enqueued) {
+                results[slot] = Some(result.map(|_| ()));
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| Err(anyhow!("job queue returned fewer ids than jobs")))
+            })
+            .collect()
+    }
+
     /// Check if an effect is registered for a command type.
     pub fn has_effect<C: Command>(&self) -> bool {
-        self.effects.contains_key(&TypeId::of::<C>())
+        let type_id = TypeId::of::<C>();
+        self.effects.contains_key(&type_id) || self.added_effects.contains_key(&type_id)
     }
 
     /// Returns the number of registered effects.
     pub fn effect_count(&self) -> usize {
-        self.effects.len()
+        self.effects.len() + self.added_effects.len()
+    }
+
+    /// Get the execution budget configured for command type `C`, if any.
+    pub fn effect_timeout<C: Command>(&self) -> Option<Duration> {
+        self.timeouts.get(&TypeId::of::<C>()).map(|t| t.duration)
+    }
+
+    /// Get the concurrency limit configured for command type `C`, if any.
+    pub fn concurrency_limit<C: Command>(&self) -> Option<usize> {
+        self.limits.get(&TypeId::of::<C>()).map(|l| l.max)
+    }
+
+    /// Number of inline effect executions that panicked since the dispatcher
+    /// was created.
+    pub fn effect_panics(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    /// Get the current circuit breaker state for command type `C`, if one is
+    /// registered.
+    pub fn circuit_state<C: Command>(&self) -> Option<CircuitState> {
+        self.breakers.get(&TypeId::of::<C>()).map(|b| b.state())
+    }
+
+    /// Health counters shared with the runtime and engine handles.
+    pub(crate) fn health(&self) -> &Arc<HealthMonitor> {
+        &self.health
     }
 
     /// Get access to the dependencies.
@@ -543,6 +1490,24 @@ impl<D: Send + Sync + 'static> Dispatcher<D> {
     }
 }
 
+/// JSON payload and job spec of a background command.
+fn background_job(command: &dyn AnyCommand) -> Result<(serde_json::Value, JobSpec)> {
+    let spec = command.get_job_spec().ok_or_else(|| {
+        anyhow!(
+            "command with TypeId {:?} uses Background execution mode but did not provide job_spec()",
+            command.command_type_id()
+        )
+    })?;
+    let payload = command.get_serialize_to_json().ok_or_else(|| {
+        anyhow!(
+            "command with TypeId {:?} uses Background execution mode but could not be serialized. \
+             Add #[derive(Serialize, Deserialize)] to your command struct.",
+            command.command_type_id()
+        )
+    })?;
+    Ok((payload, spec))
+}
+
 /// Extract a human-readable message from a panic payload.
 fn extract_panic_message(panic_info: &Box<dyn std::any::Any + Send>) -> String {
     if let Some(s) = panic_info.downcast_ref::<&str>() {
@@ -557,14 +1522,44 @@ fn extract_panic_message(panic_info: &Box<dyn std::any::Any + Send>) -> String {
 impl<D> std::fmt::Debug for Dispatcher<D> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Dispatcher")
-            .field("effect_count", &self.effects.len())
+            .field(
+                "effect_count",
+                &(self.effects.len() + self.added_effects.len()),
+            )
             .finish_non_exhaustive()
     }
 }
 
+/// An effect found by [`Dispatcher::effect`].
+enum EffectRef<'a, D> {
+    /// Registered before the dispatcher was built.
+    Registered(&'a (dyn AnyEffect<D> + 'static)),
+    /// Added to a running engine; held so removal cannot pull it from
+    /// under a dispatch.
+    Added(Arc<dyn AnyEffect<D>>),
+}
+
+impl<D> AsRef<dyn AnyEffect<D>> for EffectRef<'_, D> {
+    fn as_ref(&self) -> &(dyn AnyEffect<D> + 'static) {
+        match self {
+            EffectRef::Registered(effect) => *effect,
+            EffectRef::Added(effect) => effect.as_ref(),
+        }
+    }
+}
+
+impl<D> std::ops::Deref for EffectRef<'_, D> {
+    type Target = dyn AnyEffect<D>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::SafeErrorCategory;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use uuid::Uuid;
 
@@ -625,6 +1620,25 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Clone, serde::Serialize)]
+    struct RecurringCommand;
+    impl Command for RecurringCommand {
+        fn execution_mode(&self) -> ExecutionMode {
+            ExecutionMode::Recurring {
+                cron: "0 9 * * MON",
+                tz: "Europe/Berlin",
+            }
+        }
+
+        fn job_spec(&self) -> Option<JobSpec> {
+            Some(JobSpec::new("test:recurring"))
+        }
+
+        fn serialize_to_json(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self).ok()
+        }
+    }
+
     #[derive(Debug, Clone, PartialEq)]
     struct TestEvent {
         message: String,
@@ -756,9 +1770,11 @@ mod tests {
     }
 
     // Mock job queue for testing
+    type ScheduledLog = Arc<std::sync::Mutex<Vec<(String, DateTime<Utc>)>>>;
+
     struct MockJobQueue {
         enqueued: Arc<std::sync::Mutex<Vec<String>>>,
-        scheduled: Arc<std::sync::Mutex<Vec<(String, DateTime<Utc>)>>>,
+        scheduled: ScheduledLog,
     }
 
     #[async_trait::async_trait]
@@ -832,20 +1848,106 @@ mod tests {
             enqueued: enqueued.clone(),
             scheduled: scheduled.clone(),
         });
-
-        let bus = EventBus::new();
-        let dispatcher = Dispatcher::with_job_queue(TestDeps { value: 0 }, bus, job_queue);
-
-        let run_at = Utc::now() + chrono::Duration::hours(1);
+
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::with_job_queue(TestDeps { value: 0 }, bus, job_queue);
+
+        let run_at = Utc::now() + chrono::Duration::hours(1);
+        let cmd: Box<dyn AnyCommand> = Box::new(ScheduledCommand {
+            task: "reminder".to_string(),
+            run_at,
+        });
+        dispatcher.dispatch_one(cmd).await.unwrap();
+
+        let scheduled_items = scheduled.lock().unwrap();
+        assert_eq!(scheduled_items.len(), 1);
+        assert_eq!(scheduled_items[0].1, run_at);
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_recurring_rejected_without_recurring_jobs() {
+        let job_queue = Arc::new(MockJobQueue {
+            enqueued: Arc::new(std::sync::Mutex::new(Vec::new())),
+            scheduled: Arc::new(std::sync::Mutex::new(Vec::new())),
+        });
+        let dispatcher =
+            Dispatcher::with_job_queue(TestDeps { value: 0 }, EventBus::new(), job_queue);
+
+        let result = dispatcher.dispatch_one(Box::new(RecurringCommand)).await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("does not support recurring jobs"));
+    }
+
+    /// Job queue that only accepts encoded payloads.
+    #[derive(Default)]
+    struct EncodedJobQueue {
+        enqueued: std::sync::Mutex<Vec<EncodedPayload>>,
+    }
+
+    #[async_trait::async_trait]
+    impl JobQueue for EncodedJobQueue {
+        async fn enqueue(&self, _payload: serde_json::Value, _spec: JobSpec) -> Result<Uuid> {
+            unreachable!("payload codecs are configured")
+        }
+
+        async fn schedule(
+            &self,
+            _payload: serde_json::Value,
+            _spec: JobSpec,
+            _run_at: DateTime<Utc>,
+        ) -> Result<Uuid> {
+            unreachable!("payload codecs are configured")
+        }
+
+        async fn enqueue_encoded(&self, payload: EncodedPayload, _spec: JobSpec) -> Result<Uuid> {
+            self.enqueued.lock().unwrap().push(payload);
+            Ok(Uuid::new_v4())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_encodes_payloads_with_codecs() {
+        let job_queue = Arc::new(EncodedJobQueue::default());
+        let codecs = PayloadCodecs::new();
+        let dispatcher =
+            Dispatcher::with_job_queue(TestDeps { value: 0 }, EventBus::new(), job_queue.clone())
+                .with_payload_codecs(codecs.clone());
+
+        let cmd: Box<dyn AnyCommand> = Box::new(BackgroundCommand {
+            task: "process".to_string(),
+        });
+        dispatcher.dispatch_one(cmd).await.unwrap();
+
+        let enqueued = job_queue.enqueued.lock().unwrap().clone();
+        assert_eq!(enqueued[0].codec, "json");
+        assert_eq!(
+            codecs
+                .decode(enqueued[0].codec, &enqueued[0].bytes)
+                .unwrap(),
+            serde_json::json!({ "task": "process" })
+        );
+
+        // Queues that only take JSON reject encoded payloads by default
+        let scheduled = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let json_queue = Arc::new(MockJobQueue {
+            enqueued: Arc::new(std::sync::Mutex::new(Vec::new())),
+            scheduled: scheduled.clone(),
+        });
+        let dispatcher =
+            Dispatcher::with_job_queue(TestDeps { value: 0 }, EventBus::new(), json_queue)
+                .with_payload_codecs(codecs);
         let cmd: Box<dyn AnyCommand> = Box::new(ScheduledCommand {
             task: "reminder".to_string(),
-            run_at,
+            run_at: Utc::now(),
         });
-        dispatcher.dispatch_one(cmd).await.unwrap();
-
-        let scheduled_items = scheduled.lock().unwrap();
-        assert_eq!(scheduled_items.len(), 1);
-        assert_eq!(scheduled_items[0].1, run_at);
+        let err = dispatcher.dispatch_one(cmd).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("does not accept json-encoded payloads"));
+        assert!(scheduled.lock().unwrap().is_empty());
     }
 
     #[test]
@@ -1226,4 +2328,502 @@ mod tests {
             "dispatch_one() should enqueue background commands to job queue"
         );
     }
+
+    // Effect that panics on every call
+    struct PanickingEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<CreateCommand, TestDeps> for PanickingEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            cmd: CreateCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            panic!("cannot create {}", cmd.name);
+        }
+    }
+
+    // Effect that never finishes within any reasonable budget
+    struct HangingEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<CreateCommand, TestDeps> for HangingEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            _cmd: CreateCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(TestEvent {
+                message: "never".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_effect_timeout_aborts_dispatch() {
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(HangingEffect)
+            .with_effect_timeout::<CreateCommand>(Duration::from_millis(20));
+
+        assert_eq!(
+            dispatcher.effect_timeout::<CreateCommand>(),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(dispatcher.effect_timeout::<DeleteCommand>(), None);
+
+        let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "slow".to_string(),
+        });
+        let err = dispatcher.dispatch(vec![cmd]).await.unwrap_err();
+
+        match err.downcast_ref::<SeesawError>() {
+            Some(SeesawError::EffectTimeout {
+                type_name,
+                duration,
+            }) => {
+                assert!(type_name.contains("CreateCommand"));
+                assert_eq!(*duration, Duration::from_millis(20));
+            }
+            other => panic!("expected EffectTimeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_effect_timeout_emits_command_failed_and_releases_inflight() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let inflight = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect_timeout::<CreateCommand>(Duration::from_millis(20))
+            .with_effect::<CreateCommand, _>(HangingEffect);
+
+        let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "slow".to_string(),
+        });
+        dispatcher
+            .dispatch_with_correlation(vec![cmd], cid, Some(&inflight))
+            .await
+            .unwrap();
+
+        // Batch receipt was completed, so nothing is left pending
+        assert!(!inflight.has_pending_work(cid));
+
+        let envelope = receiver.recv().await.unwrap();
+        let failed = envelope.downcast_ref::<CommandFailed>().unwrap();
+        assert_eq!(failed.cid, cid);
+        assert_eq!(failed.safe_message, "Operation timed out");
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("name {0:?} is taken")]
+    struct NameTaken(String);
+
+    impl Categorizable for NameTaken {
+        fn category(&self) -> SafeErrorCategory {
+            SafeErrorCategory::Validation
+        }
+
+        fn safe_message(&self) -> std::borrow::Cow<'static, str> {
+            format!("Name {} is taken", self.0).into()
+        }
+    }
+
+    struct TakenEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<CreateCommand, TestDeps> for TakenEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            cmd: CreateCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            Err(anyhow::Error::new(NameTaken(cmd.name)).context("inserting user"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_failed_carries_registered_error_category() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(TakenEffect)
+            .with_error_category::<NameTaken>();
+
+        let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "ada".to_string(),
+        });
+        dispatcher
+            .dispatch_with_correlation(vec![cmd], CorrelationId::new(), None)
+            .await
+            .unwrap();
+
+        let envelope = receiver.recv().await.unwrap();
+        let failed = envelope.downcast_ref::<CommandFailed>().unwrap();
+        assert!(failed.command_type.contains("CreateCommand"));
+        assert_eq!(failed.category, SafeErrorCategory::Validation);
+        assert_eq!(failed.safe_message, "Name ada is taken");
+        assert!(!failed.retryable);
+    }
+
+    #[tokio::test]
+    async fn test_effect_panic_is_returned_as_error() {
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(PanickingEffect);
+
+        let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "boom".to_string(),
+        });
+        let err = dispatcher.dispatch(vec![cmd]).await.unwrap_err();
+
+        match err.downcast_ref::<SeesawError>() {
+            Some(SeesawError::EffectPanicked { type_name, message }) => {
+                assert!(type_name.contains("CreateCommand"));
+                assert_eq!(message, "cannot create boom");
+            }
+            other => panic!("expected EffectPanicked, got {:?}", other),
+        }
+        assert_eq!(dispatcher.effect_panics(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_effect_panic_emits_command_failed_and_releases_inflight() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let inflight = Arc::new(InflightTracker::new());
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(PanickingEffect);
+
+        for name in ["a", "b"] {
+            let cid = CorrelationId::new();
+            let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+                name: name.to_string(),
+            });
+            dispatcher
+                .dispatch_with_correlation(vec![cmd], cid, Some(&inflight))
+                .await
+                .unwrap();
+
+            assert!(!inflight.has_pending_work(cid));
+            let envelope = receiver.recv().await.unwrap();
+            let failed = envelope.downcast_ref::<CommandFailed>().unwrap();
+            assert_eq!(failed.cid, cid);
+            assert_eq!(failed.category, SafeErrorCategory::InternalError);
+        }
+
+        assert_eq!(dispatcher.effect_panics(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_effect_within_timeout_succeeds() {
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(CreateEffect {
+                call_count: call_count.clone(),
+            })
+            .with_effect_timeout::<CreateCommand>(Duration::from_secs(5));
+
+        let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "fast".to_string(),
+        });
+        dispatcher
+            .dispatch_with_correlation(vec![cmd], CorrelationId::new(), None)
+            .await
+            .unwrap();
+
+        let envelope = receiver.recv().await.unwrap();
+        assert_eq!(
+            envelope.downcast_ref::<TestEvent>().unwrap().message,
+            "created fast"
+        );
+        assert_eq!(call_count.load(Ordering::Relaxed), 1);
+    }
+
+    // Effect whose downstream is down: every call fails transiently
+    struct UnavailableEffect {
+        call_count: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Effect<CreateCommand, TestDeps> for UnavailableEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            _cmd: CreateCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            self.call_count.fetch_add(1, Ordering::Relaxed);
+            Err(anyhow!("503 service unavailable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast_once_open() {
+        use crate::circuit_breaker::{CircuitBreakerOpened, CircuitBreakerPolicy, CircuitState};
+
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let cid = CorrelationId::new();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(UnavailableEffect {
+                call_count: call_count.clone(),
+            })
+            .with_circuit_breaker::<CreateCommand>(
+                CircuitBreakerPolicy::new()
+                    .with_min_calls(2)
+                    .with_open_duration(Duration::from_secs(60)),
+            );
+        assert_eq!(
+            dispatcher.circuit_state::<CreateCommand>(),
+            Some(CircuitState::Closed)
+        );
+        assert_eq!(dispatcher.circuit_state::<DeleteCommand>(), None);
+
+        for _ in 0..3 {
+            let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+                name: "charge".to_string(),
+            });
+            dispatcher
+                .dispatch_with_correlation(vec![cmd], cid, None)
+                .await
+                .unwrap();
+        }
+
+        // Two real calls tripped the breaker; the third never reached the effect
+        assert_eq!(call_count.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            dispatcher.circuit_state::<CreateCommand>(),
+            Some(CircuitState::Open)
+        );
+
+        let mut failures = Vec::new();
+        let mut opened = 0;
+        while let Ok(envelope) = receiver.try_recv() {
+            if let Some(failed) = envelope.downcast_ref::<CommandFailed>() {
+                failures.push(failed.safe_message.clone());
+            } else if envelope.downcast_ref::<CircuitBreakerOpened>().is_some() {
+                opened += 1;
+            }
+        }
+        assert_eq!(opened, 1);
+        assert_eq!(failures.len(), 3);
+        assert_eq!(failures[2], "Service temporarily unavailable");
+    }
+
+    // Effect that records how many executions overlap
+    struct OverlapEffect {
+        active: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Effect<CreateCommand, TestDeps> for OverlapEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            cmd: CreateCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(TestEvent { message: cmd.name })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_caps_parallel_executions() {
+        let peak = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(OverlapEffect {
+                active: Arc::new(AtomicUsize::new(0)),
+                peak: peak.clone(),
+            })
+            .with_concurrency_limit::<CreateCommand>(2);
+
+        assert_eq!(dispatcher.concurrency_limit::<CreateCommand>(), Some(2));
+        assert_eq!(dispatcher.concurrency_limit::<DeleteCommand>(), None);
+
+        let calls = (0..6).map(|i| {
+            let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+                name: format!("call-{}", i),
+            });
+            dispatcher.dispatch(vec![cmd])
+        });
+        for result in futures::future::join_all(calls).await {
+            result.unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "concurrency limit must be at least 1")]
+    fn test_zero_concurrency_limit_panics() {
+        let _ = Dispatcher::new(TestDeps { value: 0 }, EventBus::new())
+            .with_concurrency_limit::<CreateCommand>(0);
+    }
+
+    #[tokio::test]
+    async fn test_rejecting_rate_limit_fails_command_without_calling_effect() {
+        use crate::rate_limit::{RateLimitPolicy, ThrottleMode, Throttled};
+
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let cid = CorrelationId::new();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(CreateEffect {
+                call_count: call_count.clone(),
+            })
+            .with_rate_limit::<CreateCommand>(
+                RateLimitPolicy::per_second(1).with_mode(ThrottleMode::Reject),
+            );
+
+        for name in ["first", "second"] {
+            let cmd: Box<dyn AnyCommand> = Box::new(CreateCommand {
+                name: name.to_string(),
+            });
+            dispatcher
+                .dispatch_with_correlation(vec![cmd], cid, None)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(call_count.load(Ordering::Relaxed), 1);
+
+        let mut throttled = 0;
+        let mut failed = None;
+        while let Ok(envelope) = receiver.try_recv() {
+            if envelope.downcast_ref::<Throttled>().is_some() {
+                assert_eq!(envelope.cid, cid);
+                throttled += 1;
+            } else if let Some(f) = envelope.downcast_ref::<CommandFailed>() {
+                failed = Some(f.category);
+            }
+        }
+        assert_eq!(throttled, 1);
+        assert_eq!(failed, Some(crate::error::SafeErrorCategory::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn test_global_rate_limit_delays_across_command_types() {
+        use crate::rate_limit::RateLimitPolicy;
+
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<CreateCommand, _>(CreateEffect {
+                call_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .with_effect::<DeleteCommand, _>(DeleteEffect {
+                call_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .with_global_rate_limit(RateLimitPolicy::new(1, Duration::from_millis(40)));
+
+        let start = std::time::Instant::now();
+        let create: Box<dyn AnyCommand> = Box::new(CreateCommand {
+            name: "a".to_string(),
+        });
+        dispatcher.dispatch(vec![create]).await.unwrap();
+        let delete: Box<dyn AnyCommand> = Box::new(DeleteCommand { id: 1 });
+        dispatcher.dispatch(vec![delete]).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(35));
+    }
+
+    #[derive(Debug, Clone)]
+    struct NotifyCommand {
+        id: u32,
+    }
+    impl Command for NotifyCommand {
+        fn independent(&self) -> bool {
+            true
+        }
+    }
+
+    /// Takes 50ms per command; fails command 1.
+    struct NotifyEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<NotifyCommand, TestDeps> for NotifyEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            cmd: NotifyCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if cmd.id == 1 {
+                anyhow::bail!("mailbox full");
+            }
+            Ok(TestEvent {
+                message: format!("notified {}", cmd.id),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_independent_batch_runs_concurrently_and_reports_failures() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let inflight = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus)
+            .with_effect::<NotifyCommand, _>(NotifyEffect);
+
+        let batch: Vec<Box<dyn AnyCommand>> = (0..3)
+            .map(|id| Box::new(NotifyCommand { id }) as Box<dyn AnyCommand>)
+            .collect();
+        let start = std::time::Instant::now();
+        let failure = dispatcher
+            .dispatch_at_depth(batch, cid, 0, None, &Metadata::default(), Some(&inflight))
+            .await
+            .unwrap();
+
+        // Sequential execution would take 150ms
+        assert!(start.elapsed() < Duration::from_millis(120));
+        assert!(!inflight.has_pending_work(cid));
+        let failure = failure.unwrap();
+        assert!(failure.contains("1 failed"));
+        assert!(failure.contains("index 1: mailbox full"));
+
+        // Emitted in batch order once all three finished
+        let mut messages = Vec::new();
+        let mut failed = 0;
+        for _ in 0..3 {
+            let envelope = receiver.recv().await.unwrap();
+            if let Some(event) = envelope.downcast_ref::<TestEvent>() {
+                messages.push(event.message.clone());
+            } else {
+                assert_eq!(envelope.downcast_ref::<CommandFailed>().unwrap().cid, cid);
+                failed += 1;
+            }
+        }
+        assert_eq!(messages, vec!["notified 0", "notified 2"]);
+        assert_eq!(failed, 1);
+    }
 }
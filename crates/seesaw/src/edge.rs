@@ -0,0 +1,430 @@
+//! WebSocket edge - bridges the event bus to browser connections.
+//!
+//! The edge is the "Edge (API/WebSocket)" box of the architecture: it
+//! pushes events to clients and turns client messages into input events.
+//! It is transport-agnostic, working on text frames so any WebSocket
+//! library (axum, tokio-tungstenite, warp) plugs in by mapping its messages
+//! into a `Stream<Item = String>` and a `Sink<String>`.
+//!
+//! # Frames
+//!
+//! Every frame is a JSON object naming the event by its registered wire
+//! name:
+//!
+//! ```text
+//! server → client  {"type": "order.shipped", "cid": "…", "payload": {…}}
+//! client → server  {"type": "order.place", "payload": {…}}
+//! server → client  {"type": "error", "payload": {"message": "…"}}
+//! ```
+//!
+//! Only types registered with [`Edge::with_outbound`] can leave the
+//! process, and only types registered with [`Edge::with_inbound`] can
+//! enter it. Each [`EdgeConnection`] further narrows outbound events with
+//! per-connection filters, e.g. to the signed-in user's orders.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw::Edge;
+//!
+//! let edge = Arc::new(
+//!     Edge::new(handle.bus().clone())
+//!         .with_outbound::<OrderShipped>("order.shipped")
+//!         .with_inbound::<PlaceOrderRequested>("order.place"),
+//! );
+//!
+//! // axum WebSocket handler
+//! async fn on_upgrade(socket: WebSocket, edge: Arc<Edge>, user_id: Uuid) {
+//!     let (sink, stream) = socket.split();
+//!     let sink = sink.with(|text: String| async { Ok::<_, axum::Error>(Message::Text(text.into())) });
+//!     let stream = stream.filter_map(|msg| async {
+//!         match msg {
+//!             Ok(Message::Text(text)) => Some(text.to_string()),
+//!             _ => None,
+//!         }
+//!     });
+//!
+//!     let connection = edge
+//!         .connection()
+//!         .with_filter::<OrderShipped, _>(move |e| e.user_id == user_id);
+//!     if let Err(e) = connection.run(Box::pin(sink), Box::pin(stream)).await {
+//!         tracing::warn!(error = %e, "websocket closed");
+//!     }
+//! }
+//! ```
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::bus::EventBus;
+use crate::core::{CorrelationId, Event, EventEnvelope};
+
+/// Frame `type` of errors reported to the client.
+pub const EDGE_ERROR_TYPE: &str = "error";
+
+type SerializeFn =
+    Box<dyn Fn(&EventEnvelope) -> Option<serde_json::Result<serde_json::Value>> + Send + Sync>;
+type EmitFn = Box<dyn Fn(serde_json::Value, CorrelationId, &EventBus) -> Result<()> + Send + Sync>;
+type FilterFn = Box<dyn Fn(&EventEnvelope) -> bool + Send + Sync>;
+
+/// A JSON frame exchanged with a client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeFrame {
+    /// Wire name of the event, or [`EDGE_ERROR_TYPE`].
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Correlation ID of an outbound event; ignored on inbound frames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cid: Option<Uuid>,
+    /// The serialized event.
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+// =============================================================================
+// Edge
+// =============================================================================
+
+/// The event types clients may receive and send, shared by all connections.
+pub struct Edge {
+    bus: EventBus,
+    outbound: HashMap<TypeId, (&'static str, SerializeFn)>,
+    inbound: HashMap<&'static str, EmitFn>,
+}
+
+impl Edge {
+    /// Create an edge for `bus` that lets no events through.
+    pub fn new(bus: EventBus) -> Self {
+        Self {
+            bus,
+            outbound: HashMap::new(),
+            inbound: HashMap::new(),
+        }
+    }
+
+    /// Allow `E` events to be pushed to clients as `name` frames.
+    pub fn with_outbound<E: Event + Serialize>(mut self, name: &'static str) -> Self {
+        self.outbound.insert(
+            TypeId::of::<E>(),
+            (
+                name,
+                Box::new(|envelope| envelope.downcast_ref::<E>().map(serde_json::to_value)),
+            ),
+        );
+        self
+    }
+
+    /// Accept `name` frames from clients, emitting them as `E` events.
+    ///
+    /// Inbound events are untrusted input: machines should validate them
+    /// like any other edge request.
+    pub fn with_inbound<E: Event + DeserializeOwned>(mut self, name: &'static str) -> Self {
+        self.inbound.insert(
+            name,
+            Box::new(move |payload, cid, bus| {
+                let event: E = serde_json::from_value(payload)
+                    .with_context(|| format!("invalid {} payload", name))?;
+                bus.emit_with_correlation(event, cid);
+                Ok(())
+            }),
+        );
+        self
+    }
+
+    /// Start configuring a connection.
+    pub fn connection(self: &Arc<Self>) -> EdgeConnection {
+        EdgeConnection {
+            edge: self.clone(),
+            filters: HashMap::new(),
+            correlation: None,
+        }
+    }
+
+    /// Serialize an outbound envelope, or `None` if its type is not outbound.
+    fn encode(&self, envelope: &EventEnvelope) -> Option<Result<String>> {
+        let (name, serialize) = self.outbound.get(&envelope.type_id)?;
+        let frame = serialize(envelope)?.map(|payload| EdgeFrame {
+            event_type: name.to_string(),
+            cid: envelope.cid.is_some().then(|| envelope.cid.into_inner()),
+            payload,
+        });
+        Some(
+            frame
+                .and_then(|frame| serde_json::to_string(&frame))
+                .map_err(Into::into),
+        )
+    }
+
+    /// Emit the event in an inbound text frame.
+    fn accept(&self, text: &str, cid: CorrelationId) -> Result<()> {
+        let frame: EdgeFrame = serde_json::from_str(text).context("malformed frame")?;
+        let emit = self
+            .inbound
+            .get(frame.event_type.as_str())
+            .ok_or_else(|| anyhow!("unknown event type: {}", frame.event_type))?;
+        emit(frame.payload, cid, &self.bus)
+    }
+}
+
+impl std::fmt::Debug for Edge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Edge")
+            .field(
+                "outbound",
+                &self
+                    .outbound
+                    .values()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("inbound", &self.inbound.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Connection
+// =============================================================================
+
+/// One client connection: which outbound events it receives.
+///
+/// A connection receives nothing until filters are added with
+/// [`with_events`](Self::with_events) or [`with_filter`](Self::with_filter).
+pub struct EdgeConnection {
+    edge: Arc<Edge>,
+    filters: HashMap<TypeId, FilterFn>,
+    correlation: Option<CorrelationId>,
+}
+
+impl EdgeConnection {
+    /// Push every `E` event to this client.
+    pub fn with_events<E: Event>(self) -> Self {
+        self.with_filter::<E, _>(|_| true)
+    }
+
+    /// Push the `E` events matching `filter` to this client.
+    ///
+    /// `E` must be registered with [`Edge::with_outbound`].
+    pub fn with_filter<E, F>(mut self, filter: F) -> Self
+    where
+        E: Event,
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.filters.insert(
+            TypeId::of::<E>(),
+            Box::new(move |envelope| envelope.downcast_ref::<E>().is_some_and(&filter)),
+        );
+        self
+    }
+
+    /// Scope the connection to one workflow.
+    ///
+    /// Only events correlated with `cid` are pushed, and inbound events are
+    /// emitted with it. Without a scope, each inbound event starts a new
+    /// correlation.
+    pub fn with_correlation(mut self, cid: CorrelationId) -> Self {
+        self.correlation = Some(cid);
+        self
+    }
+
+    /// Serialize an envelope if this connection should receive it.
+    fn outbound(&self, envelope: &EventEnvelope) -> Option<Result<String>> {
+        if self.correlation.is_some_and(|cid| cid != envelope.cid) {
+            return None;
+        }
+        let filter = self.filters.get(&envelope.type_id)?;
+        if !filter(envelope) {
+            return None;
+        }
+        self.edge.encode(envelope)
+    }
+
+    /// Handle one inbound frame, returning an error frame to send back.
+    fn inbound(&self, text: &str) -> Option<String> {
+        // Default is a fresh correlation ID
+        let cid = self.correlation.unwrap_or_default();
+        let e = self.edge.accept(text, cid).err()?;
+        let frame = EdgeFrame {
+            event_type: EDGE_ERROR_TYPE.to_string(),
+            cid: None,
+            payload: serde_json::json!({ "message": format!("{:#}", e) }),
+        };
+        serde_json::to_string(&frame).ok()
+    }
+
+    /// Bridge the bus and the client until either side closes.
+    ///
+    /// Malformed or unknown inbound frames are answered with an error frame
+    /// and do not end the connection. Events the connection missed because
+    /// the client was too slow are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sending to the client fails.
+    pub async fn run<S, R>(self, mut sink: S, mut stream: R) -> Result<()>
+    where
+        S: Sink<String> + Unpin,
+        S::Error: std::error::Error + Send + Sync + 'static,
+        R: Stream<Item = String> + Unpin,
+    {
+        let mut events = self.edge.bus.subscribe();
+        loop {
+            let frame = tokio::select! {
+                received = events.recv() => match received {
+                    Ok(envelope) => match self.outbound(&envelope) {
+                        Some(Ok(frame)) => frame,
+                        Some(Err(e)) => {
+                            warn!(error = %e, "failed to serialize edge event");
+                            continue;
+                        }
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "edge connection lagged behind the event bus");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                text = stream.next() => match text {
+                    Some(text) => match self.inbound(&text) {
+                        Some(error) => error,
+                        None => continue,
+                    },
+                    None => return Ok(()),
+                },
+            };
+            sink.send(frame).await.context("sending edge frame")?;
+        }
+    }
+}
+
+impl std::fmt::Debug for EdgeConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EdgeConnection")
+            .field("filters", &self.filters.len())
+            .field("correlation", &self.correlation)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct OrderShipped {
+        order_id: u32,
+        user_id: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct PlaceOrderRequested {
+        sku: String,
+    }
+
+    fn edge(bus: &EventBus) -> Arc<Edge> {
+        Arc::new(
+            Edge::new(bus.clone())
+                .with_outbound::<OrderShipped>("order.shipped")
+                .with_inbound::<PlaceOrderRequested>("order.place"),
+        )
+    }
+
+    async fn next_frame(rx: &mut mpsc::UnboundedReceiver<String>) -> EdgeFrame {
+        let text = tokio::time::timeout(Duration::from_secs(1), rx.next())
+            .await
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pushes_only_matching_events() {
+        let bus = EventBus::new();
+        let (to_client, mut client_rx) = mpsc::unbounded();
+        let (_client_tx, from_client) = mpsc::unbounded::<String>();
+
+        let connection = edge(&bus)
+            .connection()
+            .with_filter::<OrderShipped, _>(|e| e.user_id == 1);
+        tokio::spawn(connection.run(to_client, from_client));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        bus.emit(OrderShipped {
+            order_id: 10,
+            user_id: 2,
+        });
+        bus.emit(PlaceOrderRequested { sku: "x".into() });
+        let cid = CorrelationId::new();
+        bus.emit_with_correlation(
+            OrderShipped {
+                order_id: 11,
+                user_id: 1,
+            },
+            cid,
+        );
+
+        let frame = next_frame(&mut client_rx).await;
+        assert_eq!(frame.event_type, "order.shipped");
+        assert_eq!(frame.cid, Some(cid.into_inner()));
+        assert_eq!(frame.payload["order_id"], 11);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_frames_become_events() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let (to_client, mut client_rx) = mpsc::unbounded();
+        let (mut client_tx, from_client) = mpsc::unbounded::<String>();
+
+        let cid = CorrelationId::new();
+        let connection = edge(&bus).connection().with_correlation(cid);
+        tokio::spawn(connection.run(to_client, from_client));
+
+        client_tx
+            .send(r#"{"type":"order.place","payload":{"sku":"abc"}}"#.to_string())
+            .await
+            .unwrap();
+        let envelope = events.recv().await.unwrap();
+        assert_eq!(envelope.cid, cid);
+        assert_eq!(
+            envelope.downcast_ref::<PlaceOrderRequested>(),
+            Some(&PlaceOrderRequested { sku: "abc".into() })
+        );
+
+        // Outbound-only types cannot be sent by clients
+        client_tx
+            .send(r#"{"type":"order.shipped","payload":{}}"#.to_string())
+            .await
+            .unwrap();
+        let frame = next_frame(&mut client_rx).await;
+        assert_eq!(frame.event_type, EDGE_ERROR_TYPE);
+        assert_eq!(
+            frame.payload["message"],
+            "unknown event type: order.shipped"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_ends_when_client_disconnects() {
+        let bus = EventBus::new();
+        let (to_client, _client_rx) = mpsc::unbounded();
+        let (client_tx, from_client) = mpsc::unbounded::<String>();
+
+        let run = tokio::spawn(edge(&bus).connection().run(to_client, from_client));
+        drop(client_tx);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), run).await;
+        assert!(result.unwrap().unwrap().is_ok());
+    }
+}
@@ -9,6 +9,14 @@
 //! - **Stateless**: No access to machine state, commands carry data
 //! - **Return events**: Effects return events describing outcomes (Runtime emits)
 //! - **Narrow context**: Only `deps()` and `signal()` available
+//!
+//! # Typed Dependencies
+//!
+//! An effect that only needs part of the dependencies can ask for it by type
+//! with [`EffectContext::get`], through a [`FromDeps`] impl on the app's
+//! deps. Effects written against `D: Clone + Send + Sync + 'static` where
+//! `reqwest::Client: FromDeps<D>` work with any app providing a client, so
+//! they can live in a library that never sees the app's `Deps` type.
 
 use std::any::Any;
 use std::sync::Arc;
@@ -18,8 +26,39 @@ use async_trait::async_trait;
 
 use crate::bus::EventBus;
 use crate::core::{Command, CorrelationId, Event, EventEnvelope};
+use crate::cost::CostLedger;
 use crate::engine::InflightTracker;
 use crate::error::SeesawError;
+use crate::metadata::Metadata;
+
+/// Extract one dependency from the app's deps, like axum's `FromRef`.
+///
+/// Every deps type extracts itself. Implement it for each field an effect
+/// library should reach, or derive it with `#[derive(FromDeps)]` (`derive`
+/// feature), which implements it for the type of every field:
+///
+/// ```ignore
+/// #[derive(Clone, FromDeps)]
+/// struct Deps {
+///     db: PgPool,
+///     http: reqwest::Client,
+/// }
+///
+/// async fn execute(&self, cmd: Fetch, ctx: EffectContext<Deps>) -> Result<Fetched> {
+///     let http = ctx.get::<reqwest::Client>();
+///     // ...
+/// }
+/// ```
+pub trait FromDeps<D> {
+    /// Clone this dependency out of `deps`.
+    fn from_deps(deps: &D) -> Self;
+}
+
+impl<D: Clone> FromDeps<D> for D {
+    fn from_deps(deps: &D) -> Self {
+        deps.clone()
+    }
+}
 
 /// Context passed to effect handlers.
 ///
@@ -62,6 +101,20 @@ use crate::error::SeesawError;
 /// command is automatically propagated. This enables `emit_and_await`
 /// to track all cascading work.
 ///
+/// The chain is unbroken: the Runtime dispatches each command decided from
+/// an envelope with that envelope's correlation ID, the effect sees it via
+/// [`correlation_id()`](Self::correlation_id), and the event it returns is
+/// emitted with it. Signals and [`ToolContext`] carry it too, so progress
+/// updates and interactive requests can be tied back to the originating
+/// event.
+///
+/// Background and scheduled commands leave the process through the job
+/// queue and do not carry the correlation ID.
+///
+/// The triggering event's [`Metadata`] travels the same way: read it with
+/// [`metadata()`](Self::metadata) instead of adding tenant or user fields to
+/// every command.
+///
 /// # Example
 ///
 /// ```ignore
@@ -82,6 +135,12 @@ pub struct EffectContext<D> {
     cid: Option<CorrelationId>,
     /// Inflight tracker for increment-before-emit
     inflight: Option<Arc<InflightTracker>>,
+    /// Metadata of the event the command was decided from
+    metadata: Metadata,
+    /// Type name of the command being executed (None outside dispatch)
+    command_type: Option<&'static str>,
+    /// Ledger costs are reported to
+    costs: Option<CostLedger>,
 }
 
 impl<D> EffectContext<D> {
@@ -95,6 +154,9 @@ impl<D> EffectContext<D> {
             bus,
             cid: None,
             inflight: None,
+            metadata: Metadata::default(),
+            command_type: None,
+            costs: None,
         }
     }
 
@@ -113,9 +175,30 @@ impl<D> EffectContext<D> {
             bus,
             cid: Some(cid),
             inflight,
+            metadata: Metadata::default(),
+            command_type: None,
+            costs: None,
         }
     }
 
+    /// Set the request-scoped metadata, for contexts built outside the
+    /// dispatch loop (edge functions, tests).
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Execute a command of `command_type`, reporting costs to `costs`.
+    pub(crate) fn for_command(
+        mut self,
+        command_type: &'static str,
+        costs: Option<CostLedger>,
+    ) -> Self {
+        self.command_type = Some(command_type);
+        self.costs = costs;
+        self
+    }
+
     /// Get shared dependencies.
     ///
     /// Dependencies typically include:
@@ -126,9 +209,17 @@ impl<D> EffectContext<D> {
         &self.deps
     }
 
+    /// Get one dependency by type, through its [`FromDeps`] impl.
+    ///
+    /// Dependencies are cloned out, so they should be cheap handles such as
+    /// pools and clients.
+    pub fn get<T: FromDeps<D>>(&self) -> T {
+        T::from_deps(&self.deps)
+    }
+
     /// Get the correlation ID for outbox writes.
     ///
-    /// Returns the `CorrelationId` suitable for use with `OutboxWriter::write_event`.
+    /// Returns the `CorrelationId` suitable for use with `OutboxWriter::write_event` and `write_result`.
     /// If no correlation ID is set, returns `CorrelationId::NONE`.
     ///
     /// # Example
@@ -160,6 +251,72 @@ impl<D> EffectContext<D> {
         self.cid.unwrap_or(CorrelationId::NONE)
     }
 
+    /// Get the metadata of the event this command was decided from.
+    ///
+    /// Empty for contexts without one, such as background job execution.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Get the deadline of the event this command was decided from, if any.
+    pub fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.metadata.deadline()
+    }
+
+    /// Time left until the [deadline](Self::deadline), or `None` without
+    /// one. Zero once it has passed.
+    ///
+    /// Use it to bound outgoing calls so they give up when the caller has:
+    ///
+    /// ```ignore
+    /// let timeout = ctx.remaining().unwrap_or(Duration::from_secs(30));
+    /// let response = ctx.deps().http.get(url).timeout(timeout).send().await?;
+    /// ```
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()))
+    }
+
+    /// Report that executing this command cost `amount` of `unit`, such as
+    /// tokens, cents or API calls.
+    ///
+    /// Added to the dispatcher's [`CostLedger`] under the command type and
+    /// the correlation ID; a no-op without one. Report each unit separately:
+    ///
+    /// ```ignore
+    /// let response = call_llm(&ctx.deps().http, request).await?;
+    /// ctx.record_cost("input_tokens", response.usage.input_tokens as f64);
+    /// ctx.record_cost("output_tokens", response.usage.output_tokens as f64);
+    /// ```
+    pub fn record_cost(&self, unit: &str, amount: f64) {
+        if let (Some(ledger), Some(command_type)) = (&self.costs, self.command_type) {
+            ledger.record(self.correlation_id(), command_type, unit, amount);
+        }
+    }
+
+    /// Get the deps' `reqwest::Client`, wrapped to trace the requests made
+    /// with it (`http` feature).
+    ///
+    /// Requests carry the correlation ID in a `Seesaw-Correlation-Id`
+    /// header, give up at the [deadline](Self::deadline), and are timed per
+    /// command type. See [`HttpClient`](crate::HttpClient).
+    ///
+    /// ```ignore
+    /// let response = ctx.http().post(&cmd.url).json(&cmd.payload).send().await?;
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn http(&self) -> crate::http::HttpClient
+    where
+        reqwest::Client: FromDeps<D>,
+    {
+        crate::http::HttpClient::new(
+            self.get::<reqwest::Client>(),
+            self.correlation_id(),
+            self.command_type.unwrap_or("unknown"),
+            self.deadline(),
+        )
+    }
+
     /// Fire-and-forget signal for UI observability.
     ///
     /// Signals are NOT fact events - they are transient UI updates
@@ -188,8 +345,11 @@ impl<D> EffectContext<D> {
     /// }
     /// ```
     pub fn signal<E: Event>(&self, event: E) {
-        // Signals use random correlation (fire-and-forget, no tracking)
-        self.bus.emit(event);
+        // Signals carry the correlation ID for UI routing, but are not
+        // counted as inflight work (fire-and-forget, no tracking)
+        let cid = self.cid.unwrap_or_default();
+        self.bus
+            .emit_envelope(EventEnvelope::new(cid, event).with_metadata(self.metadata.clone()));
     }
 }
 
@@ -217,6 +377,10 @@ pub struct ToolContext<D> {
     pub deps: Arc<D>,
     /// Event bus for interactive dispatch_request calls.
     pub bus: EventBus,
+    /// Correlation ID of the effect execution that created this context.
+    ///
+    /// `CorrelationId::NONE` outside of correlated dispatch.
+    pub cid: CorrelationId,
 }
 
 impl<D> Clone for ToolContext<D> {
@@ -224,6 +388,7 @@ impl<D> Clone for ToolContext<D> {
         Self {
             deps: self.deps.clone(),
             bus: self.bus.clone(),
+            cid: self.cid,
         }
     }
 }
@@ -237,6 +402,7 @@ impl<D> EffectContext<D> {
         ToolContext {
             deps: self.deps.clone(),
             bus: self.bus.clone(),
+            cid: self.correlation_id(),
         }
     }
 }
@@ -248,6 +414,9 @@ impl<D> Clone for EffectContext<D> {
             bus: self.bus.clone(),
             cid: self.cid,
             inflight: self.inflight.clone(),
+            metadata: self.metadata.clone(),
+            command_type: self.command_type,
+            costs: self.costs.clone(),
         }
     }
 }
@@ -375,6 +544,79 @@ pub trait Effect<C: Command, D>: Send + Sync + 'static {
     }
 }
 
+/// An effect backed by an async closure.
+///
+/// For small one-off effects that don't warrant a struct and an impl block.
+/// Usually created through `with_effect_fn` on the dispatcher or engine
+/// builder.
+///
+/// # Example
+///
+/// ```ignore
+/// let engine = EngineBuilder::new(deps)
+///     .with_effect_fn::<FetchCommand, _>(|cmd, ctx| async move {
+///         let body = ctx.deps().http.get(&cmd.url).await?;
+///         Ok(FetchEvent::Fetched { url: cmd.url, bytes: body.len() })
+///     })
+///     .build();
+/// ```
+pub struct FnEffect<F> {
+    f: F,
+}
+
+impl<F> FnEffect<F> {
+    /// Wrap an async closure; see [`EffectFn`].
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+/// An async closure usable as an effect: `Fn(C, EffectContext<D>) -> impl
+/// Future<Output = Result<Event>>`.
+///
+/// Implemented for every such closure; exists so that `with_effect_fn` can
+/// name the closure with a single type parameter while still inferring its
+/// argument types.
+pub trait EffectFn<C, D>:
+    Fn(C, EffectContext<D>) -> <Self as EffectFn<C, D>>::Future + Send + Sync + 'static
+{
+    /// The future returned by the closure.
+    type Future: std::future::Future<Output = Result<Self::Event>> + Send + 'static;
+
+    /// The event the closure produces.
+    type Event: Event;
+}
+
+impl<C, D, F, Fut, Ev> EffectFn<C, D> for F
+where
+    F: Fn(C, EffectContext<D>) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = Result<Ev>> + Send + 'static,
+    Ev: Event,
+{
+    type Future = Fut;
+    type Event = Ev;
+}
+
+#[async_trait]
+impl<C, D, F> Effect<C, D> for FnEffect<F>
+where
+    C: Command,
+    D: Send + Sync + 'static,
+    F: EffectFn<C, D>,
+{
+    type Event = F::Event;
+
+    async fn execute(&self, command: C, ctx: EffectContext<D>) -> Result<F::Event> {
+        (self.f)(command, ctx).await
+    }
+}
+
+impl<F> std::fmt::Debug for FnEffect<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnEffect").finish_non_exhaustive()
+    }
+}
+
 /// Type-erased effect trait for internal use.
 ///
 /// Returns `EventEnvelope` so the Runtime can emit events.
@@ -397,6 +639,15 @@ pub(crate) trait AnyEffect<D>: Send + Sync {
         commands: Vec<Box<dyn Any + Send + Sync>>,
         ctx: EffectContext<D>,
     ) -> Result<Vec<EventEnvelope>>;
+
+    /// Type name of the command this effect handles.
+    fn command_type_name(&self) -> &'static str;
+
+    /// Mutable access to the concrete effect, for effects that are built up
+    /// after registration (per-variant routers).
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
 }
 
 /// Wrapper to make concrete effects implement AnyEffect.
@@ -470,6 +721,10 @@ where
             .map(|e| EventEnvelope::new(cid, e))
             .collect())
     }
+
+    fn command_type_name(&self) -> &'static str {
+        std::any::type_name::<C>()
+    }
 }
 
 #[cfg(test)]
@@ -530,6 +785,65 @@ mod tests {
         assert_eq!(ctx.deps().value, 42);
     }
 
+    #[derive(Debug, Clone, PartialEq)]
+    struct Client(&'static str);
+
+    #[derive(Clone)]
+    struct AppDeps {
+        client: Client,
+    }
+
+    impl FromDeps<AppDeps> for Client {
+        fn from_deps(deps: &AppDeps) -> Self {
+            deps.client.clone()
+        }
+    }
+
+    // Written without knowing the app's deps type
+    fn client_of<D>(ctx: &EffectContext<D>) -> Client
+    where
+        Client: FromDeps<D>,
+    {
+        ctx.get::<Client>()
+    }
+
+    #[tokio::test]
+    async fn test_effect_context_get() {
+        let deps = Arc::new(AppDeps {
+            client: Client("api"),
+        });
+        let ctx = EffectContext::new(deps, EventBus::new());
+
+        assert_eq!(client_of(&ctx), Client("api"));
+        assert_eq!(ctx.get::<AppDeps>().client, Client("api"));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Pool(u32);
+
+    #[derive(Clone, seesaw_macros::FromDeps)]
+    struct DerivedDeps {
+        pool: Pool,
+        client: Client,
+        #[from_deps(skip)]
+        replica: Pool,
+    }
+
+    #[tokio::test]
+    async fn test_derive_from_deps() {
+        let deps = Arc::new(DerivedDeps {
+            pool: Pool(1),
+            client: Client("api"),
+            replica: Pool(2),
+        });
+        let ctx = EffectContext::new(deps, EventBus::new());
+
+        assert_eq!(ctx.get::<Pool>(), Pool(1));
+        assert_eq!(client_of(&ctx), Client("api"));
+        // Skipped fields are still reachable through the deps themselves
+        assert_eq!(ctx.deps().replica, Pool(2));
+    }
+
     #[tokio::test]
     async fn test_effect_execute() {
         let call_count = Arc::new(AtomicUsize::new(0));
@@ -653,6 +967,21 @@ mod tests {
         assert_eq!(ctx1.deps().value, ctx2.deps().value);
     }
 
+    #[tokio::test]
+    async fn test_signal_and_tool_context_carry_correlation() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let cid = CorrelationId::new();
+        let ctx = EffectContext::with_correlation(Arc::new(TestDeps { value: 0 }), bus, cid, None);
+
+        ctx.signal(TestEvent {
+            result: "progress".to_string(),
+        });
+
+        assert_eq!(rx.recv().await.unwrap().cid, cid);
+        assert_eq!(ctx.tool_context().cid, cid);
+    }
+
     // Test effect with custom batch implementation
     struct BatchOptimizedEffect {
         individual_calls: Arc<AtomicUsize>,
@@ -758,4 +1087,70 @@ mod tests {
         assert_eq!(events[0].result, "x with value 100");
         assert_eq!(events[1].result, "y with value 100");
     }
+
+    #[tokio::test]
+    async fn test_fn_effect_execute() {
+        let effect = FnEffect::new(
+            |cmd: TestCommand, ctx: EffectContext<TestDeps>| async move {
+                Ok(TestEvent {
+                    result: format!("{} from closure {}", cmd.action, ctx.deps().value),
+                })
+            },
+        );
+
+        let ctx = EffectContext::new(Arc::new(TestDeps { value: 7 }), EventBus::new());
+        let event = effect
+            .execute(
+                TestCommand {
+                    action: "run".to_string(),
+                },
+                ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(event.result, "run from closure 7");
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_with_effect_fn() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let dispatcher = crate::dispatch::Dispatcher::new(TestDeps { value: 3 }, bus)
+            .with_effect_fn::<TestCommand, _>(|cmd, ctx| async move {
+                Ok(TestEvent {
+                    result: format!("{}:{}", cmd.action, ctx.deps().value),
+                })
+            });
+
+        let cmd: Box<dyn crate::core::AnyCommand> = Box::new(TestCommand {
+            action: "ping".to_string(),
+        });
+        dispatcher.dispatch(vec![cmd]).await.unwrap();
+
+        let envelope = rx.recv().await.unwrap();
+        assert_eq!(
+            envelope.downcast_ref::<TestEvent>(),
+            Some(&TestEvent {
+                result: "ping:3".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_remaining_budget_counts_down_to_zero() {
+        let deps = Arc::new(TestDeps { value: 0 });
+        let ctx = EffectContext::new(deps.clone(), EventBus::new())
+            .with_metadata(Metadata::new().with_timeout(std::time::Duration::from_secs(2)));
+        let remaining = ctx.remaining().unwrap();
+        assert!(remaining > std::time::Duration::from_secs(1));
+        assert!(remaining <= std::time::Duration::from_secs(2));
+
+        let passed = EffectContext::new(deps.clone(), EventBus::new())
+            .with_metadata(Metadata::new().with_deadline(tokio::time::Instant::now()));
+        assert_eq!(passed.remaining(), Some(std::time::Duration::ZERO));
+
+        let unbounded = EffectContext::new(deps, EventBus::new());
+        assert_eq!(unbounded.remaining(), None);
+    }
 }
@@ -44,24 +44,48 @@
 //! }
 //! ```
 
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::any::TypeId;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use tokio::sync::Notify;
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tracing::{info, warn};
 
 use crate::bus::EventBus;
-use crate::core::{CorrelationId, Event};
+use crate::child::{ChildEngine, ChildEngines};
+use crate::circuit_breaker::CircuitBreakerPolicy;
+use crate::control::{AddEffect, RuntimeControl, WiringChange};
+use crate::core::{CorrelationId, Event, EventEnvelope};
+use crate::cost::CostLedger;
 use crate::dispatch::Dispatcher;
-use crate::effect_impl::Effect;
+use crate::effect_impl::{Effect, EffectFn, FnEffect};
 use crate::error::{BatchOutcome, SeesawError};
-use crate::machine::Machine;
+use crate::health::{Health, HealthMonitor};
+use crate::machine::{Machine, MachineRunner};
+use crate::machine_middleware::MachineMiddleware;
+use crate::metadata::Metadata;
+use crate::middleware::EffectMiddleware;
+use crate::rate_limit::RateLimitPolicy;
+use crate::reaper::{Reaper, ReaperContext, ReaperTasks};
+use crate::replay::{EventLog, ReplayReport};
+use crate::request::{await_event, dispatch_request_timeout};
+use crate::retry::{RetryPolicy, RetryingEffect};
+use crate::routing::CommandVariant;
 use crate::runtime::Runtime;
-use crate::tap::{EventTap, TapRegistry};
+use crate::schema::SchemaRegistry;
+use crate::snapshot::{SnapshotMachine, SnapshotStore};
+use crate::supervisor::SupervisorPolicy;
+use crate::tap::{EventTap, TapPolicy, TapRegistry};
+use crate::timer::EventTimer;
+use crate::trace::{Trace, TraceRecorder};
+use crate::wiring::{Wiring, WiringReport};
 use crate::Command;
 
 // =============================================================================
@@ -95,7 +119,7 @@ impl InflightEntry {
 ///
 /// Increments the waiter count on creation, decrements on drop.
 /// This ensures proper cleanup even if the async task is cancelled.
-pub(crate) struct WaiterGuard {
+pub struct WaiterGuard {
     entry: Option<Arc<InflightEntry>>,
 }
 
@@ -123,14 +147,24 @@ impl Drop for WaiterGuard {
 #[derive(Default)]
 pub struct InflightTracker {
     entries: DashMap<CorrelationId, Arc<InflightEntry>>,
+    /// Batches begun and not yet completed or dropped, by batch id.
+    batches: DashMap<u64, BatchRecord>,
+    next_batch: AtomicU64,
+}
+
+/// What [`InflightTracker::batches`] reports about a running batch.
+struct BatchRecord {
+    cid: CorrelationId,
+    command_type: &'static str,
+    event_type: Option<&'static str>,
+    size: usize,
+    started: Instant,
 }
 
 impl InflightTracker {
     /// Create a new inflight tracker.
     pub fn new() -> Self {
-        Self {
-            entries: DashMap::new(),
-        }
+        Self::default()
     }
 
     /// Get or create an entry for the given correlation ID.
@@ -275,6 +309,15 @@ impl InflightTracker {
         self.entries.len()
     }
 
+    /// Stop tracking a correlation ID, discarding its count and error.
+    ///
+    /// Call this after giving up on `wait_zero` (e.g. on timeout) so the
+    /// entry does not leak. Work still running for the cid finishes normally;
+    /// its later decrements are ignored.
+    pub fn abandon(&self, cid: CorrelationId) {
+        self.entries.remove(&cid);
+    }
+
     /// Register a waiter for a correlation ID.
     ///
     /// Call this BEFORE emitting an event if you plan to call wait_zero.
@@ -298,20 +341,69 @@ impl InflightTracker {
     /// let outcome = batch.complete(outcome);
     /// ```
     pub fn begin_batch(self: &Arc<Self>, cid: CorrelationId, size: usize) -> InflightBatch {
+        self.begin_described_batch(cid, size, "unknown", None)
+    }
+
+    /// Begin tracking a batch of `command_type` commands decided from an
+    /// event of `event_type`, as reported by [`batches`](Self::batches).
+    pub(crate) fn begin_described_batch(
+        self: &Arc<Self>,
+        cid: CorrelationId,
+        size: usize,
+        command_type: &'static str,
+        event_type: Option<&'static str>,
+    ) -> InflightBatch {
         self.inc(cid, size);
+        let id = self.next_batch.fetch_add(1, Ordering::Relaxed);
+        self.batches.insert(
+            id,
+            BatchRecord {
+                cid,
+                command_type,
+                event_type,
+                size,
+                started: Instant::now(),
+            },
+        );
         InflightBatch {
             tracker: self.clone(),
+            id,
             cid,
             size,
             completed: false,
         }
     }
+
+    /// Batches currently executing, oldest first.
+    pub fn batches(&self) -> Vec<InflightBatchInfo> {
+        let mut batches: Vec<_> = self
+            .batches
+            .iter()
+            .map(|record| InflightBatchInfo {
+                cid: record.cid,
+                command_type: record.command_type,
+                event_type: record.event_type,
+                size: record.size,
+                age: record.started.elapsed(),
+            })
+            .collect();
+        batches.sort_by_key(|batch| std::cmp::Reverse(batch.age));
+        batches
+    }
+
+    /// The oldest batch for `cid` that has been executing for at least `limit`.
+    fn overdue_batch(&self, cid: CorrelationId, limit: Duration) -> Option<InflightBatchInfo> {
+        self.batches()
+            .into_iter()
+            .find(|batch| batch.cid == cid && batch.age >= limit)
+    }
 }
 
 impl std::fmt::Debug for InflightTracker {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("InflightTracker")
             .field("active_correlations", &self.entries.len())
+            .field("batches", &self.batches.len())
             .finish()
     }
 }
@@ -331,18 +423,6 @@ pub struct InflightGuard {
 }
 
 impl InflightGuard {
-    /// Create a guard for inline command execution.
-    ///
-    /// Increments the count now, decrements on drop.
-    pub fn for_commands(tracker: Arc<InflightTracker>, cid: CorrelationId, count: usize) -> Self {
-        tracker.inc(cid, count);
-        Self {
-            tracker,
-            cid,
-            count,
-        }
-    }
-
     /// Create a guard for event processing.
     ///
     /// Does NOT increment (caller already did via emit).
@@ -396,6 +476,7 @@ impl Drop for InflightGuard {
 /// ```
 pub struct InflightBatch {
     tracker: Arc<InflightTracker>,
+    id: u64,
     cid: CorrelationId,
     size: usize,
     completed: bool,
@@ -420,6 +501,8 @@ impl InflightBatch {
 
 impl Drop for InflightBatch {
     fn drop(&mut self) {
+        // No longer executing either way
+        self.tracker.batches.remove(&self.id);
         if !self.completed {
             warn!(
                 cid = %self.cid,
@@ -431,6 +514,32 @@ impl Drop for InflightBatch {
     }
 }
 
+/// A batch of inline commands still executing, from
+/// [`EngineHandle::inflight_batches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InflightBatchInfo {
+    /// Correlation the batch runs under.
+    pub cid: CorrelationId,
+    /// Type name of the batch's commands.
+    pub command_type: &'static str,
+    /// Type name of the event the commands were decided from, when known.
+    pub event_type: Option<&'static str>,
+    /// Number of commands in the batch.
+    pub size: usize,
+    /// How long the batch has been executing.
+    pub age: Duration,
+}
+
+impl std::fmt::Display for InflightBatchInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} x {}", self.size, self.command_type)?;
+        if let Some(event_type) = self.event_type {
+            write!(f, " decided from {event_type}")?;
+        }
+        write!(f, " for {}, running for {:?}", self.cid, self.age)
+    }
+}
+
 // =============================================================================
 // Engine
 // =============================================================================
@@ -441,8 +550,14 @@ impl Drop for InflightBatch {
 /// Use `EngineBuilder` to construct an engine with machines and effects.
 pub struct Engine<D> {
     runtime: Runtime<D>,
+    deps: Arc<D>,
     bus: EventBus,
     inflight: Arc<InflightTracker>,
+    batch_timeout: Option<Duration>,
+    reapers: Vec<Reaper<D>>,
+    trace: Option<TraceRecorder>,
+    schemas: Arc<SchemaRegistry>,
+    children: Vec<ChildEngine>,
 }
 
 impl<D: Send + Sync + 'static> Engine<D> {
@@ -468,17 +583,50 @@ impl<D: Send + Sync + 'static> Engine<D> {
         self.bus.emit(event);
     }
 
+    /// Rebuild machine state from an event log before starting.
+    ///
+    /// See [`Runtime::replay`]: commands decided during replay are discarded.
+    pub async fn replay(&mut self, log: &dyn EventLog, until: Option<u64>) -> Result<ReplayReport> {
+        self.runtime.replay(log, until).await
+    }
+
+    /// Restore snapshot machines before starting.
+    ///
+    /// Only needed to restore before [`replay`](Self::replay); otherwise
+    /// `start` restores automatically. Returns the number restored.
+    pub async fn restore_snapshots(&mut self) -> usize {
+        self.runtime.restore_snapshots().await
+    }
+
     /// Start the engine, running the runtime in the background.
     ///
     /// Returns a handle that can be used to emit events and wait for completion.
     pub fn start(self) -> EngineHandle {
         info!("starting seesaw engine");
 
-        let handle = tokio::spawn(self.runtime.run());
+        let mut runtime = self.runtime;
+        let health = runtime.dispatcher().health().clone();
+        let costs = runtime.dispatcher().cost_ledger().cloned();
+        let control = runtime.control();
+        let handle = tokio::spawn(runtime.run());
+        let reapers = ReaperTasks::spawn(
+            self.reapers,
+            ReaperContext::new(self.deps, self.bus.clone()),
+            &health,
+        );
 
         EngineHandle {
+            children: ChildEngines::spawn(self.children, &self.bus),
+            timer: EventTimer::spawn(self.bus.clone()),
+            reapers,
+            trace: self.trace,
+            costs,
+            schemas: self.schemas,
             bus: self.bus,
             inflight: self.inflight,
+            batch_timeout: self.batch_timeout,
+            health,
+            control,
             handle,
         }
     }
@@ -505,7 +653,16 @@ impl<D: Send + Sync + 'static> Engine<D> {
 pub struct EngineHandle {
     bus: EventBus,
     inflight: Arc<InflightTracker>,
+    batch_timeout: Option<Duration>,
+    health: Arc<HealthMonitor>,
+    control: RuntimeControl,
     handle: JoinHandle<()>,
+    timer: EventTimer,
+    reapers: ReaperTasks,
+    trace: Option<TraceRecorder>,
+    costs: Option<CostLedger>,
+    schemas: Arc<SchemaRegistry>,
+    children: ChildEngines,
 }
 
 impl EngineHandle {
@@ -519,6 +676,153 @@ impl EngineHandle {
         &self.inflight
     }
 
+    /// Inline command batches currently executing, oldest first.
+    ///
+    /// Use this to find the effect a hung request is waiting on.
+    pub fn inflight_batches(&self) -> Vec<InflightBatchInfo> {
+        self.inflight.batches()
+    }
+
+    /// Take a snapshot of the engine's health.
+    ///
+    /// Cheap enough to call from a status endpoint on every request.
+    pub fn health(&self) -> Health {
+        let mut health = self.health.snapshot(self.bus.buffered());
+        health.ready = self.is_ready();
+        health.children = self.children.health();
+        health
+    }
+
+    /// Whether the engine is processing events, for readiness probes.
+    ///
+    /// `true` once the runtime has restored its snapshots and subscribed to
+    /// the bus, until the runtime stops, panics or is aborted. An engine with
+    /// [child engines](EngineBuilder::with_child) is only ready while every
+    /// child is.
+    pub fn is_ready(&self) -> bool {
+        self.health.is_running() && !self.handle.is_finished() && self.children.are_ready()
+    }
+
+    /// The child engine added under `name` with [`EngineBuilder::with_child`].
+    pub fn child(&self, name: &str) -> Option<&EngineHandle> {
+        self.children.get(name)
+    }
+
+    /// Export the causality trace of `cid`.
+    ///
+    /// Returns `None` without [`EngineBuilder::with_trace_recorder`], or
+    /// once the correlation has been forgotten.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let trace = handle.export_trace(cid).context("not traced")?;
+    /// std::fs::write("trace.dot", trace.to_dot())?;
+    /// ```
+    pub fn export_trace(&self, cid: CorrelationId) -> Option<Trace> {
+        self.trace.as_ref()?.trace(cid)
+    }
+
+    /// The costs effects reported, per command type and correlation.
+    ///
+    /// Returns `None` without [`EngineBuilder::with_cost_ledger`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// handle.emit_and_await(SummarizeRequested { text }).await?;
+    /// let tokens = handle.costs().unwrap().total("SummarizeCommand", "tokens");
+    /// ```
+    pub fn costs(&self) -> Option<&CostLedger> {
+        self.costs.as_ref()
+    }
+
+    /// The JSON Schemas of the events and commands registered with
+    /// [`EngineBuilder::with_event_schema`] and
+    /// [`EngineBuilder::with_command_schema`].
+    ///
+    /// Empty unless the `schemas` feature is enabled and types were
+    /// registered. See the [`SchemaRegistry`] docs for serving the catalog.
+    pub fn schemas(&self) -> &SchemaRegistry {
+        &self.schemas
+    }
+
+    /// Add a machine to the running engine.
+    ///
+    /// The runtime loop applies the change between ticks, so no event is
+    /// decided with half of it. Once this returns, the machine decides every
+    /// event the runtime has not yet taken from the bus. Priorities and
+    /// filters configured on the builder for `M` apply to it.
+    ///
+    /// Fails with [`SeesawError::EngineStopped`] if the runtime has stopped.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Load a plugin without restarting the engine
+    /// handle
+    ///     .add_effect::<SendInvoice, _, _>(InvoiceEffect::new(client))
+    ///     .await?;
+    /// handle.add_machine(InvoiceMachine::default()).await?;
+    /// ```
+    pub async fn add_machine<M: Machine>(&self, machine: M) -> Result<()> {
+        self.control
+            .apply(WiringChange::AddMachine(Box::new(MachineRunner::new(
+                machine,
+            ))))
+            .await
+            .map(|_| ())
+    }
+
+    /// Remove every machine of type `M` from the running engine, including
+    /// machines registered on the builder.
+    ///
+    /// Applied between ticks, like [`add_machine`](Self::add_machine).
+    /// Returns the number of machines removed.
+    pub async fn remove_machine<M: Machine>(&self) -> Result<usize> {
+        self.control
+            .apply(WiringChange::RemoveMachine(TypeId::of::<M>()))
+            .await
+    }
+
+    /// Add an effect for command type `C` to the running engine.
+    ///
+    /// Applied between ticks, like [`add_machine`](Self::add_machine). Add
+    /// the effect before the machine that decides `C`, so its first command
+    /// has somewhere to go.
+    ///
+    /// Fails with [`SeesawError::EffectAlreadyRegistered`] if `C` already
+    /// has an effect, and with [`SeesawError::DepsMismatch`] if `D` is not
+    /// the engine's dependency type.
+    pub async fn add_effect<C, E, D>(&self, effect: E) -> Result<()>
+    where
+        C: Command,
+        E: Effect<C, D>,
+        D: Send + Sync + 'static,
+    {
+        let add: AddEffect<D> = Box::new(move |dispatcher| dispatcher.add_effect::<C, E>(effect));
+        self.control
+            .apply(WiringChange::AddEffect {
+                add: Box::new(add),
+                deps_type: std::any::type_name::<D>(),
+            })
+            .await
+            .map(|_| ())
+    }
+
+    /// Remove the effect for command type `C` added with
+    /// [`add_effect`](Self::add_effect).
+    ///
+    /// Effects registered on the builder are not removed. Commands of type
+    /// `C` dispatched afterwards fail with `NoEffectRegistered`; a batch
+    /// already running finishes. Returns whether an effect was removed.
+    pub async fn remove_effect<C: Command>(&self) -> Result<bool> {
+        self.control
+            .apply(WiringChange::RemoveEffect(TypeId::of::<C>()))
+            .await
+            .map(|removed| removed > 0)
+    }
+
     /// Emit an event to the bus (fire-and-forget).
     ///
     /// Returns immediately. The event will be processed asynchronously.
@@ -527,6 +831,44 @@ impl EngineHandle {
         self.bus.emit(event);
     }
 
+    /// Emit an event carrying request-scoped `metadata` (fire-and-forget).
+    ///
+    /// Effects read it with [`EffectContext::metadata`](crate::EffectContext::metadata)
+    /// and taps from [`TapContext::metadata`](crate::TapContext::metadata);
+    /// events returned by inline effects inherit it.
+    pub fn emit_with_metadata<E: Event>(&self, event: E, metadata: Metadata) {
+        self.bus
+            .emit_envelope(EventEnvelope::new_random(event).with_metadata(metadata));
+    }
+
+    /// Emit an event once `delay` has passed (fire-and-forget).
+    ///
+    /// For time-based follow-ups without external cron: the machine that
+    /// sees the event decides whether it still matters. Pending events are
+    /// held in memory and dropped by [`abort`](Self::abort) or a restart; use
+    /// a [`Scheduled`](crate::ExecutionMode::Scheduled) command when the
+    /// follow-up must be durable.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// handle.emit(OrderEvent::Placed { order_id });
+    /// // OrderMachine ignores Expired for orders confirmed in the meantime
+    /// handle.emit_after(OrderEvent::Expired { order_id }, Duration::from_secs(600));
+    /// ```
+    pub fn emit_after<E: Event>(&self, event: E, delay: Duration) {
+        self.timer
+            .schedule(Instant::now() + delay, EventEnvelope::new_random(event));
+    }
+
+    /// Emit an event at `at` (fire-and-forget).
+    ///
+    /// Emits right away if `at` has passed. See [`emit_after`](Self::emit_after).
+    pub fn emit_at<E: Event>(&self, event: E, at: DateTime<Utc>) {
+        let delay = (at - Utc::now()).to_std().unwrap_or_default();
+        self.emit_after(event, delay);
+    }
+
     /// Emit an event and wait for all inline commands to complete.
     ///
     /// Uses a default timeout of 30 seconds.
@@ -550,12 +892,39 @@ impl EngineHandle {
             .await
     }
 
-    /// Abort the engine's background task.
+    /// Emit an event carrying request-scoped `metadata` and wait for all
+    /// inline commands to complete.
+    ///
+    /// Like [`emit_and_await`](Self::emit_and_await), with the metadata of
+    /// [`emit_with_metadata`](Self::emit_with_metadata).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let metadata = Metadata::new().with(TenantId(claims.tenant)).with(UserId(claims.sub));
+    /// handle
+    ///     .emit_and_await_with_metadata(OrderEvent::PlaceRequested { input }, metadata)
+    ///     .await?;
+    /// ```
+    pub async fn emit_and_await_with_metadata<E: Event>(
+        &self,
+        event: E,
+        metadata: Metadata,
+    ) -> Result<()> {
+        self.await_envelope(event, metadata, Duration::from_secs(30))
+            .await
+    }
+
+    /// Abort the engine's background tasks, including its reapers and
+    /// child engines.
     ///
     /// Call this during test teardown to release resources held by the engine.
     /// After calling this, the engine will no longer process events.
     pub fn abort(&self) {
         self.handle.abort();
+        self.timer.abort();
+        self.reapers.abort();
+        self.children.abort();
     }
 
     /// Emit an event and wait for all inline commands to complete, with custom timeout.
@@ -564,10 +933,26 @@ impl EngineHandle {
     ///
     /// - `Ok(())` if all inline work completed successfully
     /// - `Err` if any inline command failed, or timeout was reached
+    /// - `Err(SeesawError::BusFull)` if the bus's backpressure policy
+    ///   rejected the event (under `Block` this waits for room instead)
+    /// - `Err(SeesawError::BatchTimeout)` if one of the event's inline
+    ///   batches ran longer than [`EngineBuilder::with_batch_timeout`]
     pub async fn emit_and_await_timeout<E: Event>(
         &self,
         event: E,
         timeout: Duration,
+    ) -> Result<()> {
+        self.await_envelope(event, Metadata::default(), timeout)
+            .await
+    }
+
+    /// Emit `event` with `metadata` under a new correlation ID and wait up
+    /// to `timeout` for its inline work.
+    async fn await_envelope<E: Event>(
+        &self,
+        event: E,
+        metadata: Metadata,
+        timeout: Duration,
     ) -> Result<()> {
         let cid = CorrelationId::new();
 
@@ -582,19 +967,139 @@ impl EngineHandle {
         // (Runtime will decrement when it finishes processing)
         self.inflight.inc(cid, 1);
 
-        // Emit the event with correlation
-        self.bus.emit_with_correlation(event, cid);
+        // Emit the event with correlation, waiting for room under `Block`
+        if let Err(e) = self
+            .bus
+            .emit_envelope_async(EventEnvelope::new(cid, event).with_metadata(metadata))
+            .await
+        {
+            self.inflight.abandon(cid);
+            return Err(e.into());
+        }
 
         // Wait for all inline work to complete
-        match tokio::time::timeout(timeout, self.inflight.wait_zero(cid)).await {
+        let completed = tokio::select! {
+            completed = tokio::time::timeout(timeout, self.inflight.wait_zero(cid)) => completed,
+            (batch, limit) = self.overdue_batch(cid) => {
+                warn!(%batch, ?limit, "inline batch exceeded its timeout");
+                self.inflight.abandon(cid);
+                return Err(SeesawError::BatchTimeout { batch, limit }.into());
+            }
+        };
+        match completed {
             Ok(result) => result,
             Err(_) => {
                 // Timeout - clean up the entry to prevent leak
-                self.inflight.entries.remove(&cid);
+                self.inflight.abandon(cid);
                 Err(SeesawError::Timeout { duration: timeout }.into())
             }
         }
     }
+
+    /// Resolve with the first batch for `cid` to exceed the batch timeout;
+    /// never resolves without one.
+    async fn overdue_batch(&self, cid: CorrelationId) -> (InflightBatchInfo, Duration) {
+        let Some(limit) = self.batch_timeout else {
+            return std::future::pending().await;
+        };
+        let poll = (limit / 10).max(Duration::from_millis(1));
+        loop {
+            tokio::time::sleep(poll).await;
+            if let Some(batch) = self.inflight.overdue_batch(cid, limit) {
+                return (batch, limit);
+            }
+        }
+    }
+
+    /// Emit a request event and wait for a correlated response event.
+    ///
+    /// Returns the first `Resp` emitted with the request's correlation ID
+    /// for which `matcher` returns `true`. Because correlation propagates
+    /// through commands and effects, the response may come from anywhere in
+    /// the cascade the request triggers.
+    ///
+    /// # Errors
+    ///
+    /// - The sanitized message of a correlated `CommandFailed` seen before a
+    ///   matching response
+    /// - `SeesawError::BusFull` if the bus rejected the request
+    /// - A timeout error if no matching response arrived in time
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// async fn create_user(input: CreateUser, handle: &EngineHandle) -> Result<User> {
+    ///     let created = handle
+    ///         .request::<UserEvent, UserEvent>(
+    ///             UserEvent::CreateRequested { input },
+    ///             |e| matches!(e, UserEvent::Created { .. }),
+    ///             Duration::from_secs(5),
+    ///         )
+    ///         .await?;
+    ///     // ...
+    /// }
+    /// ```
+    pub async fn request<Req, Resp>(
+        &self,
+        event: Req,
+        matcher: impl Fn(&Resp) -> bool,
+        timeout: Duration,
+    ) -> Result<Resp>
+    where
+        Req: Event + Clone,
+        Resp: Event + Clone,
+    {
+        dispatch_request_timeout(event, &self.bus, timeout, |m| {
+            m.try_match(|resp: &Resp| matcher(resp).then(|| Ok(resp.clone())))
+                .result()
+        })
+        .await
+    }
+
+    /// Wait for the first `E` event for which `matcher` returns `true`,
+    /// whatever produced it.
+    ///
+    /// Emits nothing: use it to wait for an event a background job or
+    /// another request will emit, possibly much later. Subscribes when
+    /// called, so create the future before triggering the event. Fails with
+    /// `SeesawError::Timeout` if no event matches within `timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let summarized = handle.await_event(
+    ///     move |e: &Summarized| e.task_id == task_id,
+    ///     Duration::from_secs(300),
+    /// );
+    /// handle.emit(SummarizeRequested { task_id });
+    /// let summary = summarized.await?;
+    /// ```
+    pub fn await_event<E, F>(
+        &self,
+        matcher: F,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<E>> + Send + 'static
+    where
+        E: Event + Clone,
+        F: Fn(&E) -> bool + Send + 'static,
+    {
+        await_event(&self.bus, None, matcher, timeout)
+    }
+
+    /// Like [`await_event`](Self::await_event), considering only events
+    /// correlated with `cid`.
+    pub fn await_correlated<E, F>(
+        &self,
+        cid: CorrelationId,
+        matcher: F,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<E>> + Send + 'static
+    where
+        E: Event + Clone,
+        F: Fn(&E) -> bool + Send + 'static,
+    {
+        await_event(&self.bus, Some(cid), matcher, timeout)
+    }
 }
 
 impl std::fmt::Debug for EngineHandle {
@@ -609,6 +1114,12 @@ impl std::fmt::Debug for EngineHandle {
 // Engine Builder
 // =============================================================================
 
+/// Deferred runtime configuration (machine registration) applied at build time.
+type RuntimeStep<D> = Box<dyn FnOnce(Runtime<D>) -> Runtime<D> + Send>;
+
+/// Deferred dispatcher configuration (effect registration) applied at build time.
+type DispatcherStep<D> = Box<dyn FnOnce(Dispatcher<D>) -> Dispatcher<D> + Send>;
+
 /// Builder for constructing an Engine with machines, effects, and taps.
 ///
 /// # Example
@@ -628,9 +1139,15 @@ pub struct EngineBuilder<D> {
     bus: EventBus,
     inflight: Arc<InflightTracker>,
     job_queue: Option<Arc<dyn crate::dispatch::JobQueue>>,
-    machines: Vec<Box<dyn FnOnce(Runtime<D>) -> Runtime<D> + Send>>,
-    effects: Vec<Box<dyn FnOnce(Dispatcher<D>) -> Dispatcher<D> + Send>>,
+    machines: Vec<RuntimeStep<D>>,
+    effects: Vec<DispatcherStep<D>>,
     taps: TapRegistry,
+    batch_timeout: Option<Duration>,
+    wiring: Wiring,
+    reapers: Vec<Reaper<D>>,
+    trace: Option<TraceRecorder>,
+    schemas: SchemaRegistry,
+    children: Vec<ChildEngine>,
 }
 
 impl<D: Send + Sync + 'static> EngineBuilder<D> {
@@ -644,6 +1161,12 @@ impl<D: Send + Sync + 'static> EngineBuilder<D> {
             machines: Vec::new(),
             effects: Vec::new(),
             taps: TapRegistry::new(),
+            batch_timeout: None,
+            wiring: Wiring::default(),
+            reapers: Vec::new(),
+            trace: None,
+            schemas: SchemaRegistry::new(),
+            children: Vec::new(),
         }
     }
 
@@ -659,6 +1182,12 @@ impl<D: Send + Sync + 'static> EngineBuilder<D> {
             machines: Vec::new(),
             effects: Vec::new(),
             taps: TapRegistry::new(),
+            batch_timeout: None,
+            wiring: Wiring::default(),
+            reapers: Vec::new(),
+            trace: None,
+            schemas: SchemaRegistry::new(),
+            children: Vec::new(),
         }
     }
 
@@ -671,6 +1200,105 @@ impl<D: Send + Sync + 'static> EngineBuilder<D> {
         self
     }
 
+    /// Fail `emit_and_await` as soon as one of the event's inline batches
+    /// has executed for longer than `timeout`.
+    ///
+    /// The call returns [`SeesawError::BatchTimeout`] describing the stuck
+    /// batch instead of waiting out the whole await timeout. The batch itself
+    /// keeps running; pair this with `with_effect_timeout` to cancel it.
+    pub fn with_batch_timeout(mut self, timeout: Duration) -> Self {
+        self.batch_timeout = Some(timeout);
+        self
+    }
+
+    /// Run `reaper` once the engine starts, then every `interval`.
+    ///
+    /// Reapers recover work lost to a crash: scan entity status fields for
+    /// records stuck mid-workflow and emit the events that resume them. They
+    /// stop with the runtime or [`EngineHandle::abort`]; errors and panics
+    /// are logged and the reaper runs again next interval. See the
+    /// [`ReaperContext`] docs for an example.
+    pub fn with_reaper<F, Fut>(mut self, interval: Duration, reaper: F) -> Self
+    where
+        F: Fn(ReaperContext<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.reapers.push(Reaper::new(interval, reaper));
+        self
+    }
+
+    /// Record a causality trace per correlation in `recorder`, for
+    /// [`EngineHandle::export_trace`].
+    ///
+    /// Keep a clone of the recorder to read traces without the handle.
+    pub fn with_trace_recorder(mut self, recorder: TraceRecorder) -> Self {
+        let runtime_recorder = recorder.clone();
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_trace_recorder(runtime_recorder)
+        }));
+        self.trace = Some(recorder);
+        self
+    }
+
+    /// Add the costs effects report with
+    /// [`EffectContext::record_cost`](crate::EffectContext::record_cost) to
+    /// `ledger`, for [`EngineHandle::costs`].
+    ///
+    /// Keep a clone of the ledger to read totals without the handle.
+    pub fn with_cost_ledger(mut self, ledger: CostLedger) -> Self {
+        self.effects
+            .push(Box::new(move |dispatcher| dispatcher.with_cost_ledger(ledger)));
+        self
+    }
+
+    /// Add the JSON Schema of event type `E` to [`EngineHandle::schemas`].
+    ///
+    /// Only documents the event; machines and taps still need registering.
+    #[cfg(feature = "schemas")]
+    pub fn with_event_schema<E: Event + schemars::JsonSchema>(mut self) -> Self {
+        self.schemas.register_event::<E>();
+        self
+    }
+
+    /// Add the JSON Schema of command type `C` to [`EngineHandle::schemas`].
+    ///
+    /// Only documents the command; its effect still needs registering.
+    #[cfg(feature = "schemas")]
+    pub fn with_command_schema<C: Command + schemars::JsonSchema>(mut self) -> Self {
+        self.schemas.register_command::<C>();
+        self
+    }
+
+    /// Run `child` as a sub-engine, forwarding it the event types selected
+    /// with [`ChildEngine::forward`].
+    ///
+    /// The child keeps its own bus, machines, effects and deps. Its health
+    /// is reported in [`Health::children`] and its readiness gates this
+    /// engine's. See [`ChildEngine`] for an example.
+    pub fn with_child(mut self, child: ChildEngine) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Drop events of type `E` that wait longer than `ttl` on the bus
+    /// before the runtime reads them.
+    ///
+    /// Sets the TTL on the engine's bus, so call it after
+    /// [`with_bus`](Self::with_bus). See [`EventBus::with_ttl`].
+    pub fn with_event_ttl<E: Event>(mut self, ttl: Duration) -> Self {
+        self.bus = self.bus.with_ttl::<E>(ttl);
+        self
+    }
+
+    /// Report events that reach nobody to `sink`.
+    ///
+    /// Installs the sink on the engine's bus, so call it after
+    /// [`with_bus`](Self::with_bus). See [`EventBus::with_dead_letters`].
+    pub fn with_dead_letters(mut self, sink: crate::dead_letter::DeadLetterSink) -> Self {
+        self.bus = self.bus.with_dead_letters(sink);
+        self
+    }
+
     /// Use an existing inflight tracker instead of creating a new one.
     ///
     /// This is useful when you need to share the tracker with other systems.
@@ -683,736 +1311,2304 @@ impl<D: Send + Sync + 'static> EngineBuilder<D> {
     ///
     /// When a command has `ExecutionMode::Background` or `ExecutionMode::Scheduled`,
     /// the dispatcher will route it to this job queue instead of executing inline.
+    ///
+    /// A [`JobWorker`](crate::JobWorker) running the jobs announces
+    /// [`JobClaimed`](crate::JobClaimed), [`JobHeartbeatMissed`](crate::JobHeartbeatMissed)
+    /// and [`JobDeadLettered`](crate::JobDeadLettered) on its dispatcher's
+    /// bus; give it this engine's bus for machines to see them.
     pub fn with_job_queue(mut self, job_queue: Arc<dyn crate::dispatch::JobQueue>) -> Self {
         self.job_queue = Some(job_queue);
         self
     }
 
+    /// Encode background and scheduled payloads with `codecs`.
+    ///
+    /// The job queue receives them through `enqueue_encoded` /
+    /// `schedule_encoded`. See [`Dispatcher::with_payload_codecs`].
+    pub fn with_payload_codecs(mut self, codecs: crate::codec::PayloadCodecs) -> Self {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_payload_codecs(codecs)
+        }));
+        self
+    }
+
     /// Register a machine that listens to events and emits commands.
     ///
-    /// Machines are called in the order they are registered.
+    /// Machines are called in the order they are registered, unless
+    /// reordered with [`with_machine_priority`](Self::with_machine_priority).
     /// Each machine can independently decide whether to emit a command
     /// based on the event it receives.
     pub fn with_machine<M>(mut self, machine: M) -> Self
     where
         M: Machine + 'static,
     {
+        self.wiring.machine::<M, M::Command>();
         self.machines
             .push(Box::new(move |runtime| runtime.with_machine(machine)));
         self
     }
 
-    /// Register an effect handler for a command type.
+    /// Set the decide priority of every machine of type `M`.
     ///
-    /// When a command of type `C` is dispatched, the registered effect
-    /// will be called to execute it.
-    pub fn with_effect<C, E>(mut self, effect: E) -> Self
+    /// Higher priorities decide first, and inline commands from one event
+    /// dispatch in decide order. See [`Runtime::with_machine_priority`].
+    pub fn with_machine_priority<M>(mut self, priority: i32) -> Self
     where
-        C: Command,
-        E: Effect<C, D>,
+        M: Machine + 'static,
     {
-        self.effects.push(Box::new(move |dispatcher| {
-            dispatcher.with_effect::<C, E>(effect)
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_machine_priority::<M>(priority)
         }));
         self
     }
 
-    /// Register an event tap for observing events.
+    /// Only pass events of type `E` to machines of type `M` when `filter`
+    /// returns true.
     ///
-    /// Taps run **after** effects complete. They observe committed facts
-    /// and cannot emit new events or access dependencies.
-    ///
-    /// Use taps for:
-    /// - Publishing to NATS/Kafka
-    /// - Sending webhooks
-    /// - Recording metrics
-    /// - Audit logging
+    /// The filter runs before the event is wrapped in a selector, before
+    /// machine middleware and before `decide`. See
+    /// [`Runtime::with_machine_filter`].
     ///
     /// # Example
     ///
     /// ```ignore
-    /// .with_event_tap::<EntryEvent, _>(NatsPublishTap::new(client))
-    /// .with_event_tap::<DeckEvent, _>(MetricsTap::new())
+    /// .with_machine(ShippingMachine::default())
+    /// .with_machine_filter::<ShippingMachine, OrderEvent, _>(|e| {
+    ///     matches!(e, OrderEvent::Paid { .. })
+    /// })
     /// ```
-    pub fn with_event_tap<E, T>(mut self, tap: T) -> Self
+    pub fn with_machine_filter<M, E, F>(mut self, filter: F) -> Self
     where
-        E: Event + Clone,
-        T: EventTap<E>,
+        M: Machine + 'static,
+        E: Event,
+        F: Fn(&E) -> bool + Send + Sync + 'static,
     {
-        self.taps.register::<E, T>(tap, std::any::type_name::<T>());
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_machine_filter::<M, E, F>(filter)
+        }));
         self
     }
 
-    /// Build the engine.
+    /// Register a machine that subscribes to several event types.
     ///
-    /// This creates the dispatcher, registers effects, builds the runtime,
-    /// and connects everything to the event bus.
-    pub fn build(self) -> Engine<D> {
-        // Build dispatcher with effects (use from_arc since deps is already Arc)
-        // Include job queue if configured for background command execution
-        let mut dispatcher = match self.job_queue {
-            Some(jq) => Dispatcher::from_arc_with_job_queue(self.deps, self.bus.clone(), jq),
-            None => Dispatcher::from_arc(self.deps, self.bus.clone()),
-        };
+    /// The machine's `Event` is an [`EventSelector`](crate::EventSelector)
+    /// enum wrapping each event type, so bounded contexts can keep separate
+    /// event enums. See [`Runtime::with_selector_machine`].
+    pub fn with_selector_machine<M>(mut self, machine: M) -> Self
+    where
+        M: Machine + 'static,
+        M::Event: crate::machine::EventSelector,
+    {
+        self.wiring.machine::<M, M::Command>();
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_selector_machine(machine)
+        }));
+        self
+    }
+
+    /// Register a process manager for a multi-step, long-running flow.
+    ///
+    /// State is kept per correlation ID, step timeouts emit
+    /// [`ProcessTimedOut`](crate::ProcessTimedOut), and expired steps emit
+    /// compensation commands. See [`ProcessManager`](crate::ProcessManager).
+    pub fn with_process_manager<P>(mut self, manager: P) -> Self
+    where
+        P: crate::process::ProcessManager,
+    {
+        self.wiring.machine::<P, P::Command>();
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_process_manager(manager)
+        }));
+        self
+    }
+
+    /// Register a machine whose state is visible to machine middleware.
+    ///
+    /// Like [`with_machine`](Self::with_machine), but in debug builds each
+    /// [`Decision`](crate::Decision) passed to machine middleware carries the
+    /// machine's `{:?}` state after the call.
+    pub fn with_debug_machine<M>(mut self, machine: M) -> Self
+    where
+        M: Machine + std::fmt::Debug + 'static,
+    {
+        self.wiring.machine::<M, M::Command>();
+        self.machines
+            .push(Box::new(move |runtime| runtime.with_debug_machine(machine)));
+        self
+    }
+
+    /// Add a machine whose state is persisted to the snapshot store.
+    ///
+    /// See [`with_snapshot_store`](Self::with_snapshot_store).
+    pub fn with_snapshot_machine<M>(mut self, machine: M) -> Self
+    where
+        M: SnapshotMachine + 'static,
+    {
+        self.wiring.machine::<M, M::Command>();
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_snapshot_machine(machine)
+        }));
+        self
+    }
+
+    /// Add a machine that is restarted or stopped when it misbehaves.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_supervised_machine(
+    ///         OrderMachine::new,
+    ///         SupervisorPolicy::new(SupervisorAction::Restart).with_max_panics(3),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn with_supervised_machine<M, F>(mut self, factory: F, policy: SupervisorPolicy) -> Self
+    where
+        M: Machine + 'static,
+        F: Fn() -> M + Send + Sync + 'static,
+    {
+        self.wiring.machine::<M, M::Command>();
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_supervised_machine(factory, policy)
+        }));
+        self
+    }
+
+    /// Persist snapshot machines to a store every `interval`.
+    ///
+    /// Snapshot machines are restored from the store when the engine starts,
+    /// so long-lived machine state survives restarts without a full replay.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_snapshot_machine(SeenOrdersMachine::default())
+    ///     .with_snapshot_store(Arc::new(PgSnapshotStore::new(pool)), Duration::from_secs(60))
+    ///     .build();
+    /// ```
+    pub fn with_snapshot_store(
+        mut self,
+        store: Arc<dyn SnapshotStore>,
+        interval: Duration,
+    ) -> Self {
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_snapshot_store(store, interval)
+        }));
+        self
+    }
+
+    /// Add a middleware that runs around every `Machine::decide` call.
+    ///
+    /// Use it to trace (event, machine, decision) tuples or measure decision
+    /// latency; [`DecisionLogger`](crate::DecisionLogger) logs every decision
+    /// at debug level. Middleware observes decisions but cannot change them.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_machine_middleware(DecisionLogger)
+    ///     .with_machine(OrderMachine::default())
+    ///     .build();
+    /// ```
+    pub fn with_machine_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: MachineMiddleware,
+    {
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_machine_middleware(middleware)
+        }));
+        self
+    }
+
+    /// Dispatch inline commands through `lanes` priority lanes.
+    ///
+    /// Commands with a higher [`Command::priority`] are dispatched ahead of
+    /// lower-priority inline work that is queued up at the same time. See
+    /// [`Runtime::with_priority_lanes`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_priority_lanes(2)
+    ///     .with_machine(LookupMachine)
+    ///     .with_machine(ReindexMachine)
+    ///     .build();
+    /// ```
+    pub fn with_priority_lanes(mut self, lanes: u8) -> Self {
+        self.machines
+            .push(Box::new(move |runtime| runtime.with_priority_lanes(lanes)));
+        self
+    }
+
+    /// Decide up to `max_events` waiting events per tick, dispatching their
+    /// commands together.
+    ///
+    /// Under load, same-type inline commands for one correlation ID run as
+    /// one batch and background commands are enqueued together. See
+    /// [`Runtime::with_micro_batching`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_micro_batching(128)
+    ///     .with_machine(IndexMachine)
+    ///     .with_effect::<IndexCommand, _>(BulkIndexEffect)
+    ///     .build();
+    /// ```
+    pub fn with_micro_batching(mut self, max_events: usize) -> Self {
+        self.machines.push(Box::new(move |runtime| {
+            runtime.with_micro_batching(max_events)
+        }));
+        self
+    }
+
+    /// Drop events more than `max_hops` deep in their causation chain.
+    ///
+    /// Guards against machines that feed on their own effects' events. See
+    /// [`Runtime::with_max_hops`].
+    pub fn with_max_hops(mut self, max_hops: u32) -> Self {
+        self.machines
+            .push(Box::new(move |runtime| runtime.with_max_hops(max_hops)));
+        self
+    }
+
+    /// Dispatch inline commands on parallel workers, keyed by event.
+    ///
+    /// Effects for events with the same key run in order; different keys
+    /// run in parallel. See [`Runtime::with_sharding`].
+    pub fn with_sharding(mut self, sharding: crate::shard::Sharding) -> Self {
+        self.machines
+            .push(Box::new(move |runtime| runtime.with_sharding(sharding)));
+        self
+    }
+
+    /// Write a structured audit record for every envelope to `sink`.
+    ///
+    /// Available in release builds with the `audit` feature. See
+    /// [`Runtime::with_audit_sink`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_machine(OrderMachine::default())
+    ///     .with_audit_sink(Arc::new(JsonLinesAuditSink::stdout()))
+    ///     .build();
+    /// ```
+    #[cfg(feature = "audit")]
+    pub fn with_audit_sink(mut self, sink: Arc<dyn crate::audit_sink::AuditSink>) -> Self {
+        self.machines
+            .push(Box::new(move |runtime| runtime.with_audit_sink(sink)));
+        self
+    }
+
+    /// Register an effect handler for a command type.
+    ///
+    /// When a command of type `C` is dispatched, the registered effect
+    /// will be called to execute it.
+    pub fn with_effect<C, E>(mut self, effect: E) -> Self
+    where
+        C: Command,
+        E: Effect<C, D>,
+    {
+        self.wiring.effect::<C>();
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_effect::<C, E>(effect)
+        }));
+        self
+    }
+
+    /// Register an async closure as the effect for command type `C`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect_fn::<FetchCommand, _>(|cmd, ctx| async move {
+    ///         let body = ctx.deps().http.get(&cmd.url).await?;
+    ///         Ok(FetchEvent::Fetched { url: cmd.url, bytes: body.len() })
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn with_effect_fn<C, F>(self, f: F) -> Self
+    where
+        C: Command,
+        F: EffectFn<C, D>,
+    {
+        self.with_effect::<C, FnEffect<F>>(FnEffect::new(f))
+    }
+
+    /// Register an effect for one variant of enum command `C`.
+    ///
+    /// See [`Dispatcher::with_effect_for`].
+    pub fn with_effect_for<C, V, E>(mut self, effect: E) -> Self
+    where
+        C: CommandVariant<V>,
+        V: Command,
+        E: Effect<V, D>,
+    {
+        self.wiring.variant_effect::<C, V>();
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_effect_for::<C, V, E>(effect)
+        }));
+        self
+    }
+
+    /// Add a middleware that runs around every inline effect execution.
+    ///
+    /// Use middleware for concerns that apply to all effects - logging,
+    /// metrics, auth context - instead of decorating each effect by hand.
+    /// Middleware runs in registration order, outermost first. See
+    /// [`EffectMiddleware`] for the hooks available.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect_middleware(LogMiddleware)
+    ///     .with_effect::<FetchCommand, _>(FetchEffect)
+    ///     .build();
+    /// ```
+    pub fn with_effect_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: EffectMiddleware<D>,
+    {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_middleware(middleware)
+        }));
+        self
+    }
+
+    /// Set an execution budget for the effect handling command type `C`.
+    ///
+    /// Inline executions that exceed `timeout` are cancelled and reported as
+    /// failures: a `CommandFailed` event is emitted and `emit_and_await`
+    /// returns [`SeesawError::EffectTimeout`] instead of waiting forever on a
+    /// hung call. See [`Dispatcher::with_effect_timeout`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect::<FetchCommand, _>(FetchEffect)
+    ///     .with_effect_timeout::<FetchCommand>(Duration::from_secs(10))
+    ///     .build();
+    /// ```
+    pub fn with_effect_timeout<C>(mut self, timeout: Duration) -> Self
+    where
+        C: Command,
+    {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_effect_timeout::<C>(timeout)
+        }));
+        self
+    }
+
+    /// Cap the number of concurrent inline executions of command type `C`.
+    ///
+    /// Dispatches beyond `max` wait for a free slot, so a flood of events
+    /// cannot turn into an unbounded number of simultaneous calls from one
+    /// effect. See [`Dispatcher::with_concurrency_limit`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect::<FetchCommand, _>(FetchEffect)
+    ///     .with_concurrency_limit::<FetchCommand>(16)
+    ///     .build();
+    /// ```
+    pub fn with_concurrency_limit<C>(mut self, max: usize) -> Self
+    where
+        C: Command,
+    {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_concurrency_limit::<C>(max)
+        }));
+        self
+    }
+
+    /// Rate limit inline executions of command type `C` with a token bucket.
+    ///
+    /// Over budget, executions are delayed or - in `ThrottleMode::Reject` -
+    /// fail with [`SeesawError::Throttled`] and emit a `Throttled` event.
+    /// See [`Dispatcher::with_rate_limit`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect::<GeocodeCommand, _>(GeocodeEffect)
+    ///     .with_rate_limit::<GeocodeCommand>(RateLimitPolicy::per_second(10))
+    ///     .build();
+    /// ```
+    pub fn with_rate_limit<C>(mut self, policy: RateLimitPolicy) -> Self
+    where
+        C: Command,
+    {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_rate_limit::<C>(policy)
+        }));
+        self
+    }
+
+    /// Report effect failures caused by a domain error `E` with its
+    /// [`Categorizable`](crate::Categorizable) category, retryability and
+    /// safe message, so machines can branch on `CommandFailed::category`.
+    ///
+    /// See [`Dispatcher::with_error_category`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect::<ChargeCommand, _>(ChargeEffect)
+    ///     .with_error_category::<PaymentError>()
+    ///     .build();
+    /// ```
+    pub fn with_error_category<E>(mut self) -> Self
+    where
+        E: crate::Categorizable + Send + Sync + 'static,
+    {
+        self.effects
+            .push(Box::new(|dispatcher| dispatcher.with_error_category::<E>()));
+        self
+    }
+
+    /// Scrub effect failures with `redactor` before they are recorded in
+    /// audit records and traces or reported in `CommandFailed` events.
+    ///
+    /// See [`Dispatcher::with_redactor`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_redactor(Redactor::new().with_secret(&config.stripe_key))
+    ///     .build();
+    /// ```
+    pub fn with_redactor(mut self, redactor: crate::Redactor) -> Self {
+        self.effects
+            .push(Box::new(move |dispatcher| dispatcher.with_redactor(redactor)));
+        self
+    }
+
+    /// Rate limit all inline executions, across every command type.
+    ///
+    /// See [`Dispatcher::with_global_rate_limit`] for details.
+    pub fn with_global_rate_limit(mut self, policy: RateLimitPolicy) -> Self {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_global_rate_limit(policy)
+        }));
+        self
+    }
+
+    /// Guard the effect handling command type `C` with a circuit breaker.
+    ///
+    /// Once the effect's failure rate trips the breaker, commands of type `C`
+    /// fail fast with [`SeesawError::CircuitOpen`] (surfacing as
+    /// `CommandFailed`) until a half-open probe succeeds, shielding the
+    /// downstream service during an incident. See
+    /// [`Dispatcher::with_circuit_breaker`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect::<ChargeCommand, _>(ChargeEffect)
+    ///     .with_circuit_breaker::<ChargeCommand>(CircuitBreakerPolicy::new())
+    ///     .build();
+    /// ```
+    pub fn with_circuit_breaker<C>(mut self, policy: CircuitBreakerPolicy) -> Self
+    where
+        C: Command,
+    {
+        self.effects.push(Box::new(move |dispatcher| {
+            dispatcher.with_circuit_breaker::<C>(policy)
+        }));
+        self
+    }
+
+    /// Register an effect that retries transient failures under `policy`.
+    ///
+    /// Shorthand for `with_effect::<C, _>(RetryingEffect::new(effect, policy))`.
+    /// Only once the policy gives up does the dispatcher emit `CommandFailed`.
+    /// The effect must be idempotent, and the command `Clone`, since each
+    /// attempt re-executes it from scratch.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let engine = EngineBuilder::new(deps)
+    ///     .with_effect_retry::<FetchCommand, _>(
+    ///         FetchEffect,
+    ///         RetryPolicy::new().with_max_attempts(5),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn with_effect_retry<C, E>(self, effect: E, policy: RetryPolicy) -> Self
+    where
+        C: Command + Clone,
+        E: Effect<C, D>,
+    {
+        self.with_effect::<C, _>(RetryingEffect::new(effect, policy))
+    }
+
+    /// Register an event tap for observing events.
+    ///
+    /// Taps run **after** effects complete. They observe committed facts
+    /// and cannot emit new events or access dependencies.
+    ///
+    /// Use taps for:
+    /// - Publishing to NATS/Kafka
+    /// - Sending webhooks
+    /// - Recording metrics
+    /// - Audit logging
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .with_event_tap::<EntryEvent, _>(NatsPublishTap::new(client))
+    /// .with_event_tap::<DeckEvent, _>(MetricsTap::new())
+    /// ```
+    pub fn with_event_tap<E, T>(self, tap: T) -> Self
+    where
+        E: Event + Clone,
+        T: EventTap<E>,
+    {
+        self.with_event_tap_policy::<E, T>(tap, TapPolicy::default())
+    }
+
+    /// Register an event tap with a custom buffer size and lag policy.
+    ///
+    /// Each tap buffers events for its own delivery task. When a tap falls
+    /// behind and the buffer fills, the [`TapLagPolicy`](crate::TapLagPolicy)
+    /// decides which event is dropped; dispatch never waits on a tap.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .with_event_tap_policy::<OrderEvent, _>(
+    ///     AnalyticsTap::new(client),
+    ///     TapPolicy::new(10_000).with_lag_policy(TapLagPolicy::DropOldest),
+    /// )
+    /// ```
+    pub fn with_event_tap_policy<E, T>(mut self, tap: T, policy: TapPolicy) -> Self
+    where
+        E: Event + Clone,
+        T: EventTap<E>,
+    {
+        self.taps
+            .register::<E, T>(tap, std::any::type_name::<T>(), policy);
+        self
+    }
+
+    /// Check that every command a registered machine decides has an
+    /// effect, and that no command has two.
+    ///
+    /// Returns [`SeesawError::InvalidWiring`] listing every problem when a
+    /// command would fail to dispatch or `build` would panic. Otherwise
+    /// returns the report, which may hold warnings such as effects no
+    /// machine reaches. Commands routed to a job worker need a registry;
+    /// see [`validate_with_registry`](Self::validate_with_registry).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let builder = EngineBuilder::new(deps)
+    ///     .with_machine(OrderMachine::default())
+    ///     .with_effect::<OrderCommand, _>(OrderEffect);
+    /// builder.validate()?;
+    /// let engine = builder.build();
+    /// ```
+    pub fn validate(&self) -> Result<WiringReport, SeesawError> {
+        self.check_wiring(None)
+    }
+
+    /// Like [`validate`](Self::validate), but a command without an effect
+    /// is accepted when a job queue is configured and `registry` can
+    /// deserialize it for a worker.
+    pub fn validate_with_registry(
+        &self,
+        registry: &crate::job::CommandRegistry,
+    ) -> Result<WiringReport, SeesawError> {
+        self.check_wiring(Some(registry))
+    }
+
+    fn check_wiring(
+        &self,
+        registry: Option<&crate::job::CommandRegistry>,
+    ) -> Result<WiringReport, SeesawError> {
+        let report = self.wiring.validate(self.job_queue.is_some(), registry);
+        if report.is_valid() {
+            Ok(report)
+        } else {
+            Err(SeesawError::InvalidWiring { report })
+        }
+    }
+
+    /// Build the engine.
+    ///
+    /// This creates the dispatcher, registers effects, builds the runtime,
+    /// and connects everything to the event bus.
+    pub fn build(self) -> Engine<D> {
+        // Build dispatcher with effects (use from_arc since deps is already Arc)
+        // Include job queue if configured for background command execution
+        let deps = self.deps.clone();
+        let mut dispatcher = match self.job_queue {
+            Some(jq) => Dispatcher::from_arc_with_job_queue(self.deps, self.bus.clone(), jq),
+            None => Dispatcher::from_arc(self.deps, self.bus.clone()),
+        };
         for add_effect in self.effects {
             dispatcher = add_effect(dispatcher);
         }
 
-        // Build runtime with machines, taps, and inflight tracker
-        let mut runtime = Runtime::new(dispatcher, self.bus.clone())
-            .with_inflight(self.inflight.clone())
-            .with_taps(self.taps);
-        for add_machine in self.machines {
-            runtime = add_machine(runtime);
-        }
+        // Build runtime with machines, taps, and inflight tracker
+        let mut runtime = Runtime::new(dispatcher, self.bus.clone())
+            .with_inflight(self.inflight.clone())
+            .with_taps(self.taps);
+        for add_machine in self.machines {
+            runtime = add_machine(runtime);
+        }
+
+        Engine {
+            runtime,
+            deps,
+            bus: self.bus,
+            inflight: self.inflight,
+            batch_timeout: self.batch_timeout,
+            reapers: self.reapers,
+            trace: self.trace,
+            schemas: Arc::new(self.schemas),
+            children: self.children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effect_impl::EffectContext;
+    use anyhow::Result;
+    use std::time::Duration;
+
+    // ==========================================================================
+    // Inflight Tracker Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_inflight_tracker_basic() {
+        let tracker = InflightTracker::new();
+        let cid = CorrelationId::new();
+
+        // Create entry and increment
+        tracker.inc(cid, 1);
+
+        // Entry should exist with count 1
+        let entry = tracker.entries.get(&cid).unwrap();
+        assert_eq!(entry.count.load(Ordering::Relaxed), 1);
+        drop(entry);
+
+        // Decrement to zero
+        tracker.dec(cid, 1);
+
+        // Entry should be removed
+        assert!(tracker.entries.get(&cid).is_none());
+    }
+
+    #[test]
+    fn test_inflight_tracker_multiple_inc_dec() {
+        let tracker = InflightTracker::new();
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+        tracker.inc(cid, 2);
+
+        let entry = tracker.entries.get(&cid).unwrap();
+        assert_eq!(entry.count.load(Ordering::Relaxed), 3);
+        drop(entry);
+
+        tracker.dec(cid, 2);
+
+        let entry = tracker.entries.get(&cid).unwrap();
+        assert_eq!(entry.count.load(Ordering::Relaxed), 1);
+        drop(entry);
+
+        tracker.dec(cid, 1);
+        assert!(tracker.entries.get(&cid).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wait_zero_immediate() {
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        // Entry doesn't exist - should return immediately
+        let result = tracker.wait_zero(cid).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wait_zero_with_work() {
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+
+        let tracker_clone = tracker.clone();
+        let handle = tokio::spawn(async move {
+            // Simulate work
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tracker_clone.dec(cid, 1);
+        });
+
+        let result = tracker.wait_zero(cid).await;
+        assert!(result.is_ok());
+
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_zero_with_error() {
+        // This tests the real-world pattern: wait_zero is called BEFORE dec
+        // (which is how emit_and_await works - it starts waiting before work completes)
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+        tracker.record_error(cid, anyhow::anyhow!("test error"));
+
+        // Start waiting BEFORE dec (this is what emit_and_await does)
+        let tracker_clone = tracker.clone();
+        let wait_handle = tokio::spawn(async move { tracker_clone.wait_zero(cid).await });
+
+        // Give the waiter time to register
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        // Now dec - waiter exists, so error entry is preserved
+        tracker.dec(cid, 1);
+
+        let result = wait_handle.await.unwrap();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("test error"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_zero_without_waiter_cleans_error() {
+        // If no one is waiting when dec() hits zero, error is discarded
+        // to prevent memory leaks in fire-and-forget patterns
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+        tracker.record_error(cid, anyhow::anyhow!("test error"));
+        tracker.dec(cid, 1);
+        // No waiter existed, so entry is cleaned up (error discarded)
+
+        // wait_zero called AFTER dec: entry gone, returns Ok
+        let result = tracker.wait_zero(cid).await;
+        assert!(result.is_ok(), "Expected Ok when entry already cleaned up");
+    }
+
+    #[test]
+    fn test_batches_report_running_batches_until_completed() {
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        let batch = tracker.begin_described_batch(cid, 2, "SendEmail", Some("OrderEvent"));
+        let running = tracker.batches();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].cid, cid);
+        assert_eq!(running[0].command_type, "SendEmail");
+        assert_eq!(running[0].event_type, Some("OrderEvent"));
+        assert_eq!(running[0].size, 2);
+
+        batch.complete(BatchOutcome::Complete);
+        assert!(tracker.batches().is_empty());
+    }
+
+    #[test]
+    fn test_inflight_guard_for_event() {
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        // Simulate emit doing the increment
+        tracker.inc(cid, 1);
+
+        {
+            let _guard = InflightGuard::for_event(tracker.clone(), cid);
+            let entry = tracker.entries.get(&cid).unwrap();
+            assert_eq!(entry.count.load(Ordering::Relaxed), 1);
+        }
+
+        // Guard dropped, entry should be removed
+        assert!(tracker.entries.get(&cid).is_none());
+    }
+
+    #[test]
+    fn test_inflight_guard_panic_safety() {
+        let tracker = Arc::new(InflightTracker::new());
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = InflightGuard::for_event(tracker.clone(), cid);
+            panic!("simulated panic");
+        }));
+
+        assert!(result.is_err());
+
+        // Entry should still be removed despite panic
+        assert!(tracker.entries.get(&cid).is_none());
+    }
+
+    #[test]
+    fn test_first_error_wins() {
+        let tracker = InflightTracker::new();
+        let cid = CorrelationId::new();
+
+        tracker.inc(cid, 1);
+        tracker.record_error(cid, anyhow::anyhow!("first error"));
+        tracker.record_error(cid, anyhow::anyhow!("second error"));
+
+        let entry = tracker.entries.get(&cid).unwrap();
+        let err = entry.first_error.lock().expect("mutex not poisoned");
+        assert!(err.as_ref().unwrap().to_string().contains("first error"));
+    }
+
+    // ==========================================================================
+    // Engine Tests
+    // ==========================================================================
+
+    // Test types
+    #[derive(Debug, Clone)]
+    struct TestDeps {
+        value: i32,
+    }
+
+    #[derive(Debug, Clone)]
+    enum TestEvent {
+        Start,
+        Step { n: i32 },
+        Done,
+    }
+
+    #[derive(Debug, Clone)]
+    enum TestCommand {
+        Process { n: i32 },
+        Finish,
+    }
+    impl Command for TestCommand {}
+
+    // Test machine
+    struct TestMachine {
+        step_count: i32,
+    }
+
+    impl Machine for TestMachine {
+        type Event = TestEvent;
+        type Command = TestCommand;
+
+        fn decide(&mut self, event: &TestEvent) -> Option<TestCommand> {
+            match event {
+                TestEvent::Start => {
+                    self.step_count = 1;
+                    Some(TestCommand::Process { n: 1 })
+                }
+                TestEvent::Step { n } => {
+                    self.step_count = *n + 1;
+                    if *n < 3 {
+                        Some(TestCommand::Process { n: *n + 1 })
+                    } else {
+                        Some(TestCommand::Finish)
+                    }
+                }
+                TestEvent::Done => None,
+            }
+        }
+    }
+
+    // Test effect
+    struct TestEffect {
+        process_count: Arc<AtomicUsize>,
+        finish_count: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Effect<TestCommand, TestDeps> for TestEffect {
+        type Event = TestEvent;
+
+        async fn execute(
+            &self,
+            cmd: TestCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            match cmd {
+                TestCommand::Process { n } => {
+                    self.process_count.fetch_add(1, Ordering::Relaxed);
+                    Ok(TestEvent::Step { n })
+                }
+                TestCommand::Finish => {
+                    self.finish_count.fetch_add(1, Ordering::Relaxed);
+                    Ok(TestEvent::Done)
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_builder_creates_engine() {
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let finish_count = Arc::new(AtomicUsize::new(0));
+
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: finish_count.clone(),
+            })
+            .build();
+
+        // Start engine
+        let handle = engine.start();
+
+        // Give runtime time to subscribe
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Emit start event
+        handle.emit(TestEvent::Start);
+
+        // Wait for processing
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Check results - should have processed 3 steps and finished
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
+        assert_eq!(finish_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_engine_builder_with_arc() {
+        let deps = Arc::new(TestDeps { value: 42 });
+        let _engine = EngineBuilder::with_arc(deps).build();
+    }
+
+    #[test]
+    fn test_validate_reports_wiring_before_build() {
+        let effect = || TestEffect {
+            process_count: Arc::new(AtomicUsize::new(0)),
+            finish_count: Arc::new(AtomicUsize::new(0)),
+        };
+
+        let wired = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(effect());
+        assert!(wired.validate().unwrap().issues().is_empty());
+
+        let unwired = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_machine(StallMachine);
+        match unwired.validate() {
+            Err(SeesawError::InvalidWiring { report }) => {
+                assert_eq!(report.issues().len(), 2);
+                assert!(report.to_string().contains("StallMachine decides"));
+            }
+            other => panic!("expected InvalidWiring, got {other:?}"),
+        }
+
+        // Would panic in build
+        let duplicated = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(effect())
+            .with_effect::<TestCommand, _>(effect());
+        assert!(matches!(
+            duplicated.validate(),
+            Err(SeesawError::InvalidWiring { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wire_registers_checked_machines_and_effects() {
+        let builder = crate::wire!(EngineBuilder::new(TestDeps { value: 0 }) => {
+            machines: [TestMachine { step_count: 0 }],
+            effects: [
+                TestCommand => TestEffect {
+                    process_count: Arc::new(AtomicUsize::new(0)),
+                    finish_count: Arc::new(AtomicUsize::new(0)),
+                },
+            ],
+        });
+
+        assert!(builder.validate().unwrap().issues().is_empty());
+    }
+
+    #[test]
+    fn test_engine_builder_with_bus() {
+        let bus = EventBus::new();
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_bus(bus.clone())
+            .build();
+
+        // The engine's bus should be the same instance: events emitted on
+        // one reach subscribers of the other
+        let mut receiver = engine.bus().subscribe();
+        bus.emit(TestEvent::Start);
+        let envelope = receiver.try_recv().unwrap();
+        assert!(matches!(
+            envelope.downcast_ref::<TestEvent>(),
+            Some(TestEvent::Start)
+        ));
+    }
+
+    // ==========================================================================
+    // Effect Error Handling Tests
+    // ==========================================================================
+
+    // Types for error handling tests
+    #[derive(Debug, Clone)]
+    struct ErrorTriggerEvent;
+
+    #[derive(Debug, Clone)]
+    struct ErrorResultEvent;
+
+    #[derive(Debug, Clone)]
+    struct ErrorCommand;
+    impl Command for ErrorCommand {}
+
+    // Machine that always emits ErrorCommand when it sees ErrorTriggerEvent
+    struct ErrorTriggerMachine;
+    impl Machine for ErrorTriggerMachine {
+        type Event = ErrorTriggerEvent;
+        type Command = ErrorCommand;
+
+        fn decide(&mut self, _event: &ErrorTriggerEvent) -> Option<ErrorCommand> {
+            Some(ErrorCommand)
+        }
+    }
+
+    // Effect that always returns an error
+    struct AlwaysFailsEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<ErrorCommand, TestDeps> for AlwaysFailsEffect {
+        type Event = ErrorResultEvent;
+
+        async fn execute(
+            &self,
+            _cmd: ErrorCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<ErrorResultEvent> {
+            Err(anyhow::anyhow!("authorization denied"))
+        }
+    }
+
+    // Effect that panics on its first call and succeeds afterwards
+    struct PanicsOnceEffect {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Effect<ErrorCommand, TestDeps> for PanicsOnceEffect {
+        type Event = ErrorResultEvent;
+
+        async fn execute(
+            &self,
+            _cmd: ErrorCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<ErrorResultEvent> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("ledger unavailable");
+            }
+            Ok(ErrorResultEvent)
+        }
+    }
+
+    /// Test that emit_and_await returns quickly when an effect returns an error.
+    ///
+    /// This test reproduces a bug where effect errors cause emit_and_await to hang
+    /// until timeout because:
+    /// 1. The inflight count is incremented for the command batch
+    /// 2. When the effect fails, BatchOutcome::Partial { succeeded: 0 } is used
+    /// 3. succeeded_count(1) returns 0, so dec(cid, 0) is called
+    /// 4. The inflight count never reaches zero
+    ///
+    /// Expected behavior: emit_and_await should return an error quickly, not timeout.
+    #[tokio::test]
+    async fn test_emit_and_await_returns_error_on_effect_failure() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
+
+        let handle = engine.start();
+
+        // Give runtime time to subscribe
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // This should return an error quickly, NOT timeout after 500ms
+        let start = std::time::Instant::now();
+        let result = handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
+            .await;
+
+        let elapsed = start.elapsed();
+
+        // The operation should complete quickly (< 100ms), not timeout at 500ms
+        assert!(
+            elapsed < Duration::from_millis(100),
+            "emit_and_await took {:?}, expected < 100ms. \
+             This indicates the inflight count is not being decremented on error.",
+            elapsed
+        );
+
+        // And it should return an error (the authorization denied error)
+        assert!(
+            result.is_err(),
+            "Expected an error to be returned, got Ok(())"
+        );
+
+        handle.abort();
+    }
+
+    // ==========================================================================
+    // Batch Error Handling Tests
+    // ==========================================================================
+
+    // Types for batch error tests
+    #[derive(Debug, Clone)]
+    struct BatchTriggerEvent {
+        count: usize,
+    }
+
+    #[derive(Debug, Clone)]
+    struct BatchResultEvent {
+        index: usize,
+    }
+
+    #[derive(Debug, Clone)]
+    struct BatchCommand {
+        index: usize,
+        should_fail: bool,
+    }
+    impl Command for BatchCommand {}
+
+    // Machine that emits multiple commands for a single event
+    struct BatchMachine {
+        fail_at: Option<usize>,
+    }
+
+    impl Machine for BatchMachine {
+        type Event = BatchTriggerEvent;
+        type Command = BatchCommand;
+
+        fn decide(&mut self, event: &BatchTriggerEvent) -> Option<BatchCommand> {
+            // This machine only emits one command per decide() call
+            // To test batch behavior, we need multiple machines or a different approach
+            // For now, emit one command that fails if `fail_at` is within the batch
+            let should_fail = self.fail_at.is_some_and(|index| index < event.count);
+            Some(BatchCommand {
+                index: 0,
+                should_fail,
+            })
+        }
+    }
+
+    // Effect that fails based on command's should_fail flag
+    struct ConditionalFailEffect {
+        executed: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Effect<BatchCommand, TestDeps> for ConditionalFailEffect {
+        type Event = BatchResultEvent;
+
+        async fn execute(
+            &self,
+            cmd: BatchCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<BatchResultEvent> {
+            self.executed.fetch_add(1, Ordering::SeqCst);
+            if cmd.should_fail {
+                Err(anyhow::anyhow!(
+                    "command {} failed intentionally",
+                    cmd.index
+                ))
+            } else {
+                Ok(BatchResultEvent { index: cmd.index })
+            }
+        }
+    }
+
+    /// Test that emit_and_await works correctly when effect succeeds.
+    /// This is the baseline test to ensure success path works.
+    #[tokio::test]
+    async fn test_emit_and_await_success_baseline() {
+        let executed = Arc::new(AtomicUsize::new(0));
+
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(BatchMachine { fail_at: None })
+            .with_effect::<BatchCommand, _>(ConditionalFailEffect {
+                executed: executed.clone(),
+            })
+            .build();
+
+        let handle = engine.start();
+        let mut receiver = handle.bus().subscribe();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let start = std::time::Instant::now();
+        let result = handle
+            .emit_and_await_timeout(BatchTriggerEvent { count: 1 }, Duration::from_millis(500))
+            .await;
+        let elapsed = start.elapsed();
+
+        // Success case should complete quickly
+        assert!(
+            elapsed < Duration::from_millis(100),
+            "Success case took {:?}, expected < 100ms",
+            elapsed
+        );
+        assert!(result.is_ok(), "Expected Ok, got {:?}", result);
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+        let indexes: Vec<usize> = std::iter::from_fn(|| receiver.try_recv().ok())
+            .filter_map(|envelope| envelope.downcast_ref::<BatchResultEvent>().map(|e| e.index))
+            .collect();
+        assert_eq!(indexes, vec![0]);
+
+        handle.abort();
+    }
+
+    /// Test that the error message is propagated through emit_and_await.
+    #[tokio::test]
+    async fn test_emit_and_await_error_message_propagation() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
+
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let result = handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
+            .await;
+
+        // When the fix is applied, this should contain the actual error
+        // For now, it times out, so we check for timeout error
+        assert!(result.is_err());
+        let err_msg = result.unwrap_err().to_string();
+        // After fix: should contain "authorization denied" or similar
+        // Currently: contains "timed out"
+        println!("Error message: {}", err_msg);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_effect_panic_fails_await_and_engine_keeps_running() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(PanicsOnceEffect {
+                calls: Arc::new(AtomicUsize::new(0)),
+            })
+            .build();
+
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let err = handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ledger unavailable"));
+
+        handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
+            .await
+            .unwrap();
+
+        handle.abort();
+    }
+
+    /// Test multiple sequential emit_and_await calls with errors.
+    /// Ensures error handling doesn't leave state corrupted.
+    #[tokio::test]
+    async fn test_emit_and_await_multiple_errors_sequential() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
+
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Run multiple emit_and_await calls sequentially
+        for i in 0..3 {
+            let start = std::time::Instant::now();
+            let result = handle
+                .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(200))
+                .await;
+            let elapsed = start.elapsed();
+
+            assert!(
+                elapsed < Duration::from_millis(100),
+                "Iteration {} took {:?}, expected < 100ms. State may be corrupted from previous error.",
+                i,
+                elapsed
+            );
+            assert!(result.is_err(), "Iteration {} expected error", i);
+        }
+
+        handle.abort();
+    }
+
+    /// Test that inflight tracker is properly cleaned up after errors.
+    #[tokio::test]
+    async fn test_inflight_tracker_cleanup_after_error() {
+        let inflight = Arc::new(InflightTracker::new());
+
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_inflight(inflight.clone())
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
+
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Check initial state
+        assert_eq!(
+            inflight.active_count(),
+            0,
+            "Should have no active correlations initially"
+        );
+
+        let _ = handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(200))
+            .await;
+
+        // After completion (success or failure), inflight should be cleaned up
+        // Give a moment for async cleanup
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            inflight.active_count(),
+            0,
+            "Should have no active correlations after error. \
+             Leaked correlations indicate the count never reached zero."
+        );
+
+        handle.abort();
+    }
+
+    // ==========================================================================
+    // No-Command Path Tests (event with no matching machine)
+    // ==========================================================================
+
+    /// Test emit_and_await when no machine handles the event (no command emitted).
+    /// This should complete immediately since there's no work to do.
+    #[tokio::test]
+    async fn test_emit_and_await_no_command_path() {
+        // Use an event type that no machine handles
+        #[derive(Debug, Clone)]
+        struct UnhandledEvent;
+
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine) // Only handles ErrorTriggerEvent
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
 
-        Engine {
-            runtime,
-            bus: self.bus,
-            inflight: self.inflight,
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let start = std::time::Instant::now();
+        let result = handle
+            .emit_and_await_timeout(UnhandledEvent, Duration::from_millis(500))
+            .await;
+        let elapsed = start.elapsed();
+
+        // No machine handles this event, so no command is emitted
+        // Should complete quickly with Ok
+        assert!(
+            elapsed < Duration::from_millis(100),
+            "No-command path took {:?}, expected < 100ms",
+            elapsed
+        );
+        assert!(result.is_ok(), "No-command path should succeed");
+
+        handle.abort();
+    }
+
+    // ==========================================================================
+    // Effect Timeout Tests
+    // ==========================================================================
+
+    // Effect that hangs far longer than any test budget
+    struct HangingBatchEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<BatchCommand, TestDeps> for HangingBatchEffect {
+        type Event = BatchResultEvent;
+
+        async fn execute(
+            &self,
+            cmd: BatchCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<BatchResultEvent> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(BatchResultEvent { index: cmd.index })
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::effect_impl::EffectContext;
-    use anyhow::Result;
-    use std::time::Duration;
+    /// A hung effect must not stall emit_and_await until the outer timeout.
+    #[tokio::test]
+    async fn test_emit_and_await_returns_effect_timeout() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(BatchMachine { fail_at: None })
+            .with_effect::<BatchCommand, _>(HangingBatchEffect)
+            .with_effect_timeout::<BatchCommand>(Duration::from_millis(50))
+            .build();
+
+        let inflight = engine.inflight().clone();
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let start = std::time::Instant::now();
+        let result = handle
+            .emit_and_await_timeout(BatchTriggerEvent { count: 1 }, Duration::from_secs(5))
+            .await;
+
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "effect timeout should fire long before the await timeout"
+        );
+        let err = result.expect_err("timed-out effect should surface as an error");
+        assert!(
+            err.to_string().contains("timed out after 50ms"),
+            "unexpected error: {}",
+            err
+        );
+        assert_eq!(inflight.active_count(), 0);
+
+        handle.abort();
+    }
 
     // ==========================================================================
-    // Inflight Tracker Tests
+    // Effect Retry Tests
     // ==========================================================================
 
-    #[test]
-    fn test_inflight_tracker_basic() {
-        let tracker = InflightTracker::new();
-        let cid = CorrelationId::new();
+    // Effect that fails its first attempt, then succeeds
+    struct FlakyBatchEffect {
+        attempts: Arc<AtomicUsize>,
+    }
 
-        // Create entry and increment
-        tracker.inc(cid, 1);
+    #[async_trait::async_trait]
+    impl Effect<BatchCommand, TestDeps> for FlakyBatchEffect {
+        type Event = BatchResultEvent;
 
-        // Entry should exist with count 1
-        let entry = tracker.entries.get(&cid).unwrap();
-        assert_eq!(entry.count.load(Ordering::Relaxed), 1);
-        drop(entry);
+        async fn execute(
+            &self,
+            cmd: BatchCommand,
+            _ctx: EffectContext<TestDeps>,
+        ) -> Result<BatchResultEvent> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(anyhow::anyhow!("transient failure"));
+            }
+            Ok(BatchResultEvent { index: cmd.index })
+        }
+    }
 
-        // Decrement to zero
-        tracker.dec(cid, 1);
+    #[tokio::test]
+    async fn test_emit_and_await_retries_transient_effect_failure() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(BatchMachine { fail_at: None })
+            .with_effect_retry::<BatchCommand, _>(
+                FlakyBatchEffect {
+                    attempts: attempts.clone(),
+                },
+                RetryPolicy::new().with_initial_backoff(Duration::from_millis(1)),
+            )
+            .build();
 
-        // Entry should be removed
-        assert!(tracker.entries.get(&cid).is_none());
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        handle
+            .emit_and_await(BatchTriggerEvent { count: 1 })
+            .await
+            .expect("retry should hide the transient failure");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        handle.abort();
     }
 
-    #[test]
-    fn test_inflight_tracker_multiple_inc_dec() {
-        let tracker = InflightTracker::new();
-        let cid = CorrelationId::new();
+    #[tokio::test]
+    async fn test_emit_and_await_surfaces_bus_rejection() {
+        use crate::bus::BackpressurePolicy;
 
-        tracker.inc(cid, 1);
-        tracker.inc(cid, 2);
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Error);
+        // A subscriber that never reads keeps the buffer full
+        let _stalled = bus.subscribe();
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_bus(bus.clone())
+            .build();
 
-        let entry = tracker.entries.get(&cid).unwrap();
-        assert_eq!(entry.count.load(Ordering::Relaxed), 3);
-        drop(entry);
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(TestEvent::Start);
+        bus.emit(TestEvent::Start);
 
-        tracker.dec(cid, 2);
+        let err = handle
+            .emit_and_await_timeout(TestEvent::Start, Duration::from_secs(1))
+            .await
+            .unwrap_err();
 
-        let entry = tracker.entries.get(&cid).unwrap();
-        assert_eq!(entry.count.load(Ordering::Relaxed), 1);
-        drop(entry);
+        assert!(matches!(
+            err.downcast_ref::<SeesawError>(),
+            Some(SeesawError::BusFull { capacity: 2 })
+        ));
+        assert!(handle.inflight().entries.is_empty());
+        assert!(bus.stats().rejected >= 1);
 
-        tracker.dec(cid, 1);
-        assert!(tracker.entries.get(&cid).is_none());
+        handle.abort();
     }
 
+    // ==========================================================================
+    // Health Tests
+    // ==========================================================================
+
     #[tokio::test]
-    async fn test_wait_zero_immediate() {
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    async fn test_health_reports_engine_activity() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
 
-        // Entry doesn't exist - should return immediately
-        let result = tracker.wait_zero(cid).await;
-        assert!(result.is_ok());
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(handle.is_ready());
+        assert!(handle.health().last_event_at.is_none());
+
+        handle.emit(TestEvent::Start);
+        let _ = handle
+            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
+            .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let health = handle.health();
+        assert!(health.ready);
+        assert_eq!(health.machines, 2);
+        assert_eq!(health.inflight_batches, 0);
+        assert!(health.last_event_at.is_some());
+
+        let [errors, steps] = &health.effects[..] else {
+            panic!("expected two effects, got {:?}", health.effects);
+        };
+        assert!(errors.command_type.ends_with("ErrorCommand"));
+        assert_eq!((errors.executions, errors.error_rate()), (1, 1.0));
+        assert!(steps.command_type.ends_with("TestCommand"));
+        assert_eq!((steps.executions, steps.error_rate()), (4, 0.0));
+
+        handle.abort();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!handle.is_ready());
     }
 
     #[tokio::test]
-    async fn test_wait_zero_with_work() {
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    async fn test_stalled_tap_does_not_block_dispatch() {
+        use crate::tap::TapContext;
 
-        tracker.inc(cid, 1);
+        struct StalledTap;
 
-        let tracker_clone = tracker.clone();
-        let handle = tokio::spawn(async move {
-            // Simulate work
-            tokio::time::sleep(Duration::from_millis(10)).await;
-            tracker_clone.dec(cid, 1);
-        });
+        #[async_trait::async_trait]
+        impl EventTap<TestEvent> for StalledTap {
+            async fn on_event(&self, _event: &TestEvent, _ctx: &TapContext) -> Result<()> {
+                std::future::pending().await
+            }
+        }
 
-        let result = tracker.wait_zero(cid).await;
-        assert!(result.is_ok());
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .with_event_tap_policy::<TestEvent, _>(StalledTap, TapPolicy::new(1))
+            .build();
 
-        handle.await.unwrap();
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        for _ in 0..3 {
+            handle
+                .emit_and_await_timeout(TestEvent::Start, Duration::from_millis(500))
+                .await
+                .unwrap();
+        }
+
+        let [tap] = &handle.health().taps[..] else {
+            panic!("expected one tap");
+        };
+        assert!(tap.name.ends_with("StalledTap"));
+        assert_eq!(tap.delivered, 0);
+        assert!(tap.dropped > 0);
+
+        handle.abort();
     }
 
+    // ==========================================================================
+    // Request/Response Tests
+    // ==========================================================================
+
     #[tokio::test]
-    async fn test_wait_zero_with_error() {
-        // This tests the real-world pattern: wait_zero is called BEFORE dec
-        // (which is how emit_and_await works - it starts waiting before work completes)
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    async fn test_request_returns_matching_response() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .build();
 
-        tracker.inc(cid, 1);
-        tracker.record_error(cid, anyhow::anyhow!("test error"));
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
 
-        // Start waiting BEFORE dec (this is what emit_and_await does)
-        let tracker_clone = tracker.clone();
-        let wait_handle = tokio::spawn(async move { tracker_clone.wait_zero(cid).await });
+        let response = handle
+            .request::<TestEvent, TestEvent>(
+                TestEvent::Start,
+                |e| matches!(e, TestEvent::Step { n: 2 }),
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
 
-        // Give the waiter time to register
-        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(matches!(response, TestEvent::Step { n: 2 }));
 
-        // Now dec - waiter exists, so error entry is preserved
-        tracker.dec(cid, 1);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_request_surfaces_command_failure() {
+        let engine = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(ErrorTriggerMachine)
+            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+            .build();
+
+        let handle = engine.start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let start = std::time::Instant::now();
+        let result = handle
+            .request::<ErrorTriggerEvent, ErrorResultEvent>(
+                ErrorTriggerEvent,
+                |_| true,
+                Duration::from_millis(500),
+            )
+            .await;
 
-        let result = wait_handle.await.unwrap();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("test error"));
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        handle.abort();
     }
 
-    #[tokio::test]
-    async fn test_wait_zero_without_waiter_cleans_error() {
-        // If no one is waiting when dec() hits zero, error is discarded
-        // to prevent memory leaks in fire-and-forget patterns
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    // ==========================================================================
+    // Delayed Emission Tests
+    // ==========================================================================
 
-        tracker.inc(cid, 1);
-        tracker.record_error(cid, anyhow::anyhow!("test error"));
-        tracker.dec(cid, 1);
-        // No waiter existed, so entry is cleaned up (error discarded)
+    #[tokio::test]
+    async fn test_emit_after_delays_processing() {
+        let finish_count = Arc::new(AtomicUsize::new(0));
+        let handle = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: finish_count.clone(),
+            })
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
 
-        // wait_zero called AFTER dec: entry gone, returns Ok
-        let result = tracker.wait_zero(cid).await;
-        assert!(result.is_ok(), "Expected Ok when entry already cleaned up");
-    }
+        handle.emit_after(TestEvent::Step { n: 3 }, Duration::from_millis(50));
+        // Already passed - emitted right away
+        handle.emit_at(
+            TestEvent::Step { n: 3 },
+            Utc::now() - chrono::Duration::seconds(1),
+        );
 
-    #[test]
-    fn test_inflight_guard_for_commands() {
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+        tokio::time::sleep(Duration::from_millis(25)).await;
+        assert_eq!(finish_count.load(Ordering::Relaxed), 1);
 
-        {
-            let _guard = InflightGuard::for_commands(tracker.clone(), cid, 3);
-            let entry = tracker.entries.get(&cid).unwrap();
-            assert_eq!(entry.count.load(Ordering::Relaxed), 3);
-        }
+        tokio::time::sleep(Duration::from_millis(75)).await;
+        assert_eq!(finish_count.load(Ordering::Relaxed), 2);
 
-        // Guard dropped, entry should be removed
-        assert!(tracker.entries.get(&cid).is_none());
+        handle.abort();
     }
 
-    #[test]
-    fn test_inflight_guard_for_event() {
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    // ==========================================================================
+    // Reaper Tests
+    // ==========================================================================
 
-        // Simulate emit doing the increment
-        tracker.inc(cid, 1);
+    #[tokio::test]
+    async fn test_reaper_runs_until_abort_despite_failures() {
+        let finish_count = Arc::new(AtomicUsize::new(0));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let reaper_runs = runs.clone();
+        let handle = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: finish_count.clone(),
+            })
+            .with_reaper(
+                Duration::from_millis(30),
+                move |ctx: ReaperContext<TestDeps>| {
+                    let run = reaper_runs.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        anyhow::ensure!(run > 0, "first scan fails");
+                        assert_eq!(ctx.deps().value, 42);
+                        ctx.emit(TestEvent::Step { n: 3 });
+                        Ok(())
+                    }
+                },
+            )
+            .build()
+            .start();
 
-        {
-            let _guard = InflightGuard::for_event(tracker.clone(), cid);
-            let entry = tracker.entries.get(&cid).unwrap();
-            assert_eq!(entry.count.load(Ordering::Relaxed), 1);
-        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+        let stopped_at = runs.load(Ordering::SeqCst);
+        assert!(stopped_at >= 3);
+        assert!(
+            finish_count.load(Ordering::SeqCst) >= 1,
+            "a failed run must not stop the reaper"
+        );
 
-        // Guard dropped, entry should be removed
-        assert!(tracker.entries.get(&cid).is_none());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at);
     }
 
-    #[test]
-    fn test_inflight_guard_panic_safety() {
-        let tracker = Arc::new(InflightTracker::new());
-        let cid = CorrelationId::new();
+    // ==========================================================================
+    // Trace Tests
+    // ==========================================================================
 
-        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            let _guard = InflightGuard::for_commands(tracker.clone(), cid, 1);
-            panic!("simulated panic");
-        }));
+    #[tokio::test]
+    async fn test_export_trace_follows_the_cascade() {
+        use crate::trace::TraceNodeKind;
 
-        assert!(result.is_err());
+        let recorder = TraceRecorder::new();
+        let handle = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .with_trace_recorder(recorder.clone())
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
 
-        // Entry should still be removed despite panic
-        assert!(tracker.entries.get(&cid).is_none());
+        handle
+            .emit_and_await(TestEvent::Step { n: 2 })
+            .await
+            .unwrap();
+        let cid = recorder.correlations()[0];
+        let trace = handle.export_trace(cid).unwrap();
+
+        // Step 2 -> Process 3 -> Step 3 -> Finish -> Done
+        let kinds: Vec<_> = trace
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                TraceNodeKind::Event { .. } => "event",
+                TraceNodeKind::Decision { .. } => "decision",
+                TraceNodeKind::Effect { .. } => "effect",
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            ["event", "decision", "effect", "event", "decision", "effect", "event"]
+        );
+        for pair in trace.nodes.windows(2) {
+            assert_eq!(pair[1].parent, Some(pair[0].id));
+        }
+        assert!(trace.to_dot().starts_with("digraph trace {"));
+        assert!(handle.export_trace(CorrelationId::new()).is_none());
+
+        handle.abort();
     }
 
-    #[test]
-    fn test_first_error_wins() {
-        let tracker = InflightTracker::new();
-        let cid = CorrelationId::new();
+    #[tokio::test]
+    async fn test_costs_are_totalled_per_command_type_and_correlation() {
+        let ledger = CostLedger::new();
+        let handle = EngineBuilder::new(TestDeps { value: 42 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect_fn::<TestCommand, _>(|cmd, ctx| async move {
+                match cmd {
+                    TestCommand::Process { n } => {
+                        ctx.record_cost("tokens", f64::from(n * 10));
+                        Ok(TestEvent::Step { n })
+                    }
+                    TestCommand::Finish => {
+                        ctx.record_cost("calls", 1.0);
+                        Ok(TestEvent::Done)
+                    }
+                }
+            })
+            .with_cost_ledger(ledger.clone())
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
 
-        tracker.inc(cid, 1);
-        tracker.record_error(cid, anyhow::anyhow!("first error"));
-        tracker.record_error(cid, anyhow::anyhow!("second error"));
+        // Step 1 -> Process 2 -> Step 2 -> Process 3 -> Step 3 -> Finish
+        handle
+            .emit_and_await(TestEvent::Step { n: 1 })
+            .await
+            .unwrap();
 
-        let entry = tracker.entries.get(&cid).unwrap();
-        let err = entry.first_error.lock().expect("mutex not poisoned");
-        assert!(err.as_ref().unwrap().to_string().contains("first error"));
+        let costs = handle.costs().unwrap();
+        assert_eq!(costs.total("TestCommand", "tokens"), 50.0);
+        assert_eq!(costs.total("TestCommand", "calls"), 1.0);
+        let cid = ledger.correlations()[0];
+        assert_eq!(costs.correlation_total(cid, "tokens"), 50.0);
+        assert_eq!(costs.correlation_totals(cid).unwrap().len(), 2);
+
+        handle.abort();
     }
 
     // ==========================================================================
-    // Engine Tests
+    // Shared Payload Tests
     // ==========================================================================
 
-    // Test types
-    #[derive(Debug, Clone)]
-    struct TestDeps {
-        value: i32,
-    }
-
-    #[derive(Debug, Clone)]
-    enum TestEvent {
-        Start,
-        Step { n: i32 },
-        Done,
-    }
+    static DOCUMENT_CLONES: AtomicUsize = AtomicUsize::new(0);
 
-    #[derive(Debug, Clone)]
-    enum TestCommand {
-        Process { n: i32 },
-        Finish,
+    /// A large event that counts its clones.
+    #[derive(Debug)]
+    struct Document {
+        body: String,
     }
-    impl Command for TestCommand {}
 
-    // Test machine
-    struct TestMachine {
-        step_count: i32,
+    impl Clone for Document {
+        fn clone(&self) -> Self {
+            DOCUMENT_CLONES.fetch_add(1, Ordering::Relaxed);
+            Self {
+                body: self.body.clone(),
+            }
+        }
     }
 
-    impl Machine for TestMachine {
-        type Event = TestEvent;
+    struct ReadingMachine(Arc<AtomicUsize>);
+    impl Machine for ReadingMachine {
+        type Event = Document;
         type Command = TestCommand;
 
-        fn decide(&mut self, event: &TestEvent) -> Option<TestCommand> {
-            match event {
-                TestEvent::Start => {
-                    self.step_count = 1;
-                    Some(TestCommand::Process { n: 1 })
-                }
-                TestEvent::Step { n } => {
-                    self.step_count = *n + 1;
-                    if *n < 3 {
-                        Some(TestCommand::Process { n: *n + 1 })
-                    } else {
-                        Some(TestCommand::Finish)
-                    }
-                }
-                TestEvent::Done => None,
-            }
+        fn decide(&mut self, event: &Document) -> Option<TestCommand> {
+            self.0.fetch_add(event.body.len(), Ordering::Relaxed);
+            None
         }
     }
 
-    // Test effect
-    struct TestEffect {
-        process_count: Arc<AtomicUsize>,
-        finish_count: Arc<AtomicUsize>,
-    }
+    struct KeepingTap(Arc<std::sync::Mutex<Vec<Arc<Document>>>>);
 
     #[async_trait::async_trait]
-    impl Effect<TestCommand, TestDeps> for TestEffect {
-        type Event = TestEvent;
+    impl crate::tap::EventTap<Document> for KeepingTap {
+        async fn on_event(&self, _event: &Document, _ctx: &crate::tap::TapContext) -> Result<()> {
+            unreachable!("on_shared is overridden")
+        }
 
-        async fn execute(
+        async fn on_shared(
             &self,
-            cmd: TestCommand,
-            _ctx: EffectContext<TestDeps>,
-        ) -> Result<TestEvent> {
-            match cmd {
-                TestCommand::Process { n } => {
-                    self.process_count.fetch_add(1, Ordering::Relaxed);
-                    Ok(TestEvent::Step { n })
-                }
-                TestCommand::Finish => {
-                    self.finish_count.fetch_add(1, Ordering::Relaxed);
-                    Ok(TestEvent::Done)
-                }
-            }
+            event: Arc<Document>,
+            _ctx: &crate::tap::TapContext,
+        ) -> Result<()> {
+            self.0.lock().unwrap().push(event);
+            Ok(())
         }
     }
 
     #[tokio::test]
-    async fn test_engine_builder_creates_engine() {
-        let process_count = Arc::new(AtomicUsize::new(0));
-        let finish_count = Arc::new(AtomicUsize::new(0));
-
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(TestMachine { step_count: 0 })
-            .with_effect::<TestCommand, _>(TestEffect {
-                process_count: process_count.clone(),
-                finish_count: finish_count.clone(),
-            })
-            .build();
-
-        // Start engine
-        let handle = engine.start();
-
-        // Give runtime time to subscribe
+    async fn test_fan_out_shares_one_copy_of_the_event() {
+        let read = Arc::new(AtomicUsize::new(0));
+        let kept = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let handle = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(ReadingMachine(read.clone()))
+            .with_machine(ReadingMachine(read.clone()))
+            .with_machine(ReadingMachine(read.clone()))
+            .with_event_tap::<Document, _>(KeepingTap(kept.clone()))
+            .with_event_tap::<Document, _>(KeepingTap(kept.clone()))
+            .build()
+            .start();
         tokio::time::sleep(Duration::from_millis(10)).await;
 
-        // Emit start event
-        handle.emit(TestEvent::Start);
-
-        // Wait for processing
-        tokio::time::sleep(Duration::from_millis(100)).await;
-
-        // Check results - should have processed 3 steps and finished
-        assert_eq!(process_count.load(Ordering::Relaxed), 3);
-        assert_eq!(finish_count.load(Ordering::Relaxed), 1);
-    }
-
-    #[test]
-    fn test_engine_builder_with_arc() {
-        let deps = Arc::new(TestDeps { value: 42 });
-        let _engine = EngineBuilder::with_arc(deps).build();
-    }
+        let document = Arc::new(Document {
+            body: "x".repeat(1 << 20),
+        });
+        handle.bus().emit_arc(document.clone());
+        tokio::time::sleep(Duration::from_millis(50)).await;
 
-    #[test]
-    fn test_engine_builder_with_bus() {
-        let bus = EventBus::new();
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_bus(bus.clone())
-            .build();
+        assert_eq!(read.load(Ordering::Relaxed), 3 << 20);
+        let kept = kept.lock().unwrap();
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|shared| Arc::ptr_eq(shared, &document)));
+        assert_eq!(DOCUMENT_CLONES.load(Ordering::Relaxed), 0);
 
-        // The engine's bus should be the same instance
-        // (can't directly compare, but we can verify it works)
-        bus.emit(TestEvent::Start);
+        handle.abort();
     }
 
     // ==========================================================================
-    // Effect Error Handling Tests
+    // Dead Letter Tests
     // ==========================================================================
 
-    // Types for error handling tests
-    #[derive(Debug, Clone)]
-    struct ErrorTriggerEvent;
-
     #[derive(Debug, Clone)]
-    struct ErrorResultEvent;
+    struct Orphaned;
 
     #[derive(Debug, Clone)]
-    struct ErrorCommand;
-    impl Command for ErrorCommand {}
-
-    // Machine that always emits ErrorCommand when it sees ErrorTriggerEvent
-    struct ErrorTriggerMachine;
-    impl Machine for ErrorTriggerMachine {
-        type Event = ErrorTriggerEvent;
-        type Command = ErrorCommand;
-
-        fn decide(&mut self, _event: &ErrorTriggerEvent) -> Option<ErrorCommand> {
-            Some(ErrorCommand)
-        }
-    }
+    struct Tapped;
 
-    // Effect that always returns an error
-    struct AlwaysFailsEffect;
+    struct IgnoringTap;
 
     #[async_trait::async_trait]
-    impl Effect<ErrorCommand, TestDeps> for AlwaysFailsEffect {
-        type Event = ErrorResultEvent;
-
-        async fn execute(
-            &self,
-            _cmd: ErrorCommand,
-            _ctx: EffectContext<TestDeps>,
-        ) -> Result<ErrorResultEvent> {
-            Err(anyhow::anyhow!("authorization denied"))
+    impl crate::tap::EventTap<Tapped> for IgnoringTap {
+        async fn on_event(&self, _event: &Tapped, _ctx: &crate::tap::TapContext) -> Result<()> {
+            Ok(())
         }
     }
 
-    /// Test that emit_and_await returns quickly when an effect returns an error.
-    ///
-    /// This test reproduces a bug where effect errors cause emit_and_await to hang
-    /// until timeout because:
-    /// 1. The inflight count is incremented for the command batch
-    /// 2. When the effect fails, BatchOutcome::Partial { succeeded: 0 } is used
-    /// 3. succeeded_count(1) returns 0, so dec(cid, 0) is called
-    /// 4. The inflight count never reaches zero
-    ///
-    /// Expected behavior: emit_and_await should return an error quickly, not timeout.
     #[tokio::test]
-    async fn test_emit_and_await_returns_error_on_effect_failure() {
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(ErrorTriggerMachine)
-            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
+    async fn test_dead_letters_report_events_that_reach_nobody() {
+        let (sink, mut dead) = crate::dead_letter::DeadLetterSink::channel();
+        let engine = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_event_tap::<Tapped, _>(IgnoringTap)
+            .with_dead_letters(sink)
             .build();
 
-        let handle = engine.start();
+        // Nothing subscribed yet
+        engine.bus().emit(TestEvent::Done);
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(
+            letter.reason,
+            crate::dead_letter::DeadLetterReason::NoSubscribers
+        );
 
-        // Give runtime time to subscribe
+        let handle = engine.start();
         tokio::time::sleep(Duration::from_millis(10)).await;
-
-        // This should return an error quickly, NOT timeout after 500ms
-        let start = std::time::Instant::now();
-        let result = handle
-            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
-            .await;
-
-        let elapsed = start.elapsed();
-
-        // The operation should complete quickly (< 100ms), not timeout at 500ms
-        assert!(
-            elapsed < Duration::from_millis(100),
-            "emit_and_await took {:?}, expected < 100ms. \
-             This indicates the inflight count is not being decremented on error.",
-            elapsed
+        handle.bus().emit(TestEvent::Done);
+        handle.bus().emit(Tapped);
+        handle.bus().emit(Orphaned);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(
+            letter.reason,
+            crate::dead_letter::DeadLetterReason::Unhandled
         );
+        assert!(letter.envelope.downcast_ref::<Orphaned>().is_some());
+        assert!(dead.try_recv().is_err());
 
-        // And it should return an error (the authorization denied error)
-        assert!(
-            result.is_err(),
-            "Expected an error to be returned, got Ok(())"
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_event_ttl_drops_events_before_machines_see_them() {
+        let (sink, mut dead) = crate::dead_letter::DeadLetterSink::channel();
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let handle = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            // Every event is older than zero by the time it is read
+            .with_event_ttl::<TestEvent>(Duration::ZERO)
+            .with_dead_letters(sink)
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        handle.emit(TestEvent::Start);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(process_count.load(Ordering::Relaxed), 0);
+        assert_eq!(handle.bus().stats().expired, 1);
+        let letter = dead.try_recv().unwrap();
+        assert_eq!(
+            letter.reason,
+            crate::dead_letter::DeadLetterReason::Expired
         );
+        assert!(letter.envelope.downcast_ref::<TestEvent>().is_some());
 
         handle.abort();
     }
 
     // ==========================================================================
-    // Batch Error Handling Tests
+    // Batch Timeout Tests
     // ==========================================================================
 
-    // Types for batch error tests
     #[derive(Debug, Clone)]
-    struct BatchTriggerEvent {
-        count: usize,
-    }
+    struct StallRequested;
 
     #[derive(Debug, Clone)]
-    struct BatchResultEvent {
-        index: usize,
-    }
+    struct Stall;
+    impl Command for Stall {}
 
-    #[derive(Debug, Clone)]
-    struct BatchCommand {
-        index: usize,
-        should_fail: bool,
-    }
-    impl Command for BatchCommand {}
+    struct StallMachine;
+    impl Machine for StallMachine {
+        type Event = StallRequested;
+        type Command = Stall;
 
-    // Machine that emits multiple commands for a single event
-    struct BatchMachine {
-        fail_at: Option<usize>,
+        fn decide(&mut self, _event: &StallRequested) -> Option<Stall> {
+            Some(Stall)
+        }
     }
 
-    impl Machine for BatchMachine {
-        type Event = BatchTriggerEvent;
-        type Command = BatchCommand;
+    struct StallEffect;
 
-        fn decide(&mut self, event: &BatchTriggerEvent) -> Option<BatchCommand> {
-            // This machine only emits one command per decide() call
-            // To test batch behavior, we need multiple machines or a different approach
-            // For now, emit one command that may or may not fail
-            let should_fail = self.fail_at == Some(0);
-            Some(BatchCommand {
-                index: 0,
-                should_fail,
-            })
+    #[async_trait::async_trait]
+    impl Effect<Stall, TestDeps> for StallEffect {
+        type Event = TestEvent;
+
+        async fn execute(&self, _cmd: Stall, _ctx: EffectContext<TestDeps>) -> Result<TestEvent> {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(TestEvent::Done)
         }
     }
 
-    // Effect that fails based on command's should_fail flag
-    struct ConditionalFailEffect {
-        executed: Arc<AtomicUsize>,
+    #[tokio::test]
+    async fn test_batch_timeout_fails_await_with_the_stuck_batch() {
+        let handle = Arc::new(
+            EngineBuilder::new(TestDeps { value: 0 })
+                .with_machine(StallMachine)
+                .with_effect::<Stall, _>(StallEffect)
+                .with_batch_timeout(Duration::from_millis(40))
+                .build()
+                .start(),
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let awaiting = handle.clone();
+        let started = Instant::now();
+        let result = tokio::spawn(async move {
+            awaiting
+                .emit_and_await_timeout(StallRequested, Duration::from_secs(5))
+                .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let running = handle.inflight_batches();
+        assert_eq!(running.len(), 1);
+        assert!(running[0].command_type.ends_with("Stall"));
+        assert!(running[0].event_type.unwrap().ends_with("StallRequested"));
+
+        let err = result.await.unwrap().unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(200));
+        match err.downcast_ref::<SeesawError>() {
+            Some(SeesawError::BatchTimeout { batch, limit }) => {
+                assert_eq!(*limit, Duration::from_millis(40));
+                assert_eq!(batch.size, 1);
+                assert!(batch.age >= *limit);
+            }
+            other => panic!("expected BatchTimeout, got {other:?}"),
+        }
+
+        handle.abort();
     }
 
+    struct OtherDeps;
+
     #[async_trait::async_trait]
-    impl Effect<BatchCommand, TestDeps> for ConditionalFailEffect {
-        type Event = BatchResultEvent;
+    impl Effect<Stall, OtherDeps> for StallEffect {
+        type Event = StallRequested;
 
         async fn execute(
             &self,
-            cmd: BatchCommand,
-            _ctx: EffectContext<TestDeps>,
-        ) -> Result<BatchResultEvent> {
-            self.executed.fetch_add(1, Ordering::SeqCst);
-            if cmd.should_fail {
-                Err(anyhow::anyhow!(
-                    "command {} failed intentionally",
-                    cmd.index
-                ))
-            } else {
-                Ok(BatchResultEvent { index: cmd.index })
-            }
+            _cmd: Stall,
+            _ctx: EffectContext<OtherDeps>,
+        ) -> Result<StallRequested> {
+            Ok(StallRequested)
         }
     }
 
-    /// Test that emit_and_await works correctly when effect succeeds.
-    /// This is the baseline test to ensure success path works.
     #[tokio::test]
-    async fn test_emit_and_await_success_baseline() {
-        let executed = Arc::new(AtomicUsize::new(0));
+    async fn test_machines_and_effects_added_to_a_running_engine() {
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let finish_count = Arc::new(AtomicUsize::new(0));
+        let handle = EngineBuilder::new(TestDeps { value: 0 }).build().start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
 
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(BatchMachine { fail_at: None })
-            .with_effect::<BatchCommand, _>(ConditionalFailEffect {
-                executed: executed.clone(),
+        handle
+            .add_effect::<TestCommand, _, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: finish_count.clone(),
             })
-            .build();
+            .await
+            .unwrap();
+        handle
+            .add_machine(TestMachine { step_count: 0 })
+            .await
+            .unwrap();
+        assert_eq!(handle.health().machines, 1);
 
-        let handle = engine.start();
-        tokio::time::sleep(Duration::from_millis(10)).await;
+        handle.emit_and_await(TestEvent::Start).await.unwrap();
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
+        assert_eq!(finish_count.load(Ordering::Relaxed), 1);
 
-        let start = std::time::Instant::now();
-        let result = handle
-            .emit_and_await_timeout(BatchTriggerEvent { count: 1 }, Duration::from_millis(500))
-            .await;
-        let elapsed = start.elapsed();
+        let err = handle
+            .add_effect::<TestCommand, _, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: finish_count.clone(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SeesawError>(),
+            Some(SeesawError::EffectAlreadyRegistered { .. })
+        ));
+        let err = handle
+            .add_effect::<Stall, _, OtherDeps>(StallEffect)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SeesawError>(),
+            Some(SeesawError::DepsMismatch { .. })
+        ));
+
+        // Removed machines stop deciding
+        assert_eq!(handle.remove_machine::<TestMachine>().await.unwrap(), 1);
+        handle.emit(TestEvent::Start);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
 
-        // Success case should complete quickly
-        assert!(
-            elapsed < Duration::from_millis(100),
-            "Success case took {:?}, expected < 100ms",
-            elapsed
-        );
-        assert!(result.is_ok(), "Expected Ok, got {:?}", result);
-        assert_eq!(executed.load(Ordering::SeqCst), 1);
+        assert!(handle.remove_effect::<TestCommand>().await.unwrap());
+        assert!(!handle.remove_effect::<TestCommand>().await.unwrap());
 
         handle.abort();
     }
 
-    /// Test that the error message is propagated through emit_and_await.
-    #[tokio::test]
-    async fn test_emit_and_await_error_message_propagation() {
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(ErrorTriggerMachine)
-            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
-            .build();
-
-        let handle = engine.start();
-        tokio::time::sleep(Duration::from_millis(10)).await;
+    #[derive(Debug, Clone, PartialEq)]
+    struct Tenant(u32);
 
-        let result = handle
-            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(500))
-            .await;
+    /// Records the tenant each command and event was handled for.
+    #[derive(Clone, Default)]
+    struct TenantProbe(Arc<std::sync::Mutex<Vec<Option<u32>>>>);
 
-        // When the fix is applied, this should contain the actual error
-        // For now, it times out, so we check for timeout error
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
-        // After fix: should contain "authorization denied" or similar
-        // Currently: contains "timed out"
-        println!("Error message: {}", err_msg);
+    impl TenantProbe {
+        fn record(&self, metadata: &Metadata) {
+            let tenant = metadata.get::<Tenant>().map(|tenant| tenant.0);
+            self.0.lock().unwrap().push(tenant);
+        }
 
-        handle.abort();
+        fn seen(&self) -> Vec<Option<u32>> {
+            self.0.lock().unwrap().clone()
+        }
     }
 
-    /// Test multiple sequential emit_and_await calls with errors.
-    /// Ensures error handling doesn't leave state corrupted.
-    #[tokio::test]
-    async fn test_emit_and_await_multiple_errors_sequential() {
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(ErrorTriggerMachine)
-            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
-            .build();
-
-        let handle = engine.start();
-        tokio::time::sleep(Duration::from_millis(10)).await;
-
-        // Run multiple emit_and_await calls sequentially
-        for i in 0..3 {
-            let start = std::time::Instant::now();
-            let result = handle
-                .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(200))
-                .await;
-            let elapsed = start.elapsed();
+    #[async_trait::async_trait]
+    impl Effect<TestCommand, TestDeps> for TenantProbe {
+        type Event = TestEvent;
 
-            assert!(
-                elapsed < Duration::from_millis(100),
-                "Iteration {} took {:?}, expected < 100ms. State may be corrupted from previous error.",
-                i,
-                elapsed
-            );
-            assert!(result.is_err(), "Iteration {} expected error", i);
+        async fn execute(
+            &self,
+            cmd: TestCommand,
+            ctx: EffectContext<TestDeps>,
+        ) -> Result<TestEvent> {
+            self.record(ctx.metadata());
+            Ok(match cmd {
+                TestCommand::Process { n } => TestEvent::Step { n },
+                TestCommand::Finish => TestEvent::Done,
+            })
         }
+    }
 
-        handle.abort();
+    #[async_trait::async_trait]
+    impl crate::tap::EventTap<TestEvent> for TenantProbe {
+        async fn on_event(&self, _event: &TestEvent, ctx: &crate::tap::TapContext) -> Result<()> {
+            self.record(&ctx.metadata);
+            Ok(())
+        }
     }
 
-    /// Test that inflight tracker is properly cleaned up after errors.
     #[tokio::test]
-    async fn test_inflight_tracker_cleanup_after_error() {
-        let inflight = Arc::new(InflightTracker::new());
-
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_inflight(inflight.clone())
-            .with_machine(ErrorTriggerMachine)
-            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
-            .build();
-
-        let handle = engine.start();
+    async fn test_metadata_is_inherited_along_the_causation_chain() {
+        let effects = TenantProbe::default();
+        let taps = TenantProbe::default();
+        let handle = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(effects.clone())
+            .with_event_tap::<TestEvent, _>(taps.clone())
+            .build()
+            .start();
         tokio::time::sleep(Duration::from_millis(10)).await;
 
-        // Check initial state
-        assert_eq!(
-            inflight.active_count(),
-            0,
-            "Should have no active correlations initially"
-        );
-
-        let _ = handle
-            .emit_and_await_timeout(ErrorTriggerEvent, Duration::from_millis(200))
-            .await;
-
-        // After completion (success or failure), inflight should be cleaned up
-        // Give a moment for async cleanup
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle
+            .emit_and_await_with_metadata(TestEvent::Start, Metadata::new().with(Tenant(7)))
+            .await
+            .unwrap();
+        // Start, three steps and Done reach the tap
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(effects.seen(), vec![Some(7); 4]);
+        assert_eq!(taps.seen(), vec![Some(7); 5]);
 
-        assert_eq!(
-            inflight.active_count(),
-            0,
-            "Should have no active correlations after error. \
-             Leaked correlations indicate the count never reached zero."
-        );
+        handle.emit_and_await(TestEvent::Start).await.unwrap();
+        assert_eq!(effects.seen()[4..], [None; 4]);
 
         handle.abort();
     }
 
-    // ==========================================================================
-    // No-Command Path Tests (event with no matching machine)
-    // ==========================================================================
-
-    /// Test emit_and_await when no machine handles the event (no command emitted).
-    /// This should complete immediately since there's no work to do.
     #[tokio::test]
-    async fn test_emit_and_await_no_command_path() {
-        // Use an event type that no machine handles
-        #[derive(Debug, Clone)]
-        struct UnhandledEvent;
-
-        let engine = EngineBuilder::new(TestDeps { value: 42 })
-            .with_machine(ErrorTriggerMachine) // Only handles ErrorTriggerEvent
-            .with_effect::<ErrorCommand, _>(AlwaysFailsEffect)
-            .build();
-
-        let handle = engine.start();
+    async fn test_commands_past_their_deadline_are_skipped() {
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let handle = EngineBuilder::new(TestDeps { value: 0 })
+            .with_machine(TestMachine { step_count: 0 })
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            })
+            .build()
+            .start();
+        let mut events = handle.bus().subscribe();
         tokio::time::sleep(Duration::from_millis(10)).await;
 
-        let start = std::time::Instant::now();
-        let result = handle
-            .emit_and_await_timeout(UnhandledEvent, Duration::from_millis(500))
-            .await;
-        let elapsed = start.elapsed();
+        let expired = Metadata::new().with_deadline(Instant::now());
+        let err = handle
+            .emit_and_await_with_metadata(TestEvent::Start, expired)
+            .await
+            .unwrap_err();
+        match err.downcast_ref::<SeesawError>() {
+            Some(SeesawError::DeadlineExceeded { type_name, .. }) => {
+                assert!(type_name.ends_with("TestCommand"));
+            }
+            other => panic!("expected DeadlineExceeded, got {other:?}"),
+        }
+        assert_eq!(process_count.load(Ordering::Relaxed), 0);
 
-        // No machine handles this event, so no command is emitted
-        // Should complete quickly with Ok
-        assert!(
-            elapsed < Duration::from_millis(100),
-            "No-command path took {:?}, expected < 100ms",
-            elapsed
-        );
-        assert!(result.is_ok(), "No-command path should succeed");
+        let skipped = loop {
+            let envelope = events.recv().await.unwrap();
+            if let Some(skipped) = envelope.downcast_ref::<crate::error::CommandExpired>() {
+                break skipped.clone();
+            }
+        };
+        assert_eq!(skipped.count, 1);
+
+        // With time to spare the whole chain runs
+        handle
+            .emit_and_await_with_metadata(
+                TestEvent::Start,
+                Metadata::new().with_timeout(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
 
         handle.abort();
     }
@@ -31,6 +31,12 @@
 //! }
 //! ```
 //!
+//! Domain errors implementing [`Categorizable`] keep their category through
+//! the dispatcher once registered with
+//! `EngineBuilder::with_error_category::<E>()`, even wrapped in `anyhow`
+//! context, so machines can branch on the failure class and on
+//! [`CommandFailed::retryable`].
+//!
 //! # Error Example
 //!
 //! ```ignore
@@ -77,10 +83,13 @@
 use std::any::TypeId;
 use std::borrow::Cow;
 use std::fmt;
+use std::sync::Arc;
 
 use thiserror::Error;
 
 use crate::core::CorrelationId;
+use crate::engine::InflightBatchInfo;
+use crate::wiring::WiringReport;
 
 // =============================================================================
 // Command Failed Event
@@ -118,6 +127,22 @@ pub enum SafeErrorCategory {
     AIFailure,
 }
 
+impl SafeErrorCategory {
+    /// Whether failures in this category may succeed if simply tried again.
+    ///
+    /// `Validation`, `NotFound` and `Unauthorized` are deterministic: the same
+    /// command will fail the same way on every attempt. Everything else
+    /// (internal, external service, rate limit, AI) is treated as transient.
+    pub fn is_transient(&self) -> bool {
+        !matches!(
+            self,
+            SafeErrorCategory::Validation
+                | SafeErrorCategory::NotFound
+                | SafeErrorCategory::Unauthorized
+        )
+    }
+}
+
 impl fmt::Display for SafeErrorCategory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -186,6 +211,137 @@ pub trait Categorizable: std::error::Error {
     /// Only `Validation` and `NotFound` errors may expose specific details.
     /// All other categories must return generic messages.
     fn safe_message(&self) -> Cow<'static, str>;
+
+    /// Whether the failed command may succeed if tried again.
+    ///
+    /// Defaults to [`SafeErrorCategory::is_transient`] of the category;
+    /// override it for errors that know better, such as an
+    /// `ExternalService` error for a request the service rejected outright.
+    fn is_retryable(&self) -> bool {
+        self.category().is_transient()
+    }
+}
+
+// =============================================================================
+// Error Categories
+// =============================================================================
+
+/// How an error was classified for a [`CommandFailed`] event.
+struct Classification {
+    category: SafeErrorCategory,
+    safe_message: String,
+    retryable: bool,
+}
+
+impl Classification {
+    fn of<E: Categorizable + ?Sized>(error: &E) -> Self {
+        Self {
+            category: error.category(),
+            safe_message: error.safe_message().into_owned(),
+            retryable: error.is_retryable(),
+        }
+    }
+}
+
+type Classifier =
+    Arc<dyn Fn(&(dyn std::error::Error + 'static)) -> Option<Classification> + Send + Sync>;
+
+/// Domain error types the dispatcher recognizes as [`Categorizable`].
+///
+/// Rust cannot downcast an `anyhow::Error` to `dyn Categorizable`, so each
+/// concrete type is registered once, usually through
+/// `EngineBuilder::with_error_category::<E>()`. An effect error is matched
+/// against its whole `source()` chain, so a categorized error wrapped in
+/// `anyhow` context keeps its category. Seesaw's own errors and
+/// `std::io::Error` are always recognized.
+///
+/// # Example
+///
+/// ```ignore
+/// let engine = EngineBuilder::new(deps)
+///     .with_effect::<ChargeCommand, _>(ChargeEffect)
+///     .with_error_category::<PaymentError>()
+///     .build();
+///
+/// // An effect returning `Err(PaymentError::CardDeclined.into())` emits
+/// // CommandFailed { category: Validation, retryable: false, .. }
+/// ```
+#[derive(Clone, Default)]
+pub struct ErrorCategories {
+    classifiers: Vec<Classifier>,
+}
+
+impl ErrorCategories {
+    /// Recognize only the built-in error types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recognize errors of type `E` by their [`Categorizable`] impl.
+    pub fn register<E: Categorizable + Send + Sync + 'static>(&mut self) {
+        self.classifiers
+            .push(Arc::new(|error: &(dyn std::error::Error + 'static)| {
+                error.downcast_ref::<E>().map(Classification::of)
+            }));
+    }
+
+    /// Number of registered domain error types.
+    pub fn len(&self) -> usize {
+        self.classifiers.len()
+    }
+
+    /// Whether no domain error type is registered.
+    pub fn is_empty(&self) -> bool {
+        self.classifiers.is_empty()
+    }
+
+    /// Classify `error` by the first recognized error in its chain, falling
+    /// back to a retryable `InternalError` with a generic message.
+    fn classify(&self, error: &anyhow::Error) -> Classification {
+        error
+            .chain()
+            .find_map(|cause| self.classify_cause(cause))
+            .unwrap_or_else(|| Classification {
+                // NEVER use error.to_string() here - it may contain sensitive data
+                category: SafeErrorCategory::InternalError,
+                safe_message: "An internal error occurred".into(),
+                retryable: SafeErrorCategory::InternalError.is_transient(),
+            })
+    }
+
+    fn classify_cause(&self, cause: &(dyn std::error::Error + 'static)) -> Option<Classification> {
+        if let Some(e) = cause.downcast_ref::<SeesawError>() {
+            return Some(Classification::of(e));
+        }
+        let registered = self.classifiers.iter().find_map(|classify| classify(cause));
+        if registered.is_some() {
+            return registered;
+        }
+        let io_err = cause.downcast_ref::<std::io::Error>()?;
+        let (category, safe_message) = match io_err.kind() {
+            std::io::ErrorKind::NotFound => (SafeErrorCategory::NotFound, "Resource not found"),
+            std::io::ErrorKind::PermissionDenied => {
+                (SafeErrorCategory::Unauthorized, "Access denied")
+            }
+            _ => (
+                SafeErrorCategory::InternalError,
+                "An internal error occurred",
+            ),
+        };
+        Some(Classification {
+            category,
+            safe_message: safe_message.into(),
+            retryable: category.is_transient(),
+        })
+    }
+}
+
+impl fmt::Debug for ErrorCategories {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorCategories")
+            .field("registered", &self.classifiers.len())
+            .finish()
+    }
 }
 
 /// A domain event emitted when an effect returns an error.
@@ -241,6 +397,9 @@ pub struct CommandFailed {
     /// - Database column names
     /// - PII
     pub safe_message: String,
+    /// Whether the command may succeed if tried again, from
+    /// [`Categorizable::is_retryable`].
+    pub retryable: bool,
     /// The correlation ID of the original command.
     pub cid: CorrelationId,
 }
@@ -274,74 +433,39 @@ impl CommandFailed {
         command_type: &'static str,
         cid: CorrelationId,
     ) -> Self {
-        let (category, safe_message) = Self::categorize_and_sanitize(error);
+        Self::categorized(error, command_type, cid, &ErrorCategories::default())
+    }
+
+    /// Create a CommandFailed event from an anyhow error, recognizing the
+    /// domain error types in `categories`.
+    ///
+    /// The dispatcher uses this with the types registered through
+    /// `EngineBuilder::with_error_category`.
+    pub fn categorized(
+        error: &anyhow::Error,
+        command_type: &'static str,
+        cid: CorrelationId,
+        categories: &ErrorCategories,
+    ) -> Self {
+        let classification = categories.classify(error);
         Self {
             command_type,
-            category,
-            safe_message,
+            category: classification.category,
+            safe_message: classification.safe_message,
+            retryable: classification.retryable,
             cid,
         }
     }
 
     /// Categorize and sanitize an error for external consumption.
     ///
-    /// Uses anyhow's downcasting to identify specific error types that implement
-    /// [`Categorizable`]. Falls back to InternalError with a generic message.
-    ///
-    /// # Error Type Priority
-    ///
-    /// Errors are checked in order of specificity. Add new domain error types
-    /// to this function as they implement `Categorizable`.
-    ///
-    /// # Note
-    ///
-    /// Rust cannot downcast to `dyn Categorizable` - we must use concrete types.
-    /// If this list grows large, consider generating it with a macro.
-    fn categorize_and_sanitize(error: &anyhow::Error) -> (SafeErrorCategory, String) {
-        // =================================================================
-        // Seesaw framework errors (Categorizable)
-        // =================================================================
-        if let Some(e) = error.downcast_ref::<SeesawError>() {
-            return (e.category(), e.safe_message().into_owned());
-        }
-
-        // =================================================================
-        // Domain errors (add new Categorizable types here)
-        // =================================================================
-        // TODO: Add domain errors as they implement Categorizable:
-        // if let Some(e) = error.downcast_ref::<DeckError>() {
-        //     return (e.category(), e.safe_message().into_owned());
-        // }
-        // if let Some(e) = error.downcast_ref::<AuthError>() {
-        //     return (e.category(), e.safe_message().into_owned());
-        // }
-
-        // =================================================================
-        // Standard library errors
-        // =================================================================
-        if let Some(io_err) = error.downcast_ref::<std::io::Error>() {
-            return match io_err.kind() {
-                std::io::ErrorKind::NotFound => {
-                    (SafeErrorCategory::NotFound, "Resource not found".into())
-                }
-                std::io::ErrorKind::PermissionDenied => {
-                    (SafeErrorCategory::Unauthorized, "Access denied".into())
-                }
-                _ => (
-                    SafeErrorCategory::InternalError,
-                    "An internal error occurred".into(),
-                ),
-            };
-        }
-
-        // =================================================================
-        // Default fallback
-        // =================================================================
-        // NEVER use error.to_string() here - it may contain sensitive data
-        (
-            SafeErrorCategory::InternalError,
-            "An internal error occurred".into(),
-        )
+    /// Recognizes only the built-in error types ([`SeesawError`] and
+    /// `std::io::Error`) anywhere in the error's chain, falling back to
+    /// InternalError with a generic message. Register domain errors in an
+    /// [`ErrorCategories`] instead.
+    pub(crate) fn categorize_and_sanitize(error: &anyhow::Error) -> (SafeErrorCategory, String) {
+        let classification = ErrorCategories::default().classify(error);
+        (classification.category, classification.safe_message)
     }
 
     /// Create a validation error with a safe message.
@@ -354,6 +478,7 @@ impl CommandFailed {
             command_type,
             category: SafeErrorCategory::Validation,
             safe_message: message.into(),
+            retryable: false,
             cid,
         }
     }
@@ -368,6 +493,7 @@ impl CommandFailed {
             command_type,
             category: SafeErrorCategory::NotFound,
             safe_message: format!("{} not found", resource.into()),
+            retryable: false,
             cid,
         }
     }
@@ -378,6 +504,7 @@ impl CommandFailed {
             command_type,
             category: SafeErrorCategory::Unauthorized,
             safe_message: "Access denied".into(),
+            retryable: false,
             cid,
         }
     }
@@ -388,6 +515,7 @@ impl CommandFailed {
             command_type,
             category: SafeErrorCategory::RateLimited,
             safe_message: "Rate limit exceeded. Please try again later.".into(),
+            retryable: true,
             cid,
         }
     }
@@ -403,6 +531,27 @@ impl fmt::Display for CommandFailed {
     }
 }
 
+/// Emitted instead of running inline commands whose deadline has passed.
+///
+/// The dispatcher checks the [deadline](crate::Metadata::with_deadline) of the
+/// event a batch was decided from before calling the effect. When nobody can
+/// use the result any more, the batch is skipped, `emit_and_await` returns
+/// [`SeesawError::DeadlineExceeded`], and this event is emitted with the
+/// same correlation ID so machines can compensate.
+#[derive(Debug, Clone)]
+pub struct CommandExpired {
+    /// The type name of the skipped commands.
+    pub command_type: &'static str,
+    /// How many commands were skipped.
+    pub count: usize,
+    /// How long ago the deadline passed.
+    pub overdue: std::time::Duration,
+    /// The correlation ID of the skipped commands.
+    pub cid: CorrelationId,
+}
+
+impl std::error::Error for CommandFailed {}
+
 // CommandFailed automatically implements Event via blanket impl
 // (Clone + Send + Sync + 'static)
 
@@ -439,6 +588,15 @@ pub enum BatchOutcome {
         /// The error that caused the failure.
         error: anyhow::Error,
     },
+
+    /// Independent commands ran concurrently and some of them failed.
+    ///
+    /// Every command was executed; those not listed in `failed` succeeded.
+    /// See `Command::independent`.
+    Concurrent {
+        /// Index and error of each command that failed, in batch order.
+        failed: Vec<(usize, anyhow::Error)>,
+    },
 }
 
 impl BatchOutcome {
@@ -457,6 +615,19 @@ impl BatchOutcome {
         match self {
             BatchOutcome::Complete => total,
             BatchOutcome::Partial { succeeded, .. } => *succeeded,
+            BatchOutcome::Concurrent { failed } => total.saturating_sub(failed.len()),
+        }
+    }
+
+    /// Returns the indices of the commands that failed.
+    ///
+    /// For a [`Partial`](Self::Partial) batch this is only `failed_at`; the
+    /// commands after it were not executed.
+    pub fn failed_indices(&self) -> Vec<usize> {
+        match self {
+            BatchOutcome::Complete => Vec::new(),
+            BatchOutcome::Partial { failed_at, .. } => vec![*failed_at],
+            BatchOutcome::Concurrent { failed } => failed.iter().map(|(i, _)| *i).collect(),
         }
     }
 }
@@ -476,6 +647,13 @@ impl fmt::Display for BatchOutcome {
                     succeeded, failed_at, error
                 )
             }
+            BatchOutcome::Concurrent { failed } => {
+                write!(f, "batch concurrent: {} failed", failed.len())?;
+                for (index, error) in failed {
+                    write!(f, "; index {}: {}", index, error)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -506,6 +684,29 @@ pub enum SeesawError {
         type_name: &'static str,
     },
 
+    /// `EngineBuilder::validate` found commands that would fail to dispatch
+    /// or effects registered twice.
+    #[error("invalid engine wiring: {report}")]
+    InvalidWiring {
+        /// Every problem found, warnings included.
+        report: WiringReport,
+    },
+
+    /// The engine's runtime is not running, so it cannot apply a change
+    /// made through `EngineHandle`.
+    #[error("engine is not running")]
+    EngineStopped,
+
+    /// An effect added to a running engine depends on a different type than
+    /// the engine's dependencies.
+    #[error("effect depends on {actual}, but the engine's dependencies are {expected}")]
+    DepsMismatch {
+        /// Type name of the engine's dependencies.
+        expected: &'static str,
+        /// Type name of the effect's dependencies.
+        actual: &'static str,
+    },
+
     /// Command type mismatch during dispatch (internal error).
     #[error("command type mismatch: expected {expected}")]
     CommandTypeMismatch {
@@ -522,6 +723,74 @@ pub enum SeesawError {
         duration: std::time::Duration,
     },
 
+    /// An inline batch ran longer than the engine's batch timeout while
+    /// `emit_and_await` waited on it.
+    ///
+    /// The batch was not cancelled; `batch` describes it as of the timeout.
+    #[error("inline batch exceeded the {limit:?} batch timeout: {batch}")]
+    BatchTimeout {
+        /// The batch that exceeded the timeout.
+        batch: InflightBatchInfo,
+        /// The configured batch timeout.
+        limit: std::time::Duration,
+    },
+
+    /// The deadline of the event a command was decided from passed before
+    /// the command ran, so it was skipped.
+    #[error("deadline passed {overdue:?} before command type {type_name} ran")]
+    DeadlineExceeded {
+        /// Human-readable type name of the command.
+        type_name: &'static str,
+        /// How long ago the deadline passed.
+        overdue: std::time::Duration,
+    },
+
+    /// An inline effect exceeded its configured execution budget.
+    ///
+    /// The effect future was dropped (cancelled) when the budget elapsed.
+    #[error("effect for command type {type_name} timed out after {duration:?}")]
+    EffectTimeout {
+        /// Human-readable type name of the command.
+        type_name: &'static str,
+        /// The configured timeout that was exceeded.
+        duration: std::time::Duration,
+    },
+
+    /// An inline effect panicked; the panic was caught by the dispatcher.
+    #[error("effect for command type {type_name} panicked: {message}")]
+    EffectPanicked {
+        /// Human-readable type name of the command.
+        type_name: &'static str,
+        /// The panic payload, if it was a string.
+        message: String,
+    },
+
+    /// The circuit breaker for a command type is open; the effect was not run.
+    #[error("circuit breaker open for command type {type_name}, retry after {retry_after:?}")]
+    CircuitOpen {
+        /// Human-readable type name of the command.
+        type_name: &'static str,
+        /// Time remaining until the breaker allows a probe.
+        retry_after: std::time::Duration,
+    },
+
+    /// A rate limit in `Reject` mode had no token for this execution; the
+    /// effect was not run.
+    #[error("rate limit exceeded for command type {type_name}, retry after {retry_after:?}")]
+    Throttled {
+        /// Human-readable type name of the command.
+        type_name: &'static str,
+        /// Time until the rate limit has capacity again.
+        retry_after: std::time::Duration,
+    },
+
+    /// The event bus buffer is full and its backpressure policy rejected the event.
+    #[error("event bus full (capacity {capacity}), event rejected")]
+    BusFull {
+        /// The bus's buffer capacity.
+        capacity: usize,
+    },
+
     /// Background command enqueue failed.
     #[error("failed to enqueue background command: {message}")]
     BackgroundEnqueueFailed {
@@ -539,14 +808,24 @@ pub enum SeesawError {
 
 impl Categorizable for SeesawError {
     fn category(&self) -> SafeErrorCategory {
-        // All SeesawError variants are internal errors
-        SafeErrorCategory::InternalError
+        // All SeesawError variants except throttling are internal errors
+        match self {
+            SeesawError::Throttled { .. } => SafeErrorCategory::RateLimited,
+            _ => SafeErrorCategory::InternalError,
+        }
     }
 
     fn safe_message(&self) -> Cow<'static, str> {
         // InternalError category - return generic messages only
         match self {
-            SeesawError::Timeout { .. } => "Operation timed out".into(),
+            SeesawError::Timeout { .. }
+            | SeesawError::BatchTimeout { .. }
+            | SeesawError::DeadlineExceeded { .. }
+            | SeesawError::EffectTimeout { .. } => "Operation timed out".into(),
+            SeesawError::CircuitOpen { .. } | SeesawError::BusFull { .. } => {
+                "Service temporarily unavailable".into()
+            }
+            SeesawError::Throttled { .. } => "Rate limit exceeded. Please try again later.".into(),
             _ => "An internal error occurred".into(),
         }
     }
@@ -584,6 +863,66 @@ mod tests {
         assert!(err.to_string().contains("30"));
     }
 
+    #[test]
+    fn test_effect_timeout_display_and_safe_message() {
+        let err = SeesawError::EffectTimeout {
+            type_name: "FetchCommand",
+            duration: std::time::Duration::from_millis(250),
+        };
+        assert!(err.to_string().contains("FetchCommand"));
+        assert!(err.to_string().contains("250ms"));
+        assert_eq!(err.category(), SafeErrorCategory::InternalError);
+        assert_eq!(err.safe_message(), "Operation timed out");
+    }
+
+    #[test]
+    fn test_effect_panicked_hides_panic_message() {
+        let err = SeesawError::EffectPanicked {
+            type_name: "ChargeCommand",
+            message: "index out of bounds".to_string(),
+        };
+        assert!(err.to_string().contains("index out of bounds"));
+
+        let failed = CommandFailed::from_error(&err.into(), "ChargeCommand", CorrelationId::NONE);
+        assert_eq!(failed.category, SafeErrorCategory::InternalError);
+        assert!(!failed.safe_message.contains("index out of bounds"));
+    }
+
+    #[test]
+    fn test_throttled_is_rate_limited() {
+        let err = SeesawError::Throttled {
+            type_name: "GeocodeCommand",
+            retry_after: std::time::Duration::from_millis(250),
+        };
+        assert!(err.to_string().contains("GeocodeCommand"));
+        assert_eq!(err.category(), SafeErrorCategory::RateLimited);
+
+        let failed = CommandFailed::from_error(&err.into(), "GeocodeCommand", CorrelationId::NONE);
+        assert_eq!(failed.category, SafeErrorCategory::RateLimited);
+        assert_eq!(
+            failed.safe_message,
+            "Rate limit exceeded. Please try again later."
+        );
+    }
+
+    #[test]
+    fn test_circuit_open_display_and_safe_message() {
+        let err = SeesawError::CircuitOpen {
+            type_name: "ChargeCommand",
+            retry_after: std::time::Duration::from_secs(5),
+        };
+        assert!(err.to_string().contains("ChargeCommand"));
+        assert!(err.to_string().contains("5s"));
+        assert_eq!(err.safe_message(), "Service temporarily unavailable");
+    }
+
+    #[test]
+    fn test_bus_full_display() {
+        let err = SeesawError::BusFull { capacity: 64 };
+        assert!(err.to_string().contains("64"));
+        assert_eq!(err.safe_message(), "Service temporarily unavailable");
+    }
+
     #[test]
     fn test_error_is_pattern_matchable() {
         let err = SeesawError::NoEffectRegistered {
@@ -659,6 +998,26 @@ mod tests {
         };
         assert!(outcome.is_partial());
         assert_eq!(outcome.succeeded_count(5), 0);
+        assert_eq!(outcome.failed_indices(), vec![0]);
+    }
+
+    #[test]
+    fn test_batch_outcome_concurrent_reports_each_failure() {
+        let outcome = BatchOutcome::Concurrent {
+            failed: vec![
+                (1, anyhow::anyhow!("timeout")),
+                (3, anyhow::anyhow!("refused")),
+            ],
+        };
+        assert!(!outcome.is_complete());
+        assert!(!outcome.is_partial());
+        assert_eq!(outcome.succeeded_count(4), 2);
+        assert_eq!(outcome.failed_indices(), vec![1, 3]);
+
+        let display = outcome.to_string();
+        assert!(display.contains("2 failed"));
+        assert!(display.contains("index 1: timeout"));
+        assert!(display.contains("index 3: refused"));
     }
 
     // ==========================================================================
@@ -707,4 +1066,88 @@ mod tests {
         // Timeout is safe to expose, but not the exact duration
         assert_eq!(safe_msg, "Operation timed out");
     }
+
+    #[test]
+    fn test_batch_timeout_display_and_safe_message() {
+        let batch = InflightBatchInfo {
+            cid: CorrelationId::new(),
+            command_type: "SendEmail",
+            event_type: Some("OrderEvent"),
+            size: 3,
+            age: std::time::Duration::from_secs(6),
+        };
+        let err = SeesawError::BatchTimeout {
+            batch,
+            limit: std::time::Duration::from_secs(5),
+        };
+
+        let message = err.to_string();
+        assert!(message.contains("3 x SendEmail decided from OrderEvent"));
+        assert!(message.contains("5s batch timeout"));
+        assert_eq!(err.safe_message(), "Operation timed out");
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    enum PaymentError {
+        #[error("card declined by issuer: {0}")]
+        CardDeclined(String),
+        #[error("gateway returned 400")]
+        GatewayRejected,
+    }
+
+    impl Categorizable for PaymentError {
+        fn category(&self) -> SafeErrorCategory {
+            match self {
+                PaymentError::CardDeclined(_) => SafeErrorCategory::Validation,
+                PaymentError::GatewayRejected => SafeErrorCategory::ExternalService,
+            }
+        }
+
+        fn safe_message(&self) -> Cow<'static, str> {
+            match self {
+                PaymentError::CardDeclined(_) => "Card declined".into(),
+                PaymentError::GatewayRejected => "Payment provider unavailable".into(),
+            }
+        }
+
+        fn is_retryable(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_registered_categories_survive_context() {
+        let mut categories = ErrorCategories::new();
+        categories.register::<PaymentError>();
+
+        let error = anyhow::Error::new(PaymentError::CardDeclined("4242".into()))
+            .context("charging order 17");
+        let failed = CommandFailed::categorized(&error, "Charge", CorrelationId::NONE, &categories);
+        assert_eq!(failed.category, SafeErrorCategory::Validation);
+        assert_eq!(failed.safe_message, "Card declined");
+        assert!(!failed.retryable);
+
+        // Overridden retryability, despite a transient category
+        let error = anyhow::Error::new(PaymentError::GatewayRejected);
+        let failed = CommandFailed::categorized(&error, "Charge", CorrelationId::NONE, &categories);
+        assert_eq!(failed.category, SafeErrorCategory::ExternalService);
+        assert!(!failed.retryable);
+
+        // Unregistered, the error is internal and its details are hidden
+        let failed = CommandFailed::from_error(&error, "Charge", CorrelationId::NONE);
+        assert_eq!(failed.category, SafeErrorCategory::InternalError);
+        assert_eq!(failed.safe_message, "An internal error occurred");
+        assert!(failed.retryable);
+    }
+
+    #[test]
+    fn test_category_is_transient() {
+        assert!(!SafeErrorCategory::Validation.is_transient());
+        assert!(!SafeErrorCategory::NotFound.is_transient());
+        assert!(!SafeErrorCategory::Unauthorized.is_transient());
+        assert!(SafeErrorCategory::RateLimited.is_transient());
+        assert!(SafeErrorCategory::InternalError.is_transient());
+        assert!(SafeErrorCategory::ExternalService.is_transient());
+        assert!(SafeErrorCategory::AIFailure.is_transient());
+    }
 }
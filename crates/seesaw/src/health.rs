@@ -0,0 +1,248 @@
+//! Engine health and readiness introspection.
+//!
+//! [`EngineHandle::health`](crate::EngineHandle::health) returns a [`Health`]
+//! snapshot suitable for a status endpoint, and
+//! [`EngineHandle::is_ready`](crate::EngineHandle::is_ready) a single boolean
+//! for readiness probes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! async fn readyz(State(engine): State<Arc<EngineHandle>>) -> StatusCode {
+//!     if engine.is_ready() {
+//!         StatusCode::OK
+//!     } else {
+//!         StatusCode::SERVICE_UNAVAILABLE
+//!     }
+//! }
+//!
+//! async fn healthz(State(engine): State<Arc<EngineHandle>>) -> Json<Value> {
+//!     let health = engine.health();
+//!     Json(json!({
+//!         "inflight_batches": health.inflight_batches,
+//!         "queued_events": health.queued_events,
+//!         "last_event_at": health.last_event_at,
+//!     }))
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+
+use crate::child::ChildHealth;
+use crate::metrics;
+use crate::tap::TapStats;
+
+// =============================================================================
+// Snapshot
+// =============================================================================
+
+/// Point-in-time view of a running engine.
+#[derive(Debug, Clone)]
+pub struct Health {
+    /// Whether the runtime loop is running and subscribed to the bus.
+    pub ready: bool,
+    /// Inline effect executions (single commands or batches) in progress.
+    pub inflight_batches: usize,
+    /// Events on the bus not yet received by every subscriber.
+    pub queued_events: usize,
+    /// Number of machines registered with the runtime.
+    pub machines: usize,
+    /// Execution counts per command type, sorted by command type.
+    pub effects: Vec<EffectHealth>,
+    /// Delivery counts per event tap, in registration order.
+    pub taps: Vec<TapHealth>,
+    /// When the runtime last received an event.
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Health of each child engine, in the order they were added.
+    pub children: Vec<ChildHealth>,
+}
+
+/// Execution counts for one effect since the engine started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectHealth {
+    /// Human-readable type name of the command.
+    pub command_type: &'static str,
+    /// Executions, counting a batch as one.
+    pub executions: u64,
+    /// Executions that failed, including timeouts, panics and fast-fails.
+    pub failures: u64,
+}
+
+impl EffectHealth {
+    /// Fraction of executions that failed, in `[0.0, 1.0]`.
+    pub fn error_rate(&self) -> f64 {
+        if self.executions == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.executions as f64
+        }
+    }
+}
+
+/// Delivery counts for one event tap since the engine started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapHealth {
+    /// Human-readable type name of the tap.
+    pub name: &'static str,
+    /// Events the tap handled successfully.
+    pub delivered: u64,
+    /// Events discarded because the tap's buffer was full.
+    pub dropped: u64,
+    /// Events on which the tap returned an error or panicked.
+    pub failed: u64,
+}
+
+// =============================================================================
+// Monitor
+// =============================================================================
+
+#[derive(Debug, Default)]
+struct EffectCounters {
+    executions: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// Health counters shared by the dispatcher, the runtime and engine handles.
+#[derive(Debug, Default)]
+pub(crate) struct HealthMonitor {
+    running: AtomicBool,
+    machines: AtomicUsize,
+    inflight_batches: AtomicUsize,
+    last_event_at: Mutex<Option<DateTime<Utc>>>,
+    effects: DashMap<&'static str, EffectCounters>,
+    taps: Mutex<Vec<Arc<TapStats>>>,
+}
+
+impl HealthMonitor {
+    /// Mark the runtime loop as running with `machines` registered.
+    pub(crate) fn started(&self, machines: usize) {
+        self.machines.store(machines, Ordering::Relaxed);
+        self.running.store(true, Ordering::Release);
+    }
+
+    /// Record the number of machines after the runtime's wiring changed.
+    pub(crate) fn machines_changed(&self, machines: usize) {
+        self.machines.store(machines, Ordering::Relaxed);
+    }
+
+    /// Mark the runtime loop as stopped.
+    pub(crate) fn stopped(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// Record that the runtime received an event.
+    pub(crate) fn record_event(&self) {
+        *self.last_event_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(Utc::now());
+    }
+
+    /// Count an effect execution as in progress until the guard drops.
+    pub(crate) fn begin_batch(&self) -> BatchGuard<'_> {
+        let inflight = self.inflight_batches.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::inflight_batches(inflight);
+        BatchGuard(self)
+    }
+
+    /// Record the outcome of an effect execution.
+    pub(crate) fn record_effect(&self, command_type: &'static str, ok: bool) {
+        let counters = self.effects.entry(command_type).or_default();
+        counters.executions.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            counters.failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Include these taps' delivery counters in snapshots.
+    pub(crate) fn track_taps(&self, taps: Vec<Arc<TapStats>>) {
+        *self.taps.lock().unwrap_or_else(|e| e.into_inner()) = taps;
+    }
+
+    /// Take a snapshot, given the current bus depth.
+    pub(crate) fn snapshot(&self, queued_events: usize) -> Health {
+        let mut effects: Vec<EffectHealth> = self
+            .effects
+            .iter()
+            .map(|entry| EffectHealth {
+                command_type: entry.key(),
+                executions: entry.executions.load(Ordering::Relaxed),
+                failures: entry.failures.load(Ordering::Relaxed),
+            })
+            .collect();
+        effects.sort_by_key(|e| e.command_type);
+
+        let taps = self
+            .taps
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|tap| TapHealth {
+                name: tap.name,
+                delivered: tap.delivered.load(Ordering::Relaxed),
+                dropped: tap.dropped.load(Ordering::Relaxed),
+                failed: tap.failed.load(Ordering::Relaxed),
+            })
+            .collect();
+
+        Health {
+            ready: self.is_running(),
+            inflight_batches: self.inflight_batches.load(Ordering::Relaxed),
+            queued_events,
+            machines: self.machines.load(Ordering::Relaxed),
+            effects,
+            taps,
+            last_event_at: *self.last_event_at.lock().unwrap_or_else(|e| e.into_inner()),
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Decrements the in-progress count on drop, even if the execution unwinds.
+pub(crate) struct BatchGuard<'a>(&'a HealthMonitor);
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        let inflight = self.0.inflight_batches.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::inflight_batches(inflight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_reports_counters() {
+        let monitor = HealthMonitor::default();
+        assert!(!monitor.snapshot(0).ready);
+
+        monitor.started(2);
+        monitor.record_event();
+        monitor.record_effect("B", true);
+        monitor.record_effect("A", true);
+        monitor.record_effect("A", false);
+        let guard = monitor.begin_batch();
+
+        let health = monitor.snapshot(5);
+        assert!(health.ready);
+        assert_eq!(health.machines, 2);
+        assert_eq!(health.queued_events, 5);
+        assert_eq!(health.inflight_batches, 1);
+        assert!(health.last_event_at.is_some());
+        assert_eq!(health.effects[0].command_type, "A");
+        assert_eq!(health.effects[0].error_rate(), 0.5);
+        assert_eq!(health.effects[1].error_rate(), 0.0);
+
+        drop(guard);
+        monitor.stopped();
+        let health = monitor.snapshot(0);
+        assert_eq!(health.inflight_batches, 0);
+        assert!(!health.ready);
+    }
+}
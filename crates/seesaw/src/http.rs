@@ -0,0 +1,302 @@
+//! Traced outbound HTTP calls from effects (`http` feature).
+//!
+//! [`EffectContext::http`](crate::EffectContext::http) wraps the deps'
+//! `reqwest::Client` so that every request an effect makes:
+//!
+//! - carries the correlation ID in a [`CORRELATION_ID_HEADER`] header, so
+//!   the receiving service can log the workflow that called it
+//! - gives up when the deadline of the triggering event passes, failing with
+//!   [`SeesawError::DeadlineExceeded`] if it passed before the request was
+//!   sent
+//! - is timed into `seesaw_http_request_duration_seconds` per command type
+//!   (`metrics` feature) and logged at debug level
+//!
+//! ```ignore
+//! async fn execute(&self, cmd: FetchPrice, ctx: EffectContext<Deps>) -> Result<PriceFetched> {
+//!     let response = ctx
+//!         .http()
+//!         .get(&cmd.url)
+//!         .query(&[("sku", &cmd.sku)])
+//!         .send()
+//!         .await?
+//!         .error_for_status()?;
+//!     Ok(PriceFetched { price: response.text().await?.parse()? })
+//! }
+//! ```
+//!
+//! Requests built from the client directly, `ctx.get::<reqwest::Client>()`,
+//! get none of this.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::{IntoUrl, Method, RequestBuilder, Response};
+use serde::Serialize;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::core::CorrelationId;
+use crate::error::SeesawError;
+use crate::metrics;
+
+/// Header carrying the correlation ID of the calling workflow.
+pub const CORRELATION_ID_HEADER: &str = "Seesaw-Correlation-Id";
+
+/// A `reqwest::Client` that tags, bounds and times requests for one
+/// command. See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    cid: CorrelationId,
+    command_type: &'static str,
+    deadline: Option<Instant>,
+}
+
+impl HttpClient {
+    pub(crate) fn new(
+        client: reqwest::Client,
+        cid: CorrelationId,
+        command_type: &'static str,
+        deadline: Option<Instant>,
+    ) -> Self {
+        Self {
+            client,
+            cid,
+            command_type,
+            deadline,
+        }
+    }
+
+    /// Start a `GET` request to `url`.
+    pub fn get(&self, url: impl IntoUrl) -> HttpRequest {
+        self.request(Method::GET, url)
+    }
+
+    /// Start a `POST` request to `url`.
+    pub fn post(&self, url: impl IntoUrl) -> HttpRequest {
+        self.request(Method::POST, url)
+    }
+
+    /// Start a `PUT` request to `url`.
+    pub fn put(&self, url: impl IntoUrl) -> HttpRequest {
+        self.request(Method::PUT, url)
+    }
+
+    /// Start a `PATCH` request to `url`.
+    pub fn patch(&self, url: impl IntoUrl) -> HttpRequest {
+        self.request(Method::PATCH, url)
+    }
+
+    /// Start a `DELETE` request to `url`.
+    pub fn delete(&self, url: impl IntoUrl) -> HttpRequest {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Start a request with `method` to `url`.
+    pub fn request(&self, method: Method, url: impl IntoUrl) -> HttpRequest {
+        let mut builder = self.client.request(method, url);
+        if !self.cid.is_none() {
+            builder = builder.header(CORRELATION_ID_HEADER, self.cid.to_string());
+        }
+        HttpRequest {
+            builder,
+            command_type: self.command_type,
+            deadline: self.deadline,
+            timeout: None,
+            body_error: None,
+        }
+    }
+}
+
+/// A request being built by an [`HttpClient`].
+///
+/// Mirrors the common `reqwest::RequestBuilder` methods; reach the rest
+/// with [`with`](Self::with).
+#[derive(Debug)]
+#[must_use = "a request does nothing until it is sent"]
+pub struct HttpRequest {
+    builder: RequestBuilder,
+    command_type: &'static str,
+    deadline: Option<Instant>,
+    timeout: Option<Duration>,
+    /// Failure to serialize a JSON body, reported on send.
+    body_error: Option<serde_json::Error>,
+}
+
+impl HttpRequest {
+    /// Add a header.
+    pub fn header(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.builder = self.builder.header(key.as_ref(), value.as_ref());
+        self
+    }
+
+    /// Add headers.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.builder = self.builder.headers(headers);
+        self
+    }
+
+    /// Authenticate with a bearer token.
+    pub fn bearer_auth(mut self, token: impl std::fmt::Display) -> Self {
+        self.builder = self.builder.bearer_auth(token);
+        self
+    }
+
+    /// Add `query` to the URL's query string.
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.builder = self.builder.query(query);
+        self
+    }
+
+    /// Send `body` as is.
+    pub fn body(mut self, body: impl Into<reqwest::Body>) -> Self {
+        self.builder = self.builder.body(body);
+        self
+    }
+
+    /// Send `body` as JSON.
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        match serde_json::to_vec(body) {
+            Ok(body) => {
+                self.builder = self
+                    .builder
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body);
+            }
+            Err(e) => self.body_error = Some(e),
+        }
+        self
+    }
+
+    /// Give up after `timeout`, or at the deadline if that comes first.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Configure the underlying `reqwest::RequestBuilder`.
+    pub fn with(mut self, f: impl FnOnce(RequestBuilder) -> RequestBuilder) -> Self {
+        self.builder = f(self.builder);
+        self
+    }
+
+    /// Send the request.
+    ///
+    /// Fails with [`SeesawError::DeadlineExceeded`] without sending if the
+    /// deadline has passed. Error statuses are returned as responses, as
+    /// with `reqwest`.
+    pub async fn send(self) -> Result<Response> {
+        if let Some(e) = self.body_error {
+            return Err(e.into());
+        }
+        let (client, request) = self.builder.build_split();
+        let mut request = request?;
+
+        let remaining = match self.deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline <= now {
+                    return Err(SeesawError::DeadlineExceeded {
+                        type_name: self.command_type,
+                        overdue: now - deadline,
+                    }
+                    .into());
+                }
+                Some(deadline - now)
+            }
+            None => None,
+        };
+        let timeout = match (self.timeout.or(request.timeout().copied()), remaining) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        };
+        *request.timeout_mut() = timeout;
+
+        let method = request.method().clone();
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let started = std::time::Instant::now();
+        let result = client.execute(request).await;
+        let elapsed = started.elapsed();
+
+        let status = match &result {
+            Ok(response) => status_class(response.status().as_u16()),
+            Err(e) if e.is_timeout() => "timeout",
+            Err(_) => "error",
+        };
+        debug!(
+            command_type = self.command_type,
+            %method,
+            host = %host,
+            status,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "http request"
+        );
+        metrics::http_request(self.command_type, status, elapsed);
+        Ok(result?)
+    }
+}
+
+/// The metric label for a response status: `2xx`, `4xx`, ...
+fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(cid: CorrelationId, deadline: Option<Instant>) -> HttpClient {
+        HttpClient::new(reqwest::Client::new(), cid, "FetchPrice", deadline)
+    }
+
+    #[test]
+    fn test_requests_carry_the_correlation_id() {
+        let cid = CorrelationId::new();
+
+        let (_, request) = client(cid, None)
+            .get("http://example.com/price")
+            .builder
+            .build_split();
+
+        let header = request.unwrap().headers()[CORRELATION_ID_HEADER].clone();
+        assert_eq!(header.to_str().unwrap(), cid.to_string());
+
+        let (_, request) = client(CorrelationId::NONE, None)
+            .get("http://example.com/price")
+            .builder
+            .build_split();
+        assert!(request.unwrap().headers().get(CORRELATION_ID_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_passed_deadline_fails_without_sending() {
+        let deadline = Instant::now();
+
+        let error = client(CorrelationId::new(), Some(deadline))
+            // Nothing listens here; sending would fail differently
+            .post("http://127.0.0.1:9/price")
+            .json(&serde_json::json!({ "sku": "A1" }))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<SeesawError>(),
+            Some(SeesawError::DeadlineExceeded { type_name: "FetchPrice", .. })
+        ));
+    }
+
+    #[test]
+    fn test_status_classes() {
+        assert_eq!(status_class(204), "2xx");
+        assert_eq!(status_class(429), "4xx");
+        assert_eq!(status_class(503), "5xx");
+    }
+}
@@ -3,9 +3,14 @@
 //! This module provides policy-light interfaces for job execution:
 //! - [`JobStore`] - Trait for claiming and managing jobs from persistent storage
 //! - [`ClaimedJob`] - A job claimed by a worker, ready for execution
+//! - [`JobLease`] - The claim a worker must present to heartbeat or finish a job
 //! - [`CommandRegistry`] - Registry for deserializing job payloads back to commands
+//! - [`VersionedPayload`] - `{"v": 2, "body": ...}` envelope for persisted payloads
+//! - [`Upcaster`] - Migrates a payload from one version to the next
 //! - [`DeserializationError`] - Explicit failure modes for deserialization
 //! - [`FailureKind`] - Classification of job failures for retry decisions
+//! - [`ParkedJob`] - A job set aside because no worker can run it
+//! - [`LookaheadClaim`] - Claimed jobs plus those becoming ready soon
 //!
 //! # Design Philosophy
 //!
@@ -39,10 +44,10 @@
 //!         match registry.deserialize(&job) {
 //!             Ok(cmd) => {
 //!                 dispatcher.dispatch_one(cmd).await?;
-//!                 store.mark_succeeded(job.id).await?;
+//!                 store.mark_succeeded(job.lease()).await?;
 //!             }
 //!             Err(DeserializationError::UnknownCommandType(_)) => {
-//!                 store.mark_failed(job.id, "unknown type", FailureKind::NonRetryable).await?;
+//!                 store.park(job.lease(), "unknown type").await?;
 //!             }
 //!             // ... handle other cases
 //!         }
@@ -50,11 +55,30 @@
 //!     tokio::time::sleep(poll_interval).await;
 //! }
 //! ```
+//!
+//! The same loop as a stream consumer, with up to 10 jobs in flight:
+//!
+//! ```ignore
+//! store
+//!     .claim_stream("worker-1", 10)
+//!     .for_each_concurrent(10, |job| async {
+//!         match job {
+//!             Ok(job) => run(&store, &registry, &dispatcher, job).await,
+//!             Err(e) => warn!(error = %e, "claim failed"),
+//!         }
+//!     })
+//!     .await;
+//! ```
 
-use std::collections::HashMap;
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::core::{AnyCommand, Command};
@@ -68,6 +92,10 @@ use crate::core::{AnyCommand, Command};
 ///
 /// - Use `FOR UPDATE SKIP LOCKED` (PostgreSQL) or equivalent for atomic claiming
 /// - Set lease expiration when claiming to handle worker crashes
+/// - Issue a fresh [`ClaimedJob::lease_token`] with every claim, and reject
+///   heartbeats and outcomes whose [`JobLease`] no longer matches with
+///   [`LeaseLost`]: a worker that paused past its lease must not extend or
+///   finish a job another worker now owns
 /// - The store should handle retry delay calculation internally
 #[async_trait::async_trait]
 pub trait JobStore: Send + Sync {
@@ -88,16 +116,39 @@ pub trait JobStore: Send + Sync {
     /// A vector of claimed jobs, which may be empty if no jobs are ready.
     async fn claim_ready(&self, worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>>;
 
+    /// Claim ready jobs like [`claim_ready`](Self::claim_ready), and list up
+    /// to `limit` pending jobs becoming ready within `lookahead`, unclaimed,
+    /// with their `run_at`.
+    ///
+    /// A worker that claimed nothing can sleep until the first of them
+    /// instead of polling at a fixed interval; see
+    /// [`LookaheadClaim::next_poll_in`]. A store may skip the listing when it
+    /// claimed `limit` jobs, as the worker claims again at once. The default
+    /// lists no upcoming jobs, for stores that cannot query by `run_at`.
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: Duration,
+    ) -> Result<LookaheadClaim> {
+        let _ = lookahead;
+        Ok(LookaheadClaim {
+            jobs: self.claim_ready(worker_id, limit).await?,
+            upcoming: Vec::new(),
+        })
+    }
+
     /// Mark a job as succeeded.
     ///
     /// The store should update the job status and record completion time.
-    async fn mark_succeeded(&self, job_id: Uuid) -> Result<()>;
+    /// Fails with [`LeaseLost`] if `lease` is no longer the job's claim.
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()>;
 
     /// Mark a job as failed.
     ///
     /// # Arguments
     ///
-    /// * `job_id` - The job that failed
+    /// * `lease` - The claim on the job that failed
     /// * `error` - Error message to store
     /// * `kind` - Whether this failure is retryable
     ///
@@ -108,17 +159,220 @@ pub trait JobStore: Send + Sync {
     ///
     /// For non-retryable failures, the store should:
     /// - Mark as dead-letter immediately
-    async fn mark_failed(&self, job_id: Uuid, error: &str, kind: FailureKind) -> Result<()>;
+    ///
+    /// Fails with [`LeaseLost`] if `lease` is no longer the job's claim.
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()>;
+
+    /// Mark a job as failed like [`mark_failed`](Self::mark_failed), and
+    /// return whether it was dead-lettered rather than scheduled for a retry.
+    ///
+    /// The default reports only non-retryable failures as dead-lettered, for
+    /// stores that cannot tell whether a retryable failure was the last
+    /// attempt.
+    async fn record_failure(
+        &self,
+        lease: JobLease,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<bool> {
+        self.mark_failed(lease, error, kind).await?;
+        Ok(kind == FailureKind::NonRetryable)
+    }
 
     /// Send a heartbeat to extend the lease.
     ///
     /// Workers should call this periodically for long-running jobs to prevent
-    /// the job from being reclaimed by another worker.
-    async fn heartbeat(&self, job_id: Uuid) -> Result<()>;
+    /// the job from being reclaimed by another worker. Fails with
+    /// [`LeaseLost`] once it has been: the worker should abandon the attempt.
+    async fn heartbeat(&self, lease: JobLease) -> Result<()>;
+
+    /// Set aside a job no worker can run as it stands: its payload does not
+    /// deserialize, or no handler is registered for its job type.
+    ///
+    /// A parked job is never claimed and keeps its attempt count until an
+    /// operator unparks it, e.g. after deploying the missing handler. Fails
+    /// with [`LeaseLost`] if `lease` is no longer the job's claim.
+    ///
+    /// The default dead-letters the job, for stores without a parked state.
+    async fn park(&self, lease: JobLease, reason: &str) -> Result<()> {
+        self.mark_failed(lease, reason, FailureKind::NonRetryable).await
+    }
+
+    /// Reserve ready jobs for `window` without starting an attempt.
+    ///
+    /// The first phase of a two-phase claim: the worker validates and
+    /// deserializes a reserved job, then either [`confirm`](Self::confirm)s
+    /// it within `window` or [`release`](Self::release)s it. A reservation
+    /// that is neither lapses and the job becomes claimable again, without
+    /// having cost an attempt.
+    ///
+    /// The default claims the jobs outright: in stores without reservations
+    /// every reservation is already an attempt.
+    async fn reserve(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        window: Duration,
+    ) -> Result<Vec<ClaimedJob>> {
+        let _ = window;
+        self.claim_ready(worker_id, limit).await
+    }
+
+    /// Turn a reservation into an attempt, with the job's full lease.
+    ///
+    /// Fails with [`LeaseLost`] if the reservation lapsed or was released.
+    /// The default does nothing, as the default reservation is a claim.
+    async fn confirm(&self, lease: JobLease) -> Result<()> {
+        let _ = lease;
+        Ok(())
+    }
+
+    /// Give a reserved job back unconfirmed, e.g. because this worker has no
+    /// handler for its job type. It becomes claimable again at once and no
+    /// attempt is counted.
+    ///
+    /// The default records a retryable failure, as the default reservation
+    /// is a claim.
+    async fn release(&self, lease: JobLease) -> Result<()> {
+        self.mark_failed(lease, "released by worker", FailureKind::Retryable).await
+    }
+
+    /// Jobs still running under `worker_id`, each with its current lease.
+    ///
+    /// A worker restarting under the same ID after a crash finds the
+    /// attempts its previous process never finished; see
+    /// [`JobWorker::recover`](crate::JobWorker::recover). Unconfirmed
+    /// reservations are not included, as they lapse without costing an
+    /// attempt. The default fails, for stores that do not record which
+    /// worker holds a job, so a recovery never silently finds nothing.
+    async fn running_jobs(&self, worker_id: &str) -> Result<Vec<ClaimedJob>> {
+        Err(anyhow::anyhow!(
+            "job store does not support listing running jobs (worker {})",
+            worker_id
+        ))
+    }
+
+    /// Claim jobs as they become ready, one stream item per job.
+    ///
+    /// Jobs are claimed in batches of up to `concurrency`, and the next batch
+    /// only once the consumer has taken every job of the last one, so a
+    /// worker draining the stream with `for_each_concurrent(concurrency, ..)`
+    /// holds few leases on jobs it has not started. A failed claim is yielded
+    /// as an error and retried on the next poll; the stream never ends.
+    ///
+    /// The default implementation polls
+    /// [`claim_ready_with_lookahead`](Self::claim_ready_with_lookahead)
+    /// whenever no job is ready, waiting until the next upcoming job or
+    /// [`CLAIM_POLL_INTERVAL`], whichever comes first. Stores that can be
+    /// told when a job is enqueued should override it.
+    fn claim_stream<'a>(
+        &'a self,
+        worker_id: &'a str,
+        concurrency: usize,
+    ) -> BoxStream<'a, Result<ClaimedJob>> {
+        poll_claims(self, worker_id, concurrency, None, CLAIM_POLL_INTERVAL)
+    }
+
+    /// Reserve jobs for `window` as they become ready, one stream item per
+    /// job; see [`reserve`](Self::reserve).
+    ///
+    /// Batches like [`claim_stream`](Self::claim_stream), polling
+    /// [`reserve`](Self::reserve) every [`CLAIM_POLL_INTERVAL`] while no job
+    /// is ready.
+    fn reserve_stream<'a>(
+        &'a self,
+        worker_id: &'a str,
+        concurrency: usize,
+        window: Duration,
+    ) -> BoxStream<'a, Result<ClaimedJob>> {
+        poll_claims(self, worker_id, concurrency, Some(window), CLAIM_POLL_INTERVAL)
+    }
+}
+
+/// How long the default [`JobStore::claim_stream`] waits before polling
+/// again when no job was ready, or after a failed claim.
+pub const CLAIM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Stream jobs from `store.claim_ready_with_lookahead`, or `store.reserve`
+/// for a reservation `window`, sleeping up to `interval` between empty or
+/// failed claims.
+fn poll_claims<'a, S: JobStore + ?Sized>(
+    store: &'a S,
+    worker_id: &'a str,
+    concurrency: usize,
+    window: Option<Duration>,
+    interval: Duration,
+) -> BoxStream<'a, Result<ClaimedJob>> {
+    let limit = concurrency.max(1) as i64;
+    stream::unfold(VecDeque::new(), move |mut ready| async move {
+        loop {
+            if let Some(job) = ready.pop_front() {
+                return Some((Ok(job), ready));
+            }
+            let claimed = match window {
+                Some(window) => store.reserve(worker_id, limit, window).await.map(|jobs| {
+                    LookaheadClaim {
+                        jobs,
+                        upcoming: Vec::new(),
+                    }
+                }),
+                None => store.claim_ready_with_lookahead(worker_id, limit, interval).await,
+            };
+            match claimed {
+                Ok(claim) if claim.jobs.is_empty() => {
+                    tokio::time::sleep(claim.next_poll_in(interval)).await
+                }
+                Ok(claim) => ready.extend(claim.jobs),
+                Err(e) => {
+                    tokio::time::sleep(interval).await;
+                    return Some((Err(e), ready));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Jobs claimed by
+/// [`JobStore::claim_ready_with_lookahead`], and those becoming ready soon.
+#[derive(Debug, Clone, Default)]
+pub struct LookaheadClaim {
+    /// The jobs claimed, as [`JobStore::claim_ready`] returns them.
+    pub jobs: Vec<ClaimedJob>,
+    /// Pending jobs becoming ready within the lookahead, soonest first.
+    /// They are not claimed.
+    pub upcoming: Vec<UpcomingJob>,
+}
+
+impl LookaheadClaim {
+    /// When the first upcoming job becomes ready.
+    pub fn next_run_at(&self) -> Option<DateTime<Utc>> {
+        self.upcoming.iter().map(|job| job.run_at).min()
+    }
+
+    /// How long to wait before claiming again: until the next upcoming job,
+    /// but no longer than `max`, which bounds how late jobs enqueued or
+    /// retried meanwhile are picked up.
+    pub fn next_poll_in(&self, max: Duration) -> Duration {
+        match self.next_run_at() {
+            Some(run_at) => (run_at - Utc::now()).to_std().unwrap_or_default().min(max),
+            None => max,
+        }
+    }
+}
+
+/// A pending job that is not ready yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpcomingJob {
+    pub id: Uuid,
+    pub job_type: String,
+    /// When the job becomes ready.
+    pub run_at: DateTime<Utc>,
 }
 
 /// Classification of job failures for retry decisions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FailureKind {
     /// Failure may be transient; the job should be retried.
     ///
@@ -153,14 +407,149 @@ pub struct ClaimedJob {
     /// The attempt number (1-based).
     /// First attempt is 1, first retry is 2, etc.
     pub attempt: i32,
+
+    /// Fencing token for this claim, issued by the store.
+    ///
+    /// A job reclaimed after its lease expired gets a new token, so calls
+    /// made with this one fail with [`LeaseLost`].
+    pub lease_token: Uuid,
+}
+
+impl ClaimedJob {
+    /// The lease to heartbeat and finish this job with.
+    pub fn lease(&self) -> JobLease {
+        JobLease {
+            job_id: self.id,
+            token: self.lease_token,
+        }
+    }
+}
+
+/// A worker's claim on a job: the job and the token it was claimed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobLease {
+    pub job_id: Uuid,
+    pub token: Uuid,
+}
+
+/// The store rejected a heartbeat or outcome because the claim it was made
+/// under ended: the lease expired and the job was reclaimed or finished.
+///
+/// Whoever holds the current claim records the outcome; the worker that
+/// lost the lease should drop the attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("lease on job {job_id} was lost to another claim")]
+pub struct LeaseLost {
+    pub job_id: Uuid,
+}
+
+impl LeaseLost {
+    /// Whether `error` is a [`LeaseLost`].
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<LeaseLost>().is_some()
+    }
+}
+
+/// Queue administration: what dashboards and operators need beyond claiming.
+///
+/// Implemented by stores that can query their jobs, e.g. the SQL stores.
+/// Every method is read-only except [`retry_dead_letter`](Self::retry_dead_letter),
+/// [`unpark`](Self::unpark) and [`cancel`](Self::cancel).
+#[async_trait::async_trait]
+pub trait JobAdmin: Send + Sync {
+    /// Count the jobs in each status.
+    async fn stats(&self) -> Result<QueueStats>;
+
+    /// Count the jobs in each status per job type, ordered by job type.
+    async fn job_type_stats(&self) -> Result<Vec<JobTypeStats>>;
+
+    /// List dead-lettered jobs, most recently failed first.
+    async fn dead_letters(&self, limit: i64, offset: i64) -> Result<Vec<DeadLetterJob>>;
+
+    /// Move a dead-lettered job back to pending as a fresh first attempt.
+    ///
+    /// Returns `false` if the job does not exist or is not dead-lettered.
+    async fn retry_dead_letter(&self, job_id: Uuid) -> Result<bool>;
+
+    /// List parked jobs, most recently parked first.
+    ///
+    /// The default lists none, for stores without a parked state.
+    async fn list_parked(&self, limit: i64, offset: i64) -> Result<Vec<ParkedJob>> {
+        let _ = (limit, offset);
+        Ok(Vec::new())
+    }
+
+    /// Move a parked job back to pending, keeping its attempt count.
+    ///
+    /// Returns `false` if the job does not exist or is not parked.
+    async fn unpark(&self, job_id: Uuid) -> Result<bool> {
+        let _ = job_id;
+        Ok(false)
+    }
+
+    /// Cancel a pending job, so it is never claimed.
+    ///
+    /// Returns `false` if the job does not exist or is not pending; running
+    /// jobs cannot be cancelled.
+    async fn cancel(&self, job_id: Uuid) -> Result<bool>;
+}
+
+/// Job queue statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub pending: i64,
+    pub running: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+    pub dead_letter: i64,
+}
+
+/// Job queue statistics of one job type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobTypeStats {
+    pub job_type: String,
+    /// Counts of this job type's jobs.
+    #[serde(flatten)]
+    pub stats: QueueStats,
+    /// Pending jobs waiting out a retry backoff (also counted in `pending`).
+    pub retrying: i64,
+}
+
+/// A job in the dead letter queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetterJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub version: i32,
+    /// The attempt that failed for good.
+    pub attempt: i32,
+    pub error_message: Option<String>,
+    /// `retryable` or `non_retryable`.
+    pub error_kind: Option<String>,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A job set aside with [`JobStore::park`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParkedJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub version: i32,
+    /// The attempt the job resumes at once unparked.
+    pub attempt: i32,
+    /// Why no worker could run the job.
+    pub reason: Option<String>,
+    pub parked_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Deserialization errors with explicit failure modes.
 ///
 /// Each variant maps to a specific handling strategy in the worker:
-/// - `UnknownCommandType` → Dead-letter (non-retryable)
-/// - `UnsupportedVersion` → Dead-letter (non-retryable)
-/// - `InvalidPayload` → Dead-letter (non-retryable)
+/// - `UnknownCommandType` → Park (see [`JobStore::park`])
+/// - `UnsupportedVersion` → Park
+/// - `InvalidPayload` → Park
 #[derive(Debug, thiserror::Error)]
 pub enum DeserializationError {
     /// The command type is not registered in the registry.
@@ -191,6 +580,60 @@ impl DeserializationError {
     }
 }
 
+/// A persisted payload tagged with its schema version.
+///
+/// Serializes as `{"v": 2, "body": {...}}`. Stores without a version column
+/// can persist this envelope instead of the bare command; when a claimed
+/// job's payload is an envelope, [`CommandRegistry::deserialize`] takes the
+/// version from `v` rather than [`ClaimedJob::version`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedPayload {
+    /// The payload schema version.
+    pub v: i32,
+    /// The serialized command at that version.
+    pub body: serde_json::Value,
+}
+
+impl VersionedPayload {
+    /// Wrap `body` as version `v`.
+    pub fn new(v: i32, body: serde_json::Value) -> Self {
+        Self { v, body }
+    }
+
+    /// Serialize the envelope to JSON.
+    pub fn into_value(self) -> serde_json::Value {
+        serde_json::json!({ "v": self.v, "body": self.body })
+    }
+
+    /// Parse `payload` as an envelope.
+    ///
+    /// Only an object with exactly the keys `v` and `body` is an envelope;
+    /// anything else is a bare payload and gives `None`.
+    pub fn from_value(payload: &serde_json::Value) -> Option<Self> {
+        let object = payload.as_object()?;
+        if object.len() != 2 || !object.contains_key("body") {
+            return None;
+        }
+        let v = i32::try_from(object.get("v")?.as_i64()?).ok()?;
+        Some(Self::new(v, object["body"].clone()))
+    }
+}
+
+/// Migrates a job payload from one schema version to the next.
+///
+/// Implemented for any `Fn(serde_json::Value) -> Result<serde_json::Value>`
+/// closure; register one per version step with
+/// [`CommandRegistry::register_upcaster`].
+pub trait Upcaster:
+    Fn(serde_json::Value) -> Result<serde_json::Value> + Send + Sync + 'static
+{
+}
+
+impl<F> Upcaster for F where
+    F: Fn(serde_json::Value) -> Result<serde_json::Value> + Send + Sync + 'static
+{
+}
+
 /// Type-erased deserializer function.
 type DeserializeFn = Box<dyn Fn(&serde_json::Value) -> Result<Box<dyn AnyCommand>> + Send + Sync>;
 
@@ -219,12 +662,16 @@ struct CommandDeserializer {
 /// // Later, in the worker:
 /// match registry.deserialize(&claimed_job) {
 ///     Ok(cmd) => dispatcher.dispatch_one(cmd).await?,
-///     Err(e) => store.mark_failed(job.id, &e.to_string(), e.failure_kind()).await?,
+///     Err(e) => store.park(claimed_job.lease(), &e.to_string()).await?,
 /// }
 /// ```
 #[derive(Default)]
 pub struct CommandRegistry {
     deserializers: HashMap<&'static str, CommandDeserializer>,
+    /// Upcasters keyed by (job type, version they migrate from).
+    upcasters: HashMap<(&'static str, i32), Box<dyn Upcaster>>,
+    /// Command types with a registered deserializer.
+    command_types: HashSet<TypeId>,
 }
 
 impl CommandRegistry {
@@ -268,33 +715,89 @@ impl CommandRegistry {
                 deserialize,
             },
         );
+        self.command_types.insert(TypeId::of::<C>());
+    }
+
+    /// Register an upcaster migrating `job_type` payloads from
+    /// `from_version` to `from_version + 1`.
+    ///
+    /// When a job's version is not one the deserializer supports, upcasters
+    /// are applied one step at a time until it is. Old jobs keep working
+    /// after a field rename without supporting every version forever:
+    ///
+    /// ```ignore
+    /// registry.register::<SendEmailCommand>("email:send", vec![2]);
+    /// // v1 called the field `address`
+    /// registry.register_upcaster("email:send", 1, |mut payload| {
+    ///     if let Some(address) = payload.as_object_mut().and_then(|o| o.remove("address")) {
+    ///         payload["recipient"] = address;
+    ///     }
+    ///     Ok(payload)
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an upcaster is already registered for this job type and version.
+    pub fn register_upcaster<U>(&mut self, job_type: &'static str, from_version: i32, upcaster: U)
+    where
+        U: Upcaster,
+    {
+        if self.upcasters.contains_key(&(job_type, from_version)) {
+            panic!(
+                "upcaster already registered for job type {} version {}",
+                job_type, from_version
+            );
+        }
+        self.upcasters
+            .insert((job_type, from_version), Box::new(upcaster));
     }
 
     /// Deserialize a claimed job back to a command.
     ///
+    /// The payload may be a bare command at [`ClaimedJob::version`] or a
+    /// [`VersionedPayload`] envelope carrying its own version. Unsupported
+    /// versions are migrated through registered upcasters first.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The job type is not registered (`UnknownCommandType`)
-    /// - The job version is not supported (`UnsupportedVersion`)
-    /// - The payload cannot be deserialized (`InvalidPayload`)
+    /// - The job version is not supported and no upcasters reach a
+    ///   supported one (`UnsupportedVersion`)
+    /// - An upcaster fails, or the payload cannot be deserialized (`InvalidPayload`)
     pub fn deserialize(
         &self,
         job: &ClaimedJob,
     ) -> Result<Box<dyn AnyCommand>, DeserializationError> {
-        let entry = self
+        let (job_type, entry) = self
             .deserializers
-            .get(job.job_type.as_str())
+            .get_key_value(job.job_type.as_str())
             .ok_or_else(|| DeserializationError::UnknownCommandType(job.job_type.clone()))?;
 
-        if !entry.supported_versions.contains(&job.version) {
-            return Err(DeserializationError::UnsupportedVersion {
-                job_type: job.job_type.clone(),
-                version: job.version,
-            });
+        let (original_version, mut payload) = match VersionedPayload::from_value(&job.payload) {
+            Some(envelope) => (envelope.v, envelope.body),
+            None => (job.version, job.payload.clone()),
+        };
+
+        let mut version = original_version;
+        while !entry.supported_versions.contains(&version) {
+            let upcaster = self.upcasters.get(&(*job_type, version)).ok_or_else(|| {
+                DeserializationError::UnsupportedVersion {
+                    job_type: job.job_type.clone(),
+                    version: original_version,
+                }
+            })?;
+            payload = upcaster(payload).map_err(|e| {
+                DeserializationError::InvalidPayload(e.context(format!(
+                    "upcasting {} from version {} failed",
+                    job_type, version
+                )))
+            })?;
+            version += 1;
         }
 
-        (entry.deserialize)(&job.payload).map_err(DeserializationError::InvalidPayload)
+        (entry.deserialize)(&payload).map_err(DeserializationError::InvalidPayload)
     }
 
     /// Check if a job type is registered.
@@ -302,6 +805,15 @@ impl CommandRegistry {
         self.deserializers.contains_key(job_type)
     }
 
+    /// Check if a deserializer is registered for command type `C`.
+    pub fn handles<C: 'static>(&self) -> bool {
+        self.handles_type(TypeId::of::<C>())
+    }
+
+    pub(crate) fn handles_type(&self, command_type: TypeId) -> bool {
+        self.command_types.contains(&command_type)
+    }
+
     /// Get the number of registered deserializers.
     pub fn len(&self) -> usize {
         self.deserializers.len()
@@ -320,6 +832,7 @@ impl std::fmt::Debug for CommandRegistry {
                 "registered_types",
                 &self.deserializers.keys().collect::<Vec<_>>(),
             )
+            .field("upcasters", &self.upcasters.len())
             .finish()
     }
 }
@@ -347,6 +860,7 @@ mod tests {
             payload: serde_json::json!({ "message": "hello" }),
             version: 1,
             attempt: 1,
+            lease_token: Uuid::new_v4(),
         };
 
         let result = registry.deserialize(&job);
@@ -367,6 +881,7 @@ mod tests {
             payload: serde_json::json!({}),
             version: 1,
             attempt: 1,
+            lease_token: Uuid::new_v4(),
         };
 
         let result = registry.deserialize(&job);
@@ -387,6 +902,7 @@ mod tests {
             payload: serde_json::json!({ "message": "hello" }),
             version: 99, // Not supported
             attempt: 1,
+            lease_token: Uuid::new_v4(),
         };
 
         let result = registry.deserialize(&job);
@@ -407,6 +923,7 @@ mod tests {
             payload: serde_json::json!({ "wrong_field": "value" }), // Missing 'message'
             version: 1,
             attempt: 1,
+            lease_token: Uuid::new_v4(),
         };
 
         let result = registry.deserialize(&job);
@@ -436,6 +953,100 @@ mod tests {
         registry.register::<TestCommand>("test:command", vec![2]); // Should panic
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct RenamedCommand {
+        recipient: String,
+        body: String,
+    }
+
+    impl Command for RenamedCommand {}
+
+    /// v1 called `recipient` `address`; v2 called `body` `text`.
+    fn upcasting_registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::new();
+        registry.register::<RenamedCommand>("email:send", vec![3]);
+        registry.register_upcaster("email:send", 1, |mut payload| {
+            let address = payload
+                .as_object_mut()
+                .and_then(|o| o.remove("address"))
+                .ok_or_else(|| anyhow::anyhow!("missing address"))?;
+            payload["recipient"] = address;
+            Ok(payload)
+        });
+        registry.register_upcaster("email:send", 2, |mut payload| {
+            if let Some(text) = payload.as_object_mut().and_then(|o| o.remove("text")) {
+                payload["body"] = text;
+            }
+            Ok(payload)
+        });
+        registry
+    }
+
+    fn email_job(payload: serde_json::Value, version: i32) -> ClaimedJob {
+        ClaimedJob {
+            id: Uuid::new_v4(),
+            job_type: "email:send".to_string(),
+            payload,
+            version,
+            attempt: 1,
+            lease_token: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn test_registry_upcasts_old_versions() {
+        let registry = upcasting_registry();
+
+        let v1 = email_job(serde_json::json!({ "address": "a@b.c", "text": "hi" }), 1);
+        let cmd = registry.deserialize(&v1).unwrap();
+        let cmd = cmd.as_any().downcast_ref::<RenamedCommand>().unwrap();
+        assert_eq!(cmd.recipient, "a@b.c");
+        assert_eq!(cmd.body, "hi");
+
+        // The envelope's version wins over the job's
+        let enveloped =
+            VersionedPayload::new(2, serde_json::json!({ "recipient": "x@y.z", "text": "yo" }));
+        let job = email_job(enveloped.into_value(), 1);
+        let cmd = registry.deserialize(&job).unwrap();
+        let cmd = cmd.as_any().downcast_ref::<RenamedCommand>().unwrap();
+        assert_eq!(cmd.recipient, "x@y.z");
+        assert_eq!(cmd.body, "yo");
+    }
+
+    #[test]
+    fn test_registry_upcast_failures() {
+        let registry = upcasting_registry();
+
+        // No upcaster from version 0
+        let job = email_job(serde_json::json!({}), 0);
+        assert!(matches!(
+            registry.deserialize(&job),
+            Err(DeserializationError::UnsupportedVersion { version: 0, .. })
+        ));
+
+        let job = email_job(serde_json::json!({ "text": "hi" }), 1);
+        let err = registry.deserialize(&job).err().unwrap();
+        assert!(matches!(err, DeserializationError::InvalidPayload(_)));
+        assert!(format!("{:#}", err).contains("upcasting email:send from version 1"));
+    }
+
+    #[test]
+    fn test_versioned_payload_requires_exact_shape() {
+        let envelope = VersionedPayload::new(2, serde_json::json!({ "a": 1 }));
+        assert_eq!(
+            VersionedPayload::from_value(&envelope.clone().into_value()),
+            Some(envelope)
+        );
+        assert_eq!(
+            VersionedPayload::from_value(&serde_json::json!({ "v": 2, "body": {}, "extra": 1 })),
+            None
+        );
+        assert_eq!(
+            VersionedPayload::from_value(&serde_json::json!({ "body": "hi", "to": "x" })),
+            None
+        );
+    }
+
     #[test]
     fn test_registry_has() {
         let mut registry = CommandRegistry::new();
@@ -443,6 +1054,8 @@ mod tests {
 
         assert!(registry.has("test:command"));
         assert!(!registry.has("other:command"));
+        assert!(registry.handles::<TestCommand>());
+        assert!(!registry.handles::<String>());
     }
 
     #[test]
@@ -464,6 +1077,7 @@ mod tests {
             payload: serde_json::json!({}),
             version: 1,
             attempt: 1,
+            lease_token: Uuid::new_v4(),
         };
         let debug = format!("{:?}", job);
         assert!(debug.contains("ClaimedJob"));
@@ -476,4 +1090,101 @@ mod tests {
         assert_eq!(FailureKind::NonRetryable, FailureKind::NonRetryable);
         assert_ne!(FailureKind::Retryable, FailureKind::NonRetryable);
     }
+
+    #[test]
+    fn test_lookahead_claim_polls_at_next_run_at() {
+        let upcoming = |secs| UpcomingJob {
+            id: Uuid::new_v4(),
+            job_type: "test:command".to_string(),
+            run_at: Utc::now() + chrono::Duration::seconds(secs),
+        };
+        let max = Duration::from_secs(30);
+
+        assert_eq!(LookaheadClaim::default().next_poll_in(max), max);
+
+        let claim = LookaheadClaim {
+            jobs: Vec::new(),
+            upcoming: vec![upcoming(10), upcoming(-5), upcoming(60)],
+        };
+        // Already due: claim again at once
+        assert_eq!(claim.next_poll_in(max), Duration::ZERO);
+
+        let claim = LookaheadClaim {
+            jobs: Vec::new(),
+            upcoming: vec![upcoming(60), upcoming(10)],
+        };
+        let wait = claim.next_poll_in(max);
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+        assert_eq!(claim.next_poll_in(Duration::from_secs(1)), Duration::from_secs(1));
+    }
+
+    /// Hands out `pending` jobs, never more than asked for.
+    struct QueueStore {
+        pending: std::sync::Mutex<VecDeque<ClaimedJob>>,
+        limits: std::sync::Mutex<Vec<i64>>,
+    }
+
+    #[async_trait::async_trait]
+    impl JobStore for QueueStore {
+        async fn claim_ready(&self, _worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+            self.limits.lock().unwrap().push(limit);
+            let mut pending = self.pending.lock().unwrap();
+            let n = pending.len().min(limit as usize);
+            Ok(pending.drain(..n).collect())
+        }
+        async fn mark_succeeded(&self, _lease: JobLease) -> Result<()> {
+            Ok(())
+        }
+        async fn mark_failed(
+            &self,
+            _lease: JobLease,
+            _error: &str,
+            _kind: FailureKind,
+        ) -> Result<()> {
+            Ok(())
+        }
+        async fn heartbeat(&self, _lease: JobLease) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_stream_claims_in_batches() {
+        let store = QueueStore {
+            pending: std::sync::Mutex::new(
+                (0..5)
+                    .map(|attempt| ClaimedJob {
+                        id: Uuid::new_v4(),
+                        job_type: "test:command".to_string(),
+                        payload: serde_json::json!({}),
+                        version: 1,
+                        attempt,
+                        lease_token: Uuid::new_v4(),
+                    })
+                    .collect(),
+            ),
+            limits: Default::default(),
+        };
+
+        let attempts: Vec<i32> = store
+            .claim_stream("worker-1", 2)
+            .take(5)
+            .map(|job| job.unwrap().attempt)
+            .collect()
+            .await;
+        assert_eq!(attempts, [0, 1, 2, 3, 4]);
+        // Three claims of two: the next batch waits for the last to drain
+        assert_eq!(*store.limits.lock().unwrap(), [2, 2, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_running_jobs_is_unsupported_by_default() {
+        let store = QueueStore {
+            pending: Default::default(),
+            limits: Default::default(),
+        };
+
+        let error = store.running_jobs("worker-1").await.unwrap_err();
+        assert!(error.to_string().contains("does not support listing running jobs"));
+    }
 }
@@ -15,7 +15,7 @@
 //! ## Architecture
 //!
 //! ```text
-//! Edge (API/WebSocket)
+//! Edge (API/WebSocket, see Edge)
 //!     │
 //!     ▼ emit()
 //! EventBus ──────────────────────────────────────┐
@@ -54,12 +54,13 @@
 //!
 //! - **At-most-once delivery**: Slow receivers may miss events
 //! - **In-memory only**: Events are not persisted by seesaw
-//! - **No replay**: Lagged receivers get errors
+//! - **No replay**: Lagged receivers get errors, unless the bus retains
+//!   recent events ([`EventBus::with_retention`])
 //!
 //! For durability, use:
 //! - Entity status fields for workflow state
 //! - Jobs for durable command execution
-//! - Reapers for crash recovery
+//! - Reapers for crash recovery ([`EngineBuilder::with_reaper`])
 //!
 //! ## Example
 //!
@@ -143,6 +144,32 @@
 //! bus.emit(BakeEvent::Requested { deck_id, recipe_id });
 //! ```
 //!
+//! ## Feature Flags
+//!
+//! All off by default:
+//!
+//! - `audit`: structured audit records for every envelope, written to an
+//!   `AuditSink` (also in release builds)
+//! - `tracing-spans`: `seesaw.event`, `seesaw.decide` and `seesaw.effect`
+//!   tracing spans
+//! - `metrics`: counters, gauges and histograms through the `metrics` facade
+//!   (`seesaw_events_processed_total`, `seesaw_decide_duration_seconds`,
+//!   `seesaw_commands_dispatched_total`, `seesaw_effect_duration_seconds`,
+//!   `seesaw_command_failed_total`, `seesaw_bus_lagged_events_total`,
+//!   `seesaw_bus_queued_events`, `seesaw_inflight_batches`)
+//! - `derive`: `#[derive(SeesawCommand)]`, implementing [`Command`] from
+//!   `#[command(mode = "background", job_type = "email:send", ...)]`
+//!   attributes, `#[derive(CommandVariants)]`, implementing
+//!   [`CommandVariant`] for per-variant effects on enum commands, and
+//!   `#[derive(EventSelector)]` for machines subscribing to several event types,
+//!   and `#[derive(FromDeps)]`, implementing [`FromDeps`] for every field of
+//!   a deps struct
+//! - `msgpack`: [`MessagePackCodec`], a MessagePack [`PayloadCodec`] for job payloads
+//! - `cbor`: [`CborCodec`], a CBOR [`PayloadCodec`] for job payloads
+//! - `schemas`: `EngineBuilder::with_event_schema` and `with_command_schema`,
+//!   collecting the JSON Schemas of events and commands into
+//!   [`EngineHandle::schemas`] (see [`SchemaRegistry`])
+//!
 //! ## What This Is Not
 //!
 //! Seesaw is **not**:
@@ -155,18 +182,51 @@
 //! > A deterministic, event-driven coordination layer where machines decide,
 //! > effects execute, and transactions define authority.
 
+// Lets `::seesaw_core` paths from derive macros resolve inside this crate
+extern crate self as seesaw_core;
+
 // Core modules
 mod bus;
+mod child;
+mod circuit_breaker;
+mod codec;
 mod command_macro;
+mod control;
 mod core;
+mod cost;
+mod dead_letter;
 mod dispatch;
+mod edge;
 mod effect_impl;
 mod engine;
 mod error;
+mod health;
 mod machine;
+mod machine_middleware;
+mod metadata;
+mod metrics;
+mod middleware;
+mod offload;
+mod process;
+mod rate_limit;
+mod reaper;
+mod redact;
+mod replay;
 mod request;
+mod retry;
+mod router;
+mod routing;
 mod runtime;
+mod schema;
+mod shard;
+mod snapshot;
+mod spans;
+mod supervisor;
 mod tap;
+mod timer;
+mod trace;
+mod wiring;
+mod worker;
 
 // Job interfaces (policy-light)
 pub mod job;
@@ -175,6 +235,14 @@ pub mod job;
 #[cfg(debug_assertions)]
 pub mod audit;
 
+// Structured audit trail for release builds
+#[cfg(feature = "audit")]
+mod audit_sink;
+
+// Traced outbound HTTP from effects
+#[cfg(feature = "http")]
+mod http;
+
 // Testing utilities are in the separate seesaw-testing crate
 
 // Code smell tests (test-only)
@@ -195,37 +263,162 @@ pub use crate::core::{
     ExecutionMode, JobSpec, MatchChain, SerializableCommand,
 };
 
+// Re-export the derive macros
+#[cfg(feature = "derive")]
+pub use seesaw_macros::{CommandVariants, EventSelector, FromDeps, SeesawCommand};
+
 // Re-export request helpers (syntactic sugar over event bus)
-pub use request::{dispatch_request, dispatch_request_timeout, DEFAULT_REQUEST_TIMEOUT};
+pub use request::{
+    await_event, dispatch_request, dispatch_request_timeout, DEFAULT_REQUEST_TIMEOUT,
+};
+
+// Re-export edge types (WebSocket clients)
+pub use edge::{Edge, EdgeConnection, EdgeFrame, EDGE_ERROR_TYPE};
 
 // Re-export error types
 pub use crate::error::{
-    BatchOutcome, Categorizable, CommandFailed, SafeErrorCategory, SeesawError,
+    BatchOutcome, Categorizable, CommandExpired, CommandFailed, ErrorCategories, SafeErrorCategory,
+    SeesawError,
 };
 
+// Re-export envelope metadata (request-scoped context)
+pub use metadata::Metadata;
+
 // Re-export machine types
-pub use machine::Machine;
+pub use machine::{EventSelector, Machine};
+
+// Re-export process manager types (sagas)
+pub use process::{ProcessManager, ProcessStep, ProcessTimedOut};
+
+// Re-export machine middleware types (decision tracing)
+pub use machine_middleware::{
+    Decision, DecisionContext, DecisionLogger, DecisionOutcome, MachineMiddleware,
+};
 
 // Re-export effect types
-pub use effect_impl::{Effect, EffectContext, ToolContext};
+pub use effect_impl::{Effect, EffectContext, EffectFn, FnEffect, FromDeps, ToolContext};
+
+// Re-export per-variant routing for enum commands
+pub use routing::CommandVariant;
+
+// Re-export effect middleware types
+pub use middleware::{EffectCall, EffectMiddleware, EffectOutput, Next};
+
+// Re-export retry types (inline effect durability)
+pub use retry::{RetryPolicy, RetryingEffect};
+
+// Re-export circuit breaker types
+pub use circuit_breaker::{
+    CircuitBreakerClosed, CircuitBreakerOpened, CircuitBreakerPolicy, CircuitState,
+};
+
+// Re-export rate limit types
+pub use rate_limit::{RateLimitPolicy, ThrottleMode, Throttled};
 
 // Re-export tap types (event observation)
-pub use tap::{EventTap, TapContext};
+pub use tap::{
+    EventTap, FilteredTap, SampledTap, TapContext, TapLagPolicy, TapPolicy, ThrottledTap,
+    DEFAULT_TAP_CAPACITY,
+};
+
+// Re-export redaction types (secrets in error messages)
+pub use redact::{Redactor, REDACTED};
 
 // Re-export bus types
-pub use bus::EventBus;
+pub use bus::{BackpressurePolicy, BusStats, EventBus, RecoveringReceiver};
+
+// Re-export dead-letter types (undelivered events)
+pub use dead_letter::{DeadLetter, DeadLetterReason, DeadLetterSink};
 
 // Re-export dispatcher types
 pub use dispatch::{Dispatcher, JobQueue, NoOpJobQueue};
 
+// Re-export payload codec types (job payload wire formats)
+#[cfg(feature = "cbor")]
+pub use codec::CborCodec;
+#[cfg(feature = "msgpack")]
+pub use codec::MessagePackCodec;
+pub use codec::{EncodedPayload, JsonCodec, PayloadCodec, PayloadCodecs};
+
+// Re-export payload offloading types (large payloads in external storage)
+pub use offload::{
+    OffloadRef, OffloadingJobQueue, OffloadingJobStore, PayloadOffload, DEFAULT_OFFLOAD_THRESHOLD,
+};
+
 // Re-export job types (policy-light interfaces)
-pub use job::{ClaimedJob, CommandRegistry, DeserializationError, FailureKind, JobStore};
+pub use job::{
+    ClaimedJob, CommandRegistry, DeadLetterJob, DeserializationError, FailureKind, JobAdmin,
+    JobLease, JobStore, JobTypeStats, LeaseLost, LookaheadClaim, ParkedJob, QueueStats,
+    UpcomingJob, Upcaster, VersionedPayload,
+};
+
+// Re-export cost accounting types (spend per command type and workflow)
+pub use cost::{CostLedger, CostRecord, CostSink, CostTotal, DEFAULT_COST_CAPACITY};
+
+// Re-export traced HTTP types (outbound calls from effects)
+#[cfg(feature = "http")]
+pub use http::{HttpClient, HttpRequest, CORRELATION_ID_HEADER};
+
+// Re-export trace types
+pub use trace::{Trace, TraceNode, TraceNodeKind, TraceRecorder, DEFAULT_TRACE_CAPACITY};
+
+// Re-export reaper types
+pub use reaper::ReaperContext;
+
+// Re-export job worker
+pub use worker::{
+    JobClaimed, JobDeadLettered, JobHeartbeatMissed, JobRecovered, JobWorker, RecoveredJobResolved,
+    RecoveryAction, ResolveRecoveredJob, ResolveRecoveredJobEffect, DEFAULT_HEARTBEAT_INTERVAL,
+};
 
 // Re-export runtime types
-pub use runtime::{Runtime, RuntimeBuilder};
+pub use runtime::{LoopDetected, Runtime, RuntimeBuilder};
+
+// Re-export replay types (rebuilding machine state)
+pub use replay::{EventLog, LoggedEvent, MemoryEventLog, ReplayReport};
+
+// Re-export sharded dispatch configuration
+pub use shard::Sharding;
+
+// Re-export event catalog types (JSON Schemas of events and commands)
+pub use schema::{SchemaKind, SchemaRegistry, TypeSchema};
+
+// Re-export snapshot types (persisting machine state)
+pub use snapshot::{MemorySnapshotStore, Snapshot, SnapshotMachine, SnapshotStore};
+
+// Re-export supervision types (misbehaving machines)
+pub use supervisor::{MachineSupervised, SupervisionReason, SupervisorAction, SupervisorPolicy};
+
+// Re-export audit trail types (release-build forensics)
+#[cfg(feature = "audit")]
+pub use audit_sink::{
+    AuditDecision, AuditEffect, AuditExecution, AuditRecord, AuditSink, JsonLinesAuditSink,
+    MemoryAuditSink,
+};
+
+// Re-export health types (engine introspection)
+pub use health::{EffectHealth, Health, TapHealth};
+
+// Re-export wiring validation types (startup checks)
+pub use wiring::{WiringIssue, WiringReport};
 
 // Re-export engine types (primary entry point)
-pub use engine::{Engine, EngineBuilder, EngineHandle, InflightBatch, InflightTracker};
+pub use engine::{
+    Engine, EngineBuilder, EngineHandle, InflightBatch, InflightBatchInfo, InflightTracker,
+};
+
+// Re-export event routing types (runtime-configurable destinations)
+pub use router::{Destination, RoutePublisher, Router};
+
+// Re-export sub-engine types (one engine per bounded context)
+pub use child::{ChildEngine, ChildHealth};
 
 // Re-export commonly used external types
 pub use async_trait::async_trait;
+pub use bytes::Bytes;
+
+// Dependencies used by generated code; not public API
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+}
@@ -12,10 +12,18 @@
 
 use std::any::{Any, TypeId};
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::Instant;
 
+use bytes::Bytes;
 use tracing::error;
 
-use crate::core::{AnyCommand, Command, Event};
+use crate::bus::EventBus;
+use crate::core::{AnyCommand, Command, CorrelationId, Event};
+use crate::machine_middleware::{Decision, DecisionContext, DecisionOutcome, MachineMiddleware};
+use crate::process::{ProcessAdapter, ProcessManager, ProcessTimedOut};
+use crate::snapshot::{SnapshotAdapter, SnapshotMachine};
+use crate::supervisor::{MachineSupervised, SupervisionReason, Supervisor, SupervisorPolicy};
 
 /// A state machine that interprets events and decides on commands.
 ///
@@ -89,10 +97,81 @@ pub trait Machine: Send + Sync + 'static {
     fn decide(&mut self, event: &Self::Event) -> Option<Self::Command>;
 }
 
+/// An enum over several event types, letting one machine subscribe to all
+/// of them.
+///
+/// A machine's `Event` is normally a single type, which pushes every event
+/// it reacts to into one enum. Use a selector as the `Event` instead and
+/// register the machine with `with_selector_machine`: each published event
+/// of a selected type is wrapped in the selector and passed to `decide`.
+/// Bounded contexts keep their own event enums.
+///
+/// Usually derived with `#[derive(EventSelector)]` (`derive` feature), on an
+/// enum whose variants each wrap one event type.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Debug, Clone, EventSelector)]
+/// enum FulfillmentInput {
+///     Order(OrderEvent),
+///     Payment(PaymentEvent),
+/// }
+///
+/// impl Machine for FulfillmentMachine {
+///     type Event = FulfillmentInput;
+///     type Command = FulfillmentCommand;
+///
+///     fn decide(&mut self, event: &FulfillmentInput) -> Option<FulfillmentCommand> {
+///         match event {
+///             FulfillmentInput::Order(OrderEvent::Placed { id }) => { ... }
+///             FulfillmentInput::Payment(PaymentEvent::Captured { order_id }) => { ... }
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let engine = EngineBuilder::new(deps)
+///     .with_selector_machine(FulfillmentMachine::default())
+///     .build();
+/// ```
+pub trait EventSelector: Event + Sized {
+    /// The event types this selector wraps.
+    fn event_types() -> Vec<TypeId>;
+
+    /// Wrap `event` if its type is one of [`event_types`](Self::event_types).
+    fn select(event: &dyn Any) -> Option<Self>;
+}
+
 /// Type-erased machine trait for internal use.
 pub(crate) trait AnyMachine: Send + Sync {
     /// Process a type-erased event and optionally return a type-erased command.
     fn decide_any(&mut self, event: &dyn Any) -> Option<Box<dyn AnyCommand>>;
+
+    /// Process a type-erased event whose correlation ID matters to the
+    /// machine. Defaults to [`decide_any`](Self::decide_any).
+    fn decide_correlated_any(
+        &mut self,
+        event: &dyn Any,
+        _cid: CorrelationId,
+    ) -> Option<Box<dyn AnyCommand>> {
+        self.decide_any(event)
+    }
+
+    /// Render the machine state for debugging, if the machine supports it.
+    fn debug_state(&self) -> Option<String> {
+        None
+    }
+
+    /// Serialize the machine state, if the machine supports snapshots.
+    fn snapshot(&self) -> Option<Bytes> {
+        None
+    }
+
+    /// Restore the machine state from a snapshot.
+    fn restore(&mut self, _snapshot: Bytes) -> anyhow::Result<()> {
+        anyhow::bail!("machine does not support snapshots")
+    }
 }
 
 impl<M: Machine> AnyMachine for M {
@@ -103,6 +182,50 @@ impl<M: Machine> AnyMachine for M {
     }
 }
 
+/// Wrapper that feeds a selector machine every event its selector wraps.
+struct SelectorMachine<M>(M);
+
+impl<M> AnyMachine for SelectorMachine<M>
+where
+    M: Machine,
+    M::Event: EventSelector,
+{
+    fn decide_any(&mut self, event: &dyn Any) -> Option<Box<dyn AnyCommand>> {
+        // The selector itself may also be published directly
+        if let Some(event) = event.downcast_ref::<M::Event>() {
+            return self.0.decide_any(event);
+        }
+        let event = M::Event::select(event)?;
+        let cmd = self.0.decide(&event)?;
+        Some(Box::new(cmd))
+    }
+}
+
+/// Wrapper that exposes a `Debug` machine's state to machine middleware.
+struct DebugMachine<M>(M);
+
+impl<M: Machine + std::fmt::Debug> AnyMachine for DebugMachine<M> {
+    fn decide_any(&mut self, event: &dyn Any) -> Option<Box<dyn AnyCommand>> {
+        self.0.decide_any(event)
+    }
+
+    fn debug_state(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+/// Type-erased event predicate registered with `with_machine_filter`.
+pub(crate) type EventFilter = Arc<dyn Fn(&dyn Any) -> bool + Send + Sync>;
+
+/// Wrap a typed predicate; events of other types pass.
+pub(crate) fn event_filter<E, F>(filter: F) -> EventFilter
+where
+    E: Event,
+    F: Fn(&E) -> bool + Send + Sync + 'static,
+{
+    Arc::new(move |event| event.downcast_ref::<E>().is_none_or(&filter))
+}
+
 /// Type-erased wrapper for machines.
 ///
 /// `MachineRunner` enables a runtime to hold multiple machines with different
@@ -110,8 +233,22 @@ impl<M: Machine> AnyMachine for M {
 pub struct MachineRunner {
     inner: Box<dyn AnyMachine>,
     event_type: TypeId,
+    /// Further event types handled through an [`EventSelector`].
+    selected_types: Vec<TypeId>,
+    /// TypeId of the machine itself, for per-machine-type settings.
+    machine_type: TypeId,
+    /// Decide order among machines; higher decides first.
+    priority: i32,
     /// Human-readable name for debugging/auditing.
     name: &'static str,
+    event_type_name: &'static str,
+    command_type_name: &'static str,
+    /// Store key for snapshot machines; `None` if snapshots are unsupported.
+    snapshot_key: Option<&'static str>,
+    /// Supervision state for supervised machines.
+    supervisor: Option<Supervisor>,
+    /// Predicates an event of the keyed type must pass to reach the machine.
+    filters: Vec<(TypeId, EventFilter)>,
 }
 
 impl MachineRunner {
@@ -121,19 +258,122 @@ impl MachineRunner {
     pub fn new<M: Machine>(machine: M) -> Self {
         Self {
             event_type: TypeId::of::<M::Event>(),
+            selected_types: Vec::new(),
+            machine_type: TypeId::of::<M>(),
+            priority: 0,
             inner: Box::new(machine),
             name: std::any::type_name::<M>(),
+            event_type_name: std::any::type_name::<M::Event>(),
+            command_type_name: std::any::type_name::<M::Command>(),
+            snapshot_key: None,
+            supervisor: None,
+            filters: Vec::new(),
         }
     }
 
-    /// Create a new machine runner with a custom name.
+    /// Create a machine runner whose state is visible to machine middleware.
     ///
-    /// Useful when you have multiple instances of the same machine type.
-    pub fn with_name<M: Machine>(machine: M, name: &'static str) -> Self {
+    /// In debug builds, [`Decision::state`] carries the machine's `{:?}`
+    /// output after each decision.
+    pub fn new_debug<M: Machine + std::fmt::Debug>(machine: M) -> Self {
         Self {
             event_type: TypeId::of::<M::Event>(),
-            inner: Box::new(machine),
-            name,
+            selected_types: Vec::new(),
+            machine_type: TypeId::of::<M>(),
+            priority: 0,
+            inner: Box::new(DebugMachine(machine)),
+            name: std::any::type_name::<M>(),
+            event_type_name: std::any::type_name::<M::Event>(),
+            command_type_name: std::any::type_name::<M::Command>(),
+            snapshot_key: None,
+            supervisor: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Create a machine runner for a machine whose `Event` is an
+    /// [`EventSelector`], handling every event type the selector wraps.
+    pub fn new_selector<M>(machine: M) -> Self
+    where
+        M: Machine,
+        M::Event: EventSelector,
+    {
+        Self {
+            event_type: TypeId::of::<M::Event>(),
+            selected_types: M::Event::event_types(),
+            machine_type: TypeId::of::<M>(),
+            priority: 0,
+            inner: Box::new(SelectorMachine(machine)),
+            name: std::any::type_name::<M>(),
+            event_type_name: std::any::type_name::<M::Event>(),
+            command_type_name: std::any::type_name::<M::Command>(),
+            snapshot_key: None,
+            supervisor: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Create a machine runner whose state is saved and restored by the runtime.
+    pub fn new_snapshot<M: SnapshotMachine>(machine: M) -> Self {
+        Self {
+            event_type: TypeId::of::<M::Event>(),
+            selected_types: Vec::new(),
+            machine_type: TypeId::of::<M>(),
+            priority: 0,
+            snapshot_key: Some(machine.snapshot_key()),
+            inner: Box::new(SnapshotAdapter(machine)),
+            name: std::any::type_name::<M>(),
+            event_type_name: std::any::type_name::<M::Event>(),
+            command_type_name: std::any::type_name::<M::Command>(),
+            supervisor: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Create a machine runner for a process manager, keeping one instance
+    /// per correlation ID.
+    ///
+    /// Step timeouts emit [`ProcessTimedOut`] on `bus`.
+    pub fn new_process<P: ProcessManager>(manager: P, bus: EventBus) -> Self {
+        Self {
+            event_type: TypeId::of::<P::Event>(),
+            selected_types: vec![TypeId::of::<ProcessTimedOut>()],
+            machine_type: TypeId::of::<P>(),
+            priority: 0,
+            inner: Box::new(ProcessAdapter::new(manager, bus)),
+            name: std::any::type_name::<P>(),
+            event_type_name: std::any::type_name::<P::Event>(),
+            command_type_name: std::any::type_name::<P::Command>(),
+            snapshot_key: None,
+            supervisor: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Create a machine runner that the runtime restarts or stops when it
+    /// crosses the policy's limits.
+    ///
+    /// The first instance is built by calling `factory`.
+    pub fn new_supervised<M, F>(factory: F, policy: SupervisorPolicy) -> Self
+    where
+        M: Machine,
+        F: Fn() -> M + Send + Sync + 'static,
+    {
+        Self {
+            event_type: TypeId::of::<M::Event>(),
+            selected_types: Vec::new(),
+            machine_type: TypeId::of::<M>(),
+            priority: 0,
+            inner: Box::new(factory()),
+            name: std::any::type_name::<M>(),
+            event_type_name: std::any::type_name::<M::Event>(),
+            command_type_name: std::any::type_name::<M::Command>(),
+            snapshot_key: None,
+            supervisor: Some(Supervisor::new(
+                policy,
+                Box::new(move || Box::new(factory())),
+            )),
+            filters: Vec::new(),
         }
     }
 
@@ -154,10 +394,27 @@ impl MachineRunner {
     /// from crashing the entire runtime. The machine's state may be inconsistent
     /// after a panic.
     pub fn decide(&mut self, event: &dyn Any) -> Result<Option<Box<dyn AnyCommand>>, String> {
+        self.decide_correlated(event, CorrelationId::NONE)
+    }
+
+    /// [`decide`](Self::decide), passing the event's correlation ID to
+    /// machines that key state by it.
+    fn decide_correlated(
+        &mut self,
+        event: &dyn Any,
+        correlation_id: CorrelationId,
+    ) -> Result<Option<Box<dyn AnyCommand>>, String> {
+        // Filtered out before the machine (or its selector) sees the event
+        if !self.passes_filters(event) {
+            return Ok(None);
+        }
+
         // Wrap in catch_unwind to prevent machine panics from crashing the runtime.
         // AssertUnwindSafe is needed because &mut self is not UnwindSafe by default.
         // This is safe because we don't access the machine after a panic.
-        let result = catch_unwind(AssertUnwindSafe(|| self.inner.decide_any(event)));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            self.inner.decide_correlated_any(event, correlation_id)
+        }));
 
         match result {
             Ok(cmd) => Ok(cmd),
@@ -181,22 +438,162 @@ impl MachineRunner {
         }
     }
 
+    /// Decide on a command, running machine middleware around the call.
+    ///
+    /// Middleware only runs when this machine handles the event's type.
+    pub(crate) fn decide_with_middleware(
+        &mut self,
+        event: &dyn Any,
+        correlation_id: CorrelationId,
+        middleware: &[Arc<dyn MachineMiddleware>],
+    ) -> Result<Option<Box<dyn AnyCommand>>, String> {
+        if middleware.is_empty() || !self.accepts(event) {
+            return self.decide_correlated(event, correlation_id);
+        }
+
+        let ctx = DecisionContext {
+            machine: self.name,
+            event,
+            event_type: self.event_type_name,
+            command_type: self.command_type_name,
+            correlation_id,
+        };
+        for m in middleware {
+            m.before_decide(&ctx);
+        }
+
+        let start = Instant::now();
+        let result = self.decide_correlated(event, correlation_id);
+        let elapsed = start.elapsed();
+
+        #[cfg(debug_assertions)]
+        let state = self.inner.debug_state();
+        #[cfg(not(debug_assertions))]
+        let state: Option<String> = None;
+
+        let decision = Decision {
+            outcome: match &result {
+                Ok(Some(cmd)) => DecisionOutcome::Command(cmd.as_ref()),
+                Ok(None) => DecisionOutcome::NoCommand,
+                Err(msg) => DecisionOutcome::Panicked(msg),
+            },
+            elapsed,
+            state: state.as_deref(),
+        };
+        for m in middleware.iter().rev() {
+            m.after_decide(&ctx, &decision);
+        }
+
+        result
+    }
+
+    /// Whether a supervisor stopped this machine.
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.supervisor.as_ref().is_some_and(Supervisor::is_stopped)
+    }
+
+    /// Count a decision against the supervision policy, if any.
+    ///
+    /// When a limit is crossed, the policy's action is applied and the
+    /// diagnostic event is returned. A command decided by a runaway machine
+    /// is discarded from `result`.
+    pub(crate) fn supervise(
+        &mut self,
+        result: &mut Result<Option<Box<dyn AnyCommand>>, String>,
+    ) -> Option<MachineSupervised> {
+        let supervisor = self.supervisor.as_mut()?;
+        let reason = match result {
+            Ok(cmd) => supervisor.observe(None, cmd.is_some())?,
+            Err(msg) => supervisor.observe(Some(msg), false)?,
+        };
+
+        if matches!(reason, SupervisionReason::Runaway { .. }) {
+            *result = Ok(None);
+        }
+        if let Some(fresh) = supervisor.act() {
+            self.inner = fresh;
+        }
+
+        Some(MachineSupervised {
+            machine: self.name,
+            reason,
+            action: supervisor.action(),
+        })
+    }
+
     /// Check if this machine handles the given event type.
     ///
-    /// Returns true if the event's TypeId matches this machine's event type.
+    /// Returns true if the event's TypeId matches this machine's event type,
+    /// or one of the types its [`EventSelector`] wraps.
     pub fn handles_event(&self, event: &dyn Any) -> bool {
-        (*event).type_id() == self.event_type
+        let type_id = (*event).type_id();
+        type_id == self.event_type || self.selected_types.contains(&type_id)
     }
 
-    /// Returns the TypeId of events this machine handles.
-    pub fn event_type(&self) -> TypeId {
-        self.event_type
+    /// Whether the machine handles the event and its filters let it through.
+    pub(crate) fn accepts(&self, event: &dyn Any) -> bool {
+        self.handles_event(event) && self.passes_filters(event)
+    }
+
+    fn passes_filters(&self, event: &dyn Any) -> bool {
+        let type_id = (*event).type_id();
+        self.filters
+            .iter()
+            .all(|(filtered, filter)| *filtered != type_id || filter(event))
+    }
+
+    /// Only pass events of type `event_type` on to the machine when `filter`
+    /// returns true, replacing any filter set for that type.
+    pub(crate) fn set_filter(&mut self, event_type: TypeId, filter: EventFilter) {
+        self.filters.retain(|(filtered, _)| *filtered != event_type);
+        self.filters.push((event_type, filter));
+    }
+
+    /// Returns the TypeId of the wrapped machine.
+    pub(crate) fn machine_type(&self) -> TypeId {
+        self.machine_type
+    }
+
+    /// Decide priority; machines with higher priority decide first.
+    pub(crate) fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Set the decide priority.
+    pub(crate) fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
     }
 
     /// Returns the machine's name for debugging/auditing.
     pub fn name(&self) -> &'static str {
         self.name
     }
+
+    /// Type name of the events this machine handles.
+    pub(crate) fn event_type_name(&self) -> &'static str {
+        self.event_type_name
+    }
+
+    /// Type name of the commands this machine decides.
+    pub(crate) fn command_type_name(&self) -> &'static str {
+        self.command_type_name
+    }
+
+    /// Store key of a snapshot machine; `None` if snapshots are unsupported.
+    pub(crate) fn snapshot_key(&self) -> Option<&'static str> {
+        self.snapshot_key
+    }
+
+    /// Snapshot the machine state, returning it with its store key.
+    pub(crate) fn snapshot(&self) -> Option<(&'static str, Bytes)> {
+        let key = self.snapshot_key?;
+        self.inner.snapshot().map(|bytes| (key, bytes))
+    }
+
+    /// Restore the machine state from a snapshot.
+    pub(crate) fn restore(&mut self, snapshot: Bytes) -> anyhow::Result<()> {
+        self.inner.restore(snapshot)
+    }
 }
 
 #[cfg(test)]
@@ -353,7 +750,11 @@ mod tests {
             Err(e) => e,
             Ok(_) => panic!("Expected error"),
         };
-        assert!(err.contains("panicked"), "Error should mention panic: {}", err);
+        assert!(
+            err.contains("panicked"),
+            "Error should mention panic: {}",
+            err
+        );
         assert!(
             err.contains("intentional panic"),
             "Error should contain panic message: {}",
@@ -366,7 +767,108 @@ mod tests {
         let machine = CounterMachine::new();
         let runner = MachineRunner::new(machine);
 
-        assert_eq!(runner.event_type(), TypeId::of::<CounterEvent>());
+        assert_eq!(runner.event_type, TypeId::of::<CounterEvent>());
+    }
+
+    #[derive(Debug, Clone)]
+    enum OrderEvent {
+        Placed { id: u32 },
+    }
+
+    #[derive(Debug, Clone)]
+    enum PaymentEvent {
+        Captured { order_id: u32 },
+    }
+
+    #[derive(Debug, Clone, seesaw_macros::EventSelector)]
+    enum FulfillmentInput {
+        Order(OrderEvent),
+        Payment(PaymentEvent),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Ship {
+        order_id: u32,
+    }
+    impl Command for Ship {}
+
+    /// Ships an order once it is both placed and paid, in either order.
+    #[derive(Default)]
+    struct FulfillmentMachine {
+        placed: HashSet<u32>,
+        paid: HashSet<u32>,
+    }
+
+    impl Machine for FulfillmentMachine {
+        type Event = FulfillmentInput;
+        type Command = Ship;
+
+        fn decide(&mut self, event: &FulfillmentInput) -> Option<Ship> {
+            let order_id = match event {
+                FulfillmentInput::Order(OrderEvent::Placed { id }) => {
+                    self.placed.insert(*id);
+                    *id
+                }
+                FulfillmentInput::Payment(PaymentEvent::Captured { order_id }) => {
+                    self.paid.insert(*order_id);
+                    *order_id
+                }
+            };
+            (self.placed.contains(&order_id) && self.paid.contains(&order_id))
+                .then_some(Ship { order_id })
+        }
+    }
+
+    fn shipped(result: Result<Option<Box<dyn AnyCommand>>, String>) -> Option<Ship> {
+        result
+            .unwrap()
+            .map(|cmd| cmd.as_any().downcast_ref::<Ship>().unwrap().clone())
+    }
+
+    #[test]
+    fn test_selector_machine_decides_on_every_selected_event_type() {
+        let mut runner = MachineRunner::new_selector(FulfillmentMachine::default());
+
+        let paid = PaymentEvent::Captured { order_id: 7 };
+        assert!(runner.handles_event(&paid));
+        assert_eq!(shipped(runner.decide(&paid)), None);
+
+        let placed = OrderEvent::Placed { id: 7 };
+        assert!(runner.handles_event(&placed));
+        assert_eq!(shipped(runner.decide(&placed)), Some(Ship { order_id: 7 }));
+
+        // The selector itself can be published directly
+        let input = FulfillmentInput::from(OrderEvent::Placed { id: 8 });
+        assert!(runner.handles_event(&input));
+        assert_eq!(shipped(runner.decide(&input)), None);
+
+        assert!(!runner.handles_event(&CounterEvent::Reset));
+        assert_eq!(shipped(runner.decide(&CounterEvent::Reset)), None);
+    }
+
+    #[test]
+    fn test_filter_rejects_events_before_the_selector_wraps_them() {
+        let mut runner = MachineRunner::new_selector(FulfillmentMachine::default());
+        runner.set_filter(
+            TypeId::of::<OrderEvent>(),
+            event_filter(|e: &OrderEvent| matches!(e, OrderEvent::Placed { id } if *id < 100)),
+        );
+
+        let paid = PaymentEvent::Captured { order_id: 200 };
+        assert!(runner.accepts(&paid));
+        assert_eq!(shipped(runner.decide(&paid)), None);
+
+        // Filtered out: never reaches the machine, so 200 is not marked placed
+        let placed = OrderEvent::Placed { id: 200 };
+        assert!(runner.handles_event(&placed));
+        assert!(!runner.accepts(&placed));
+        assert_eq!(shipped(runner.decide(&placed)), None);
+
+        let paid = PaymentEvent::Captured { order_id: 7 };
+        assert_eq!(shipped(runner.decide(&paid)), None);
+        let placed = OrderEvent::Placed { id: 7 };
+        assert!(runner.accepts(&placed));
+        assert_eq!(shipped(runner.decide(&placed)), Some(Ship { order_id: 7 }));
     }
 
     // Test machine that sometimes returns None
@@ -465,7 +967,7 @@ mod tests {
         type Event = SharedEvent;
         type Command = MetricCommand;
 
-        fn decide(&mut self, event: &SharedEvent) -> Option<MetricCommand> {
+        fn decide(&mut self, _event: &SharedEvent) -> Option<MetricCommand> {
             self.total += 1.0;
             Some(MetricCommand {
                 name: "event_count".to_string(),
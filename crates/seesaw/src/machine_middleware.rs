@@ -0,0 +1,359 @@
+//! Machine middleware - hooks around every `Machine::decide` call.
+//!
+//! Machines are pure and synchronous, so these hooks are too: they observe
+//! decisions, they never change them. Use them to log every
+//! (event, machine, decision) tuple, measure decision latency, or inspect
+//! machine state while debugging.
+//!
+//! Hooks run only for machines that handle the event's type. `before_decide`
+//! runs in registration order and `after_decide` in reverse, mirroring the
+//! nesting of effect middleware.
+//!
+//! # State Snapshots
+//!
+//! Machines registered with `with_debug_machine` (which requires `Debug`)
+//! expose a `{:?}` rendering of their state after each decision through
+//! [`Decision::state`]. Snapshots are only captured in debug builds; release
+//! builds always report `None`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! struct SlowDecisionAlarm;
+//!
+//! impl MachineMiddleware for SlowDecisionAlarm {
+//!     fn after_decide(&self, ctx: &DecisionContext<'_>, decision: &Decision<'_>) {
+//!         if decision.elapsed() > Duration::from_millis(1) {
+//!             warn!(machine = ctx.machine(), elapsed = ?decision.elapsed(), "slow decide()");
+//!         }
+//!     }
+//! }
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine_middleware(DecisionLogger)
+//!     .with_machine_middleware(SlowDecisionAlarm)
+//!     .with_debug_machine(OrderMachine::default())
+//!     .build();
+//! ```
+
+use std::any::Any;
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::core::{AnyCommand, CorrelationId};
+
+// =============================================================================
+// Decision Context
+// =============================================================================
+
+/// The machine and event a decision is being made for.
+pub struct DecisionContext<'a> {
+    pub(crate) machine: &'static str,
+    pub(crate) event: &'a dyn Any,
+    pub(crate) event_type: &'static str,
+    pub(crate) command_type: &'static str,
+    pub(crate) correlation_id: CorrelationId,
+}
+
+impl<'a> DecisionContext<'a> {
+    /// Name of the machine deciding.
+    pub fn machine(&self) -> &'static str {
+        self.machine
+    }
+
+    /// The event being decided on, for downcasting.
+    pub fn event(&self) -> &'a dyn Any {
+        self.event
+    }
+
+    /// Type name of the machine's event type.
+    pub fn event_type(&self) -> &'static str {
+        self.event_type
+    }
+
+    /// Type name of the machine's command type.
+    pub fn command_type(&self) -> &'static str {
+        self.command_type
+    }
+
+    /// Correlation ID of the event (NONE if uncorrelated).
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation_id
+    }
+}
+
+impl std::fmt::Debug for DecisionContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecisionContext")
+            .field("machine", &self.machine)
+            .field("event_type", &self.event_type)
+            .field("correlation_id", &self.correlation_id)
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Decision
+// =============================================================================
+
+/// What a machine decided.
+pub enum DecisionOutcome<'a> {
+    /// The machine emitted a command.
+    Command(&'a dyn AnyCommand),
+    /// The machine observed the event without emitting a command.
+    NoCommand,
+    /// `decide` panicked; the message is attached.
+    Panicked(&'a str),
+}
+
+impl std::fmt::Debug for DecisionOutcome<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecisionOutcome::Command(_) => f.write_str("Command"),
+            DecisionOutcome::NoCommand => f.write_str("NoCommand"),
+            DecisionOutcome::Panicked(msg) => f.debug_tuple("Panicked").field(msg).finish(),
+        }
+    }
+}
+
+/// The result of one `decide` call, as seen by machine middleware.
+#[derive(Debug)]
+pub struct Decision<'a> {
+    pub(crate) outcome: DecisionOutcome<'a>,
+    pub(crate) elapsed: Duration,
+    pub(crate) state: Option<&'a str>,
+}
+
+impl<'a> Decision<'a> {
+    /// What the machine decided.
+    pub fn outcome(&self) -> &DecisionOutcome<'a> {
+        &self.outcome
+    }
+
+    /// The command emitted, if any.
+    pub fn command(&self) -> Option<&'a dyn AnyCommand> {
+        match self.outcome {
+            DecisionOutcome::Command(cmd) => Some(cmd),
+            _ => None,
+        }
+    }
+
+    /// How long `decide` took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// `{:?}` of the machine state after the decision.
+    ///
+    /// Only available for machines registered with `with_debug_machine`,
+    /// and only in debug builds.
+    pub fn state(&self) -> Option<&'a str> {
+        self.state
+    }
+}
+
+// =============================================================================
+// Middleware Trait
+// =============================================================================
+
+/// Hooks that run around every `Machine::decide` call.
+///
+/// Both hooks default to no-ops. They must be cheap and must not block:
+/// they run inline in the runtime loop, once per machine per event.
+pub trait MachineMiddleware: Send + Sync + 'static {
+    /// Called before `decide`.
+    fn before_decide(&self, _ctx: &DecisionContext<'_>) {}
+
+    /// Called after `decide` returns (or panics).
+    fn after_decide(&self, _ctx: &DecisionContext<'_>, _decision: &Decision<'_>) {}
+}
+
+/// Logs every decision at `debug` level via `tracing`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecisionLogger;
+
+impl MachineMiddleware for DecisionLogger {
+    fn after_decide(&self, ctx: &DecisionContext<'_>, decision: &Decision<'_>) {
+        debug!(
+            machine = ctx.machine(),
+            event_type = ctx.event_type(),
+            cid = %ctx.correlation_id(),
+            decision = ?decision.outcome(),
+            command_type = decision.command().map(|_| ctx.command_type()),
+            elapsed_us = decision.elapsed().as_micros() as u64,
+            state = decision.state(),
+            "machine decided"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Command;
+    use crate::machine::{Machine, MachineRunner};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone)]
+    enum CounterEvent {
+        Increment,
+        Ignore,
+        Explode,
+    }
+
+    #[derive(Debug, Clone)]
+    struct ShowCount(u32);
+    impl Command for ShowCount {}
+
+    #[derive(Debug, Default)]
+    struct CounterMachine {
+        count: u32,
+    }
+
+    impl Machine for CounterMachine {
+        type Event = CounterEvent;
+        type Command = ShowCount;
+
+        fn decide(&mut self, event: &CounterEvent) -> Option<ShowCount> {
+            match event {
+                CounterEvent::Increment => {
+                    self.count += 1;
+                    Some(ShowCount(self.count))
+                }
+                CounterEvent::Ignore => None,
+                CounterEvent::Explode => panic!("boom"),
+            }
+        }
+    }
+
+    /// Records every hook call as a string.
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MachineMiddleware for Recorder {
+        fn before_decide(&self, ctx: &DecisionContext<'_>) {
+            assert!(ctx.machine().contains("CounterMachine"));
+            assert!(ctx.event().downcast_ref::<CounterEvent>().is_some());
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:before", self.name));
+        }
+
+        fn after_decide(&self, ctx: &DecisionContext<'_>, decision: &Decision<'_>) {
+            assert!(ctx.command_type().contains("ShowCount"));
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:after:{:?}", self.name, decision.outcome()));
+        }
+    }
+
+    fn recorders(log: &Arc<Mutex<Vec<String>>>) -> Vec<Arc<dyn MachineMiddleware>> {
+        vec![
+            Arc::new(Recorder {
+                name: "a",
+                log: log.clone(),
+            }),
+            Arc::new(Recorder {
+                name: "b",
+                log: log.clone(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_hooks_wrap_decide_in_nested_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let middleware = recorders(&log);
+        let mut runner = MachineRunner::new(CounterMachine::default());
+
+        let cmd = runner
+            .decide_with_middleware(&CounterEvent::Increment, CorrelationId::NONE, &middleware)
+            .unwrap()
+            .expect("Increment decides a command");
+        assert_eq!(cmd.as_any().downcast_ref::<ShowCount>().map(|c| c.0), Some(1));
+        runner
+            .decide_with_middleware(&CounterEvent::Ignore, CorrelationId::NONE, &middleware)
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "a:before",
+                "b:before",
+                "b:after:Command",
+                "a:after:Command",
+                "a:before",
+                "b:before",
+                "b:after:NoCommand",
+                "a:after:NoCommand",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hooks_skip_machines_that_do_not_handle_event() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let middleware = recorders(&log);
+        let mut runner = MachineRunner::new(CounterMachine::default());
+
+        let cmd = runner
+            .decide_with_middleware(&"unrelated", CorrelationId::NONE, &middleware)
+            .unwrap();
+
+        assert!(cmd.is_none());
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_panic_is_reported_to_after_hook() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let middleware = recorders(&log);
+        let mut runner = MachineRunner::new(CounterMachine::default());
+
+        let result =
+            runner.decide_with_middleware(&CounterEvent::Explode, CorrelationId::NONE, &middleware);
+
+        assert!(result.is_err());
+        assert!(log.lock().unwrap()[2].starts_with("b:after:Panicked"));
+    }
+
+    #[test]
+    fn test_debug_machine_exposes_state_in_debug_builds() {
+        struct StateCapture(Arc<Mutex<Vec<Option<String>>>>);
+
+        impl MachineMiddleware for StateCapture {
+            fn after_decide(&self, _ctx: &DecisionContext<'_>, decision: &Decision<'_>) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(decision.state().map(str::to_owned));
+            }
+        }
+
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let middleware: Vec<Arc<dyn MachineMiddleware>> =
+            vec![Arc::new(StateCapture(states.clone()))];
+
+        let mut plain = MachineRunner::new(CounterMachine::default());
+        let mut debug = MachineRunner::new_debug(CounterMachine::default());
+        plain
+            .decide_with_middleware(&CounterEvent::Increment, CorrelationId::NONE, &middleware)
+            .unwrap();
+        debug
+            .decide_with_middleware(&CounterEvent::Increment, CorrelationId::NONE, &middleware)
+            .unwrap();
+
+        let states = states.lock().unwrap();
+        assert_eq!(states[0], None);
+        if cfg!(debug_assertions) {
+            assert_eq!(states[1].as_deref(), Some("CounterMachine { count: 1 }"));
+        } else {
+            assert_eq!(states[1], None);
+        }
+    }
+}
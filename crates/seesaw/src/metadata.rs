@@ -0,0 +1,179 @@
+//! Request-scoped metadata carried on event envelopes.
+//!
+//! Who a request is for (tenant, user, locale, deadline) is transport-level
+//! context, like the correlation ID. Rather than copying it into every
+//! command struct, set it once when emitting and read it wherever the work
+//! lands:
+//!
+//! ```ignore
+//! #[derive(Debug, Clone)]
+//! struct TenantId(Uuid);
+//!
+//! let metadata = Metadata::new().with(TenantId(tenant)).with(Locale::from("de-DE"));
+//! handle
+//!     .emit_and_await_with_metadata(OrderEvent::Placed { order_id }, metadata)
+//!     .await?;
+//!
+//! // In any effect downstream, however many hops later
+//! let tenant = ctx.metadata().get::<TenantId>().context("no tenant")?;
+//! ```
+//!
+//! Every event an inline effect returns inherits the metadata of the event
+//! its command was decided from, as do `CommandFailed` and signals. Metadata
+//! is not serialized: background and scheduled jobs, and events sent to
+//! other services, start without it.
+//!
+//! # Deadlines
+//!
+//! A [deadline](Metadata::with_deadline) is the one value the engine reads
+//! itself. Inline commands decided after it passed are skipped (see
+//! [`CommandExpired`](crate::CommandExpired)), and effects can size their
+//! own timeouts from [`EffectContext::remaining`](crate::EffectContext::remaining).
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// A typed bag of values, at most one per type.
+///
+/// Cloning is cheap: the values are shared, and an empty bag allocates
+/// nothing. Wrap plain values in a newtype (`TenantId(Uuid)` rather than
+/// `Uuid`) so unrelated crates do not overwrite each other's entries.
+#[derive(Clone, Default)]
+pub struct Metadata {
+    values: Option<Arc<HashMap<TypeId, Entry>>>,
+}
+
+/// One value, with its type name for `Debug`.
+#[derive(Clone)]
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl Metadata {
+    /// Create an empty bag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `value`, replacing any earlier value of type `T`.
+    pub fn with<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.insert(value);
+        self
+    }
+
+    /// Add `value`, replacing any earlier value of type `T`.
+    ///
+    /// Only this bag changes; envelopes sharing its values keep theirs.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        Arc::make_mut(self.values.get_or_insert_default()).insert(
+            TypeId::of::<T>(),
+            Entry {
+                value: Arc::new(value),
+                type_name: std::any::type_name::<T>(),
+            },
+        );
+    }
+
+    /// Get the value of type `T`, if set.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.values
+            .as_ref()?
+            .get(&TypeId::of::<T>())?
+            .value
+            .downcast_ref()
+    }
+
+    /// Whether a value of type `T` is set.
+    pub fn contains<T: Any>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    /// Number of values set.
+    pub fn len(&self) -> usize {
+        self.values.as_ref().map_or(0, |values| values.len())
+    }
+
+    /// Whether no value is set.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Set the instant after which the work is no longer wanted.
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        self.with(Deadline(deadline))
+    }
+
+    /// Set the deadline to `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Get the deadline, if one is set.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.get::<Deadline>().map(|deadline| deadline.0)
+    }
+}
+
+/// Metadata key for the request deadline.
+struct Deadline(Instant);
+
+impl std::fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(
+                self.values
+                    .iter()
+                    .flat_map(|values| values.values())
+                    .map(|e| e.type_name),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TenantId(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Locale(&'static str);
+
+    #[test]
+    fn test_values_are_keyed_by_type() {
+        let metadata = Metadata::new()
+            .with(TenantId(1))
+            .with(Locale("de-DE"))
+            .with(TenantId(2));
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata.get::<TenantId>(), Some(&TenantId(2)));
+        assert_eq!(metadata.get::<Locale>(), Some(&Locale("de-DE")));
+        assert!(!metadata.contains::<u32>());
+        assert!(Metadata::new().is_empty());
+    }
+
+    #[test]
+    fn test_deadline_from_timeout() {
+        let before = Instant::now();
+        let metadata = Metadata::new().with_timeout(Duration::from_secs(2));
+        let deadline = metadata.deadline().unwrap();
+        assert!(deadline >= before + Duration::from_secs(2));
+        assert!(deadline <= Instant::now() + Duration::from_secs(2));
+        assert_eq!(Metadata::new().deadline(), None);
+    }
+
+    #[test]
+    fn test_insert_leaves_clones_untouched() {
+        let original = Metadata::new().with(TenantId(1));
+        let mut changed = original.clone();
+        changed.insert(TenantId(2));
+        assert_eq!(original.get::<TenantId>(), Some(&TenantId(1)));
+        assert_eq!(changed.get::<TenantId>(), Some(&TenantId(2)));
+    }
+}
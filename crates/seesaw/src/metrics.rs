@@ -0,0 +1,260 @@
+//! Engine metrics through the [`metrics`](https://docs.rs/metrics) facade
+//! (`metrics` feature).
+//!
+//! The runtime and dispatcher report to whatever recorder the application
+//! installs (Prometheus, StatsD, ...). Without the feature every function
+//! here compiles to nothing.
+//!
+//! | Metric                                 | Kind      | Labels                    |
+//! |----------------------------------------|-----------|---------------------------|
+//! | `seesaw_events_processed_total`        | counter   |                           |
+//! | `seesaw_decide_duration_seconds`       | histogram | `machine`                 |
+//! | `seesaw_commands_dispatched_total`     | counter   | `mode`                    |
+//! | `seesaw_effect_duration_seconds`       | histogram | `command_type`, `outcome` |
+//! | `seesaw_command_failed_total`          | counter   | `command_type`            |
+//! | `seesaw_bus_lagged_events_total`       | counter   |                           |
+//! | `seesaw_events_expired_total`          | counter   | `event_type`              |
+//! | `seesaw_bus_queued_events`             | gauge     |                           |
+//! | `seesaw_inflight_batches`              | gauge     |                           |
+//! | `seesaw_http_request_duration_seconds` | histogram | `command_type`, `status`  |
+//!
+//! `mode` is `inline`, `background` or `scheduled`; `outcome` is `ok` or
+//! `failed`. `status` is the response's class (`2xx`, `4xx`, ...), `timeout`
+//! or `error`, for requests made with
+//! [`EffectContext::http`](crate::EffectContext::http) (`http` feature).
+
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use ::metrics::{counter, gauge, histogram};
+
+/// An event was received by the runtime.
+pub(crate) fn event_processed() {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_events_processed_total").increment(1);
+}
+
+/// A machine's `decide` returned (or panicked) after `elapsed`.
+pub(crate) fn decided(machine: &'static str, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    histogram!("seesaw_decide_duration_seconds", "machine" => machine).record(elapsed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (machine, elapsed);
+}
+
+/// A decided command was handed to an effect or the job queue.
+pub(crate) fn command_dispatched(mode: &'static str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_commands_dispatched_total", "mode" => mode).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = mode;
+}
+
+/// An inline effect execution finished after `elapsed`.
+pub(crate) fn effect_finished(command_type: &'static str, elapsed: Duration, ok: bool) {
+    #[cfg(feature = "metrics")]
+    histogram!(
+        "seesaw_effect_duration_seconds",
+        "command_type" => command_type,
+        "outcome" => if ok { "ok" } else { "failed" },
+    )
+    .record(elapsed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (command_type, elapsed, ok);
+}
+
+/// `CommandFailed` was emitted for a command of this type.
+pub(crate) fn command_failed(command_type: &'static str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_command_failed_total", "command_type" => command_type).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = command_type;
+}
+
+/// The runtime fell behind the bus and missed `missed` events.
+pub(crate) fn bus_lagged(missed: u64) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_bus_lagged_events_total").increment(missed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = missed;
+}
+
+/// An event of this type outlived its TTL and was dropped.
+pub(crate) fn event_expired(event_type: &'static str) {
+    #[cfg(feature = "metrics")]
+    counter!("seesaw_events_expired_total", "event_type" => event_type).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = event_type;
+}
+
+/// Events on the bus not yet received by every subscriber.
+pub(crate) fn bus_queued(queued: usize) {
+    #[cfg(feature = "metrics")]
+    gauge!("seesaw_bus_queued_events").set(queued as f64);
+    #[cfg(not(feature = "metrics"))]
+    let _ = queued;
+}
+
+/// Inline effect executions in progress.
+pub(crate) fn inflight_batches(inflight: usize) {
+    #[cfg(feature = "metrics")]
+    gauge!("seesaw_inflight_batches").set(inflight as f64);
+    #[cfg(not(feature = "metrics"))]
+    let _ = inflight;
+}
+
+/// An effect's HTTP request finished with `status` after `elapsed`.
+#[cfg(feature = "http")]
+pub(crate) fn http_request(command_type: &'static str, status: &'static str, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    histogram!(
+        "seesaw_http_request_duration_seconds",
+        "command_type" => command_type,
+        "status" => status,
+    )
+    .record(elapsed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (command_type, status, elapsed);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use crate::bus::EventBus;
+    use crate::core::Command;
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::machine::Machine;
+    use crate::runtime::Runtime;
+    use ::metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Counts counter increments by `name{label=value,...}`.
+    #[derive(Default)]
+    struct CountingRecorder {
+        counters: Mutex<HashMap<String, Arc<AtomicU64>>>,
+    }
+
+    impl CountingRecorder {
+        fn count(&self, key: &str) -> u64 {
+            self.counters
+                .lock()
+                .unwrap()
+                .get(key)
+                .map_or(0, |c| c.load(Ordering::Relaxed))
+        }
+    }
+
+    impl Recorder for CountingRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            let labels: Vec<String> = key
+                .labels()
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect();
+            let name = if labels.is_empty() {
+                key.name().to_string()
+            } else {
+                format!("{}{{{}}}", key.name(), labels.join(","))
+            };
+            let counter = self
+                .counters
+                .lock()
+                .unwrap()
+                .entry(name)
+                .or_default()
+                .clone();
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Charge(u32);
+
+    #[derive(Debug, Clone)]
+    struct ChargeCard(u32);
+    impl Command for ChargeCard {}
+
+    #[derive(Debug, Clone)]
+    struct Charged;
+
+    struct ChargeMachine;
+
+    impl Machine for ChargeMachine {
+        type Event = Charge;
+        type Command = ChargeCard;
+
+        fn decide(&mut self, event: &Charge) -> Option<ChargeCard> {
+            Some(ChargeCard(event.0))
+        }
+    }
+
+    struct ChargeEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<ChargeCard, ()> for ChargeEffect {
+        type Event = Charged;
+
+        async fn execute(
+            &self,
+            cmd: ChargeCard,
+            _ctx: EffectContext<()>,
+        ) -> anyhow::Result<Charged> {
+            if cmd.0 == 0 {
+                anyhow::bail!("declined");
+            }
+            Ok(Charged)
+        }
+    }
+
+    #[test]
+    fn test_runtime_reports_metrics() {
+        let recorder = CountingRecorder::default();
+
+        ::metrics::with_local_recorder(&recorder, || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            rt.block_on(async {
+                let bus = EventBus::new();
+                let dispatcher =
+                    Dispatcher::new((), bus.clone()).with_effect::<ChargeCard, _>(ChargeEffect);
+                let runtime = Runtime::new(dispatcher, bus.clone()).with_machine(ChargeMachine);
+                let handle = tokio::spawn(runtime.run());
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                bus.emit(Charge(5));
+                bus.emit(Charge(0));
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                handle.abort();
+            });
+        });
+
+        // Two charges, one Charged, one CommandFailed
+        assert_eq!(recorder.count("seesaw_events_processed_total"), 4);
+        assert_eq!(
+            recorder.count("seesaw_commands_dispatched_total{mode=inline}"),
+            2
+        );
+        let failed = format!(
+            "seesaw_command_failed_total{{command_type={}}}",
+            std::any::type_name::<ChargeCard>()
+        );
+        assert_eq!(recorder.count(&failed), 1);
+    }
+}
@@ -0,0 +1,402 @@
+//! Effect middleware - cross-cutting behavior around every effect execution.
+//!
+//! Logging, metrics, auth context and similar concerns apply to all effects
+//! alike. Rather than decorating each `Effect` struct by hand, register an
+//! [`EffectMiddleware`] once and the dispatcher runs it around every inline
+//! execution.
+//!
+//! # Ordering
+//!
+//! Middleware runs in registration order, the first registered being the
+//! outermost layer. The whole chain wraps the circuit breaker and timeout of
+//! the command type, so middleware observes fast failures and timeouts like
+//! any other error:
+//!
+//! ```text
+//! middleware[0].around
+//!  └─► middleware[1].around
+//!       └─► circuit breaker → timeout → Effect::execute / execute_batch
+//! ```
+//!
+//! # Example
+//!
+//! ```ignore
+//! struct LogMiddleware;
+//!
+//! #[async_trait]
+//! impl<D: Send + Sync + 'static> EffectMiddleware<D> for LogMiddleware {
+//!     async fn before(&self, call: &EffectCall<D>) -> Result<()> {
+//!         info!(command = call.command_type(), "executing");
+//!         Ok(())
+//!     }
+//!
+//!     async fn after(&self, call: &EffectCall<D>, result: &Result<EffectOutput>) {
+//!         info!(command = call.command_type(), ok = result.is_ok(), "executed");
+//!     }
+//! }
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect_middleware(LogMiddleware)
+//!     .with_effect::<FetchCommand, _>(FetchEffect)
+//!     .build();
+//! ```
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+
+use crate::core::{CorrelationId, EventEnvelope};
+
+// =============================================================================
+// Effect Call
+// =============================================================================
+
+/// Description of the effect execution a middleware is wrapping.
+pub struct EffectCall<D> {
+    command_type: &'static str,
+    batch_size: usize,
+    correlation_id: CorrelationId,
+    deps: Arc<D>,
+}
+
+impl<D> EffectCall<D> {
+    pub(crate) fn new(
+        command_type: &'static str,
+        batch_size: usize,
+        correlation_id: CorrelationId,
+        deps: Arc<D>,
+    ) -> Self {
+        Self {
+            command_type,
+            batch_size,
+            correlation_id,
+            deps,
+        }
+    }
+
+    /// Type name of the command being executed.
+    pub fn command_type(&self) -> &'static str {
+        self.command_type
+    }
+
+    /// Number of commands in this execution (1 unless batched).
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Correlation ID of the execution (NONE if uncorrelated).
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation_id
+    }
+
+    /// Get a reference to the shared dependencies.
+    pub fn deps(&self) -> &D {
+        &self.deps
+    }
+}
+
+impl<D> std::fmt::Debug for EffectCall<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EffectCall")
+            .field("command_type", &self.command_type)
+            .field("batch_size", &self.batch_size)
+            .field("correlation_id", &self.correlation_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Events produced by an effect execution, before the dispatcher emits them.
+///
+/// Only the dispatcher can create one, so a middleware can short-circuit an
+/// execution with an error but cannot fabricate a successful result.
+#[derive(Debug)]
+pub struct EffectOutput {
+    envelopes: Vec<EventEnvelope>,
+}
+
+impl EffectOutput {
+    pub(crate) fn new(envelopes: Vec<EventEnvelope>) -> Self {
+        Self { envelopes }
+    }
+
+    /// The event envelopes returned by the effect, one per command.
+    pub fn envelopes(&self) -> &[EventEnvelope] {
+        &self.envelopes
+    }
+
+    /// Number of events returned.
+    pub fn len(&self) -> usize {
+        self.envelopes.len()
+    }
+
+    /// Check if the effect returned no events.
+    pub fn is_empty(&self) -> bool {
+        self.envelopes.is_empty()
+    }
+
+    pub(crate) fn into_envelopes(self) -> Vec<EventEnvelope> {
+        self.envelopes
+    }
+}
+
+// =============================================================================
+// Middleware Trait
+// =============================================================================
+
+/// Hook that runs around every inline effect execution.
+///
+/// Implement `before` / `after` for simple observation, or override `around`
+/// for full control - for example to scope a task-local around the effect or
+/// to short-circuit with an error. `around` must either call `next.run()` or
+/// return an error; the default implementation calls `before`, `next.run()`
+/// and `after`.
+///
+/// An error from a middleware is treated exactly like an effect error:
+/// it is recorded for `emit_and_await` and emitted as `CommandFailed`.
+///
+/// `next.run()` can be called at most once, since the command is consumed by
+/// the effect. Use [`RetryingEffect`](crate::RetryingEffect) to retry.
+#[async_trait]
+pub trait EffectMiddleware<D>: Send + Sync + 'static
+where
+    D: Send + Sync + 'static,
+{
+    /// Called before the effect runs. Returning `Err` skips the effect.
+    async fn before(&self, _call: &EffectCall<D>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the effect (and inner middleware) finished.
+    async fn after(&self, _call: &EffectCall<D>, _result: &Result<EffectOutput>) {}
+
+    /// Wrap the rest of the chain.
+    async fn around(&self, call: &EffectCall<D>, next: Next<'_, D>) -> Result<EffectOutput> {
+        self.before(call).await?;
+        let result = next.run().await;
+        self.after(call, &result).await;
+        result
+    }
+}
+
+// =============================================================================
+// Chain
+// =============================================================================
+
+/// The remainder of the middleware chain, ending in the effect itself.
+pub struct Next<'a, D> {
+    chain: &'a [Arc<dyn EffectMiddleware<D>>],
+    call: &'a EffectCall<D>,
+    execution: BoxFuture<'a, Result<EffectOutput>>,
+}
+
+impl<'a, D: Send + Sync + 'static> Next<'a, D> {
+    pub(crate) fn new(
+        chain: &'a [Arc<dyn EffectMiddleware<D>>],
+        call: &'a EffectCall<D>,
+        execution: BoxFuture<'a, Result<EffectOutput>>,
+    ) -> Self {
+        Self {
+            chain,
+            call,
+            execution,
+        }
+    }
+
+    /// Run the next middleware, or the effect if this is the last layer.
+    pub async fn run(self) -> Result<EffectOutput> {
+        match self.chain.split_first() {
+            None => self.execution.await,
+            Some((middleware, rest)) => {
+                let next = Next::new(rest, self.call, self.execution);
+                middleware.around(self.call, next).await
+            }
+        }
+    }
+}
+
+impl<D> std::fmt::Debug for Next<'_, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &self.chain.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::core::{AnyCommand, Command};
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::error::CommandFailed;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone)]
+    struct PingCommand {
+        fail: bool,
+    }
+    impl Command for PingCommand {}
+
+    #[derive(Debug, Clone)]
+    struct PongEvent {
+        user: Option<String>,
+    }
+
+    tokio::task_local! {
+        static CURRENT_USER: String;
+    }
+
+    struct PingEffect {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Effect<PingCommand, ()> for PingEffect {
+        type Event = PongEvent;
+
+        async fn execute(&self, cmd: PingCommand, _ctx: EffectContext<()>) -> Result<PongEvent> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if cmd.fail {
+                anyhow::bail!("ping failed");
+            }
+            Ok(PongEvent {
+                user: CURRENT_USER.try_with(|u| u.clone()).ok(),
+            })
+        }
+    }
+
+    struct RecordingMiddleware {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl EffectMiddleware<()> for RecordingMiddleware {
+        async fn before(&self, call: &EffectCall<()>) -> Result<()> {
+            assert!(call.command_type().contains("PingCommand"));
+            assert_eq!(call.batch_size(), 1);
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:before", self.name));
+            Ok(())
+        }
+
+        async fn after(&self, _call: &EffectCall<()>, result: &Result<EffectOutput>) {
+            let status = match result {
+                Ok(output) => format!("ok({})", output.len()),
+                Err(e) => format!("err({})", e),
+            };
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:after:{}", self.name, status));
+        }
+    }
+
+    fn ping(fail: bool) -> Vec<Box<dyn AnyCommand>> {
+        vec![Box::new(PingCommand { fail })]
+    }
+
+    #[tokio::test]
+    async fn test_middleware_runs_in_registration_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let dispatcher = Dispatcher::new((), EventBus::new())
+            .with_middleware(RecordingMiddleware {
+                name: "outer",
+                log: log.clone(),
+            })
+            .with_middleware(RecordingMiddleware {
+                name: "inner",
+                log: log.clone(),
+            })
+            .with_effect::<PingCommand, _>(PingEffect {
+                calls: calls.clone(),
+            });
+
+        dispatcher.dispatch(ping(false)).await.unwrap();
+        dispatcher.dispatch(ping(true)).await.unwrap_err();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "outer:before",
+                "inner:before",
+                "inner:after:ok(1)",
+                "outer:after:ok(1)",
+                "outer:before",
+                "inner:before",
+                "inner:after:err(ping failed)",
+                "outer:after:err(ping failed)",
+            ]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_before_error_skips_effect_and_emits_command_failed() {
+        struct DenyAll;
+
+        #[async_trait]
+        impl EffectMiddleware<()> for DenyAll {
+            async fn before(&self, _call: &EffectCall<()>) -> Result<()> {
+                anyhow::bail!("denied")
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let cid = CorrelationId::new();
+        let dispatcher = Dispatcher::new((), bus)
+            .with_middleware(DenyAll)
+            .with_effect::<PingCommand, _>(PingEffect {
+                calls: calls.clone(),
+            });
+
+        dispatcher
+            .dispatch_with_correlation(ping(false), cid, None)
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let envelope = receiver.recv().await.unwrap();
+        let failed = envelope.downcast_ref::<CommandFailed>().unwrap();
+        assert_eq!(failed.cid, cid);
+    }
+
+    #[tokio::test]
+    async fn test_around_can_scope_context_for_effect() {
+        struct AuthMiddleware;
+
+        #[async_trait]
+        impl EffectMiddleware<()> for AuthMiddleware {
+            async fn around(
+                &self,
+                _call: &EffectCall<()>,
+                next: Next<'_, ()>,
+            ) -> Result<EffectOutput> {
+                CURRENT_USER.scope("alice".to_string(), next.run()).await
+            }
+        }
+
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        let dispatcher = Dispatcher::new((), bus)
+            .with_middleware(AuthMiddleware)
+            .with_effect::<PingCommand, _>(PingEffect {
+                calls: Arc::new(AtomicUsize::new(0)),
+            });
+
+        dispatcher.dispatch(ping(false)).await.unwrap();
+
+        let envelope = receiver.recv().await.unwrap();
+        let pong = envelope.downcast_ref::<PongEvent>().unwrap();
+        assert_eq!(pong.user.as_deref(), Some("alice"));
+    }
+}
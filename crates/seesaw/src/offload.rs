@@ -0,0 +1,707 @@
+//! Payload offloading - large job payloads kept outside the queue.
+//!
+//! Document-processing commands can carry payloads of several megabytes,
+//! which bloat a jobs table and slow every claim. A [`PayloadOffload`]
+//! backend (object storage or a shared filesystem, see the `seesaw-offload`
+//! crate) stores payloads above a size threshold externally, and the queue
+//! keeps only a small reference in their place:
+//!
+//! - **Enqueue**: [`OffloadingJobQueue`] wraps the dispatcher's
+//!   [`JobQueue`]. Payloads larger than the threshold, JSON or
+//!   [encoded](crate::PayloadCodecs), are uploaded and replaced by an
+//!   [`OffloadRef`].
+//! - **Claim**: [`OffloadingJobStore`] wraps the worker's [`JobStore`] and
+//!   downloads referenced payloads before jobs are handed out, so effects
+//!   see the original command. A payload that cannot be loaded fails the
+//!   attempt retryably.
+//! - **Cleanup**: the object is deleted once its job succeeds. Jobs that are
+//!   dead-lettered keep theirs so they can be retried; expire leftovers with
+//!   the storage's lifecycle rules.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let offload: Arc<dyn PayloadOffload> = Arc::new(S3PayloadOffload::from_env("documents")?);
+//!
+//! let queue = OffloadingJobQueue::new(queue, offload.clone()).with_threshold(512 * 1024);
+//! let dispatcher = Dispatcher::with_job_queue(deps, bus, Arc::new(queue));
+//!
+//! let store = Arc::new(OffloadingJobStore::new(PgJobStore::new(pool), offload));
+//! let worker = JobWorker::new(store, registry, worker_dispatcher);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::codec::{EncodedPayload, PayloadCodecs};
+use crate::core::JobSpec;
+use crate::dispatch::JobQueue;
+use crate::job::{ClaimedJob, FailureKind, JobLease, JobStore, LookaheadClaim};
+
+/// Payloads larger than this many bytes are offloaded by default (256 KiB).
+pub const DEFAULT_OFFLOAD_THRESHOLD: usize = 256 * 1024;
+
+/// The field an offloaded payload is replaced by a reference under.
+const REF_FIELD: &str = "$seesaw_offload";
+
+/// External storage for job payloads.
+///
+/// Keys are generated by the offloading wrappers and contain only ASCII
+/// letters, digits and `-`.
+#[async_trait]
+pub trait PayloadOffload: Send + Sync + 'static {
+    /// Store `bytes` under `key`, replacing any previous object.
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()>;
+
+    /// Load the object stored under `key`.
+    async fn get(&self, key: &str) -> Result<Bytes>;
+
+    /// Delete the object stored under `key`. Deleting a missing object
+    /// succeeds.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// What an offloaded payload is replaced by in the queue.
+///
+/// Serialized as `{"$seesaw_offload": {"key": …, "size": …, "codec": …}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffloadRef {
+    /// Key of the object in the [`PayloadOffload`] backend.
+    pub key: String,
+    /// Size of the stored payload in bytes.
+    pub size: u64,
+    /// [`PayloadCodec`](crate::PayloadCodec) the stored bytes are encoded
+    /// with; `"json"` for plain payloads.
+    pub codec: String,
+}
+
+impl OffloadRef {
+    /// The reference a payload was replaced by, if it was offloaded.
+    pub fn from_payload(payload: &serde_json::Value) -> Option<Self> {
+        let reference = payload.as_object()?.get(REF_FIELD)?;
+        serde_json::from_value(reference.clone()).ok()
+    }
+
+    /// The payload this reference stands in for.
+    pub fn to_payload(&self) -> serde_json::Value {
+        serde_json::json!({ REF_FIELD: self })
+    }
+}
+
+// =============================================================================
+// Offloading Job Queue
+// =============================================================================
+
+/// A job queue wrapper that offloads payloads above a size threshold.
+///
+/// Every payload is serialized once to measure it; payloads at or below the
+/// threshold reach the inner queue unchanged.
+pub struct OffloadingJobQueue<Q> {
+    inner: Q,
+    offload: Arc<dyn PayloadOffload>,
+    threshold: usize,
+}
+
+impl<Q: JobQueue> OffloadingJobQueue<Q> {
+    /// Wrap `inner`, offloading payloads above [`DEFAULT_OFFLOAD_THRESHOLD`].
+    pub fn new(inner: Q, offload: Arc<dyn PayloadOffload>) -> Self {
+        Self {
+            inner,
+            offload,
+            threshold: DEFAULT_OFFLOAD_THRESHOLD,
+        }
+    }
+
+    /// Offload payloads larger than `bytes`.
+    pub fn with_threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+        self
+    }
+
+    /// Get the wrapped queue.
+    pub fn inner(&self) -> &Q {
+        &self.inner
+    }
+
+    /// Upload `bytes` if they are over the threshold, returning the
+    /// reference to enqueue instead.
+    async fn upload(&self, codec: &str, bytes: &[u8]) -> Result<Option<OffloadRef>> {
+        if bytes.len() <= self.threshold {
+            return Ok(None);
+        }
+        let reference = OffloadRef {
+            key: Uuid::new_v4().to_string(),
+            size: bytes.len() as u64,
+            codec: codec.to_string(),
+        };
+        self.offload
+            .put(&reference.key, Bytes::copy_from_slice(bytes))
+            .await
+            .with_context(|| format!("offloading {} byte payload", bytes.len()))?;
+        Ok(Some(reference))
+    }
+
+    /// The payload to enqueue for `payload`, and the reference it uploaded.
+    async fn prepare(
+        &self,
+        payload: serde_json::Value,
+    ) -> Result<(serde_json::Value, Option<OffloadRef>)> {
+        let bytes = serde_json::to_vec(&payload)?;
+        match self.upload("json", &bytes).await? {
+            Some(reference) => Ok((reference.to_payload(), Some(reference))),
+            None => Ok((payload, None)),
+        }
+    }
+
+    /// Delete the object behind `reference` if enqueueing it failed.
+    async fn discard_on_error<T>(
+        &self,
+        result: Result<T>,
+        reference: Option<OffloadRef>,
+    ) -> Result<T> {
+        if let (Err(_), Some(reference)) = (&result, reference) {
+            if let Err(e) = self.offload.delete(&reference.key).await {
+                warn!(key = %reference.key, error = %e, "failed to delete offloaded payload of unqueued job");
+            }
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<Q: JobQueue> JobQueue for OffloadingJobQueue<Q> {
+    async fn enqueue(&self, payload: serde_json::Value, spec: JobSpec) -> Result<Uuid> {
+        let (payload, reference) = self.prepare(payload).await?;
+        let result = self.inner.enqueue(payload, spec).await;
+        self.discard_on_error(result, reference).await
+    }
+
+    async fn schedule(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let (payload, reference) = self.prepare(payload).await?;
+        let result = self.inner.schedule(payload, spec, run_at).await;
+        self.discard_on_error(result, reference).await
+    }
+
+    async fn enqueue_batch(&self, jobs: Vec<(serde_json::Value, JobSpec)>) -> Vec<Result<Uuid>> {
+        let mut results: Vec<Option<Result<Uuid>>> = Vec::with_capacity(jobs.len());
+        let mut prepared = Vec::new();
+        let mut references = Vec::new();
+        for (payload, spec) in jobs {
+            match self.prepare(payload).await {
+                Ok((payload, reference)) => {
+                    results.push(None);
+                    prepared.push((payload, spec));
+                    references.push(reference);
+                }
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let mut enqueued = self.inner.enqueue_batch(prepared).await.into_iter();
+        let mut references = references.into_iter();
+        for slot in results.iter_mut().filter(|slot| slot.is_none()) {
+            let result = enqueued
+                .next()
+                .unwrap_or_else(|| Err(anyhow::anyhow!("job queue returned too few results")));
+            let reference = references.next().flatten();
+            *slot = Some(self.discard_on_error(result, reference).await);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    async fn enqueue_encoded(&self, payload: EncodedPayload, spec: JobSpec) -> Result<Uuid> {
+        let Some(reference) = self.upload(payload.codec, &payload.bytes).await? else {
+            return self.inner.enqueue_encoded(payload, spec).await;
+        };
+        let result = self.inner.enqueue(reference.to_payload(), spec).await;
+        self.discard_on_error(result, Some(reference)).await
+    }
+
+    async fn schedule_encoded(
+        &self,
+        payload: EncodedPayload,
+        spec: JobSpec,
+        run_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let Some(reference) = self.upload(payload.codec, &payload.bytes).await? else {
+            return self.inner.schedule_encoded(payload, spec, run_at).await;
+        };
+        let result = self
+            .inner
+            .schedule(reference.to_payload(), spec, run_at)
+            .await;
+        self.discard_on_error(result, Some(reference)).await
+    }
+    async fn schedule_recurring(
+        &self,
+        payload: serde_json::Value,
+        spec: JobSpec,
+        cron: &str,
+        tz: &str,
+    ) -> Result<Uuid> {
+        // Not offloaded: every occurrence reads the payload, and the first
+        // to succeed would delete it
+        self.inner.schedule_recurring(payload, spec, cron, tz).await
+    }
+}
+
+impl<Q> std::fmt::Debug for OffloadingJobQueue<Q> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OffloadingJobQueue")
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+// =============================================================================
+// Offloading Job Store
+// =============================================================================
+
+/// A job store wrapper that rehydrates offloaded payloads on claim.
+///
+/// Jobs without a reference pass through unchanged, so the wrapper can be
+/// deployed before the queue starts offloading.
+pub struct OffloadingJobStore<S> {
+    inner: S,
+    offload: Arc<dyn PayloadOffload>,
+    codecs: PayloadCodecs,
+    /// Objects of jobs claimed through this store, deleted on success.
+    claimed: Mutex<HashMap<Uuid, String>>,
+}
+
+impl<S: JobStore> OffloadingJobStore<S> {
+    /// Wrap `inner`, loading references from `offload`.
+    pub fn new(inner: S, offload: Arc<dyn PayloadOffload>) -> Self {
+        Self {
+            inner,
+            offload,
+            codecs: PayloadCodecs::new(),
+            claimed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decode offloaded payloads that were encoded by a codec from `codecs`.
+    ///
+    /// Without it only JSON payloads can be rehydrated.
+    pub fn with_codecs(mut self, codecs: PayloadCodecs) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Get the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Replace the job's reference with its payload, failing the attempt if
+    /// it cannot be loaded. Returns `None` for failed jobs.
+    async fn rehydrate(&self, mut job: ClaimedJob) -> Result<Option<ClaimedJob>> {
+        let Some(reference) = OffloadRef::from_payload(&job.payload) else {
+            return Ok(Some(job));
+        };
+        let loaded = async {
+            let bytes = self.offload.get(&reference.key).await?;
+            self.codecs.decode(&reference.codec, &bytes)
+        }
+        .await;
+
+        match loaded {
+            Ok(payload) => {
+                job.payload = payload;
+                self.claimed.lock().unwrap().insert(job.id, reference.key);
+                Ok(Some(job))
+            }
+            Err(e) => {
+                let error = format!("loading offloaded payload {}: {e:#}", reference.key);
+                warn!(job_id = %job.id, error = %error, "failing job with unloadable payload");
+                self.inner
+                    .mark_failed(job.lease(), &error, FailureKind::Retryable)
+                    .await?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: JobStore> JobStore for OffloadingJobStore<S> {
+    async fn claim_ready(&self, worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+        let mut jobs = Vec::new();
+        for job in self.inner.claim_ready(worker_id, limit).await? {
+            jobs.extend(self.rehydrate(job).await?);
+        }
+        Ok(jobs)
+    }
+
+    async fn claim_ready_with_lookahead(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        lookahead: Duration,
+    ) -> Result<LookaheadClaim> {
+        let claim = self
+            .inner
+            .claim_ready_with_lookahead(worker_id, limit, lookahead)
+            .await?;
+        let mut jobs = Vec::new();
+        for job in claim.jobs {
+            jobs.extend(self.rehydrate(job).await?);
+        }
+        Ok(LookaheadClaim {
+            jobs,
+            upcoming: claim.upcoming,
+        })
+    }
+
+    async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+        self.inner.mark_succeeded(lease).await?;
+        let key = self.claimed.lock().unwrap().remove(&lease.job_id);
+        if let Some(key) = key {
+            if let Err(e) = self.offload.delete(&key).await {
+                warn!(job_id = %lease.job_id, key = %key, error = %e, "failed to delete offloaded payload");
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+        // Retries and dead letters still need the payload
+        self.claimed.lock().unwrap().remove(&lease.job_id);
+        self.inner.mark_failed(lease, error, kind).await
+    }
+
+    async fn record_failure(
+        &self,
+        lease: JobLease,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<bool> {
+        self.claimed.lock().unwrap().remove(&lease.job_id);
+        self.inner.record_failure(lease, error, kind).await
+    }
+
+    async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+        self.inner.heartbeat(lease).await
+    }
+
+    async fn park(&self, lease: JobLease, reason: &str) -> Result<()> {
+        // Unparking needs the payload
+        self.claimed.lock().unwrap().remove(&lease.job_id);
+        self.inner.park(lease, reason).await
+    }
+
+    async fn reserve(
+        &self,
+        worker_id: &str,
+        limit: i64,
+        window: Duration,
+    ) -> Result<Vec<ClaimedJob>> {
+        let mut jobs = Vec::new();
+        for job in self.inner.reserve(worker_id, limit, window).await? {
+            jobs.extend(self.rehydrate(job).await?);
+        }
+        Ok(jobs)
+    }
+
+    async fn confirm(&self, lease: JobLease) -> Result<()> {
+        self.inner.confirm(lease).await
+    }
+
+    async fn release(&self, lease: JobLease) -> Result<()> {
+        // The next claim needs the payload
+        self.claimed.lock().unwrap().remove(&lease.job_id);
+        self.inner.release(lease).await
+    }
+
+    async fn running_jobs(&self, worker_id: &str) -> Result<Vec<ClaimedJob>> {
+        let mut jobs = Vec::new();
+        for job in self.inner.running_jobs(worker_id).await? {
+            jobs.extend(self.rehydrate(job).await?);
+        }
+        Ok(jobs)
+    }
+
+    /// Rehydrate the inner store's stream, keeping its wake-ups.
+    fn claim_stream<'a>(
+        &'a self,
+        worker_id: &'a str,
+        concurrency: usize,
+    ) -> BoxStream<'a, Result<ClaimedJob>> {
+        self.inner
+            .claim_stream(worker_id, concurrency)
+            .filter_map(move |claimed| async move {
+                match claimed {
+                    Ok(job) => self.rehydrate(job).await.transpose(),
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .boxed()
+    }
+}
+
+impl<S> std::fmt::Debug for OffloadingJobStore<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OffloadingJobStore").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Objects kept in memory.
+    #[derive(Default)]
+    struct MemoryOffload {
+        objects: Mutex<HashMap<String, Bytes>>,
+    }
+
+    #[async_trait]
+    impl PayloadOffload for MemoryOffload {
+        async fn put(&self, key: &str, bytes: Bytes) -> Result<()> {
+            self.objects.lock().unwrap().insert(key.to_string(), bytes);
+            Ok(())
+        }
+
+        async fn get(&self, key: &str) -> Result<Bytes> {
+            self.objects
+                .lock()
+                .unwrap()
+                .get(key)
+                .cloned()
+                .with_context(|| format!("no object {key}"))
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.objects.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    /// A queue whose enqueued jobs are claimed in order.
+    #[derive(Default)]
+    struct Jobs {
+        pending: Mutex<Vec<ClaimedJob>>,
+        succeeded: Mutex<Vec<Uuid>>,
+        failed: Mutex<Vec<(Uuid, FailureKind)>>,
+    }
+
+    #[derive(Clone, Default)]
+    struct MemoryQueue(Arc<Jobs>);
+
+    impl MemoryQueue {
+        fn queued(&self) -> Vec<serde_json::Value> {
+            let pending = self.0.pending.lock().unwrap();
+            pending.iter().map(|job| job.payload.clone()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl JobQueue for MemoryQueue {
+        async fn enqueue(&self, payload: serde_json::Value, spec: JobSpec) -> Result<Uuid> {
+            let id = Uuid::new_v4();
+            self.0.pending.lock().unwrap().push(ClaimedJob {
+                id,
+                job_type: spec.job_type.to_string(),
+                payload,
+                version: spec.version,
+                attempt: 1,
+                lease_token: Uuid::new_v4(),
+            });
+            Ok(id)
+        }
+
+        async fn schedule(
+            &self,
+            payload: serde_json::Value,
+            spec: JobSpec,
+            _run_at: DateTime<Utc>,
+        ) -> Result<Uuid> {
+            self.enqueue(payload, spec).await
+        }
+    }
+
+    #[async_trait]
+    impl JobStore for MemoryQueue {
+        async fn claim_ready(&self, _worker_id: &str, limit: i64) -> Result<Vec<ClaimedJob>> {
+            let mut pending = self.0.pending.lock().unwrap();
+            let n = pending.len().min(limit as usize);
+            Ok(pending.drain(..n).collect())
+        }
+
+        async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+            self.0.succeeded.lock().unwrap().push(lease.job_id);
+            Ok(())
+        }
+
+        async fn mark_failed(
+            &self,
+            lease: JobLease,
+            _error: &str,
+            kind: FailureKind,
+        ) -> Result<()> {
+            self.0.failed.lock().unwrap().push((lease.job_id, kind));
+            Ok(())
+        }
+
+        async fn heartbeat(&self, _lease: JobLease) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn document(size: usize) -> serde_json::Value {
+        serde_json::json!({ "body": "x".repeat(size) })
+    }
+
+    #[tokio::test]
+    async fn test_small_payloads_are_queued_inline() {
+        let offload = Arc::new(MemoryOffload::default());
+        let jobs = MemoryQueue::default();
+        let queue = OffloadingJobQueue::new(jobs.clone(), offload.clone()).with_threshold(1024);
+
+        queue
+            .enqueue(document(10), JobSpec::new("doc:parse"))
+            .await
+            .unwrap();
+
+        assert_eq!(jobs.queued(), vec![document(10)]);
+        assert!(offload.objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_large_payload_is_offloaded_rehydrated_and_deleted() {
+        let offload = Arc::new(MemoryOffload::default());
+        let jobs = MemoryQueue::default();
+        let queue = OffloadingJobQueue::new(jobs.clone(), offload.clone()).with_threshold(1024);
+        queue
+            .enqueue(document(4096), JobSpec::new("doc:parse"))
+            .await
+            .unwrap();
+
+        let reference = OffloadRef::from_payload(&jobs.queued()[0]).unwrap();
+        assert_eq!(reference.codec, "json");
+        assert!(reference.size > 4096);
+        assert_eq!(offload.objects.lock().unwrap().len(), 1);
+
+        let store = OffloadingJobStore::new(jobs.clone(), offload.clone());
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
+        assert_eq!(claimed[0].payload, document(4096));
+
+        store.mark_succeeded(claimed[0].lease()).await.unwrap();
+        assert_eq!(*jobs.0.succeeded.lock().unwrap(), vec![claimed[0].id]);
+        assert!(offload.objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_keeps_its_payload() {
+        let offload = Arc::new(MemoryOffload::default());
+        let jobs = MemoryQueue::default();
+        let queue = OffloadingJobQueue::new(jobs.clone(), offload.clone()).with_threshold(16);
+        queue
+            .enqueue(document(64), JobSpec::new("doc:parse"))
+            .await
+            .unwrap();
+
+        let store = OffloadingJobStore::new(jobs.clone(), offload.clone());
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
+        store
+            .mark_failed(claimed[0].lease(), "boom", FailureKind::NonRetryable)
+            .await
+            .unwrap();
+
+        assert_eq!(offload.objects.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_object_fails_attempt_retryably() {
+        let jobs = MemoryQueue::default();
+        let reference = OffloadRef {
+            key: "gone".to_string(),
+            size: 1,
+            codec: "json".to_string(),
+        };
+        let id = jobs
+            .enqueue(reference.to_payload(), JobSpec::new("doc:parse"))
+            .await
+            .unwrap();
+
+        let store = OffloadingJobStore::new(jobs.clone(), Arc::new(MemoryOffload::default()));
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
+
+        assert!(claimed.is_empty());
+        assert_eq!(
+            *jobs.0.failed.lock().unwrap(),
+            vec![(id, FailureKind::Retryable)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encoded_payload_is_offloaded_with_its_codec() {
+        struct Reversed;
+        impl crate::PayloadCodec for Reversed {
+            fn name(&self) -> &'static str {
+                "reversed"
+            }
+            fn encode(&self, payload: &serde_json::Value) -> Result<Bytes> {
+                let mut bytes = serde_json::to_vec(payload)?;
+                bytes.reverse();
+                Ok(bytes.into())
+            }
+            fn decode(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+                let mut bytes = bytes.to_vec();
+                bytes.reverse();
+                Ok(serde_json::from_slice(&bytes)?)
+            }
+        }
+
+        let codecs = PayloadCodecs::new().with_default(Reversed);
+        let offload = Arc::new(MemoryOffload::default());
+        let jobs = MemoryQueue::default();
+        let queue = OffloadingJobQueue::new(jobs.clone(), offload.clone()).with_threshold(16);
+        let encoded = codecs.encode("doc:parse", &document(64)).unwrap();
+        queue
+            .enqueue_encoded(encoded, JobSpec::new("doc:parse"))
+            .await
+            .unwrap();
+
+        let reference = OffloadRef::from_payload(&jobs.queued()[0]).unwrap();
+        assert_eq!(reference.codec, "reversed");
+
+        let store = OffloadingJobStore::new(jobs.clone(), offload).with_codecs(codecs);
+        let claimed = store.claim_ready("worker-1", 10).await.unwrap();
+        assert_eq!(claimed[0].payload, document(64));
+    }
+
+    #[tokio::test]
+    async fn test_batch_offloads_per_job() {
+        let offload = Arc::new(MemoryOffload::default());
+        let jobs = MemoryQueue::default();
+        let queue = OffloadingJobQueue::new(jobs.clone(), offload.clone()).with_threshold(32);
+
+        let ids = queue
+            .enqueue_batch(vec![
+                (document(1), JobSpec::new("doc:parse")),
+                (document(100), JobSpec::new("doc:parse")),
+            ])
+            .await;
+
+        assert!(ids.iter().all(|id| id.is_ok()));
+        let queued = jobs.queued();
+        assert_eq!(queued[0], document(1));
+        assert!(OffloadRef::from_payload(&queued[1]).is_some());
+    }
+}
@@ -0,0 +1,520 @@
+//! Process managers - multi-step, long-running flows built on machines.
+//!
+//! A [`ProcessManager`] is a machine whose state is kept per instance, keyed
+//! by correlation ID. An event emitted with `bus.emit` gets a fresh
+//! correlation ID that effects propagate to the events they return, so each
+//! flow started by an event is one instance, and many run side by side.
+//!
+//! Each step can arm a timeout. If it expires before the instance takes its
+//! next step, [`ProcessTimedOut`] is emitted with the instance's correlation
+//! ID and passed to [`ProcessManager::timed_out`], which by default emits the
+//! [`compensate`](ProcessManager::compensate) command and ends the instance.
+//!
+//! Timeouts are timers on the runtime: they do not survive a restart. For
+//! durable deadlines, emit an `ExecutionMode::Scheduled` command instead.
+//!
+//! # Example
+//!
+//! ```ignore
+//! struct CheckoutProcess;
+//!
+//! impl ProcessManager for CheckoutProcess {
+//!     type Event = CheckoutEvent;
+//!     type Command = CheckoutCommand;
+//!     type State = Checkout;
+//!
+//!     fn start(&mut self, event: &CheckoutEvent) -> Option<Checkout> {
+//!         match event {
+//!             CheckoutEvent::Requested { order_id } => Some(Checkout::new(*order_id)),
+//!             _ => None,
+//!         }
+//!     }
+//!
+//!     fn step(&mut self, state: &mut Checkout, event: &CheckoutEvent) -> ProcessStep<CheckoutCommand> {
+//!         match event {
+//!             CheckoutEvent::Requested { .. } => {
+//!                 ProcessStep::command(CheckoutCommand::ReserveStock { order_id: state.order_id })
+//!                     .with_timeout(Duration::from_secs(30))
+//!             }
+//!             CheckoutEvent::StockReserved { .. } => {
+//!                 state.reserved = true;
+//!                 ProcessStep::command(CheckoutCommand::ChargeCard { order_id: state.order_id })
+//!                     .with_timeout(Duration::from_secs(60))
+//!             }
+//!             CheckoutEvent::Charged { .. } => ProcessStep::done(),
+//!             _ => ProcessStep::none(),
+//!         }
+//!     }
+//!
+//!     fn compensate(&mut self, state: &Checkout) -> Option<CheckoutCommand> {
+//!         state
+//!             .reserved
+//!             .then(|| CheckoutCommand::ReleaseStock { order_id: state.order_id })
+//!     }
+//! }
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_process_manager(CheckoutProcess)
+//!     .build();
+//! ```
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::task::AbortHandle;
+use tracing::warn;
+
+use crate::bus::EventBus;
+use crate::core::{AnyCommand, Command, CorrelationId, Event};
+use crate::machine::AnyMachine;
+
+// =============================================================================
+// Process Manager
+// =============================================================================
+
+/// A multi-step flow with per-instance state, step timeouts and compensation.
+///
+/// Register it with `with_process_manager`. Like a [`Machine`](crate::Machine),
+/// it is called synchronously and serially, and each call decides at most one
+/// command.
+pub trait ProcessManager: Send + Sync + 'static {
+    /// The event type this process handles.
+    type Event: Event;
+
+    /// The command type this process can emit.
+    type Command: Command;
+
+    /// State of one instance.
+    type State: Send + Sync + 'static;
+
+    /// Start an instance for an event whose correlation ID has none running.
+    ///
+    /// Returns `None` if the event does not begin the process; it is then
+    /// ignored. A started instance is passed to [`step`](Self::step) with the
+    /// same event right away.
+    fn start(&mut self, event: &Self::Event) -> Option<Self::State>;
+
+    /// Advance an instance on one of its events.
+    fn step(&mut self, state: &mut Self::State, event: &Self::Event) -> ProcessStep<Self::Command>;
+
+    /// Handle the expiry of a step's timeout.
+    ///
+    /// Defaults to emitting [`compensate`](Self::compensate)'s command, if
+    /// any, and ending the instance.
+    fn timed_out(
+        &mut self,
+        state: &mut Self::State,
+        timeout: &ProcessTimedOut,
+    ) -> ProcessStep<Self::Command> {
+        let _ = timeout;
+        match self.compensate(state) {
+            Some(cmd) => ProcessStep::compensate(cmd),
+            None => ProcessStep::done(),
+        }
+    }
+
+    /// Command that undoes the steps an instance completed, if any.
+    ///
+    /// Defaults to `None`.
+    fn compensate(&mut self, state: &Self::State) -> Option<Self::Command> {
+        let _ = state;
+        None
+    }
+}
+
+/// What a [`ProcessManager`] decided for one event or timeout.
+#[derive(Debug)]
+pub struct ProcessStep<C> {
+    command: Option<C>,
+    timer: TimerChange,
+    done: bool,
+}
+
+/// How a step changes the instance's timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimerChange {
+    Keep,
+    Cancel,
+    Arm(Duration),
+}
+
+impl<C> ProcessStep<C> {
+    /// Take no step; an armed timeout keeps running.
+    ///
+    /// For events an instance doesn't react to in its current step.
+    pub fn none() -> Self {
+        Self {
+            command: None,
+            timer: TimerChange::Keep,
+            done: false,
+        }
+    }
+
+    /// Emit `command`, starting a new step without a timeout.
+    ///
+    /// Cancels the previous step's timeout. Arm one for the new step with
+    /// [`with_timeout`](Self::with_timeout).
+    pub fn command(command: C) -> Self {
+        Self {
+            command: Some(command),
+            timer: TimerChange::Cancel,
+            done: false,
+        }
+    }
+
+    /// End the instance, cancelling its timeout and dropping its state.
+    pub fn done() -> Self {
+        Self {
+            command: None,
+            timer: TimerChange::Cancel,
+            done: true,
+        }
+    }
+
+    /// Emit the compensation `command` and end the instance.
+    pub fn compensate(command: C) -> Self {
+        Self {
+            command: Some(command),
+            timer: TimerChange::Cancel,
+            done: true,
+        }
+    }
+
+    /// Emit [`ProcessTimedOut`] if the instance takes no further step
+    /// within `after`.
+    ///
+    /// Replaces any timeout already armed. Ignored on a step that ends the
+    /// instance.
+    pub fn with_timeout(mut self, after: Duration) -> Self {
+        self.timer = TimerChange::Arm(after);
+        self
+    }
+}
+
+/// Emitted when a process instance's step timeout expires.
+///
+/// Carries the instance's correlation ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessTimedOut {
+    /// Type name of the process manager.
+    pub process: &'static str,
+    /// The instance's step the timeout was armed for.
+    pub step: u64,
+    /// How long the step waited.
+    pub after: Duration,
+}
+
+// =============================================================================
+// Machine Adapter
+// =============================================================================
+
+/// State and armed timer of one running instance.
+struct Instance<S> {
+    state: S,
+    /// Bumped whenever the timer changes, so a stale expiry is ignored.
+    step: u64,
+    timer: Option<AbortHandle>,
+}
+
+impl<S> Instance<S> {
+    fn cancel_timer(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+        self.step += 1;
+    }
+}
+
+/// Runs a [`ProcessManager`] as a machine, one instance per correlation ID.
+pub(crate) struct ProcessAdapter<P: ProcessManager> {
+    manager: P,
+    bus: EventBus,
+    instances: HashMap<CorrelationId, Instance<P::State>>,
+}
+
+impl<P: ProcessManager> ProcessAdapter<P> {
+    pub(crate) fn new(manager: P, bus: EventBus) -> Self {
+        Self {
+            manager,
+            bus,
+            instances: HashMap::new(),
+        }
+    }
+
+    fn apply(&mut self, cid: CorrelationId, step: ProcessStep<P::Command>) -> Option<P::Command> {
+        if step.done {
+            if let Some(mut instance) = self.instances.remove(&cid) {
+                instance.cancel_timer();
+            }
+            return step.command;
+        }
+
+        let instance = self.instances.get_mut(&cid)?;
+        match step.timer {
+            TimerChange::Keep => {}
+            TimerChange::Cancel => instance.cancel_timer(),
+            TimerChange::Arm(after) => {
+                instance.cancel_timer();
+                instance.timer = arm::<P>(&self.bus, cid, instance.step, after);
+            }
+        }
+        step.command
+    }
+}
+
+/// Spawn the timer emitting [`ProcessTimedOut`] for an instance's step.
+fn arm<P: ProcessManager>(
+    bus: &EventBus,
+    cid: CorrelationId,
+    step: u64,
+    after: Duration,
+) -> Option<AbortHandle> {
+    let process = std::any::type_name::<P>();
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        warn!(process, "no tokio runtime, step timeout not armed");
+        return None;
+    };
+
+    let bus = bus.clone();
+    let timed_out = ProcessTimedOut {
+        process,
+        step,
+        after,
+    };
+    let task = runtime.spawn(async move {
+        tokio::time::sleep(after).await;
+        bus.emit_with_correlation(timed_out, cid);
+    });
+    Some(task.abort_handle())
+}
+
+impl<P: ProcessManager> AnyMachine for ProcessAdapter<P> {
+    fn decide_any(&mut self, event: &dyn Any) -> Option<Box<dyn AnyCommand>> {
+        self.decide_correlated_any(event, CorrelationId::NONE)
+    }
+
+    fn decide_correlated_any(
+        &mut self,
+        event: &dyn Any,
+        cid: CorrelationId,
+    ) -> Option<Box<dyn AnyCommand>> {
+        if let Some(timeout) = event.downcast_ref::<ProcessTimedOut>() {
+            if timeout.process != std::any::type_name::<P>() {
+                return None;
+            }
+            // Stale if the instance ended or moved on since it was armed
+            let instance = self
+                .instances
+                .get_mut(&cid)
+                .filter(|instance| instance.step == timeout.step)?;
+            instance.timer = None;
+            let step = self.manager.timed_out(&mut instance.state, timeout);
+            let cmd = self.apply(cid, step)?;
+            return Some(Box::new(cmd));
+        }
+
+        let event = event.downcast_ref::<P::Event>()?;
+        if !self.instances.contains_key(&cid) {
+            let state = self.manager.start(event)?;
+            self.instances.insert(
+                cid,
+                Instance {
+                    state,
+                    step: 0,
+                    timer: None,
+                },
+            );
+        }
+        let instance = self.instances.get_mut(&cid)?;
+        let step = self.manager.step(&mut instance.state, event);
+        let cmd = self.apply(cid, step)?;
+        Some(Box::new(cmd))
+    }
+}
+
+impl<P: ProcessManager> Drop for ProcessAdapter<P> {
+    fn drop(&mut self) {
+        for instance in self.instances.values_mut() {
+            instance.cancel_timer();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::MachineRunner;
+
+    #[derive(Debug, Clone)]
+    enum PaymentEvent {
+        Requested,
+        Authorized,
+        Captured,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum PaymentCommand {
+        Authorize,
+        Capture,
+        Void,
+    }
+    impl Command for PaymentCommand {}
+
+    #[derive(Default)]
+    struct Payment {
+        authorized: bool,
+    }
+
+    struct PaymentProcess;
+
+    impl ProcessManager for PaymentProcess {
+        type Event = PaymentEvent;
+        type Command = PaymentCommand;
+        type State = Payment;
+
+        fn start(&mut self, event: &PaymentEvent) -> Option<Payment> {
+            matches!(event, PaymentEvent::Requested).then(Payment::default)
+        }
+
+        fn step(
+            &mut self,
+            state: &mut Payment,
+            event: &PaymentEvent,
+        ) -> ProcessStep<PaymentCommand> {
+            match event {
+                PaymentEvent::Requested => ProcessStep::command(PaymentCommand::Authorize),
+                PaymentEvent::Authorized => {
+                    state.authorized = true;
+                    ProcessStep::command(PaymentCommand::Capture)
+                        .with_timeout(Duration::from_millis(20))
+                }
+                PaymentEvent::Captured => ProcessStep::done(),
+            }
+        }
+
+        fn compensate(&mut self, state: &Payment) -> Option<PaymentCommand> {
+            state.authorized.then_some(PaymentCommand::Void)
+        }
+    }
+
+    fn decide(
+        runner: &mut MachineRunner,
+        event: &dyn Any,
+        cid: CorrelationId,
+    ) -> Option<PaymentCommand> {
+        let cmd = runner.decide_with_middleware(event, cid, &[]).unwrap()?;
+        cmd.into_any().downcast::<PaymentCommand>().ok().map(|c| *c)
+    }
+
+    #[test]
+    fn test_instances_are_keyed_by_correlation_id() {
+        let mut runner = MachineRunner::new_process(PaymentProcess, EventBus::new());
+        let (a, b) = (CorrelationId::new(), CorrelationId::new());
+
+        // Only a starting event opens an instance
+        assert_eq!(decide(&mut runner, &PaymentEvent::Authorized, a), None);
+
+        assert_eq!(
+            decide(&mut runner, &PaymentEvent::Requested, a),
+            Some(PaymentCommand::Authorize)
+        );
+        assert_eq!(
+            decide(&mut runner, &PaymentEvent::Requested, b),
+            Some(PaymentCommand::Authorize)
+        );
+        assert_eq!(decide(&mut runner, &PaymentEvent::Captured, a), None);
+
+        // `a` ended, `b` is still running
+        assert_eq!(decide(&mut runner, &PaymentEvent::Authorized, a), None);
+        assert_eq!(
+            decide(&mut runner, &PaymentEvent::Authorized, b),
+            Some(PaymentCommand::Capture)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_step_timeout_emits_timed_out_and_compensates() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let mut runner = MachineRunner::new_process(PaymentProcess, bus);
+        let cid = CorrelationId::new();
+
+        decide(&mut runner, &PaymentEvent::Requested, cid);
+        decide(&mut runner, &PaymentEvent::Authorized, cid);
+
+        let envelope = events.recv().await.unwrap();
+        assert_eq!(envelope.cid, cid);
+        let timed_out = envelope.downcast_ref::<ProcessTimedOut>().unwrap().clone();
+        assert_eq!(timed_out.after, Duration::from_millis(20));
+        assert!(runner.handles_event(&timed_out));
+
+        assert_eq!(
+            decide(&mut runner, &timed_out, cid),
+            Some(PaymentCommand::Void)
+        );
+        // Compensation ended the instance
+        assert_eq!(decide(&mut runner, &timed_out, cid), None);
+        assert_eq!(decide(&mut runner, &PaymentEvent::Captured, cid), None);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_cancelled_by_next_step() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let mut runner = MachineRunner::new_process(PaymentProcess, bus);
+        let cid = CorrelationId::new();
+
+        decide(&mut runner, &PaymentEvent::Requested, cid);
+        decide(&mut runner, &PaymentEvent::Authorized, cid);
+        decide(&mut runner, &PaymentEvent::Captured, cid);
+
+        let expired = tokio::time::timeout(Duration::from_millis(60), events.recv()).await;
+        assert!(expired.is_err(), "cancelled timeout still fired");
+
+        // A stale expiry is ignored as well
+        let stale = ProcessTimedOut {
+            process: std::any::type_name::<PaymentProcess>(),
+            step: 1,
+            after: Duration::from_millis(20),
+        };
+        assert_eq!(decide(&mut runner, &stale, cid), None);
+    }
+
+    #[tokio::test]
+    async fn test_engine_compensates_stuck_instance() {
+        use crate::{EffectContext, EngineBuilder};
+        use std::sync::{Arc, Mutex};
+
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        let log = executed.clone();
+        let handle = EngineBuilder::new(())
+            .with_process_manager(PaymentProcess)
+            .with_effect_fn::<PaymentCommand, _>(move |cmd, _ctx: EffectContext<()>| {
+                log.lock().unwrap().push(cmd.clone());
+                async move {
+                    match cmd {
+                        PaymentCommand::Authorize => Ok(PaymentEvent::Authorized),
+                        // Capture never reports back, so its step times out
+                        _ => anyhow::bail!("gateway unavailable"),
+                    }
+                }
+            })
+            .build()
+            .start();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        handle.emit(PaymentEvent::Requested);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(
+            *executed.lock().unwrap(),
+            vec![
+                PaymentCommand::Authorize,
+                PaymentCommand::Capture,
+                PaymentCommand::Void
+            ]
+        );
+        handle.abort();
+    }
+}
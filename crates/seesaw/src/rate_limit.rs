@@ -0,0 +1,258 @@
+//! Token-bucket rate limiting for inline effects.
+//!
+//! External APIs often enforce strict QPS limits. Instead of embedding a
+//! limiter in every effect, register one on the dispatcher - per command
+//! type, globally across all inline executions, or both.
+//!
+//! A bucket holds up to `burst` tokens and refills at `permits` per `per`.
+//! Each inline execution takes one token (a batch counts as one). When the
+//! bucket is empty, the [`ThrottleMode`] decides what happens:
+//!
+//! - [`ThrottleMode::Delay`] (default): wait until a token is available,
+//!   then execute.
+//! - [`ThrottleMode::Reject`]: fail immediately with
+//!   [`SeesawError::Throttled`] and emit a [`Throttled`] event carrying the
+//!   command's correlation ID. The failure surfaces as `CommandFailed` with
+//!   category `RateLimited`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect::<GeocodeCommand, _>(GeocodeEffect)
+//!     .with_rate_limit::<GeocodeCommand>(RateLimitPolicy::per_second(10))
+//!     .with_global_rate_limit(
+//!         RateLimitPolicy::per_second(100).with_mode(ThrottleMode::Reject),
+//!     )
+//!     .build();
+//! ```
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use crate::bus::EventBus;
+use crate::core::CorrelationId;
+use crate::error::SeesawError;
+
+// =============================================================================
+// Policy
+// =============================================================================
+
+/// What to do with an execution when its bucket is empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThrottleMode {
+    /// Wait for a token, then execute.
+    #[default]
+    Delay,
+    /// Fail with [`SeesawError::Throttled`] and emit a [`Throttled`] event.
+    Reject,
+}
+
+/// Rate and burst size of a token bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    permits: u32,
+    per: Duration,
+    burst: u32,
+    mode: ThrottleMode,
+}
+
+impl RateLimitPolicy {
+    /// Allow `permits` executions per `per`, with a burst of `permits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` is zero or `per` is zero.
+    pub fn new(permits: u32, per: Duration) -> Self {
+        assert!(permits > 0, "rate limit must allow at least 1 permit");
+        assert!(!per.is_zero(), "rate limit period must be non-zero");
+        Self {
+            permits,
+            per,
+            burst: permits,
+            mode: ThrottleMode::Delay,
+        }
+    }
+
+    /// Allow `permits` executions per second.
+    pub fn per_second(permits: u32) -> Self {
+        Self::new(permits, Duration::from_secs(1))
+    }
+
+    /// Set how many executions may run back-to-back on a full bucket.
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Set what happens when the bucket is empty.
+    pub fn with_mode(mut self, mode: ThrottleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Tokens added per second.
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.permits) / self.per.as_secs_f64()
+    }
+}
+
+// =============================================================================
+// Events
+// =============================================================================
+
+/// Emitted when an execution is rejected by a `Reject`-mode rate limit.
+#[derive(Debug, Clone)]
+pub struct Throttled {
+    /// Human-readable type name of the rejected command.
+    pub command_type: &'static str,
+    /// Time until the bucket has a token again.
+    pub retry_after: Duration,
+}
+
+// =============================================================================
+// Limiter
+// =============================================================================
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket shared by all dispatches it applies to.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    policy: RateLimitPolicy,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(policy: RateLimitPolicy) -> Self {
+        Self {
+            policy,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(policy.burst),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, or report how long until one is available.
+    pub(crate) fn try_take(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let rate = self.policy.refill_rate();
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(f64::from(self.policy.burst));
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// Take a token for one execution, applying the policy's throttle mode.
+    pub(crate) async fn acquire(
+        &self,
+        type_name: &'static str,
+        cid: CorrelationId,
+        bus: &EventBus,
+    ) -> Result<(), SeesawError> {
+        loop {
+            let wait = match self.try_take() {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+
+            match self.policy.mode {
+                ThrottleMode::Delay => {
+                    debug!(
+                        command = type_name,
+                        ?wait,
+                        "rate limited, delaying execution"
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                ThrottleMode::Reject => {
+                    debug!(command = type_name, retry_after = ?wait, "rate limited, rejecting");
+                    bus.emit_with_correlation(
+                        Throttled {
+                            command_type: type_name,
+                            retry_after: wait,
+                        },
+                        cid,
+                    );
+                    return Err(SeesawError::Throttled {
+                        type_name,
+                        retry_after: wait,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_empty() {
+        let limiter = RateLimiter::new(RateLimitPolicy::per_second(1).with_burst(2));
+
+        assert!(limiter.try_take().is_ok());
+        assert!(limiter.try_take().is_ok());
+        let wait = limiter.try_take().unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_delay_mode_waits_for_refill() {
+        let limiter = RateLimiter::new(RateLimitPolicy::new(1, Duration::from_millis(30)));
+        let bus = EventBus::new();
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter
+                .acquire("TestCommand", CorrelationId::NONE, &bus)
+                .await
+                .unwrap();
+        }
+
+        // First token is free, the next two each wait for a refill
+        assert!(start.elapsed() >= Duration::from_millis(55));
+    }
+
+    #[tokio::test]
+    async fn test_reject_mode_emits_throttled() {
+        let limiter =
+            RateLimiter::new(RateLimitPolicy::per_second(1).with_mode(ThrottleMode::Reject));
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let cid = CorrelationId::new();
+
+        limiter.acquire("TestCommand", cid, &bus).await.unwrap();
+        let err = limiter.acquire("TestCommand", cid, &bus).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            SeesawError::Throttled {
+                type_name: "TestCommand",
+                ..
+            }
+        ));
+        let envelope = rx.recv().await.unwrap();
+        assert_eq!(envelope.cid, cid);
+        assert_eq!(
+            envelope.downcast_ref::<Throttled>().unwrap().command_type,
+            "TestCommand"
+        );
+    }
+}
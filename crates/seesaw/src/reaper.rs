@@ -0,0 +1,162 @@
+//! Reapers - periodic recovery scans run alongside an engine.
+//!
+//! Events are in-memory, so a crash between "order placed" and "payment
+//! requested" leaves the order stuck in its status field with nothing left
+//! to move it along. A reaper finds such entities and emits the events that
+//! put them back on track:
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_reaper(Duration::from_secs(60), |ctx: ReaperContext<Deps>| async move {
+//!         for order_id in ctx.deps().db.orders_stuck_in("placed", minutes(5)).await? {
+//!             ctx.emit(OrderEvent::Placed { order_id });
+//!         }
+//!         Ok(())
+//!     })
+//!     .build();
+//! ```
+//!
+//! Each reaper runs once the runtime is listening, then every interval,
+//! until the runtime stops or the engine is aborted. Runs of one reaper
+//! never overlap, and an error or panic is logged without stopping it.
+//! Machines must tolerate the re-emitted events: a reaper cannot tell a
+//! stuck entity from one whose events are still in flight.
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::bus::EventBus;
+use crate::core::Event;
+use crate::health::HealthMonitor;
+
+/// What a reaper run gets: the engine's dependencies and bus.
+pub struct ReaperContext<D> {
+    deps: Arc<D>,
+    bus: EventBus,
+}
+
+impl<D> Clone for ReaperContext<D> {
+    fn clone(&self) -> Self {
+        Self {
+            deps: self.deps.clone(),
+            bus: self.bus.clone(),
+        }
+    }
+}
+
+impl<D> ReaperContext<D> {
+    pub(crate) fn new(deps: Arc<D>, bus: EventBus) -> Self {
+        Self { deps, bus }
+    }
+
+    /// Get the engine's dependencies.
+    pub fn deps(&self) -> &D {
+        &self.deps
+    }
+
+    /// Get the engine's event bus.
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
+
+    /// Emit a corrective event to the engine.
+    pub fn emit<E: Event>(&self, event: E) {
+        self.bus.emit(event);
+    }
+}
+
+impl<D> std::fmt::Debug for ReaperContext<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReaperContext").finish_non_exhaustive()
+    }
+}
+
+/// A reaper function, boxed.
+type ReaperFn<D> = Arc<dyn Fn(ReaperContext<D>) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// A reaper registered on an `EngineBuilder`, not yet running.
+pub(crate) struct Reaper<D> {
+    name: &'static str,
+    interval: Duration,
+    run: ReaperFn<D>,
+}
+
+impl<D: Send + Sync + 'static> Reaper<D> {
+    pub(crate) fn new<F, Fut>(interval: Duration, reaper: F) -> Self
+    where
+        F: Fn(ReaperContext<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name: std::any::type_name::<F>(),
+            interval,
+            run: Arc::new(move |ctx| reaper(ctx).boxed()),
+        }
+    }
+
+    /// Run the reaper every interval while the runtime reported by `health`
+    /// is running.
+    pub(crate) fn spawn(self, ctx: ReaperContext<D>, health: Arc<HealthMonitor>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            // Events emitted before the runtime subscribes would be lost
+            while !health.is_running() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+
+            let mut ticker = tokio::time::interval(self.interval.max(Duration::from_millis(1)));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if !health.is_running() {
+                    debug!(reaper = self.name, "runtime stopped, stopping reaper");
+                    return;
+                }
+                match AssertUnwindSafe((self.run)(ctx.clone()))
+                    .catch_unwind()
+                    .await
+                {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!(reaper = self.name, error = %e, "reaper failed"),
+                    Err(_) => warn!(reaper = self.name, "reaper panicked"),
+                }
+            }
+        })
+    }
+}
+
+/// Running reaper tasks of an engine.
+pub(crate) struct ReaperTasks {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ReaperTasks {
+    /// Start `reapers` for the runtime reported by `health`.
+    pub(crate) fn spawn<D: Send + Sync + 'static>(
+        reapers: Vec<Reaper<D>>,
+        ctx: ReaperContext<D>,
+        health: &Arc<HealthMonitor>,
+    ) -> Self {
+        Self {
+            tasks: reapers
+                .into_iter()
+                .map(|reaper| reaper.spawn(ctx.clone(), health.clone()))
+                .collect(),
+        }
+    }
+
+    /// Stop every reaper, cancelling runs in progress.
+    pub(crate) fn abort(&self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Redaction of secrets from error messages before they leave the process.
+//!
+//! `anyhow` chains carry whatever their sources put in them: a connection
+//! error quotes the database URL, an HTTP client echoes the request's query
+//! string. A [`Redactor`] scrubs those messages before the dispatcher
+//! reports a failure to audit records and traces, and before a
+//! [`JobWorker`](crate::JobWorker) persists it as a job's error or announces
+//! it in [`JobDeadLettered`](crate::JobDeadLettered) and
+//! [`JobHeartbeatMissed`](crate::JobHeartbeatMissed).
+//!
+//! The default redactor removes:
+//!
+//! - URL credentials: `postgres://app:hunter2@db/app` becomes
+//!   `postgres://[REDACTED]@db/app`
+//! - Values of sensitive keys, in `key=value`, `key: value` and JSON form:
+//!   `password`, `passwd`, `secret`, `token`, `api_key`, `apikey`,
+//!   `access_key` and `authorization`, matched case-insensitively and as
+//!   part of longer names such as `db_password`
+//! - Bearer tokens: `Bearer eyJhb...` becomes `Bearer [REDACTED]`
+//!
+//! Known secret values and further key names are added to the deny-list,
+//! and anything else, such as a regex, is plugged in as a rule:
+//!
+//! ```ignore
+//! let redactor = Redactor::new()
+//!     .with_secret(&config.stripe_key)
+//!     .with_sensitive_key("x-signature")
+//!     .with_rule(|message| CARD_NUMBER.replace_all(message, "[CARD]").into_owned());
+//!
+//! let engine = EngineBuilder::new(deps).with_redactor(redactor).build();
+//! ```
+//!
+//! Redaction is best-effort: it cannot recognize a secret that looks like
+//! ordinary text. Keep secrets out of error messages where you can.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Replacement for redacted text.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key names whose values the default redactor removes.
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "access_key",
+    "authorization",
+];
+
+type Rule = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Scrubs secrets from error messages. See the [module docs](self).
+#[derive(Clone)]
+pub struct Redactor {
+    /// Whether URL credentials and bearer tokens are removed.
+    builtins: bool,
+    /// Lowercased key names whose values are removed.
+    sensitive_keys: Vec<String>,
+    /// Literal values removed wherever they appear.
+    secrets: Vec<String>,
+    /// Custom rules, applied in order after everything else.
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    /// Create a redactor with the built-in rules.
+    pub fn new() -> Self {
+        Self {
+            builtins: true,
+            sensitive_keys: SENSITIVE_KEYS.iter().map(|key| key.to_string()).collect(),
+            secrets: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Create a redactor that leaves messages unchanged until configured.
+    pub fn none() -> Self {
+        Self {
+            builtins: false,
+            sensitive_keys: Vec::new(),
+            secrets: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Remove `secret` wherever it appears. Empty values are ignored.
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+        if !secret.is_empty() {
+            self.secrets.push(secret);
+        }
+        self
+    }
+
+    /// Remove the value of `key` in `key=value`, `key: value` and JSON form.
+    pub fn with_sensitive_key(mut self, key: impl Into<String>) -> Self {
+        self.sensitive_keys.push(key.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Apply `rule` to every message after the built-in rules and the
+    /// deny-list.
+    pub fn with_rule<F>(mut self, rule: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Redact `message`.
+    pub fn redact(&self, message: &str) -> String {
+        let mut message = message.to_string();
+        for secret in &self.secrets {
+            if message.contains(secret.as_str()) {
+                message = message.replace(secret.as_str(), REDACTED);
+            }
+        }
+
+        let mut ranges = Vec::new();
+        if self.builtins {
+            url_credentials(&message, &mut ranges);
+            bearer_tokens(&message, &mut ranges);
+        }
+        sensitive_values(&message, &self.sensitive_keys, &mut ranges);
+        let mut message = splice(&message, ranges);
+
+        for rule in &self.rules {
+            message = rule(&message);
+        }
+        message
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redactor")
+            .field("builtins", &self.builtins)
+            .field("sensitive_keys", &self.sensitive_keys)
+            .field("secrets", &self.secrets.len())
+            .field("rules", &self.rules.len())
+            .finish()
+    }
+}
+
+/// Whether `c` ends an unquoted value or URL authority.
+fn is_delimiter(c: u8) -> bool {
+    c.is_ascii_whitespace() || matches!(c, b'&' | b',' | b';' | b'"' | b'\'' | b')' | b']' | b'}')
+}
+
+/// Collect the user info of every URL in `message`.
+fn url_credentials(message: &str, ranges: &mut Vec<Range<usize>>) {
+    let bytes = message.as_bytes();
+    for (index, _) in message.match_indices("://") {
+        let start = index + 3;
+        let end = bytes[start..]
+            .iter()
+            .position(|&c| is_delimiter(c) || matches!(c, b'/' | b'?' | b'#'))
+            .map_or(bytes.len(), |len| start + len);
+        if let Some(at) = message[start..end].rfind('@') {
+            ranges.push(start..start + at);
+        }
+    }
+}
+
+/// Collect the token following every `Bearer ` in `message`.
+fn bearer_tokens(message: &str, ranges: &mut Vec<Range<usize>>) {
+    let lower = message.to_ascii_lowercase();
+    let bytes = message.as_bytes();
+    for (index, _) in lower.match_indices("bearer") {
+        let mut start = index + "bearer".len();
+        if bytes.get(start) != Some(&b' ') {
+            continue;
+        }
+        while bytes.get(start) == Some(&b' ') {
+            start += 1;
+        }
+        let end = value_end(bytes, start);
+        if end > start {
+            ranges.push(start..end);
+        }
+    }
+}
+
+/// Collect the value assigned to each of `keys` in `message`.
+fn sensitive_values(message: &str, keys: &[String], ranges: &mut Vec<Range<usize>>) {
+    let lower = message.to_ascii_lowercase();
+    let bytes = message.as_bytes();
+    for key in keys.iter().filter(|key| !key.is_empty()) {
+        for (index, _) in lower.match_indices(key.as_str()) {
+            let mut cursor = index + key.len();
+            // The rest of a longer name, like `password_hash`, then the
+            // closing quote of a JSON key
+            while bytes
+                .get(cursor)
+                .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-'))
+            {
+                cursor += 1;
+            }
+            if matches!(bytes.get(cursor), Some(b'"' | b'\'')) {
+                cursor += 1;
+            }
+            while bytes.get(cursor) == Some(&b' ') {
+                cursor += 1;
+            }
+            if !matches!(bytes.get(cursor), Some(b'=' | b':')) {
+                continue;
+            }
+            cursor += 1;
+            while bytes.get(cursor) == Some(&b' ') {
+                cursor += 1;
+            }
+            // Keep the scheme of `Authorization: Bearer <token>`
+            for scheme in ["bearer ", "basic "] {
+                if lower[cursor..].starts_with(scheme) {
+                    cursor += scheme.len();
+                }
+            }
+
+            let (start, end) = match bytes.get(cursor) {
+                Some(&quote) if matches!(quote, b'"' | b'\'') => {
+                    let start = cursor + 1;
+                    let end = bytes[start..]
+                        .iter()
+                        .position(|&c| c == quote)
+                        .map_or(bytes.len(), |len| start + len);
+                    (start, end)
+                }
+                _ => (cursor, value_end(bytes, cursor)),
+            };
+            if end > start {
+                ranges.push(start..end);
+            }
+        }
+    }
+}
+
+/// The end of the unquoted value starting at `start`.
+fn value_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&c| is_delimiter(c))
+        .map_or(bytes.len(), |len| start + len)
+}
+
+/// Replace `ranges` of `message` with [`REDACTED`], merging overlaps.
+fn splice(message: &str, mut ranges: Vec<Range<usize>>) -> String {
+    if ranges.is_empty() {
+        return message.to_string();
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    let mut redacted = String::with_capacity(message.len());
+    let mut copied = 0;
+    for range in ranges {
+        if range.end <= copied {
+            continue;
+        }
+        if range.start >= copied {
+            redacted.push_str(&message[copied..range.start]);
+            redacted.push_str(REDACTED);
+        }
+        copied = range.end;
+    }
+    redacted.push_str(&message[copied..]);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_url_credentials() {
+        let redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("connecting to postgres://app:hunter2@db:5432/app failed"),
+            "connecting to postgres://[REDACTED]@db:5432/app failed"
+        );
+        assert_eq!(
+            redactor.redact("GET https://api.example.com/v1 failed"),
+            "GET https://api.example.com/v1 failed"
+        );
+    }
+
+    #[test]
+    fn test_redacts_sensitive_values() {
+        let redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("request to /charge?api_key=sk_live_123&amount=5 failed"),
+            "request to /charge?api_key=[REDACTED]&amount=5 failed"
+        );
+        assert_eq!(
+            redactor.redact(r#"bad config: {"db_password": "hunter2", "port": 5432}"#),
+            r#"bad config: {"db_password": "[REDACTED]", "port": 5432}"#
+        );
+        assert_eq!(
+            redactor.redact("Authorization: Bearer eyJhbGciOi.payload rejected"),
+            "Authorization: Bearer [REDACTED] rejected"
+        );
+        assert_eq!(redactor.redact("token expired"), "token expired");
+    }
+
+    #[test]
+    fn test_redacts_bearer_tokens() {
+        let redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("upstream rejected Bearer abc.def.ghi"),
+            "upstream rejected Bearer [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_deny_list_and_rules() {
+        let redactor = Redactor::none()
+            .with_secret("sk_live_123")
+            .with_sensitive_key("X-Signature")
+            .with_rule(|message| message.replace("4242", "****"));
+        assert_eq!(
+            redactor.redact("card 4242 charged with sk_live_123, x-signature=abc"),
+            "card **** charged with [REDACTED], x-signature=[REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact("postgres://app:hunter2@db/app"),
+            "postgres://app:hunter2@db/app"
+        );
+    }
+}
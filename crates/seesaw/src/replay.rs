@@ -0,0 +1,276 @@
+//! Event replay - rebuilding machine state from a persisted event log.
+//!
+//! Machines keep their state in memory, so a restarted process starts with
+//! empty machines. [`Runtime::replay`](crate::Runtime::replay) feeds historical
+//! events from an [`EventLog`] through every machine *without dispatching the
+//! commands they decide*: the effects already ran the first time around.
+//!
+//! Replay before starting the runtime, then resume live traffic:
+//!
+//! ```ignore
+//! let mut engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::new())
+//!     .with_effect::<ShipCommand, _>(ShipEffect)
+//!     .build();
+//!
+//! let report = engine.replay(&log, None).await?;
+//! info!(events = report.events, "machine state rebuilt");
+//!
+//! let handle = engine.start();
+//! ```
+//!
+//! Machines must be deterministic for replay to reproduce their state -
+//! which they already are, since `decide` is pure.
+
+use std::sync::RwLock;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::core::EventEnvelope;
+
+/// Number of events requested from the log per read during replay.
+pub(crate) const REPLAY_PAGE_SIZE: usize = 1000;
+
+// =============================================================================
+// Event Log
+// =============================================================================
+
+/// An event read back from an [`EventLog`].
+#[derive(Debug, Clone)]
+pub struct LoggedEvent {
+    /// Position in the log. Strictly increasing in append order.
+    pub position: u64,
+    /// When the event was recorded.
+    pub recorded_at: DateTime<Utc>,
+    /// The event, deserialized into its envelope.
+    pub envelope: EventEnvelope,
+}
+
+/// A persisted, ordered log of events.
+///
+/// Implementations typically deserialize stored rows into typed events
+/// through a registry, like the outbox publisher does.
+///
+/// # Example Implementation
+///
+/// ```ignore
+/// #[async_trait]
+/// impl EventLog for PgEventLog {
+///     async fn read(&self, from: u64, limit: usize) -> Result<Vec<LoggedEvent>> {
+///         let rows = sqlx::query("SELECT position, event_type, payload, correlation_id, recorded_at
+///                                 FROM event_log WHERE position >= $1
+///                                 ORDER BY position LIMIT $2")
+///             .bind(from as i64)
+///             .bind(limit as i64)
+///             .fetch_all(&self.pool)
+///             .await?;
+///         rows.iter().map(|row| self.registry.decode(row)).collect()
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait EventLog: Send + Sync {
+    /// Read up to `limit` events with `position >= from`, in position order.
+    ///
+    /// Returning fewer than `limit` events signals the end of the log.
+    async fn read(&self, from: u64, limit: usize) -> Result<Vec<LoggedEvent>>;
+}
+
+/// In-memory [`EventLog`], for tests and single-process setups.
+#[derive(Debug, Default)]
+pub struct MemoryEventLog {
+    events: RwLock<Vec<LoggedEvent>>,
+}
+
+impl MemoryEventLog {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an envelope, returning its position.
+    pub fn append(&self, envelope: EventEnvelope) -> u64 {
+        let mut events = self.events.write().unwrap_or_else(|e| e.into_inner());
+        let position = events.len() as u64;
+        events.push(LoggedEvent {
+            position,
+            recorded_at: Utc::now(),
+            envelope,
+        });
+        position
+    }
+
+    /// Number of events in the log.
+    pub fn len(&self) -> usize {
+        self.events.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check if the log is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl EventLog for MemoryEventLog {
+    async fn read(&self, from: u64, limit: usize) -> Result<Vec<LoggedEvent>> {
+        let events = self.events.read().unwrap_or_else(|e| e.into_inner());
+        Ok(events
+            .iter()
+            .skip(from.min(events.len() as u64) as usize)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+// =============================================================================
+// Replay Report
+// =============================================================================
+
+/// Summary of a completed replay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Events fed through the machines.
+    pub events: usize,
+    /// Commands decided during replay and discarded instead of dispatched.
+    pub commands_suppressed: usize,
+    /// `decide` calls that panicked (logged and skipped).
+    pub panics: usize,
+    /// Position of the last replayed event, if any.
+    pub last_position: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::core::Command;
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::machine::Machine;
+    use crate::runtime::Runtime;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone)]
+    struct Deposited(u32);
+
+    #[derive(Debug, Clone)]
+    struct RecordBalance(u32);
+    impl Command for RecordBalance {}
+
+    #[derive(Debug, Clone)]
+    struct BalanceRecorded;
+
+    #[derive(Default)]
+    struct BalanceMachine {
+        balance: u32,
+    }
+
+    impl Machine for BalanceMachine {
+        type Event = Deposited;
+        type Command = RecordBalance;
+
+        fn decide(&mut self, event: &Deposited) -> Option<RecordBalance> {
+            self.balance += event.0;
+            Some(RecordBalance(self.balance))
+        }
+    }
+
+    struct RecordEffect {
+        calls: Arc<AtomicUsize>,
+        balances: Arc<Mutex<Vec<u32>>>,
+    }
+
+    #[async_trait]
+    impl Effect<RecordBalance, ()> for RecordEffect {
+        type Event = BalanceRecorded;
+
+        async fn execute(
+            &self,
+            cmd: RecordBalance,
+            _ctx: EffectContext<()>,
+        ) -> Result<BalanceRecorded> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.balances.lock().unwrap().push(cmd.0);
+            Ok(BalanceRecorded)
+        }
+    }
+
+    fn deposits(amounts: &[u32]) -> MemoryEventLog {
+        let log = MemoryEventLog::new();
+        for amount in amounts {
+            log.append(EventEnvelope::new_random(Deposited(*amount)));
+        }
+        log
+    }
+
+    #[tokio::test]
+    async fn test_memory_log_reads_pages_in_order() {
+        let log = deposits(&[1, 2, 3]);
+
+        let page = log.read(1, 10).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].position, 1);
+        assert_eq!(page[0].envelope.downcast_ref::<Deposited>().unwrap().0, 2);
+        assert!(log.read(5, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_rebuilds_state_without_dispatching() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let balances = Arc::new(Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<RecordBalance, _>(RecordEffect {
+                calls: calls.clone(),
+                balances: balances.clone(),
+            });
+        let mut runtime =
+            Runtime::new(dispatcher, bus.clone()).with_machine(BalanceMachine::default());
+
+        let report = runtime
+            .replay(&deposits(&[10, 20, 30]), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            report,
+            ReplayReport {
+                events: 3,
+                commands_suppressed: 3,
+                panics: 0,
+                last_position: Some(2),
+            }
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        // Live traffic continues from the rebuilt state
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Deposited(5));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(*balances.lock().unwrap(), vec![65]);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_replay_stops_at_until_position() {
+        let bus = EventBus::new();
+        let mut runtime = Runtime::new(Dispatcher::new((), bus.clone()), bus)
+            .with_machine(BalanceMachine::default());
+
+        let report = runtime
+            .replay(&deposits(&[1, 2, 3, 4]), Some(1))
+            .await
+            .unwrap();
+
+        assert_eq!(report.events, 2);
+        assert_eq!(report.last_position, Some(1));
+    }
+}
@@ -32,14 +32,15 @@
 //! ).await?;
 //! ```
 
+use std::future::Future;
 use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use tokio::time::timeout;
 
-use crate::bus::EventBus;
-use crate::core::{CorrelationId, EnvelopeMatch, Event};
-use crate::error::CommandFailed;
+use crate::bus::{EventBus, RecoveringReceiver};
+use crate::core::{CorrelationId, EnvelopeMatch, Event, EventEnvelope};
+use crate::error::{CommandFailed, SeesawError};
 
 /// Default timeout for request/response operations.
 pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
@@ -58,7 +59,13 @@ pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 ///
 /// # Returns
 ///
-/// The extracted response, or an error on timeout/bus closure.
+/// The extracted response, or an error on timeout/bus closure, or
+/// `SeesawError::BusFull` if the bus's backpressure policy rejected the request.
+///
+/// A correlated `CommandFailed` fails the request with its `safe_message`;
+/// the event itself stays reachable with `err.downcast_ref::<CommandFailed>()`,
+/// as does `SeesawError::Timeout` on timeout, so edges can map failures to
+/// status codes.
 ///
 /// # Example
 ///
@@ -100,8 +107,9 @@ where
     // Subscribe before emitting to avoid race
     let mut receiver = bus.subscribe();
 
-    // Emit with correlation
-    bus.emit_with_correlation(request, cid);
+    // Emit with correlation, surfacing backpressure rejection immediately
+    bus.emit_envelope_async(EventEnvelope::new(cid, request))
+        .await?;
 
     // Wait for matching response
     let result = timeout(request_timeout, async {
@@ -120,7 +128,9 @@ where
 
                     // Auto-handle CommandFailed events so edges don't have to
                     if let Some(failed) = envelope.downcast_ref::<CommandFailed>() {
-                        return Err(anyhow!("{}", failed.safe_message));
+                        return Err(
+                            anyhow::Error::new(failed.clone()).context(failed.safe_message.clone())
+                        );
                     }
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Closed) => {
@@ -137,7 +147,87 @@ where
 
     match result {
         Ok(res) => res,
-        Err(_) => Err(anyhow!("request timed out after {:?}", request_timeout)),
+        Err(_) => Err(anyhow::Error::new(SeesawError::Timeout {
+            duration: request_timeout,
+        })
+        .context(format!("request timed out after {:?}", request_timeout))),
+    }
+}
+
+/// Wait for the first `E` event on `bus` for which `matcher` returns `true`.
+///
+/// Unlike [`dispatch_request`] this emits nothing, so the event may come
+/// from anywhere: another request, a reaper, or a background job finishing
+/// minutes later on a worker sharing the bus. Events of any correlation ID
+/// are considered; with `cid`, only those correlated with it.
+///
+/// The bus is subscribed when this is called, not when the future is first
+/// polled: create the future before emitting whatever produces the event.
+/// Lagging past the bus's retention can miss the event, and the wait then
+/// ends with `SeesawError::Timeout`, like one that never came.
+///
+/// # Example
+///
+/// ```ignore
+/// let summarized = await_event(
+///     &bus,
+///     None,
+///     move |e: &Summarized| e.task_id == task_id,
+///     Duration::from_secs(300),
+/// );
+/// bus.emit(SummarizeRequested { task_id });
+/// let summary = summarized.await?.summary;
+/// ```
+pub fn await_event<E, F>(
+    bus: &EventBus,
+    cid: Option<CorrelationId>,
+    matcher: F,
+    wait: Duration,
+) -> impl Future<Output = Result<E>> + Send + 'static
+where
+    E: Event + Clone,
+    F: Fn(&E) -> bool + Send + 'static,
+{
+    let receiver = bus.subscribe_recovering();
+    async move {
+        match timeout(wait, first_match(receiver, cid, matcher)).await {
+            Ok(found) => found,
+            Err(_) => Err(anyhow::Error::new(SeesawError::Timeout { duration: wait })
+                .context(format!(
+                    "no matching {} within {:?}",
+                    std::any::type_name::<E>(),
+                    wait
+                ))),
+        }
+    }
+}
+
+async fn first_match<E, F>(
+    mut receiver: RecoveringReceiver,
+    cid: Option<CorrelationId>,
+    matcher: F,
+) -> Result<E>
+where
+    E: Event + Clone,
+    F: Fn(&E) -> bool,
+{
+    loop {
+        match receiver.recv().await {
+            Ok(envelope) if cid.is_none_or(|cid| envelope.cid == cid) => {
+                if let Some(event) = envelope.downcast_ref::<E>() {
+                    if matcher(event) {
+                        return Ok(event.clone());
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                return Err(anyhow!("event bus closed"));
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!(lagged = n, "event waiter lagged, events may be missed");
+            }
+        }
     }
 }
 
@@ -304,6 +394,7 @@ mod tests {
                             command_type: "TestCommand",
                             category: SafeErrorCategory::Unauthorized,
                             safe_message: "Not authorized to perform this action".into(),
+                            retryable: false,
                             cid: envelope.cid,
                         },
                         envelope.cid,
@@ -325,11 +416,57 @@ mod tests {
         .await;
 
         assert!(result.is_err(), "Expected error from CommandFailed");
-        let err_msg = result.unwrap_err().to_string();
+        let err = result.unwrap_err();
+        let err_msg = err.to_string();
         assert!(
             err_msg.contains("Not authorized"),
             "Expected auth error, got: {}",
             err_msg
         );
+        let failed = err.downcast_ref::<CommandFailed>().unwrap();
+        assert_eq!(failed.category, SafeErrorCategory::Unauthorized);
+    }
+
+    #[tokio::test]
+    async fn test_await_event_matches_without_emitting() {
+        let bus = EventBus::new();
+        let cid = CorrelationId::new();
+        let any = await_event(
+            &bus,
+            None,
+            |e: &TestResponse| e.result > 1,
+            Duration::from_secs(1),
+        );
+        let correlated = await_event(
+            &bus,
+            Some(cid),
+            |_: &TestResponse| true,
+            Duration::from_secs(1),
+        );
+
+        bus.emit(TestResponse { result: 1 });
+        bus.emit(TestResponse { result: 2 });
+        bus.emit_with_correlation(TestResponse { result: 3 }, cid);
+
+        assert_eq!(any.await.unwrap().result, 2);
+        assert_eq!(correlated.await.unwrap().result, 3);
+    }
+
+    #[tokio::test]
+    async fn test_await_event_times_out() {
+        let bus = EventBus::new();
+        let err = await_event(
+            &bus,
+            None,
+            |_: &TestResponse| true,
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<SeesawError>(),
+            Some(SeesawError::Timeout { .. })
+        ));
     }
 }
@@ -0,0 +1,498 @@
+//! Retry policies for inline effects.
+//!
+//! Background and scheduled commands get durability from the job store: a
+//! failed job is retried by the worker according to its `JobSpec`. Inline
+//! effects have no such safety net - a single transient failure (a dropped
+//! connection, a 503 from a third party) immediately becomes `CommandFailed`.
+//!
+//! [`RetryingEffect`] closes that gap. It wraps any effect and re-runs it with
+//! exponential backoff and jitter while failures look transient, surfacing
+//! the last error only once the [`RetryPolicy`] gives up.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use seesaw::{EngineBuilder, RetryPolicy, RetryingEffect};
+//!
+//! let policy = RetryPolicy::new()
+//!     .with_max_attempts(5)
+//!     .with_initial_backoff(Duration::from_millis(50));
+//!
+//! let engine = EngineBuilder::new(deps)
+//!     .with_effect_retry::<FetchCommand, _>(FetchEffect, policy)
+//!     .build();
+//! ```
+//!
+//! # Idempotency
+//!
+//! Retrying re-executes the effect with a clone of the original command.
+//! Effects wrapped in a retry policy must be idempotent - use idempotency
+//! keys or status checks so that a partially completed attempt followed by a
+//! retry does not apply its writes twice.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::core::Command;
+use crate::effect_impl::{Effect, EffectContext};
+use crate::error::CommandFailed;
+
+// =============================================================================
+// Retry Policy
+// =============================================================================
+
+/// Predicate deciding whether an error is worth another attempt.
+type RetryPredicate = Arc<dyn Fn(&anyhow::Error) -> bool + Send + Sync>;
+
+/// How many times, and how patiently, a failing effect is retried.
+///
+/// The delay before retry `n` (1-based) is
+/// `initial_backoff * multiplier^(n - 1)`, capped at `max_backoff`, then
+/// scaled by a random factor in `[1 - jitter, 1 + jitter]` so that many
+/// effects failing together do not retry in lockstep.
+///
+/// By default only transient failures are retried: errors whose
+/// [`SafeErrorCategory`](crate::SafeErrorCategory) is `Validation`,
+/// `NotFound` or `Unauthorized` fail immediately, since retrying them
+/// cannot change the outcome.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    retry_if: RetryPredicate,
+}
+
+impl RetryPolicy {
+    /// Create a policy with the defaults: 3 attempts, 100ms initial backoff
+    /// doubling up to 10s, and 20% jitter.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retry_if: Arc::new(is_transient),
+        }
+    }
+
+    /// Set the total number of attempts, including the first one.
+    ///
+    /// A value of 0 is treated as 1 (the effect always runs once).
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound for the delay between attempts.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay grows by after each retry.
+    ///
+    /// Values below 1.0 are clamped to 1.0 (constant backoff).
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set the jitter fraction, clamped to `[0.0, 1.0]`.
+    ///
+    /// `0.0` disables jitter; `0.2` spreads each delay over ±20%.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Replace the transient-failure classifier.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let policy = RetryPolicy::new().with_retry_if(|e| {
+    ///     e.downcast_ref::<reqwest::Error>()
+    ///         .is_some_and(|e| e.is_timeout() || e.is_connect())
+    /// });
+    /// ```
+    pub fn with_retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&anyhow::Error) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Arc::new(predicate);
+        self
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether `error` should be retried under this policy.
+    pub fn should_retry(&self, error: &anyhow::Error) -> bool {
+        (self.retry_if)(error)
+    }
+
+    /// Delay before retry number `retry` (1-based), without jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let scaled = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = scaled.min(self.max_backoff.as_secs_f64());
+        Duration::try_from_secs_f64(capped).unwrap_or(self.max_backoff)
+    }
+
+    /// Delay before retry number `retry` (1-based), with jitter applied.
+    fn jittered_backoff(&self, retry: u32) -> Duration {
+        let base = self.backoff(retry);
+        if self.jitter == 0.0 {
+            return base;
+        }
+        let factor = 1.0 + self.jitter * (fastrand::f64() * 2.0 - 1.0);
+        base.mul_f64(factor)
+    }
+
+    /// Run `operation` until it succeeds, fails permanently, or attempts run
+    /// out, returning its last result.
+    ///
+    /// Before each retry, `on_retry` is called with the attempt that failed
+    /// (1-based), its error and the jittered delay about to be slept, e.g.
+    /// to log or count it.
+    pub async fn retry<T, F, Fut>(
+        &self,
+        mut operation: F,
+        mut on_retry: impl FnMut(u32, &anyhow::Error, Duration),
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < self.max_attempts && self.should_retry(&error) => {
+                    let delay = self.jittered_backoff(attempt);
+                    on_retry(attempt, &error, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// [`retry`](Self::retry) an effect's `operation`, logging each retry.
+    async fn run<T, F, Fut>(&self, type_name: &'static str, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.retry(operation, |attempt, error, delay| {
+            warn!(
+                command = type_name,
+                attempt,
+                max_attempts = self.max_attempts,
+                delay_ms = delay.as_millis() as u64,
+                error = %error,
+                "effect failed, retrying"
+            );
+        })
+        .await
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Default classifier: retry unless the error's category is deterministic.
+fn is_transient(error: &anyhow::Error) -> bool {
+    CommandFailed::categorize_and_sanitize(error)
+        .0
+        .is_transient()
+}
+
+// =============================================================================
+// Retrying Effect
+// =============================================================================
+
+/// Effect decorator that retries the wrapped effect under a [`RetryPolicy`].
+///
+/// Requires the command to be `Clone`, since every attempt consumes its own
+/// copy. Batches are retried as a whole: if the inner `execute_batch` fails,
+/// the full batch is re-run, so batch effects must be idempotent per command.
+///
+/// A timeout registered with `with_effect_timeout` bounds the whole retry
+/// loop, not each attempt.
+pub struct RetryingEffect<E> {
+    inner: E,
+    policy: RetryPolicy,
+}
+
+impl<E> RetryingEffect<E> {
+    /// Wrap `inner` so that transient failures are retried under `policy`.
+    pub fn new(inner: E, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Get the retry policy.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl<E> std::fmt::Debug for RetryingEffect<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryingEffect")
+            .field("effect", &std::any::type_name::<E>())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<C, D, E> Effect<C, D> for RetryingEffect<E>
+where
+    C: Command + Clone,
+    D: Send + Sync + 'static,
+    E: Effect<C, D>,
+{
+    type Event = E::Event;
+
+    async fn execute(&self, command: C, ctx: EffectContext<D>) -> Result<Self::Event> {
+        self.policy
+            .run(std::any::type_name::<C>(), || {
+                self.inner.execute(command.clone(), ctx.clone())
+            })
+            .await
+    }
+
+    async fn execute_batch(
+        &self,
+        commands: Vec<C>,
+        ctx: EffectContext<D>,
+    ) -> Result<Vec<Self::Event>>
+    where
+        D: Send + Sync + 'static,
+    {
+        self.policy
+            .run(std::any::type_name::<C>(), || {
+                self.inner.execute_batch(commands.clone(), ctx.clone())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::error::SeesawError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone)]
+    struct PingCommand;
+    impl Command for PingCommand {}
+
+    #[derive(Debug, Clone)]
+    struct PongEvent;
+
+    /// Fails the first `failures` attempts with the error built by `make_error`.
+    struct FlakyEffect {
+        attempts: Arc<AtomicUsize>,
+        failures: usize,
+        make_error: fn() -> anyhow::Error,
+    }
+
+    #[async_trait]
+    impl Effect<PingCommand, ()> for FlakyEffect {
+        type Event = PongEvent;
+
+        async fn execute(&self, _cmd: PingCommand, _ctx: EffectContext<()>) -> Result<PongEvent> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures {
+                return Err((self.make_error)());
+            }
+            Ok(PongEvent)
+        }
+    }
+
+    fn ctx() -> EffectContext<()> {
+        EffectContext::new(Arc::new(()), EventBus::new())
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_jitter(0.0)
+    }
+
+    fn flaky(
+        failures: usize,
+        make_error: fn() -> anyhow::Error,
+    ) -> (FlakyEffect, Arc<AtomicUsize>) {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let effect = FlakyEffect {
+            attempts: attempts.clone(),
+            failures,
+            make_error,
+        };
+        (effect, attempts)
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = RetryPolicy::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .with_multiplier(2.0);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_jitter(0.5);
+
+        for _ in 0..100 {
+            let delay = policy.jittered_backoff(1);
+            assert!(delay >= Duration::from_millis(50), "{:?}", delay);
+            assert!(delay <= Duration::from_millis(150), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn test_builder_clamps_values() {
+        let policy = RetryPolicy::new()
+            .with_max_attempts(0)
+            .with_multiplier(0.5)
+            .with_jitter(3.0);
+
+        assert_eq!(policy.max_attempts(), 1);
+        assert_eq!(policy.multiplier, 1.0);
+        assert_eq!(policy.jitter, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried_until_success() {
+        let (effect, attempts) = flaky(2, || anyhow::anyhow!("connection reset"));
+        let effect = RetryingEffect::new(effect, fast_policy().with_max_attempts(3));
+
+        let result = effect.execute(PingCommand, ctx()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_reports_each_retry() {
+        let policy = fast_policy().with_max_attempts(3);
+        let mut retries = Vec::new();
+
+        let result: Result<()> = policy
+            .retry(
+                || async { Err(anyhow::anyhow!("connection reset")) },
+                |attempt, error, _delay| retries.push((attempt, error.to_string())),
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            retries,
+            [(1, "connection reset".to_string()), (2, "connection reset".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts_with_last_error() {
+        let (effect, attempts) = flaky(usize::MAX, || anyhow::anyhow!("still down"));
+        let effect = RetryingEffect::new(effect, fast_policy().with_max_attempts(4));
+
+        let err = effect.execute(PingCommand, ctx()).await.unwrap_err();
+
+        assert_eq!(err.to_string(), "still down");
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_is_not_retried() {
+        let (effect, attempts) = flaky(usize::MAX, || {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no such row").into()
+        });
+        let effect = RetryingEffect::new(effect, fast_policy().with_max_attempts(5));
+
+        let result = effect.execute(PingCommand, ctx()).await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_retry_predicate() {
+        let (effect, attempts) = flaky(usize::MAX, || {
+            SeesawError::Timeout {
+                duration: Duration::from_secs(1),
+            }
+            .into()
+        });
+        let policy = fast_policy()
+            .with_max_attempts(5)
+            .with_retry_if(|e| !matches!(e.downcast_ref(), Some(SeesawError::Timeout { .. })));
+        let effect = RetryingEffect::new(effect, policy);
+
+        let result = effect.execute(PingCommand, ctx()).await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_retried_as_a_whole() {
+        let (effect, attempts) = flaky(1, || anyhow::anyhow!("blip"));
+        let effect = RetryingEffect::new(effect, fast_policy());
+
+        let events = effect
+            .execute_batch(vec![PingCommand, PingCommand], ctx())
+            .await
+            .unwrap();
+
+        assert_eq!(events.len(), 2);
+        // First batch fails on command 1; retry runs both commands again.
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+}
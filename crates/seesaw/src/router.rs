@@ -0,0 +1,424 @@
+//! Event routing table - sending event types elsewhere, changeable at runtime.
+//!
+//! A [`Router`] watches an engine's bus and sends each event whose type has
+//! routes to its [`Destination`]s: the bus of another engine in the process,
+//! a subject published through a [`RoutePublisher`] (NATS, with
+//! `seesaw-bus-nats`), or a job on a [`JobQueue`]. Routes can be replaced
+//! while the router runs, so a workflow can be extracted from a monolith one
+//! step at a time - first into its own engine, then into its own service -
+//! by changing configuration rather than code:
+//!
+//! ```ignore
+//! let router = Router::new(monolith.bus().clone())
+//!     .with_event::<OrderPlaced>("order.placed")
+//!     .with_bus("fulfillment", fulfillment.bus().clone())
+//!     .with_publisher(Arc::new(NatsPublisher::new(client)))
+//!     .with_job_queue(store.clone(), ["fulfillment:order"]);
+//! let task = router.spawn();
+//!
+//! router.set_routes("order.placed", vec![Destination::bus("fulfillment")])?;
+//!
+//! // Later, once the fulfillment service is deployed
+//! router.set_routes("order.placed", vec![Destination::subject("orders.placed.v1")])?;
+//! ```
+//!
+//! [`Destination`] is serializable, so the table can come from a config file
+//! or an admin endpoint through [`Router::set_routes`] and be inspected with
+//! [`Router::routes`]. Events keep being decided by the engine they were
+//! emitted on; a router only sends copies. Delivery is at most once: events
+//! emitted while the router lags too far behind the bus are lost, and failed
+//! sends are logged and skipped.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+use crate::bus::EventBus;
+use crate::core::{CorrelationId, Event, EventEnvelope, JobSpec};
+use crate::dispatch::JobQueue;
+
+type EncodeFn =
+    Box<dyn Fn(&EventEnvelope) -> Option<serde_json::Result<serde_json::Value>> + Send + Sync>;
+
+/// Publishes routed events to a message broker subject.
+#[async_trait]
+pub trait RoutePublisher: Send + Sync + 'static {
+    /// Publish `payload`, a JSON-encoded event, on `subject`.
+    async fn publish(&self, subject: &str, cid: CorrelationId, payload: Bytes) -> Result<()>;
+}
+
+/// Where a [`Router`] sends an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Destination {
+    /// Emit on the bus registered under `name` with [`Router::with_bus`].
+    Bus {
+        /// The bus's name.
+        name: String,
+    },
+    /// Publish on `subject` through the router's [`RoutePublisher`].
+    Subject {
+        /// The subject, e.g. `"orders.placed.v1"`.
+        subject: String,
+    },
+    /// Enqueue as a `job_type` job, with the event as its JSON payload.
+    Job {
+        /// A job type registered with [`Router::with_job_queue`].
+        job_type: String,
+    },
+}
+
+impl Destination {
+    /// The bus registered under `name`.
+    pub fn bus(name: impl Into<String>) -> Self {
+        Destination::Bus { name: name.into() }
+    }
+
+    /// The broker subject `subject`.
+    pub fn subject(subject: impl Into<String>) -> Self {
+        Destination::Subject {
+            subject: subject.into(),
+        }
+    }
+
+    /// A `job_type` job.
+    pub fn job(job_type: impl Into<String>) -> Self {
+        Destination::Job {
+            job_type: job_type.into(),
+        }
+    }
+}
+
+struct RoutedEvent {
+    name: &'static str,
+    encode: EncodeFn,
+}
+
+impl RoutedEvent {
+    fn encode(&self, envelope: &EventEnvelope) -> Result<serde_json::Value> {
+        match (self.encode)(envelope) {
+            Some(value) => Ok(value?),
+            None => bail!("payload is not a {} event", self.name),
+        }
+    }
+}
+
+struct Inner {
+    source: EventBus,
+    events: HashMap<TypeId, RoutedEvent>,
+    buses: HashMap<String, EventBus>,
+    publisher: Option<Arc<dyn RoutePublisher>>,
+    job_queue: Option<Arc<dyn JobQueue>>,
+    job_types: Vec<&'static str>,
+    routes: RwLock<HashMap<TypeId, Vec<Destination>>>,
+}
+
+/// Sends the events of one bus to the destinations of a routing table.
+///
+/// Cloning is cheap and clones share the table, so one clone can run the
+/// router while another updates its routes. See the [module docs](self).
+#[derive(Clone)]
+pub struct Router {
+    inner: Arc<Inner>,
+}
+
+impl Router {
+    /// A router for the events of `source`, with no routes.
+    pub fn new(source: EventBus) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                source,
+                events: HashMap::new(),
+                buses: HashMap::new(),
+                publisher: None,
+                job_queue: None,
+                job_types: Vec::new(),
+                routes: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    fn configure(mut self, configure: impl FnOnce(&mut Inner)) -> Self {
+        let inner = Arc::get_mut(&mut self.inner)
+            .expect("configure a Router before cloning or spawning it");
+        configure(inner);
+        self
+    }
+
+    /// Allow `E` events to be routed, naming them `name` in the table.
+    pub fn with_event<E: Event + Serialize>(self, name: &'static str) -> Self {
+        self.configure(|inner| {
+            inner.events.insert(
+                TypeId::of::<E>(),
+                RoutedEvent {
+                    name,
+                    encode: Box::new(|envelope| {
+                        envelope.downcast_ref::<E>().map(serde_json::to_value)
+                    }),
+                },
+            );
+        })
+    }
+
+    /// Make `bus`, e.g. another engine's, a destination named `name`.
+    pub fn with_bus(self, name: impl Into<String>, bus: EventBus) -> Self {
+        self.configure(|inner| {
+            inner.buses.insert(name.into(), bus);
+        })
+    }
+
+    /// Publish [`Destination::Subject`] routes through `publisher`.
+    pub fn with_publisher(self, publisher: Arc<dyn RoutePublisher>) -> Self {
+        self.configure(|inner| inner.publisher = Some(publisher))
+    }
+
+    /// Enqueue [`Destination::Job`] routes on `queue`, as one of `job_types`.
+    ///
+    /// Job types are fixed up front so the workers consuming them are known
+    /// when the router is built.
+    pub fn with_job_queue(
+        self,
+        queue: Arc<dyn JobQueue>,
+        job_types: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        self.configure(|inner| {
+            inner.job_queue = Some(queue);
+            inner.job_types.extend(job_types);
+        })
+    }
+
+    /// Replace the destinations of the event named `event`.
+    ///
+    /// An empty list stops routing the event. Takes effect for the next
+    /// event the router receives.
+    ///
+    /// # Errors
+    ///
+    /// Fails, leaving the routes unchanged, if `event` was not registered
+    /// with [`with_event`](Self::with_event) or a destination is unknown: a
+    /// bus not registered, a subject without a publisher, or a job type not
+    /// registered with [`with_job_queue`](Self::with_job_queue).
+    pub fn set_routes(&self, event: &str, destinations: Vec<Destination>) -> Result<()> {
+        let type_id = self.type_of(event)?;
+        for destination in &destinations {
+            self.check(destination)?;
+        }
+        let mut routes = self.inner.routes.write().unwrap_or_else(|e| e.into_inner());
+        if destinations.is_empty() {
+            routes.remove(&type_id);
+        } else {
+            routes.insert(type_id, destinations);
+        }
+        Ok(())
+    }
+
+    /// The routing table: each routed event's name and destinations, sorted
+    /// by name.
+    pub fn routes(&self) -> Vec<(&'static str, Vec<Destination>)> {
+        let routes = self.inner.routes.read().unwrap_or_else(|e| e.into_inner());
+        let mut table: Vec<_> = routes
+            .iter()
+            .map(|(type_id, destinations)| {
+                (self.inner.events[type_id].name, destinations.clone())
+            })
+            .collect();
+        table.sort_by_key(|(name, _)| *name);
+        table
+    }
+
+    fn type_of(&self, event: &str) -> Result<TypeId> {
+        self.inner
+            .events
+            .iter()
+            .find(|(_, routed)| routed.name == event)
+            .map(|(type_id, _)| *type_id)
+            .ok_or_else(|| anyhow!("event {event} is not registered with the router"))
+    }
+
+    fn check(&self, destination: &Destination) -> Result<()> {
+        match destination {
+            Destination::Bus { name } if !self.inner.buses.contains_key(name) => {
+                bail!("bus {name} is not registered with the router")
+            }
+            Destination::Subject { subject } if self.inner.publisher.is_none() => {
+                bail!("cannot route to subject {subject}: the router has no publisher")
+            }
+            Destination::Job { job_type } if self.job_type(job_type).is_none() => {
+                bail!("job type {job_type} is not registered with the router")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn job_type(&self, job_type: &str) -> Option<&'static str> {
+        self.inner
+            .job_types
+            .iter()
+            .copied()
+            .find(|registered| *registered == job_type)
+    }
+
+    /// Subscribe to the source bus and route its events until it closes.
+    ///
+    /// Subscribes before returning, so every event emitted afterwards is
+    /// routed.
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let mut events = self.inner.source.subscribe_recovering();
+        let router = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(envelope) => router.route(&envelope).await,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "router lagged behind the event bus, events lost");
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+
+    /// Send `envelope` to its destinations, logging failures.
+    async fn route(&self, envelope: &EventEnvelope) {
+        let destinations = {
+            let routes = self.inner.routes.read().unwrap_or_else(|e| e.into_inner());
+            match routes.get(&envelope.type_id) {
+                Some(destinations) => destinations.clone(),
+                None => return,
+            }
+        };
+        let routed = &self.inner.events[&envelope.type_id];
+        for destination in &destinations {
+            if let Err(e) = self.send(routed, envelope, destination).await {
+                error!(
+                    event = routed.name,
+                    cid = %envelope.cid,
+                    ?destination,
+                    error = %e,
+                    "failed to route event"
+                );
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        routed: &RoutedEvent,
+        envelope: &EventEnvelope,
+        destination: &Destination,
+    ) -> Result<()> {
+        match destination {
+            Destination::Bus { name } => {
+                let bus = &self.inner.buses[name];
+                bus.emit_envelope_async(envelope.clone()).await?;
+            }
+            Destination::Subject { subject } => {
+                let publisher = self.inner.publisher.as_ref().expect("checked by set_routes");
+                let payload = Bytes::from(serde_json::to_vec(&routed.encode(envelope)?)?);
+                publisher.publish(subject, envelope.cid, payload).await?;
+            }
+            Destination::Job { job_type } => {
+                let queue = self.inner.job_queue.as_ref().expect("checked by set_routes");
+                let job_type = self.job_type(job_type).expect("checked by set_routes");
+                queue
+                    .enqueue(routed.encode(envelope)?, JobSpec::new(job_type))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Router {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Router")
+            .field("routes", &self.routes())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    struct OrderPlaced {
+        order_id: u32,
+    }
+
+    #[tokio::test]
+    async fn test_routes_can_be_replaced_while_running() {
+        let source = EventBus::new();
+        let (inventory, fulfillment) = (EventBus::new(), EventBus::new());
+        let mut to_inventory = inventory.subscribe();
+        let mut to_fulfillment = fulfillment.subscribe();
+        let router = Router::new(source.clone())
+            .with_event::<OrderPlaced>("order.placed")
+            .with_bus("inventory", inventory)
+            .with_bus("fulfillment", fulfillment);
+        let task = router.spawn();
+
+        router
+            .set_routes("order.placed", vec![Destination::bus("inventory")])
+            .unwrap();
+        let cid = CorrelationId::new();
+        source.emit_with_correlation(OrderPlaced { order_id: 1 }, cid);
+        let routed = to_inventory.recv().await.unwrap();
+        assert_eq!(routed.cid, cid);
+        assert_eq!(routed.downcast_ref(), Some(&OrderPlaced { order_id: 1 }));
+
+        router
+            .set_routes("order.placed", vec![Destination::bus("fulfillment")])
+            .unwrap();
+        source.emit(OrderPlaced { order_id: 2 });
+        let routed = to_fulfillment.recv().await.unwrap();
+        assert_eq!(routed.downcast_ref(), Some(&OrderPlaced { order_id: 2 }));
+        assert!(to_inventory.try_recv().is_err());
+
+        task.abort();
+    }
+
+    #[test]
+    fn test_rejects_unknown_events_and_destinations() {
+        let router = Router::new(EventBus::new()).with_event::<OrderPlaced>("order.placed");
+
+        assert!(router.set_routes("order.shipped", vec![]).is_err());
+        assert!(router
+            .set_routes("order.placed", vec![Destination::bus("fulfillment")])
+            .is_err());
+        assert!(router
+            .set_routes("order.placed", vec![Destination::subject("orders.placed.v1")])
+            .is_err());
+        assert!(router
+            .set_routes("order.placed", vec![Destination::job("fulfillment:order")])
+            .is_err());
+        assert!(router.routes().is_empty());
+    }
+
+    #[test]
+    fn test_destinations_deserialize_from_config() {
+        let destinations: Vec<Destination> = serde_json::from_value(serde_json::json!([
+            { "kind": "bus", "name": "fulfillment" },
+            { "kind": "subject", "subject": "orders.placed.v1" },
+            { "kind": "job", "job_type": "fulfillment:order" },
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            destinations,
+            [
+                Destination::bus("fulfillment"),
+                Destination::subject("orders.placed.v1"),
+                Destination::job("fulfillment:order"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Per-variant effects for enum commands.
+//!
+//! A machine whose `Command` is an enum would otherwise need one effect for
+//! the whole enum, starting with a `match` and an `unreachable!()` for the
+//! variants it does not handle. Instead, wrap each variant's payload in its
+//! own command type and register one effect per payload type:
+//!
+//! ```ignore
+//! #[derive(Debug, Clone, CommandVariants)]
+//! enum DeckCommand {
+//!     Setup(SetupLoaf),
+//!     Summarize(Summarize),
+//! }
+//! impl Command for DeckCommand {}
+//!
+//! let dispatcher = Dispatcher::new(deps, bus)
+//!     .with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+//!     .with_effect_for::<DeckCommand, Summarize, _>(SummarizeEffect);
+//! ```
+//!
+//! The enum stays the unit of dispatch: timeouts, circuit breakers and other
+//! per-command-type settings are keyed by the enum, not the variant.
+
+use std::any::{Any, TypeId};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::core::{Command, EventEnvelope};
+use crate::effect_impl::{AnyEffect, Effect, EffectContext};
+use crate::error::SeesawError;
+
+/// An enum command with a variant wrapping command type `V`.
+///
+/// Implemented by `#[derive(CommandVariants)]` (`derive` feature) for every
+/// single-field tuple variant, along with `From<V>`.
+pub trait CommandVariant<V>: Command + Sized {
+    /// Unwrap the variant, or give the command back if it is another one.
+    fn into_variant(self) -> Result<V, Self>;
+}
+
+/// One variant's effect, erased over the variant type.
+#[async_trait]
+trait VariantEffect<C, D>: Send + Sync {
+    /// Execute if `command` is this variant; otherwise hand it back.
+    async fn try_execute(
+        &self,
+        command: C,
+        ctx: EffectContext<D>,
+    ) -> std::result::Result<Result<EventEnvelope>, C>;
+}
+
+struct VariantEffectWrapper<E, V> {
+    effect: E,
+    _phantom: std::marker::PhantomData<fn() -> V>,
+}
+
+#[async_trait]
+impl<C, V, D, E> VariantEffect<C, D> for VariantEffectWrapper<E, V>
+where
+    C: CommandVariant<V>,
+    V: Command,
+    D: Send + Sync + 'static,
+    E: Effect<V, D>,
+{
+    async fn try_execute(
+        &self,
+        command: C,
+        ctx: EffectContext<D>,
+    ) -> std::result::Result<Result<EventEnvelope>, C> {
+        let variant = command.into_variant()?;
+        let cid = ctx.correlation_id();
+        Ok(self
+            .effect
+            .execute(variant, ctx)
+            .await
+            .map(|event| EventEnvelope::new(cid, event)))
+    }
+}
+
+/// The effect registered for enum command `C`, delegating each variant to
+/// its own effect.
+pub(crate) struct VariantRouter<C, D> {
+    variants: Vec<(TypeId, Box<dyn VariantEffect<C, D>>)>,
+}
+
+impl<C, D> VariantRouter<C, D>
+where
+    C: Command,
+    D: Send + Sync + 'static,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            variants: Vec::new(),
+        }
+    }
+
+    /// Add the effect for variant type `V`.
+    pub(crate) fn add<V, E>(&mut self, effect: E) -> Result<()>
+    where
+        C: CommandVariant<V>,
+        V: Command,
+        E: Effect<V, D>,
+    {
+        let type_id = TypeId::of::<V>();
+        if self.variants.iter().any(|(id, _)| *id == type_id) {
+            return Err(SeesawError::EffectAlreadyRegistered {
+                type_name: std::any::type_name::<V>(),
+            }
+            .into());
+        }
+        self.variants.push((
+            type_id,
+            Box::new(VariantEffectWrapper {
+                effect,
+                _phantom: std::marker::PhantomData,
+            }),
+        ));
+        Ok(())
+    }
+
+    async fn route(&self, mut command: C, ctx: EffectContext<D>) -> Result<EventEnvelope> {
+        for (_, variant) in &self.variants {
+            match variant.try_execute(command, ctx.clone()).await {
+                Ok(result) => return result,
+                Err(unmatched) => command = unmatched,
+            }
+        }
+        Err(SeesawError::NoEffectRegistered {
+            type_id: TypeId::of::<C>(),
+            type_name: std::any::type_name::<C>(),
+        }
+        .into())
+    }
+}
+
+#[async_trait]
+impl<C, D> AnyEffect<D> for VariantRouter<C, D>
+where
+    C: Command,
+    D: Send + Sync + 'static,
+{
+    async fn execute_any(
+        &self,
+        command: Box<dyn Any + Send + Sync>,
+        ctx: EffectContext<D>,
+    ) -> Result<EventEnvelope> {
+        let command = command
+            .downcast::<C>()
+            .map_err(|c| SeesawError::CommandTypeMismatch {
+                expected: std::any::type_name::<C>(),
+                actual_type_id: (*c).type_id(),
+            })?;
+        self.route(*command, ctx).await
+    }
+
+    async fn execute_any_batch(
+        &self,
+        commands: Vec<Box<dyn Any + Send + Sync>>,
+        ctx: EffectContext<D>,
+    ) -> Result<Vec<EventEnvelope>> {
+        // Variants may go to different effects, so run them one at a time
+        let mut envelopes = Vec::with_capacity(commands.len());
+        for command in commands {
+            envelopes.push(self.execute_any(command, ctx.clone()).await?);
+        }
+        Ok(envelopes)
+    }
+
+    fn command_type_name(&self) -> &'static str {
+        std::any::type_name::<C>()
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::dispatch::Dispatcher;
+    use seesaw_macros::CommandVariants;
+
+    #[derive(Debug, Clone)]
+    struct SetupLoaf {
+        deck_id: u32,
+    }
+    impl Command for SetupLoaf {}
+
+    #[derive(Debug, Clone)]
+    struct Summarize {
+        text: String,
+    }
+    impl Command for Summarize {}
+
+    #[derive(Debug, Clone)]
+    struct Discard;
+    impl Command for Discard {}
+
+    #[derive(Debug, Clone, CommandVariants)]
+    enum DeckCommand {
+        Setup(SetupLoaf),
+        Summarize(Summarize),
+        Discard(Discard),
+    }
+    impl Command for DeckCommand {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum DeckEvent {
+        LoafReady { deck_id: u32 },
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Summary(usize);
+
+    struct SetupEffect;
+
+    #[async_trait]
+    impl Effect<SetupLoaf, ()> for SetupEffect {
+        type Event = DeckEvent;
+
+        async fn execute(&self, cmd: SetupLoaf, _ctx: EffectContext<()>) -> Result<DeckEvent> {
+            Ok(DeckEvent::LoafReady {
+                deck_id: cmd.deck_id,
+            })
+        }
+    }
+
+    struct SummarizeEffect;
+
+    #[async_trait]
+    impl Effect<Summarize, ()> for SummarizeEffect {
+        type Event = Summary;
+
+        async fn execute(&self, cmd: Summarize, _ctx: EffectContext<()>) -> Result<Summary> {
+            Ok(Summary(cmd.text.len()))
+        }
+    }
+
+    fn dispatcher(bus: &EventBus) -> Dispatcher<()> {
+        Dispatcher::new((), bus.clone())
+            .with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+            .with_effect_for::<DeckCommand, Summarize, _>(SummarizeEffect)
+    }
+
+    #[test]
+    fn test_derive_converts_between_enum_and_variants() {
+        let cmd: DeckCommand = SetupLoaf { deck_id: 1 }.into();
+        assert!(CommandVariant::<Summarize>::into_variant(cmd.clone()).is_err());
+        let setup: SetupLoaf = cmd.into_variant().unwrap();
+        assert_eq!(setup.deck_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_routes_each_variant_to_its_effect() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        dispatcher(&bus)
+            .dispatch(vec![
+                Box::new(DeckCommand::Setup(SetupLoaf { deck_id: 7 })),
+                Box::new(DeckCommand::Summarize(Summarize {
+                    text: "crumb".to_string(),
+                })),
+            ])
+            .await
+            .unwrap();
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(
+            first.downcast_ref::<DeckEvent>(),
+            Some(&DeckEvent::LoafReady { deck_id: 7 })
+        );
+        let second = rx.recv().await.unwrap();
+        assert_eq!(second.downcast_ref::<Summary>(), Some(&Summary(5)));
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_variant_fails() {
+        let bus = EventBus::new();
+        let err = dispatcher(&bus)
+            .dispatch(vec![Box::new(DeckCommand::Discard(Discard))])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no effect registered"));
+    }
+
+    #[test]
+    fn test_duplicate_variant_registration_is_rejected() {
+        let err = dispatcher(&EventBus::new())
+            .try_with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered"));
+
+        let err = Dispatcher::new((), EventBus::new())
+            .with_effect::<DeckCommand, _>(DeckEffect)
+            .try_with_effect_for::<DeckCommand, SetupLoaf, _>(SetupEffect)
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered"));
+    }
+
+    struct DeckEffect;
+
+    #[async_trait]
+    impl Effect<DeckCommand, ()> for DeckEffect {
+        type Event = Summary;
+
+        async fn execute(&self, _cmd: DeckCommand, _ctx: EffectContext<()>) -> Result<Summary> {
+            Ok(Summary(0))
+        }
+    }
+}
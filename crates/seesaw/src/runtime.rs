@@ -7,20 +7,62 @@
 //! - An event bus for broadcasting events
 
 use std::any::TypeId;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::sync::broadcast::error::RecvError;
-use tracing::{debug, error, info, trace, warn};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn, Instrument, Span};
 
 use crate::bus::EventBus;
+use crate::control::{AddEffect, ControlReceiver, RuntimeControl, WiringChange};
+use crate::core::{AnyCommand, CorrelationId, Event, EventEnvelope};
+use crate::dead_letter::DeadLetterReason;
 use crate::dispatch::{Dispatcher, JobQueue};
 use crate::engine::{InflightGuard, InflightTracker};
-use crate::machine::{Machine, MachineRunner};
+use crate::error::SeesawError;
+use crate::machine::{event_filter, EventFilter, EventSelector, Machine, MachineRunner};
+use crate::machine_middleware::MachineMiddleware;
+use crate::metadata::Metadata;
+use crate::metrics;
+use crate::process::ProcessManager;
+use crate::replay::{EventLog, ReplayReport, REPLAY_PAGE_SIZE};
+use crate::shard::Sharding;
+use crate::snapshot::{Snapshot, SnapshotMachine, SnapshotStore};
+use crate::spans;
+use crate::supervisor::SupervisorPolicy;
 use crate::tap::TapRegistry;
+use crate::trace::{TraceParent, TraceRecorder};
 
 #[cfg(debug_assertions)]
 use crate::audit::{AuditEntryBuilder, AuditLog, SharedAuditLog};
+#[cfg(feature = "audit")]
+use crate::audit_sink::{AuditExecution, AuditRecordBuilder, AuditSink, AuditWriter};
+
+/// Maximum number of events decided together in one tick when priority
+/// lanes are enabled.
+pub(crate) const PRIORITY_WINDOW: usize = 64;
+
+/// Default maximum causation depth before an event is treated as a loop.
+pub(crate) const DEFAULT_MAX_HOPS: u32 = 256;
+
+/// Emitted when the runtime drops an event whose causation depth exceeds the
+/// configured maximum.
+///
+/// Carries the correlation ID of the dropped event. A loop usually means a
+/// machine decides a command for every event its own effect returns.
+#[derive(Debug, Clone)]
+pub struct LoopDetected {
+    /// Type of the dropped event.
+    pub event_type: TypeId,
+    /// Causation depth of the dropped event.
+    pub hops: u32,
+    /// The configured maximum depth.
+    pub max_hops: u32,
+}
 
 /// Runtime for coordinating seesaw components.
 ///
@@ -52,16 +94,267 @@ use crate::audit::{AuditEntryBuilder, AuditLog, SharedAuditLog};
 /// bus.emit(BakeEvent::Requested { deck_id, recipe_id });
 /// ```
 pub struct Runtime<D> {
+    /// Machines in decide order: priority (highest first), then registration.
     machines: Vec<MachineRunner>,
-    dispatcher: Dispatcher<D>,
+    /// Decide priority overrides, keyed by machine type.
+    machine_priorities: HashMap<TypeId, i32>,
+    /// Event filters, keyed by machine type and then event type.
+    machine_filters: HashMap<TypeId, Vec<(TypeId, EventFilter)>>,
+    /// Hooks around every `decide` call, in registration order.
+    machine_middleware: Vec<Arc<dyn MachineMiddleware>>,
+    dispatcher: Arc<Dispatcher<D>>,
     bus: EventBus,
     /// Optional inflight tracker for correlation-based await.
     inflight: Option<Arc<InflightTracker>>,
     /// Event taps for observing committed facts.
-    taps: TapRegistry,
+    taps: Arc<TapRegistry>,
+    /// Where and how often snapshot machines are persisted.
+    snapshots: Option<SnapshotSchedule>,
+    /// Whether snapshot machines were already restored (explicitly or by `run`).
+    snapshots_restored: bool,
+    /// Number of inline dispatch priority lanes (1 = priorities ignored).
+    priority_lanes: u8,
+    /// Most waiting events decided together in one tick (1 = no batching).
+    micro_batch: usize,
+    /// Maximum causation depth of an event before it is dropped as a loop.
+    max_hops: u32,
+    /// Dispatch workers keyed by event, when sharded.
+    sharding: Option<Sharding>,
+    /// Wiring changes from engine handles, applied between ticks.
+    control: ControlReceiver,
     /// Debug-only audit log for event visibility.
     #[cfg(debug_assertions)]
     audit_log: SharedAuditLog,
+    /// Structured audit trail, when a sink is configured.
+    #[cfg(feature = "audit")]
+    audit: Option<Arc<AuditWriter>>,
+    /// Causality traces, when a recorder is configured.
+    trace: Option<TraceRecorder>,
+}
+
+/// Snapshot persistence configuration.
+struct SnapshotSchedule {
+    store: Arc<dyn SnapshotStore>,
+    interval: Duration,
+}
+
+/// Inline batch key: lane (highest first), event order, position of the first
+/// machine that decided the command type, command type, correlation.
+type BatchKey = (Reverse<u8>, usize, usize, TypeId, CorrelationId);
+
+/// Work decided from one or more events, dispatched together.
+#[derive(Default)]
+struct Tick {
+    /// Inline command batches, in dispatch order.
+    batches: BTreeMap<BatchKey, Vec<Box<dyn AnyCommand>>>,
+    /// Event order and machine rank of the batch each (lane, command type,
+    /// correlation) joins, when micro-batching merges events' batches.
+    merged: HashMap<(u8, TypeId, CorrelationId), (usize, usize)>,
+    /// Background commands deferred for one batched enqueue, with the
+    /// event they were decided from and their type name.
+    jobs: Vec<(usize, &'static str, Box<dyn AnyCommand>)>,
+    /// Decided events, in arrival order, with their inflight guards.
+    envelopes: Vec<(EventEnvelope, Option<InflightGuard>)>,
+    /// Type names of `envelopes`, from the first machine handling each.
+    event_types: Vec<Option<&'static str>>,
+    /// `seesaw.event` spans for `envelopes`.
+    spans: Vec<Span>,
+    /// Audit records for `envelopes`, when an audit sink is configured.
+    #[cfg(feature = "audit")]
+    audits: Vec<Option<AuditRecordBuilder>>,
+    /// Trace node and command type name of the decision each inline batch
+    /// was first decided at, keyed by event order and command type.
+    traced: HashMap<(usize, TypeId), (u64, &'static str)>,
+}
+
+/// What dispatching a tick needs, shared with the shard workers.
+struct TickDispatch<D> {
+    dispatcher: Arc<Dispatcher<D>>,
+    inflight: Option<Arc<InflightTracker>>,
+    taps: Arc<TapRegistry>,
+    #[cfg(feature = "audit")]
+    audit: Option<Arc<AuditWriter>>,
+    trace: Option<TraceRecorder>,
+}
+
+impl<D: Send + Sync + 'static> TickDispatch<D> {
+    /// Dispatch a tick's inline batches, then run taps for its events.
+    async fn dispatch(&self, mut tick: Tick) {
+        // 2. Dispatch inline batches (highest lane first, deterministic order via BTreeMap).
+        // Runs of batches whose commands are all independent dispatch
+        // concurrently; any other batch waits for everything before it.
+        let mut batches = std::mem::take(&mut tick.batches).into_iter().peekable();
+        while let Some(first) = batches.next() {
+            let mut group = vec![first];
+            if is_independent(&group[0].1) {
+                while let Some(next) = batches.next_if(|(_, batch)| is_independent(batch)) {
+                    group.push(next);
+                }
+            }
+
+            // Traced batches pass their effect's node on to the events it returns
+            let traced: Vec<Option<(u64, Metadata)>> = group
+                .iter()
+                .map(|((_, seq, _, type_id, cid), batch)| {
+                    let trace = self.trace.as_ref()?;
+                    let &(decision, command_type) = tick.traced.get(&(*seq, *type_id))?;
+                    let effect = trace.effect_started(*cid, decision, command_type, batch.len());
+                    let metadata = tick.envelopes[*seq].0.metadata.clone();
+                    Some((effect, metadata.with(TraceParent(effect))))
+                })
+                .collect();
+
+            let dispatched = futures::future::join_all(group.into_iter().zip(&traced).map(
+                |(((_, seq, _, type_id, cid), batch), traced)| {
+                    let hops = tick.envelopes[seq].0.hops;
+                    let event_type = tick.event_types[seq];
+                    let (effect, metadata) = match traced {
+                        Some((effect, metadata)) => (Some(*effect), metadata),
+                        None => (None, &tick.envelopes[seq].0.metadata),
+                    };
+                    let batch_size = batch.len();
+                    if batch_size > 1 {
+                        debug!(batch_size, ?type_id, %cid, "dispatching command batch");
+                    }
+
+                    // Dispatch with correlation for inflight tracking
+                    let started = Instant::now();
+                    let dispatch = self
+                        .dispatcher
+                        .dispatch_at_depth(
+                            batch,
+                            cid,
+                            hops,
+                            event_type,
+                            metadata,
+                            self.inflight.as_ref(),
+                        )
+                        .instrument(tick.spans[seq].clone());
+                    async move {
+                        let dispatched = dispatch.await;
+                        let elapsed = started.elapsed();
+                        (seq, type_id, cid, batch_size, effect, elapsed, dispatched)
+                    }
+                },
+            ))
+            .await;
+
+            #[cfg_attr(not(feature = "audit"), allow(unused_variables))]
+            for (seq, type_id, cid, batch_size, effect, elapsed, dispatched) in dispatched {
+                if let (Some(trace), Some(effect)) = (&self.trace, effect) {
+                    let failure = match &dispatched {
+                        Ok(failure) => failure.clone(),
+                        Err(e) => Some(self.dispatcher.redactor().redact(&e.to_string())),
+                    };
+                    trace.effect_finished(cid, effect, elapsed, failure);
+                }
+
+                #[cfg(feature = "audit")]
+                if let Some(record) = &mut tick.audits[seq] {
+                    record.dispatched(
+                        type_id,
+                        batch_size,
+                        elapsed,
+                        match &dispatched {
+                            Ok(failure) => failure.clone(),
+                            Err(e) => Some(self.dispatcher.redactor().redact(&e.to_string())),
+                        },
+                    );
+                }
+
+                if let Err(e) = dispatched {
+                    error!(error = %e, "batch dispatch failed");
+                    // Record error for correlation
+                    if let Some(tracker) = &self.inflight {
+                        tracker.record_error(cid, e);
+                    }
+                }
+            }
+        }
+
+        // 3. Run event taps (after effects complete)
+        // Taps observe committed facts - queued to their own tasks, never awaited
+        if !self.taps.is_empty() {
+            for (envelope, _) in &tick.envelopes {
+                self.taps.run_envelope(envelope);
+            }
+        }
+
+        #[cfg(feature = "audit")]
+        if let Some(audit) = &self.audit {
+            for record in tick.audits.into_iter().flatten() {
+                audit.write(record.finish());
+            }
+        }
+
+        // Event guards drop here, after the work they cover
+    }
+}
+
+/// Wait for the snapshot ticker's next tick; never completes without one.
+async fn next_snapshot(ticker: &mut Option<tokio::time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Whether a batch may dispatch alongside other independent batches.
+fn is_independent(batch: &[Box<dyn AnyCommand>]) -> bool {
+    batch.iter().all(|command| command.is_independent())
+}
+
+/// Dispatch workers of a sharded runtime, one tick queue each.
+struct ShardPool {
+    sharding: Sharding,
+    queues: Vec<mpsc::Sender<Tick>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// Ticks a shard worker queues before the runtime waits for it.
+const SHARD_QUEUE_CAPACITY: usize = 64;
+
+impl ShardPool {
+    fn spawn<D: Send + Sync + 'static>(sharding: Sharding, ticks: &Arc<TickDispatch<D>>) -> Self {
+        let (queues, workers) = (0..sharding.workers())
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<Tick>(SHARD_QUEUE_CAPACITY);
+                let ticks = ticks.clone();
+                let worker = tokio::spawn(async move {
+                    while let Some(tick) = rx.recv().await {
+                        ticks.dispatch(tick).await;
+                    }
+                });
+                (tx, worker)
+            })
+            .unzip();
+        Self {
+            sharding,
+            queues,
+            workers,
+        }
+    }
+
+    /// Queue `tick` on the worker owning its event's key.
+    async fn dispatch(&self, tick: Tick) {
+        // Empty if the event was dropped as a loop
+        let Some((envelope, _)) = tick.envelopes.first() else {
+            return;
+        };
+        let shard = self.sharding.shard_of(envelope);
+        // Workers only stop once their queue is closed
+        let _ = self.queues[shard].send(tick).await;
+    }
+
+    /// Wait for the workers to finish the queued ticks.
+    async fn drain(self) {
+        drop(self.queues);
+        for worker in self.workers {
+            let _ = worker.await;
+        }
+    }
 }
 
 impl<D: Send + Sync + 'static> Runtime<D> {
@@ -69,12 +362,25 @@ impl<D: Send + Sync + 'static> Runtime<D> {
     pub fn new(dispatcher: Dispatcher<D>, bus: EventBus) -> Self {
         Self {
             machines: Vec::new(),
-            dispatcher,
+            machine_priorities: HashMap::new(),
+            machine_filters: HashMap::new(),
+            machine_middleware: Vec::new(),
+            dispatcher: Arc::new(dispatcher),
             bus,
             inflight: None,
-            taps: TapRegistry::new(),
+            taps: Arc::new(TapRegistry::new()),
+            snapshots: None,
+            snapshots_restored: false,
+            priority_lanes: 1,
+            micro_batch: 1,
+            max_hops: DEFAULT_MAX_HOPS,
+            sharding: None,
+            control: ControlReceiver::default(),
             #[cfg(debug_assertions)]
             audit_log: Arc::new(AuditLog::new()),
+            #[cfg(feature = "audit")]
+            audit: None,
+            trace: None,
         }
     }
 
@@ -82,10 +388,19 @@ impl<D: Send + Sync + 'static> Runtime<D> {
     ///
     /// Taps run after effects complete, observing committed facts.
     pub(crate) fn with_taps(mut self, taps: TapRegistry) -> Self {
-        self.taps = taps;
+        self.dispatcher.health().track_taps(taps.stats());
+        self.taps = Arc::new(taps);
         self
     }
 
+    /// Open a channel for changing this runtime's machines and effects
+    /// while it runs, replacing any earlier one.
+    pub(crate) fn control(&mut self) -> RuntimeControl {
+        let (control, receiver) = RuntimeControl::channel();
+        self.control = receiver;
+        control
+    }
+
     /// Set the inflight tracker for correlation-based await.
     ///
     /// When set, the runtime can track pending work for `emit_and_await`.
@@ -96,14 +411,383 @@ impl<D: Send + Sync + 'static> Runtime<D> {
 
     /// Add a machine to the runtime.
     ///
-    /// Machines are called in the order they are added. Each machine
-    /// can observe the same event and independently decide whether
+    /// Machines are called in the order they are added, unless reordered
+    /// with [`with_machine_priority`](Self::with_machine_priority). Each
+    /// machine can observe the same event and independently decide whether
     /// to emit a command.
     pub fn with_machine<M: Machine>(mut self, machine: M) -> Self {
-        self.machines.push(MachineRunner::new(machine));
+        self.add_machine(MachineRunner::new(machine));
+        self
+    }
+
+    /// Set the decide priority of every machine of type `M`.
+    ///
+    /// For each event, machines decide in priority order, highest first;
+    /// machines with equal priority (0 by default) decide in registration
+    /// order. Inline commands decided from the same event are dispatched in
+    /// the order their machines decided, so an order-sensitive effect can
+    /// rely on it. Applies to machines added before or after this call.
+    ///
+    /// Priority lanes ([`with_priority_lanes`](Self::with_priority_lanes))
+    /// still take precedence: a command in a higher lane is dispatched
+    /// before commands from earlier machines in lower lanes.
+    pub fn with_machine_priority<M: Machine>(mut self, priority: i32) -> Self {
+        let machine_type = TypeId::of::<M>();
+        self.machine_priorities.insert(machine_type, priority);
+        for machine in &mut self.machines {
+            if machine.machine_type() == machine_type {
+                machine.set_priority(priority);
+            }
+        }
+        // Stable, so equal priorities keep registration order
+        self.machines
+            .sort_by_key(|machine| Reverse(machine.priority()));
+        self
+    }
+
+    /// Only pass events of type `E` to machines of type `M` when `filter`
+    /// returns true.
+    ///
+    /// The filter runs before the machine sees the event: before an
+    /// [`EventSelector`] wraps (and clones) it, before machine middleware,
+    /// and before `decide`. Use it to keep a machine that reacts to one
+    /// variant of a high-volume event from paying for the rest. `E` may be
+    /// the machine's `Event` or any type its selector wraps; setting a filter
+    /// for the same `M` and `E` again replaces it. Applies to machines added
+    /// before or after this call.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// runtime.with_machine_filter::<ShippingMachine, OrderEvent, _>(|e| {
+    ///     matches!(e, OrderEvent::Paid { .. })
+    /// })
+    /// ```
+    pub fn with_machine_filter<M, E, F>(mut self, filter: F) -> Self
+    where
+        M: Machine,
+        E: Event,
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        let machine_type = TypeId::of::<M>();
+        let filter = event_filter(filter);
+        for machine in &mut self.machines {
+            if machine.machine_type() == machine_type {
+                machine.set_filter(TypeId::of::<E>(), filter.clone());
+            }
+        }
+        let filters = self.machine_filters.entry(machine_type).or_default();
+        filters.retain(|(event_type, _)| *event_type != TypeId::of::<E>());
+        filters.push((TypeId::of::<E>(), filter));
+        self
+    }
+
+    /// Insert `machine` after every machine with the same or higher priority.
+    fn add_machine(&mut self, mut machine: MachineRunner) {
+        if let Some(&priority) = self.machine_priorities.get(&machine.machine_type()) {
+            machine.set_priority(priority);
+        }
+        for (event_type, filter) in self
+            .machine_filters
+            .get(&machine.machine_type())
+            .into_iter()
+            .flatten()
+        {
+            machine.set_filter(*event_type, filter.clone());
+        }
+        let index = self
+            .machines
+            .partition_point(|existing| existing.priority() >= machine.priority());
+        self.machines.insert(index, machine);
+    }
+
+    /// Add a machine that subscribes to several event types.
+    ///
+    /// The machine's `Event` is an [`EventSelector`]; every published event
+    /// of a type the selector wraps is passed to `decide`. Registering such a
+    /// machine with [`with_machine`](Self::with_machine) would only feed it
+    /// the selector type itself.
+    pub fn with_selector_machine<M>(mut self, machine: M) -> Self
+    where
+        M: Machine,
+        M::Event: EventSelector,
+    {
+        self.add_machine(MachineRunner::new_selector(machine));
+        self
+    }
+
+    /// Add a process manager, keeping one instance per correlation ID.
+    ///
+    /// Step timeouts emit [`ProcessTimedOut`](crate::ProcessTimedOut) on
+    /// this runtime's bus. See [`ProcessManager`].
+    pub fn with_process_manager<P: ProcessManager>(mut self, manager: P) -> Self {
+        self.add_machine(MachineRunner::new_process(manager, self.bus.clone()));
+        self
+    }
+
+    /// Add a machine whose state is visible to machine middleware.
+    ///
+    /// Behaves like [`with_machine`](Self::with_machine), but in debug builds
+    /// each `Decision` passed to middleware carries the machine's `{:?}`
+    /// state after the call.
+    pub fn with_debug_machine<M: Machine + std::fmt::Debug>(mut self, machine: M) -> Self {
+        self.add_machine(MachineRunner::new_debug(machine));
+        self
+    }
+
+    /// Add a middleware that runs around every `Machine::decide` call.
+    ///
+    /// Middleware can observe decisions (machine, event, command, latency,
+    /// state) but cannot change them.
+    pub fn with_machine_middleware<M: MachineMiddleware>(mut self, middleware: M) -> Self {
+        self.machine_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Add a machine whose state is persisted to the snapshot store.
+    ///
+    /// Behaves like [`with_machine`](Self::with_machine) until a store is set
+    /// with [`with_snapshot_store`](Self::with_snapshot_store).
+    pub fn with_snapshot_machine<M: SnapshotMachine>(mut self, machine: M) -> Self {
+        self.add_machine(MachineRunner::new_snapshot(machine));
+        self
+    }
+
+    /// Add a machine that is restarted or stopped when it misbehaves.
+    ///
+    /// The machine is built by calling `factory`, which is called again for
+    /// every restart. See [`SupervisorPolicy`] for the limits.
+    pub fn with_supervised_machine<M, F>(mut self, factory: F, policy: SupervisorPolicy) -> Self
+    where
+        M: Machine,
+        F: Fn() -> M + Send + Sync + 'static,
+    {
+        self.add_machine(MachineRunner::new_supervised(factory, policy));
+        self
+    }
+
+    /// Persist snapshot machines to `store` every `interval`.
+    ///
+    /// Snapshot machines are restored from the store when the runtime starts
+    /// (unless [`restore_snapshots`](Self::restore_snapshots) was already called).
+    pub fn with_snapshot_store(
+        mut self,
+        store: Arc<dyn SnapshotStore>,
+        interval: Duration,
+    ) -> Self {
+        self.snapshots = Some(SnapshotSchedule { store, interval });
+        self
+    }
+
+    /// Dispatch inline commands through `lanes` priority lanes.
+    ///
+    /// Each inline command's [`Command::priority`](crate::Command::priority)
+    /// is clamped to `0..lanes`, and higher lanes are dispatched first, so
+    /// latency-sensitive commands jump ahead of bulk inline work. Within a
+    /// lane, commands keep event order.
+    ///
+    /// With more than one lane, every event already waiting on the bus (up
+    /// to 64) is decided before the tick's commands are dispatched, so
+    /// commands from queued-up events compete by priority. Machines still
+    /// see events in bus order. The default of one lane ignores priorities
+    /// and dispatches each event's commands before deciding the next event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lanes` is zero.
+    pub fn with_priority_lanes(mut self, lanes: u8) -> Self {
+        assert!(lanes > 0, "runtime needs at least 1 priority lane");
+        self.priority_lanes = lanes;
+        self
+    }
+
+    /// Decide up to `max_events` waiting events per tick, dispatching their
+    /// commands together.
+    ///
+    /// When events pile up on the bus, the runtime takes in up to
+    /// `max_events` that are already waiting (it never waits for more),
+    /// decides them all in bus order, and then:
+    ///
+    /// - Inline commands of one type decided for the same correlation ID
+    ///   are dispatched as one batch, so [`Effect::execute_batch`](crate::Effect::execute_batch)
+    ///   sees them together. Commands for different correlation IDs stay in
+    ///   separate batches so their results keep their correlation.
+    /// - Background commands are enqueued together through
+    ///   [`JobQueue::enqueue_batch`](crate::JobQueue::enqueue_batch).
+    ///
+    /// As with priority lanes, machines have decided the whole tick before
+    /// its effects run. Ignored when sharded. The default of 1 decides and
+    /// dispatches one event at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_events` is zero.
+    pub fn with_micro_batching(mut self, max_events: usize) -> Self {
+        assert!(
+            max_events > 0,
+            "micro-batching needs at least 1 event per tick"
+        );
+        self.micro_batch = max_events;
+        self
+    }
+
+    /// Most events decided together in one tick.
+    fn tick_window(&self) -> usize {
+        let lanes = if self.priority_lanes > 1 {
+            PRIORITY_WINDOW
+        } else {
+            1
+        };
+        lanes.max(self.micro_batch)
+    }
+
+    /// Drop events more than `max_hops` deep in their causation chain.
+    ///
+    /// Each event returned by an inline effect is one hop deeper than the
+    /// event whose command produced it. An event deeper than `max_hops` is
+    /// dropped without being decided, and the runtime emits [`LoopDetected`]
+    /// instead. Defaults to 256.
+    pub fn with_max_hops(mut self, max_hops: u32) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    /// Dispatch inline commands on parallel workers, keyed by event.
+    ///
+    /// Each event's inline commands are dispatched on the worker that owns
+    /// the event's [`Sharding`] key: effects for one key run in event order,
+    /// effects for different keys run in parallel. Machines still decide
+    /// every event serially, in bus order. Background and scheduled commands
+    /// go to the job queue as before.
+    ///
+    /// Sharded events are decided one per tick: with
+    /// [priority lanes](Self::with_priority_lanes), lanes order one event's
+    /// batches but waiting events are not pulled into the tick.
+    ///
+    /// Each worker queues up to 64 ticks; when the owning worker's queue is
+    /// full, the runtime waits before deciding further events.
+    pub fn with_sharding(mut self, sharding: Sharding) -> Self {
+        self.sharding = Some(sharding);
+        self
+    }
+
+    /// Write a structured [`AuditRecord`](crate::AuditRecord) for every
+    /// envelope to `sink`.
+    ///
+    /// Unlike the debug audit log, this is available in release builds.
+    /// Records are written from a dedicated task; see [`AuditSink`].
+    #[cfg(feature = "audit")]
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(Arc::new(AuditWriter::new(sink)));
+        self
+    }
+
+    /// Record a causality [`Trace`](crate::Trace) per correlation in
+    /// `recorder`.
+    pub fn with_trace_recorder(mut self, recorder: TraceRecorder) -> Self {
+        self.trace = Some(recorder);
         self
     }
 
+    /// Restore every snapshot machine from the snapshot store.
+    ///
+    /// Returns the number of machines restored. A missing snapshot, a store
+    /// error, or a failed `restore` is logged and leaves that machine as
+    /// constructed. Call this before [`replay`](Self::replay) when combining
+    /// snapshots with a log; otherwise `run` calls it automatically.
+    pub async fn restore_snapshots(&mut self) -> usize {
+        self.snapshots_restored = true;
+        let Some(schedule) = &self.snapshots else {
+            return 0;
+        };
+
+        let mut restored = 0;
+        for machine in &mut self.machines {
+            let Some(key) = machine.snapshot_key() else {
+                continue;
+            };
+            match schedule.store.load(key).await {
+                Ok(Some(snapshot)) => match machine.restore(snapshot.data) {
+                    Ok(()) => {
+                        debug!(key, taken_at = %snapshot.taken_at, "restored machine snapshot");
+                        restored += 1;
+                    }
+                    Err(e) => warn!(key, error = %e, "failed to restore machine snapshot"),
+                },
+                Ok(None) => debug!(key, "no machine snapshot to restore"),
+                Err(e) => warn!(key, error = %e, "failed to load machine snapshot"),
+            }
+        }
+        restored
+    }
+
+    /// Save every snapshot machine to the snapshot store.
+    async fn save_snapshots(&self) {
+        let Some(schedule) = &self.snapshots else {
+            return;
+        };
+        for (key, data) in self.machines.iter().filter_map(MachineRunner::snapshot) {
+            let snapshot = Snapshot {
+                data,
+                taken_at: chrono::Utc::now(),
+            };
+            if let Err(e) = schedule.store.save(key, snapshot).await {
+                warn!(key, error = %e, "failed to save machine snapshot");
+            }
+        }
+    }
+
+    /// Rebuild machine state by replaying events from a log.
+    ///
+    /// Each logged event is passed to every machine's `decide`, in log
+    /// order, but the decided commands are discarded: nothing is dispatched,
+    /// emitted, tapped, or tracked. `until` is the last position to replay
+    /// (inclusive); `None` replays the whole log.
+    ///
+    /// Call this before [`run`](Self::run). A machine that panics is logged,
+    /// counted in the report, and replay continues.
+    pub async fn replay(
+        &mut self,
+        log: &dyn EventLog,
+        until: Option<u64>,
+    ) -> anyhow::Result<ReplayReport> {
+        let mut report = ReplayReport::default();
+        let mut from = 0;
+
+        'pages: loop {
+            let page = log.read(from, REPLAY_PAGE_SIZE).await?;
+            let page_len = page.len();
+
+            for logged in page {
+                if until.is_some_and(|until| logged.position > until) {
+                    break 'pages;
+                }
+                for machine in &mut self.machines {
+                    match machine.decide(logged.envelope.payload.as_ref()) {
+                        Ok(Some(_)) => report.commands_suppressed += 1,
+                        Ok(None) => {}
+                        Err(_) => report.panics += 1,
+                    }
+                }
+                report.events += 1;
+                report.last_position = Some(logged.position);
+                from = logged.position + 1;
+            }
+
+            if page_len < REPLAY_PAGE_SIZE {
+                break;
+            }
+        }
+
+        info!(
+            events = report.events,
+            commands_suppressed = report.commands_suppressed,
+            panics = report.panics,
+            "replayed event log"
+        );
+        Ok(report)
+    }
+
     /// Run the runtime, processing events until the bus is closed.
     ///
     /// This method consumes the runtime and runs the main event loop.
@@ -124,189 +808,549 @@ impl<D: Send + Sync + 'static> Runtime<D> {
     /// This enables effects to optimize batch operations (e.g., bulk inserts)
     /// while maintaining correlation for `emit_and_await`.
     ///
+    /// Batches are dispatched in decide order: a batch goes at the position
+    /// of the first machine that decided its command type. Machines decide
+    /// in registration order, adjusted by
+    /// [`with_machine_priority`](Self::with_machine_priority).
+    ///
     /// Invariant: Events emitted by effects are not processed until the
     /// next tick (via the event bus). This ensures batching is a semantic
     /// no-op—machines never observe effect completion within the same tick.
+    ///
+    /// With [priority lanes](Self::with_priority_lanes), a tick also takes in
+    /// events already waiting on the bus, and its batches are dispatched
+    /// highest lane first. [Micro-batching](Self::with_micro_batching) takes
+    /// in waiting events the same way.
     pub async fn run(mut self) {
         info!(
             machine_count = self.machines.len(),
             "seesaw runtime starting"
         );
 
-        let mut receiver = self.bus.subscribe();
-
-        loop {
-            match receiver.recv().await {
-                Ok(envelope) => {
-                    // RAII guard for event processing - decrements on drop even if we panic
-                    // Only create guard if:
-                    // 1. We have an inflight tracker
-                    // 2. There's pending work for this cid (count > 0)
-                    // This prevents extra decrements for events emitted by effects
-                    // (like CommandFailed) which share the same cid but weren't inc'd separately.
-                    let _event_guard = self.inflight.as_ref().and_then(|tracker| {
-                        if tracker.has_pending_work(envelope.cid) {
-                            Some(InflightGuard::for_event(tracker.clone(), envelope.cid))
-                        } else {
-                            None
-                        }
-                    });
-
-                    // 1. Collect commands from all machines for this event
-                    //    Group inline commands by (TypeId, CorrelationId) for batching
-                    let mut inline_batches: BTreeMap<
-                        (TypeId, crate::core::CorrelationId),
-                        Vec<Box<dyn crate::core::AnyCommand>>,
-                    > = BTreeMap::new();
-
-                    // Debug audit: track which machines observe/emit
-                    #[cfg(debug_assertions)]
-                    let mut audit_builder = AuditEntryBuilder::with_type_id(
-                        envelope.type_id,
-                        // We don't have the event type name in the envelope, use a placeholder
-                        "unknown",
-                    );
-
-                    for machine in &mut self.machines {
-                        // Check if this machine handles this event type
-                        #[cfg(debug_assertions)]
-                        let handles_event = machine.handles_event(envelope.payload.as_ref());
-
-                        // Pass the envelope's payload to machines
-                        match machine.decide(envelope.payload.as_ref()) {
-                            Ok(Some(cmd)) => {
-                                debug!(machine = machine.name(), "machine emitted command");
-
-                                // Record in audit log
-                                #[cfg(debug_assertions)]
-                                {
-                                    audit_builder.observed(machine.name());
-                                    audit_builder.emitted(machine.name());
-                                }
+        let mut receiver = self.bus.subscribe_recovering();
 
-                                let mode = cmd.get_execution_mode();
-                                let type_id = cmd.command_type_id();
+        if !self.snapshots_restored {
+            self.restore_snapshots().await;
+        }
+        #[cfg(feature = "audit")]
+        if let Some(audit) = &self.audit {
+            audit.start();
+        }
+        let health = self.dispatcher.health().clone();
+        health.started(self.machines.len());
+        let ticks = Arc::new(TickDispatch {
+            dispatcher: self.dispatcher.clone(),
+            inflight: self.inflight.clone(),
+            taps: self.taps.clone(),
+            #[cfg(feature = "audit")]
+            audit: self.audit.clone(),
+            trace: self.trace.clone(),
+        });
+        let mut shards = self
+            .sharding
+            .take()
+            .map(|sharding| ShardPool::spawn(sharding, &ticks));
+        let mut snapshot_ticker = self.snapshots.as_ref().map(|schedule| {
+            let mut ticker = tokio::time::interval(schedule.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker
+        });
+        let mut unsaved_events = false;
+        let mut control = std::mem::take(&mut self.control);
 
-                                match mode {
-                                    crate::core::ExecutionMode::Inline => {
-                                        // Group by (TypeId, cid) to maintain correlation per batch
-                                        inline_batches
-                                            .entry((type_id, envelope.cid))
-                                            .or_default()
-                                            .push(cmd);
-                                    }
-                                    crate::core::ExecutionMode::Background
-                                    | crate::core::ExecutionMode::Scheduled { .. } => {
-                                        // Background/scheduled: dispatch immediately to job queue
-                                        if let Err(e) = self.dispatcher.dispatch_one(cmd).await {
-                                            error!(error = %e, "background command dispatch failed");
-                                        }
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                // Machine didn't emit, but may have observed
-                                #[cfg(debug_assertions)]
-                                if handles_event {
-                                    audit_builder.observed(machine.name());
-                                }
-                            }
-                            Err(panic_msg) => {
-                                // Machine panicked - record error for correlation tracking
-                                if let Some(ref inflight) = self.inflight {
-                                    if envelope.cid.is_some() {
-                                        inflight
-                                            .record_error(envelope.cid, anyhow::anyhow!("{}", panic_msg));
-                                    }
-                                }
-                                // Continue processing other machines - one bad machine
-                                // shouldn't stop others from handling this event
-                            }
-                        }
+        loop {
+            let received = tokio::select! {
+                biased;
+                // Wiring changes go ahead of waiting events, so an event
+                // emitted after a change is applied sees the new wiring
+                request = control.recv() => {
+                    let _ = request.done.send(self.apply_change(request.change));
+                    health.machines_changed(self.machines.len());
+                    continue;
+                }
+                _ = next_snapshot(&mut snapshot_ticker) => {
+                    if unsaved_events {
+                        self.save_snapshots().await;
+                        unsaved_events = false;
                     }
+                    continue;
+                }
+                received = receiver.recv() => received,
+            };
 
-                    // Record audit entry
-                    #[cfg(debug_assertions)]
-                    self.audit_log.record(audit_builder.build());
-
-                    // 2. Dispatch inline batches (deterministic order via BTreeMap)
-                    for ((type_id, cid), batch) in inline_batches {
-                        let batch_size = batch.len();
-                        if batch_size > 1 {
-                            debug!(batch_size, ?type_id, %cid, "dispatching command batch");
-                        }
-
-                        // Dispatch with correlation for inflight tracking
-                        if let Err(e) = self
-                            .dispatcher
-                            .dispatch_with_correlation(batch, cid, self.inflight.as_ref())
-                            .await
-                        {
-                            error!(error = %e, "batch dispatch failed");
-                            // Record error for correlation
-                            if let Some(tracker) = &self.inflight {
-                                tracker.record_error(cid, e);
+            match received {
+                Ok(envelope) => {
+                    unsaved_events = true;
+
+                    let mut tick = Tick::default();
+                    self.decide_envelope(envelope, &mut tick).await;
+
+                    // With priority lanes or micro-batching, events already waiting
+                    // on the bus join this tick so their commands compete by
+                    // priority and batch together
+                    if shards.is_none() {
+                        let window = self.tick_window();
+                        while tick.envelopes.len() < window {
+                            match receiver.try_recv() {
+                                Ok(envelope) => self.decide_envelope(envelope, &mut tick).await,
+                                Err(TryRecvError::Lagged(n)) => {
+                                    warn!(missed = n, "event bus lagged, missed events");
+                                    metrics::bus_lagged(n);
+                                }
+                                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
                             }
                         }
                     }
 
-                    // 3. Run event taps (after effects complete)
-                    // Taps observe committed facts - they run fire-and-forget
-                    if !self.taps.is_empty() {
-                        self.taps
-                            .run_all(envelope.payload.as_ref(), Some(envelope.cid));
+                    self.enqueue_jobs(&mut tick).await;
+                    match &shards {
+                        Some(shards) => shards.dispatch(tick).await,
+                        None => ticks.dispatch(tick).await,
                     }
+                    metrics::bus_queued(self.bus.buffered());
                 }
                 Err(RecvError::Lagged(n)) => {
                     warn!(missed = n, "event bus lagged, missed events");
+                    metrics::bus_lagged(n);
                 }
                 Err(RecvError::Closed) => {
                     info!("event bus closed, runtime shutting down");
+                    if let Some(shards) = shards.take() {
+                        shards.drain().await;
+                    }
+                    if unsaved_events {
+                        self.save_snapshots().await;
+                    }
                     break;
                 }
             }
         }
 
+        health.stopped();
         info!("seesaw runtime stopped");
     }
 
-    /// Get the number of registered machines.
-    pub fn machine_count(&self) -> usize {
-        self.machines.len()
+    /// Apply a wiring change from the control channel, returning the number
+    /// of machines or effects added or removed.
+    fn apply_change(&mut self, change: WiringChange) -> anyhow::Result<usize> {
+        match change {
+            WiringChange::AddMachine(machine) => {
+                info!(machine = machine.name(), "adding machine");
+                self.add_machine(*machine);
+                Ok(1)
+            }
+            WiringChange::RemoveMachine(machine_type) => {
+                let before = self.machines.len();
+                self.machines
+                    .retain(|machine| machine.machine_type() != machine_type);
+                let removed = before - self.machines.len();
+                info!(removed, "removed machines");
+                Ok(removed)
+            }
+            WiringChange::AddEffect { add, deps_type } => {
+                let add =
+                    add.downcast::<AddEffect<D>>()
+                        .map_err(|_| SeesawError::DepsMismatch {
+                            expected: std::any::type_name::<D>(),
+                            actual: deps_type,
+                        })?;
+                add(&self.dispatcher)?;
+                Ok(1)
+            }
+            WiringChange::RemoveEffect(command_type) => {
+                Ok(self.dispatcher.remove_effect(command_type).into())
+            }
+        }
     }
 
-    /// Get access to the dispatcher.
-    pub fn dispatcher(&self) -> &Dispatcher<D> {
-        &self.dispatcher
-    }
+    /// Enqueue the background commands micro-batching deferred, together.
+    async fn enqueue_jobs(&self, tick: &mut Tick) {
+        if tick.jobs.is_empty() {
+            return;
+        }
+        let (owners, commands): (Vec<_>, Vec<_>) = std::mem::take(&mut tick.jobs)
+            .into_iter()
+            .map(|(seq, command_type, cmd)| ((seq, command_type), cmd))
+            .unzip();
+
+        #[cfg(feature = "audit")]
+        let enqueue_started = Instant::now();
+        let enqueued = self.dispatcher.enqueue_background(commands).await;
+
+        #[cfg_attr(not(feature = "audit"), allow(unused_variables))]
+        for ((seq, command_type), result) in owners.into_iter().zip(enqueued) {
+            #[cfg(feature = "audit")]
+            if let Some(record) = &mut tick.audits[seq] {
+                record.effect(
+                    command_type,
+                    AuditExecution::Background,
+                    1,
+                    enqueue_started.elapsed(),
+                    result.as_ref().err().map(ToString::to_string),
+                );
+            }
 
-    /// Get access to the event bus.
-    pub fn bus(&self) -> &EventBus {
-        &self.bus
+            if let Err(e) = result {
+                error!(error = %e, "background command dispatch failed");
+            }
+        }
     }
 
-    /// Get access to the audit log (debug builds only).
-    ///
-    /// The audit log tracks which machines observe and emit commands for each event.
-    /// Use this to debug wiring issues or find dead machines.
-    ///
-    /// # Example
+    /// Pass one event to every machine, collecting its inline commands into `tick`.
     ///
-    /// ```ignore
-    /// #[cfg(debug_assertions)]
-    /// {
-    ///     let audit = runtime.audit_log();
-    ///     for entry in audit.silent_events() {
-    ///         tracing::warn!(
-    ///             event_type = entry.event_type_name,
-    ///             observers = ?entry.observers,
-    ///             "event had no command emitters"
-    ///         );
-    ///     }
-    /// }
-    /// ```
-    #[cfg(debug_assertions)]
-    pub fn audit_log(&self) -> &SharedAuditLog {
+    /// Background and scheduled commands are handed to the job queue immediately,
+    /// except background commands deferred by micro-batching.
+    async fn decide_envelope(&mut self, envelope: EventEnvelope, tick: &mut Tick) {
+        let span = spans::event(&envelope);
+        self.decide_in_span(envelope, tick, &span)
+            .instrument(span.clone())
+            .await;
+    }
+
+    /// Body of [`decide_envelope`](Self::decide_envelope), run inside the
+    /// event's span.
+    async fn decide_in_span(&mut self, envelope: EventEnvelope, tick: &mut Tick, span: &Span) {
+        self.dispatcher.health().record_event();
+        metrics::event_processed();
+
+        // RAII guard for event processing - decrements on drop even if we panic
+        // Only create guard if:
+        // 1. We have an inflight tracker
+        // 2. There's pending work for this cid (count > 0)
+        // This prevents extra decrements for events emitted by effects
+        // (like CommandFailed) which share the same cid but weren't inc'd separately.
+        let event_guard = self.inflight.as_ref().and_then(|tracker| {
+            if tracker.has_pending_work(envelope.cid) {
+                Some(InflightGuard::for_event(tracker.clone(), envelope.cid))
+            } else {
+                None
+            }
+        });
+
+        #[cfg(feature = "audit")]
+        let mut audit_record = self
+            .audit
+            .as_ref()
+            .map(|_| AuditRecordBuilder::new(&envelope));
+        let trace_event = self.trace.as_ref().map(TraceRecorder::next_id);
+
+        // Causation chain too deep - almost certainly a feedback loop.
+        // Drop the event instead of feeding it back into the machines.
+        if envelope.hops > self.max_hops {
+            warn!(
+                cid = %envelope.cid,
+                hops = envelope.hops,
+                max_hops = self.max_hops,
+                "event loop detected, dropping event"
+            );
+            let detected = LoopDetected {
+                event_type: envelope.type_id,
+                hops: envelope.hops,
+                max_hops: self.max_hops,
+            };
+            self.bus.emit_envelope(
+                EventEnvelope::new(envelope.cid, detected).with_metadata(envelope.metadata.clone()),
+            );
+            #[cfg(feature = "audit")]
+            if let (Some(audit), Some(mut record)) = (&self.audit, audit_record) {
+                record.dropped();
+                audit.write(record.finish());
+            }
+            if let (Some(trace), Some(id)) = (&self.trace, trace_event) {
+                trace.event(id, &envelope, None, true);
+            }
+            return;
+        }
+
+        // Waited out its TTL in a backlog - acting on it now is pointless
+        if self.bus.expire_stale(&envelope) {
+            #[cfg(feature = "audit")]
+            if let (Some(audit), Some(mut record)) = (&self.audit, audit_record) {
+                record.dropped();
+                audit.write(record.finish());
+            }
+            if let (Some(trace), Some(id)) = (&self.trace, trace_event) {
+                trace.event(id, &envelope, None, true);
+            }
+            return;
+        }
+
+        // 1. Collect commands from all machines for this event
+        //    Group inline commands by (lane, event, machine, TypeId, CorrelationId) for batching
+        let seq = tick.envelopes.len();
+
+        // Debug audit: track which machines observe/emit
+        #[cfg(debug_assertions)]
+        let mut audit_builder = AuditEntryBuilder::with_type_id(
+            envelope.type_id,
+            // We don't have the event type name in the envelope, use a placeholder
+            "unknown",
+        );
+
+        // Batches of one command type dispatch at the position of the first
+        // machine that decided it, so dispatch follows decide order
+        let mut first_decided: HashMap<TypeId, usize> = HashMap::new();
+        // Type name from the first running machine that subscribes to the
+        // event's type; `None` if none does
+        let mut event_type = None;
+
+        for (position, machine) in self.machines.iter_mut().enumerate() {
+            // Stopped by its supervisor - isolated from the event stream
+            if machine.is_stopped() {
+                continue;
+            }
+            if event_type.is_none() && machine.handles_event(envelope.payload.as_ref()) {
+                event_type = Some(machine.event_type_name());
+            }
+
+            // Check if this machine handles this event type (and its filters pass)
+            let handles_event = machine.accepts(envelope.payload.as_ref());
+            let decide_span = if handles_event {
+                span.record("event_type", machine.event_type_name());
+                spans::decide(
+                    machine.name(),
+                    machine.event_type_name(),
+                    machine.command_type_name(),
+                )
+            } else {
+                Span::none()
+            };
+
+            let decide_started = Instant::now();
+
+            // Pass the envelope's payload to machines
+            let mut result = decide_span.in_scope(|| {
+                machine.decide_with_middleware(
+                    envelope.payload.as_ref(),
+                    envelope.cid,
+                    &self.machine_middleware,
+                )
+            });
+            if handles_event {
+                metrics::decided(machine.name(), decide_started.elapsed());
+            }
+
+            // Apply the supervision policy before acting on the decision
+            if let Some(supervised) = machine.supervise(&mut result) {
+                warn!(
+                    machine = supervised.machine,
+                    reason = ?supervised.reason,
+                    action = ?supervised.action,
+                    "machine supervised"
+                );
+                self.bus.emit_with_correlation(supervised, envelope.cid);
+            }
+            decide_span.record(
+                "outcome",
+                match &result {
+                    Ok(Some(_)) => "command",
+                    Ok(None) => "none",
+                    Err(_) => "panicked",
+                },
+            );
+
+            #[cfg(feature = "audit")]
+            if let Some(record) = audit_record.as_mut().filter(|_| handles_event) {
+                let (command_type, panic) = match &result {
+                    Ok(cmd) => (cmd.as_ref().map(|_| machine.command_type_name()), None),
+                    Err(panic_msg) => (None, Some(panic_msg.clone())),
+                };
+                record.decided(
+                    machine.name(),
+                    machine.event_type_name(),
+                    command_type,
+                    panic,
+                    decide_started.elapsed(),
+                );
+            }
+
+            match result {
+                Ok(Some(cmd)) => {
+                    debug!(machine = machine.name(), "machine emitted command");
+
+                    // Record in audit log
+                    #[cfg(debug_assertions)]
+                    {
+                        audit_builder.observed(machine.name());
+                        audit_builder.emitted(machine.name());
+                    }
+
+                    let mode = cmd.get_execution_mode();
+                    let type_id = cmd.command_type_id();
+                    let decision = self.trace.as_ref().zip(trace_event).map(|(trace, event)| {
+                        let mode = match mode {
+                            crate::core::ExecutionMode::Inline => "inline",
+                            crate::core::ExecutionMode::Background => "background",
+                            crate::core::ExecutionMode::Scheduled { .. } => "scheduled",
+                            crate::core::ExecutionMode::Recurring { .. } => "recurring",
+                        };
+                        trace.decided(
+                            envelope.cid,
+                            event,
+                            machine.name(),
+                            machine.command_type_name(),
+                            mode,
+                        )
+                    });
+
+                    match mode {
+                        crate::core::ExecutionMode::Inline => {
+                            metrics::command_dispatched("inline");
+
+                            #[cfg(feature = "audit")]
+                            if let Some(record) = &mut audit_record {
+                                record.batched(type_id, machine.command_type_name());
+                            }
+
+                            // Group by (lane, TypeId, cid) to maintain correlation per batch
+                            let lane = cmd.get_priority().min(self.priority_lanes - 1);
+                            let rank = *first_decided.entry(type_id).or_insert(position);
+                            // Micro-batching joins the first batch of this type
+                            // and correlation in the tick
+                            let (batch_seq, batch_rank) = if self.micro_batch > 1 {
+                                *tick
+                                    .merged
+                                    .entry((lane, type_id, envelope.cid))
+                                    .or_insert((seq, rank))
+                            } else {
+                                (seq, rank)
+                            };
+                            if let Some(decision) = decision {
+                                tick.traced
+                                    .entry((batch_seq, type_id))
+                                    .or_insert((decision, machine.command_type_name()));
+                            }
+                            tick.batches
+                                .entry((
+                                    Reverse(lane),
+                                    batch_seq,
+                                    batch_rank,
+                                    type_id,
+                                    envelope.cid,
+                                ))
+                                .or_default()
+                                .push(cmd);
+                        }
+                        crate::core::ExecutionMode::Background if self.micro_batch > 1 => {
+                            metrics::command_dispatched("background");
+                            tick.jobs.push((seq, machine.command_type_name(), cmd));
+                        }
+                        crate::core::ExecutionMode::Background
+                        | crate::core::ExecutionMode::Scheduled { .. }
+                        | crate::core::ExecutionMode::Recurring { .. } => {
+                            // Background/scheduled/recurring: dispatch immediately to job queue
+                            metrics::command_dispatched(match mode {
+                                crate::core::ExecutionMode::Scheduled { .. } => "scheduled",
+                                crate::core::ExecutionMode::Recurring { .. } => "recurring",
+                                _ => "background",
+                            });
+                            #[cfg(feature = "audit")]
+                            let enqueue_started = Instant::now();
+                            let enqueued = self.dispatcher.dispatch_one(cmd).await;
+
+                            #[cfg(feature = "audit")]
+                            if let Some(record) = &mut audit_record {
+                                let execution = match mode {
+                                    crate::core::ExecutionMode::Scheduled { .. } => {
+                                        AuditExecution::Scheduled
+                                    }
+                                    crate::core::ExecutionMode::Recurring { .. } => {
+                                        AuditExecution::Recurring
+                                    }
+                                    _ => AuditExecution::Background,
+                                };
+                                record.effect(
+                                    machine.command_type_name(),
+                                    execution,
+                                    1,
+                                    enqueue_started.elapsed(),
+                                    enqueued.as_ref().err().map(ToString::to_string),
+                                );
+                            }
+
+                            if let Err(e) = enqueued {
+                                error!(error = %e, "background command dispatch failed");
+                            }
+                        }
+                    }
+                }
+                Ok(None) => {
+                    // Machine didn't emit, but may have observed
+                    #[cfg(debug_assertions)]
+                    if handles_event {
+                        audit_builder.observed(machine.name());
+                    }
+                }
+                Err(panic_msg) => {
+                    // Machine panicked - record error for correlation tracking
+                    if let Some(ref inflight) = self.inflight {
+                        if envelope.cid.is_some() {
+                            inflight.record_error(envelope.cid, anyhow::anyhow!("{}", panic_msg));
+                        }
+                    }
+                    // Continue processing other machines - one bad machine
+                    // shouldn't stop others from handling this event
+                }
+            }
+        }
+
+        // Record audit entry
+        #[cfg(debug_assertions)]
+        self.audit_log.record(audit_builder.build());
+
+        if event_type.is_none() && !self.taps.observes(envelope.type_id) {
+            debug!(cid = %envelope.cid, "no machine or tap handles event");
+            self.bus
+                .dead_letter(envelope.clone(), DeadLetterReason::Unhandled);
+        }
+
+        if let (Some(trace), Some(id)) = (&self.trace, trace_event) {
+            trace.event(id, &envelope, event_type, false);
+        }
+        tick.envelopes.push((envelope, event_guard));
+        tick.event_types.push(event_type);
+        tick.spans.push(span.clone());
+        #[cfg(feature = "audit")]
+        tick.audits.push(audit_record);
+    }
+
+    /// Get the number of registered machines.
+    pub fn machine_count(&self) -> usize {
+        self.machines.len()
+    }
+
+    /// Get access to the dispatcher.
+    pub fn dispatcher(&self) -> &Dispatcher<D> {
+        &self.dispatcher
+    }
+
+    /// Get access to the event bus.
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
+
+    /// Get access to the audit log (debug builds only).
+    ///
+    /// The audit log tracks which machines observe and emit commands for each event.
+    /// Use this to debug wiring issues or find dead machines.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// #[cfg(debug_assertions)]
+    /// {
+    ///     let audit = runtime.audit_log();
+    ///     for entry in audit.silent_events() {
+    ///         tracing::warn!(
+    ///             event_type = entry.event_type_name,
+    ///             observers = ?entry.observers,
+    ///             "event had no command emitters"
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    #[cfg(debug_assertions)]
+    pub fn audit_log(&self) -> &SharedAuditLog {
         &self.audit_log
     }
 }
@@ -319,6 +1363,9 @@ impl<D> std::fmt::Debug for Runtime<D> {
     }
 }
 
+/// Deferred dispatcher configuration applied by `RuntimeBuilder::build`.
+type DispatcherStep<D> = Box<dyn FnOnce(Dispatcher<D>) -> Dispatcher<D>>;
+
 /// Builder for constructing a complete seesaw application.
 ///
 /// `RuntimeBuilder` provides a convenient way to assemble all the
@@ -340,9 +1387,20 @@ impl<D> std::fmt::Debug for Runtime<D> {
 pub struct RuntimeBuilder<D> {
     deps: D,
     machines: Vec<MachineRunner>,
+    machine_priorities: HashMap<TypeId, i32>,
+    machine_filters: HashMap<TypeId, Vec<(TypeId, EventFilter)>>,
+    machine_middleware: Vec<Arc<dyn MachineMiddleware>>,
     bus: EventBus,
     job_queue: Option<Arc<dyn JobQueue>>,
-    effects: Vec<Box<dyn FnOnce(Dispatcher<D>) -> Dispatcher<D>>>,
+    effects: Vec<DispatcherStep<D>>,
+    snapshots: Option<SnapshotSchedule>,
+    priority_lanes: u8,
+    micro_batch: usize,
+    max_hops: u32,
+    sharding: Option<Sharding>,
+    #[cfg(feature = "audit")]
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    trace: Option<TraceRecorder>,
 }
 
 impl<D: Send + Sync + 'static> RuntimeBuilder<D> {
@@ -351,9 +1409,20 @@ impl<D: Send + Sync + 'static> RuntimeBuilder<D> {
         Self {
             deps,
             machines: Vec::new(),
+            machine_priorities: HashMap::new(),
+            machine_filters: HashMap::new(),
+            machine_middleware: Vec::new(),
             bus: EventBus::new(),
             job_queue: None,
             effects: Vec::new(),
+            snapshots: None,
+            priority_lanes: 1,
+            micro_batch: 1,
+            max_hops: DEFAULT_MAX_HOPS,
+            sharding: None,
+            #[cfg(feature = "audit")]
+            audit_sink: None,
+            trace: None,
         }
     }
 
@@ -375,6 +1444,135 @@ impl<D: Send + Sync + 'static> RuntimeBuilder<D> {
         self
     }
 
+    /// Set the decide priority of every machine of type `M`.
+    ///
+    /// See [`Runtime::with_machine_priority`].
+    pub fn with_machine_priority<M: Machine>(mut self, priority: i32) -> Self {
+        self.machine_priorities.insert(TypeId::of::<M>(), priority);
+        self
+    }
+
+    /// Only pass events of type `E` to machines of type `M` when `filter`
+    /// returns true.
+    ///
+    /// See [`Runtime::with_machine_filter`].
+    pub fn with_machine_filter<M, E, F>(mut self, filter: F) -> Self
+    where
+        M: Machine,
+        E: Event,
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        let filters = self.machine_filters.entry(TypeId::of::<M>()).or_default();
+        filters.retain(|(event_type, _)| *event_type != TypeId::of::<E>());
+        filters.push((TypeId::of::<E>(), event_filter(filter)));
+        self
+    }
+
+    /// Add a machine that subscribes to several event types.
+    ///
+    /// See [`Runtime::with_selector_machine`].
+    pub fn with_selector_machine<M>(mut self, machine: M) -> Self
+    where
+        M: Machine,
+        M::Event: EventSelector,
+    {
+        self.machines.push(MachineRunner::new_selector(machine));
+        self
+    }
+
+    /// Add a machine whose state is visible to machine middleware (debug builds).
+    pub fn with_debug_machine<M: Machine + std::fmt::Debug>(mut self, machine: M) -> Self {
+        self.machines.push(MachineRunner::new_debug(machine));
+        self
+    }
+
+    /// Add a middleware that runs around every `Machine::decide` call.
+    pub fn with_machine_middleware<M: MachineMiddleware>(mut self, middleware: M) -> Self {
+        self.machine_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Add a machine whose state is persisted to the snapshot store.
+    pub fn with_snapshot_machine<M: SnapshotMachine>(mut self, machine: M) -> Self {
+        self.machines.push(MachineRunner::new_snapshot(machine));
+        self
+    }
+
+    /// Add a machine that is restarted or stopped when it misbehaves.
+    pub fn with_supervised_machine<M, F>(mut self, factory: F, policy: SupervisorPolicy) -> Self
+    where
+        M: Machine,
+        F: Fn() -> M + Send + Sync + 'static,
+    {
+        self.machines
+            .push(MachineRunner::new_supervised(factory, policy));
+        self
+    }
+
+    /// Persist snapshot machines to `store` every `interval`.
+    pub fn with_snapshot_store(
+        mut self,
+        store: Arc<dyn SnapshotStore>,
+        interval: Duration,
+    ) -> Self {
+        self.snapshots = Some(SnapshotSchedule { store, interval });
+        self
+    }
+
+    /// Dispatch inline commands through `lanes` priority lanes.
+    ///
+    /// See [`Runtime::with_priority_lanes`].
+    pub fn with_priority_lanes(mut self, lanes: u8) -> Self {
+        assert!(lanes > 0, "runtime needs at least 1 priority lane");
+        self.priority_lanes = lanes;
+        self
+    }
+
+    /// Decide up to `max_events` waiting events per tick.
+    ///
+    /// See [`Runtime::with_micro_batching`].
+    pub fn with_micro_batching(mut self, max_events: usize) -> Self {
+        assert!(
+            max_events > 0,
+            "micro-batching needs at least 1 event per tick"
+        );
+        self.micro_batch = max_events;
+        self
+    }
+
+    /// Drop events more than `max_hops` deep in their causation chain.
+    ///
+    /// See [`Runtime::with_max_hops`].
+    pub fn with_max_hops(mut self, max_hops: u32) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    /// Dispatch inline commands on parallel workers, keyed by event.
+    ///
+    /// See [`Runtime::with_sharding`].
+    pub fn with_sharding(mut self, sharding: Sharding) -> Self {
+        self.sharding = Some(sharding);
+        self
+    }
+
+    /// Write a structured audit record for every envelope to `sink`.
+    ///
+    /// See [`Runtime::with_audit_sink`].
+    #[cfg(feature = "audit")]
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Record a causality trace per correlation in `recorder`.
+    ///
+    /// See [`Runtime::with_trace_recorder`].
+    pub fn with_trace_recorder(mut self, recorder: TraceRecorder) -> Self {
+        self.trace = Some(recorder);
+        self
+    }
+
     /// Register an effect handler for a command type.
     pub fn with_effect<C, E>(mut self, effect: E) -> Self
     where
@@ -406,15 +1604,31 @@ impl<D: Send + Sync + 'static> RuntimeBuilder<D> {
         }
 
         // Build runtime
-        let runtime = Runtime {
-            machines: self.machines,
-            dispatcher,
+        let mut runtime = Runtime {
+            machines: Vec::new(),
+            machine_priorities: self.machine_priorities,
+            machine_filters: self.machine_filters,
+            machine_middleware: self.machine_middleware,
+            dispatcher: Arc::new(dispatcher),
             bus: bus.clone(),
             inflight: None,
-            taps: TapRegistry::new(),
+            taps: Arc::new(TapRegistry::new()),
+            snapshots: self.snapshots,
+            snapshots_restored: false,
+            priority_lanes: self.priority_lanes,
+            micro_batch: self.micro_batch,
+            max_hops: self.max_hops,
+            sharding: self.sharding,
+            control: ControlReceiver::default(),
             #[cfg(debug_assertions)]
             audit_log: Arc::new(AuditLog::new()),
+            #[cfg(feature = "audit")]
+            audit: self.audit_sink.map(|sink| Arc::new(AuditWriter::new(sink))),
+            trace: self.trace,
         };
+        for machine in self.machines {
+            runtime.add_machine(machine);
+        }
 
         (runtime, bus)
     }
@@ -423,7 +1637,7 @@ impl<D: Send + Sync + 'static> RuntimeBuilder<D> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::{Command, Event};
+    use crate::core::{Command, CorrelationId};
     use crate::effect_impl::Effect;
     use anyhow::Result;
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -653,6 +1867,639 @@ mod tests {
         let _ = tokio::time::timeout(Duration::from_millis(100), handle).await;
     }
 
+    #[tokio::test]
+    async fn test_runtime_propagates_correlation_through_cascade() {
+        struct RecordingEffect(Arc<std::sync::Mutex<Vec<CorrelationId>>>);
+
+        #[async_trait::async_trait]
+        impl Effect<TestCommand, TestDeps> for RecordingEffect {
+            type Event = TestEvent;
+
+            async fn execute(
+                &self,
+                cmd: TestCommand,
+                ctx: crate::effect_impl::EffectContext<TestDeps>,
+            ) -> Result<TestEvent> {
+                self.0.lock().unwrap().push(ctx.correlation_id());
+                Ok(match cmd {
+                    TestCommand::Process { n } => TestEvent::Step { n },
+                    TestCommand::Finish => TestEvent::Done,
+                })
+            }
+        }
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus.clone())
+            .with_effect::<TestCommand, _>(RecordingEffect(seen.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_machine(TestMachine::new());
+
+        let mut rx = bus.subscribe();
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let cid = CorrelationId::new();
+        bus.emit_with_correlation(TestEvent::Start, cid);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Start, Step 1..=3, Done - every event in the cascade shares the cid
+        let mut cids = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            cids.push(envelope.cid);
+        }
+        assert_eq!(cids, vec![cid; 5]);
+        assert_eq!(*seen.lock().unwrap(), vec![cid; 4]);
+
+        drop(bus);
+        let _ = tokio::time::timeout(Duration::from_millis(100), handle).await;
+    }
+
+    #[tokio::test]
+    async fn test_effect_events_are_one_hop_deeper() {
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus.clone())
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: Arc::new(AtomicUsize::new(0)),
+                finish_count: Arc::new(AtomicUsize::new(0)),
+            });
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_machine(TestMachine::new());
+
+        let mut rx = bus.subscribe();
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        bus.emit(TestEvent::Start);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut hops = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            hops.push(envelope.hops);
+        }
+        assert_eq!(hops, vec![0, 1, 2, 3, 4]);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_block_bus_delivers_effect_events_to_a_busy_runtime() {
+        use crate::bus::BackpressurePolicy;
+
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let finish_count = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::with_capacity(2).with_backpressure(BackpressurePolicy::Block);
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus.clone())
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: finish_count.clone(),
+            });
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_machine(TestMachine::new());
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Keeps the buffer full while effects emit their events
+        for _ in 0..4 {
+            bus.emit(TestEvent::Start);
+        }
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while finish_count.load(Ordering::Relaxed) < 4 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("every chain should finish");
+
+        assert_eq!(process_count.load(Ordering::Relaxed), 12);
+        assert_eq!(bus.stats().rejected, 0);
+        assert!(bus.stats().deferred > 0);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_runtime_drops_events_past_max_hops() {
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let finish_count = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new(TestDeps { value: 0 }, bus.clone())
+            .with_effect::<TestCommand, _>(TestEffect {
+                process_count: process_count.clone(),
+                finish_count: finish_count.clone(),
+            });
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(TestMachine::new())
+            .with_max_hops(2);
+
+        let mut rx = bus.subscribe();
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let cid = CorrelationId::new();
+        bus.emit_with_correlation(TestEvent::Start, cid);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        // Step 3 arrives three hops deep and is never decided
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
+        assert_eq!(finish_count.load(Ordering::Relaxed), 0);
+
+        let (envelope, detected) = loop {
+            let envelope = rx.recv().await.unwrap();
+            if let Some(detected) = envelope.downcast_ref::<LoopDetected>() {
+                break (envelope.clone(), detected.clone());
+            }
+        };
+        assert_eq!(envelope.cid, cid);
+        assert_eq!(detected.event_type, TypeId::of::<TestEvent>());
+        assert_eq!(detected.hops, 3);
+        assert_eq!(detected.max_hops, 2);
+    }
+
+    #[derive(Debug, Clone)]
+    struct Job {
+        id: u32,
+        urgent: bool,
+    }
+
+    #[derive(Debug, Clone)]
+    struct RunJob(Job);
+    impl Command for RunJob {
+        fn priority(&self) -> u8 {
+            u8::from(self.0.urgent)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct JobRan;
+
+    struct JobMachine;
+    impl Machine for JobMachine {
+        type Event = Job;
+        type Command = RunJob;
+
+        fn decide(&mut self, event: &Job) -> Option<RunJob> {
+            Some(RunJob(event.clone()))
+        }
+    }
+
+    struct SlowJobEffect(Arc<std::sync::Mutex<Vec<u32>>>);
+
+    #[async_trait::async_trait]
+    impl Effect<RunJob, ()> for SlowJobEffect {
+        type Event = JobRan;
+
+        async fn execute(
+            &self,
+            cmd: RunJob,
+            _ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<JobRan> {
+            self.0.lock().unwrap().push(cmd.0.id);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(JobRan)
+        }
+    }
+
+    /// Run job 0, then queue three bulk jobs and an urgent one while it runs.
+    async fn run_jobs(lanes: u8) -> Vec<u32> {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<RunJob, _>(SlowJobEffect(order.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(JobMachine)
+            .with_priority_lanes(lanes);
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        bus.emit(Job {
+            id: 0,
+            urgent: false,
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        for id in 1..=3 {
+            bus.emit(Job { id, urgent: false });
+        }
+        bus.emit(Job {
+            id: 4,
+            urgent: true,
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        handle.abort();
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn test_sharding_keeps_key_order_and_runs_keys_in_parallel() {
+        // Jobs are keyed by group: id / 10
+        let sharding = Sharding::new(2).with_key(|job: &Job| job.id / 10);
+        let shard = |group: u32| {
+            sharding.shard_of(&EventEnvelope::new_random(Job {
+                id: group * 10,
+                urgent: false,
+            }))
+        };
+        let b = (1..).find(|&group| shard(group) != shard(0)).unwrap();
+
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<RunJob, _>(SlowJobEffect(order.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(JobMachine)
+            .with_sharding(sharding.clone());
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        for id in [0, 1, 2, b * 10, b * 10 + 1, b * 10 + 2] {
+            bus.emit(Job { id, urgent: false });
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        handle.abort();
+
+        let order = order.lock().unwrap().clone();
+        let group =
+            |g: u32| -> Vec<u32> { order.iter().copied().filter(|id| id / 10 == g).collect() };
+        assert_eq!(group(0), vec![0, 1, 2]);
+        assert_eq!(group(b), vec![b * 10, b * 10 + 1, b * 10 + 2]);
+
+        // Group b started before group a's first job finished
+        let position = |id: u32| order.iter().position(|&x| x == id).unwrap();
+        assert!(
+            position(b * 10) < position(1),
+            "keys ran serially: {order:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_priority_lanes_let_urgent_commands_jump_the_queue() {
+        assert_eq!(run_jobs(2).await, vec![0, 4, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_single_lane_dispatches_in_event_order() {
+        assert_eq!(run_jobs(1).await, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[derive(Debug, Clone)]
+    struct Checkout;
+
+    #[derive(Debug, Clone)]
+    struct Reserve;
+    impl Command for Reserve {}
+
+    #[derive(Debug, Clone)]
+    struct Charge;
+    impl Command for Charge {}
+
+    struct ReserveMachine;
+    impl Machine for ReserveMachine {
+        type Event = Checkout;
+        type Command = Reserve;
+
+        fn decide(&mut self, _event: &Checkout) -> Option<Reserve> {
+            Some(Reserve)
+        }
+    }
+
+    struct ChargeMachine;
+    impl Machine for ChargeMachine {
+        type Event = Checkout;
+        type Command = Charge;
+
+        fn decide(&mut self, _event: &Checkout) -> Option<Charge> {
+            Some(Charge)
+        }
+    }
+
+    struct RecordEffect(&'static str, Arc<std::sync::Mutex<Vec<&'static str>>>);
+
+    #[async_trait::async_trait]
+    impl<C: Command> Effect<C, ()> for RecordEffect {
+        type Event = JobRan;
+
+        async fn execute(
+            &self,
+            _cmd: C,
+            _ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<JobRan> {
+            self.1.lock().unwrap().push(self.0);
+            Ok(JobRan)
+        }
+    }
+
+    /// Emit one `Checkout` to a reserve and a charge machine, in that order.
+    async fn checkout_order(charge_priority: Option<i32>) -> Vec<&'static str> {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new((), bus.clone())
+            .with_effect::<Reserve, _>(RecordEffect("reserve", order.clone()))
+            .with_effect::<Charge, _>(RecordEffect("charge", order.clone()));
+        let mut runtime = Runtime::new(dispatcher, bus.clone());
+        if let Some(priority) = charge_priority {
+            runtime = runtime.with_machine_priority::<ChargeMachine>(priority);
+        }
+        let runtime = runtime
+            .with_machine(ReserveMachine)
+            .with_machine(ChargeMachine);
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Checkout);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        handle.abort();
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn test_machines_on_one_event_dispatch_in_registration_order() {
+        assert_eq!(checkout_order(None).await, vec!["reserve", "charge"]);
+    }
+
+    #[tokio::test]
+    async fn test_machine_priority_reorders_decide_and_dispatch() {
+        assert_eq!(checkout_order(Some(1)).await, vec!["charge", "reserve"]);
+    }
+
+    #[tokio::test]
+    async fn test_machine_filter_applies_to_machines_added_before_and_after() {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<RunJob, _>(SlowJobEffect(order.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(JobMachine)
+            .with_machine_filter::<JobMachine, Job, _>(|job| job.urgent)
+            .with_machine(JobMachine);
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Job {
+            id: 1,
+            urgent: false,
+        });
+        bus.emit(Job {
+            id: 2,
+            urgent: true,
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        // Both machines see only the urgent job
+        assert_eq!(*order.lock().unwrap(), vec![2, 2]);
+    }
+
+    #[derive(Debug, Clone)]
+    struct Indexed(u32);
+
+    #[derive(Debug, Clone)]
+    struct IndexDoc;
+    impl Command for IndexDoc {}
+
+    #[derive(Debug, Clone, serde::Serialize)]
+    struct NotifyDoc(u32);
+    impl Command for NotifyDoc {
+        fn execution_mode(&self) -> crate::core::ExecutionMode {
+            crate::core::ExecutionMode::Background
+        }
+
+        fn job_spec(&self) -> Option<crate::core::JobSpec> {
+            Some(crate::core::JobSpec::new("doc:notify"))
+        }
+
+        fn serialize_to_json(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self).ok()
+        }
+    }
+
+    struct IndexMachine;
+    impl Machine for IndexMachine {
+        type Event = Indexed;
+        type Command = IndexDoc;
+
+        fn decide(&mut self, _event: &Indexed) -> Option<IndexDoc> {
+            Some(IndexDoc)
+        }
+    }
+
+    struct NotifyMachine;
+    impl Machine for NotifyMachine {
+        type Event = Indexed;
+        type Command = NotifyDoc;
+
+        fn decide(&mut self, event: &Indexed) -> Option<NotifyDoc> {
+            Some(NotifyDoc(event.0))
+        }
+    }
+
+    /// Records the size of every batch; each takes 20ms.
+    struct BulkIndexEffect(Arc<std::sync::Mutex<Vec<usize>>>);
+
+    #[async_trait::async_trait]
+    impl Effect<IndexDoc, ()> for BulkIndexEffect {
+        type Event = JobRan;
+
+        async fn execute(
+            &self,
+            cmd: IndexDoc,
+            ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<JobRan> {
+            let mut events = self.execute_batch(vec![cmd], ctx).await?;
+            Ok(events.remove(0))
+        }
+
+        async fn execute_batch(
+            &self,
+            commands: Vec<IndexDoc>,
+            _ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<Vec<JobRan>> {
+            self.0.lock().unwrap().push(commands.len());
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(commands.iter().map(|_| JobRan).collect())
+        }
+    }
+
+    /// Records the size of every `enqueue_batch` call.
+    struct BatchRecordingQueue(Arc<std::sync::Mutex<Vec<usize>>>);
+
+    #[async_trait::async_trait]
+    impl JobQueue for BatchRecordingQueue {
+        async fn enqueue(
+            &self,
+            _payload: serde_json::Value,
+            _spec: crate::core::JobSpec,
+        ) -> Result<uuid::Uuid> {
+            self.0.lock().unwrap().push(1);
+            Ok(uuid::Uuid::new_v4())
+        }
+
+        async fn schedule(
+            &self,
+            _payload: serde_json::Value,
+            _spec: crate::core::JobSpec,
+            _run_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<uuid::Uuid> {
+            anyhow::bail!("not scheduled")
+        }
+
+        async fn enqueue_batch(
+            &self,
+            jobs: Vec<(serde_json::Value, crate::core::JobSpec)>,
+        ) -> Vec<Result<uuid::Uuid>> {
+            self.0.lock().unwrap().push(jobs.len());
+            jobs.iter().map(|_| Ok(uuid::Uuid::new_v4())).collect()
+        }
+    }
+
+    /// Emit one `Indexed`, then four more for one correlation ID while the
+    /// first is being indexed. Returns index batch and enqueue sizes.
+    async fn index_burst(micro_batch: usize) -> (Vec<usize>, Vec<usize>) {
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let enqueues = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::with_job_queue(
+            (),
+            bus.clone(),
+            Arc::new(BatchRecordingQueue(enqueues.clone())),
+        )
+        .with_effect::<IndexDoc, _>(BulkIndexEffect(batches.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(IndexMachine)
+            .with_machine(NotifyMachine)
+            .with_micro_batching(micro_batch);
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Indexed(0));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let cid = CorrelationId::new();
+        for id in 1..=4 {
+            bus.emit_with_correlation(Indexed(id), cid);
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        handle.abort();
+
+        let batches = batches.lock().unwrap().clone();
+        let enqueues = enqueues.lock().unwrap().clone();
+        (batches, enqueues)
+    }
+
+    #[tokio::test]
+    async fn test_micro_batching_merges_waiting_events_into_one_batch() {
+        assert_eq!(index_burst(16).await, (vec![1, 4], vec![1, 4]));
+    }
+
+    #[tokio::test]
+    async fn test_without_micro_batching_each_event_dispatches_alone() {
+        assert_eq!(
+            index_burst(1).await,
+            (vec![1, 1, 1, 1, 1], vec![1, 1, 1, 1, 1])
+        );
+    }
+
+    #[derive(Debug, Clone)]
+    struct EmailReceipt;
+    impl Command for EmailReceipt {
+        fn independent(&self) -> bool {
+            true
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct SmsReceipt;
+    impl Command for SmsReceipt {
+        fn independent(&self) -> bool {
+            true
+        }
+    }
+
+    struct EmailMachine;
+    impl Machine for EmailMachine {
+        type Event = Checkout;
+        type Command = EmailReceipt;
+
+        fn decide(&mut self, _event: &Checkout) -> Option<EmailReceipt> {
+            Some(EmailReceipt)
+        }
+    }
+
+    struct SmsMachine;
+    impl Machine for SmsMachine {
+        type Event = Checkout;
+        type Command = SmsReceipt;
+
+        fn decide(&mut self, _event: &Checkout) -> Option<SmsReceipt> {
+            Some(SmsReceipt)
+        }
+    }
+
+    /// Records when it starts and finishes, 30ms apart.
+    struct SlowRecordEffect(&'static str, Arc<std::sync::Mutex<Vec<String>>>);
+
+    #[async_trait::async_trait]
+    impl<C: Command> Effect<C, ()> for SlowRecordEffect {
+        type Event = JobRan;
+
+        async fn execute(
+            &self,
+            _cmd: C,
+            _ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<JobRan> {
+            self.1.lock().unwrap().push(format!("start {}", self.0));
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            self.1.lock().unwrap().push(format!("end {}", self.0));
+            Ok(JobRan)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_independent_batches_from_one_event_dispatch_concurrently() {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new((), bus.clone())
+            .with_effect::<EmailReceipt, _>(SlowRecordEffect("email", order.clone()))
+            .with_effect::<SmsReceipt, _>(SlowRecordEffect("sms", order.clone()))
+            .with_effect::<Reserve, _>(SlowRecordEffect("reserve", order.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone())
+            .with_machine(EmailMachine)
+            .with_machine(SmsMachine)
+            .with_machine(ReserveMachine);
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Checkout);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        handle.abort();
+
+        let order = order.lock().unwrap().clone();
+        // Both receipts start together; the dependent reserve waits for them
+        assert_eq!(&order[..2], ["start email", "start sms"]);
+        assert_eq!(&order[4..], ["start reserve", "end reserve"]);
+    }
+
+    #[test]
+    fn test_machine_priority_keeps_registration_order_for_ties() {
+        let bus = EventBus::new();
+        let runtime = Runtime::new(Dispatcher::new((), bus.clone()), bus)
+            .with_machine(ReserveMachine)
+            .with_machine(JobMachine)
+            .with_machine(ChargeMachine)
+            .with_machine_priority::<ChargeMachine>(5)
+            .with_machine_priority::<JobMachine>(-1)
+            .with_machine(TestMachine::new());
+
+        let names: Vec<_> = runtime.machines.iter().map(MachineRunner::name).collect();
+        assert!(names[0].ends_with("ChargeMachine"));
+        assert!(names[1].ends_with("ReserveMachine"));
+        assert!(names[2].ends_with("TestMachine"));
+        assert!(names[3].ends_with("JobMachine"));
+    }
+
     #[test]
     fn test_runtime_builder() {
         let process_count = Arc::new(AtomicUsize::new(0));
@@ -668,6 +2515,7 @@ mod tests {
 
         assert_eq!(runtime.machine_count(), 1);
         assert!(runtime.dispatcher().has_effect::<TestCommand>());
+        assert_eq!(runtime.dispatcher().deps().value, 42);
     }
 
     #[test]
@@ -692,4 +2540,76 @@ mod tests {
         assert!(debug.contains("Runtime"));
         assert!(debug.contains("machine_count"));
     }
+
+    // Lag recovery: a stalled runtime replays events retained by the bus
+
+    #[derive(Debug, Clone)]
+    struct Burst(u32);
+
+    #[derive(Debug, Clone)]
+    struct Pause;
+    impl Command for Pause {}
+
+    #[derive(Debug, Clone)]
+    struct Paused;
+
+    /// Counts bursts, stalling the runtime on the first.
+    struct BurstMachine(Arc<AtomicUsize>);
+    impl Machine for BurstMachine {
+        type Event = Burst;
+        type Command = Pause;
+
+        fn decide(&mut self, event: &Burst) -> Option<Pause> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            (event.0 == 0).then_some(Pause)
+        }
+    }
+
+    struct PauseEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<Pause, ()> for PauseEffect {
+        type Event = Paused;
+
+        async fn execute(
+            &self,
+            _cmd: Pause,
+            _ctx: crate::effect_impl::EffectContext<()>,
+        ) -> Result<Paused> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(Paused)
+        }
+    }
+
+    /// Emit 20 bursts at a runtime stalled on the first; returns how many it saw.
+    async fn burst_past_a_stall(retention: usize) -> usize {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let (runtime, bus) = RuntimeBuilder::new(())
+            .with_bus(EventBus::with_capacity(4).with_retention(retention))
+            .with_machine(BurstMachine(seen.clone()))
+            .with_effect::<Pause, _>(PauseEffect)
+            .build();
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        bus.emit(Burst(0));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        for i in 1..20 {
+            bus.emit(Burst(i));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        handle.abort();
+        seen.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn test_stalled_runtime_recovers_retained_events() {
+        assert_eq!(burst_past_a_stall(32).await, 20);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_runtime_loses_events_without_retention() {
+        assert!(burst_past_a_stall(0).await < 20);
+    }
 }
@@ -0,0 +1,169 @@
+//! Event catalog - the JSON Schemas of an engine's events and commands.
+//!
+//! Types deriving [`schemars::JsonSchema`] can be registered with
+//! [`EngineBuilder::with_event_schema`](crate::EngineBuilder::with_event_schema)
+//! and [`with_command_schema`](crate::EngineBuilder::with_command_schema)
+//! (`schemas` feature). The running engine exposes them through
+//! [`EngineHandle::schemas`](crate::EngineHandle::schemas), for a docs
+//! endpoint or a contract check against the services consuming the events:
+//!
+//! ```ignore
+//! #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+//! enum OrderEvent {
+//!     Placed { order_id: Uuid, total_cents: i64 },
+//!     Shipped { order_id: Uuid },
+//! }
+//!
+//! let handle = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_event_schema::<OrderEvent>()
+//!     .with_command_schema::<ShipOrder>()
+//!     .build()
+//!     .start();
+//!
+//! // GET /schemas
+//! async fn schemas(seesaw: Seesaw) -> Json<serde_json::Value> {
+//!     Json(seesaw.schemas().to_json())
+//! }
+//! ```
+//!
+//! Schemas are keyed by their schemars name, the type name without its
+//! module path unless overridden with `#[schemars(rename = "...")]`. Give
+//! versioned types versioned names so both versions can be registered.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Whether a registered type is an event or a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaKind {
+    /// A fact machines decide from.
+    Event,
+    /// An intent effects execute.
+    Command,
+}
+
+/// The JSON Schema of one registered type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeSchema {
+    /// The schema's name, e.g. `OrderEvent`.
+    pub name: String,
+    /// Whether the type is an event or a command.
+    pub kind: SchemaKind,
+    /// The full Rust type name.
+    pub type_name: &'static str,
+    /// The JSON Schema, with its definitions.
+    pub schema: serde_json::Value,
+}
+
+/// The schemas registered with an engine, ordered by name.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: BTreeMap<String, TypeSchema>,
+}
+
+impl SchemaRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the schema of event type `E`, replacing one of the same name.
+    #[cfg(feature = "schemas")]
+    pub fn register_event<E: schemars::JsonSchema>(&mut self) {
+        self.register::<E>(SchemaKind::Event);
+    }
+
+    /// Register the schema of command type `C`, replacing one of the same
+    /// name.
+    #[cfg(feature = "schemas")]
+    pub fn register_command<C: schemars::JsonSchema>(&mut self) {
+        self.register::<C>(SchemaKind::Command);
+    }
+
+    #[cfg(feature = "schemas")]
+    fn register<T: schemars::JsonSchema>(&mut self, kind: SchemaKind) {
+        let name = T::schema_name().into_owned();
+        let schema = TypeSchema {
+            name: name.clone(),
+            kind,
+            type_name: std::any::type_name::<T>(),
+            schema: schemars::schema_for!(T).to_value(),
+        };
+        self.schemas.insert(name, schema);
+    }
+
+    /// The schema named `name`, if registered.
+    pub fn get(&self, name: &str) -> Option<&TypeSchema> {
+        self.schemas.get(name)
+    }
+
+    /// Every registered schema, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = &TypeSchema> {
+        self.schemas.values()
+    }
+
+    /// The registered schemas of `kind`, ordered by name.
+    pub fn of_kind(&self, kind: SchemaKind) -> impl Iterator<Item = &TypeSchema> {
+        self.iter().filter(move |schema| schema.kind == kind)
+    }
+
+    /// Number of registered schemas.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Whether no schema is registered.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// The catalog as `{"events": {name: schema}, "commands": {name: schema}}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let section = |kind| {
+            self.of_kind(kind)
+                .map(|schema| (schema.name.clone(), schema.schema.clone()))
+                .collect::<serde_json::Map<_, _>>()
+        };
+        serde_json::json!({
+            "events": section(SchemaKind::Event),
+            "commands": section(SchemaKind::Command),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "schemas"))]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, serde::Deserialize, schemars::JsonSchema)]
+    enum OrderEvent {
+        Placed { order_id: u64 },
+        Shipped { order_id: u64 },
+    }
+
+    #[derive(Serialize, serde::Deserialize, schemars::JsonSchema)]
+    struct ShipOrder {
+        order_id: u64,
+    }
+
+    #[test]
+    fn test_catalog_splits_events_and_commands() {
+        let mut registry = SchemaRegistry::new();
+        registry.register_event::<OrderEvent>();
+        registry.register_command::<ShipOrder>();
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get("OrderEvent").unwrap().kind, SchemaKind::Event);
+
+        let catalog = registry.to_json();
+        assert_eq!(
+            catalog["commands"]["ShipOrder"]["properties"]["order_id"]["type"],
+            "integer"
+        );
+        assert!(catalog["events"]["OrderEvent"]["oneOf"].is_array());
+        assert!(catalog["events"].get("ShipOrder").is_none());
+    }
+}
@@ -72,10 +72,12 @@ impl Command for AutoSerializeInlineCommand {
 // Mock Job Queue for Testing
 // ============================================================================
 
+type ScheduledLog = Arc<Mutex<Vec<(String, serde_json::Value, DateTime<Utc>)>>>;
+
 #[derive(Clone)]
 struct TestJobQueue {
     enqueued: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
-    scheduled: Arc<Mutex<Vec<(String, serde_json::Value, DateTime<Utc>)>>>,
+    scheduled: ScheduledLog,
 }
 
 impl TestJobQueue {
@@ -0,0 +1,148 @@
+//! Sharded dispatch - per-key ordering with parallelism across keys.
+//!
+//! By default the runtime dispatches one event's inline commands before
+//! deciding the next event, so a slow effect holds up every other entity.
+//! With [`Sharding`], each event is assigned a key - its correlation ID, or
+//! a key extracted from the event - and its commands are dispatched on the
+//! worker that owns that key:
+//!
+//! - Events with the same key have their effects run one after another, in
+//!   event order.
+//! - Events with different keys run on different workers in parallel, up to
+//!   the number of workers.
+//!
+//! Machines still decide every event serially and in bus order, so their
+//! state needs no locking. Stateless effects are shared by all workers.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_effect::<OrderCommand, _>(OrderEffect)
+//!     // One worker per order; other events shard by correlation ID
+//!     .with_sharding(Sharding::new(8).with_key(|e: &OrderEvent| e.order_id()))
+//!     .build();
+//! ```
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use crate::core::{Event, EventEnvelope};
+
+/// Extracts the hashed shard key from a type-erased event.
+type KeyFn = Arc<dyn Fn(&EventEnvelope) -> u64 + Send + Sync>;
+
+/// How a runtime assigns events to its dispatch workers.
+///
+/// Events are keyed by correlation ID unless a key is registered for their
+/// type with [`with_key`](Self::with_key). Register a key for every event
+/// type of an entity whose effects must stay in order.
+#[derive(Clone)]
+pub struct Sharding {
+    workers: usize,
+    keys: HashMap<TypeId, KeyFn>,
+}
+
+impl Sharding {
+    /// Dispatch on `workers` parallel workers, keyed by correlation ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "sharding needs at least 1 worker");
+        Self {
+            workers,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Key events of type `E` by `key` instead of their correlation ID.
+    pub fn with_key<E, K, F>(mut self, key: F) -> Self
+    where
+        E: Event,
+        K: Hash,
+        F: Fn(&E) -> K + Send + Sync + 'static,
+    {
+        self.keys.insert(
+            TypeId::of::<E>(),
+            Arc::new(move |envelope| {
+                let event = envelope
+                    .downcast_ref::<E>()
+                    .expect("shard key registered for the envelope's type");
+                hash(&key(event))
+            }),
+        );
+        self
+    }
+
+    /// Number of dispatch workers.
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Index of the worker that owns `envelope`'s key.
+    pub(crate) fn shard_of(&self, envelope: &EventEnvelope) -> usize {
+        let key = match self.keys.get(&envelope.type_id) {
+            Some(key) => key(envelope),
+            None => hash(&envelope.cid),
+        };
+        (key % self.workers as u64) as usize
+    }
+}
+
+impl std::fmt::Debug for Sharding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sharding")
+            .field("workers", &self.workers)
+            .field("keyed_types", &self.keys.len())
+            .finish()
+    }
+}
+
+/// Stable within a process, so a key always maps to the same worker.
+fn hash(key: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CorrelationId;
+
+    #[derive(Debug, Clone)]
+    struct AccountEvent {
+        account: u32,
+    }
+
+    #[test]
+    fn test_registered_key_overrides_correlation() {
+        let sharding = Sharding::new(4).with_key(|e: &AccountEvent| e.account);
+
+        let shards: Vec<_> = (0..16)
+            .map(|_| EventEnvelope::new_random(AccountEvent { account: 7 }))
+            .map(|envelope| sharding.shard_of(&envelope))
+            .collect();
+        assert!(shards.iter().all(|&shard| shard == shards[0]));
+    }
+
+    #[test]
+    fn test_unkeyed_events_shard_by_correlation() {
+        let sharding = Sharding::new(4);
+        let cid = CorrelationId::new();
+
+        let first = sharding.shard_of(&EventEnvelope::new(cid, "placed"));
+        assert_eq!(sharding.shard_of(&EventEnvelope::new(cid, 42u8)), first);
+
+        let spread: std::collections::HashSet<_> = (0..64)
+            .map(|_| sharding.shard_of(&EventEnvelope::new_random("placed")))
+            .collect();
+        assert!(spread.len() > 1);
+        assert!(spread.iter().all(|&shard| shard < 4));
+    }
+}
@@ -0,0 +1,222 @@
+//! Tracing spans for the event loop (`tracing-spans` feature).
+//!
+//! With the feature enabled, the runtime opens one `seesaw.event` span per
+//! envelope. Each machine decision gets a `seesaw.decide` child span and each
+//! inline effect execution a `seesaw.effect` child span, so a subscriber can
+//! follow one event through its decisions into the effects they caused.
+//!
+//! | Span            | Level | Fields                                              |
+//! |-----------------|-------|-----------------------------------------------------|
+//! | `seesaw.event`  | INFO  | `cid`, `hops`, `event_type`                         |
+//! | `seesaw.decide` | DEBUG | `machine`, `event_type`, `command_type`, `outcome`  |
+//! | `seesaw.effect` | INFO  | `command_type`, `cid`, `batch_size`, `outcome`, `error` |
+//!
+//! `outcome` is `command`, `none` or `panicked` for decisions and `ok` or
+//! `failed` for effects. An effect that panics closes its span without an
+//! outcome; the `effect panicked` error event follows in the event span.
+//!
+//! The bus additionally logs a TRACE `event emitted` event per envelope.
+//!
+//! Without the feature every function here returns [`Span::none`], which
+//! costs next to nothing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! tracing_subscriber::fmt()
+//!     .with_span_events(FmtSpan::CLOSE)
+//!     .with_env_filter("seesaw_core=debug")
+//!     .init();
+//! ```
+
+use tracing::Span;
+
+use crate::core::{CorrelationId, EventEnvelope};
+
+/// Span covering everything the runtime does with one envelope.
+pub(crate) fn event(envelope: &EventEnvelope) -> Span {
+    #[cfg(feature = "tracing-spans")]
+    {
+        tracing::info_span!(
+            "seesaw.event",
+            cid = %envelope.cid,
+            hops = envelope.hops,
+            event_type = tracing::field::Empty,
+        )
+    }
+    #[cfg(not(feature = "tracing-spans"))]
+    {
+        let _ = envelope;
+        Span::none()
+    }
+}
+
+/// Span covering one machine's `decide` call.
+pub(crate) fn decide(
+    machine: &'static str,
+    event_type: &'static str,
+    command_type: &'static str,
+) -> Span {
+    #[cfg(feature = "tracing-spans")]
+    {
+        tracing::debug_span!(
+            "seesaw.decide",
+            machine,
+            event_type,
+            command_type,
+            outcome = tracing::field::Empty,
+        )
+    }
+    #[cfg(not(feature = "tracing-spans"))]
+    {
+        let _ = (machine, event_type, command_type);
+        Span::none()
+    }
+}
+
+/// Span covering one inline effect execution (a single command or a batch).
+pub(crate) fn effect(command_type: &'static str, cid: CorrelationId, batch_size: usize) -> Span {
+    #[cfg(feature = "tracing-spans")]
+    {
+        tracing::info_span!(
+            "seesaw.effect",
+            command_type,
+            cid = %cid,
+            batch_size,
+            outcome = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+    }
+    #[cfg(not(feature = "tracing-spans"))]
+    {
+        let _ = (command_type, cid, batch_size);
+        Span::none()
+    }
+}
+
+/// Record the outcome of an effect execution on its span.
+pub(crate) fn record_effect<T>(span: &Span, result: &anyhow::Result<T>) {
+    match result {
+        Ok(_) => {
+            span.record("outcome", "ok");
+        }
+        Err(e) => {
+            span.record("outcome", "failed");
+            span.record("error", tracing::field::display(e));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tracing-spans"))]
+mod tests {
+    use crate::bus::EventBus;
+    use crate::core::Command;
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::machine::Machine;
+    use crate::runtime::Runtime;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Span names paired with their parent span's name.
+    type SpanTree = Arc<Mutex<Vec<(&'static str, Option<&'static str>)>>>;
+
+    /// Records each span's name and the name of its parent.
+    #[derive(Default)]
+    struct SpanRecorder {
+        next_id: AtomicU64,
+        names: Mutex<Vec<&'static str>>,
+        stack: Mutex<Vec<u64>>,
+        spans: SpanTree,
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut names = self.names.lock().unwrap();
+            let parent = match attrs.parent() {
+                Some(parent) => Some(parent.into_u64()),
+                None if attrs.is_contextual() => self.stack.lock().unwrap().last().copied(),
+                None => None,
+            };
+            let name = attrs.metadata().name();
+            names.push(name);
+            self.spans
+                .lock()
+                .unwrap()
+                .push((name, parent.map(|p| names[p as usize - 1])));
+            tracing::span::Id::from_u64(id)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, span: &tracing::span::Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &tracing::span::Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Ordered;
+
+    #[derive(Debug, Clone)]
+    struct Ship;
+    impl Command for Ship {}
+
+    #[derive(Debug, Clone)]
+    struct Shipped;
+
+    struct ShipMachine;
+
+    impl Machine for ShipMachine {
+        type Event = Ordered;
+        type Command = Ship;
+
+        fn decide(&mut self, _event: &Ordered) -> Option<Ship> {
+            Some(Ship)
+        }
+    }
+
+    struct ShipEffect;
+
+    #[async_trait::async_trait]
+    impl Effect<Ship, ()> for ShipEffect {
+        type Event = Shipped;
+
+        async fn execute(&self, _cmd: Ship, _ctx: EffectContext<()>) -> anyhow::Result<Shipped> {
+            Ok(Shipped)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decide_and_effect_spans_nest_under_event_span() {
+        let recorder = SpanRecorder::default();
+        let spans = recorder.spans.clone();
+        let _guard = tracing::subscriber::set_default(recorder);
+
+        let bus = EventBus::new();
+        let dispatcher = Dispatcher::new((), bus.clone()).with_effect::<Ship, _>(ShipEffect);
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_machine(ShipMachine);
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Ordered);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        let spans = spans.lock().unwrap();
+        assert!(spans.contains(&("seesaw.decide", Some("seesaw.event"))));
+        assert!(spans.contains(&("seesaw.effect", Some("seesaw.event"))));
+    }
+}
@@ -3,6 +3,7 @@
 //! These tests exercise edge cases, race conditions, and potential failure modes.
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod stress_tests {
     use crate::bus::EventBus;
     use crate::core::{Command, CorrelationId};
@@ -723,7 +724,7 @@ mod stress_tests {
 
         let mut failures = 0;
         for h in handles {
-            if let Err(_) = h.await.unwrap() {
+            if h.await.unwrap().is_err() {
                 failures += 1;
             }
         }
@@ -798,14 +799,7 @@ mod stress_tests {
     // 3. Return an error to the caller
 
     #[derive(Debug, Clone)]
-    struct PanicTriggerEvent {
-        id: usize,
-    }
-
-    #[derive(Debug, Clone)]
-    struct PanicResultEvent {
-        id: usize,
-    }
+    struct PanicTriggerEvent;
 
     struct PanicTriggerMachine;
     impl Machine for PanicTriggerMachine {
@@ -833,7 +827,7 @@ mod stress_tests {
         // This should NOT hang - should return error or timeout quickly
         let start = std::time::Instant::now();
         let result = handle
-            .emit_and_await_timeout(PanicTriggerEvent { id: 1 }, Duration::from_millis(500))
+            .emit_and_await_timeout(PanicTriggerEvent, Duration::from_millis(500))
             .await;
         let elapsed = start.elapsed();
 
@@ -883,7 +877,7 @@ mod stress_tests {
 
         // First: trigger a panic
         let _ = handle
-            .emit_and_await_timeout(PanicTriggerEvent { id: 1 }, Duration::from_millis(500))
+            .emit_and_await_timeout(PanicTriggerEvent, Duration::from_millis(500))
             .await;
 
         // Wait for cleanup
@@ -988,9 +982,20 @@ mod stress_tests {
             }
         }
 
-        // Build a new engine to verify runtime concept
-        // (We can't add machines to running engine)
-        // Instead, check inflight cleanup
+        handle.add_machine(OtherMachine).await.unwrap();
+        handle
+            .add_effect::<OtherCommand, _, _>(OtherEffect)
+            .await
+            .unwrap();
+        let result = handle
+            .emit_and_await_timeout(OtherEvent, Duration::from_millis(500))
+            .await;
+        assert!(
+            result.is_ok(),
+            "Runtime stopped processing after machine panic: {:?}",
+            result
+        );
+
         assert_eq!(
             inflight.active_count(),
             0,
@@ -0,0 +1,429 @@
+//! Supervision for machines that misbehave.
+//!
+//! A panicking `decide` is always caught, but by default the machine keeps
+//! receiving events - possibly with corrupted state, possibly panicking on
+//! every one. A machine can also be pathological without panicking, for
+//! example by deciding a command for every event its own effect emits.
+//!
+//! Register a machine with `with_supervised_machine` to have the runtime
+//! watch it. When, within one window, the machine panics `max_panics` times
+//! or decides more than `max_commands` commands, the runtime applies the
+//! policy's [`SupervisorAction`] and emits a [`MachineSupervised`] event:
+//!
+//! - [`SupervisorAction::Restart`]: replace the machine with a fresh one from
+//!   its factory. Its state is lost.
+//! - [`SupervisorAction::Stop`]: isolate the machine. It receives no further
+//!   events until the runtime is rebuilt.
+//!
+//! A command decided by a runaway machine is dropped when the limit trips, so
+//! the loop is broken even when the action is `Restart`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_supervised_machine(
+//!         OrderMachine::new,
+//!         SupervisorPolicy::new(SupervisorAction::Restart)
+//!             .with_max_panics(3)
+//!             .with_max_commands(1_000)
+//!             .with_window(Duration::from_secs(10)),
+//!     )
+//!     .build();
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::machine::AnyMachine;
+
+// =============================================================================
+// Policy
+// =============================================================================
+
+/// What the runtime does with a machine that crossed a supervision limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupervisorAction {
+    /// Replace the machine with a fresh instance from its factory.
+    Restart,
+    /// Stop delivering events to the machine.
+    Stop,
+}
+
+/// Limits a supervised machine must stay within.
+#[derive(Debug, Clone, Copy)]
+pub struct SupervisorPolicy {
+    action: SupervisorAction,
+    max_panics: u32,
+    max_commands: Option<u32>,
+    window: Duration,
+}
+
+impl SupervisorPolicy {
+    /// Create a policy with the defaults: act on the first panic, no command
+    /// limit, 60s window.
+    pub fn new(action: SupervisorAction) -> Self {
+        Self {
+            action,
+            max_panics: 1,
+            max_commands: None,
+            window: Duration::from_secs(60),
+        }
+    }
+
+    /// Set how many panics within one window trigger the action.
+    pub fn with_max_panics(mut self, max_panics: u32) -> Self {
+        self.max_panics = max_panics.max(1);
+        self
+    }
+
+    /// Set how many commands the machine may decide within one window.
+    ///
+    /// The command that exceeds the limit is dropped and triggers the action.
+    pub fn with_max_commands(mut self, max_commands: u32) -> Self {
+        self.max_commands = Some(max_commands);
+        self
+    }
+
+    /// Set the window panics and commands are counted over.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+// =============================================================================
+// Events
+// =============================================================================
+
+/// Why a machine was supervised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisionReason {
+    /// `decide` panicked `max_panics` times within the window.
+    Panicked {
+        /// Message of the last panic.
+        message: String,
+    },
+    /// The machine decided more than `max_commands` commands within the window.
+    Runaway {
+        /// Commands decided in the window, including the dropped one.
+        commands: u32,
+        /// The window the commands were counted over.
+        window: Duration,
+    },
+}
+
+/// Emitted when a supervised machine is restarted or stopped.
+///
+/// Carries the correlation ID of the event being decided when the limit
+/// tripped.
+#[derive(Debug, Clone)]
+pub struct MachineSupervised {
+    /// Name of the supervised machine.
+    pub machine: &'static str,
+    /// Which limit was crossed.
+    pub reason: SupervisionReason,
+    /// What the runtime did about it.
+    pub action: SupervisorAction,
+}
+
+// =============================================================================
+// Supervisor
+// =============================================================================
+
+/// Builds a fresh instance of a supervised machine.
+pub(crate) type MachineFactory = Box<dyn Fn() -> Box<dyn AnyMachine> + Send + Sync>;
+
+/// Per-machine supervision state, owned by its `MachineRunner`.
+pub(crate) struct Supervisor {
+    policy: SupervisorPolicy,
+    factory: MachineFactory,
+    window_start: Instant,
+    panics: u32,
+    commands: u32,
+    stopped: bool,
+}
+
+impl Supervisor {
+    pub(crate) fn new(policy: SupervisorPolicy, factory: MachineFactory) -> Self {
+        Self {
+            policy,
+            factory,
+            window_start: Instant::now(),
+            panics: 0,
+            commands: 0,
+            stopped: false,
+        }
+    }
+
+    /// Whether the machine was stopped and must not receive events.
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Count one decision, returning the reason if it crossed a limit.
+    pub(crate) fn observe(
+        &mut self,
+        panic: Option<&str>,
+        decided: bool,
+    ) -> Option<SupervisionReason> {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= self.policy.window {
+            self.window_start = now;
+            self.panics = 0;
+            self.commands = 0;
+        }
+
+        if let Some(message) = panic {
+            self.panics += 1;
+            if self.panics >= self.policy.max_panics {
+                return Some(SupervisionReason::Panicked {
+                    message: message.to_string(),
+                });
+            }
+        } else if decided {
+            self.commands += 1;
+            if self
+                .policy
+                .max_commands
+                .is_some_and(|max| self.commands > max)
+            {
+                return Some(SupervisionReason::Runaway {
+                    commands: self.commands,
+                    window: self.policy.window,
+                });
+            }
+        }
+        None
+    }
+
+    /// Apply the policy's action, returning the replacement machine on restart.
+    pub(crate) fn act(&mut self) -> Option<Box<dyn AnyMachine>> {
+        self.window_start = Instant::now();
+        self.panics = 0;
+        self.commands = 0;
+        match self.policy.action {
+            SupervisorAction::Restart => Some((self.factory)()),
+            SupervisorAction::Stop => {
+                self.stopped = true;
+                None
+            }
+        }
+    }
+
+    pub(crate) fn action(&self) -> SupervisorAction {
+        self.policy.action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use crate::core::Command;
+    use crate::dispatch::Dispatcher;
+    use crate::effect_impl::{Effect, EffectContext};
+    use crate::machine::Machine;
+    use crate::runtime::Runtime;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn supervisor(policy: SupervisorPolicy) -> Supervisor {
+        Supervisor::new(policy, Box::new(|| Box::new(NullMachine)))
+    }
+
+    struct NullMachine;
+
+    impl AnyMachine for NullMachine {
+        fn decide_any(
+            &mut self,
+            _event: &dyn std::any::Any,
+        ) -> Option<Box<dyn crate::core::AnyCommand>> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_panics_trip_at_max() {
+        let mut sup = supervisor(SupervisorPolicy::new(SupervisorAction::Stop).with_max_panics(2));
+
+        assert_eq!(sup.observe(Some("first"), false), None);
+        assert_eq!(
+            sup.observe(Some("second"), false),
+            Some(SupervisionReason::Panicked {
+                message: "second".to_string()
+            })
+        );
+        assert!(sup.act().is_none());
+        assert!(sup.is_stopped());
+    }
+
+    #[test]
+    fn test_commands_trip_above_max_and_window_resets() {
+        let mut sup = supervisor(
+            SupervisorPolicy::new(SupervisorAction::Restart)
+                .with_max_commands(2)
+                .with_window(Duration::from_millis(20)),
+        );
+
+        assert_eq!(sup.observe(None, true), None);
+        assert_eq!(sup.observe(None, true), None);
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(sup.observe(None, true), None);
+        assert_eq!(sup.observe(None, false), None);
+        assert_eq!(sup.observe(None, true), None);
+        assert!(matches!(
+            sup.observe(None, true),
+            Some(SupervisionReason::Runaway { commands: 3, .. })
+        ));
+        assert!(sup.act().is_some());
+        assert!(!sup.is_stopped());
+    }
+
+    // Machine counting events, panicking on request
+    #[derive(Debug, Clone)]
+    struct Tick {
+        panic: bool,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Report(u32);
+    impl Command for Report {}
+
+    #[derive(Debug, Clone)]
+    struct Reported;
+
+    #[derive(Default)]
+    struct Counter {
+        seen: u32,
+    }
+
+    impl Machine for Counter {
+        type Event = Tick;
+        type Command = Report;
+
+        fn decide(&mut self, event: &Tick) -> Option<Report> {
+            assert!(!event.panic, "bad tick");
+            self.seen += 1;
+            Some(Report(self.seen))
+        }
+    }
+
+    struct ReportEffect(Arc<Mutex<Vec<u32>>>);
+
+    #[async_trait]
+    impl Effect<Report, ()> for ReportEffect {
+        type Event = Reported;
+
+        async fn execute(&self, cmd: Report, _ctx: EffectContext<()>) -> Result<Reported> {
+            self.0.lock().unwrap().push(cmd.0);
+            Ok(Reported)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_machine_is_restarted() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let dispatcher = Dispatcher::new((), bus.clone())
+            .with_effect::<Report, _>(ReportEffect(reports.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_supervised_machine(
+            Counter::default,
+            SupervisorPolicy::new(SupervisorAction::Restart),
+        );
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        for panic in [false, false, true, false] {
+            bus.emit(Tick { panic });
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        // State was lost on restart
+        assert_eq!(*reports.lock().unwrap(), vec![1, 2, 1]);
+
+        let supervised = loop {
+            let envelope = rx.recv().await.unwrap();
+            if let Some(supervised) = envelope.downcast_ref::<MachineSupervised>() {
+                break supervised.clone();
+            }
+        };
+        assert!(supervised.machine.contains("Counter"));
+        assert_eq!(supervised.action, SupervisorAction::Restart);
+        assert!(matches!(
+            supervised.reason,
+            SupervisionReason::Panicked { ref message } if message.contains("bad tick")
+        ));
+    }
+
+    // Machine that reacts to its own effect's events forever
+    #[derive(Debug, Clone)]
+    struct Ping;
+
+    #[derive(Debug, Clone)]
+    struct SendPing;
+    impl Command for SendPing {}
+
+    struct Echo;
+
+    impl Machine for Echo {
+        type Event = Ping;
+        type Command = SendPing;
+
+        fn decide(&mut self, _event: &Ping) -> Option<SendPing> {
+            Some(SendPing)
+        }
+    }
+
+    struct PingEffect(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Effect<SendPing, ()> for PingEffect {
+        type Event = Ping;
+
+        async fn execute(&self, _cmd: SendPing, _ctx: EffectContext<()>) -> Result<Ping> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Ping)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runaway_machine_is_stopped() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let dispatcher =
+            Dispatcher::new((), bus.clone()).with_effect::<SendPing, _>(PingEffect(calls.clone()));
+        let runtime = Runtime::new(dispatcher, bus.clone()).with_supervised_machine(
+            || Echo,
+            SupervisorPolicy::new(SupervisorAction::Stop).with_max_commands(5),
+        );
+
+        let handle = tokio::spawn(runtime.run());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bus.emit(Ping);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        // Stopped machines see no further events
+        bus.emit(Ping);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        handle.abort();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        let supervised = loop {
+            let envelope = rx.recv().await.unwrap();
+            if let Some(supervised) = envelope.downcast_ref::<MachineSupervised>() {
+                break supervised.clone();
+            }
+        };
+        assert_eq!(supervised.action, SupervisorAction::Stop);
+        assert!(matches!(
+            supervised.reason,
+            SupervisionReason::Runaway { commands: 6, .. }
+        ));
+    }
+}
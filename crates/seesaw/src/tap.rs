@@ -34,6 +34,47 @@
 //! - No rollback ambiguity
 //! - Observability correctness
 //!
+//! # Delivery
+//!
+//! Each tap gets its own bounded buffer and a dedicated task that delivers
+//! events one at a time, in the order the runtime observed them. The runtime
+//! loop only pushes into the buffer, so a slow tap never stalls dispatch.
+//!
+//! When a tap falls behind and its buffer fills, the [`TapLagPolicy`] picks
+//! which event to drop. Drops, failures and panics are logged and counted
+//! per tap in [`Health::taps`](crate::Health::taps).
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     .with_event_tap::<EntryEvent, _>(NatsPublishTap::new(client))
+//!     .with_event_tap_policy::<EntryEvent, _>(
+//!         AnalyticsTap::new(),
+//!         TapPolicy::new(10_000).with_lag_policy(TapLagPolicy::DropOldest),
+//!     )
+//!     .build();
+//! ```
+//!
+//! # Sampling and Throttling
+//!
+//! A logging or analytics sink rarely needs every event of a high-volume
+//! stream. The combinators on [`EventTap`] thin the stream out before it is
+//! queued, so skipped events cost neither buffer space nor a wakeup:
+//!
+//! ```ignore
+//! let engine = EngineBuilder::new(deps)
+//!     // 1% of price ticks
+//!     .with_event_tap::<PriceTick, _>(LogTap::new().sampled(0.01))
+//!     // At most 50 presence updates a second
+//!     .with_event_tap::<PresenceChanged, _>(
+//!         AnalyticsTap::new().throttled(RateLimitPolicy::per_second(50)),
+//!     )
+//!     // Only failed payments
+//!     .with_event_tap::<PaymentEvent, _>(
+//!         AlertTap::new().filtered(|e: &PaymentEvent| e.is_failure()),
+//!     )
+//!     .build();
+//! ```
+//!
 //! # Example
 //!
 //! ```ignore
@@ -53,15 +94,22 @@
 //! }
 //! ```
 
-use std::any::TypeId;
-use std::sync::Arc;
+use std::any::{Any, TypeId};
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::FutureExt;
+use tokio::sync::Notify;
 use tracing::warn;
 
-use crate::core::{CorrelationId, Event};
+use crate::core::{CorrelationId, Event, EventEnvelope};
+use crate::metadata::Metadata;
+use crate::rate_limit::{RateLimitPolicy, RateLimiter};
 
 // =============================================================================
 // Tap Context
@@ -74,6 +122,8 @@ use crate::core::{CorrelationId, Event};
 pub struct TapContext {
     /// Correlation ID for the event (NONE if uncorrelated).
     pub correlation_id: CorrelationId,
+    /// Request-scoped metadata the event was emitted with.
+    pub metadata: Metadata,
     /// When this tap execution started.
     pub timestamp: Instant,
 }
@@ -83,10 +133,17 @@ impl TapContext {
     pub fn new(correlation_id: CorrelationId) -> Self {
         Self {
             correlation_id,
+            metadata: Metadata::default(),
             timestamp: Instant::now(),
         }
     }
 
+    /// Set the request-scoped metadata.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Check if this event has a real correlation ID (not NONE).
     pub fn has_correlation(&self) -> bool {
         self.correlation_id.is_some()
@@ -103,7 +160,7 @@ impl TapContext {
 /// - Receive committed facts
 /// - Cannot emit new events
 /// - Cannot access deps (use closure capture if needed)
-/// - Run concurrently by default
+/// - Run in a dedicated task per tap, in event order
 ///
 /// Use taps for:
 /// - Publishing to NATS/Kafka
@@ -114,71 +171,415 @@ impl TapContext {
 pub trait EventTap<E: Event>: Send + Sync + 'static {
     /// Called when an event of type E is observed.
     ///
-    /// Errors are logged but do not affect the main flow.
-    /// Taps should be fire-and-forget - don't rely on their success.
+    /// Errors and panics are logged and counted but do not affect the
+    /// main flow. Taps are best-effort - don't rely on their success.
     async fn on_event(&self, event: &E, ctx: &TapContext) -> Result<()>;
+
+    /// Called with the shared event, for taps that keep it past the call.
+    ///
+    /// A batching tap can hold the `Arc` instead of cloning a large event.
+    /// Defaults to [`on_event`](Self::on_event).
+    async fn on_shared(&self, event: Arc<E>, ctx: &TapContext) -> Result<()> {
+        self.on_event(&event, ctx).await
+    }
+
+    /// Cheap filter checked before the event is queued for this tap.
+    ///
+    /// Rejected events never take a buffer slot or wake the tap's task, so
+    /// a tap that only cares about one variant of a high-volume event can
+    /// skip the rest. Runs on the runtime loop: keep it to a discriminant
+    /// check or field comparison. Defaults to accepting every event.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn accepts(&self, event: &OrderEvent) -> bool {
+    ///     matches!(event, OrderEvent::Shipped { .. })
+    /// }
+    /// ```
+    fn accepts(&self, _event: &E) -> bool {
+        true
+    }
+
+    /// Pass on a random `rate` fraction of the events this tap accepts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not between 0 and 1.
+    fn sampled(self, rate: f64) -> SampledTap<Self>
+    where
+        Self: Sized,
+    {
+        SampledTap::new(self, rate)
+    }
+
+    /// Pass on the events this tap accepts at no more than `policy`'s rate,
+    /// skipping the rest.
+    ///
+    /// The policy's [`ThrottleMode`](crate::ThrottleMode) is ignored: a tap
+    /// is never delayed, events over the limit are skipped.
+    fn throttled(self, policy: RateLimitPolicy) -> ThrottledTap<Self>
+    where
+        Self: Sized,
+    {
+        ThrottledTap {
+            tap: self,
+            limiter: RateLimiter::new(policy),
+        }
+    }
+
+    /// Pass on only the events `predicate` and this tap both accept.
+    ///
+    /// Like [`accepts`](Self::accepts), the predicate runs on the runtime
+    /// loop and should be cheap.
+    fn filtered<F>(self, predicate: F) -> FilteredTap<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        FilteredTap {
+            tap: self,
+            predicate,
+        }
+    }
+}
+
+// =============================================================================
+// Tap Combinators
+// =============================================================================
+
+/// A tap passed a random fraction of events. See [`EventTap::sampled`].
+#[derive(Debug)]
+pub struct SampledTap<T> {
+    tap: T,
+    rate: f64,
+}
+
+impl<T> SampledTap<T> {
+    fn new(tap: T, rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "tap sample rate must be between 0 and 1"
+        );
+        Self { tap, rate }
+    }
+}
+
+#[async_trait]
+impl<E: Event, T: EventTap<E>> EventTap<E> for SampledTap<T> {
+    async fn on_event(&self, event: &E, ctx: &TapContext) -> Result<()> {
+        self.tap.on_event(event, ctx).await
+    }
+
+    async fn on_shared(&self, event: Arc<E>, ctx: &TapContext) -> Result<()> {
+        self.tap.on_shared(event, ctx).await
+    }
+
+    fn accepts(&self, event: &E) -> bool {
+        self.tap.accepts(event) && fastrand::f64() < self.rate
+    }
+}
+
+/// A tap passed events at a limited rate. See [`EventTap::throttled`].
+#[derive(Debug)]
+pub struct ThrottledTap<T> {
+    tap: T,
+    limiter: RateLimiter,
+}
+
+#[async_trait]
+impl<E: Event, T: EventTap<E>> EventTap<E> for ThrottledTap<T> {
+    async fn on_event(&self, event: &E, ctx: &TapContext) -> Result<()> {
+        self.tap.on_event(event, ctx).await
+    }
+
+    async fn on_shared(&self, event: Arc<E>, ctx: &TapContext) -> Result<()> {
+        self.tap.on_shared(event, ctx).await
+    }
+
+    fn accepts(&self, event: &E) -> bool {
+        // Only events the tap wants use up tokens
+        self.tap.accepts(event) && self.limiter.try_take().is_ok()
+    }
+}
+
+/// A tap passed the events a predicate accepts. See [`EventTap::filtered`].
+pub struct FilteredTap<T, F> {
+    tap: T,
+    predicate: F,
+}
+
+impl<T: std::fmt::Debug, F> std::fmt::Debug for FilteredTap<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilteredTap")
+            .field("tap", &self.tap)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<E, T, F> EventTap<E> for FilteredTap<T, F>
+where
+    E: Event,
+    T: EventTap<E>,
+    F: Fn(&E) -> bool + Send + Sync + 'static,
+{
+    async fn on_event(&self, event: &E, ctx: &TapContext) -> Result<()> {
+        self.tap.on_event(event, ctx).await
+    }
+
+    async fn on_shared(&self, event: Arc<E>, ctx: &TapContext) -> Result<()> {
+        self.tap.on_shared(event, ctx).await
+    }
+
+    fn accepts(&self, event: &E) -> bool {
+        (self.predicate)(event) && self.tap.accepts(event)
+    }
+}
+
+// =============================================================================
+// Tap Policy
+// =============================================================================
+
+/// Default number of events buffered per tap.
+pub const DEFAULT_TAP_CAPACITY: usize = 1024;
+
+/// What to do with a new event when a tap's buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TapLagPolicy {
+    /// Discard the new event and keep the buffered backlog.
+    #[default]
+    DropNewest,
+    /// Discard the oldest buffered event to make room for the new one.
+    DropOldest,
+}
+
+/// Buffering for one tap's delivery task.
+#[derive(Debug, Clone, Copy)]
+pub struct TapPolicy {
+    capacity: usize,
+    lag: TapLagPolicy,
+}
+
+impl TapPolicy {
+    /// Buffer up to `capacity` events, dropping new events when full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "tap capacity must be at least 1");
+        Self {
+            capacity,
+            lag: TapLagPolicy::DropNewest,
+        }
+    }
+
+    /// Set which event is discarded when the buffer is full.
+    pub fn with_lag_policy(mut self, lag: TapLagPolicy) -> Self {
+        self.lag = lag;
+        self
+    }
+}
+
+impl Default for TapPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_TAP_CAPACITY)
+    }
+}
+
+// =============================================================================
+// Tap Queue
+// =============================================================================
+
+/// An event waiting for delivery to a tap.
+type TapItem = (Arc<dyn Any + Send + Sync>, CorrelationId, Metadata);
+
+/// Delivery counters for one tap, shared with health snapshots.
+#[derive(Debug)]
+pub(crate) struct TapStats {
+    pub(crate) name: &'static str,
+    pub(crate) delivered: AtomicU64,
+    pub(crate) dropped: AtomicU64,
+    pub(crate) failed: AtomicU64,
+}
+
+/// Bounded buffer between the runtime loop and a tap's delivery task.
+///
+/// Pushing never waits: when the buffer is full, the lag policy decides
+/// which event is dropped.
+struct TapQueue {
+    items: Mutex<VecDeque<TapItem>>,
+    closed: AtomicBool,
+    notify: Notify,
+    policy: TapPolicy,
+}
+
+impl TapQueue {
+    fn new(policy: TapPolicy) -> Self {
+        Self {
+            items: Mutex::new(VecDeque::with_capacity(policy.capacity.min(64))),
+            closed: AtomicBool::new(false),
+            notify: Notify::new(),
+            policy,
+        }
+    }
+
+    /// Buffer an event, returning `false` if an event was dropped.
+    fn push(&self, item: TapItem) -> bool {
+        let mut items = self.items.lock().unwrap_or_else(|e| e.into_inner());
+        let mut kept_all = true;
+        if items.len() >= self.policy.capacity {
+            kept_all = false;
+            match self.policy.lag {
+                TapLagPolicy::DropNewest => return false,
+                TapLagPolicy::DropOldest => {
+                    items.pop_front();
+                }
+            }
+        }
+        items.push_back(item);
+        drop(items);
+        self.notify.notify_one();
+        kept_all
+    }
+
+    /// Wait for the next event, or `None` once closed and drained.
+    async fn pop(&self) -> Option<TapItem> {
+        loop {
+            if let Some(item) = self
+                .items
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .pop_front()
+            {
+                return Some(item);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            // notify_one stores a permit, so a push between the check and
+            // this await is not missed
+            self.notify.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
 }
 
 // =============================================================================
 // Tap Runner (Type-Erased)
 // =============================================================================
 
+/// Type-erased delivery task: drains the queue into the tap.
+type TapSpawnFn = Box<dyn Fn(Arc<TapQueue>, Arc<TapStats>) + Send + Sync>;
+
+/// Type-erased [`EventTap::accepts`].
+type TapAcceptsFn = Box<dyn Fn(&(dyn Any + Send + Sync)) -> bool + Send + Sync>;
+
 /// Type-erased tap runner that can handle any event type.
+///
+/// Each runner owns a bounded queue and a dedicated delivery task, started
+/// on the first matching event. The task delivers events one at a time in
+/// the order they were observed and exits once the runner is dropped and
+/// the queue is drained.
 pub(crate) struct TapRunner {
     event_type: TypeId,
-    run_fn: Box<dyn Fn(&dyn std::any::Any, CorrelationId) + Send + Sync>,
     name: &'static str,
+    queue: Arc<TapQueue>,
+    stats: Arc<TapStats>,
+    spawn_fn: TapSpawnFn,
+    accepts_fn: TapAcceptsFn,
+    started: OnceLock<()>,
 }
 
 impl TapRunner {
     /// Create a tap runner for a specific tap and event type.
-    ///
-    /// E must be Clone (which Event types are via the blanket impl).
-    pub fn new<E: Event + Clone, T: EventTap<E>>(tap: T, name: &'static str) -> Self {
+    pub fn new<E: Event + Clone, T: EventTap<E>>(
+        tap: T,
+        name: &'static str,
+        policy: TapPolicy,
+    ) -> Self {
         let tap = Arc::new(tap);
+        let filter = tap.clone();
 
         Self {
             event_type: TypeId::of::<E>(),
             name,
-            run_fn: Box::new(move |any_event, correlation_id| {
-                // Downcast and clone the event before spawning
-                let Some(event) = any_event.downcast_ref::<E>() else {
-                    return;
-                };
-                let event = event.clone();
-
+            queue: Arc::new(TapQueue::new(policy)),
+            stats: Arc::new(TapStats {
+                name,
+                delivered: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
+            }),
+            spawn_fn: Box::new(move |queue, stats| {
                 let tap = tap.clone();
-                let ctx = TapContext::new(correlation_id);
-
-                // Spawn as fire-and-forget - taps don't block the main flow
                 tokio::spawn(async move {
-                    if let Err(e) = tap.on_event(&event, &ctx).await {
-                        warn!(
-                            tap = %std::any::type_name::<T>(),
-                            error = %e,
-                            "tap failed"
-                        );
+                    while let Some((payload, correlation_id, metadata)) = queue.pop().await {
+                        let Ok(event) = payload.downcast::<E>() else {
+                            continue;
+                        };
+                        let ctx = TapContext::new(correlation_id).with_metadata(metadata);
+
+                        let result = AssertUnwindSafe(tap.on_shared(event, &ctx))
+                            .catch_unwind()
+                            .await;
+                        let error = match result {
+                            Ok(Ok(())) => {
+                                stats.delivered.fetch_add(1, Ordering::Relaxed);
+                                continue;
+                            }
+                            Ok(Err(e)) => e.to_string(),
+                            Err(_) => "tap panicked".to_string(),
+                        };
+                        stats.failed.fetch_add(1, Ordering::Relaxed);
+                        warn!(tap = stats.name, %error, "tap failed");
                     }
                 });
             }),
+            accepts_fn: Box::new(move |payload| {
+                payload
+                    .downcast_ref::<E>()
+                    .is_some_and(|event| filter.accepts(event))
+            }),
+            started: OnceLock::new(),
         }
     }
 
-    /// Get the event type this runner handles.
-    pub fn event_type(&self) -> TypeId {
-        self.event_type
-    }
+    /// Queue the event for delivery if it matches, without waiting.
+    pub fn try_run(
+        &self,
+        event: &Arc<dyn Any + Send + Sync>,
+        correlation_id: CorrelationId,
+        metadata: &Metadata,
+    ) {
+        if (**event).type_id() != self.event_type || !(self.accepts_fn)(event.as_ref()) {
+            return;
+        }
 
-    /// Get the tap name (for debugging).
-    pub fn name(&self) -> &'static str {
-        self.name
+        self.started
+            .get_or_init(|| (self.spawn_fn)(self.queue.clone(), self.stats.clone()));
+
+        if !self
+            .queue
+            .push((event.clone(), correlation_id, metadata.clone()))
+        {
+            let dropped = self.stats.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            // Log the first drop and then every thousandth, not every event
+            if dropped == 1 || dropped.is_multiple_of(1000) {
+                warn!(tap = self.name, dropped, "tap lagging, dropping events");
+            }
+        }
     }
+}
 
-    /// Run the tap if the event matches.
-    pub fn try_run(&self, event: &dyn std::any::Any, correlation_id: CorrelationId) {
-        if event.type_id() == self.event_type {
-            (self.run_fn)(event, correlation_id);
-        }
+impl Drop for TapRunner {
+    fn drop(&mut self) {
+        self.queue.close();
     }
 }
 
@@ -186,6 +587,7 @@ impl std::fmt::Debug for TapRunner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TapRunner")
             .field("name", &self.name)
+            .field("policy", &self.queue.policy)
             .finish_non_exhaustive()
     }
 }
@@ -209,18 +611,33 @@ impl TapRegistry {
     }
 
     /// Register a tap for an event type.
-    pub fn register<E: Event + Clone, T: EventTap<E>>(&mut self, tap: T, name: &'static str) {
-        self.taps.push(TapRunner::new(tap, name));
+    pub fn register<E: Event + Clone, T: EventTap<E>>(
+        &mut self,
+        tap: T,
+        name: &'static str,
+        policy: TapPolicy,
+    ) {
+        self.taps.push(TapRunner::new(tap, name, policy));
     }
 
-    /// Run all taps that match the given event.
-    pub fn run_all(&self, event: &dyn std::any::Any, correlation_id: Option<CorrelationId>) {
-        let cid = correlation_id.unwrap_or(CorrelationId::NONE);
+    /// Queue the envelope's event for every tap that matches it, with the
+    /// envelope's correlation ID and metadata.
+    pub(crate) fn run_envelope(&self, envelope: &EventEnvelope) {
         for tap in &self.taps {
-            tap.try_run(event, cid);
+            tap.try_run(&envelope.payload, envelope.cid, &envelope.metadata);
         }
     }
 
+    /// Whether any tap is registered for events of `event_type`.
+    pub(crate) fn observes(&self, event_type: TypeId) -> bool {
+        self.taps.iter().any(|tap| tap.event_type == event_type)
+    }
+
+    /// Delivery counters for every registered tap.
+    pub(crate) fn stats(&self) -> Vec<Arc<TapStats>> {
+        self.taps.iter().map(|tap| tap.stats.clone()).collect()
+    }
+
     /// Check if any taps are registered.
     pub fn is_empty(&self) -> bool {
         self.taps.is_empty()
@@ -235,7 +652,7 @@ impl TapRegistry {
 impl std::fmt::Debug for TapRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TapRegistry")
-            .field("tap_count", &self.taps.len())
+            .field("tap_count", &self.len())
             .finish()
     }
 }
@@ -273,10 +690,10 @@ mod tests {
                 count: count.clone(),
             },
             "test_tap",
+            TapPolicy::default(),
         );
 
-        let event = TestEvent { value: 42 };
-        registry.run_all(&event, None);
+        registry.run_envelope(&EventEnvelope::new(CorrelationId::NONE, TestEvent { value: 42 }));
 
         // Give the spawned task time to run
         tokio::time::sleep(Duration::from_millis(10)).await;
@@ -306,17 +723,201 @@ mod tests {
                 received: received_cid.clone(),
             },
             "cid_tap",
+            TapPolicy::default(),
         );
 
         let cid = CorrelationId::new();
-        let event = TestEvent { value: 42 };
-        registry.run_all(&event, Some(cid));
+        registry.run_envelope(&EventEnvelope::new(cid, TestEvent { value: 42 }));
 
         tokio::time::sleep(Duration::from_millis(10)).await;
 
         assert_eq!(*received_cid.lock().unwrap(), cid);
     }
 
+    /// Only accepts even values.
+    struct EvenTap {
+        seen: Arc<std::sync::Mutex<Vec<i32>>>,
+    }
+
+    #[async_trait]
+    impl EventTap<TestEvent> for EvenTap {
+        async fn on_event(&self, event: &TestEvent, _ctx: &TapContext) -> Result<()> {
+            self.seen.lock().unwrap().push(event.value);
+            Ok(())
+        }
+
+        fn accepts(&self, event: &TestEvent) -> bool {
+            event.value % 2 == 0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tap_accepts_filters_before_queueing() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = TapRegistry::new();
+        // Capacity 2: rejected events must not take buffer slots
+        registry.register(
+            EvenTap { seen: seen.clone() },
+            "even_tap",
+            TapPolicy::new(2),
+        );
+
+        for value in [1, 2, 3, 5, 4] {
+            registry.run_envelope(&EventEnvelope::new(CorrelationId::NONE, TestEvent { value }));
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(*seen.lock().unwrap(), vec![2, 4]);
+        let stats = &registry.stats()[0];
+        assert_eq!(stats.delivered.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.dropped.load(Ordering::Relaxed), 0);
+    }
+
+    /// Register `tap` and feed it the values 1 to 10.
+    async fn run_values<T: EventTap<TestEvent>>(tap: T) {
+        let mut registry = TapRegistry::new();
+        registry.register(tap, "combined_tap", TapPolicy::default());
+        for value in 1..=10 {
+            registry.run_envelope(&EventEnvelope::new(CorrelationId::NONE, TestEvent { value }));
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(registry.stats()[0].dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_combinators_thin_out_events() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let even = || EvenTap { seen: seen.clone() };
+        let taken = || std::mem::take(&mut *seen.lock().unwrap());
+
+        run_values(even().sampled(0.0)).await;
+        assert!(taken().is_empty());
+
+        run_values(even().sampled(1.0)).await;
+        assert_eq!(taken(), vec![2, 4, 6, 8, 10]);
+
+        // Odd values are rejected by the tap before taking a token
+        let throttle = RateLimitPolicy::per_second(1).with_burst(3);
+        run_values(even().throttled(throttle)).await;
+        assert_eq!(taken(), vec![2, 4, 6]);
+
+        run_values(even().filtered(|e: &TestEvent| e.value > 5)).await;
+        assert_eq!(taken(), vec![6, 8, 10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "between 0 and 1")]
+    fn test_sample_rate_must_be_a_fraction() {
+        let _ = CountingTap {
+            count: Arc::default(),
+        }
+        .sampled(1.5);
+    }
+
+    struct SharedTap(Arc<std::sync::Mutex<Option<Arc<TestEvent>>>>);
+
+    #[async_trait]
+    impl EventTap<TestEvent> for SharedTap {
+        async fn on_event(&self, _event: &TestEvent, _ctx: &TapContext) -> Result<()> {
+            unreachable!("on_shared is overridden")
+        }
+
+        async fn on_shared(&self, event: Arc<TestEvent>, _ctx: &TapContext) -> Result<()> {
+            *self.0.lock().unwrap() = Some(event);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_on_shared_receives_the_published_payload() {
+        let kept = Arc::new(std::sync::Mutex::new(None));
+        let mut registry = TapRegistry::new();
+        registry.register(SharedTap(kept.clone()), "shared_tap", TapPolicy::default());
+
+        let event = Arc::new(TestEvent { value: 7 });
+        registry.run_envelope(&EventEnvelope::from_arc(CorrelationId::NONE, event.clone()));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let kept = kept.lock().unwrap().clone().unwrap();
+        assert!(Arc::ptr_eq(&kept, &event));
+    }
+
+    /// Records event values, blocking until the gate opens.
+    struct GatedTap {
+        gate: Arc<Notify>,
+        seen: Arc<std::sync::Mutex<Vec<i32>>>,
+    }
+
+    #[async_trait]
+    impl EventTap<TestEvent> for GatedTap {
+        async fn on_event(&self, event: &TestEvent, _ctx: &TapContext) -> Result<()> {
+            self.gate.notified().await;
+            self.seen.lock().unwrap().push(event.value);
+            if event.value < 0 {
+                anyhow::bail!("negative value");
+            }
+            Ok(())
+        }
+    }
+
+    /// Push `values` into a tap with capacity 2 that is stuck on the first
+    /// event, then release it and return what it saw.
+    async fn run_lagging_tap(lag: TapLagPolicy, values: &[i32]) -> (Vec<i32>, Arc<TapStats>) {
+        let gate = Arc::new(Notify::new());
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = TapRegistry::new();
+        registry.register(
+            GatedTap {
+                gate: gate.clone(),
+                seen: seen.clone(),
+            },
+            "gated_tap",
+            TapPolicy::new(2).with_lag_policy(lag),
+        );
+        let stats = registry.stats().remove(0);
+
+        for &value in values {
+            registry.run_envelope(&EventEnvelope::new(CorrelationId::NONE, TestEvent { value }));
+            // Let the delivery task pick up the first event and block on it
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        for _ in 0..values.len() {
+            gate.notify_one();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let seen = seen.lock().unwrap().clone();
+        (seen, stats)
+    }
+
+    #[tokio::test]
+    async fn test_lagging_tap_drops_newest_without_blocking() {
+        let (seen, stats) = run_lagging_tap(TapLagPolicy::DropNewest, &[1, 2, 3, 4, 5]).await;
+
+        // 1 is in flight, 2 and 3 fill the buffer, 4 and 5 are dropped
+        assert_eq!(seen, vec![1, 2, 3]);
+        assert_eq!(stats.delivered.load(Ordering::Relaxed), 3);
+        assert_eq!(stats.dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_tap_drops_oldest() {
+        let (seen, stats) = run_lagging_tap(TapLagPolicy::DropOldest, &[1, 2, 3, 4, 5]).await;
+
+        assert_eq!(seen, vec![1, 4, 5]);
+        assert_eq!(stats.dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tap_failures_are_counted() {
+        let (seen, stats) = run_lagging_tap(TapLagPolicy::DropNewest, &[1, -2]).await;
+
+        assert_eq!(seen, vec![1, -2]);
+        assert_eq!(stats.delivered.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.failed.load(Ordering::Relaxed), 1);
+    }
+
     #[test]
     fn test_tap_context_has_timestamp() {
         let ctx = TapContext::new(CorrelationId::NONE);
@@ -0,0 +1,126 @@
+//! Delayed event emission - one timer task per running engine.
+//!
+//! `EngineHandle::emit_after` and `emit_at` hand events to an [`EventTimer`],
+//! which keeps them ordered by deadline and emits each to the bus when it is
+//! due. Pending events live in memory: they are dropped if the engine is
+//! aborted or the process exits. For follow-ups that must survive a restart,
+//! use an `ExecutionMode::Scheduled` command.
+
+use std::collections::BTreeMap;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::bus::EventBus;
+use crate::core::EventEnvelope;
+
+/// Emits envelopes to the bus at their deadlines.
+pub(crate) struct EventTimer {
+    tx: mpsc::UnboundedSender<(Instant, EventEnvelope)>,
+    task: JoinHandle<()>,
+}
+
+impl EventTimer {
+    /// Spawn the timer task for `bus`.
+    pub(crate) fn spawn(bus: EventBus) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            task: tokio::spawn(run(bus, rx)),
+        }
+    }
+
+    /// Emit `envelope` at `deadline`, or right away if it has passed.
+    pub(crate) fn schedule(&self, deadline: Instant, envelope: EventEnvelope) {
+        // Only fails once aborted, when pending events are dropped anyway
+        let _ = self.tx.send((deadline, envelope));
+    }
+
+    /// Stop the timer, dropping pending events.
+    pub(crate) fn abort(&self) {
+        self.task.abort();
+    }
+}
+
+async fn run(bus: EventBus, mut rx: mpsc::UnboundedReceiver<(Instant, EventEnvelope)>) {
+    // Keyed by (deadline, arrival) so equal deadlines emit in schedule order
+    let mut pending: BTreeMap<(Instant, u64), EventEnvelope> = BTreeMap::new();
+    let mut seq = 0u64;
+    // The handle was dropped; finish what is pending, then exit
+    let mut closed = false;
+
+    loop {
+        let next = pending
+            .first_key_value()
+            .map(|(&(deadline, _), _)| deadline);
+        if closed && next.is_none() {
+            return;
+        }
+
+        tokio::select! {
+            received = rx.recv(), if !closed => match received {
+                Some((deadline, envelope)) => {
+                    pending.insert((deadline, seq), envelope);
+                    seq += 1;
+                }
+                None => closed = true,
+            },
+            _ = tokio::time::sleep_until(next.unwrap_or_else(Instant::now)), if next.is_some() => {
+                let now = Instant::now();
+                while let Some(entry) = pending.first_entry() {
+                    if entry.key().0 > now {
+                        break;
+                    }
+                    bus.emit_envelope(entry.remove());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_emits_in_deadline_order() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let timer = EventTimer::spawn(bus);
+        let start = Instant::now();
+
+        timer.schedule(
+            start + Duration::from_millis(60),
+            EventEnvelope::new_random("late"),
+        );
+        timer.schedule(
+            start + Duration::from_millis(30),
+            EventEnvelope::new_random("early"),
+        );
+        timer.schedule(start, EventEnvelope::new_random("now"));
+
+        for (expected, at) in [("now", 0), ("early", 30), ("late", 60)] {
+            let envelope = events.recv().await.unwrap();
+            assert_eq!(envelope.downcast_ref::<&str>(), Some(&expected));
+            assert!(start.elapsed() >= Duration::from_millis(at));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_abort_drops_pending_events() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let timer = EventTimer::spawn(bus.clone());
+
+        timer.schedule(
+            Instant::now() + Duration::from_millis(20),
+            EventEnvelope::new_random("dropped"),
+        );
+        timer.abort();
+
+        let received = tokio::time::timeout(Duration::from_millis(60), events.recv()).await;
+        assert!(received.is_err());
+    }
+}
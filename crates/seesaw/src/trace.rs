@@ -0,0 +1,490 @@
+//! Causality traces - what happened for one correlation ID, and why.
+//!
+//! A [`TraceRecorder`] given to a runtime records, for every correlation,
+//! the chain event → machine decision → effect → event, with timestamps.
+//! Export a [`Trace`] as JSON or Graphviz DOT to see where a workflow went,
+//! and where it stopped:
+//!
+//! ```ignore
+//! let recorder = TraceRecorder::new();
+//! let handle = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_effect::<ChargeCommand, _>(ChargeEffect)
+//!     .with_trace_recorder(recorder.clone())
+//!     .build()
+//!     .start();
+//!
+//! // Later, for a correlation ID taken from a log line or CommandFailed
+//! if let Some(trace) = handle.export_trace(cid) {
+//!     std::fs::write("trace.dot", trace.to_dot())?; // dot -Tsvg trace.dot
+//! }
+//! ```
+//!
+//! An effect still running has no duration, and an event no machine decided
+//! has no children: those are where to look when a workflow stalls.
+//! Recording is in memory and bounded; the oldest correlations are forgotten
+//! first.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::core::{CorrelationId, EventEnvelope};
+
+/// Correlations a recorder keeps by default.
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+/// Nodes recorded per correlation before further nodes are dropped.
+const MAX_TRACE_NODES: usize = 10_000;
+
+/// Metadata key linking an effect's events to the effect's trace node.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceParent(pub(crate) u64);
+
+// =============================================================================
+// Trace
+// =============================================================================
+
+/// One step of a trace.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceNode {
+    /// Unique within the recorder.
+    pub id: u64,
+    /// The node that caused this one; `None` for events emitted from
+    /// outside the runtime.
+    pub parent: Option<u64>,
+    /// When the step happened (for effects, when they started).
+    pub at: DateTime<Utc>,
+    /// What happened.
+    #[serde(flatten)]
+    pub kind: TraceNodeKind,
+}
+
+/// What a [`TraceNode`] records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceNodeKind {
+    /// The runtime received an event.
+    Event {
+        /// Type name of the event, or `None` if no machine handles it.
+        event_type: Option<&'static str>,
+        /// Causation depth of the event.
+        hops: u32,
+        /// Whether the event was dropped as a loop instead of decided.
+        dropped: bool,
+    },
+    /// A machine decided a command for its parent event.
+    Decision {
+        /// Type name of the machine.
+        machine: &'static str,
+        /// Type name of the command decided.
+        command_type: &'static str,
+        /// `"inline"`, `"background"` or `"scheduled"`.
+        mode: &'static str,
+    },
+    /// An inline effect ran a batch of commands.
+    Effect {
+        /// Type name of the commands.
+        command_type: &'static str,
+        /// Number of commands in the batch.
+        batch_size: usize,
+        /// How long the effect ran, or `None` while it is still running.
+        duration_us: Option<u64>,
+        /// Why the batch failed, if it did.
+        error: Option<String>,
+    },
+}
+
+/// Everything recorded for one correlation ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct Trace {
+    /// The correlation traced.
+    pub correlation_id: Uuid,
+    /// Nodes in the order they were created; parents come first.
+    pub nodes: Vec<TraceNode>,
+}
+
+impl Trace {
+    /// The trace as a JSON value.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// The trace as a Graphviz digraph.
+    ///
+    /// Events are ellipses, decisions boxes and effects rounded boxes;
+    /// failed effects and dropped events are red, running effects dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph trace {\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            let (label, attrs) = match &node.kind {
+                TraceNodeKind::Event {
+                    event_type,
+                    hops,
+                    dropped,
+                } => (
+                    format!(
+                        "{}\\nhops {}",
+                        event_type.map_or("unhandled event", short_name),
+                        hops
+                    ),
+                    if *dropped {
+                        "shape=ellipse, color=red"
+                    } else {
+                        "shape=ellipse"
+                    },
+                ),
+                TraceNodeKind::Decision {
+                    machine,
+                    command_type,
+                    mode,
+                } => (
+                    format!(
+                        "{}\\n→ {} ({})",
+                        short_name(machine),
+                        short_name(command_type),
+                        mode
+                    ),
+                    "shape=box",
+                ),
+                TraceNodeKind::Effect {
+                    command_type,
+                    batch_size,
+                    duration_us,
+                    error,
+                } => {
+                    let mut label = format!("{} x {}", short_name(command_type), batch_size);
+                    match duration_us {
+                        Some(us) => {
+                            let _ = write!(label, "\\n{:?}", Duration::from_micros(*us));
+                        }
+                        None => label.push_str("\\nrunning"),
+                    }
+                    if let Some(error) = error {
+                        let _ = write!(label, "\\n{}", error.replace('\\', "\\\\"));
+                    }
+                    let attrs = match (duration_us, error) {
+                        (_, Some(_)) => "shape=box, style=rounded, color=red",
+                        (None, None) => "shape=box, style=\"rounded,dashed\"",
+                        (Some(_), None) => "shape=box, style=rounded",
+                    };
+                    (label, attrs)
+                }
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\", {}];",
+                node.id,
+                escape(&label),
+                attrs
+            );
+            if let Some(parent) = node.parent {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, node.id);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Last path segment of a type name, keeping generic arguments whole.
+fn short_name(type_name: &str) -> &str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    match path.rfind("::") {
+        Some(i) => &type_name[i + 2..],
+        None => type_name,
+    }
+}
+
+/// Escape quotes for a DOT label, keeping `\n` line breaks.
+fn escape(label: &str) -> String {
+    label.replace('"', "\\\"")
+}
+
+// =============================================================================
+// Recorder
+// =============================================================================
+
+/// Records causality traces, shared between a runtime and its handles.
+///
+/// Cloning is cheap; clones record into and read from the same traces.
+#[derive(Clone)]
+pub struct TraceRecorder {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    next_id: AtomicU64,
+    capacity: usize,
+    traces: Mutex<Traces>,
+}
+
+#[derive(Default)]
+struct Traces {
+    by_cid: HashMap<CorrelationId, Vec<TraceNode>>,
+    /// Correlations in the order they were first seen.
+    order: VecDeque<CorrelationId>,
+}
+
+impl TraceRecorder {
+    /// Create a recorder keeping the last [`DEFAULT_TRACE_CAPACITY`]
+    /// correlations.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_TRACE_CAPACITY)
+    }
+
+    /// Create a recorder keeping the last `capacity` correlations.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                next_id: AtomicU64::new(1),
+                capacity: capacity.max(1),
+                traces: Mutex::new(Traces::default()),
+            }),
+        }
+    }
+
+    /// Export the trace of `cid`, if it is still recorded.
+    pub fn trace(&self, cid: CorrelationId) -> Option<Trace> {
+        let traces = self.inner.traces.lock().unwrap();
+        let mut nodes = traces.by_cid.get(&cid)?.clone();
+        nodes.sort_by_key(|node| node.id);
+        Some(Trace {
+            correlation_id: cid.into_inner(),
+            nodes,
+        })
+    }
+
+    /// Correlations currently recorded, oldest first.
+    pub fn correlations(&self) -> Vec<CorrelationId> {
+        self.inner
+            .traces
+            .lock()
+            .unwrap()
+            .order
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Forget every trace.
+    pub fn clear(&self) {
+        let mut traces = self.inner.traces.lock().unwrap();
+        traces.by_cid.clear();
+        traces.order.clear();
+    }
+
+    /// Reserve the node ID of an event about to be decided.
+    pub(crate) fn next_id(&self) -> u64 {
+        self.inner.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Record the event `envelope` as node `id`.
+    pub(crate) fn event(
+        &self,
+        id: u64,
+        envelope: &EventEnvelope,
+        event_type: Option<&'static str>,
+        dropped: bool,
+    ) {
+        self.push(
+            envelope.cid,
+            TraceNode {
+                id,
+                parent: envelope
+                    .metadata
+                    .get::<TraceParent>()
+                    .map(|parent| parent.0),
+                at: Utc::now(),
+                kind: TraceNodeKind::Event {
+                    event_type,
+                    hops: envelope.hops,
+                    dropped,
+                },
+            },
+        );
+    }
+
+    /// Record a machine's decision for event node `event`.
+    pub(crate) fn decided(
+        &self,
+        cid: CorrelationId,
+        event: u64,
+        machine: &'static str,
+        command_type: &'static str,
+        mode: &'static str,
+    ) -> u64 {
+        let id = self.next_id();
+        self.push(
+            cid,
+            TraceNode {
+                id,
+                parent: Some(event),
+                at: Utc::now(),
+                kind: TraceNodeKind::Decision {
+                    machine,
+                    command_type,
+                    mode,
+                },
+            },
+        );
+        id
+    }
+
+    /// Record an effect starting on commands decided at node `decision`.
+    pub(crate) fn effect_started(
+        &self,
+        cid: CorrelationId,
+        decision: u64,
+        command_type: &'static str,
+        batch_size: usize,
+    ) -> u64 {
+        let id = self.next_id();
+        self.push(
+            cid,
+            TraceNode {
+                id,
+                parent: Some(decision),
+                at: Utc::now(),
+                kind: TraceNodeKind::Effect {
+                    command_type,
+                    batch_size,
+                    duration_us: None,
+                    error: None,
+                },
+            },
+        );
+        id
+    }
+
+    /// Record how the effect at node `id` ended.
+    pub(crate) fn effect_finished(
+        &self,
+        cid: CorrelationId,
+        id: u64,
+        elapsed: Duration,
+        failure: Option<String>,
+    ) {
+        let mut traces = self.inner.traces.lock().unwrap();
+        let node = traces
+            .by_cid
+            .get_mut(&cid)
+            .and_then(|nodes| nodes.iter_mut().rev().find(|node| node.id == id));
+        if let Some(TraceNode {
+            kind: TraceNodeKind::Effect {
+                duration_us, error, ..
+            },
+            ..
+        }) = node
+        {
+            *duration_us = Some(elapsed.as_micros() as u64);
+            *error = failure;
+        }
+    }
+
+    fn push(&self, cid: CorrelationId, node: TraceNode) {
+        let mut traces = self.inner.traces.lock().unwrap();
+        if !traces.by_cid.contains_key(&cid) {
+            if traces.order.len() >= self.inner.capacity {
+                if let Some(oldest) = traces.order.pop_front() {
+                    traces.by_cid.remove(&oldest);
+                }
+            }
+            traces.order.push_back(cid);
+        }
+        let nodes = traces.by_cid.entry(cid).or_default();
+        if nodes.len() < MAX_TRACE_NODES {
+            nodes.push(node);
+        }
+    }
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for TraceRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraceRecorder")
+            .field("capacity", &self.inner.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Metadata;
+
+    #[derive(Debug, Clone)]
+    struct OrderPlaced;
+
+    #[test]
+    fn test_trace_links_effect_events_to_effect() {
+        let recorder = TraceRecorder::new();
+        let cid = CorrelationId::new();
+
+        let placed = recorder.next_id();
+        recorder.event(
+            placed,
+            &EventEnvelope::new(cid, OrderPlaced),
+            Some("app::OrderPlaced"),
+            false,
+        );
+        let decision = recorder.decided(cid, placed, "app::OrderMachine", "app::Charge", "inline");
+        let effect = recorder.effect_started(cid, decision, "app::Charge", 1);
+        recorder.effect_finished(
+            cid,
+            effect,
+            Duration::from_millis(3),
+            Some("card declined".into()),
+        );
+        let failed = recorder.next_id();
+        let envelope = EventEnvelope::new(cid, OrderPlaced)
+            .with_metadata(Metadata::new().with(TraceParent(effect)));
+        recorder.event(failed, &envelope, None, false);
+
+        let trace = recorder.trace(cid).unwrap();
+        let parents: Vec<_> = trace.nodes.iter().map(|n| n.parent).collect();
+        assert_eq!(parents, [None, Some(placed), Some(decision), Some(effect)]);
+        assert!(matches!(
+            &trace.nodes[2].kind,
+            TraceNodeKind::Effect { duration_us: Some(3000), error: Some(e), .. } if e == "card declined"
+        ));
+
+        let dot = trace.to_dot();
+        assert!(dot.contains("OrderMachine\\n→ Charge (inline)"));
+        assert!(dot.contains(&format!("n{} -> n{};", effect, failed)));
+        assert_eq!(trace.to_json()["nodes"][1]["kind"], "decision");
+    }
+
+    #[test]
+    fn test_oldest_correlations_are_forgotten() {
+        let recorder = TraceRecorder::with_capacity(2);
+        let cids: Vec<_> = (0..3).map(|_| CorrelationId::new()).collect();
+        for &cid in &cids {
+            let id = recorder.next_id();
+            recorder.event(id, &EventEnvelope::new(cid, OrderPlaced), None, false);
+        }
+        assert!(recorder.trace(cids[0]).is_none());
+        assert_eq!(recorder.correlations(), cids[1..]);
+    }
+
+    #[test]
+    fn test_short_name_keeps_generics() {
+        assert_eq!(short_name("app::orders::Charge"), "Charge");
+        assert_eq!(
+            short_name("app::Wrapper<app::Charge>"),
+            "Wrapper<app::Charge>"
+        );
+        assert_eq!(short_name("Charge"), "Charge");
+    }
+}
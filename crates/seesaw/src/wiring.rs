@@ -0,0 +1,414 @@
+//! Wiring validation - checking machines against effects before startup.
+//!
+//! A command decided by a machine with no effect to run it only fails when
+//! it is dispatched, and a duplicate effect registration panics in
+//! `EngineBuilder::build`. [`EngineBuilder::validate`] checks the wiring
+//! up front and returns a [`WiringReport`] instead:
+//!
+//! ```ignore
+//! let builder = EngineBuilder::new(deps)
+//!     .with_machine(OrderMachine::default())
+//!     .with_effect::<OrderCommand, _>(OrderEffect);
+//!
+//! let report = builder.validate()?; // Err lists every problem
+//! for warning in report.issues() {
+//!     warn!(%warning, "engine wiring");
+//! }
+//! let engine = builder.build();
+//! ```
+//!
+//! [`wire!`](crate::wire) registers machines and effects on a builder and
+//! checks at compile time that every machine's command type has an effect
+//! or a job route, so a missing effect fails the build rather than startup.
+//!
+//! [`EngineBuilder::validate`]: crate::EngineBuilder::validate
+
+use std::any::TypeId;
+use std::fmt;
+
+use crate::job::CommandRegistry;
+
+/// Register machines and effects on an `EngineBuilder`, failing compilation
+/// if a machine decides a command type with no effect and no job route.
+///
+/// `effects` pairs each command type with its effect, registered with
+/// `with_effect`. `jobs` lists command types that need no effect because
+/// they are enqueued for a worker. A command type listed twice is a
+/// compile error as well, since `build` would panic on it.
+///
+/// ```ignore
+/// let engine = seesaw_core::wire!(EngineBuilder::new(deps) => {
+///     machines: [OrderMachine::default(), AuditMachine],
+///     effects: [
+///         OrderCommand => OrderEffect,
+///         AuditCommand => AuditEffect::new(pool.clone()),
+///     ],
+///     jobs: [SendEmail],
+/// })
+/// .with_job_queue(store)
+/// .build();
+/// ```
+///
+/// Only the types are checked: that a job queue is configured and a worker
+/// can deserialize the jobs is still up to
+/// [`EngineBuilder::validate_with_registry`](crate::EngineBuilder::validate_with_registry).
+/// Per-variant effects and machines added with other builder methods are
+/// not covered; register them on the returned builder.
+///
+/// Leaving `ChargeCard` out of both lists fails to compile, naming the
+/// command type that has no effect:
+///
+/// ```compile_fail,E0277
+/// # use seesaw_core::{Command, EngineBuilder, Machine};
+/// # #[derive(Debug, Clone)]
+/// # struct OrderPlaced;
+/// # #[derive(Debug, Clone)]
+/// # struct ChargeCard;
+/// # impl Command for ChargeCard {}
+/// # struct OrderMachine;
+/// # impl Machine for OrderMachine {
+/// #     type Event = OrderPlaced;
+/// #     type Command = ChargeCard;
+/// #     fn decide(&mut self, _: &OrderPlaced) -> Option<ChargeCard> {
+/// #         Some(ChargeCard)
+/// #     }
+/// # }
+/// let builder = seesaw_core::wire!(EngineBuilder::new(()) => {
+///     machines: [OrderMachine],
+///     effects: [],
+/// });
+/// ```
+///
+/// while routing it to a job compiles:
+///
+/// ```
+/// # use seesaw_core::{Command, EngineBuilder, Machine};
+/// # #[derive(Debug, Clone)]
+/// # struct OrderPlaced;
+/// # #[derive(Debug, Clone)]
+/// # struct ChargeCard;
+/// # impl Command for ChargeCard {}
+/// # struct OrderMachine;
+/// # impl Machine for OrderMachine {
+/// #     type Event = OrderPlaced;
+/// #     type Command = ChargeCard;
+/// #     fn decide(&mut self, _: &OrderPlaced) -> Option<ChargeCard> {
+/// #         Some(ChargeCard)
+/// #     }
+/// # }
+/// let builder = seesaw_core::wire!(EngineBuilder::new(()) => {
+///     machines: [OrderMachine],
+///     effects: [],
+///     jobs: [ChargeCard],
+/// });
+/// ```
+#[macro_export]
+macro_rules! wire {
+    (
+        $builder:expr => {
+            machines: [$($machine:expr),* $(,)?],
+            effects: [$($command:ty => $effect:expr),* $(,)?]
+            $(, jobs: [$($job:ty),* $(,)?])?
+            $(,)?
+        }
+    ) => {{
+        #[diagnostic::on_unimplemented(
+            message = "`{Self}` is decided by a machine but has no effect or job route",
+            label = "no effect or job for `{Self}`",
+            note = "add `{Self} => SomeEffect` to `effects` or `{Self}` to `jobs`"
+        )]
+        trait Wired {}
+        $(impl Wired for $command {})*
+        $($(impl Wired for $job {})*)?
+
+        fn wired<M>(machine: M) -> M
+        where
+            M: $crate::Machine,
+            M::Command: Wired,
+        {
+            machine
+        }
+
+        let builder = $builder;
+        $(let builder = builder.with_machine(wired($machine));)*
+        $(let builder = builder.with_effect::<$command, _>($effect);)*
+        builder
+    }};
+}
+
+/// A problem found by [`EngineBuilder::validate`](crate::EngineBuilder::validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WiringIssue {
+    /// A machine decides a command type that no effect handles and that
+    /// cannot be enqueued as a job. Dispatching it fails at runtime.
+    MissingEffect {
+        /// Type name of the machine.
+        machine: &'static str,
+        /// Type name of the command it decides.
+        command_type: &'static str,
+    },
+    /// More than one effect is registered for a command type, or for one
+    /// variant of an enum command. `build` would panic.
+    DuplicateEffect {
+        /// Type name of the command.
+        command_type: &'static str,
+        /// Type name of the variant, for per-variant effects.
+        variant: Option<&'static str>,
+    },
+    /// No registered machine decides the command type this effect handles.
+    ///
+    /// Only a warning: the effect may be reached by dispatching directly or
+    /// from a job worker.
+    OrphanEffect {
+        /// Type name of the command.
+        command_type: &'static str,
+    },
+}
+
+impl WiringIssue {
+    /// Whether the engine would misbehave, rather than merely carry dead code.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, WiringIssue::OrphanEffect { .. })
+    }
+}
+
+impl fmt::Display for WiringIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WiringIssue::MissingEffect {
+                machine,
+                command_type,
+            } => write!(f, "{machine} decides {command_type}, which has no effect"),
+            WiringIssue::DuplicateEffect {
+                command_type,
+                variant: None,
+            } => write!(f, "{command_type} has more than one effect"),
+            WiringIssue::DuplicateEffect {
+                command_type,
+                variant: Some(variant),
+            } => write!(
+                f,
+                "{command_type} has a conflicting effect for variant {variant}"
+            ),
+            WiringIssue::OrphanEffect { command_type } => {
+                write!(
+                    f,
+                    "effect for {command_type} is unreachable from any machine"
+                )
+            }
+        }
+    }
+}
+
+/// The outcome of validating an engine's wiring.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WiringReport {
+    issues: Vec<WiringIssue>,
+}
+
+impl WiringReport {
+    /// Every problem found, in registration order.
+    pub fn issues(&self) -> &[WiringIssue] {
+        &self.issues
+    }
+
+    /// Whether no problem is an error.
+    pub fn is_valid(&self) -> bool {
+        !self.issues.iter().any(WiringIssue::is_error)
+    }
+}
+
+impl fmt::Display for WiringReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return write!(f, "no wiring issues");
+        }
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A command type as registered, with its type name.
+#[derive(Clone, Copy)]
+struct Registered {
+    type_id: TypeId,
+    name: &'static str,
+}
+
+impl Registered {
+    fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+        }
+    }
+}
+
+/// What an `EngineBuilder` registered, recorded for validation.
+#[derive(Default)]
+pub(crate) struct Wiring {
+    /// Machine type name and the command type it decides.
+    machines: Vec<(&'static str, Registered)>,
+    /// Command type and, for per-variant effects, the variant type.
+    effects: Vec<(Registered, Option<Registered>)>,
+}
+
+impl Wiring {
+    /// Record machine `M` deciding commands of type `C`.
+    pub(crate) fn machine<M: 'static, C: 'static>(&mut self) {
+        self.machines
+            .push((std::any::type_name::<M>(), Registered::of::<C>()));
+    }
+
+    /// Record an effect for command type `C`.
+    pub(crate) fn effect<C: 'static>(&mut self) {
+        self.effects.push((Registered::of::<C>(), None));
+    }
+
+    /// Record an effect for variant `V` of enum command `C`.
+    pub(crate) fn variant_effect<C: 'static, V: 'static>(&mut self) {
+        self.effects
+            .push((Registered::of::<C>(), Some(Registered::of::<V>())));
+    }
+
+    /// Check the recorded wiring. Commands without an effect are allowed
+    /// when they can be enqueued: a job queue is configured and `registry`
+    /// can deserialize them for a worker.
+    pub(crate) fn validate(
+        &self,
+        has_job_queue: bool,
+        registry: Option<&CommandRegistry>,
+    ) -> WiringReport {
+        let mut issues = Vec::new();
+
+        // Duplicates: a second whole effect, a second effect for a variant,
+        // or a whole effect mixed with per-variant effects
+        for (i, (command, variant)) in self.effects.iter().enumerate() {
+            let conflicts = self.effects[..i].iter().any(|(earlier, earlier_variant)| {
+                earlier.type_id == command.type_id
+                    && match (earlier_variant, variant) {
+                        (Some(a), Some(b)) => a.type_id == b.type_id,
+                        _ => true,
+                    }
+            });
+            if conflicts {
+                issues.push(WiringIssue::DuplicateEffect {
+                    command_type: command.name,
+                    variant: variant.map(|v| v.name),
+                });
+            }
+        }
+
+        let has_effect = |type_id: TypeId| {
+            self.effects
+                .iter()
+                .any(|(command, _)| command.type_id == type_id)
+        };
+        let enqueueable = |type_id: TypeId| {
+            has_job_queue && registry.is_some_and(|registry| registry.handles_type(type_id))
+        };
+        for (machine, command) in &self.machines {
+            if !has_effect(command.type_id) && !enqueueable(command.type_id) {
+                issues.push(WiringIssue::MissingEffect {
+                    machine,
+                    command_type: command.name,
+                });
+            }
+        }
+
+        let mut orphans: Vec<Registered> = Vec::new();
+        for (command, _) in &self.effects {
+            let decided = self
+                .machines
+                .iter()
+                .any(|(_, decided)| decided.type_id == command.type_id);
+            if !decided && !orphans.iter().any(|o| o.type_id == command.type_id) {
+                orphans.push(*command);
+            }
+        }
+        issues.extend(
+            orphans
+                .into_iter()
+                .map(|command| WiringIssue::OrphanEffect {
+                    command_type: command.name,
+                }),
+        );
+
+        WiringReport { issues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct OrderMachine;
+    struct AuditMachine;
+    struct PlaceOrder;
+    struct WriteAudit;
+    struct Unused;
+
+    #[test]
+    fn test_reports_missing_orphan_and_duplicate_effects() {
+        let mut wiring = Wiring::default();
+        wiring.machine::<OrderMachine, PlaceOrder>();
+        wiring.machine::<AuditMachine, WriteAudit>();
+        wiring.effect::<PlaceOrder>();
+        wiring.effect::<PlaceOrder>();
+        wiring.effect::<Unused>();
+
+        let report = wiring.validate(false, None);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.issues(),
+            [
+                WiringIssue::DuplicateEffect {
+                    command_type: std::any::type_name::<PlaceOrder>(),
+                    variant: None,
+                },
+                WiringIssue::MissingEffect {
+                    machine: std::any::type_name::<AuditMachine>(),
+                    command_type: std::any::type_name::<WriteAudit>(),
+                },
+                WiringIssue::OrphanEffect {
+                    command_type: std::any::type_name::<Unused>(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variant_effects_conflict_with_whole_effects() {
+        let mut wiring = Wiring::default();
+        wiring.machine::<OrderMachine, PlaceOrder>();
+        wiring.variant_effect::<PlaceOrder, WriteAudit>();
+        wiring.variant_effect::<PlaceOrder, Unused>();
+        assert!(wiring.validate(false, None).issues().is_empty());
+
+        wiring.effect::<PlaceOrder>();
+        let report = wiring.validate(false, None);
+        assert_eq!(report.issues().len(), 1);
+        assert!(report.issues()[0].is_error());
+    }
+
+    #[test]
+    fn test_registered_jobs_need_no_effect() {
+        #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+        struct SendEmail;
+        impl crate::Command for SendEmail {}
+
+        let mut wiring = Wiring::default();
+        wiring.machine::<OrderMachine, SendEmail>();
+        let mut registry = CommandRegistry::new();
+        registry.register::<SendEmail>("email:send", vec![1]);
+
+        assert!(wiring.validate(true, Some(&registry)).is_valid());
+        // Without a job queue the command can only run inline
+        assert!(!wiring.validate(false, Some(&registry)).is_valid());
+    }
+}
@@ -0,0 +1,900 @@
+//! Job worker - running background and scheduled commands from a job store.
+//!
+//! A [`JobWorker`] claims jobs from a [`JobStore`], turns them back into
+//! commands with a [`CommandRegistry`], and runs them inline on its own
+//! [`Dispatcher`]. Each attempt is bounded: while the effect runs the
+//! worker extends the job's lease with heartbeats, and once the job type's
+//! timeout elapses the effect future is dropped and the attempt is recorded
+//! as a retryable failure.
+//!
+//! ```ignore
+//! let dispatcher = Dispatcher::new(deps, bus).with_effect::<SendEmail, _>(EmailEffect);
+//!
+//! let worker = JobWorker::new(store, registry, dispatcher)
+//!     .with_worker_id("worker-1")
+//!     .with_concurrency(8)
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_job_timeout("report:generate", Duration::from_secs(600))
+//!     .with_heartbeat_interval(Duration::from_secs(10));
+//!
+//! tokio::spawn(async move { worker.run().await });
+//! ```
+//!
+//! Keep the heartbeat interval well below the store's lease timeout, so a
+//! slow heartbeat does not let another worker reclaim a running job. If one
+//! does (the process paused, the database was unreachable), the store fences
+//! the old claim: the next heartbeat fails with [`LeaseLost`], and the
+//! worker drops the attempt without recording an outcome.
+//!
+//! # Two-Phase Claims
+//!
+//! With [`with_reservation_window`](JobWorker::with_reservation_window), the
+//! worker reserves jobs instead of claiming them, deserializes each one, and
+//! only then confirms it as an attempt. A job of a type this worker has no
+//! handler for (an older build during a rolling deploy) is released for
+//! another worker rather than dead-lettered.
+//!
+//! # Liveness Events
+//!
+//! The worker announces on its dispatcher's bus when it starts an attempt
+//! ([`JobClaimed`]), when a heartbeat fails ([`JobHeartbeatMissed`]), and
+//! when a failure dead-letters a job ([`JobDeadLettered`]). Give the
+//! dispatcher the engine's bus and machines can react to queue health:
+//!
+//! ```ignore
+//! let dispatcher = Dispatcher::new(deps, handle.bus().clone());
+//!
+//! impl Machine for OnCallMachine {
+//!     type Event = JobDeadLettered;
+//!     type Command = NotifySlack;
+//!
+//!     fn decide(&mut self, event: &JobDeadLettered) -> Option<NotifySlack> {
+//!         Some(NotifySlack::new(format!("{} dead-lettered: {}", event.job_type, event.error)))
+//!     }
+//! }
+//! ```
+//!
+//! # Warm Start
+//!
+//! A worker that crashed mid-attempt leaves its jobs running until their
+//! leases expire. Restarted under the same worker ID, it can hand them to
+//! machines instead: [`recover`](JobWorker::recover) emits a
+//! [`JobRecovered`] for each, and a machine decides whether to retry the
+//! job, mark it done, or dead-letter it with a [`ResolveRecoveredJob`],
+//! executed by [`ResolveRecoveredJobEffect`]:
+//!
+//! ```ignore
+//! impl Machine for RecoveryMachine {
+//!     type Event = JobRecovered;
+//!     type Command = ResolveRecoveredJob;
+//!
+//!     fn decide(&mut self, job: &JobRecovered) -> Option<ResolveRecoveredJob> {
+//!         let action = match job.job_type.as_str() {
+//!             // Charging twice is worse than not charging: let a human look
+//!             "payment:charge" => RecoveryAction::dead_letter("worker crashed mid-charge"),
+//!             _ => RecoveryAction::Retry,
+//!         };
+//!         Some(ResolveRecoveredJob::new(job, action))
+//!     }
+//! }
+//!
+//! let handle = EngineBuilder::new(deps)
+//!     .with_machine(RecoveryMachine)
+//!     .with_effect::<ResolveRecoveredJob, _>(ResolveRecoveredJobEffect::new(store.clone()))
+//!     .build()
+//!     .start();
+//!
+//! let worker = JobWorker::new(store, registry, Dispatcher::new(deps, handle.bus().clone()))
+//!     .with_worker_id(hostname);
+//! worker.recover().await?;
+//! tokio::spawn(async move { worker.run().await });
+//! ```
+//!
+//! To compensate first, decide a command of your own from the
+//! [`JobRecovered`] and finish the job from its effect with
+//! [`RecoveryAction::apply`].
+//!
+//! # Error Redaction
+//!
+//! Errors are scrubbed with the dispatcher's
+//! [`Redactor`](crate::Redactor) before the store persists them and before
+//! they are announced in [`JobDeadLettered`] or [`JobHeartbeatMissed`], so
+//! a connection string quoted by an `anyhow` chain does not end up in the
+//! jobs table:
+//!
+//! ```ignore
+//! let dispatcher = Dispatcher::new(deps, bus)
+//!     .with_redactor(Redactor::new().with_secret(&config.smtp_password));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tracing::warn;
+
+use uuid::Uuid;
+
+use crate::core::Command;
+use crate::dispatch::Dispatcher;
+use crate::effect_impl::{Effect, EffectContext};
+use crate::error::CommandFailed;
+use crate::job::{
+    ClaimedJob, CommandRegistry, DeserializationError, FailureKind, JobLease, JobStore, LeaseLost,
+};
+
+/// Default interval between heartbeats while a job runs.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+
+// =============================================================================
+// Events
+// =============================================================================
+
+/// Emitted when a worker starts an attempt of a job.
+#[derive(Debug, Clone)]
+pub struct JobClaimed {
+    pub job_id: Uuid,
+    pub job_type: String,
+    /// Attempt number, starting at 1.
+    pub attempt: i32,
+    pub worker_id: String,
+}
+
+/// Emitted when a heartbeat for a running job fails.
+#[derive(Debug, Clone)]
+pub struct JobHeartbeatMissed {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub worker_id: String,
+    /// Whether the job was reclaimed, abandoning the attempt; otherwise the
+    /// store could not be reached and the worker keeps trying.
+    pub lease_lost: bool,
+    pub error: String,
+}
+
+/// Emitted when a failed attempt moves a job to the dead letter queue.
+///
+/// Stores that cannot tell whether a retryable failure was the job's last
+/// attempt only report non-retryable failures (see
+/// [`JobStore::record_failure`]).
+#[derive(Debug, Clone)]
+pub struct JobDeadLettered {
+    pub job_id: Uuid,
+    pub job_type: String,
+    /// The attempt that failed.
+    pub attempt: i32,
+    pub error: String,
+}
+
+/// Emitted by [`JobWorker::recover`] for each job a previous process of
+/// the worker left running.
+#[derive(Debug, Clone)]
+pub struct JobRecovered {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    /// The attempt that was interrupted.
+    pub attempt: i32,
+    pub worker_id: String,
+    /// The claim to finish the job with.
+    pub lease: JobLease,
+}
+
+// =============================================================================
+// Recovery
+// =============================================================================
+
+/// What to do with a job a crashed worker left running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Fail the interrupted attempt retryably, so the store runs the job
+    /// again after its backoff, or dead-letters it if no retries remain.
+    Retry,
+    /// Mark the job succeeded, e.g. because its effect is known to have
+    /// completed or has been compensated.
+    Succeed,
+    /// Move the job to the dead letter queue for an operator.
+    DeadLetter {
+        /// Recorded as the job's error.
+        reason: String,
+    },
+}
+
+impl RecoveryAction {
+    /// Dead-letter the job with `reason`.
+    pub fn dead_letter(reason: impl Into<String>) -> Self {
+        RecoveryAction::DeadLetter {
+            reason: reason.into(),
+        }
+    }
+
+    /// Finish the job `lease` holds in `store`, returning whether it was
+    /// dead-lettered.
+    ///
+    /// Fails with [`LeaseLost`] if the lease expired and the job was
+    /// reclaimed in the meantime.
+    pub async fn apply(&self, store: &dyn JobStore, lease: JobLease) -> Result<bool> {
+        match self {
+            RecoveryAction::Retry => {
+                store
+                    .record_failure(lease, RECOVERED_ATTEMPT_ERROR, FailureKind::Retryable)
+                    .await
+            }
+            RecoveryAction::Succeed => store.mark_succeeded(lease).await.map(|()| false),
+            RecoveryAction::DeadLetter { reason } => {
+                store
+                    .record_failure(lease, reason, FailureKind::NonRetryable)
+                    .await
+            }
+        }
+    }
+}
+
+/// Error recorded for an attempt retried with [`RecoveryAction::Retry`].
+const RECOVERED_ATTEMPT_ERROR: &str = "attempt interrupted by a worker crash";
+
+/// Resolve a recovered job: the command machines decide from a
+/// [`JobRecovered`].
+#[derive(Debug, Clone)]
+pub struct ResolveRecoveredJob {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub lease: JobLease,
+    pub action: RecoveryAction,
+}
+
+impl ResolveRecoveredJob {
+    /// Resolve `job` with `action`.
+    pub fn new(job: &JobRecovered, action: RecoveryAction) -> Self {
+        Self {
+            job_id: job.job_id,
+            job_type: job.job_type.clone(),
+            lease: job.lease,
+            action,
+        }
+    }
+}
+
+impl Command for ResolveRecoveredJob {}
+
+/// Emitted once a [`ResolveRecoveredJob`] has been applied.
+#[derive(Debug, Clone)]
+pub struct RecoveredJobResolved {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub action: RecoveryAction,
+    /// Whether the job ended up in the dead letter queue.
+    pub dead_lettered: bool,
+}
+
+/// Applies [`ResolveRecoveredJob`] commands to a job store.
+pub struct ResolveRecoveredJobEffect {
+    store: Arc<dyn JobStore>,
+}
+
+impl ResolveRecoveredJobEffect {
+    /// Resolve jobs in `store`.
+    pub fn new(store: Arc<dyn JobStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl<D: Send + Sync + 'static> Effect<ResolveRecoveredJob, D> for ResolveRecoveredJobEffect {
+    type Event = RecoveredJobResolved;
+
+    async fn execute(
+        &self,
+        command: ResolveRecoveredJob,
+        _ctx: EffectContext<D>,
+    ) -> Result<RecoveredJobResolved> {
+        let dead_lettered = command.action.apply(&*self.store, command.lease).await?;
+        Ok(RecoveredJobResolved {
+            job_id: command.job_id,
+            job_type: command.job_type,
+            action: command.action,
+            dead_lettered,
+        })
+    }
+}
+
+// =============================================================================
+// Worker
+// =============================================================================
+
+/// Claims jobs from a store and runs them on a dispatcher.
+pub struct JobWorker<D> {
+    store: Arc<dyn JobStore>,
+    registry: Arc<CommandRegistry>,
+    dispatcher: Dispatcher<D>,
+    worker_id: String,
+    concurrency: usize,
+    /// Timeout for job types without their own.
+    timeout: Option<Duration>,
+    /// Per-job-type timeouts, keyed by `job_type`.
+    job_timeouts: HashMap<String, Duration>,
+    /// `None` disables heartbeats.
+    heartbeat_interval: Option<Duration>,
+    /// `Some` reserves jobs for this long before confirming them.
+    reservation_window: Option<Duration>,
+}
+
+impl<D: Send + Sync + 'static> JobWorker<D> {
+    /// Create a worker running jobs from `store` on `dispatcher`.
+    ///
+    /// # Default Settings
+    ///
+    /// - Worker ID: `"seesaw-worker"`
+    /// - Concurrency: 1
+    /// - Timeout: none
+    /// - Heartbeat interval: 20 seconds
+    pub fn new(
+        store: Arc<dyn JobStore>,
+        registry: Arc<CommandRegistry>,
+        dispatcher: Dispatcher<D>,
+    ) -> Self {
+        Self {
+            store,
+            registry,
+            dispatcher,
+            worker_id: "seesaw-worker".to_string(),
+            concurrency: 1,
+            timeout: None,
+            job_timeouts: HashMap::new(),
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            reservation_window: None,
+        }
+    }
+
+    /// Set the identifier the worker claims jobs under.
+    pub fn with_worker_id(mut self, worker_id: impl Into<String>) -> Self {
+        self.worker_id = worker_id.into();
+        self
+    }
+
+    /// Set how many jobs run at once. A value of 0 is treated as 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Bound every attempt of job types without their own timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Bound every attempt of jobs of `job_type`.
+    pub fn with_job_timeout(mut self, job_type: impl Into<String>, timeout: Duration) -> Self {
+        self.job_timeouts.insert(job_type.into(), timeout);
+        self
+    }
+
+    /// Set how often the lease of a running job is extended.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Do not send heartbeats, for stores whose leases outlast any attempt.
+    pub fn without_heartbeats(mut self) -> Self {
+        self.heartbeat_interval = None;
+        self
+    }
+
+    /// Reserve jobs for `window`, and confirm each one as an attempt only
+    /// once its payload deserialized.
+    ///
+    /// Keep the window short: a job whose reservation lapses waits that long
+    /// before another worker can claim it.
+    pub fn with_reservation_window(mut self, window: Duration) -> Self {
+        self.reservation_window = Some(window);
+        self
+    }
+
+    /// The timeout for attempts of `job_type`, if any.
+    pub fn timeout_for(&self, job_type: &str) -> Option<Duration> {
+        self.job_timeouts.get(job_type).copied().or(self.timeout)
+    }
+
+    /// Get the worker's dispatcher.
+    pub fn dispatcher(&self) -> &Dispatcher<D> {
+        &self.dispatcher
+    }
+
+    /// Announce the jobs a previous process of this worker left running.
+    ///
+    /// Call once at startup, before [`run`](Self::run), under the worker ID
+    /// the crashed process used. Each job is emitted on the dispatcher's bus
+    /// as a [`JobRecovered`] for machines to resolve; see the
+    /// [module docs](self#warm-start). A job nobody resolves is reclaimed
+    /// once its lease expires, like any abandoned attempt. Returns the
+    /// number of jobs announced.
+    pub async fn recover(&self) -> Result<usize> {
+        let jobs = self.store.running_jobs(&self.worker_id).await?;
+        for job in &jobs {
+            warn!(
+                job_id = %job.id,
+                job_type = %job.job_type,
+                attempt = job.attempt,
+                worker_id = %self.worker_id,
+                "recovered job left running by a previous worker process"
+            );
+            self.dispatcher.bus().emit(JobRecovered {
+                job_id: job.id,
+                job_type: job.job_type.clone(),
+                payload: job.payload.clone(),
+                attempt: job.attempt,
+                worker_id: self.worker_id.clone(),
+                lease: job.lease(),
+            });
+        }
+        Ok(jobs.len())
+    }
+
+    /// Claim and run jobs until the future is dropped.
+    ///
+    /// Failed claims and failures to record an outcome are logged and do not
+    /// stop the worker.
+    pub async fn run(&self) {
+        let jobs = match self.reservation_window {
+            Some(window) => self.store.reserve_stream(&self.worker_id, self.concurrency, window),
+            None => self.store.claim_stream(&self.worker_id, self.concurrency),
+        };
+        jobs.for_each_concurrent(self.concurrency, |job| async move {
+            match job {
+                Ok(job) => {
+                    let job_id = job.id;
+                    if let Err(e) = self.run_job(job).await {
+                        warn!(%job_id, error = %e, "failed to record job outcome");
+                    }
+                }
+                Err(e) => {
+                    warn!(worker_id = %self.worker_id, error = %e, "failed to claim jobs")
+                }
+            }
+        })
+        .await;
+    }
+
+    /// Run one attempt of `job` and record its outcome in the store.
+    ///
+    /// - A payload the registry cannot deserialize is
+    ///   [parked](JobStore::park) for an operator. With a reservation
+    ///   window, a job of an unknown type is released instead, and every
+    ///   other job is confirmed before it runs.
+    /// - An effect error fails the job, retryably unless its
+    ///   [`SafeErrorCategory`](crate::SafeErrorCategory) is deterministic.
+    /// - An attempt that outlives its timeout is cancelled and fails
+    ///   retryably.
+    /// - An attempt whose lease is lost is cancelled, and nothing is
+    ///   recorded: the job belongs to whoever claimed it next.
+    ///
+    /// Only errors from the store itself are returned.
+    pub async fn run_job(&self, job: ClaimedJob) -> Result<()> {
+        let lease = job.lease();
+        let command = match self.registry.deserialize(&job) {
+            Ok(command) => command,
+            Err(DeserializationError::UnknownCommandType(_))
+                if self.reservation_window.is_some() =>
+            {
+                return self.store.release(lease).await;
+            }
+            Err(e) => {
+                let error = self.dispatcher.redactor().redact(&e.to_string());
+                return self.store.park(lease, &error).await;
+            }
+        };
+        if self.reservation_window.is_some() {
+            match self.store.confirm(lease).await {
+                Ok(()) => {}
+                Err(e) if LeaseLost::is(&e) => {
+                    warn!(job_id = %job.id, job_type = %job.job_type, "job reservation lapsed");
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.dispatcher.bus().emit(JobClaimed {
+            job_id: job.id,
+            job_type: job.job_type.clone(),
+            attempt: job.attempt,
+            worker_id: self.worker_id.clone(),
+        });
+
+        let attempt = async {
+            tokio::select! {
+                result = self.dispatcher.dispatch(vec![command]) => Some(result),
+                () = self.keep_alive(&job) => None,
+            }
+        };
+        let outcome = match self.timeout_for(&job.job_type) {
+            Some(timeout) => tokio::time::timeout(timeout, attempt)
+                .await
+                .map_err(|_| timeout),
+            None => Ok(attempt.await),
+        };
+
+        match outcome {
+            Ok(None) => {
+                warn!(
+                    job_id = %job.id,
+                    job_type = %job.job_type,
+                    attempt = job.attempt,
+                    "job lease lost, abandoning attempt"
+                );
+                Ok(())
+            }
+            Ok(Some(Ok(()))) => self.store.mark_succeeded(lease).await,
+            Ok(Some(Err(e))) => {
+                let kind = if CommandFailed::categorize_and_sanitize(&e).0.is_transient() {
+                    FailureKind::Retryable
+                } else {
+                    FailureKind::NonRetryable
+                };
+                self.fail(&job, &format!("{:#}", e), kind).await
+            }
+            Err(timeout) => {
+                warn!(
+                    job_id = %job.id,
+                    job_type = %job.job_type,
+                    attempt = job.attempt,
+                    ?timeout,
+                    "job attempt timed out"
+                );
+                let error = format!("job attempt timed out after {:?}", timeout);
+                self.fail(&job, &error, FailureKind::Retryable).await
+            }
+        }
+    }
+
+    /// Record a failed attempt of `job`, announcing it if the job was
+    /// dead-lettered. `error` is redacted first.
+    async fn fail(&self, job: &ClaimedJob, error: &str, kind: FailureKind) -> Result<()> {
+        let error = self.dispatcher.redactor().redact(error);
+        if self.store.record_failure(job.lease(), &error, kind).await? {
+            self.dispatcher.bus().emit(JobDeadLettered {
+                job_id: job.id,
+                job_type: job.job_type.clone(),
+                attempt: job.attempt,
+                error,
+            });
+        }
+        Ok(())
+    }
+
+    /// Extend the lease of `job` every heartbeat interval. Completes only
+    /// when the store reports the lease lost.
+    async fn keep_alive(&self, job: &ClaimedJob) {
+        let Some(interval) = self.heartbeat_interval else {
+            return std::future::pending().await;
+        };
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Err(e) = self.store.heartbeat(job.lease()).await else {
+                continue;
+            };
+            let lease_lost = LeaseLost::is(&e);
+            if !lease_lost {
+                warn!(job_id = %job.id, error = %e, "job heartbeat failed");
+            }
+            self.dispatcher.bus().emit(JobHeartbeatMissed {
+                job_id: job.id,
+                job_type: job.job_type.clone(),
+                worker_id: self.worker_id.clone(),
+                lease_lost,
+                error: self.dispatcher.redactor().redact(&format!("{:#}", e)),
+            });
+            if lease_lost {
+                return;
+            }
+        }
+    }
+}
+
+impl<D> std::fmt::Debug for JobWorker<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobWorker")
+            .field("worker_id", &self.worker_id)
+            .field("concurrency", &self.concurrency)
+            .field("timeout", &self.timeout)
+            .field("job_timeouts", &self.job_timeouts)
+            .field("heartbeat_interval", &self.heartbeat_interval)
+            .field("reservation_window", &self.reservation_window)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::EventBus;
+    use async_trait::async_trait;
+    use serde::{Deserialize, Serialize};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SleepCommand {
+        millis: u64,
+    }
+    impl Command for SleepCommand {}
+
+    #[derive(Debug, Clone)]
+    struct Slept;
+
+    struct SleepEffect;
+
+    #[async_trait]
+    impl Effect<SleepCommand, ()> for SleepEffect {
+        type Event = Slept;
+
+        async fn execute(&self, cmd: SleepCommand, _ctx: EffectContext<()>) -> Result<Slept> {
+            tokio::time::sleep(Duration::from_millis(cmd.millis)).await;
+            Ok(Slept)
+        }
+    }
+
+    /// Records heartbeats and outcomes; claims nothing. Heartbeats fail
+    /// with [`LeaseLost`] once `lease_lost_after` have been recorded.
+    #[derive(Default)]
+    struct RecordingStore {
+        heartbeats: Mutex<usize>,
+        lease_lost_after: Option<usize>,
+        outcomes: Mutex<Vec<(Uuid, Option<FailureKind>, String)>>,
+        confirmed: Mutex<Vec<Uuid>>,
+        released: Mutex<Vec<Uuid>>,
+        parked: Mutex<Vec<(Uuid, String)>>,
+        running: Vec<ClaimedJob>,
+    }
+
+    #[async_trait]
+    impl JobStore for RecordingStore {
+        async fn claim_ready(&self, _worker_id: &str, _limit: i64) -> Result<Vec<ClaimedJob>> {
+            Ok(Vec::new())
+        }
+        async fn mark_succeeded(&self, lease: JobLease) -> Result<()> {
+            self.outcomes
+                .lock()
+                .unwrap()
+                .push((lease.job_id, None, String::new()));
+            Ok(())
+        }
+        async fn mark_failed(&self, lease: JobLease, error: &str, kind: FailureKind) -> Result<()> {
+            self.outcomes
+                .lock()
+                .unwrap()
+                .push((lease.job_id, Some(kind), error.to_string()));
+            Ok(())
+        }
+        async fn heartbeat(&self, lease: JobLease) -> Result<()> {
+            let mut heartbeats = self.heartbeats.lock().unwrap();
+            if self.lease_lost_after == Some(*heartbeats) {
+                return Err(LeaseLost {
+                    job_id: lease.job_id,
+                }
+                .into());
+            }
+            *heartbeats += 1;
+            Ok(())
+        }
+        async fn confirm(&self, lease: JobLease) -> Result<()> {
+            self.confirmed.lock().unwrap().push(lease.job_id);
+            Ok(())
+        }
+        async fn release(&self, lease: JobLease) -> Result<()> {
+            self.released.lock().unwrap().push(lease.job_id);
+            Ok(())
+        }
+        async fn park(&self, lease: JobLease, reason: &str) -> Result<()> {
+            self.parked
+                .lock()
+                .unwrap()
+                .push((lease.job_id, reason.to_string()));
+            Ok(())
+        }
+        async fn running_jobs(&self, _worker_id: &str) -> Result<Vec<ClaimedJob>> {
+            Ok(self.running.clone())
+        }
+    }
+
+    fn worker(store: Arc<RecordingStore>) -> JobWorker<()> {
+        let mut registry = CommandRegistry::new();
+        registry.register::<SleepCommand>("test:sleep", vec![1]);
+        let dispatcher =
+            Dispatcher::new((), EventBus::new()).with_effect::<SleepCommand, _>(SleepEffect);
+        JobWorker::new(store, Arc::new(registry), dispatcher)
+    }
+
+    fn sleep_job(millis: u64) -> ClaimedJob {
+        ClaimedJob {
+            id: Uuid::new_v4(),
+            job_type: "test:sleep".to_string(),
+            payload: serde_json::json!({ "millis": millis }),
+            version: 1,
+            attempt: 1,
+            lease_token: Uuid::new_v4(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_attempt_fails_retryably() {
+        let store = Arc::new(RecordingStore::default());
+        let worker = worker(store.clone())
+            .with_timeout(Duration::from_secs(5))
+            .with_job_timeout("test:sleep", Duration::from_millis(20));
+        let job = sleep_job(5_000);
+        let job_id = job.id;
+
+        let started = std::time::Instant::now();
+        worker.run_job(job).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let outcomes = store.outcomes.lock().unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].0, job_id);
+        assert_eq!(outcomes[0].1, Some(FailureKind::Retryable));
+        assert!(outcomes[0].2.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_while_attempt_runs() {
+        let store = Arc::new(RecordingStore::default());
+        let worker = worker(store.clone()).with_heartbeat_interval(Duration::from_millis(10));
+
+        worker.run_job(sleep_job(55)).await.unwrap();
+
+        assert!(*store.heartbeats.lock().unwrap() >= 3);
+        assert_eq!(store.outcomes.lock().unwrap()[0].1, None);
+    }
+
+    #[tokio::test]
+    async fn test_lost_lease_abandons_attempt() {
+        let store = Arc::new(RecordingStore {
+            lease_lost_after: Some(2),
+            ..Default::default()
+        });
+        let worker = worker(store.clone()).with_heartbeat_interval(Duration::from_millis(10));
+
+        let started = std::time::Instant::now();
+        worker.run_job(sleep_job(5_000)).await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(*store.heartbeats.lock().unwrap(), 2);
+        assert!(store.outcomes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lost_lease_is_announced_on_bus() {
+        let store = Arc::new(RecordingStore {
+            lease_lost_after: Some(0),
+            ..Default::default()
+        });
+        let worker = worker(store.clone()).with_heartbeat_interval(Duration::from_millis(10));
+        let mut rx = worker.dispatcher().bus().subscribe();
+        let job = sleep_job(5_000);
+        let job_id = job.id;
+
+        worker.run_job(job).await.unwrap();
+
+        let claimed = rx.try_recv().unwrap();
+        assert_eq!(claimed.downcast_ref::<JobClaimed>().unwrap().job_id, job_id);
+        let missed = rx.try_recv().unwrap();
+        let missed = missed.downcast_ref::<JobHeartbeatMissed>().unwrap();
+        assert_eq!(missed.job_id, job_id);
+        assert!(missed.lease_lost);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_type_is_parked() {
+        let store = Arc::new(RecordingStore::default());
+        let mut job = sleep_job(0);
+        job.job_type = "test:unknown".to_string();
+        let job_id = job.id;
+
+        worker(store.clone()).run_job(job).await.unwrap();
+
+        assert_eq!(
+            *store.parked.lock().unwrap(),
+            [(job_id, "unknown command type: test:unknown".to_string())]
+        );
+        assert!(store.outcomes.lock().unwrap().is_empty());
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct ConnectCommand;
+    impl Command for ConnectCommand {}
+
+    struct ConnectEffect;
+
+    #[async_trait]
+    impl Effect<ConnectCommand, ()> for ConnectEffect {
+        type Event = Slept;
+
+        async fn execute(&self, _cmd: ConnectCommand, _ctx: EffectContext<()>) -> Result<Slept> {
+            Err(anyhow::anyhow!("connection refused")
+                .context("connecting to postgres://app:hunter2@db:5432/app"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_redacted_before_it_is_recorded() {
+        let store = Arc::new(RecordingStore::default());
+        let mut registry = CommandRegistry::new();
+        registry.register::<ConnectCommand>("test:connect", vec![1]);
+        let dispatcher =
+            Dispatcher::new((), EventBus::new()).with_effect::<ConnectCommand, _>(ConnectEffect);
+        let worker = JobWorker::new(store.clone(), Arc::new(registry), dispatcher);
+        let mut job = sleep_job(0);
+        job.job_type = "test:connect".to_string();
+        job.payload = serde_json::json!(null);
+
+        worker.run_job(job).await.unwrap();
+
+        let outcomes = store.outcomes.lock().unwrap();
+        assert_eq!(
+            outcomes[0].2,
+            "connecting to postgres://[REDACTED]@db:5432/app: connection refused"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reserved_job_is_confirmed_before_running() {
+        let store = Arc::new(RecordingStore::default());
+        let worker = worker(store.clone()).with_reservation_window(Duration::from_secs(5));
+        let job = sleep_job(0);
+        let job_id = job.id;
+
+        worker.run_job(job).await.unwrap();
+
+        assert_eq!(*store.confirmed.lock().unwrap(), vec![job_id]);
+        assert_eq!(store.outcomes.lock().unwrap()[0].1, None);
+    }
+
+    #[tokio::test]
+    async fn test_reserved_job_of_unknown_type_is_released() {
+        let store = Arc::new(RecordingStore::default());
+        let worker = worker(store.clone()).with_reservation_window(Duration::from_secs(5));
+        let mut job = sleep_job(0);
+        job.job_type = "test:unknown".to_string();
+        let job_id = job.id;
+
+        worker.run_job(job).await.unwrap();
+
+        assert_eq!(*store.released.lock().unwrap(), vec![job_id]);
+        assert!(store.confirmed.lock().unwrap().is_empty());
+        assert!(store.outcomes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recover_announces_running_jobs_for_resolution() {
+        let job = sleep_job(0);
+        let job_id = job.id;
+        let store = Arc::new(RecordingStore {
+            running: vec![job],
+            ..Default::default()
+        });
+        let worker = worker(store.clone()).with_worker_id("worker-1");
+        let mut rx = worker.dispatcher().bus().subscribe();
+
+        assert_eq!(worker.recover().await.unwrap(), 1);
+
+        let recovered = rx.try_recv().unwrap();
+        let recovered = recovered.downcast_ref::<JobRecovered>().unwrap();
+        assert_eq!(recovered.job_id, job_id);
+        assert_eq!(recovered.worker_id, "worker-1");
+
+        let resolve = ResolveRecoveredJob::new(recovered, RecoveryAction::dead_letter("unsafe"));
+        resolve.action.apply(&*store, resolve.lease).await.unwrap();
+        let outcomes = store.outcomes.lock().unwrap();
+        assert_eq!(
+            outcomes[0],
+            (job_id, Some(FailureKind::NonRetryable), "unsafe".to_string())
+        );
+    }
+}
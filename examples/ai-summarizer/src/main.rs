@@ -2,12 +2,13 @@
 //!
 //! Shows how to call the Anthropic API directly in Seesaw effects.
 //! No special adapter - just reqwest + serde.
+//!
+//! Token usage is reported to the engine's cost ledger, so spend can be
+//! read back per command type and per workflow.
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
-use seesaw_core::{
-    Command, Effect, EffectContext, Engine, EngineBuilder, Event, Machine,
-};
+use seesaw_core::{Command, CostLedger, Effect, EffectContext, EngineBuilder, Machine};
 use serde::{Deserialize, Serialize};
 use std::env;
 use uuid::Uuid;
@@ -72,7 +73,14 @@ impl Machine for SummaryMachine {
                     text: text.clone(),
                 })
             }
-            _ => None,
+            SummaryEvent::Summarized { task_id, summary, tokens_used } => {
+                println!("[{}] {} ({} tokens)", task_id, summary.trim(), tokens_used);
+                None
+            }
+            SummaryEvent::SummaryFailed { task_id, reason } => {
+                eprintln!("[{}] summary failed: {}", task_id, reason);
+                None
+            }
         }
     }
 }
@@ -108,8 +116,12 @@ impl Effect<SummaryCommand, Deps> for SummarizeEffect {
 
         match call_anthropic(&ctx.deps().http_client, &ctx.deps().api_key, request).await {
             Ok(response) => {
+                ctx.record_cost("input_tokens", response.usage.input_tokens as f64);
+                ctx.record_cost("output_tokens", response.usage.output_tokens as f64);
+
                 let summary = response.content
-                    .first()
+                    .iter()
+                    .find(|c| c.block_type == "text")
                     .and_then(|c| c.text.clone())
                     .unwrap_or_default();
 
@@ -215,6 +227,7 @@ async fn main() -> Result<()> {
     let engine = EngineBuilder::new(deps)
         .with_machine(SummaryMachine)
         .with_effect::<SummaryCommand, _>(SummarizeEffect)
+        .with_cost_ledger(CostLedger::new())
         .build();
 
     let handle = engine.start();
@@ -238,5 +251,10 @@ async fn main() -> Result<()> {
 
     println!("Summary complete!");
 
+    let costs = handle.costs().expect("cost ledger configured");
+    for total in costs.totals() {
+        println!("{} spent {} {}", total.command_type, total.amount, total.unit);
+    }
+
     Ok(())
 }
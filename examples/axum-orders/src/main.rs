@@ -0,0 +1,185 @@
+//! # Axum Orders Example
+//!
+//! Shows HTTP request/response over a running engine with `seesaw-axum`:
+//! each request becomes an input event, the handler awaits the correlated
+//! fact, and effect failures map to HTTP status codes.
+//!
+//! ```bash
+//! cargo run
+//! curl -X POST localhost:3000/orders -H 'content-type: application/json' -d '{"sku":"book","quantity":2}'
+//! curl localhost:3000/orders/<id>        # 200 with the order
+//! curl localhost:3000/orders/$(uuidgen)  # 404 {"error":"Resource not found"}
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::extract::Path;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use seesaw_axum::{request_handler, ApiError, Seesaw};
+use seesaw_core::{Command, Effect, EffectContext, EngineBuilder, Machine};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ============================================================================
+// Events (Facts)
+// ============================================================================
+
+#[derive(Debug, Clone)]
+enum OrderEvent {
+    /// A client asked to place an order (edge input)
+    PlaceRequested { sku: String, quantity: u32 },
+
+    /// A client asked to see an order (edge input)
+    LookupRequested { order_id: Uuid },
+
+    /// The order was stored
+    Placed { order: Order },
+
+    /// The order was found
+    Found { order: Order },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Order {
+    id: Uuid,
+    sku: String,
+    quantity: u32,
+}
+
+// ============================================================================
+// Commands (Intent)
+// ============================================================================
+
+#[derive(Debug, Clone)]
+enum OrderCommand {
+    Place { sku: String, quantity: u32 },
+    Lookup { order_id: Uuid },
+}
+
+impl Command for OrderCommand {}
+
+// ============================================================================
+// Machine (Decision Logic)
+// ============================================================================
+
+struct OrderMachine;
+
+impl Machine for OrderMachine {
+    type Event = OrderEvent;
+    type Command = OrderCommand;
+
+    fn decide(&mut self, event: &OrderEvent) -> Option<OrderCommand> {
+        match event {
+            OrderEvent::PlaceRequested { sku, quantity } => Some(OrderCommand::Place {
+                sku: sku.clone(),
+                quantity: *quantity,
+            }),
+            OrderEvent::LookupRequested { order_id } => Some(OrderCommand::Lookup {
+                order_id: *order_id,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// Effect (IO)
+// ============================================================================
+
+#[derive(Default)]
+struct Deps {
+    orders: Mutex<HashMap<Uuid, Order>>,
+}
+
+struct OrderEffect;
+
+#[async_trait]
+impl Effect<OrderCommand, Deps> for OrderEffect {
+    type Event = OrderEvent;
+
+    async fn execute(&self, cmd: OrderCommand, ctx: EffectContext<Deps>) -> Result<OrderEvent> {
+        let mut orders = ctx.deps().orders.lock().unwrap();
+        match cmd {
+            OrderCommand::Place { sku, quantity } => {
+                let order = Order {
+                    id: Uuid::new_v4(),
+                    sku,
+                    quantity,
+                };
+                orders.insert(order.id, order.clone());
+                Ok(OrderEvent::Placed { order })
+            }
+            OrderCommand::Lookup { order_id } => match orders.get(&order_id) {
+                Some(order) => Ok(OrderEvent::Found {
+                    order: order.clone(),
+                }),
+                // Becomes CommandFailed { category: NotFound }, served as a 404
+                None => Err(io::Error::from(io::ErrorKind::NotFound).into()),
+            },
+        }
+    }
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+#[derive(Deserialize)]
+struct NewOrder {
+    sku: String,
+    quantity: u32,
+}
+
+/// A hand-written handler, for when the request isn't a JSON body.
+async fn get_order(seesaw: Seesaw, Path(order_id): Path<Uuid>) -> Result<Json<Order>, ApiError> {
+    seesaw
+        .request(
+            OrderEvent::LookupRequested { order_id },
+            |e: &OrderEvent| match e {
+                OrderEvent::Found { order } => Some(Json(order.clone())),
+                _ => None,
+            },
+        )
+        .await
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let handle = EngineBuilder::new(Deps::default())
+        .with_machine(OrderMachine)
+        .with_effect::<OrderCommand, _>(OrderEffect)
+        .build()
+        .start();
+
+    let app = Router::new()
+        .route(
+            "/orders",
+            post(request_handler(
+                |body: NewOrder| OrderEvent::PlaceRequested {
+                    sku: body.sku,
+                    quantity: body.quantity,
+                },
+                |e: &OrderEvent| match e {
+                    OrderEvent::Placed { order } => Some(order.clone()),
+                    _ => None,
+                },
+            )),
+        )
+        .route("/orders/{order_id}", get(get_order))
+        .with_state(Arc::new(handle));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    println!("Listening on http://127.0.0.1:3000");
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
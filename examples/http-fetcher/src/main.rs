@@ -5,10 +5,7 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
-use seesaw_core::{
-    Command, Effect, EffectContext, Engine, EngineBuilder, Event, Machine,
-};
-use std::collections::HashMap;
+use seesaw_core::{Command, Effect, EffectContext, EngineBuilder, Machine};
 use uuid::Uuid;
 
 // ============================================================================
@@ -74,7 +71,14 @@ impl Machine for FetchMachine {
                     url: url.clone(),
                 })
             }
-            _ => None,
+            FetchEvent::Fetched { fetch_id, url, content, status } => {
+                println!("[{}] {} -> {} ({} bytes)", fetch_id, url, status, content.len());
+                None
+            }
+            FetchEvent::FetchFailed { fetch_id, url, reason } => {
+                eprintln!("[{}] {} failed: {}", fetch_id, url, reason);
+                None
+            }
         }
     }
 }